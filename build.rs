@@ -0,0 +1,100 @@
+//! Captures build metadata for `zavora_cli::build_info`. Every value falls
+//! back to "unknown" so builds from a source tarball without git (or without
+//! a working rustc --version) still succeed.
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const UNKNOWN: &str = "unknown";
+
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let text = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!text.is_empty()).then_some(text)
+}
+
+/// `YYYY-MM-DDTHH:MM:SSZ` for a unix timestamp, without pulling chrono into
+/// the build script.
+fn rfc3339_utc(unix_secs: u64) -> String {
+    let days = (unix_secs / 86_400) as i64;
+    let secs_of_day = unix_secs % 86_400;
+    // Civil-from-days (Howard Hinnant), valid for the proleptic Gregorian calendar.
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        secs_of_day / 3_600,
+        secs_of_day % 3_600 / 60,
+        secs_of_day % 60
+    )
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+    // Only watch git state when there is a checkout; a missing path would
+    // make cargo rerun the script on every build.
+    for path in [".git/HEAD", ".git/index"] {
+        if std::path::Path::new(path).exists() {
+            println!("cargo:rerun-if-changed={path}");
+        }
+    }
+
+    let git_sha = command_output("git", &["rev-parse", "--short=12", "HEAD"]);
+    let git_dirty = match &git_sha {
+        Some(_) => command_output("git", &["status", "--porcelain", "--untracked-files=no"])
+            .map_or("false", |_| "true"),
+        None => UNKNOWN,
+    };
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let rustc_version = command_output(&rustc, &["--version"]);
+    let built_at = std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|value| value.parse::<u64>().ok())
+        .or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .ok()
+                .map(|elapsed| elapsed.as_secs())
+        })
+        .map(rfc3339_utc);
+    let mut features = std::env::vars()
+        .filter_map(|(key, _)| {
+            key.strip_prefix("CARGO_FEATURE_")
+                .filter(|name| *name != "DEFAULT")
+                .map(|name| name.to_ascii_lowercase().replace('_', "-"))
+        })
+        .collect::<Vec<String>>();
+    features.sort();
+
+    println!(
+        "cargo:rustc-env=ZAVORA_GIT_SHA={}",
+        git_sha.as_deref().unwrap_or(UNKNOWN)
+    );
+    println!("cargo:rustc-env=ZAVORA_GIT_DIRTY={git_dirty}");
+    println!(
+        "cargo:rustc-env=ZAVORA_BUILD_TIMESTAMP={}",
+        built_at.as_deref().unwrap_or(UNKNOWN)
+    );
+    println!(
+        "cargo:rustc-env=ZAVORA_RUSTC_VERSION={}",
+        rustc_version.as_deref().unwrap_or(UNKNOWN)
+    );
+    println!(
+        "cargo:rustc-env=ZAVORA_BUILD_FEATURES={}",
+        if features.is_empty() {
+            "none".to_string()
+        } else {
+            features.join(",")
+        }
+    );
+}
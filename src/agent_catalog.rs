@@ -17,20 +17,36 @@ pub fn run_agents_list(
         let marker = if name == active_agent { "*" } else { " " };
         let source = agents
             .get(&name)
-            .map(|agent| agent.source.label())
-            .unwrap_or("unknown");
+            .map(format_agent_source)
+            .unwrap_or_else(|| "unknown".to_string());
         println!("{marker} {name} ({source})");
     }
-    println!("Local catalog: {}", paths.local_catalog.display());
+    if let Some(local) = paths.local_catalog.as_ref() {
+        println!("Local catalog: {}", local.display());
+    } else {
+        println!("Local catalog: <workspace not trusted>");
+    }
     if let Some(global) = paths.global_catalog.as_ref() {
         println!("Global catalog: {}", global.display());
     } else {
         println!("Global catalog: <HOME not set>");
     }
+    for extra in &paths.extra_catalogs {
+        let missing = if extra.exists() { "" } else { " (missing)" };
+        println!("Agent path: {}{missing}", extra.display());
+    }
     println!("Selection file: {}", paths.selection_file.display());
     Ok(())
 }
 
+/// `local: .zavora/agents.toml`, or just the label for built-in agents.
+pub fn format_agent_source(agent: &ResolvedAgent) -> String {
+    match &agent.origin {
+        Some(origin) => format!("{}: {}", agent.source.label(), origin.display()),
+        None => agent.source.label().to_string(),
+    }
+}
+
 pub fn run_agents_show(
     agents: &HashMap<String, ResolvedAgent>,
     active_agent: &str,
@@ -47,7 +63,11 @@ pub fn run_agents_show(
         )
     })?;
 
-    println!("Agent: {} (source={})", agent.name, agent.source.label());
+    println!(
+        "Agent: {} (source={})",
+        agent.name,
+        format_agent_source(agent)
+    );
     println!(
         "Description: {}",
         agent.config.description.as_deref().unwrap_or("<none>")
@@ -100,6 +120,33 @@ pub fn run_agents_show(
             agent.config.resource_paths.join(", ")
         }
     );
+    if agent.config.mcp_servers.is_empty() {
+        println!("MCP servers: <none>");
+    } else {
+        println!("MCP servers:");
+        for server in &agent.config.mcp_servers {
+            let state = if server.enabled.unwrap_or(true) {
+                "enabled"
+            } else {
+                "disabled"
+            };
+            println!(
+                "- {} target={} {}",
+                server.name,
+                server.display_target(),
+                state
+            );
+        }
+    }
+    match agent.config.starters.as_deref() {
+        Some(starters) if !starters.is_empty() => {
+            println!("Starters:");
+            for (index, starter) in starters.iter().enumerate() {
+                println!("{}. {}: {}", index + 1, starter.label, starter.prompt);
+            }
+        }
+        _ => println!("Starters: <none>"),
+    }
     Ok(())
 }
 
@@ -5,16 +5,19 @@
 use anyhow::{Context, Result};
 use std::sync::{Arc, OnceLock};
 
-const DB_PATH: &str = ".zavora/memory.db";
+use crate::paths::ZavoraPaths;
+
 const APP_NAME: &str = "zavora-cli";
 const USER_ID: &str = "default";
 
 static MEMORY: OnceLock<Arc<adk_memory::MemoryServiceAdapter>> = OnceLock::new();
+static DB_URL: OnceLock<String> = OnceLock::new();
 
 /// Initialize memory at startup. Call once from main before any memory use.
-pub async fn init() -> Result<()> {
-    std::fs::create_dir_all(".zavora").ok();
-    let svc = adk_memory::SqliteMemoryService::new(&format!("sqlite:{DB_PATH}"))
+pub async fn init(paths: &ZavoraPaths) -> Result<()> {
+    std::fs::create_dir_all(&paths.workspace_dir).ok();
+    let db_url = DB_URL.get_or_init(|| paths.memory_db_url());
+    let svc = adk_memory::SqliteMemoryService::new(db_url)
         .await
         .context("failed to open memory database")?;
     svc.migrate().await.context("memory migration failed")?;
@@ -54,7 +57,8 @@ pub async fn recall(query: &str, limit: usize) -> Result<Vec<String>> {
 /// List all memories (bypasses FTS5 which can't match empty queries).
 async fn recall_all(limit: usize) -> Result<Vec<String>> {
     use sqlx::Row;
-    let pool = sqlx::SqlitePool::connect(&format!("sqlite:{DB_PATH}"))
+    let db_url = DB_URL.get().context("memory not initialized — call memory::init() first")?;
+    let pool = sqlx::SqlitePool::connect(db_url)
         .await
         .context("failed to open memory db")?;
     let rows = sqlx::query(
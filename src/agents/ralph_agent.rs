@@ -157,6 +157,7 @@ mod tests {
         RuntimeConfig {
             profile: "default".to_string(),
             config_path: String::new(),
+            paths: crate::paths::ZavoraPaths::under(".zavora"),
             agent_name: "default".to_string(),
             agent_source: crate::config::AgentSource::Implicit,
             agent_description: None,
@@ -173,33 +174,107 @@ mod tests {
             session_id: "test-session".to_string(),
             session_backend: crate::cli::SessionBackend::Memory,
             session_db_url: String::new(),
+            session_encryption_key_env: None,
+            session_busy_retry_attempts: 0,
+            session_busy_retry_max_ms: 0,
             show_sensitive_config: false,
+            fake_script: None,
+            show_context_budget: false,
+            provenance: true,
+            read_only: false,
+            strict_final_text: false,
+            pager: false,
             retrieval_backend: crate::cli::RetrievalBackend::Disabled,
             retrieval_doc_path: None,
             retrieval_max_chunks: 3,
             retrieval_max_chars: 4000,
             retrieval_min_score: 1,
+            retrieval_chunking: crate::cli::RetrievalChunking::Paragraph,
+            retrieval_query_strategy: crate::cli::RetrievalQueryStrategy::Latest,
+            retrieval_query_window: 3,
+            retrieval_chunk_size: 1200,
+            retrieval_chunk_overlap: 200,
+            retrieval_watch: crate::cli::RetrievalWatch::Off,
+            memory_backend: crate::cli::MemoryBackend::Disabled,
+            memory_recall_max_facts: 5,
             tool_confirmation_mode: crate::cli::ToolConfirmationMode::McpOnly,
             require_confirm_tool: Vec::new(),
             approve_tool: Vec::new(),
             tool_timeout_secs: 45,
+            slow_tool_warn_secs: 10,
+            max_runtime_secs: 0,
+            max_runtime_from_cli: false,
+            chat_show_tool_results: crate::cli::ToolResultDisplay::Off,
+            chat_tool_annotations: crate::cli::ChatToolAnnotations::On,
+            tool_schema_strategy: crate::cli::ToolSchemaStrategy::Full,
+            tool_schema_top_k: 12,
+            chat_tool_result_max_chars: 2_000,
+            tool_output_max_chars: 32_000,
+            tool_budgets: Default::default(),
+            tool_call_counter: Default::default(),
             tool_retry_attempts: 2,
             tool_retry_delay_ms: 500,
+            tool_arg_retry_hint: true,
+            mcp_failure_threshold: 3,
+            mcp_breaker_max_secs: 3600,
             telemetry_enabled: false,
             telemetry_path: "/tmp/test-telemetry.jsonl".to_string(),
+            telemetry_capture_environment: true,
+            telemetry_redact_fields: Vec::new(),
+            telemetry_redaction: crate::cli::TelemetryRedaction::On,
+            telemetry_export: None,
+            telemetry_export_dry_run: false,
+            offline: false,
             guardrail_input_mode: crate::cli::GuardrailMode::Disabled,
+            guardrail_retrieval_mode: crate::cli::GuardrailMode::Observe,
             guardrail_output_mode: crate::cli::GuardrailMode::Disabled,
+            content_injection_mode: crate::cli::ContentInjectionMode::Observe,
+            content_injection_patterns: Vec::new(),
+            fs_write_secret_policy: crate::cli::FsWriteSecretPolicy::Block,
+            fs_write_secret_allowlist: Vec::new(),
+            output_sanitize: crate::cli::OutputSanitize::Strip,
             guardrail_terms: Vec::new(),
+            guardrail_term_severities: Default::default(),
+            guardrail_severity_actions: Default::default(),
             guardrail_redact_replacement: "[REDACTED]".to_string(),
+            guardrail_matcher_cache: Default::default(),
             mcp_servers: Vec::new(),
             permission_rules: Default::default(),
+            retention: Default::default(),
             max_prompt_chars: 32_000,
             server_runner_cache_max: 64,
+            server_max_body_bytes: 1024 * 1024,
+            server_max_prompt_chars: 32_000,
+            server_legacy_status_codes: false,
+            server_per_user_max_concurrent: 0,
+            server_per_user_requests_per_minute: 0,
+            chat_notify_command: None,
+            chat_notify_threshold_secs: 30,
+            chat_notify_enabled: true,
+            chat_idle_timeout_secs: 0,
+            chat_idle_transcript_dir: String::new(),
+            chat_idle_snapshot_db_url: String::new(),
+            verbosity: crate::verbosity::Verbosity::Normal,
+            daily_token_budget: None,
+            daily_cost_budget: None,
+            usage_budget_mode: crate::cli::UsageBudgetMode::Warn,
+            usage_cost_per_1k_tokens: 0.0,
+            usage_dir: ".zavora/test-usage".to_string(),
+            update_check: false,
+            update_check_url: crate::update_check::DEFAULT_UPDATE_CHECK_URL.to_string(),
+            ask_cache_ttl_secs: 86_400,
+            ask_default_ephemeral: false,
+            ephemeral: None,
+            execute_bash_sandbox: None,
+            execute_bash_summarize: false,
+            mock_tools: None,
+            provider_overrides: Default::default(),
             auto_compact_enabled: true,
             compact_interval: 10,
             compact_overlap: 2,
             compaction_threshold: 0.75,
             compaction_target: 0.10,
+            history_trim_threshold: 0.9,
         }
     }
 
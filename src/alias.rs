@@ -0,0 +1,373 @@
+/// Command aliases: `alias set <name> -- <args...>` stores an expansion in
+/// `<config dir>/aliases.toml`, and [`expand_cli_aliases`] splices it into
+/// argv before clap parses it.
+///
+/// The first positional token of an invocation is looked up; built-in
+/// subcommands always win over aliases. Flags the user passes override the
+/// same flags stored in the alias, and words after the alias name are
+/// appended, so `zavora-cli hb "what is the leave policy"` works for an
+/// alias ending in `ask`.
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use clap::CommandFactory;
+use serde::{Deserialize, Serialize};
+
+use crate::cli::Cli;
+use crate::paths::ZavoraPaths;
+
+/// clap id of `--no-alias`.
+const NO_ALIAS_ID: &str = "no_alias";
+/// clap id of `--config-dir`, read before parsing to find the alias file.
+const CONFIG_DIR_ID: &str = "config_dir";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AliasFile {
+    #[serde(default)]
+    pub aliases: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AliasExpansion {
+    /// argv after expansion, program name included.
+    pub args: Vec<String>,
+    /// Aliases applied, outermost first; empty when nothing was expanded.
+    pub aliases: Vec<String>,
+}
+
+/// One argv item: a flag with its value tokens, or a positional word.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ArgToken {
+    Flag { id: String, raw: Vec<String> },
+    Word(String),
+}
+
+impl ArgToken {
+    fn raw(&self) -> Vec<String> {
+        match self {
+            Self::Flag { raw, .. } => raw.clone(),
+            Self::Word(word) => vec![word.clone()],
+        }
+    }
+
+    fn flag_id(&self) -> Option<&str> {
+        match self {
+            Self::Flag { id, .. } => Some(id),
+            Self::Word(_) => None,
+        }
+    }
+}
+
+/// clap id of the flag `token` names in `command`, and whether its value is
+/// the next token. Unknown flags keep their spelling as id and take no value.
+fn flag_spec(command: &clap::Command, token: &str) -> (String, bool) {
+    let found = if let Some(long) = token.strip_prefix("--") {
+        let (name, inline_value) = match long.split_once('=') {
+            Some((name, _)) => (name, true),
+            None => (long, false),
+        };
+        command
+            .get_arguments()
+            .find(|arg| {
+                arg.get_long() == Some(name)
+                    || arg
+                        .get_all_aliases()
+                        .is_some_and(|aliases| aliases.contains(&name))
+            })
+            .map(|arg| (arg, !inline_value))
+    } else {
+        let short = token.chars().nth(1);
+        command
+            .get_arguments()
+            .find(|arg| arg.get_short().is_some() && arg.get_short() == short)
+            .map(|arg| (arg, token.chars().count() == 2))
+    };
+    match found {
+        Some((arg, value_may_follow)) => (
+            arg.get_id().to_string(),
+            value_may_follow && arg.get_action().takes_values(),
+        ),
+        None => (token.to_string(), false),
+    }
+}
+
+fn is_flag(token: &str) -> bool {
+    token.starts_with('-') && token.len() > 1 && token != "--"
+}
+
+/// Flags before the first positional word, and that word's index.
+fn leading_flags(tokens: &[String], command: &clap::Command) -> (Vec<ArgToken>, Option<usize>) {
+    let mut flags = Vec::new();
+    let mut index = 0;
+    while index < tokens.len() {
+        let token = &tokens[index];
+        if !is_flag(token) {
+            return (flags, (token != "--").then_some(index));
+        }
+        let (id, takes_value) = flag_spec(command, token);
+        let mut raw = vec![token.clone()];
+        if takes_value && let Some(value) = tokens.get(index + 1) {
+            raw.push(value.clone());
+            index += 1;
+        }
+        flags.push(ArgToken::Flag { id, raw });
+        index += 1;
+    }
+    (flags, None)
+}
+
+/// Group `tokens`, descending into subcommands as their names appear. Each
+/// token carries the subcommand depth it belongs to; the command reached at
+/// the end and its depth are returned too.
+fn group_tokens(
+    tokens: &[String],
+    root: &clap::Command,
+) -> (Vec<(usize, ArgToken)>, clap::Command, usize) {
+    let mut command = root.clone();
+    let mut depth = 0;
+    let mut positional_seen = false;
+    let mut grouped = Vec::new();
+    let mut index = 0;
+    while index < tokens.len() {
+        let token = &tokens[index];
+        if token == "--" {
+            grouped.extend(
+                tokens[index..]
+                    .iter()
+                    .map(|word| (depth, ArgToken::Word(word.clone()))),
+            );
+            break;
+        }
+        if is_flag(token) {
+            let (id, takes_value) = flag_spec(&command, token);
+            let mut raw = vec![token.clone()];
+            if takes_value && let Some(value) = tokens.get(index + 1) {
+                raw.push(value.clone());
+                index += 1;
+            }
+            grouped.push((depth, ArgToken::Flag { id, raw }));
+        } else if !positional_seen && let Some(sub) = command.find_subcommand(token).cloned() {
+            grouped.push((depth, ArgToken::Word(token.clone())));
+            command = sub;
+            depth += 1;
+        } else {
+            positional_seen = true;
+            grouped.push((depth, ArgToken::Word(token.clone())));
+        }
+        index += 1;
+    }
+    (grouped, command, depth)
+}
+
+fn is_builtin_command(root: &clap::Command, name: &str) -> bool {
+    name == "help" || root.find_subcommand(name).is_some()
+}
+
+/// Splice the alias at the first positional of `args`, repeatedly, until
+/// the first positional is a built-in command or no alias. An alias seen
+/// twice in one expansion is an error.
+pub fn expand_aliases(
+    args: Vec<String>,
+    aliases: &BTreeMap<String, Vec<String>>,
+) -> Result<AliasExpansion> {
+    let root = Cli::command();
+    let mut args = args;
+    let mut applied = Vec::<String>::new();
+    loop {
+        let Some((program, rest)) = args.split_first() else {
+            break;
+        };
+        let (leading, word_index) = leading_flags(rest, &root);
+        if leading
+            .iter()
+            .any(|flag| flag.flag_id() == Some(NO_ALIAS_ID))
+        {
+            break;
+        }
+        let Some(word_index) = word_index else {
+            break;
+        };
+        let name = &rest[word_index];
+        if is_builtin_command(&root, name) {
+            break;
+        }
+        let Some(expansion) = aliases.get(name) else {
+            break;
+        };
+        if applied.contains(name) {
+            return Err(anyhow::anyhow!(
+                "recursive alias: {} -> {name}",
+                applied.join(" -> ")
+            ));
+        }
+        applied.push(name.clone());
+
+        let user_flags = leading
+            .iter()
+            .filter_map(ArgToken::flag_id)
+            .collect::<BTreeSet<&str>>();
+        let trailing = &rest[word_index + 1..];
+        let (stored, final_command, final_depth) = group_tokens(expansion, &root);
+        let (trailing_grouped, _, _) = group_tokens(trailing, &final_command);
+        let trailing_flags = trailing_grouped
+            .iter()
+            .filter(|(depth, _)| *depth == 0)
+            .filter_map(|(_, token)| token.flag_id())
+            .collect::<BTreeSet<&str>>();
+
+        let mut expanded = vec![program.clone()];
+        expanded.extend(leading.iter().flat_map(ArgToken::raw));
+        for (depth, token) in &stored {
+            let overridden = token.flag_id().is_some_and(|id| {
+                (*depth == 0 && user_flags.contains(id))
+                    || (*depth == final_depth && trailing_flags.contains(id))
+            });
+            if !overridden {
+                expanded.extend(token.raw());
+            }
+        }
+        expanded.extend(trailing.iter().cloned());
+        args = expanded;
+    }
+    Ok(AliasExpansion {
+        args,
+        aliases: applied,
+    })
+}
+
+/// Expand `args` (the process argv) with the aliases stored under the
+/// config dir named by `--config-dir` / `ZAVORA_HOME`.
+pub fn expand_cli_aliases(args: Vec<String>) -> Result<AliasExpansion> {
+    let root = Cli::command();
+    let (leading, _) = leading_flags(args.get(1..).unwrap_or_default(), &root);
+    let config_dir = leading
+        .iter()
+        .find_map(|flag| match flag {
+            ArgToken::Flag { id, raw } if id == CONFIG_DIR_ID => raw
+                .get(1)
+                .cloned()
+                .or_else(|| raw[0].split_once('=').map(|(_, dir)| dir.to_string())),
+            _ => None,
+        })
+        .or_else(|| std::env::var("ZAVORA_HOME").ok());
+    let path = ZavoraPaths::from_env(config_dir.as_deref()).aliases_file();
+    let file = load_alias_file(&path)?;
+    expand_aliases(args, &file.aliases)
+}
+
+pub fn load_alias_file(path: &Path) -> Result<AliasFile> {
+    if !path.exists() {
+        return Ok(AliasFile::default());
+    }
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read alias file '{}'", path.display()))?;
+    toml::from_str::<AliasFile>(&content)
+        .with_context(|| format!("invalid alias file '{}'", path.display()))
+}
+
+pub fn save_alias_file(path: &Path, file: &AliasFile) -> Result<()> {
+    if let Some(parent) = path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create alias directory '{}'", parent.display()))?;
+    }
+    let content = toml::to_string_pretty(file).context("failed to serialize aliases")?;
+    std::fs::write(path, content)
+        .with_context(|| format!("failed to write alias file '{}'", path.display()))
+}
+
+/// Check that `name` can be stored with `args` next to `aliases`.
+pub fn validate_alias(
+    name: &str,
+    args: &[String],
+    aliases: &BTreeMap<String, Vec<String>>,
+) -> Result<()> {
+    let valid_name = !name.is_empty()
+        && !name.starts_with('-')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid_name {
+        return Err(anyhow::anyhow!(
+            "invalid alias name '{name}': use letters, digits, '-' and '_'"
+        ));
+    }
+    if is_builtin_command(&Cli::command(), name) {
+        return Err(anyhow::anyhow!(
+            "alias '{name}' would shadow the built-in '{name}' command"
+        ));
+    }
+    if args.is_empty() {
+        return Err(anyhow::anyhow!(
+            "alias '{name}' needs an expansion: zavora-cli alias set {name} -- <args...>"
+        ));
+    }
+    let mut candidate = aliases.clone();
+    candidate.insert(name.to_string(), args.to_vec());
+    expand_aliases(vec!["zavora-cli".to_string(), name.to_string()], &candidate)
+        .with_context(|| format!("alias '{name}' was not saved"))?;
+    Ok(())
+}
+
+/// Space-joined args, single-quoting any that need it in a shell.
+pub fn format_alias_args(args: &[String]) -> String {
+    args.iter()
+        .map(|arg| {
+            let plain = !arg.is_empty()
+                && arg
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "-_./:=,@%+".contains(c));
+            if plain {
+                arg.clone()
+            } else {
+                format!("'{}'", arg.replace('\'', r"'\''"))
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+pub fn run_alias_set(paths: &ZavoraPaths, name: &str, args: &[String]) -> Result<()> {
+    let path = paths.aliases_file();
+    let mut file = load_alias_file(&path)?;
+    validate_alias(name, args, &file.aliases)?;
+    let replaced = file
+        .aliases
+        .insert(name.to_string(), args.to_vec())
+        .is_some();
+    save_alias_file(&path, &file)?;
+    let verb = if replaced { "Updated" } else { "Added" };
+    println!("{verb} alias '{name}' = {}", format_alias_args(args));
+    Ok(())
+}
+
+pub fn run_alias_list(paths: &ZavoraPaths) -> Result<()> {
+    let path = paths.aliases_file();
+    let file = load_alias_file(&path)?;
+    if file.aliases.is_empty() {
+        println!("No aliases defined in {}", path.display());
+        return Ok(());
+    }
+    let width = file.aliases.keys().map(String::len).max().unwrap_or(0);
+    for (name, args) in &file.aliases {
+        println!("{name:<width$}  {}", format_alias_args(args));
+    }
+    Ok(())
+}
+
+pub fn run_alias_rm(paths: &ZavoraPaths, name: &str) -> Result<()> {
+    let path = paths.aliases_file();
+    let mut file = load_alias_file(&path)?;
+    if file.aliases.remove(name).is_none() {
+        return Err(anyhow::anyhow!(
+            "alias '{name}' not found in '{}'",
+            path.display()
+        ));
+    }
+    save_alias_file(&path, &file)?;
+    println!("Removed alias '{name}'");
+    Ok(())
+}
@@ -0,0 +1,231 @@
+/// Content-addressed answer cache for `ask --cache`.
+///
+/// Entries live in `.zavora/cache/answers/<sha256>.json`. The key covers the
+/// assembled prompt (system instruction and user content after retrieval),
+/// provider, model and generation params, so any change to what would be
+/// sent is a miss. Only answers that passed the output guardrail are stored.
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::config::RuntimeConfig;
+use crate::guardrail::{guardrail_mode_label, guardrail_severity_label};
+use crate::session_bundle::sha256_hex;
+use crate::telemetry::TelemetrySink;
+
+pub const DEFAULT_ASK_CACHE_TTL_SECS: u64 = 24 * 60 * 60;
+
+/// Everything that shapes the answer; hashed into the cache key.
+#[derive(Debug, Clone, Serialize)]
+pub struct AnswerCacheKeyInput<'a> {
+    pub provider: &'a str,
+    pub model: &'a str,
+    pub system_instruction: &'a str,
+    pub user_content: &'a str,
+    pub params: Value,
+}
+
+/// Generation params for the key. No sampling settings are configurable
+/// yet, so this is the toolset the model can call plus the output guardrail
+/// config (mode, terms, severities, severity actions and replacement),
+/// which decides what text is stored. Changing any of it is a miss, so a
+/// hit never bypasses a guardrail the stored answer was not checked against.
+pub fn ask_cache_params(mut tool_names: Vec<String>, cfg: &RuntimeConfig) -> Value {
+    tool_names.sort();
+    let severities = cfg
+        .guardrail_term_severities
+        .iter()
+        .map(|(term, severity)| (term.clone(), guardrail_severity_label(*severity)))
+        .collect::<BTreeMap<String, &str>>();
+    let severity_actions = cfg
+        .guardrail_severity_actions
+        .iter()
+        .map(|(severity, mode)| {
+            (
+                guardrail_severity_label(*severity).to_string(),
+                guardrail_mode_label(*mode),
+            )
+        })
+        .collect::<BTreeMap<String, &str>>();
+    json!({
+        "tools": tool_names,
+        "guardrail_output_mode": guardrail_mode_label(cfg.guardrail_output_mode),
+        "guardrail_terms": cfg.guardrail_terms,
+        "guardrail_term_severities": severities,
+        "guardrail_severity_actions": severity_actions,
+        "guardrail_redact_replacement": cfg.guardrail_redact_replacement
+    })
+}
+
+pub fn answer_cache_key(input: &AnswerCacheKeyInput<'_>) -> String {
+    let canonical = serde_json::to_vec(input).unwrap_or_default();
+    sha256_hex(&canonical)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct CachedAnswer {
+    pub created_unix_secs: u64,
+    pub provider: String,
+    pub model: String,
+    pub answer: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct AnswerCache {
+    dir: PathBuf,
+    ttl: Duration,
+}
+
+impl AnswerCache {
+    pub fn new(dir: impl Into<PathBuf>, ttl_secs: u64) -> Self {
+        Self {
+            dir: dir.into(),
+            ttl: Duration::from_secs(ttl_secs),
+        }
+    }
+
+    fn entry_path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{key}.json"))
+    }
+
+    /// The entry for `key` when it is younger than the TTL. Unreadable or
+    /// corrupt entries count as misses.
+    pub fn get(&self, key: &str, now_unix_secs: u64) -> Option<CachedAnswer> {
+        let text = std::fs::read_to_string(self.entry_path(key)).ok()?;
+        let entry = serde_json::from_str::<CachedAnswer>(&text).ok()?;
+        let age = now_unix_secs.saturating_sub(entry.created_unix_secs);
+        (age < self.ttl.as_secs()).then_some(entry)
+    }
+
+    pub fn put(&self, key: &str, entry: &CachedAnswer) -> Result<()> {
+        std::fs::create_dir_all(&self.dir).with_context(|| {
+            format!(
+                "failed to create answer cache directory '{}'",
+                self.dir.display()
+            )
+        })?;
+        let path = self.entry_path(key);
+        let tmp = path.with_extension("json.tmp");
+        let body = serde_json::to_string_pretty(entry).context("failed to encode cached answer")?;
+        std::fs::write(&tmp, body)
+            .with_context(|| format!("failed to write cached answer '{}'", tmp.display()))?;
+        std::fs::rename(&tmp, &path)
+            .with_context(|| format!("failed to store cached answer '{}'", path.display()))
+    }
+}
+
+pub fn unix_now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+/// Serve `key` from `cache`, or run `generate` and store its result.
+/// `generate` must apply the output guardrail itself: when it fails (for
+/// example because the guardrail blocked) nothing is written. Returns the
+/// answer and whether it came from the cache.
+pub async fn answer_with_cache<F>(
+    cache: &AnswerCache,
+    key: &str,
+    provider: &str,
+    model: &str,
+    telemetry: &TelemetrySink,
+    now_unix_secs: u64,
+    generate: F,
+) -> Result<(String, bool)>
+where
+    F: Future<Output = Result<String>>,
+{
+    if let Some(entry) = cache.get(key, now_unix_secs) {
+        telemetry.emit(
+            "model.cache_hit",
+            json!({
+                "key": key,
+                "provider": provider,
+                "model": model,
+                "age_secs": now_unix_secs.saturating_sub(entry.created_unix_secs)
+            }),
+        );
+        return Ok((entry.answer, true));
+    }
+
+    let answer = generate.await?;
+    let entry = CachedAnswer {
+        created_unix_secs: now_unix_secs,
+        provider: provider.to_string(),
+        model: model.to_string(),
+        answer: answer.clone(),
+    };
+    if let Err(err) = cache.put(key, &entry) {
+        tracing::warn!("answer cache write failed: {err:#}");
+    }
+    Ok((answer, false))
+}
+
+/// `30s`, `15m`, `12h`, `7d`, or plain seconds.
+pub fn parse_cache_age(value: &str) -> Result<Duration> {
+    let value = value.trim();
+    let (digits, unit_secs) = match value.char_indices().last() {
+        Some((index, 's')) => (&value[..index], 1),
+        Some((index, 'm')) => (&value[..index], 60),
+        Some((index, 'h')) => (&value[..index], 60 * 60),
+        Some((index, 'd')) => (&value[..index], 24 * 60 * 60),
+        _ => (value, 1),
+    };
+    let amount = digits.parse::<u64>().with_context(|| {
+        format!("invalid age '{value}'; expected e.g. 30s, 15m, 12h, 7d or plain seconds")
+    })?;
+    Ok(Duration::from_secs(amount.saturating_mul(unit_secs)))
+}
+
+/// Delete entries at least `older_than` old (all entries when `None`).
+/// Returns how many files were removed.
+pub fn prune_answer_cache(
+    dir: &Path,
+    older_than: Option<Duration>,
+    now_unix_secs: u64,
+) -> Result<usize> {
+    if !dir.exists() {
+        return Ok(0);
+    }
+    let entries = std::fs::read_dir(dir)
+        .with_context(|| format!("failed to read answer cache '{}'", dir.display()))?;
+    let mut removed = 0;
+    for entry in entries {
+        let path = entry
+            .with_context(|| format!("failed to read answer cache '{}'", dir.display()))?
+            .path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+            continue;
+        }
+        let expired = match older_than {
+            None => true,
+            // Corrupt entries are never served, so they always go.
+            Some(limit) => std::fs::read_to_string(&path)
+                .ok()
+                .and_then(|text| serde_json::from_str::<CachedAnswer>(&text).ok())
+                .is_none_or(|cached| {
+                    now_unix_secs.saturating_sub(cached.created_unix_secs) >= limit.as_secs()
+                }),
+        };
+        if expired {
+            std::fs::remove_file(&path)
+                .with_context(|| format!("failed to remove cached answer '{}'", path.display()))?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+pub fn run_cache_clear(dir: &Path, older_than: Option<&str>) -> Result<()> {
+    let older_than = older_than.map(parse_cache_age).transpose()?;
+    let removed = prune_answer_cache(dir, older_than, unix_now_secs())?;
+    println!("Removed {removed} cached answer(s) from {}", dir.display());
+    Ok(())
+}
@@ -0,0 +1,377 @@
+/// Batch mode for `ask --batch-file`.
+///
+/// The runtime (model, MCP tools, retrieval) is built once and every prompt
+/// runs in its own ephemeral session, at most `--batch-jobs` at a time. Each
+/// answer lands in `<output-dir>/<id>.md` and every prompt gets a line in
+/// `<output-dir>/summary.jsonl`. A failed prompt is recorded and the batch
+/// goes on, unless `--batch-fail-fast`, which stops scheduling new prompts.
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use adk_rust::prelude::*;
+use adk_session::SessionService;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::config::RuntimeConfig;
+use crate::context::estimate_tokens;
+use crate::guardrail::{apply_guardrail, enforce_prompt_limit};
+use crate::retrieval::RetrievalService;
+use crate::runner::{
+    ResolvedRuntimeTools, ToolConfirmationSettings, build_runner_with_session_service,
+    build_single_agent_with_tools,
+};
+use crate::sanitize::sanitize_output;
+use crate::streaming::run_prompt_with_retrieval;
+use crate::telemetry::{TelemetrySink, unix_ms_now};
+
+pub const DEFAULT_BATCH_JOBS: usize = 4;
+pub const BATCH_SUMMARY_FILE: &str = "summary.jsonl";
+
+/// One prompt of a batch file.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BatchPrompt {
+    pub id: String,
+    pub prompt: String,
+}
+
+fn validate_batch_id(id: &str) -> Result<()> {
+    let valid = !id.is_empty()
+        && !id.starts_with('.')
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if valid {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!(
+            "invalid batch id '{id}': use letters, digits, '-', '_' or '.' (not leading)"
+        ))
+    }
+}
+
+/// Parse a batch file: JSONL `{"id", "prompt"}` records when the first
+/// prompt line opens with `{`, otherwise one prompt per line with ids
+/// `0001`, `0002`, ... Blank lines and `#` comments are skipped.
+pub fn parse_batch_prompts(text: &str) -> Result<Vec<BatchPrompt>> {
+    let lines = text
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .collect::<Vec<(usize, &str)>>();
+    let jsonl = lines.first().is_some_and(|(_, line)| line.starts_with('{'));
+    let mut prompts = Vec::<BatchPrompt>::with_capacity(lines.len());
+    for (line_number, line) in lines {
+        let prompt = if jsonl {
+            serde_json::from_str::<BatchPrompt>(line)
+                .with_context(|| format!("invalid batch record on line {line_number}"))?
+        } else {
+            BatchPrompt {
+                id: format!("{:04}", prompts.len() + 1),
+                prompt: line.to_string(),
+            }
+        };
+        validate_batch_id(&prompt.id)
+            .with_context(|| format!("invalid batch record on line {line_number}"))?;
+        if prompt.prompt.trim().is_empty() {
+            return Err(anyhow::anyhow!(
+                "batch record '{}' on line {line_number} has an empty prompt",
+                prompt.id
+            ));
+        }
+        if prompts.iter().any(|existing| existing.id == prompt.id) {
+            return Err(anyhow::anyhow!(
+                "duplicate batch id '{}' on line {line_number}",
+                prompt.id
+            ));
+        }
+        prompts.push(prompt);
+    }
+    if prompts.is_empty() {
+        return Err(anyhow::anyhow!("batch file has no prompts"));
+    }
+    Ok(prompts)
+}
+
+pub fn load_batch_file(path: &str) -> Result<Vec<BatchPrompt>> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read batch file '{path}'"))?;
+    parse_batch_prompts(&text).with_context(|| format!("failed to parse batch file '{path}'"))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BatchStatus {
+    Ok,
+    Failed,
+    /// Not run because `--batch-fail-fast` stopped the batch.
+    Skipped,
+}
+
+/// A `summary.jsonl` line.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchRecord {
+    pub id: String,
+    pub status: BatchStatus,
+    pub session_id: String,
+    pub latency_ms: u64,
+    pub input_tokens_est: usize,
+    pub output_tokens_est: usize,
+    /// Answer file, relative to the output directory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct BatchOptions {
+    pub jobs: usize,
+    pub output_dir: PathBuf,
+    pub fail_fast: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct BatchSummary {
+    /// In batch-file order.
+    pub records: Vec<BatchRecord>,
+    pub summary_path: PathBuf,
+    pub elapsed_ms: u64,
+}
+
+impl BatchSummary {
+    pub fn count(&self, status: BatchStatus) -> usize {
+        self.records
+            .iter()
+            .filter(|record| record.status == status)
+            .count()
+    }
+}
+
+/// Everything a batch prompt runs against, built once per batch.
+pub struct BatchRuntime<'a> {
+    pub model: Arc<dyn Llm>,
+    pub session_service: Arc<dyn SessionService>,
+    pub runtime_tools: &'a ResolvedRuntimeTools,
+    pub tool_confirmation: &'a ToolConfirmationSettings,
+    pub retrieval: &'a dyn RetrievalService,
+    pub telemetry: &'a TelemetrySink,
+}
+
+async fn answer_batch_prompt(
+    cfg: &RuntimeConfig,
+    runtime: &BatchRuntime<'_>,
+    prompt: &str,
+) -> Result<String> {
+    enforce_prompt_limit(prompt, cfg.max_prompt_chars)?;
+    let prompt = apply_guardrail(
+        cfg,
+        runtime.telemetry,
+        "input",
+        cfg.guardrail_input_mode,
+        prompt,
+    )?;
+    let agent = build_single_agent_with_tools(
+        runtime.model.clone(),
+        &runtime.runtime_tools.tools,
+        runtime.tool_confirmation.policy.clone(),
+        Duration::from_secs(cfg.tool_timeout_secs),
+        Some(cfg),
+    )?;
+    let runner = build_runner_with_session_service(
+        agent,
+        cfg,
+        runtime.session_service.clone(),
+        Some(runtime.tool_confirmation.run_config.clone()),
+    )
+    .await?;
+    let answer =
+        run_prompt_with_retrieval(&runner, cfg, &prompt, runtime.retrieval, runtime.telemetry)
+            .await?;
+    let answer = apply_guardrail(
+        cfg,
+        runtime.telemetry,
+        "output",
+        cfg.guardrail_output_mode,
+        &answer,
+    )?;
+    Ok(sanitize_output(cfg, &answer))
+}
+
+fn write_batch_answer(output_dir: &Path, id: &str, answer: &str) -> Result<String> {
+    let file_name = format!("{id}.md");
+    let path = output_dir.join(&file_name);
+    std::fs::write(&path, format!("{}\n", answer.trim_end()))
+        .with_context(|| format!("failed to write batch answer '{}'", path.display()))?;
+    Ok(file_name)
+}
+
+fn write_batch_summary(path: &Path, records: &[BatchRecord]) -> Result<()> {
+    let mut out = String::new();
+    for record in records {
+        out.push_str(&serde_json::to_string(record)?);
+        out.push('\n');
+    }
+    std::fs::write(path, out)
+        .with_context(|| format!("failed to write batch summary '{}'", path.display()))
+}
+
+/// Run every prompt with at most `options.jobs` in flight, writing answers
+/// and the summary under `options.output_dir`, and emit `batch.completed`.
+/// With `fail_fast` the first failure skips the prompts not yet started and
+/// the batch returns an error once the summary is written.
+pub async fn run_batch(
+    cfg: &RuntimeConfig,
+    runtime: &BatchRuntime<'_>,
+    prompts: Vec<BatchPrompt>,
+    options: &BatchOptions,
+) -> Result<BatchSummary> {
+    use adk_rust::futures::StreamExt;
+
+    std::fs::create_dir_all(&options.output_dir).with_context(|| {
+        format!(
+            "failed to create batch output directory '{}'",
+            options.output_dir.display()
+        )
+    })?;
+    let total = prompts.len();
+    let jobs = options.jobs.max(1);
+    let stamp = unix_ms_now();
+    let started = Instant::now();
+    let stop = AtomicBool::new(false);
+    let done = AtomicUsize::new(0);
+
+    let mut records = adk_rust::futures::stream::iter(prompts.into_iter().enumerate())
+        .map(|(index, batch_prompt)| {
+            let (stop, done) = (&stop, &done);
+            async move {
+                let mut run_cfg = cfg.clone();
+                run_cfg.session_id = format!("batch-{stamp}-{}", batch_prompt.id);
+                let mut record = BatchRecord {
+                    id: batch_prompt.id.clone(),
+                    status: BatchStatus::Skipped,
+                    session_id: run_cfg.session_id.clone(),
+                    latency_ms: 0,
+                    input_tokens_est: estimate_tokens(batch_prompt.prompt.chars().count()),
+                    output_tokens_est: 0,
+                    output: None,
+                    error: None,
+                };
+                if stop.load(Ordering::SeqCst) {
+                    return (index, record);
+                }
+                let prompt_started = Instant::now();
+                let result =
+                    match answer_batch_prompt(&run_cfg, runtime, &batch_prompt.prompt).await {
+                        Ok(answer) => {
+                            write_batch_answer(&options.output_dir, &batch_prompt.id, &answer)
+                                .map(|file| (answer, file))
+                        }
+                        Err(err) => Err(err),
+                    };
+                record.latency_ms = prompt_started.elapsed().as_millis() as u64;
+                match result {
+                    Ok((answer, file)) => {
+                        record.status = BatchStatus::Ok;
+                        record.output_tokens_est = estimate_tokens(answer.chars().count());
+                        record.output = Some(file);
+                    }
+                    Err(err) => {
+                        record.status = BatchStatus::Failed;
+                        record.error = Some(format!("{err:#}"));
+                        if options.fail_fast {
+                            stop.store(true, Ordering::SeqCst);
+                        }
+                    }
+                }
+                let finished = done.fetch_add(1, Ordering::SeqCst) + 1;
+                eprintln!(
+                    "[{finished}/{total}] {} {} ({} ms)",
+                    record.id,
+                    if record.status == BatchStatus::Ok {
+                        "ok"
+                    } else {
+                        "failed"
+                    },
+                    record.latency_ms
+                );
+                (index, record)
+            }
+        })
+        .buffer_unordered(jobs)
+        .collect::<Vec<(usize, BatchRecord)>>()
+        .await;
+    records.sort_by_key(|(index, _)| *index);
+    let records = records
+        .into_iter()
+        .map(|(_, record)| record)
+        .collect::<Vec<BatchRecord>>();
+
+    let summary = BatchSummary {
+        summary_path: options.output_dir.join(BATCH_SUMMARY_FILE),
+        elapsed_ms: started.elapsed().as_millis() as u64,
+        records,
+    };
+    write_batch_summary(&summary.summary_path, &summary.records)?;
+
+    let ran = summary
+        .records
+        .iter()
+        .filter(|record| record.status != BatchStatus::Skipped)
+        .collect::<Vec<&BatchRecord>>();
+    let latency_total = ran.iter().map(|record| record.latency_ms).sum::<u64>();
+    runtime.telemetry.emit(
+        "batch.completed",
+        json!({
+            "prompts": total,
+            "ok": summary.count(BatchStatus::Ok),
+            "failed": summary.count(BatchStatus::Failed),
+            "skipped": summary.count(BatchStatus::Skipped),
+            "jobs": jobs,
+            "fail_fast": options.fail_fast,
+            "elapsed_ms": summary.elapsed_ms,
+            "mean_latency_ms": latency_total / (ran.len().max(1) as u64),
+            "max_latency_ms": ran.iter().map(|record| record.latency_ms).max().unwrap_or(0),
+            "input_tokens_est": ran.iter().map(|record| record.input_tokens_est).sum::<usize>(),
+            "output_tokens_est": ran.iter().map(|record| record.output_tokens_est).sum::<usize>(),
+        }),
+    );
+
+    if options.fail_fast
+        && let Some(failed) = summary
+            .records
+            .iter()
+            .find(|record| record.status == BatchStatus::Failed)
+    {
+        return Err(anyhow::anyhow!(
+            "batch stopped after '{}' failed: {} (summary: {})",
+            failed.id,
+            failed.error.as_deref().unwrap_or("unknown error"),
+            summary.summary_path.display()
+        ));
+    }
+    Ok(summary)
+}
+
+/// `Batch finished: 198 ok, 2 failed in 41.2s; summary: out/summary.jsonl`.
+pub fn format_batch_summary(summary: &BatchSummary) -> String {
+    let mut counts = vec![
+        format!("{} ok", summary.count(BatchStatus::Ok)),
+        format!("{} failed", summary.count(BatchStatus::Failed)),
+    ];
+    let skipped = summary.count(BatchStatus::Skipped);
+    if skipped > 0 {
+        counts.push(format!("{skipped} skipped"));
+    }
+    format!(
+        "Batch finished: {} in {:.1}s; summary: {}",
+        counts.join(", "),
+        summary.elapsed_ms as f64 / 1000.0,
+        summary.summary_path.display()
+    )
+}
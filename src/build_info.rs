@@ -0,0 +1,81 @@
+/// What binary is running: crate version plus the metadata `build.rs`
+/// captures (git sha and dirty flag, build time, rustc, cargo features).
+///
+/// Shown by `--version` and `doctor`, stamped on `command.started` telemetry
+/// and returned by `/healthz`. Fields are "unknown" when the build had no git
+/// checkout or could not run rustc.
+use serde::Serialize;
+use serde_json::{Value, json};
+
+pub const CLI_VERSION: &str = env!("CARGO_PKG_VERSION");
+pub const GIT_SHA: &str = env!("ZAVORA_GIT_SHA");
+
+/// `zavora-cli --version` output after the binary name.
+pub const LONG_VERSION: &str = concat!(
+    env!("CARGO_PKG_VERSION"),
+    "\ncommit: ",
+    env!("ZAVORA_GIT_SHA"),
+    " (dirty: ",
+    env!("ZAVORA_GIT_DIRTY"),
+    ")\nbuilt: ",
+    env!("ZAVORA_BUILD_TIMESTAMP"),
+    "\nrustc: ",
+    env!("ZAVORA_RUSTC_VERSION"),
+    "\nfeatures: ",
+    env!("ZAVORA_BUILD_FEATURES")
+);
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BuildInfo {
+    pub cli_version: &'static str,
+    pub git_sha: &'static str,
+    /// `None` when git state is unknown.
+    pub git_dirty: Option<bool>,
+    pub build_timestamp: &'static str,
+    pub rustc_version: &'static str,
+    pub features: Vec<&'static str>,
+}
+
+pub fn build_info() -> BuildInfo {
+    BuildInfo {
+        cli_version: CLI_VERSION,
+        git_sha: GIT_SHA,
+        git_dirty: match env!("ZAVORA_GIT_DIRTY") {
+            "true" => Some(true),
+            "false" => Some(false),
+            _ => None,
+        },
+        build_timestamp: env!("ZAVORA_BUILD_TIMESTAMP"),
+        rustc_version: env!("ZAVORA_RUSTC_VERSION"),
+        features: match env!("ZAVORA_BUILD_FEATURES") {
+            "none" => Vec::new(),
+            features => features.split(',').collect(),
+        },
+    }
+}
+
+/// Fields stamped on every `command.started` event.
+pub fn build_telemetry_fields() -> Value {
+    json!({
+        "cli_version": CLI_VERSION,
+        "git_sha": GIT_SHA
+    })
+}
+
+/// `doctor` "Build" section.
+pub fn format_build_info(info: &BuildInfo) -> String {
+    let dirty = match info.git_dirty {
+        Some(true) => " (dirty)",
+        Some(false) => "",
+        None => " (dirty: unknown)",
+    };
+    let features = if info.features.is_empty() {
+        "none".to_string()
+    } else {
+        info.features.join(", ")
+    };
+    format!(
+        "Build:\n- version: {}\n- commit: {}{dirty}\n- built: {}\n- rustc: {}\n- features: {features}\n",
+        info.cli_version, info.git_sha, info.build_timestamp, info.rustc_version
+    )
+}
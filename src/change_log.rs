@@ -0,0 +1,306 @@
+/// What the chat session changed in the workspace, for `/diff` and the
+/// `session.changes` telemetry event at exit.
+///
+/// Mutating tools (`fs_write`, `file_edit`, `apply_patch`, non-read-only
+/// `execute_bash`) record into a process-wide log, like the `/undo` stack. A
+/// file's content at first touch is kept (up to `MAX_BASELINE_BYTES`) so
+/// `/diff <path>` can show everything changed since then.
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+
+use serde_json::{Value, json};
+use similar::TextDiff;
+
+use crate::session_bundle::sha256_hex;
+
+const MAX_BASELINE_BYTES: usize = 1024 * 1024;
+
+/// One successful mutating tool call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ToolChange {
+    FileWrite {
+        path: PathBuf,
+        /// Workspace-relative path as the tool reported it.
+        display_path: String,
+        mode: String,
+        /// Content before the write; `None` when the file did not exist.
+        before: Option<Vec<u8>>,
+        /// Content after the write; `None` when it could not be read back.
+        after: Option<Vec<u8>>,
+    },
+    Command {
+        command: String,
+        exit_code: Option<i64>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileSnapshot {
+    pub size: u64,
+    /// First 12 hex chars of the SHA-256.
+    pub hash: String,
+}
+
+impl FileSnapshot {
+    pub fn of(bytes: &[u8]) -> Self {
+        Self {
+            size: bytes.len() as u64,
+            hash: sha256_hex(bytes)[..12].to_string(),
+        }
+    }
+
+    fn label(&self) -> String {
+        format!("{} B {}", self.size, self.hash)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileChange {
+    pub path: PathBuf,
+    pub display_path: String,
+    /// Write modes used, in first-use order.
+    pub modes: Vec<String>,
+    pub writes: usize,
+    /// State at first touch; `None` for files the session created.
+    pub before: Option<FileSnapshot>,
+    /// Content at first touch, when small enough to keep.
+    pub baseline: Option<Vec<u8>>,
+    pub after: Option<FileSnapshot>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandRecord {
+    pub command: String,
+    pub exit_code: Option<i64>,
+}
+
+#[derive(Debug, Default)]
+pub struct SessionChangeLog {
+    files: Vec<FileChange>,
+    commands: Vec<CommandRecord>,
+}
+
+impl SessionChangeLog {
+    pub const fn new() -> Self {
+        Self {
+            files: Vec::new(),
+            commands: Vec::new(),
+        }
+    }
+
+    pub fn record(&mut self, change: ToolChange) {
+        match change {
+            ToolChange::FileWrite {
+                path,
+                display_path,
+                mode,
+                before,
+                after,
+            } => {
+                let after = after.as_deref().map(FileSnapshot::of);
+                if let Some(existing) = self.files.iter_mut().find(|file| file.path == path) {
+                    existing.writes += 1;
+                    if !existing.modes.contains(&mode) {
+                        existing.modes.push(mode);
+                    }
+                    existing.after = after;
+                    return;
+                }
+                self.files.push(FileChange {
+                    path,
+                    display_path,
+                    modes: vec![mode],
+                    writes: 1,
+                    before: before.as_deref().map(FileSnapshot::of),
+                    baseline: before.filter(|bytes| bytes.len() <= MAX_BASELINE_BYTES),
+                    after,
+                });
+            }
+            ToolChange::Command { command, exit_code } => {
+                self.commands.push(CommandRecord { command, exit_code });
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty() && self.commands.is_empty()
+    }
+
+    pub fn files(&self) -> &[FileChange] {
+        &self.files
+    }
+
+    pub fn commands(&self) -> &[CommandRecord] {
+        &self.commands
+    }
+
+    /// The touched file named `path`, as shown by `/diff` or as a path
+    /// suffix (`main.rs` finds `src/main.rs` when unambiguous).
+    pub fn file(&self, path: &str) -> Option<&FileChange> {
+        let wanted = path.trim().trim_start_matches("./");
+        self.files
+            .iter()
+            .find(|file| file.display_path == wanted || file.path == Path::new(wanted))
+            .or_else(|| {
+                let mut matches = self
+                    .files
+                    .iter()
+                    .filter(|file| Path::new(&file.display_path).ends_with(wanted));
+                let first = matches.next();
+                if matches.next().is_some() {
+                    None
+                } else {
+                    first
+                }
+            })
+    }
+
+    /// Payload for the `session.changes` event. Paths and hashes only.
+    pub fn to_telemetry(&self) -> Value {
+        json!({
+            "files": self.files.iter().map(|file| json!({
+                "path": file.display_path,
+                "modes": file.modes,
+                "writes": file.writes,
+                "created": file.before.is_none(),
+                "before_hash": file.before.as_ref().map(|snapshot| &snapshot.hash),
+                "after_hash": file.after.as_ref().map(|snapshot| &snapshot.hash),
+            })).collect::<Vec<Value>>(),
+            "commands": self.commands.iter().map(|record| json!({
+                "command": record.command,
+                "exit_code": record.exit_code,
+            })).collect::<Vec<Value>>(),
+        })
+    }
+}
+
+static SESSION_CHANGES: Mutex<SessionChangeLog> = Mutex::new(SessionChangeLog::new());
+
+/// Record a mutating tool call into the session change log.
+pub fn record_tool_change(change: ToolChange) {
+    if let Ok(mut log) = SESSION_CHANGES.lock() {
+        log.record(change);
+    }
+}
+
+pub fn with_session_changes<R>(read: impl FnOnce(&SessionChangeLog) -> R) -> R {
+    match SESSION_CHANGES.lock() {
+        Ok(log) => read(&log),
+        Err(poisoned) => read(&poisoned.into_inner()),
+    }
+}
+
+/// `(insertions, deletions)` for `path` against `HEAD`, or `None` outside a
+/// git repository or for files git does not track.
+pub fn git_numstat(workspace: &Path, path: &Path) -> Option<(u64, u64)> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(workspace)
+        .args(["diff", "--numstat", "HEAD", "--"])
+        .arg(path)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut fields = stdout.lines().next()?.split_whitespace();
+    // Binary files report `-` for both counts.
+    let insertions = fields.next()?.parse::<u64>().ok()?;
+    let deletions = fields.next()?.parse::<u64>().ok()?;
+    Some((insertions, deletions))
+}
+
+/// `/diff` without arguments: touched files (git numstat when `numstat`
+/// knows the file, otherwise before/after size and hash) and the commands
+/// that ran.
+pub fn format_change_summary(
+    log: &SessionChangeLog,
+    numstat: &dyn Fn(&FileChange) -> Option<(u64, u64)>,
+) -> String {
+    if log.is_empty() {
+        return "No workspace changes recorded in this session.\n".to_string();
+    }
+    let mut out = String::new();
+    if !log.files.is_empty() {
+        let width = log
+            .files
+            .iter()
+            .map(|file| file.display_path.chars().count())
+            .max()
+            .unwrap_or_default();
+        out.push_str(&format!("Files changed ({}):\n", log.files.len()));
+        for file in &log.files {
+            let stat = match numstat(file) {
+                Some((insertions, deletions)) => format!("+{insertions} -{deletions}"),
+                None => {
+                    let before = file
+                        .before
+                        .as_ref()
+                        .map(FileSnapshot::label)
+                        .unwrap_or_else(|| "new".to_string());
+                    let after = file
+                        .after
+                        .as_ref()
+                        .map(FileSnapshot::label)
+                        .unwrap_or_else(|| "unreadable".to_string());
+                    format!("{before} -> {after}")
+                }
+            };
+            out.push_str(&format!(
+                "  {:<width$} | {stat} ({}, {} write(s))\n",
+                file.display_path,
+                file.modes.join(", "),
+                file.writes
+            ));
+        }
+    }
+    if !log.commands.is_empty() {
+        out.push_str(&format!("Commands run ({}):\n", log.commands.len()));
+        for record in &log.commands {
+            match record.exit_code {
+                Some(code) => out.push_str(&format!("  $ {} (exit {code})\n", record.command)),
+                None => out.push_str(&format!("  $ {}\n", record.command)),
+            }
+        }
+    }
+    out
+}
+
+/// `/diff <path>`: unified diff from the content at first touch to
+/// `current` (the file as it is now; `None` when it is gone).
+pub fn format_file_diff(file: &FileChange, current: Option<&[u8]>) -> String {
+    let now = current.map(FileSnapshot::of);
+    if now.is_some() && now == file.before {
+        return format!(
+            "{} is unchanged since it was first touched.\n",
+            file.display_path
+        );
+    }
+    let baseline = match (&file.before, &file.baseline) {
+        (None, _) => Vec::new(),
+        (Some(_), Some(baseline)) => baseline.clone(),
+        (Some(before), None) => {
+            return format!(
+                "{}: first-touch content was not kept (over {} bytes); {} -> {}\n",
+                file.display_path,
+                MAX_BASELINE_BYTES,
+                before.label(),
+                now.map(|snapshot| snapshot.label())
+                    .unwrap_or_else(|| "deleted".to_string())
+            );
+        }
+    };
+    let current = current.unwrap_or_default();
+    let (Ok(old), Ok(new)) = (std::str::from_utf8(&baseline), std::str::from_utf8(current)) else {
+        return format!("{}: binary file changed\n", file.display_path);
+    };
+    let old_header = if file.before.is_some() {
+        format!("a/{}", file.display_path)
+    } else {
+        "/dev/null".to_string()
+    };
+    TextDiff::from_lines(old, new)
+        .unified_diff()
+        .header(&old_header, &format!("b/{}", file.display_path))
+        .to_string()
+}
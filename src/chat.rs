@@ -1,6 +1,8 @@
 use adk_rust::ToolConfirmationDecision;
+use std::collections::HashMap;
 use std::io::{self, Write};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use adk_rust::prelude::*;
 
@@ -12,20 +14,39 @@ use crate::agents::{
     orchestrator::{Orchestrator, OrchestratorConfig},
     time::TimeAgent,
 };
+use crate::chat_input::{ChatInput, ChatInputEvent, IdleTimer, ReadlineInput, ScriptInput};
+use crate::chat_stats::{ChatSessionStats, format_chat_stats};
 use crate::checkpoint::{
     CheckpointStore, format_checkpoint_list, restore_session_events, snapshot_session_events,
 };
-use crate::cli::{GuardrailMode, Provider};
+use crate::cli::{Provider, SessionBackend};
 use crate::compact::{CompactStrategy, compact_session, compact_to_target};
-use crate::config::RuntimeConfig;
+use crate::config::{ResolvedAgent, RuntimeConfig};
 use crate::context::{ContextUsage, compute_context_usage};
+use crate::ephemeral::{ephemeral_banner, persist_ephemeral_session};
 use crate::error::format_cli_error;
-use crate::guardrail::{apply_guardrail, buffered_output_required};
-use crate::provider::parse_provider_name;
+use crate::fake_provider::FAKE_MODEL_NAME;
+use crate::guardrail::{apply_guardrail, guardrail_active, output_buffering_required};
+use crate::history_trim::{HistoryWindow, TrimmedHistorySessionService, plan_chat_history_trim};
+use crate::hooks::{CHAT_NOTIFY_TIMEOUT, run_chat_notify_command, spawn_chat_notification};
+use crate::model_catalog::chat_model_picker_options;
+use crate::palette::{
+    PALETTE_MAX_RESULTS, PaletteIndex, format_palette_results, palette_agents,
+};
+use crate::provenance::answer_provenance;
+use crate::handoff::{parse_handoff_args, run_handoff};
+use crate::provider::{
+    parse_provider_name, resolve_model, split_model_shorthand, validate_model_for_provider,
+};
+use crate::read_only::read_only_banner;
 use crate::retrieval::RetrievalService;
+use crate::retrieval_query::{ConversationRetrieval, chat_retrieval_query};
 use crate::runner::{ResolvedRuntimeTools, ToolConfirmationSettings, build_single_runner_for_chat};
-use crate::session::build_session_service;
-use crate::streaming::{run_prompt_streaming_with_retrieval, run_prompt_with_retrieval};
+use crate::session::{build_session_service, save_session_transcript, snapshot_session_to_sqlite};
+use crate::starters::{agent_starters, format_starters_hint, pick_starter};
+use crate::streaming::{
+    run_prompt_streaming_with_retrieval, run_prompt_with_retrieval, stream_stats_status_lines,
+};
 use crate::telemetry::TelemetrySink;
 use crate::theme::{
     BOLD, CYAN, DIM, GREEN, RESET, YELLOW, build_prompt, print_startup_banner,
@@ -33,13 +54,18 @@ use crate::theme::{
 };
 use crate::todos;
 use crate::tool_policy::matches_wildcard;
+use crate::tools::budget::format_tool_budgets;
+use crate::tools::doc_search::{DOC_SEARCH_TOOL_NAME, format_doc_search_status};
+use crate::tools::output_cap::format_tool_output_cap;
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ChatCommand {
     Exit,
     Status,
+    Stats { json: bool },
     Help,
-    Tools,
-    Mcp,
+    Find(String),
+    Tools { verbose: bool },
+    Mcp(String),
     Usage,
     Compact,
     Checkpoint(String),
@@ -57,6 +83,11 @@ pub enum ChatCommand {
     Allow(String),
     Deny(String),
     Undo,
+    Diff(Option<String>),
+    Notify(String),
+    Handoff(String),
+    Persist,
+    Starters(String),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -93,9 +124,31 @@ pub fn parse_chat_command(input: &str) -> ParsedChatCommand {
     match command.as_str() {
         "exit" => ParsedChatCommand::Command(ChatCommand::Exit),
         "status" => ParsedChatCommand::Command(ChatCommand::Status),
+        "stats" => match arg {
+            "" => ParsedChatCommand::Command(ChatCommand::Stats { json: false }),
+            "--json" => ParsedChatCommand::Command(ChatCommand::Stats { json: true }),
+            _ => ParsedChatCommand::MissingArgument {
+                usage: "/stats [--json]",
+            },
+        },
         "help" => ParsedChatCommand::Command(ChatCommand::Help),
-        "tools" => ParsedChatCommand::Command(ChatCommand::Tools),
-        "mcp" => ParsedChatCommand::Command(ChatCommand::Mcp),
+        "find" => {
+            if arg.is_empty() {
+                ParsedChatCommand::MissingArgument {
+                    usage: "/find <text> (search commands, tools, agents and MCP servers)",
+                }
+            } else {
+                ParsedChatCommand::Command(ChatCommand::Find(arg.to_string()))
+            }
+        }
+        "tools" => match arg {
+            "" => ParsedChatCommand::Command(ChatCommand::Tools { verbose: false }),
+            "--verbose" | "-v" => ParsedChatCommand::Command(ChatCommand::Tools { verbose: true }),
+            _ => ParsedChatCommand::MissingArgument {
+                usage: "/tools [--verbose]",
+            },
+        },
+        "mcp" => ParsedChatCommand::Command(ChatCommand::Mcp(arg.to_string())),
         "usage" => ParsedChatCommand::Command(ChatCommand::Usage),
         "compact" => ParsedChatCommand::Command(ChatCommand::Compact),
         "agent" => ParsedChatCommand::Command(ChatCommand::Agent),
@@ -127,6 +180,28 @@ pub fn parse_chat_command(input: &str) -> ParsedChatCommand {
             }
         }
         "undo" => ParsedChatCommand::Command(ChatCommand::Undo),
+        "diff" => ParsedChatCommand::Command(ChatCommand::Diff(
+            (!arg.is_empty()).then(|| arg.to_string()),
+        )),
+        "handoff" => {
+            if arg.is_empty() {
+                ParsedChatCommand::MissingArgument {
+                    usage: "/handoff <agent>|back [instructions]",
+                }
+            } else {
+                ParsedChatCommand::Command(ChatCommand::Handoff(arg.to_string()))
+            }
+        }
+        "persist" => ParsedChatCommand::Command(ChatCommand::Persist),
+        "starters" => ParsedChatCommand::Command(ChatCommand::Starters(arg.to_string())),
+        "notify" => match arg.to_ascii_lowercase().as_str() {
+            "" | "on" | "off" | "test" => {
+                ParsedChatCommand::Command(ChatCommand::Notify(arg.to_ascii_lowercase()))
+            }
+            _ => ParsedChatCommand::MissingArgument {
+                usage: "/notify [on|off|test]",
+            },
+        },
         "provider" => {
             if arg.is_empty() {
                 ParsedChatCommand::MissingArgument {
@@ -151,22 +226,27 @@ pub fn print_chat_help() {
     println!();
     println!("  {BOLD}Commands{RESET}");
     println!("  {CYAN}/help{RESET}              {DIM}show this reference{RESET}");
+    println!("  {CYAN}/find{RESET} <text>       {DIM}search commands, tools, agents, MCP servers{RESET}");
     println!("  {CYAN}/status{RESET}            {DIM}active provider, model, session{RESET}");
     println!("  {CYAN}/usage{RESET}             {DIM}context window token breakdown{RESET}");
+    println!("  {CYAN}/stats{RESET} [--json]    {DIM}exchanges, tokens, tools and switches this session{RESET}");
     println!("  {CYAN}/compact{RESET}           {DIM}summarize history to free context{RESET}");
     println!("  {CYAN}/autocompact{RESET}       {DIM}toggle automatic compaction{RESET}");
     println!("  {CYAN}/memory{RESET} <cmd>      {DIM}recall|remember|forget learnings{RESET}");
     println!("  {CYAN}/time{RESET} <query>      {DIM}get time context or parse dates{RESET}");
     println!("  {CYAN}/orchestrate{RESET} <goal> {DIM}run full agent orchestration loop{RESET}");
     println!("  {CYAN}/ralph{RESET} <prompt>     {DIM}run Ralph autonomous dev pipeline{RESET}");
-    println!("  {CYAN}/tools{RESET}             {DIM}list active tools and policy{RESET}");
-    println!("  {CYAN}/mcp{RESET}               {DIM}MCP server diagnostics{RESET}");
+    println!("  {CYAN}/tools{RESET} [--verbose]  {DIM}list active tools, policy, and arg schemas{RESET}");
+    println!("  {CYAN}/mcp{RESET} [server]      {DIM}MCP server diagnostics or per-server tool detail{RESET}");
     println!();
     println!("  {BOLD}Session{RESET}");
     println!("  {CYAN}/checkpoint{RESET} save|list|restore  {DIM}manage snapshots{RESET}");
     println!("  {CYAN}/tangent{RESET} start|end  {DIM}exploratory branch{RESET}");
     println!("  {CYAN}/todos{RESET} list|show|clear  {DIM}task lists{RESET}");
     println!("  {CYAN}/delegate{RESET} <task>    {DIM}run isolated sub-agent{RESET}");
+    println!("  {CYAN}/handoff{RESET} <agent>    {DIM}hand the conversation to another agent with a brief{RESET}");
+    println!("  {CYAN}/persist{RESET}            {DIM}save an ephemeral chat to the session store{RESET}");
+    println!("  {CYAN}/starters{RESET} [n]       {DIM}send one of the active agent's conversation starters{RESET}");
     println!();
     println!("  {BOLD}Config{RESET}");
     println!("  {CYAN}/provider{RESET} <name>    {DIM}switch provider{RESET}");
@@ -174,6 +254,8 @@ pub fn print_chat_help() {
     println!("  {CYAN}/allow{RESET} <pattern>    {DIM}auto-approve tool pattern for session{RESET}");
     println!("  {CYAN}/deny{RESET} <pattern>     {DIM}deny tool pattern for session{RESET}");
     println!("  {CYAN}/undo{RESET}              {DIM}restore last modified file{RESET}");
+    println!("  {CYAN}/diff{RESET} [path]        {DIM}workspace changes made this session{RESET}");
+    println!("  {CYAN}/notify{RESET} on|off|test {DIM}completion notifications for long responses{RESET}");
     println!("  {CYAN}/exit{RESET}              {DIM}quit chat{RESET}");
     println!(
         "  {CYAN}/agent{RESET}             {DIM}toggle agent mode (auto-approve tools){RESET}"
@@ -187,122 +269,84 @@ pub fn print_chat_usage() {
     println!("- /provider openai");
     println!("- /model");
     println!("- /model gpt-4.1");
+    println!("- /model anthropic/claude-sonnet-4-20250514");
     println!("- /tools");
+    println!("- /tools --verbose");
+    println!("- /find search");
     println!("- /mcp");
+    println!("- /mcp ops-tools");
+    println!("- /notify test");
     println!("- /status");
     println!("- /exit");
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ModelPickerOption {
-    pub id: &'static str,
-    pub context_window: &'static str,
-    pub description: &'static str,
+    pub id: String,
+    pub context_window: String,
+    pub description: String,
 }
 
-pub fn model_picker_options(provider: Provider) -> Vec<ModelPickerOption> {
+impl ModelPickerOption {
+    pub fn new(id: &str, context_window: &str, description: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            context_window: context_window.to_string(),
+            description: description.to_string(),
+        }
+    }
+}
+
+/// Built-in picker table: id, context window, description.
+fn static_model_picker_entries(
+    provider: Provider,
+) -> &'static [(&'static str, &'static str, &'static str)] {
     match provider {
-        Provider::Gemini => vec![
-            ModelPickerOption {
-                id: "gemini-2.5-flash",
-                context_window: "1M",
-                description: "fast balanced default",
-            },
-            ModelPickerOption {
-                id: "gemini-3-pro",
-                context_window: "2M",
-                description: "most capable, deep reasoning",
-            },
-            ModelPickerOption {
-                id: "gemini-2.5-pro",
-                context_window: "1M",
-                description: "strong reasoning, stable",
-            },
+        Provider::Gemini => &[
+            ("gemini-2.5-flash", "1M", "fast balanced default"),
+            ("gemini-3-pro", "2M", "most capable, deep reasoning"),
+            ("gemini-2.5-pro", "1M", "strong reasoning, stable"),
         ],
-        Provider::Openai => vec![
-            ModelPickerOption {
-                id: "gpt-4.1",
-                context_window: "1M",
-                description: "balanced default",
-            },
-            ModelPickerOption {
-                id: "gpt-5.3-codex",
-                context_window: "400k",
-                description: "agentic coding, most capable",
-            },
-            ModelPickerOption {
-                id: "gpt-5-mini",
-                context_window: "400k",
-                description: "fast low-latency",
-            },
-            ModelPickerOption {
-                id: "o3-mini",
-                context_window: "200k",
-                description: "reasoning-focused",
-            },
+        Provider::Openai => &[
+            ("gpt-4.1", "1M", "balanced default"),
+            ("gpt-5.3-codex", "400k", "agentic coding, most capable"),
+            ("gpt-5-mini", "400k", "fast low-latency"),
+            ("o3-mini", "200k", "reasoning-focused"),
         ],
-        Provider::Anthropic => vec![
-            ModelPickerOption {
-                id: "claude-sonnet-4-20250514",
-                context_window: "1M",
-                description: "balanced default",
-            },
-            ModelPickerOption {
-                id: "claude-opus-4-6",
-                context_window: "1M",
-                description: "most capable, agentic",
-            },
-            ModelPickerOption {
-                id: "claude-3-5-haiku-latest",
-                context_window: "200k",
-                description: "fast low-latency",
-            },
+        Provider::Anthropic => &[
+            ("claude-sonnet-4-20250514", "1M", "balanced default"),
+            ("claude-opus-4-6", "1M", "most capable, agentic"),
+            ("claude-3-5-haiku-latest", "200k", "fast low-latency"),
         ],
-        Provider::Deepseek => vec![
-            ModelPickerOption {
-                id: "deepseek-chat",
-                context_window: "128k",
-                description: "general conversation default",
-            },
-            ModelPickerOption {
-                id: "deepseek-reasoner",
-                context_window: "128k",
-                description: "reasoning-focused",
-            },
+        Provider::Deepseek => &[
+            ("deepseek-chat", "128k", "general conversation default"),
+            ("deepseek-reasoner", "128k", "reasoning-focused"),
         ],
-        Provider::Groq => vec![
-            ModelPickerOption {
-                id: "llama-3.3-70b-versatile",
-                context_window: "131k",
-                description: "balanced default",
-            },
-            ModelPickerOption {
-                id: "llama-4-scout-17b-16e-instruct",
-                context_window: "131k",
-                description: "Llama 4, fast MoE",
-            },
-            ModelPickerOption {
-                id: "deepseek-r1-distill-llama-70b",
-                context_window: "128k",
-                description: "reasoning-focused",
-            },
+        Provider::Groq => &[
+            ("llama-3.3-70b-versatile", "131k", "balanced default"),
+            ("llama-4-scout-17b-16e-instruct", "131k", "Llama 4, fast MoE"),
+            ("deepseek-r1-distill-llama-70b", "128k", "reasoning-focused"),
         ],
-        Provider::Ollama => vec![
-            ModelPickerOption {
-                id: "llama4",
-                context_window: "local-configured",
-                description: "default local model",
-            },
-            ModelPickerOption {
-                id: "qwen2.5-coder",
-                context_window: "local-configured",
-                description: "coding-optimized local model",
-            },
+        Provider::Ollama => &[
+            ("llama4", "local-configured", "default local model"),
+            ("qwen2.5-coder", "local-configured", "coding-optimized local model"),
         ],
-        Provider::Auto => Vec::new(),
+        Provider::Fake => &[(FAKE_MODEL_NAME, "n/a", "scripted responses (--fake-script)")],
+        // Deployments and served models are per-installation; the picker
+        // adds the configured one.
+        Provider::AzureOpenai | Provider::OpenaiCompatible | Provider::Auto => &[],
     }
 }
 
+pub fn model_picker_options(provider: Provider) -> Vec<ModelPickerOption> {
+    static_model_picker_entries(provider)
+        .iter()
+        .map(|(id, context_window, description)| {
+            ModelPickerOption::new(id, context_window, description)
+        })
+        .collect()
+}
+
 pub fn resolve_model_picker_selection(
     options: &[ModelPickerOption],
     selection: &str,
@@ -337,8 +381,12 @@ pub fn resolve_model_picker_selection(
     Ok(Some(trimmed.to_string()))
 }
 
-pub fn prompt_model_picker(provider: Provider, current_model: &str) -> Result<Option<String>> {
-    let options = model_picker_options(provider);
+pub fn prompt_model_picker(
+    cfg: &RuntimeConfig,
+    provider: Provider,
+    current_model: &str,
+) -> Result<Option<String>> {
+    let options = chat_model_picker_options(cfg, provider);
     if options.is_empty() {
         println!(
             "Model picker catalog unavailable for provider {:?}. Use /model <model-id>.",
@@ -370,10 +418,27 @@ pub fn prompt_model_picker(provider: Provider, current_model: &str) -> Result<Op
     resolve_model_picker_selection(&options, &selection)
 }
 
+/// Config for `/model <value>`. `provider/model` shorthand switches the
+/// provider too; the pair is validated before a runner is built.
+pub fn chat_model_switch_config(
+    cfg: &RuntimeConfig,
+    current_provider: Provider,
+    value: &str,
+) -> Result<RuntimeConfig> {
+    let (shorthand, model) = split_model_shorthand(value)?;
+    let provider = shorthand.unwrap_or(current_provider);
+    validate_model_for_provider(provider, &model)?;
+    let mut switched = cfg.clone();
+    switched.provider = provider;
+    switched.model = Some(model);
+    Ok(switched)
+}
+
 pub fn print_chat_tools(
     cfg: &RuntimeConfig,
     runtime_tools: &ResolvedRuntimeTools,
     tool_confirmation: &ToolConfirmationSettings,
+    verbose: bool,
 ) {
     let mut built_in_tools = Vec::<String>::new();
     let mut mcp_tools = Vec::<String>::new();
@@ -410,6 +475,18 @@ pub fn print_chat_tools(
         mcp_tools.len()
     );
     println!("Tool confirmation mode: {:?}", cfg.tool_confirmation_mode);
+    println!(
+        "{}",
+        format_tool_output_cap(cfg.tool_output_max_chars, &cfg.paths.tool_output_dir())
+    );
+    println!("{}", format_tool_budgets(&cfg.tool_budgets));
+    println!(
+        "{}",
+        format_doc_search_status(cfg, built_in_tools.iter().any(|name| name == DOC_SEARCH_TOOL_NAME))
+    );
+    if let Some(banner) = read_only_banner(cfg) {
+        println!("{YELLOW}{banner}{RESET}");
+    }
     println!(
         "Confirmation decisions: required={} approved={} denied={}",
         required_count,
@@ -429,6 +506,9 @@ pub fn print_chat_tools(
             let suffix =
                 tool_permission_label(name, allow, deny, has_policy, &tool_confirmation.run_config);
             println!("  - {name}{suffix}");
+            if verbose {
+                println!("      args: {}", tool_schema_summary(name, runtime_tools));
+            }
         }
     }
     println!("MCP tools:");
@@ -439,10 +519,28 @@ pub fn print_chat_tools(
             let suffix =
                 tool_permission_label(name, allow, deny, has_policy, &tool_confirmation.run_config);
             println!("  - {name}{suffix}");
+            if verbose {
+                println!("      args: {}", tool_schema_summary(name, runtime_tools));
+            }
         }
     }
 }
 
+/// Summarize a tool's argument schema, preferring the built-in validation schema.
+pub fn tool_schema_summary(name: &str, runtime_tools: &ResolvedRuntimeTools) -> String {
+    let schema = crate::tools::schemas::builtin_tool_schema(name).or_else(|| {
+        runtime_tools
+            .tools
+            .iter()
+            .find(|tool| tool.name() == name)
+            .and_then(|tool| tool.parameters_schema())
+    });
+    match schema {
+        Some(schema) => crate::tools::schemas::schema_summary(&schema),
+        None => "<no schema>".to_string(),
+    }
+}
+
 fn tool_permission_label(
     name: &str,
     allow: &[String],
@@ -493,8 +591,10 @@ pub fn print_chat_mcp(cfg: &RuntimeConfig, runtime_tools: &ResolvedRuntimeTools)
             None if server.auth_bearer_env.is_some() => "✓ configured".to_string(),
             None => "none".to_string(),
         };
-        let server_tools: Vec<&String> = runtime_tools.mcp_tool_names.iter().collect();
-        let tool_count = server_tools.len();
+        let tool_count = runtime_tools
+            .mcp_tools_for_server(&server.name)
+            .filter(|tool| !tool.excluded)
+            .count();
         println!(
             "  {} endpoint={} auth={} tools={}",
             server.name, server.endpoint, auth_status, tool_count
@@ -511,9 +611,61 @@ pub fn print_chat_mcp(cfg: &RuntimeConfig, runtime_tools: &ResolvedRuntimeTools)
     }
 }
 
+/// Render the `/mcp <server>` detail view: one line per discovered tool with
+/// the first line of its description, a `[confirm]` marker for
+/// confirmation-gated tools, and excluded tools dimmed with `[excluded]`.
+pub fn render_chat_mcp_server(server: &str, runtime_tools: &ResolvedRuntimeTools) -> String {
+    let tools = runtime_tools
+        .mcp_tools_for_server(server)
+        .collect::<Vec<_>>();
+    if tools.is_empty() {
+        return format!(
+            "No MCP tools discovered for server '{server}'. Use /mcp to list configured servers.\n"
+        );
+    }
+
+    let excluded_count = tools.iter().filter(|tool| tool.excluded).count();
+    let mut out = format!(
+        "MCP server '{server}': tools={} active={} excluded={}\n",
+        tools.len(),
+        tools.len() - excluded_count,
+        excluded_count
+    );
+    for tool in tools {
+        let summary = tool
+            .description
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .unwrap_or("<no description>");
+        let mut markers = Vec::<&str>::new();
+        if tool.requires_confirmation {
+            markers.push("[confirm]");
+        }
+        if tool.excluded {
+            markers.push("[excluded]");
+        }
+        let markers = if markers.is_empty() {
+            String::new()
+        } else {
+            format!(" {}", markers.join(" "))
+        };
+        if tool.excluded {
+            out.push_str(&format!("  {DIM}- {}{markers} — {summary}{RESET}\n", tool.name));
+        } else {
+            out.push_str(&format!("  - {}{markers} — {summary}\n", tool.name));
+        }
+    }
+    out
+}
+
 pub enum ChatCommandAction {
     Continue,
     Exit,
+    /// A handoff happened; the brief goes in front of the next prompt.
+    Handoff(String),
+    /// Send this text as if the user had typed it (a picked starter).
+    Prompt(String),
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -529,6 +681,9 @@ pub async fn dispatch_chat_command(
     telemetry: &TelemetrySink,
     context_usage: Option<&ContextUsage>,
     checkpoint_store: &mut CheckpointStore,
+    palette: &mut PaletteIndex,
+    agents: &HashMap<String, ResolvedAgent>,
+    input: &mut dyn ChatInput,
 ) -> Result<ChatCommandAction> {
     match command {
         ChatCommand::Exit => Ok(ChatCommandAction::Exit),
@@ -539,13 +694,43 @@ pub async fn dispatch_chat_command(
             println!("  {DIM}Provider:{RESET} {GREEN}{prov}{RESET}");
             println!("  {DIM}Model:{RESET}    {GREEN}{model_name}{RESET}");
             println!("  {DIM}Session:{RESET}  {}{RESET}", cfg.session_id);
+            for line in stream_stats_status_lines() {
+                println!("  {DIM}Latency:{RESET}  {line}");
+            }
             println!();
             Ok(ChatCommandAction::Continue)
         }
+        ChatCommand::Stats { json } => {
+            let Some(stats) = &telemetry.chat_stats else {
+                println!("Session stats are not collected outside chat.");
+                return Ok(ChatCommandAction::Continue);
+            };
+            let snapshot = stats
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .snapshot(cfg, Instant::now());
+            if json {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&snapshot)
+                        .context("failed to serialize session stats")?
+                );
+            } else {
+                println!();
+                print!("{}", format_chat_stats(&snapshot));
+                println!();
+            }
+            Ok(ChatCommandAction::Continue)
+        }
         ChatCommand::Help => {
             print_chat_help();
             Ok(ChatCommandAction::Continue)
         }
+        ChatCommand::Find(query) => {
+            let hits = palette.search(&query, PALETTE_MAX_RESULTS);
+            print!("{}", format_palette_results(&query, &hits));
+            Ok(ChatCommandAction::Continue)
+        }
         ChatCommand::Usage => {
             if let Some(usage) = context_usage {
                 print!("{}", usage.format_usage());
@@ -709,12 +894,16 @@ pub async fn dispatch_chat_command(
             }
             Ok(ChatCommandAction::Continue)
         }
-        ChatCommand::Tools => {
-            print_chat_tools(cfg, runtime_tools, tool_confirmation);
+        ChatCommand::Tools { verbose } => {
+            print_chat_tools(cfg, runtime_tools, tool_confirmation, verbose);
             Ok(ChatCommandAction::Continue)
         }
-        ChatCommand::Mcp => {
-            print_chat_mcp(cfg, runtime_tools);
+        ChatCommand::Mcp(server) => {
+            if server.is_empty() {
+                print_chat_mcp(cfg, runtime_tools);
+            } else {
+                print!("{}", render_chat_mcp_server(&server, runtime_tools));
+            }
             Ok(ChatCommandAction::Continue)
         }
         ChatCommand::Provider(provider_name) => {
@@ -746,6 +935,7 @@ pub async fn dispatch_chat_command(
                     switched_cfg.provider = *resolved_provider;
                     switched_cfg.model = Some(model_name.clone());
                     *cfg = switched_cfg;
+                    palette.refresh(cfg, runtime_tools);
                     tracing::info!(
                         provider = ?resolved_provider,
                         model = %model_name,
@@ -770,7 +960,7 @@ pub async fn dispatch_chat_command(
         ChatCommand::Model(next_model) => {
             let chosen_model = match next_model {
                 Some(value) => Some(value),
-                None => prompt_model_picker(*resolved_provider, model_name)?,
+                None => prompt_model_picker(cfg, *resolved_provider, model_name)?,
             };
             let Some(chosen_model) = chosen_model else {
                 println!(
@@ -780,8 +970,18 @@ pub async fn dispatch_chat_command(
                 return Ok(ChatCommandAction::Continue);
             };
 
-            let mut switched_cfg = cfg.clone();
-            switched_cfg.model = Some(chosen_model);
+            let mut switched_cfg =
+                match chat_model_switch_config(cfg, *resolved_provider, &chosen_model) {
+                    Ok(switched_cfg) => switched_cfg,
+                    Err(err) => {
+                        eprintln!("{}", format_cli_error(&err, cfg.show_sensitive_config));
+                        println!(
+                            "Model remains '{}' on provider {:?}.",
+                            model_name, resolved_provider
+                        );
+                        return Ok(ChatCommandAction::Continue);
+                    }
+                };
 
             match build_single_runner_for_chat(
                 &switched_cfg,
@@ -806,6 +1006,7 @@ pub async fn dispatch_chat_command(
                     switched_cfg.provider = *resolved_provider;
                     switched_cfg.model = Some(model_name.clone());
                     *cfg = switched_cfg;
+                    palette.refresh(cfg, runtime_tools);
                     tracing::info!(
                         provider = ?resolved_provider,
                         model = %model_name,
@@ -827,6 +1028,101 @@ pub async fn dispatch_chat_command(
 
             Ok(ChatCommandAction::Continue)
         }
+        ChatCommand::Handoff(arg) => {
+            let Some((target, instructions)) = parse_handoff_args(&arg) else {
+                println!("Usage: /handoff <agent>|back [instructions]");
+                return Ok(ChatCommandAction::Continue);
+            };
+            let from_agent = cfg.agent_name.clone();
+            let outcome = match resolve_model(cfg) {
+                Ok((model, _, _)) => {
+                    run_handoff(
+                        cfg,
+                        agents,
+                        session_service,
+                        model,
+                        telemetry,
+                        &target,
+                        instructions,
+                        |switched| async move {
+                            let built = build_single_runner_for_chat(
+                                &switched,
+                                session_service.clone(),
+                                runtime_tools,
+                                tool_confirmation,
+                                telemetry,
+                            )
+                            .await?;
+                            Ok((built, switched))
+                        },
+                    )
+                    .await
+                }
+                Err(err) => Err(err),
+            };
+            match outcome {
+                Ok(((new_runner, new_resolved_provider, new_model_name), record)) => {
+                    *runner = new_runner;
+                    *resolved_provider = new_resolved_provider;
+                    *model_name = new_model_name;
+                    cfg.provider = *resolved_provider;
+                    cfg.model = Some(model_name.clone());
+                    palette.refresh(cfg, runtime_tools);
+                    println!(
+                        "Handed off from '{}' to '{}' (model={}). Brief for your next prompt:",
+                        record.from_agent, record.to_agent, model_name
+                    );
+                    println!("{DIM}{}{RESET}", record.brief);
+                    Ok(ChatCommandAction::Handoff(record.prompt_context()))
+                }
+                Err(err) => {
+                    eprintln!("{}", format_cli_error(&err, cfg.show_sensitive_config));
+                    println!("Agent remains '{from_agent}'.");
+                    Ok(ChatCommandAction::Continue)
+                }
+            }
+        }
+        ChatCommand::Starters(selection) => {
+            let Some(starters) = agent_starters(agents, &cfg.agent_name) else {
+                println!("Agent '{}' has no conversation starters.", cfg.agent_name);
+                return Ok(ChatCommandAction::Continue);
+            };
+            match pick_starter(starters, &selection, input) {
+                Ok(Some(prompt)) => {
+                    telemetry.emit(
+                        "chat.starter_used",
+                        json!({ "agent": cfg.agent_name.clone() }),
+                    );
+                    Ok(ChatCommandAction::Prompt(prompt))
+                }
+                Ok(None) => {
+                    println!("No starter sent.");
+                    Ok(ChatCommandAction::Continue)
+                }
+                Err(err) => {
+                    eprintln!("{}", format_cli_error(&err, cfg.show_sensitive_config));
+                    Ok(ChatCommandAction::Continue)
+                }
+            }
+        }
+        ChatCommand::Persist => {
+            match persist_ephemeral_session(session_service, cfg).await {
+                Ok((backend, events)) => {
+                    let backend = format!("{backend:?}").to_ascii_lowercase();
+                    println!(
+                        "Saved {events} events to {backend} session '{}'. Later turns stay \
+                         ephemeral until the next /persist; resume with --session-id {}.",
+                        cfg.session_id, cfg.session_id
+                    );
+                    telemetry.emit(
+                        "chat.persisted",
+                        json!({ "backend": backend, "events": events }),
+                    );
+                }
+                Err(err) => eprintln!("{}", format_cli_error(&err, cfg.show_sensitive_config)),
+            }
+            Ok(ChatCommandAction::Continue)
+        }
         ChatCommand::Agent => {
             use crate::tools::confirming::{is_agent_mode, trust_tool};
             if is_agent_mode() {
@@ -977,6 +1273,64 @@ pub async fn dispatch_chat_command(
             }
             Ok(ChatCommandAction::Continue)
         }
+        ChatCommand::Diff(path) => {
+            let output = crate::change_log::with_session_changes(|log| match path.as_deref() {
+                None => {
+                    let workspace = std::env::current_dir().unwrap_or_default();
+                    crate::change_log::format_change_summary(log, &|file| {
+                        crate::change_log::git_numstat(&workspace, &file.path)
+                    })
+                }
+                Some(path) => match log.file(path) {
+                    Some(file) => crate::change_log::format_file_diff(
+                        file,
+                        std::fs::read(&file.path).ok().as_deref(),
+                    ),
+                    None => format!("'{path}' was not changed by a tool in this session.\n"),
+                },
+            });
+            print!("{output}");
+            Ok(ChatCommandAction::Continue)
+        }
+        ChatCommand::Notify(action) => {
+            let Some(command) = cfg.chat_notify_command.clone() else {
+                println!("No chat_notify_command configured for profile '{}'.", cfg.profile);
+                println!(
+                    "  Example: chat_notify_command = 'notify-send zavora \"$ZAVORA_STATUS ($ZAVORA_DURATION_MS ms)\"'"
+                );
+                return Ok(ChatCommandAction::Continue);
+            };
+            match action.as_str() {
+                "on" => cfg.chat_notify_enabled = true,
+                "off" => cfg.chat_notify_enabled = false,
+                "test" => {
+                    match run_chat_notify_command(
+                        &command,
+                        Duration::ZERO,
+                        "test",
+                        &cfg.session_id,
+                        CHAT_NOTIFY_TIMEOUT,
+                    )
+                    .await
+                    {
+                        Ok(()) => println!("Test notification sent."),
+                        Err(err) => eprintln!("Test notification failed: {err}"),
+                    }
+                    return Ok(ChatCommandAction::Continue);
+                }
+                _ => {}
+            }
+            let status = if cfg.chat_notify_enabled {
+                "enabled"
+            } else {
+                "disabled"
+            };
+            println!(
+                "Notifications {status} (responses over {}s run: {command})",
+                cfg.chat_notify_threshold_secs
+            );
+            Ok(ChatCommandAction::Continue)
+        }
         ChatCommand::Ralph(prompt) => {
             if prompt.trim().is_empty() {
                 println!("Usage: /ralph <prompt>");
@@ -1000,14 +1354,49 @@ pub async fn dispatch_chat_command(
     }
 }
 
+/// Fire `chat_notify_command` in the background for slow responses.
+fn notify_chat_response(cfg: &RuntimeConfig, elapsed: Duration, status: &str) {
+    let _ = spawn_chat_notification(
+        cfg.chat_notify_command.as_deref(),
+        cfg.chat_notify_enabled,
+        cfg.chat_notify_threshold_secs,
+        elapsed,
+        status,
+        &cfg.session_id,
+    );
+}
+
+/// `chat --script` settings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChatScriptOptions {
+    pub path: String,
+    /// Abort with an error on the first failed turn instead of continuing.
+    pub fail_fast: bool,
+}
+
 pub async fn run_chat(
     mut cfg: RuntimeConfig,
     retrieval_service: Arc<dyn RetrievalService>,
     runtime_tools: ResolvedRuntimeTools,
     tool_confirmation: ToolConfirmationSettings,
     telemetry: &TelemetrySink,
+    script: Option<ChatScriptOptions>,
+    agents: &HashMap<String, ResolvedAgent>,
 ) -> Result<()> {
-    let session_service = build_session_service(&cfg).await?;
+    let mut input: Box<dyn ChatInput> = match &script {
+        Some(options) => Box::new(ScriptInput::load(&options.path)?),
+        None => Box::new(ReadlineInput::new()?),
+    };
+    let fail_fast = script.as_ref().is_some_and(|options| options.fail_fast);
+    let telemetry = &telemetry
+        .clone()
+        .with_chat_stats(ChatSessionStats::shared());
+
+    let history_window = HistoryWindow::default();
+    let session_service: Arc<dyn SessionService> = Arc::new(TrimmedHistorySessionService::new(
+        build_session_service(&cfg).await?,
+        history_window.clone(),
+    ));
     let (mut runner, mut resolved_provider, mut model_name) = build_single_runner_for_chat(
         &cfg,
         session_service.clone(),
@@ -1019,6 +1408,7 @@ pub async fn run_chat(
 
     cfg.provider = resolved_provider;
     cfg.model = Some(model_name.clone());
+    let mut palette = PaletteIndex::build(&cfg, &runtime_tools, palette_agents(agents));
 
     telemetry.emit(
         "chat.started",
@@ -1032,16 +1422,61 @@ pub async fn run_chat(
     tracing::info!(provider = ?resolved_provider, model = %model_name, "Using model");
     let provider_label = format!("{:?}", resolved_provider).to_ascii_lowercase();
     print_startup_banner(&provider_label, &model_name);
-    if buffered_output_required(cfg.guardrail_output_mode) {
+    if let Some(banner) = read_only_banner(&cfg) {
+        println!("  {YELLOW}{banner}{RESET}");
+        println!();
+    }
+    if let Some(banner) = ephemeral_banner(&cfg) {
+        println!("  {YELLOW}{banner}{RESET}");
+        println!();
+    }
+    if output_buffering_required(&cfg) {
         println!(
-            "  {YELLOW}Guardrail output mode {:?} active: responses will be buffered.{RESET}",
-            cfg.guardrail_output_mode
+            "  {YELLOW}Guardrail output can block or redact: responses will be buffered.{RESET}"
         );
         println!();
     }
-    let mut rl = rustyline::DefaultEditor::new().context("failed to initialize readline")?;
-    let workspace = std::env::current_dir().unwrap_or_default();
+    if let Some(starters) = agent_starters(agents, &cfg.agent_name) {
+        println!("  {DIM}{}{RESET}", format_starters_hint(starters));
+        println!();
+    }
+
+    // Scripted runs skip the LLM greeting so their output is reproducible.
+    if script.is_none() {
+        print_chat_greeting(&runner, &cfg, retrieval_service.as_ref(), telemetry).await;
+    }
+
+    let result = run_chat_loop(
+        &mut cfg,
+        &mut runner,
+        &mut resolved_provider,
+        &mut model_name,
+        &session_service,
+        &history_window,
+        retrieval_service.as_ref(),
+        &runtime_tools,
+        &tool_confirmation,
+        telemetry,
+        input.as_mut(),
+        fail_fast,
+        &mut palette,
+        agents,
+    )
+    .await;
+
+    // Cleanup browser session if started
+    #[cfg(feature = "browser")]
+    crate::tools::browser::cleanup_browser().await;
+
+    result
+}
 
+async fn print_chat_greeting(
+    runner: &Runner,
+    cfg: &RuntimeConfig,
+    retrieval_service: &dyn RetrievalService,
+    telemetry: &TelemetrySink,
+) {
     // Bootstrap: Get time and memory context once at startup for personalized greeting
     let time_context = TimeAgent::handshake();
     let memories = crate::agents::memory::recall("", 5).await.unwrap_or_default();
@@ -1067,10 +1502,10 @@ pub async fn run_chat(
         );
 
         match run_prompt_streaming_with_retrieval(
-            &runner,
-            &cfg,
+            runner,
+            cfg,
             &greeting_prompt,
-            retrieval_service.as_ref(),
+            retrieval_service,
             telemetry,
         )
         .await
@@ -1082,23 +1517,120 @@ pub async fn run_chat(
         }
     }
     println!();
+}
+
+/// Idle exit: tell the user, save a transcript and, for the memory backend,
+/// flush the session into the sqlite snapshot database. Save failures are
+/// reported but never keep chat open.
+pub async fn handle_chat_idle(
+    cfg: &RuntimeConfig,
+    session_service: &Arc<dyn SessionService>,
+    telemetry: &TelemetrySink,
+) {
+    println!(
+        "{YELLOW}No input for {}s; ending the chat session.{RESET}",
+        cfg.chat_idle_timeout_secs
+    );
+    if !cfg.chat_idle_transcript_dir.is_empty() {
+        let provenance = answer_provenance(cfg, telemetry);
+        match save_session_transcript(
+            session_service,
+            cfg,
+            std::path::Path::new(&cfg.chat_idle_transcript_dir),
+            provenance.as_ref(),
+        )
+        .await
+        {
+            Ok(path) => println!("{DIM}Transcript saved to {}{RESET}", path.display()),
+            Err(err) => eprintln!("{}", format_cli_error(&err, cfg.show_sensitive_config)),
+        }
+    }
+    if cfg.session_backend == SessionBackend::Memory && !cfg.chat_idle_snapshot_db_url.is_empty() {
+        match snapshot_session_to_sqlite(session_service, cfg, &cfg.chat_idle_snapshot_db_url).await
+        {
+            Ok(count) => println!(
+                "{DIM}Session '{}' ({count} events) flushed to {}; resume with --session-backend sqlite --session-db-url {} --session-id {}{RESET}",
+                cfg.session_id,
+                cfg.chat_idle_snapshot_db_url,
+                cfg.chat_idle_snapshot_db_url,
+                cfg.session_id
+            ),
+            Err(err) => eprintln!("{}", format_cli_error(&err, cfg.show_sensitive_config)),
+        }
+    }
+}
+
+/// Whether a failed chat turn ends the loop.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ChatTurnFailure {
+    /// Guardrail blocks: interactive chat reports them and keeps going.
+    Recoverable,
+    /// Provider and command failures: interactive chat exits with the error.
+    Fatal,
+}
+
+/// Report a failed turn and continue (`Ok`), or return the error that ends
+/// the loop. Scripts continue past every failure unless `fail_fast` is set.
+fn handle_chat_turn_failure(
+    cfg: &RuntimeConfig,
+    input: &dyn ChatInput,
+    fail_fast: bool,
+    failure: ChatTurnFailure,
+    err: anyhow::Error,
+) -> Result<()> {
+    match input.script_line() {
+        Some(line) if fail_fast => Err(err.context(format!("chat script aborted at line {line}"))),
+        None if failure == ChatTurnFailure::Fatal => Err(err),
+        _ => {
+            eprintln!("{}", format_cli_error(&err, cfg.show_sensitive_config));
+            Ok(())
+        }
+    }
+}
 
+/// The chat read-eval loop, reading from `input` until it ends or `/exit`.
+#[allow(clippy::too_many_arguments)]
+pub async fn run_chat_loop(
+    cfg: &mut RuntimeConfig,
+    runner: &mut Runner,
+    resolved_provider: &mut Provider,
+    model_name: &mut String,
+    session_service: &Arc<dyn SessionService>,
+    history_window: &HistoryWindow,
+    retrieval_service: &dyn RetrievalService,
+    runtime_tools: &ResolvedRuntimeTools,
+    tool_confirmation: &ToolConfirmationSettings,
+    telemetry: &TelemetrySink,
+    input: &mut dyn ChatInput,
+    fail_fast: bool,
+    palette: &mut PaletteIndex,
+    agents: &HashMap<String, ResolvedAgent>,
+) -> Result<()> {
+    let workspace = std::env::current_dir().unwrap_or_default();
+    let mut idle = IdleTimer::new(cfg.chat_idle_timeout_secs, Instant::now());
     let mut checkpoint_store = CheckpointStore::load_from_disk(&workspace);
     let mut last_ctrl_c: Option<std::time::Instant> = None;
+    let mut pending_handoff: Option<String> = None;
+    let mut omitted_exchanges = 0usize;
 
     loop {
-        // Compute context usage from live session data
-        let context_usage = match snapshot_session_events(&session_service, &cfg).await {
-            Ok(events) => {
-                let provider_str = format!("{:?}", resolved_provider).to_ascii_lowercase();
-                Some(compute_context_usage(&events, &provider_str, &model_name))
-            }
-            Err(_) => None,
-        };
+        // Compute context usage from live session data; the same snapshot
+        // is the history a window or summary retrieval query reads.
+        let history = snapshot_session_events(session_service, cfg).await.ok();
+        let context_usage = history.as_ref().map(|events| {
+            let provider_str = format!("{:?}", resolved_provider).to_ascii_lowercase();
+            compute_context_usage(events, &provider_str, model_name)
+        });
         let prompt = build_prompt(&checkpoint_store, context_usage.as_ref());
-        let input = match rl.readline(&prompt) {
-            Ok(line) => line,
-            Err(rustyline::error::ReadlineError::Interrupted) => {
+        let event = input.read_line_with_timeout(&prompt, idle.remaining(Instant::now()))?;
+        idle.reset(Instant::now());
+        let line = match event {
+            ChatInputEvent::Line(line) => line,
+            ChatInputEvent::Idle => {
+                handle_chat_idle(cfg, session_service, telemetry).await;
+                break;
+            }
+            ChatInputEvent::Interrupted => {
                 if let Some(prev) = last_ctrl_c {
                     if prev.elapsed() < std::time::Duration::from_secs(2) {
                         println!();
@@ -1109,21 +1641,25 @@ pub async fn run_chat(
                 println!("\n{}Press Ctrl+C again to exit.{}", crate::theme::DIM, crate::theme::RESET);
                 continue;
             }
-            Err(rustyline::error::ReadlineError::Eof) => break,
-            Err(e) => return Err(anyhow::anyhow!("readline error: {e}")),
+            ChatInputEvent::Eof => break,
         };
-        let input = input.trim();
-        if input.is_empty() {
+        let line = line.trim();
+        if line.is_empty() {
             continue;
         }
         last_ctrl_c = None; // Reset double Ctrl+C window on valid input
-        rl.add_history_entry(input).ok();
-        if input.eq_ignore_ascii_case("/exit") || input.eq_ignore_ascii_case("exit") {
+        if input.script_line().is_some() {
+            println!("{BOLD}>> {line}{RESET}");
+        }
+        input.add_history(line);
+        if line.eq_ignore_ascii_case("/exit") || line.eq_ignore_ascii_case("exit") {
             break;
         }
 
-        match parse_chat_command(input) {
-            ParsedChatCommand::NotACommand => {}
+        // A picked starter replaces the command line as this turn's input.
+        let starter_prompt: String;
+        let line = match parse_chat_command(line) {
+            ParsedChatCommand::NotACommand => line,
             ParsedChatCommand::MissingArgument { usage } => {
                 println!("Usage: {usage}");
                 continue;
@@ -1138,50 +1674,118 @@ pub async fn run_chat(
                 continue;
             }
             ParsedChatCommand::Command(command) => {
-                let action = dispatch_chat_command(
+                let action = match dispatch_chat_command(
                     command,
-                    &mut cfg,
-                    &mut runner,
-                    &mut resolved_provider,
-                    &mut model_name,
-                    &session_service,
-                    &runtime_tools,
-                    &tool_confirmation,
+                    cfg,
+                    runner,
+                    resolved_provider,
+                    model_name,
+                    session_service,
+                    runtime_tools,
+                    tool_confirmation,
                     telemetry,
                     context_usage.as_ref(),
                     &mut checkpoint_store,
+                    palette,
+                    agents,
+                    &mut *input,
                 )
-                .await?;
+                .await
+                {
+                    Ok(action) => action,
+                    Err(err) => {
+                        handle_chat_turn_failure(
+                            cfg,
+                            input,
+                            fail_fast,
+                            ChatTurnFailure::Fatal,
+                            err,
+                        )?;
+                        continue;
+                    }
+                };
                 // Persist checkpoint store after any command that may mutate it
                 let _ = checkpoint_store.save_to_disk(&workspace);
-                if matches!(action, ChatCommandAction::Exit) {
-                    break;
+                match action {
+                    ChatCommandAction::Exit => break,
+                    ChatCommandAction::Handoff(context) => {
+                        pending_handoff = Some(context);
+                        continue;
+                    }
+                    ChatCommandAction::Continue => continue,
+                    ChatCommandAction::Prompt(prompt) => {
+                        starter_prompt = prompt;
+                        starter_prompt.as_str()
+                    }
                 }
-                continue;
             }
-        }
+        };
 
         let guarded_input =
-            match apply_guardrail(&cfg, telemetry, "input", cfg.guardrail_input_mode, input) {
+            match apply_guardrail(cfg, telemetry, "input", cfg.guardrail_input_mode, line) {
                 Ok(text) => text,
                 Err(err) => {
-                    eprintln!("{}", format_cli_error(&err, cfg.show_sensitive_config));
+                    handle_chat_turn_failure(
+                        cfg,
+                        input,
+                        fail_fast,
+                        ChatTurnFailure::Recoverable,
+                        err,
+                    )?;
                     continue;
                 }
             };
 
-        if buffered_output_required(cfg.guardrail_output_mode) {
+        let chat_retrieval = ConversationRetrieval::new(
+            retrieval_service,
+            chat_retrieval_query(cfg, history.as_deref().unwrap_or_default(), &guarded_input),
+        );
+        // After a handoff the brief leads the first prompt to the new agent;
+        // retrieval still uses the user's own text.
+        let model_prompt = match pending_handoff.take() {
+            Some(context) => format!("{context}\nUser request:\n{guarded_input}"),
+            None => guarded_input.clone(),
+        };
+        // Near the context limit the oldest exchanges sit this turn out.
+        let history_trim = plan_chat_history_trim(
+            cfg,
+            telemetry,
+            history.as_deref().unwrap_or_default(),
+            &model_prompt,
+            &format!("{:?}", resolved_provider).to_ascii_lowercase(),
+            model_name,
+            omitted_exchanges,
+        );
+        omitted_exchanges = history_trim
+            .as_ref()
+            .map_or(0, |trim| trim.omitted_exchanges);
+        let trim_guard = history_window.apply(&cfg.session_id, history_trim);
+        let response_started = Instant::now();
+        if output_buffering_required(cfg) {
             println!();
-            let answer = run_prompt_with_retrieval(
-                &runner,
-                &cfg,
-                &guarded_input,
-                retrieval_service.as_ref(),
+            let answer = match run_prompt_with_retrieval(
+                runner,
+                cfg,
+                &model_prompt,
+                &chat_retrieval,
                 telemetry,
             )
-            .await?;
+            .await
+            {
+                Ok(answer) => answer,
+                Err(err) => {
+                    handle_chat_turn_failure(
+                        cfg,
+                        input,
+                        fail_fast,
+                        ChatTurnFailure::Fatal,
+                        err,
+                    )?;
+                    continue;
+                }
+            };
             let answer = match apply_guardrail(
-                &cfg,
+                cfg,
                 telemetry,
                 "output",
                 cfg.guardrail_output_mode,
@@ -1189,13 +1793,20 @@ pub async fn run_chat(
             ) {
                 Ok(text) => text,
                 Err(err) => {
-                    eprintln!("{}", format_cli_error(&err, cfg.show_sensitive_config));
+                    notify_chat_response(cfg, response_started.elapsed(), "blocked");
+                    handle_chat_turn_failure(
+                        cfg,
+                        input,
+                        fail_fast,
+                        ChatTurnFailure::Recoverable,
+                        err,
+                    )?;
                     continue;
                 }
             };
             // Render markdown for buffered output
             let mut md_state = crate::markdown::ParseState::new();
-            let mut buf = answer.clone();
+            let mut buf = crate::sanitize::sanitize_output(cfg, &answer);
             buf.push('\n');
             let mut offset = 0;
             let mut stdout = std::io::stdout();
@@ -1214,17 +1825,30 @@ pub async fn run_chat(
             println!();
         } else {
             println!();
-            let answer = run_prompt_streaming_with_retrieval(
-                &runner,
-                &cfg,
-                &guarded_input,
-                retrieval_service.as_ref(),
+            let answer = match run_prompt_streaming_with_retrieval(
+                runner,
+                cfg,
+                &model_prompt,
+                &chat_retrieval,
                 telemetry,
             )
-            .await?;
-            if matches!(cfg.guardrail_output_mode, GuardrailMode::Observe)
+            .await
+            {
+                Ok(answer) => answer,
+                Err(err) => {
+                    handle_chat_turn_failure(
+                        cfg,
+                        input,
+                        fail_fast,
+                        ChatTurnFailure::Fatal,
+                        err,
+                    )?;
+                    continue;
+                }
+            };
+            if guardrail_active(cfg, cfg.guardrail_output_mode)
                 && let Err(err) = apply_guardrail(
-                    &cfg,
+                    cfg,
                     telemetry,
                     "output",
                     cfg.guardrail_output_mode,
@@ -1234,12 +1858,14 @@ pub async fn run_chat(
                 eprintln!("{}", format_cli_error(&err, cfg.show_sensitive_config));
             }
         }
+        notify_chat_response(cfg, response_started.elapsed(), "ok");
+        drop(trim_guard);
 
         // Check if auto-compaction should trigger
         if cfg.auto_compact_enabled {
-            if let Ok(events) = snapshot_session_events(&session_service, &cfg).await {
+            if let Ok(events) = snapshot_session_events(session_service, cfg).await {
                 let provider_str = format!("{:?}", resolved_provider).to_ascii_lowercase();
-                let usage = compute_context_usage(&events, &provider_str, &model_name);
+                let usage = compute_context_usage(&events, &provider_str, model_name);
                 if usage.utilization() >= cfg.compaction_threshold {
                     println!();
                     println!(
@@ -1249,7 +1875,7 @@ pub async fn run_chat(
                         crate::theme::RESET
                     );
                     let target_util = cfg.compaction_target;
-                    match compact_to_target(&session_service, &cfg, target_util).await {
+                    match compact_to_target(session_service, cfg, target_util).await {
                         Ok(msg) => println!("{}{}{}", crate::theme::DIM, msg, crate::theme::RESET),
                         Err(e) => eprintln!("Auto-compaction failed: {e}"),
                     }
@@ -1259,9 +1885,11 @@ pub async fn run_chat(
         }
     }
 
-    // Cleanup browser session if started
-    #[cfg(feature = "browser")]
-    crate::tools::browser::cleanup_browser().await;
+    crate::change_log::with_session_changes(|log| {
+        if !log.is_empty() {
+            telemetry.emit("session.changes", log.to_telemetry());
+        }
+    });
 
     Ok(())
 }
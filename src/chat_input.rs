@@ -0,0 +1,237 @@
+/// Input sources for the chat loop: the interactive readline editor and
+/// `chat --script` files.
+use std::collections::VecDeque;
+use std::io::{self, IsTerminal, Write};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use crossterm::event::{Event, KeyCode, KeyEventKind, KeyModifiers, poll, read};
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
+
+/// What the chat loop receives when it asks for the next line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChatInputEvent {
+    Line(String),
+    /// Ctrl+C at the prompt.
+    Interrupted,
+    /// End of input (Ctrl+D or the end of a script).
+    Eof,
+    /// Nothing was typed before the idle timeout.
+    Idle,
+}
+
+pub trait ChatInput {
+    fn read_line(&mut self, prompt: &str) -> Result<ChatInputEvent>;
+
+    /// Like [`ChatInput::read_line`], but gives up with
+    /// [`ChatInputEvent::Idle`] when nothing is typed within `timeout`.
+    /// Sources that cannot time out ignore it.
+    fn read_line_with_timeout(
+        &mut self,
+        prompt: &str,
+        _timeout: Option<Duration>,
+    ) -> Result<ChatInputEvent> {
+        self.read_line(prompt)
+    }
+
+    fn add_history(&mut self, _line: &str) {}
+
+    /// 1-based script line of the last input returned; `None` for
+    /// interactive sources.
+    fn script_line(&self) -> Option<usize> {
+        None
+    }
+}
+
+/// Interactive input through rustyline.
+pub struct ReadlineInput {
+    editor: rustyline::DefaultEditor,
+}
+
+impl ReadlineInput {
+    pub fn new() -> Result<Self> {
+        let editor = rustyline::DefaultEditor::new().context("failed to initialize readline")?;
+        Ok(Self { editor })
+    }
+}
+
+impl ChatInput for ReadlineInput {
+    fn read_line(&mut self, prompt: &str) -> Result<ChatInputEvent> {
+        match self.editor.readline(prompt) {
+            Ok(line) => Ok(ChatInputEvent::Line(line)),
+            Err(rustyline::error::ReadlineError::Interrupted) => Ok(ChatInputEvent::Interrupted),
+            Err(rustyline::error::ReadlineError::Eof) => Ok(ChatInputEvent::Eof),
+            Err(e) => Err(anyhow::anyhow!("readline error: {e}")),
+        }
+    }
+
+    fn read_line_with_timeout(
+        &mut self,
+        prompt: &str,
+        timeout: Option<Duration>,
+    ) -> Result<ChatInputEvent> {
+        let Some(timeout) = timeout else {
+            return self.read_line(prompt);
+        };
+        if !io::stdin().is_terminal() {
+            return self.read_line(prompt);
+        }
+
+        // rustyline cannot be interrupted mid-read, so wait for the first
+        // key ourselves and hand it to the editor as initial text.
+        print!("{prompt}");
+        io::stdout().flush().ok();
+        enable_raw_mode().context("failed to enable raw mode")?;
+        let first_key = wait_for_first_key(timeout);
+        disable_raw_mode().context("failed to disable raw mode")?;
+        let initial = match first_key? {
+            FirstKey::Timeout => {
+                println!();
+                return Ok(ChatInputEvent::Idle);
+            }
+            FirstKey::Interrupted => {
+                println!();
+                return Ok(ChatInputEvent::Interrupted);
+            }
+            FirstKey::Eof => {
+                println!();
+                return Ok(ChatInputEvent::Eof);
+            }
+            FirstKey::Enter => {
+                println!();
+                return Ok(ChatInputEvent::Line(String::new()));
+            }
+            FirstKey::Text(text) => text,
+        };
+        print!("\r");
+        match self.editor.readline_with_initial(prompt, (&initial, "")) {
+            Ok(line) => Ok(ChatInputEvent::Line(line)),
+            Err(rustyline::error::ReadlineError::Interrupted) => Ok(ChatInputEvent::Interrupted),
+            Err(rustyline::error::ReadlineError::Eof) => Ok(ChatInputEvent::Eof),
+            Err(e) => Err(anyhow::anyhow!("readline error: {e}")),
+        }
+    }
+
+    fn add_history(&mut self, line: &str) {
+        self.editor.add_history_entry(line).ok();
+    }
+}
+
+enum FirstKey {
+    Timeout,
+    Interrupted,
+    Eof,
+    Enter,
+    /// A printable key (empty for navigation keys), passed on to the editor.
+    Text(String),
+}
+
+fn wait_for_first_key(timeout: Duration) -> Result<FirstKey> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() || !poll(remaining).context("failed to poll terminal input")? {
+            return Ok(FirstKey::Timeout);
+        }
+        let Event::Key(key) = read().context("failed to read terminal input")? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        return Ok(match key.code {
+            KeyCode::Char('c') if ctrl => FirstKey::Interrupted,
+            KeyCode::Char('d') if ctrl => FirstKey::Eof,
+            KeyCode::Enter => FirstKey::Enter,
+            KeyCode::Char(c) if !ctrl => FirstKey::Text(c.to_string()),
+            _ => FirstKey::Text(String::new()),
+        });
+    }
+}
+
+/// Countdown for `chat_idle_timeout_secs`, driven by explicit instants so
+/// tests can use a fake clock.
+#[derive(Debug, Clone, Copy)]
+pub struct IdleTimer {
+    timeout: Option<Duration>,
+    last_input: Instant,
+}
+
+impl IdleTimer {
+    /// `timeout_secs == 0` disables the timer.
+    pub fn new(timeout_secs: u64, now: Instant) -> Self {
+        Self {
+            timeout: (timeout_secs > 0).then(|| Duration::from_secs(timeout_secs)),
+            last_input: now,
+        }
+    }
+
+    /// Restart the countdown; called on any input, including Ctrl+C.
+    pub fn reset(&mut self, now: Instant) {
+        self.last_input = now;
+    }
+
+    /// Time left before the session counts as idle; `None` when disabled.
+    pub fn remaining(&self, now: Instant) -> Option<Duration> {
+        self.timeout
+            .map(|timeout| timeout.saturating_sub(now.saturating_duration_since(self.last_input)))
+    }
+
+    pub fn expired(&self, now: Instant) -> bool {
+        self.remaining(now).is_some_and(|left| left.is_zero())
+    }
+}
+
+/// Inputs replayed from a `chat --script` file, one per line.
+#[derive(Debug, Clone, Default)]
+pub struct ScriptInput {
+    lines: VecDeque<(usize, String)>,
+    current_line: Option<usize>,
+}
+
+impl ScriptInput {
+    pub fn from_text(text: &str) -> Self {
+        Self {
+            lines: parse_chat_script(text).into(),
+            current_line: None,
+        }
+    }
+
+    pub fn load(path: &str) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read chat script '{path}'"))?;
+        Ok(Self::from_text(&text))
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.lines.len()
+    }
+}
+
+impl ChatInput for ScriptInput {
+    fn read_line(&mut self, _prompt: &str) -> Result<ChatInputEvent> {
+        match self.lines.pop_front() {
+            Some((line_number, line)) => {
+                self.current_line = Some(line_number);
+                Ok(ChatInputEvent::Line(line))
+            }
+            None => Ok(ChatInputEvent::Eof),
+        }
+    }
+
+    fn script_line(&self) -> Option<usize> {
+        self.current_line
+    }
+}
+
+/// Script inputs with their 1-based line numbers; blank lines and `#`
+/// comments are skipped.
+pub fn parse_chat_script(text: &str) -> Vec<(usize, String)> {
+    text.lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(line_number, line)| (line_number, line.to_string()))
+        .collect()
+}
@@ -0,0 +1,163 @@
+//! `/stats`: activity counters for the current chat process.
+//!
+//! `run_chat` attaches a [`ChatSessionStats`] to its telemetry sink, and
+//! `TelemetrySink::emit` feeds it every event before deciding whether to
+//! write it, so the counters work with telemetry disabled and cannot drift
+//! from what the telemetry stream records.
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::config::RuntimeConfig;
+use crate::usage::usage_cost;
+
+/// Shared between the telemetry sink and the `/stats` handler.
+pub type SharedChatStats = Arc<Mutex<ChatSessionStats>>;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ToolStats {
+    pub invocations: u64,
+    pub succeeded: u64,
+    pub failed: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ModelSwitch {
+    /// `provider` or `model`.
+    pub kind: String,
+    pub provider: String,
+    pub model: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChatSessionStats {
+    #[serde(skip)]
+    started: Instant,
+    pub started_at: String,
+    /// Filled in by [`ChatSessionStats::snapshot`].
+    pub elapsed_secs: u64,
+    /// Completed model calls (one `model.stream_stats` each).
+    pub exchanges: u64,
+    pub estimated_tokens: u64,
+    /// `None` without `usage_cost_per_1k_tokens` pricing.
+    pub estimated_cost: Option<f64>,
+    pub tools: BTreeMap<String, ToolStats>,
+    /// Hits keyed `direction.mode`, e.g. `input.redacted`.
+    pub guardrail_hits: BTreeMap<String, u64>,
+    pub switches: Vec<ModelSwitch>,
+}
+
+impl ChatSessionStats {
+    pub fn new(started: Instant) -> Self {
+        Self {
+            started,
+            started_at: chrono::Utc::now().to_rfc3339(),
+            elapsed_secs: 0,
+            exchanges: 0,
+            estimated_tokens: 0,
+            estimated_cost: None,
+            tools: BTreeMap::new(),
+            guardrail_hits: BTreeMap::new(),
+            switches: Vec::new(),
+        }
+    }
+
+    pub fn shared() -> SharedChatStats {
+        Arc::new(Mutex::new(Self::new(Instant::now())))
+    }
+
+    /// Update the counters for one telemetry event.
+    pub fn record(&mut self, event: &str, payload: &Value) {
+        let tool = || payload["tool"].as_str().unwrap_or("unknown").to_string();
+        match event {
+            "model.stream_stats" => {
+                self.exchanges += 1;
+                self.estimated_tokens += payload["tokens"].as_u64().unwrap_or_default();
+            }
+            "tool.requested" => self.tools.entry(tool()).or_default().invocations += 1,
+            "tool.succeeded" => self.tools.entry(tool()).or_default().succeeded += 1,
+            "tool.failed" => self.tools.entry(tool()).or_default().failed += 1,
+            "chat.provider_switched" | "chat.model_switched" => self.switches.push(ModelSwitch {
+                kind: if event == "chat.provider_switched" {
+                    "provider"
+                } else {
+                    "model"
+                }
+                .to_string(),
+                provider: payload["provider"].as_str().unwrap_or_default().to_string(),
+                model: payload["model"].as_str().unwrap_or_default().to_string(),
+            }),
+            _ => {
+                if let Some(hit) = event.strip_prefix("guardrail.") {
+                    *self.guardrail_hits.entry(hit.to_string()).or_default() += 1;
+                }
+            }
+        }
+    }
+
+    /// Copy with elapsed time and cost filled in as of `now`.
+    pub fn snapshot(&self, cfg: &RuntimeConfig, now: Instant) -> Self {
+        Self {
+            elapsed_secs: now.saturating_duration_since(self.started).as_secs(),
+            estimated_cost: (cfg.usage_cost_per_1k_tokens > 0.0)
+                .then(|| usage_cost(cfg, self.estimated_tokens)),
+            ..self.clone()
+        }
+    }
+}
+
+fn format_elapsed(secs: u64) -> String {
+    match secs {
+        0..60 => format!("{secs}s"),
+        60..3600 => format!("{}m {}s", secs / 60, secs % 60),
+        _ => format!("{}h {}m", secs / 3600, (secs % 3600) / 60),
+    }
+}
+
+/// `/stats` text for a [`ChatSessionStats::snapshot`].
+pub fn format_chat_stats(stats: &ChatSessionStats) -> String {
+    let mut out = format!(
+        "Session stats ({} elapsed)\n  exchanges: {}\n  estimated tokens: {}\n",
+        format_elapsed(stats.elapsed_secs),
+        stats.exchanges,
+        stats.estimated_tokens
+    );
+    if let Some(cost) = stats.estimated_cost {
+        out.push_str(&format!("  estimated cost: ${cost:.4}\n"));
+    }
+    if stats.tools.is_empty() {
+        out.push_str("  tools: none\n");
+    } else {
+        out.push_str("  tools:\n");
+        for (name, tool) in &stats.tools {
+            out.push_str(&format!(
+                "    {name}: {} call(s), {} ok, {} failed\n",
+                tool.invocations, tool.succeeded, tool.failed
+            ));
+        }
+    }
+    if !stats.guardrail_hits.is_empty() {
+        let hits = stats
+            .guardrail_hits
+            .iter()
+            .map(|(key, count)| format!("{key}={count}"))
+            .collect::<Vec<String>>()
+            .join(", ");
+        out.push_str(&format!("  guardrail hits: {hits}\n"));
+    }
+    if stats.switches.is_empty() {
+        out.push_str("  switches: none\n");
+    } else {
+        out.push_str("  switches:\n");
+        for switch in &stats.switches {
+            out.push_str(&format!(
+                "    /{} -> {} {}\n",
+                switch.kind, switch.provider, switch.model
+            ));
+        }
+    }
+    out
+}
@@ -13,7 +13,8 @@ use std::path::Path;
 use std::sync::Arc;
 
 use crate::config::RuntimeConfig;
-use crate::session::ensure_session_exists;
+use crate::paths::WORKSPACE_DIR;
+use crate::session::{ensure_session_exists, with_session_busy_retry};
 
 // ---------------------------------------------------------------------------
 // Checkpoint
@@ -74,6 +75,21 @@ impl CheckpointStore {
         self.checkpoints.iter().find(|c| c.tag == tag)
     }
 
+    /// Drop checkpoints whose last event is older than `cutoff`. Empty
+    /// checkpoints and the tangent baseline are kept. Returns how many went.
+    pub fn prune_older_than(&mut self, cutoff: chrono::DateTime<chrono::Utc>) -> usize {
+        let before = self.checkpoints.len();
+        let baseline = self.tangent_baseline;
+        self.checkpoints.retain(|cp| {
+            Some(cp.tag) == baseline
+                || cp
+                    .events
+                    .last()
+                    .is_none_or(|event| event.timestamp >= cutoff)
+        });
+        before - self.checkpoints.len()
+    }
+
     /// Whether tangent mode is active.
     pub fn in_tangent(&self) -> bool {
         self.tangent_baseline.is_some()
@@ -119,7 +135,7 @@ impl CheckpointStore {
 
     /// Save the checkpoint store to disk.
     pub fn save_to_disk(&self, workspace: &Path) -> Result<()> {
-        let dir = workspace.join(".zavora");
+        let dir = workspace.join(WORKSPACE_DIR);
         std::fs::create_dir_all(&dir).context("failed to create .zavora directory")?;
         let path = dir.join("checkpoints.json");
         let json = serde_json::to_string_pretty(self).context("failed to serialize checkpoints")?;
@@ -129,7 +145,7 @@ impl CheckpointStore {
 
     /// Load the checkpoint store from disk, or return a new empty store.
     pub fn load_from_disk(workspace: &Path) -> Self {
-        let path = workspace.join(".zavora").join("checkpoints.json");
+        let path = workspace.join(WORKSPACE_DIR).join("checkpoints.json");
         if !path.exists() {
             return Self::default();
         }
@@ -168,22 +184,24 @@ pub async fn restore_session_events(
     cfg: &RuntimeConfig,
     events: &[Event],
 ) -> Result<()> {
-    session_service
-        .delete(adk_session::DeleteRequest {
+    with_session_busy_retry(cfg, "delete", || {
+        session_service.delete(adk_session::DeleteRequest {
             app_name: cfg.app_name.clone(),
             user_id: cfg.user_id.clone(),
             session_id: cfg.session_id.clone(),
         })
-        .await
-        .context("failed to delete session for restore")?;
+    })
+    .await
+    .context("failed to delete session for restore")?;
 
     ensure_session_exists(session_service, cfg).await?;
 
     for event in events {
-        session_service
-            .append_event(&cfg.session_id, event.clone())
-            .await
-            .context("failed to re-append event during restore")?;
+        with_session_busy_retry(cfg, "append_event", || {
+            session_service.append_event(&cfg.session_id, event.clone())
+        })
+        .await
+        .context("failed to re-append event during restore")?;
     }
     Ok(())
 }
@@ -225,6 +243,8 @@ pub mod shadow {
     use std::path::{Path, PathBuf};
     use std::process::{Command, Output};
 
+    use crate::paths::WORKSPACE_DIR;
+
     /// File change statistics between two checkpoints.
     #[derive(Debug, Clone, Default, Serialize, Deserialize)]
     pub struct FileStats {
@@ -257,7 +277,7 @@ pub mod shadow {
                 bail!("git is required for checkpoints but not installed");
             }
 
-            let shadow_path = workspace.join(".zavora").join("shadow-repo");
+            let shadow_path = workspace.join(WORKSPACE_DIR).join("shadow-repo");
             std::fs::create_dir_all(&shadow_path)
                 .context("failed to create shadow repo directory")?;
 
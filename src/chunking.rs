@@ -0,0 +1,198 @@
+/// Document chunking shared by the local and semantic retrieval backends and
+/// the eval harness.
+///
+/// Every chunk's `source` records where it came from: `#<n>` for paragraphs,
+/// `#<n> <H1 > H2>` for headings and `@<start>-<end>` (char offsets) for
+/// fixed windows.
+use crate::cli::RetrievalChunking;
+use crate::config::RuntimeConfig;
+use crate::retrieval::RetrievedChunk;
+
+pub const DEFAULT_CHUNK_SIZE: usize = 1200;
+pub const DEFAULT_CHUNK_OVERLAP: usize = 200;
+pub const MIN_CHUNK_SIZE: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChunkingStrategy {
+    /// Split on blank lines.
+    Paragraph,
+    /// Split markdown on `#`..`######` headings; each chunk starts with its
+    /// heading line.
+    Heading,
+    /// Windows of at most `size` chars, broken on whitespace, where each
+    /// window repeats up to `overlap` chars of the previous one.
+    Fixed { size: usize, overlap: usize },
+}
+
+impl ChunkingStrategy {
+    /// Clamps size to [`MIN_CHUNK_SIZE`] and overlap to half the size so
+    /// windows always advance.
+    pub fn new(kind: RetrievalChunking, size: usize, overlap: usize) -> Self {
+        match kind {
+            RetrievalChunking::Paragraph => Self::Paragraph,
+            RetrievalChunking::Heading => Self::Heading,
+            RetrievalChunking::Fixed => {
+                let size = size.max(MIN_CHUNK_SIZE);
+                Self::Fixed {
+                    size,
+                    overlap: overlap.min(size / 2),
+                }
+            }
+        }
+    }
+
+    pub fn from_config(cfg: &RuntimeConfig) -> Self {
+        Self::new(
+            cfg.retrieval_chunking,
+            cfg.retrieval_chunk_size,
+            cfg.retrieval_chunk_overlap,
+        )
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            Self::Paragraph => "paragraph".to_string(),
+            Self::Heading => "heading".to_string(),
+            Self::Fixed { size, overlap } => format!("fixed({size},{overlap})"),
+        }
+    }
+}
+
+pub fn is_markdown_path(path: &str) -> bool {
+    let lower = path.to_ascii_lowercase();
+    lower.ends_with(".md") || lower.ends_with(".markdown") || lower.ends_with(".mdx")
+}
+
+/// Split `content` into chunks whose sources start with `source_base`.
+pub fn chunk_document(
+    content: &str,
+    source_base: &str,
+    strategy: ChunkingStrategy,
+) -> Vec<RetrievedChunk> {
+    match strategy {
+        ChunkingStrategy::Paragraph => chunk_paragraphs(content, source_base),
+        ChunkingStrategy::Heading => chunk_headings(content, source_base),
+        ChunkingStrategy::Fixed { size, overlap } => {
+            chunk_fixed(content, source_base, size, overlap)
+        }
+    }
+}
+
+fn chunk(source: String, text: &str) -> RetrievedChunk {
+    RetrievedChunk {
+        source,
+        text: text.to_string(),
+        score: 0,
+    }
+}
+
+fn chunk_paragraphs(content: &str, source_base: &str) -> Vec<RetrievedChunk> {
+    content
+        .split("\n\n")
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+        .enumerate()
+        .map(|(index, text)| chunk(format!("{source_base}#{}", index + 1), text))
+        .collect()
+}
+
+/// Heading level and title for a markdown ATX heading line.
+fn markdown_heading(line: &str) -> Option<(usize, &str)> {
+    let trimmed = line.trim_start();
+    let level = trimmed.chars().take_while(|c| *c == '#').count();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+    let rest = &trimmed[level..];
+    if !rest.is_empty() && !rest.starts_with([' ', '\t']) {
+        return None;
+    }
+    Some((level, rest.trim().trim_end_matches('#').trim()))
+}
+
+fn chunk_headings(content: &str, source_base: &str) -> Vec<RetrievedChunk> {
+    let mut chunks = Vec::new();
+    // (level, title) of the enclosing headings, outermost first.
+    let mut trail: Vec<(usize, String)> = Vec::new();
+    let mut current = String::new();
+    let mut in_fence = false;
+
+    let mut flush = |current: &mut String, trail: &[(usize, String)]| {
+        let text = current.trim();
+        if !text.is_empty() {
+            let path = trail
+                .iter()
+                .map(|(_, title)| title.as_str())
+                .collect::<Vec<&str>>()
+                .join(" > ");
+            let index = chunks.len() + 1;
+            let source = if path.is_empty() {
+                format!("{source_base}#{index}")
+            } else {
+                format!("{source_base}#{index} {path}")
+            };
+            chunks.push(chunk(source, text));
+        }
+        current.clear();
+    };
+
+    for line in content.lines() {
+        if line.trim_start().starts_with("```") || line.trim_start().starts_with("~~~") {
+            in_fence = !in_fence;
+        }
+        if !in_fence && let Some((level, title)) = markdown_heading(line) {
+            flush(&mut current, &trail);
+            trail.retain(|(open_level, _)| *open_level < level);
+            trail.push((level, title.to_string()));
+        }
+        current.push_str(line);
+        current.push('\n');
+    }
+    flush(&mut current, &trail);
+    chunks
+}
+
+fn chunk_fixed(
+    content: &str,
+    source_base: &str,
+    size: usize,
+    overlap: usize,
+) -> Vec<RetrievedChunk> {
+    let chars = content.chars().collect::<Vec<char>>();
+    let size = size.max(1);
+    let overlap = overlap.min(size / 2);
+    let mut chunks = Vec::new();
+    let mut start = 0usize;
+
+    while start < chars.len() {
+        let mut end = (start + size).min(chars.len());
+        if end < chars.len()
+            && !chars[end].is_whitespace()
+            && let Some(space) = (start + 1..end).rev().find(|i| chars[*i].is_whitespace())
+        {
+            end = space;
+        }
+
+        let text = chars[start..end].iter().collect::<String>();
+        if !text.trim().is_empty() {
+            chunks.push(chunk(format!("{source_base}@{start}-{end}"), text.trim()));
+        }
+        if end >= chars.len() {
+            break;
+        }
+
+        // Step back by the overlap, then forward to the next word start so
+        // a window never begins mid-word.
+        let mut next = end.saturating_sub(overlap).max(start + 1);
+        if next < end && !chars[next - 1].is_whitespace() {
+            next = (next..end)
+                .find(|i| chars[*i].is_whitespace())
+                .unwrap_or(end);
+        }
+        while next < chars.len() && chars[next].is_whitespace() {
+            next += 1;
+        }
+        start = next;
+    }
+    chunks
+}
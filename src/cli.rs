@@ -1,7 +1,20 @@
 use clap::{Parser, Subcommand, ValueEnum};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize, Serialize)]
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    ValueEnum,
+    Deserialize,
+    Serialize,
+    JsonSchema,
+)]
 #[serde(rename_all = "lowercase")]
 pub enum Provider {
     Auto,
@@ -11,6 +24,17 @@ pub enum Provider {
     Deepseek,
     Groq,
     Ollama,
+    /// Azure OpenAI: routed by deployment, configured under
+    /// `providers.azure-openai`.
+    #[serde(rename = "azure-openai")]
+    AzureOpenai,
+    /// Any server speaking the OpenAI chat API (vLLM, LM Studio, ...) at
+    /// `providers.openai-compatible.base_url`.
+    #[serde(rename = "openai-compatible")]
+    OpenaiCompatible,
+    /// Scripted responses from `--fake-script`, for tests and demos.
+    #[value(hide = true)]
+    Fake,
 }
 
 #[derive(Debug, Clone, Copy, ValueEnum)]
@@ -22,14 +46,14 @@ pub enum WorkflowMode {
     Graph,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize, Serialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum SessionBackend {
     Memory,
     Sqlite,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize, Serialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum RetrievalBackend {
     Disabled,
@@ -37,7 +61,46 @@ pub enum RetrievalBackend {
     Semantic,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize, Serialize)]
+/// Long-term memory store (`remember`/`recall`, prompt recall).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum MemoryBackend {
+    Disabled,
+    /// Facts in `.zavora/memory/facts.jsonl`.
+    Local,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum RetrievalChunking {
+    Paragraph,
+    Heading,
+    Fixed,
+}
+
+/// What chat retrieves with: the latest input alone, the last few user
+/// turns, or a rolling summary of the conversation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum RetrievalQueryStrategy {
+    Latest,
+    Window,
+    Summary,
+}
+
+/// When a loaded retrieval doc is checked for edits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum RetrievalWatch {
+    /// Chunks are loaded once at startup.
+    Off,
+    /// Before every retrieval.
+    OnDemand,
+    /// At most once per poll interval, for busy servers.
+    Poll,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize, Serialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub enum ToolConfirmationMode {
     Never,
@@ -45,7 +108,52 @@ pub enum ToolConfirmationMode {
     Always,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize, Serialize)]
+/// How chat shows tool results observed in the event stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum ToolResultDisplay {
+    Off,
+    /// One line per result: tool, status, key fields and payload size.
+    Summary,
+    /// Pretty-printed JSON, truncated to `chat_tool_result_max_chars`.
+    Full,
+}
+
+/// Where chat prints the inline `· running tool(...)` / `✓ tool done` lines.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Deserialize, Serialize, JsonSchema,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum ChatToolAnnotations {
+    /// Inline with the answer when stdout is a terminal, on stderr otherwise.
+    #[default]
+    On,
+    Off,
+    /// Always on stderr, even when stdout is a terminal.
+    StderrOnly,
+}
+
+/// Which tool declarations go into each model request.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Deserialize, Serialize, JsonSchema,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum ToolSchemaStrategy {
+    /// Every available tool, on every request.
+    #[default]
+    Full,
+    /// The tools most relevant to the prompt, plus ones mentioned or already used.
+    Relevant,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum UsageBudgetMode {
+    Warn,
+    Enforce,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize, Serialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub enum GuardrailMode {
     Disabled,
@@ -54,6 +162,90 @@ pub enum GuardrailMode {
     Redact,
 }
 
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    ValueEnum,
+    Deserialize,
+    Serialize,
+    JsonSchema,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum GuardrailSeverity {
+    Low,
+    #[default]
+    Medium,
+    High,
+}
+
+/// How control characters and escape sequences in model output are printed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputSanitize {
+    /// Remove them.
+    Strip,
+    /// Render them visibly, e.g. `\x1b[31m`.
+    Escape,
+    /// Print model output unchanged.
+    Off,
+}
+
+/// What happens to retrieved content that looks like a prompt injection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ContentInjectionMode {
+    /// Record it and include the content unchanged.
+    Observe,
+    /// Remove the matching lines and keep the rest.
+    Strip,
+    /// Drop the whole chunk and warn.
+    Quarantine,
+}
+
+/// What `fs_write` and `apply_patch` do with content that looks like it
+/// contains a secret.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum FsWriteSecretPolicy {
+    /// Refuse the write with a `secret_detected` error.
+    Block,
+    /// Write it with each secret replaced by the guardrail redaction marker.
+    Redact,
+    /// Write it unchanged; the finding is still reported.
+    Allow,
+}
+
+/// Whether telemetry payloads are scrubbed before they are written.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TelemetryRedaction {
+    #[default]
+    On,
+    /// Only honored together with `--show-sensitive-config`.
+    Off,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ProgressMode {
+    None,
+    Json,
+    Bar,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum TelemetryExportFormat {
+    Csv,
+    /// Column-oriented JSON (`{"columns": [...], "data": {col: [...]}}`)
+    /// that loads directly into dataframe tools.
+    ParquetLite,
+}
+
 #[derive(Debug, Clone, Copy, ValueEnum)]
 pub enum RalphPhase {
     Prd,
@@ -67,6 +259,8 @@ pub enum ProfileCommands {
     List,
     #[command(about = "Show the active profile's resolved runtime settings")]
     Show,
+    #[command(about = "Persist the default profile for this workspace")]
+    Select { name: String },
 }
 
 #[derive(Debug, Subcommand)]
@@ -85,6 +279,33 @@ pub enum AgentCommands {
     },
 }
 
+#[derive(Debug, Subcommand)]
+pub enum AliasCommands {
+    #[command(about = "Store an alias: zavora-cli alias set <name> -- <args...>")]
+    Set {
+        name: String,
+        #[arg(last = true, required = true, help = "Arguments the alias expands to")]
+        args: Vec<String>,
+    },
+    #[command(about = "List stored aliases and their expansions")]
+    List,
+    #[command(about = "Remove an alias")]
+    Rm { name: String },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum MemoryCommands {
+    #[command(about = "List remembered facts")]
+    List,
+    #[command(about = "Remember a fact")]
+    Add {
+        #[arg(required = true, num_args = 1.., help = "The fact, e.g. \"we use sqlx, not diesel\"")]
+        fact: Vec<String>,
+    },
+    #[command(about = "Forget a fact by id (see `memory list`)")]
+    Rm { id: String },
+}
+
 #[derive(Debug, Subcommand)]
 pub enum McpCommands {
     #[command(about = "List MCP servers configured for the active profile")]
@@ -93,6 +314,12 @@ pub enum McpCommands {
     Discover {
         #[arg(long)]
         server: Option<String>,
+        #[arg(
+            long,
+            requires = "server",
+            help = "Clear the server's circuit breaker and contact it now"
+        )]
+        reset_breaker: bool,
     },
     #[command(about = "Run as an MCP server over stdio, exposing built-in tools")]
     Serve,
@@ -101,13 +328,37 @@ pub enum McpCommands {
 #[derive(Debug, Subcommand)]
 pub enum SessionCommands {
     #[command(about = "List all sessions for the current app/user")]
-    List,
+    List {
+        #[arg(
+            long,
+            help = "Show each session's latest prompt and reply (fetched concurrently, 5s budget)"
+        )]
+        preview: bool,
+    },
     #[command(about = "Show events for a specific session")]
     Show {
         #[arg(long)]
         session_id: Option<String>,
         #[arg(long, default_value_t = 20)]
         recent: usize,
+        #[arg(
+            long,
+            help = "Keep printing new events as they are appended, until Ctrl-C"
+        )]
+        follow: bool,
+        #[arg(
+            long,
+            requires = "follow",
+            help = "With --follow, print NDJSON event records"
+        )]
+        json: bool,
+        #[arg(
+            long,
+            default_value_t = 1000,
+            requires = "follow",
+            help = "Polling interval for --follow in milliseconds"
+        )]
+        poll_interval_ms: u64,
     },
     #[command(about = "Delete a session (requires --force)")]
     Delete {
@@ -116,6 +367,19 @@ pub enum SessionCommands {
         #[arg(long, default_value_t = false)]
         force: bool,
     },
+    #[command(about = "Search all sessions for the current app/user by keyword")]
+    Search {
+        #[arg(long)]
+        query: String,
+        #[arg(long, default_value_t = 10)]
+        limit: usize,
+        #[arg(long, default_value_t = 500)]
+        max_events: usize,
+        #[arg(long, default_value_t = 30)]
+        budget_secs: u64,
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
     #[command(
         about = "Prune old sessions, keeping N most recent (requires --force unless --dry-run)"
     )]
@@ -127,6 +391,25 @@ pub enum SessionCommands {
         #[arg(long, default_value_t = false)]
         force: bool,
     },
+    #[command(about = "Export every session into an archival bundle with a hashed manifest")]
+    ExportAll {
+        #[arg(long, help = "Bundle directory, or .tar.gz file with --compress")]
+        output: String,
+        #[arg(long, default_value_t = false)]
+        compress: bool,
+    },
+    #[command(about = "Re-hash a session bundle and report files that differ from its manifest")]
+    VerifyBundle {
+        #[arg(help = "Bundle directory or .tar.gz file")]
+        path: String,
+    },
+    #[command(
+        about = "Encrypt plaintext events already in the sqlite session database (requires --force)"
+    )]
+    Encrypt {
+        #[arg(long, default_value_t = false)]
+        force: bool,
+    },
 }
 
 #[derive(Debug, Subcommand)]
@@ -145,6 +428,190 @@ pub enum RagCommands {
     },
 }
 
+#[derive(Debug, Subcommand)]
+pub enum RetrievalCommands {
+    #[command(
+        about = "Print retrieval score distributions for sample queries and suggest a min_score"
+    )]
+    Calibrate {
+        #[arg(long, help = "Document to retrieve from (default: retrieval_doc_path)")]
+        doc_path: Option<String>,
+        #[arg(long, help = "Sample queries, one per line (# starts a comment)")]
+        queries_file: String,
+        #[arg(
+            long,
+            default_value_t = 3,
+            help = "Chunks per query the suggested min_score should keep"
+        )]
+        top_n: usize,
+    },
+}
+
+/// Config files `config schema` describes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SchemaTarget {
+    /// Profiles file (`config.toml`).
+    Profiles,
+    /// Agent catalogs (`agents.toml`).
+    Agents,
+    All,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ConfigCommands {
+    #[command(about = "Write JSON Schema for the profiles file and agent catalogs")]
+    Schema {
+        #[arg(long, value_enum, default_value_t = SchemaTarget::All, help = "Schema(s) to export")]
+        target: SchemaTarget,
+        #[arg(
+            long,
+            help = "Directory to write <target>.schema.json files to (default: print one schema)"
+        )]
+        output: Option<String>,
+    },
+    #[command(
+        about = "Set one key in the profiles file, e.g. `config set profiles.work.model gpt-4.1`"
+    )]
+    Set {
+        #[arg(help = "Dotted key path, e.g. profiles.work.guardrail_input_mode")]
+        path: String,
+        #[arg(
+            help = "Value, coerced to the field's type; arrays and tables as JSON-style literals"
+        )]
+        value: String,
+    },
+    #[command(about = "Remove one key from the profiles file")]
+    Unset {
+        #[arg(help = "Dotted key path, e.g. profiles.work.model")]
+        path: String,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum MigrateCommands {
+    #[command(about = "Show the current schema version and pending migrations")]
+    Status,
+    #[command(about = "Apply pending migrations, printing each one")]
+    Up,
+    #[command(about = "Roll back to a schema version (requires --force)")]
+    Down {
+        #[arg(long)]
+        to: i64,
+        #[arg(long, default_value_t = false)]
+        force: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum PolicyCommands {
+    #[command(
+        about = "Show available tools, confirmation requirements, command rules and path denials"
+    )]
+    Show {
+        #[arg(long, help = "Print the policy as JSON")]
+        json: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum CacheCommands {
+    #[command(about = "Delete cached `ask --cache` answers")]
+    Clear {
+        #[arg(
+            long,
+            value_name = "AGE",
+            help = "Only delete entries at least this old (e.g. 30m, 12h, 7d)"
+        )]
+        older_than: Option<String>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ModelsCommands {
+    #[command(about = "List models from the providers' live catalogs (cached for an hour)")]
+    List {
+        #[arg(
+            long,
+            value_enum,
+            help = "Only this provider; all providers with credentials by default"
+        )]
+        provider: Option<Provider>,
+        #[arg(long, help = "Print JSON instead of a table")]
+        json: bool,
+        #[arg(long, help = "Ignore the cache and query the endpoints again")]
+        refresh: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum UsageCommands {
+    #[command(about = "Show today's token/cost usage against the configured daily budget")]
+    Status,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum MaintenanceCommands {
+    #[command(about = "Run the retention pass and report what each policy removed")]
+    Run {
+        #[arg(long, help = "Run even if the pass already ran in the last 24 hours")]
+        now: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum DebugCommands {
+    #[command(
+        about = "Print the system instruction and user content `ask` would send, without calling a model"
+    )]
+    RenderPrompt {
+        #[arg(required_unless_present_any = ["stdin", "template"])]
+        prompt: Vec<String>,
+        #[arg(long, help = "Read the prompt body from stdin (same as passing `-`)")]
+        stdin: bool,
+        #[arg(long, env = "ZAVORA_STDIN_MAX_BYTES", default_value_t = crate::prompt_input::DEFAULT_STDIN_MAX_BYTES)]
+        stdin_max_bytes: usize,
+        #[arg(
+            long,
+            help = "Prompt template file; {{args}} and {{stdin}} are substituted"
+        )]
+        template: Option<String>,
+        #[arg(long, help = "Emit the assembled prompt and its sections as JSON")]
+        json: bool,
+    },
+    #[command(
+        about = "Collect a redacted reproduction bundle (.tar.gz) for one ask/chat run from telemetry"
+    )]
+    Bundle {
+        #[arg(
+            long,
+            conflicts_with = "run_id",
+            required_unless_present = "run_id",
+            help = "Bundle the most recent ask or chat run"
+        )]
+        last_run: bool,
+        #[arg(long, help = "Bundle the run with this telemetry run_id")]
+        run_id: Option<String>,
+        #[arg(
+            long,
+            help = "Prompt to reassemble the user content from (telemetry does not record it)"
+        )]
+        prompt: Option<String>,
+        #[arg(
+            long,
+            value_name = "PATH",
+            help = "Include a chat --script replay file"
+        )]
+        replay: Option<String>,
+        #[arg(long, help = "Bundle path (default: zavora-debug-<run_id>.tar.gz)")]
+        output: Option<String>,
+        #[arg(
+            long,
+            help = "Print the would-be contents instead of writing the bundle"
+        )]
+        review: bool,
+    },
+}
+
 #[derive(Debug, Subcommand)]
 pub enum TelemetryCommands {
     #[command(about = "Summarize telemetry events from a JSONL stream")]
@@ -153,6 +620,26 @@ pub enum TelemetryCommands {
         path: Option<String>,
         #[arg(long, default_value_t = 5000)]
         limit: usize,
+        #[arg(long, help = "Print the report as JSON")]
+        json: bool,
+    },
+    #[command(about = "Export telemetry events as CSV or columnar JSON for spreadsheet analysis")]
+    Export {
+        #[arg(long)]
+        path: Option<String>,
+        #[arg(long)]
+        output: String,
+        #[arg(long, value_enum, default_value_t = TelemetryExportFormat::Csv)]
+        format: TelemetryExportFormat,
+        /// Comma-separated columns to export (default: all known columns)
+        #[arg(long, conflicts_with = "group_by")]
+        columns: Option<String>,
+        /// Aggregate rows by this column instead of exporting raw events
+        #[arg(long)]
+        group_by: Option<String>,
+        /// Comma-separated aggregations for --group-by, e.g. count,avg:duration_ms
+        #[arg(long, default_value = "count", requires = "group_by")]
+        agg: String,
     },
 }
 
@@ -168,6 +655,12 @@ pub enum EvalCommands {
         benchmark_iterations: usize,
         #[arg(long, default_value_t = 0.80)]
         fail_under: f64,
+        #[arg(
+            long,
+            default_value_t = 1,
+            help = "Cases to evaluate concurrently; latency is still measured per case"
+        )]
+        jobs: usize,
     },
 }
 
@@ -186,10 +679,17 @@ pub enum ServerCommands {
 
 const CLI_EXAMPLES: &str = "Examples:\n\
   zavora-cli ask \"Design a Rust CLI with release-based milestones\"\n\
+  git diff | zavora-cli ask \"Review this diff\" -\n\
+  zavora-cli --quiet ask \"Summarize CHANGELOG.md in one line\" > summary.txt\n\
+  zavora-cli ask --compare-with openai:gpt-4.1 \"Explain Rust lifetimes\"\n\
   zavora-cli --provider openai --model gpt-4.1 chat\n\
+  zavora-cli models list --provider openai --json\n\
+  zavora-cli chat --script demo.chat --script-fail-fast\n\
   zavora-cli workflow sequential \"Plan a v0.2.0 rollout\"\n\
   zavora-cli --session-backend sqlite --session-db-url sqlite://.zavora/sessions.db sessions list\n\
   zavora-cli --session-backend sqlite --session-db-url sqlite://.zavora/sessions.db sessions prune --keep 20 --dry-run\n\
+  zavora-cli --session-backend sqlite sessions search --query \"migration plan\" --limit 5\n\
+  zavora-cli --session-backend sqlite sessions encrypt --force\n\
   zavora-cli agents list\n\
   zavora-cli agents show --name coder\n\
   zavora-cli agents select --name reviewer\n\
@@ -200,15 +700,24 @@ const CLI_EXAMPLES: &str = "Examples:\n\
   zavora-cli server serve --host 127.0.0.1 --port 8787\n\
   zavora-cli server a2a-smoke\n\
   zavora-cli telemetry report --limit 2000\n\
+  zavora-cli telemetry export --output telemetry.csv --group-by command --agg count,avg:duration_ms\n\
+  zavora-cli usage status\n\
+  zavora-cli debug render-prompt --json \"Explain Rust lifetimes\"\n\
+  zavora-cli debug bundle --last-run --review\n\
   zavora-cli eval run --benchmark-iterations 200 --fail-under 0.90\n\
+  zavora-cli retrieval calibrate --doc-path docs/guide.md --queries-file queries.txt\n\
+  zavora-cli config schema --target all --output .zavora/schemas\n\
+  zavora-cli init --template rust-cli --var docs_path=docs/guide.md\n\
 \n\
 Switching behavior:\n\
   - Use --agent <name> to select a named agent profile for this invocation.\n\
-  - Use --provider/--model to switch runtime model selection per invocation.\n\
+  - Use --provider/--model to switch runtime model selection per invocation;\n\
+    --model openai/gpt-4.1 sets both.\n\
   - In chat, use /help for command discovery and /provider, /model, /tools, /mcp, /usage, /status.";
 
 #[derive(Debug, Parser)]
 #[command(name = "zavora-cli")]
+#[command(version, long_version = crate::build_info::LONG_VERSION)]
 #[command(about = "Rust CLI agent shell built on ADK-Rust")]
 #[command(after_long_help = CLI_EXAMPLES)]
 pub struct Cli {
@@ -218,15 +727,37 @@ pub struct Cli {
     #[arg(long, env = "ZAVORA_MODEL")]
     pub model: Option<String>,
 
+    #[arg(
+        long,
+        env = "ZAVORA_FAKE_SCRIPT",
+        hide = true,
+        help = "Response script for --provider fake (JSON; for tests and demos)"
+    )]
+    pub fake_script: Option<String>,
+
     #[arg(long, env = "ZAVORA_AGENT")]
     pub agent: Option<String>,
 
-    #[arg(long, env = "ZAVORA_PROFILE", default_value = "default")]
-    pub profile: String,
+    #[arg(
+        long,
+        env = "ZAVORA_PROFILE",
+        help = "Profile to use [default: workspace selection from `profiles select`, else default]"
+    )]
+    pub profile: Option<String>,
 
     #[arg(long, env = "ZAVORA_CONFIG", default_value = ".zavora/config.toml")]
     pub config_path: String,
 
+    #[arg(
+        long,
+        env = "ZAVORA_HOME",
+        help = "Directory for global config and state (default: XDG config/state dirs)"
+    )]
+    pub config_dir: Option<String>,
+
+    #[arg(long, help = "Do not expand command aliases (see `alias list`)")]
+    pub no_alias: bool,
+
     #[arg(long, env = "ZAVORA_APP_NAME")]
     pub app_name: Option<String>,
 
@@ -245,6 +776,69 @@ pub struct Cli {
     #[arg(long, env = "ZAVORA_SHOW_SENSITIVE_CONFIG", default_value_t = false)]
     pub show_sensitive_config: bool,
 
+    #[arg(
+        long,
+        env = "ZAVORA_SHOW_CONTEXT_BUDGET",
+        default_value_t = false,
+        help = "Print how each prompt's bytes were spent (instruction, retrieval, user text) to stderr"
+    )]
+    pub show_context_budget: bool,
+
+    #[arg(
+        long,
+        env = "ZAVORA_NO_PROVENANCE",
+        default_value_t = false,
+        help = "Do not attach provenance (run id, model, sources, tools) to saved answers"
+    )]
+    pub no_provenance: bool,
+
+    #[arg(
+        long,
+        env = "ZAVORA_READ_ONLY",
+        default_value_t = false,
+        help = "Disable every mutating tool and destructive command"
+    )]
+    pub read_only: bool,
+
+    #[arg(
+        long,
+        env = "ZAVORA_STRICT_FINAL_TEXT",
+        default_value_t = false,
+        help = "Fail when the agent ends without final text instead of summarizing the last tool result"
+    )]
+    pub strict_final_text: bool,
+
+    #[arg(
+        long,
+        env = "ZAVORA_NO_PAGER",
+        default_value_t = false,
+        help = "Print long output directly instead of piping it through $PAGER"
+    )]
+    pub no_pager: bool,
+
+    #[arg(
+        long,
+        env = "ZAVORA_NO_ONBOARDING",
+        default_value_t = false,
+        help = "Never offer the first-run setup wizard"
+    )]
+    pub no_onboarding: bool,
+
+    #[arg(
+        long,
+        env = "ZAVORA_NO_MAINTENANCE",
+        default_value_t = false,
+        help = "Skip the daily retention pass at startup (see `maintenance run`)"
+    )]
+    pub no_maintenance: bool,
+
+    #[arg(
+        long,
+        default_value_t = false,
+        help = "Take over a session another chat or server holds open (its turns will interleave)"
+    )]
+    pub steal_session_lock: bool,
+
     #[arg(long, env = "ZAVORA_RETRIEVAL_BACKEND", value_enum)]
     pub retrieval_backend: Option<RetrievalBackend>,
 
@@ -260,6 +854,47 @@ pub struct Cli {
     #[arg(long, env = "ZAVORA_RETRIEVAL_MIN_SCORE")]
     pub retrieval_min_score: Option<usize>,
 
+    #[arg(long, env = "ZAVORA_RETRIEVAL_CHUNKING", value_enum)]
+    pub retrieval_chunking: Option<RetrievalChunking>,
+
+    #[arg(
+        long,
+        env = "ZAVORA_RETRIEVAL_QUERY_STRATEGY",
+        value_enum,
+        help = "Chat retrieval query: latest input, window of user turns, or rolling summary"
+    )]
+    pub retrieval_query_strategy: Option<RetrievalQueryStrategy>,
+
+    #[arg(
+        long,
+        env = "ZAVORA_RETRIEVAL_QUERY_WINDOW",
+        help = "User turns in the window retrieval query (default 3)"
+    )]
+    pub retrieval_query_window: Option<usize>,
+
+    #[arg(
+        long,
+        env = "ZAVORA_RETRIEVAL_WATCH",
+        value_enum,
+        help = "Reload an edited retrieval doc: off, on-demand (before each retrieval) or poll"
+    )]
+    pub retrieval_watch: Option<RetrievalWatch>,
+
+    #[arg(
+        long,
+        env = "ZAVORA_MEMORY_BACKEND",
+        value_enum,
+        help = "Long-term memory: local keeps facts in .zavora/memory/facts.jsonl [default: disabled]"
+    )]
+    pub memory_backend: Option<MemoryBackend>,
+
+    #[arg(
+        long,
+        env = "ZAVORA_MEMORY_RECALL_MAX_FACTS",
+        help = "Most remembered facts injected into a prompt; 0 turns recall off [default: 5]"
+    )]
+    pub memory_recall_max_facts: Option<usize>,
+
     #[arg(long, env = "ZAVORA_TOOL_CONFIRMATION_MODE", value_enum)]
     pub tool_confirmation_mode: Option<ToolConfirmationMode>,
 
@@ -272,33 +907,187 @@ pub struct Cli {
     #[arg(long, env = "ZAVORA_TOOL_TIMEOUT_SECS")]
     pub tool_timeout_secs: Option<u64>,
 
+    #[arg(
+        long,
+        env = "ZAVORA_SLOW_TOOL_WARN_SECS",
+        help = "Warn in chat when a tool runs longer than this many seconds (0 disables)"
+    )]
+    pub slow_tool_warn_secs: Option<u64>,
+
+    #[arg(
+        long,
+        env = "ZAVORA_MAX_RUNTIME_SECS",
+        help = "Abort the whole command after this many seconds (0 disables; chat and servers only when passed here)"
+    )]
+    pub max_runtime_secs: Option<u64>,
+
+    #[arg(
+        long,
+        env = "ZAVORA_CHAT_SHOW_TOOL_RESULTS",
+        value_enum,
+        help = "Show tool results in chat: off, summary (one line each) or full (pretty JSON)"
+    )]
+    pub chat_show_tool_results: Option<ToolResultDisplay>,
+
+    #[arg(
+        long,
+        env = "ZAVORA_TOOL_OUTPUT_MAX_CHARS",
+        help = "Cap on any tool result sent to the model; larger results spill to .zavora/tool-output/ (0 disables)"
+    )]
+    pub tool_output_max_chars: Option<usize>,
+
+    #[arg(
+        long,
+        env = "ZAVORA_MAX_TOOL_CALLS_PER_PROMPT",
+        help = "Tool calls allowed per prompt; further calls return budget_exhausted to the model (0 = unlimited)"
+    )]
+    pub max_tool_calls_per_prompt: Option<u32>,
+
+    #[arg(
+        long,
+        env = "ZAVORA_MOCK_TOOLS",
+        help = "TOML/JSON file of canned tool responses; matching tools return them instead of running"
+    )]
+    pub mock_tools: Option<String>,
+
     #[arg(long, env = "ZAVORA_TOOL_RETRY_ATTEMPTS")]
     pub tool_retry_attempts: Option<u32>,
 
     #[arg(long, env = "ZAVORA_TOOL_RETRY_DELAY_MS")]
     pub tool_retry_delay_ms: Option<u64>,
 
+    #[arg(long, env = "ZAVORA_TOOL_ARG_RETRY_HINT", action = clap::ArgAction::Set)]
+    pub tool_arg_retry_hint: Option<bool>,
+
     #[arg(long, env = "ZAVORA_TELEMETRY_ENABLED", action = clap::ArgAction::Set)]
     pub telemetry_enabled: Option<bool>,
 
     #[arg(long, env = "ZAVORA_TELEMETRY_PATH")]
     pub telemetry_path: Option<String>,
 
+    #[arg(
+        long,
+        env = "ZAVORA_TELEMETRY_EXPORT_DRY_RUN",
+        default_value_t = false,
+        help = "Print the OTLP records telemetry_export would send to stderr instead of sending them"
+    )]
+    pub telemetry_export_dry_run: bool,
+
+    #[arg(
+        long,
+        env = "ZAVORA_OFFLINE",
+        default_value_t = false,
+        help = "Make no optional network calls (telemetry export, remote init templates)"
+    )]
+    pub offline: bool,
+
     #[arg(long, env = "ZAVORA_GUARDRAIL_INPUT_MODE", value_enum)]
     pub guardrail_input_mode: Option<GuardrailMode>,
 
     #[arg(long, env = "ZAVORA_GUARDRAIL_OUTPUT_MODE", value_enum)]
     pub guardrail_output_mode: Option<GuardrailMode>,
 
-    #[arg(long, env = "ZAVORA_GUARDRAIL_TERM")]
+    #[arg(
+        long,
+        env = "ZAVORA_GUARDRAIL_RETRIEVAL_MODE",
+        value_enum,
+        help = "Guardrail mode for retrieved context chunks (default: observe)"
+    )]
+    pub guardrail_retrieval_mode: Option<GuardrailMode>,
+
+    #[arg(
+        long,
+        env = "ZAVORA_CONTENT_INJECTION_MODE",
+        value_enum,
+        help = "Action on retrieved chunks that look like prompt injections (default: observe)"
+    )]
+    pub content_injection_mode: Option<ContentInjectionMode>,
+
+    #[arg(
+        long,
+        env = "ZAVORA_FS_WRITE_SECRET_POLICY",
+        value_enum,
+        help = "Action on file writes whose content looks like a secret (default: block)"
+    )]
+    pub fs_write_secret_policy: Option<FsWriteSecretPolicy>,
+
+    #[arg(
+        long,
+        env = "ZAVORA_OUTPUT_SANITIZE",
+        value_enum,
+        help = "Escape sequences and control characters in model output: strip (default), escape or off"
+    )]
+    pub output_sanitize: Option<OutputSanitize>,
+
+    #[arg(
+        long,
+        env = "ZAVORA_GUARDRAIL_TERM",
+        help = "Guardrail term, optionally with a severity suffix (e.g. ssn:high)"
+    )]
     pub guardrail_term: Vec<String>,
 
     #[arg(long, env = "ZAVORA_GUARDRAIL_REDACT_REPLACEMENT")]
     pub guardrail_redact_replacement: Option<String>,
 
+    #[arg(long, env = "ZAVORA_SERVER_MAX_BODY_BYTES")]
+    pub server_max_body_bytes: Option<usize>,
+
+    #[arg(long, env = "ZAVORA_SERVER_MAX_PROMPT_CHARS")]
+    pub server_max_prompt_chars: Option<usize>,
+
+    #[arg(long, env = "ZAVORA_SERVER_LEGACY_STATUS_CODES", action = clap::ArgAction::Set)]
+    pub server_legacy_status_codes: Option<bool>,
+
+    #[arg(
+        long,
+        env = "ZAVORA_SERVER_PER_USER_MAX_CONCURRENT",
+        help = "Server mode: requests one user_id may have in flight (0 = unlimited)"
+    )]
+    pub server_per_user_max_concurrent: Option<usize>,
+
+    #[arg(
+        long,
+        env = "ZAVORA_SERVER_PER_USER_REQUESTS_PER_MINUTE",
+        help = "Server mode: requests one user_id may start per minute (0 = unlimited)"
+    )]
+    pub server_per_user_requests_per_minute: Option<u32>,
+
+    #[arg(long, env = "ZAVORA_CHAT_NOTIFY_COMMAND")]
+    pub chat_notify_command: Option<String>,
+
+    #[arg(long, env = "ZAVORA_CHAT_NOTIFY_THRESHOLD_SECS")]
+    pub chat_notify_threshold_secs: Option<u64>,
+
+    #[arg(
+        long,
+        env = "ZAVORA_CHAT_IDLE_TIMEOUT_SECS",
+        help = "End chat after this many idle seconds at the prompt (0 disables)"
+    )]
+    pub chat_idle_timeout_secs: Option<u64>,
+
     #[arg(long, env = "RUST_LOG", default_value = "error")]
     pub log_filter: String,
 
+    #[arg(
+        short = 'v',
+        long = "verbose",
+        action = clap::ArgAction::Count,
+        help = "More detail: -v info, -vv debug, -vvv trace (overrides --log-filter)"
+    )]
+    pub verbose: u8,
+
+    #[arg(
+        short = 'q',
+        long,
+        env = "ZAVORA_QUIET",
+        conflicts_with = "verbose",
+        help = "Only print the answer, errors and requested reports; drop banners and notices"
+    )]
+    pub quiet: bool,
+
+    #[arg(long, env = "ZAVORA_PROGRESS", value_enum, default_value_t = ProgressMode::None)]
+    pub progress: ProgressMode,
+
     #[command(subcommand)]
     pub command: Option<Commands>,
 }
@@ -307,11 +1096,118 @@ pub struct Cli {
 pub enum Commands {
     #[command(about = "Run a one-shot prompt and print the final response")]
     Ask {
-        #[arg(required = true)]
+        #[arg(required_unless_present_any = ["stdin", "template", "batch_file"])]
         prompt: Vec<String>,
+        #[arg(long, help = "Read the prompt body from stdin (same as passing `-`)")]
+        stdin: bool,
+        #[arg(long, env = "ZAVORA_STDIN_MAX_BYTES", default_value_t = crate::prompt_input::DEFAULT_STDIN_MAX_BYTES)]
+        stdin_max_bytes: usize,
+        #[arg(
+            long,
+            help = "Prompt template file; {{args}} and {{stdin}} are substituted"
+        )]
+        template: Option<String>,
+        #[arg(
+            long,
+            value_name = "PROVIDER[:MODEL]",
+            help = "Also run the prompt on a second model and print a word-level diff"
+        )]
+        compare_with: Option<String>,
+        #[arg(
+            long,
+            requires = "compare_with",
+            help = "Run both --compare-with models concurrently"
+        )]
+        compare_concurrent: bool,
+        #[arg(
+            long,
+            conflicts_with = "compare_with",
+            help = "Reuse a fresh cached answer for an identical prompt and model (ask_cache_ttl_secs)"
+        )]
+        cache: bool,
+        #[arg(
+            long,
+            value_name = "PATH",
+            conflicts_with_all = ["compare_with", "cache"],
+            help = "Require a JSON answer valid against this schema (type/required/enum/properties/items)"
+        )]
+        json_schema: Option<String>,
+        #[arg(
+            long,
+            requires = "json_schema",
+            default_value_t = crate::json_answer::DEFAULT_JSON_REPAIR_ATTEMPTS,
+            help = "Follow-up turns that feed validation errors back before giving up"
+        )]
+        json_repair_attempts: u32,
+        #[arg(long, requires = "json_schema", help = "Pretty-print the JSON answer")]
+        json_pretty: bool,
+        #[arg(
+            long,
+            value_name = "PATH",
+            conflicts_with = "compare_with",
+            help = "Also save the answer to a file, with provenance (markdown footer or <PATH>.provenance.json)"
+        )]
+        output_file: Option<String>,
+        #[arg(
+            long,
+            conflicts_with = "no_ephemeral",
+            help = "Use a throwaway in-memory session; nothing is saved to the session store"
+        )]
+        ephemeral: bool,
+        #[arg(
+            long,
+            help = "Save to the configured session even with ask_default_ephemeral"
+        )]
+        no_ephemeral: bool,
+        #[arg(
+            long,
+            value_name = "PATH",
+            requires = "batch_output_dir",
+            conflicts_with_all = ["prompt", "stdin", "template", "compare_with", "cache", "json_schema", "output_file"],
+            help = "Run every prompt in a file (one per line, or JSONL {id, prompt} records), each in its own ephemeral session"
+        )]
+        batch_file: Option<String>,
+        #[arg(
+            long,
+            requires = "batch_file",
+            default_value_t = crate::batch::DEFAULT_BATCH_JOBS,
+            help = "Batch prompts run concurrently"
+        )]
+        batch_jobs: usize,
+        #[arg(
+            long,
+            value_name = "DIR",
+            requires = "batch_file",
+            help = "Directory for <id>.md answers and summary.jsonl"
+        )]
+        batch_output_dir: Option<String>,
+        #[arg(
+            long,
+            requires = "batch_file",
+            help = "Stop starting batch prompts after the first failure and exit non-zero"
+        )]
+        batch_fail_fast: bool,
     },
     #[command(about = "Run interactive chat mode")]
-    Chat,
+    Chat {
+        #[arg(
+            long,
+            value_name = "PATH",
+            help = "Replay inputs (prompts and /commands, one per line) from a file instead of stdin"
+        )]
+        script: Option<String>,
+        #[arg(
+            long,
+            requires = "script",
+            help = "Abort the script with a non-zero exit on the first failed input"
+        )]
+        script_fail_fast: bool,
+        #[arg(
+            long,
+            help = "Start in a throwaway in-memory session; /persist saves it later"
+        )]
+        ephemeral: bool,
+    },
     #[command(about = "Run a workflow mode (single, sequential, parallel, loop) for a prompt")]
     Workflow {
         #[arg(value_enum)]
@@ -327,11 +1223,36 @@ pub enum Commands {
         goal: Vec<String>,
         #[arg(long, default_value_t = 3)]
         releases: u32,
+        #[arg(
+            long,
+            default_value_t = 0,
+            help = "Run up to N review-and-revise iterations on the finished plan"
+        )]
+        refine: u32,
+        #[arg(
+            long,
+            default_value_t = 8,
+            help = "Most scope items the reviewer accepts in one release (with --refine)"
+        )]
+        max_scope_items: usize,
     },
     #[command(about = "Validate provider environment and session backend configuration")]
-    Doctor,
-    #[command(about = "Run session backend migrations (sqlite only)")]
-    Migrate,
+    Doctor {
+        #[arg(
+            long,
+            help = "Only validate the profile config and list its resolved include chain"
+        )]
+        check_config: bool,
+        #[arg(long, help = "Query the releases endpoint now and cache the result")]
+        check_update: bool,
+    },
+    #[command(
+        about = "Session backend migrations (sqlite only); bare `migrate` applies pending ones"
+    )]
+    Migrate {
+        #[command(subcommand)]
+        command: Option<MigrateCommands>,
+    },
     #[command(about = "Inspect profile configuration and active resolved profile state")]
     Profiles {
         #[command(subcommand)]
@@ -342,6 +1263,16 @@ pub enum Commands {
         #[command(subcommand)]
         command: AgentCommands,
     },
+    #[command(about = "Define shortcuts that expand to full CLI invocations")]
+    Alias {
+        #[command(subcommand)]
+        command: AliasCommands,
+    },
+    #[command(about = "Curate long-term memory facts (memory_backend = local)")]
+    Memory {
+        #[command(subcommand)]
+        command: MemoryCommands,
+    },
     #[command(about = "Manage MCP toolset registration and discovery")]
     Mcp {
         #[command(subcommand)]
@@ -363,11 +1294,53 @@ pub enum Commands {
         #[command(subcommand)]
         command: RagCommands,
     },
+    #[command(about = "Config file utilities (JSON Schema export, single-key edits)")]
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommands,
+    },
+    #[command(about = "Retrieval tuning utilities")]
+    Retrieval {
+        #[command(subcommand)]
+        command: RetrievalCommands,
+    },
     #[command(about = "Telemetry utilities and reporting")]
     Telemetry {
         #[command(subcommand)]
         command: TelemetryCommands,
     },
+    #[command(about = "Inspect the effective tool policy for the active profile and agent")]
+    Policy {
+        #[command(subcommand)]
+        command: PolicyCommands,
+    },
+    #[command(about = "Maintain the `ask --cache` answer store")]
+    Cache {
+        #[command(subcommand)]
+        command: CacheCommands,
+    },
+    #[command(about = "Provider model catalogs")]
+    Models {
+        #[command(subcommand)]
+        command: ModelsCommands,
+    },
+    #[command(about = "Daily usage counters and budget status")]
+    Usage {
+        #[command(subcommand)]
+        command: UsageCommands,
+    },
+    #[command(
+        about = "Apply the profile's retention policies to telemetry, checkpoints and caches"
+    )]
+    Maintenance {
+        #[command(subcommand)]
+        command: MaintenanceCommands,
+    },
+    #[command(about = "Developer diagnostics (prompt assembly)")]
+    Debug {
+        #[command(subcommand)]
+        command: DebugCommands,
+    },
     #[command(about = "Evaluation harness and benchmark suite")]
     Eval {
         #[command(subcommand)]
@@ -394,6 +1367,35 @@ pub enum Commands {
     },
     #[command(about = "Run the interactive provider setup wizard")]
     Setup,
+    #[command(about = "Bootstrap .zavora/ config and an eval skeleton from a template")]
+    Init {
+        #[arg(
+            long,
+            help = "Built-in template (rust-cli, docs-site), template directory or git URL"
+        )]
+        template: String,
+        #[arg(
+            long = "var",
+            value_name = "NAME=VALUE",
+            help = "Set a template variable (repeatable)"
+        )]
+        vars: Vec<String>,
+        #[arg(long, help = "Overwrite files that already exist")]
+        force: bool,
+        #[arg(long, help = "Leave out the template's evals/ files")]
+        skip_evals: bool,
+        #[arg(
+            long,
+            env = "ZAVORA_OFFLINE",
+            help = "Only allow built-in templates (no git clone)"
+        )]
+        offline: bool,
+    },
+    #[command(about = "Trust this workspace so its .zavora/ config is loaded")]
+    Trust {
+        #[arg(long, help = "Stop trusting this workspace")]
+        revoke: bool,
+    },
     #[command(about = "Initialize LSP configuration for code intelligence")]
     LspInit,
 }
@@ -408,36 +1410,95 @@ pub fn workflow_mode_label(mode: WorkflowMode) -> &'static str {
     }
 }
 
+impl Commands {
+    /// Interactive chat, the command run when none is given.
+    pub fn default_chat() -> Self {
+        Self::Chat {
+            script: None,
+            script_fail_fast: false,
+            ephemeral: false,
+        }
+    }
+}
+
 pub fn command_label(command: &Commands) -> String {
     match command {
         Commands::Ask { .. } => "ask".to_string(),
-        Commands::Chat => "chat".to_string(),
+        Commands::Chat { .. } => "chat".to_string(),
         Commands::Workflow { mode, .. } => format!("workflow.{}", workflow_mode_label(*mode)),
         Commands::ReleasePlan { .. } => "release-plan".to_string(),
-        Commands::Doctor => "doctor".to_string(),
-        Commands::Migrate => "migrate".to_string(),
+        Commands::Doctor { .. } => "doctor".to_string(),
+        Commands::Migrate { command } => match command {
+            None => "migrate".to_string(),
+            Some(MigrateCommands::Status) => "migrate.status".to_string(),
+            Some(MigrateCommands::Up) => "migrate.up".to_string(),
+            Some(MigrateCommands::Down { .. }) => "migrate.down".to_string(),
+        },
         Commands::Profiles { command } => match command {
             ProfileCommands::List => "profiles.list".to_string(),
             ProfileCommands::Show => "profiles.show".to_string(),
+            ProfileCommands::Select { .. } => "profiles.select".to_string(),
         },
         Commands::Agents { command } => match command {
             AgentCommands::List => "agents.list".to_string(),
             AgentCommands::Show { .. } => "agents.show".to_string(),
             AgentCommands::Select { .. } => "agents.select".to_string(),
         },
+        Commands::Alias { command } => match command {
+            AliasCommands::Set { .. } => "alias.set".to_string(),
+            AliasCommands::List => "alias.list".to_string(),
+            AliasCommands::Rm { .. } => "alias.rm".to_string(),
+        },
+        Commands::Memory { command } => match command {
+            MemoryCommands::List => "memory.list".to_string(),
+            MemoryCommands::Add { .. } => "memory.add".to_string(),
+            MemoryCommands::Rm { .. } => "memory.rm".to_string(),
+        },
         Commands::Mcp { command } => match command {
             McpCommands::List => "mcp.list".to_string(),
             McpCommands::Discover { .. } => "mcp.discover".to_string(),
             McpCommands::Serve => "mcp.serve".to_string(),
         },
         Commands::Sessions { command } => match command {
-            SessionCommands::List => "sessions.list".to_string(),
+            SessionCommands::List { .. } => "sessions.list".to_string(),
             SessionCommands::Show { .. } => "sessions.show".to_string(),
             SessionCommands::Delete { .. } => "sessions.delete".to_string(),
+            SessionCommands::Search { .. } => "sessions.search".to_string(),
             SessionCommands::Prune { .. } => "sessions.prune".to_string(),
+            SessionCommands::ExportAll { .. } => "sessions.export-all".to_string(),
+            SessionCommands::VerifyBundle { .. } => "sessions.verify-bundle".to_string(),
+            SessionCommands::Encrypt { .. } => "sessions.encrypt".to_string(),
         },
         Commands::Telemetry { command } => match command {
             TelemetryCommands::Report { .. } => "telemetry.report".to_string(),
+            TelemetryCommands::Export { .. } => "telemetry.export".to_string(),
+        },
+        Commands::Policy { command } => match command {
+            PolicyCommands::Show { .. } => "policy.show".to_string(),
+        },
+        Commands::Cache { command } => match command {
+            CacheCommands::Clear { .. } => "cache.clear".to_string(),
+        },
+        Commands::Models { command } => match command {
+            ModelsCommands::List { .. } => "models.list".to_string(),
+        },
+        Commands::Usage { command } => match command {
+            UsageCommands::Status => "usage.status".to_string(),
+        },
+        Commands::Maintenance { command } => match command {
+            MaintenanceCommands::Run { .. } => "maintenance.run".to_string(),
+        },
+        Commands::Config { command } => match command {
+            ConfigCommands::Schema { .. } => "config.schema".to_string(),
+            ConfigCommands::Set { .. } => "config.set".to_string(),
+            ConfigCommands::Unset { .. } => "config.unset".to_string(),
+        },
+        Commands::Retrieval { command } => match command {
+            RetrievalCommands::Calibrate { .. } => "retrieval.calibrate".to_string(),
+        },
+        Commands::Debug { command } => match command {
+            DebugCommands::RenderPrompt { .. } => "debug.render-prompt".to_string(),
+            DebugCommands::Bundle { .. } => "debug.bundle".to_string(),
         },
         Commands::Skills { command } => match command {
             SkillCommands::List => "skills.list".to_string(),
@@ -454,6 +1515,9 @@ pub fn command_label(command: &Commands) -> String {
             ServerCommands::A2aSmoke => "server.a2a-smoke".to_string(),
         },
         Commands::Setup => "setup".to_string(),
+        Commands::Init { .. } => "init".to_string(),
+        Commands::Trust { revoke: false } => "trust".to_string(),
+        Commands::Trust { revoke: true } => "trust.revoke".to_string(),
         Commands::LspInit => "lsp.init".to_string(),
         Commands::Ralph { .. } => "ralph".to_string(),
     }
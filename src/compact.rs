@@ -14,7 +14,7 @@ use std::sync::Arc;
 
 use crate::config::RuntimeConfig;
 use crate::context::estimate_tokens;
-use crate::session::ensure_session_exists;
+use crate::session::{ensure_session_exists, with_session_busy_retry};
 
 // ---------------------------------------------------------------------------
 // Configuration
@@ -233,14 +233,15 @@ pub async fn compact_session(
     };
 
     // Delete and recreate session
-    session_service
-        .delete(adk_session::DeleteRequest {
+    with_session_busy_retry(cfg, "delete", || {
+        session_service.delete(adk_session::DeleteRequest {
             app_name: cfg.app_name.clone(),
             user_id: cfg.user_id.clone(),
             session_id: cfg.session_id.clone(),
         })
-        .await
-        .context("failed to delete session for compaction")?;
+    })
+    .await
+    .context("failed to delete session for compaction")?;
 
     ensure_session_exists(session_service, cfg).await?;
 
@@ -267,17 +268,19 @@ pub async fn compact_session(
         ..Default::default()
     };
 
-    session_service
-        .append_event(&cfg.session_id, summary_event)
-        .await
-        .context("failed to append compaction summary")?;
+    with_session_busy_retry(cfg, "append_event", || {
+        session_service.append_event(&cfg.session_id, summary_event.clone())
+    })
+    .await
+    .context("failed to append compaction summary")?;
 
     // Re-append kept events
     for event in to_keep {
-        session_service
-            .append_event(&cfg.session_id, event.clone())
-            .await
-            .context("failed to re-append kept event")?;
+        with_session_busy_retry(cfg, "append_event", || {
+            session_service.append_event(&cfg.session_id, event.clone())
+        })
+        .await
+        .context("failed to re-append kept event")?;
     }
 
     let compacted_count = to_compact.len();
@@ -487,20 +490,22 @@ pub async fn apply_snip(
     let removed_count = events.len() - kept.len();
 
     // Delete and recreate with kept events
-    session_service
-        .delete(adk_session::DeleteRequest {
+    with_session_busy_retry(cfg, "delete", || {
+        session_service.delete(adk_session::DeleteRequest {
             app_name: cfg.app_name.clone(),
             user_id: cfg.user_id.clone(),
             session_id: cfg.session_id.clone(),
         })
-        .await?;
+    })
+    .await?;
 
     ensure_session_exists(session_service, cfg).await?;
 
     for event in kept {
-        session_service
-            .append_event(&cfg.session_id, event)
-            .await?;
+        with_session_busy_retry(cfg, "append_event", || {
+            session_service.append_event(&cfg.session_id, event.clone())
+        })
+        .await?;
     }
 
     Ok(removed_count)
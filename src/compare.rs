@@ -0,0 +1,276 @@
+/// Answer diffing for `ask --compare-with`.
+///
+/// Runs one prompt through two models, each in its own ephemeral session so
+/// histories never cross, then renders both answers with a word-level diff.
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use adk_rust::prelude::*;
+use adk_session::SessionService;
+use anyhow::{Context, Result};
+use serde_json::json;
+use similar::{ChangeTag, TextDiff};
+
+use crate::cli::Provider;
+use crate::config::RuntimeConfig;
+use crate::context::estimate_tokens;
+use crate::guardrail::apply_guardrail;
+use crate::provider::parse_provider_name;
+use crate::retrieval::RetrievalService;
+use crate::runner::{
+    ResolvedRuntimeTools, ToolConfirmationSettings, build_runner_with_session_service,
+    build_single_agent_with_tools,
+};
+use crate::sanitize::sanitize_output;
+use crate::streaming::run_prompt_with_retrieval;
+use crate::telemetry::{TelemetrySink, unix_ms_now};
+
+/// A model taking part in a comparison.
+#[derive(Clone)]
+pub struct ComparedModel {
+    pub model: Arc<dyn Llm>,
+    pub provider: Provider,
+    pub model_name: String,
+}
+
+impl ComparedModel {
+    pub fn label(&self) -> String {
+        format!(
+            "{}:{}",
+            format!("{:?}", self.provider).to_ascii_lowercase(),
+            self.model_name
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ComparisonRun {
+    pub label: String,
+    pub session_id: String,
+    pub answer: String,
+    pub latency_ms: u128,
+    pub output_tokens_est: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct WordDiff {
+    pub added: usize,
+    pub removed: usize,
+    pub unchanged: usize,
+    /// Inline diff: removals as `[-...-]`, additions as `{+...+}`.
+    pub rendered: String,
+}
+
+impl WordDiff {
+    /// Share of words common to both answers, in `[0, 1]`.
+    pub fn similarity(&self) -> f64 {
+        let total = self.added + self.removed + self.unchanged * 2;
+        if total == 0 {
+            1.0
+        } else {
+            (self.unchanged * 2) as f64 / total as f64
+        }
+    }
+}
+
+/// Parse a `--compare-with` value: `provider` or `provider:model`.
+pub fn parse_compare_target(spec: &str) -> Result<(Provider, Option<String>)> {
+    let spec = spec.trim();
+    let (provider, model) = match spec.split_once(':') {
+        Some((provider, model)) => (provider, Some(model.trim())),
+        None => (spec, None),
+    };
+    let provider = parse_provider_name(provider.trim())
+        .with_context(|| format!("invalid value for --compare-with '{spec}'"))?;
+    let model = model.filter(|model| !model.is_empty()).map(str::to_string);
+    Ok((provider, model))
+}
+
+/// Config for the comparison model: same profile, different provider/model.
+pub fn compare_runtime_config(
+    cfg: &RuntimeConfig,
+    provider: Provider,
+    model: Option<String>,
+) -> RuntimeConfig {
+    let mut compare_cfg = cfg.clone();
+    compare_cfg.provider = provider;
+    compare_cfg.model = model;
+    compare_cfg
+}
+
+/// Word-level diff between two answers.
+pub fn word_diff(left: &str, right: &str) -> WordDiff {
+    let diff = TextDiff::from_words(left, right);
+    let mut out = WordDiff::default();
+    let mut open: Option<ChangeTag> = None;
+
+    for change in diff.iter_all_changes() {
+        let tag = change.tag();
+        let value = change.value();
+        let is_word = !value.trim().is_empty();
+        if is_word {
+            match tag {
+                ChangeTag::Equal => out.unchanged += 1,
+                ChangeTag::Delete => out.removed += 1,
+                ChangeTag::Insert => out.added += 1,
+            }
+        }
+
+        if open != Some(tag) {
+            close_marker(&mut out.rendered, open);
+            open = Some(tag);
+            match tag {
+                ChangeTag::Delete => out.rendered.push_str("[-"),
+                ChangeTag::Insert => out.rendered.push_str("{+"),
+                ChangeTag::Equal => {}
+            }
+        }
+        out.rendered.push_str(value);
+    }
+    close_marker(&mut out.rendered, open);
+    out
+}
+
+fn close_marker(rendered: &mut String, tag: Option<ChangeTag>) {
+    match tag {
+        Some(ChangeTag::Delete) => rendered.push_str("-]"),
+        Some(ChangeTag::Insert) => rendered.push_str("+}"),
+        _ => {}
+    }
+}
+
+pub fn render_comparison_report(left: &ComparisonRun, right: &ComparisonRun) -> String {
+    let diff = word_diff(&left.answer, &right.answer);
+    let mut out = String::new();
+    for (tag, run) in [("A", left), ("B", right)] {
+        out.push_str(&format!(
+            "=== [{tag}] {} ({}ms, ~{} output tokens) ===\n{}\n\n",
+            run.label, run.latency_ms, run.output_tokens_est, run.answer
+        ));
+    }
+    out.push_str(&format!(
+        "=== Diff [A] → [B] (word-level) ===\n+{} added, -{} removed, {} unchanged (similarity {:.2})\n{}\n",
+        diff.added,
+        diff.removed,
+        diff.unchanged,
+        diff.similarity(),
+        diff.rendered
+    ));
+    out
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn run_compared_model(
+    cfg: &RuntimeConfig,
+    compared: &ComparedModel,
+    session_service: Arc<dyn SessionService>,
+    session_id: String,
+    runtime_tools: &ResolvedRuntimeTools,
+    tool_confirmation: &ToolConfirmationSettings,
+    prompt: &str,
+    retrieval: &dyn RetrievalService,
+    telemetry: &TelemetrySink,
+) -> Result<ComparisonRun> {
+    let mut run_cfg = cfg.clone();
+    run_cfg.session_id = session_id.clone();
+    let label = compared.label();
+
+    let agent = build_single_agent_with_tools(
+        compared.model.clone(),
+        &runtime_tools.tools,
+        tool_confirmation.policy.clone(),
+        Duration::from_secs(cfg.tool_timeout_secs),
+        Some(&run_cfg),
+    )?;
+    let runner = build_runner_with_session_service(
+        agent,
+        &run_cfg,
+        session_service,
+        Some(tool_confirmation.run_config.clone()),
+    )
+    .await?;
+
+    let started = Instant::now();
+    let answer = run_prompt_with_retrieval(&runner, &run_cfg, prompt, retrieval, telemetry)
+        .await
+        .with_context(|| format!("comparison run failed for '{label}'"))?;
+    let latency_ms = started.elapsed().as_millis();
+    let answer = apply_guardrail(
+        &run_cfg,
+        telemetry,
+        "output",
+        run_cfg.guardrail_output_mode,
+        &answer,
+    )?;
+    let answer = sanitize_output(&run_cfg, &answer);
+
+    Ok(ComparisonRun {
+        label,
+        session_id,
+        output_tokens_est: estimate_tokens(answer.chars().count()),
+        answer,
+        latency_ms,
+    })
+}
+
+/// Run `prompt` (already input-guarded) through both models, each in the
+/// matching ephemeral `session_services` entry, and emit `model.compared`.
+#[allow(clippy::too_many_arguments)]
+pub async fn run_model_comparison(
+    cfg: &RuntimeConfig,
+    models: [&ComparedModel; 2],
+    session_services: [Arc<dyn SessionService>; 2],
+    runtime_tools: &ResolvedRuntimeTools,
+    tool_confirmation: &ToolConfirmationSettings,
+    prompt: &str,
+    retrieval: &dyn RetrievalService,
+    telemetry: &TelemetrySink,
+    concurrent: bool,
+) -> Result<(ComparisonRun, ComparisonRun)> {
+    let stamp = unix_ms_now();
+    let [left_service, right_service] = session_services;
+    let left = run_compared_model(
+        cfg,
+        models[0],
+        left_service,
+        format!("compare-a-{stamp}"),
+        runtime_tools,
+        tool_confirmation,
+        prompt,
+        retrieval,
+        telemetry,
+    );
+    let right = run_compared_model(
+        cfg,
+        models[1],
+        right_service,
+        format!("compare-b-{stamp}"),
+        runtime_tools,
+        tool_confirmation,
+        prompt,
+        retrieval,
+        telemetry,
+    );
+
+    let (left, right) = if concurrent {
+        let (left, right) = tokio::join!(left, right);
+        (left?, right?)
+    } else {
+        let left = left.await?;
+        (left, right.await?)
+    };
+
+    telemetry.emit(
+        "model.compared",
+        json!({
+            "concurrent": concurrent,
+            "similarity": word_diff(&left.answer, &right.answer).similarity(),
+            "models": [
+                { "model": left.label, "latency_ms": left.latency_ms, "output_tokens_est": left.output_tokens_est },
+                { "model": right.label, "latency_ms": right.latency_ms, "output_tokens_est": right.output_tokens_est }
+            ]
+        }),
+    );
+
+    Ok((left, right))
+}
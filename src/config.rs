@@ -1,16 +1,37 @@
-use std::collections::{BTreeSet, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use anyhow::{Context, Result};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use crate::cli::*;
-use crate::guardrail::default_guardrail_terms;
+use crate::file_lock::{DEFAULT_FILE_LOCK_TIMEOUT, acquire_file_lock};
+use crate::guardrail::{
+    GuardrailMatcher, GuardrailMatcherCache, default_guardrail_terms, parse_guardrail_severity,
+    parse_guardrail_term,
+};
+use crate::paths::ZavoraPaths;
+use crate::profile_inheritance::resolve_profile;
+use crate::provider::{split_model_shorthand, validate_model_for_provider};
+use crate::starters::{AgentStarter, validate_agent_starters};
+use crate::telemetry::{DEFAULT_TELEMETRY_REDACT_FIELDS, TelemetrySink};
+use crate::tools::budget::{ToolBudgets, ToolCallCounter};
+use crate::tools::execute_bash::{ExecuteBashOptions, ExecuteBashSandbox};
+use crate::tools::mock::{MockToolSet, load_mock_tools};
+use crate::tools::secret_scan::SecretScanOptions;
+use crate::verbosity::Verbosity;
+
+/// Default request body cap for `server serve` (1 MiB).
+pub const DEFAULT_SERVER_MAX_BODY_BYTES: usize = 1024 * 1024;
 
 #[derive(Debug, Clone)]
 pub struct RuntimeConfig {
     pub profile: String,
     pub config_path: String,
+    /// Resolved workspace, config and state locations.
+    pub paths: ZavoraPaths,
     pub agent_name: String,
     pub agent_source: AgentSource,
     pub agent_description: Option<String>,
@@ -27,43 +48,200 @@ pub struct RuntimeConfig {
     pub session_id: String,
     pub session_backend: SessionBackend,
     pub session_db_url: String,
+    /// Env var holding the key that encrypts sqlite session content.
+    pub session_encryption_key_env: Option<String>,
+    /// Retries for session calls that hit a locked/busy sqlite database.
+    pub session_busy_retry_attempts: u32,
+    /// Cap on the exponential backoff between those retries.
+    pub session_busy_retry_max_ms: u64,
     pub show_sensitive_config: bool,
+    /// Response script for `--provider fake`.
+    pub fake_script: Option<String>,
+    /// Print the per-prompt context budget table to stderr.
+    pub show_context_budget: bool,
+    /// Attach provenance to saved answers and transcripts (`--no-provenance`
+    /// turns it off).
+    pub provenance: bool,
+    /// Read-only mode: mutating tools and destructive commands are refused.
+    pub read_only: bool,
+    /// Fail a prompt that ends without final text instead of falling back
+    /// to the last tool result.
+    pub strict_final_text: bool,
+    /// Page long `ask` answers and `sessions show` output on a TTY.
+    pub pager: bool,
     pub retrieval_backend: RetrievalBackend,
     pub retrieval_doc_path: Option<String>,
     pub retrieval_max_chunks: usize,
     pub retrieval_max_chars: usize,
     pub retrieval_min_score: usize,
+    pub retrieval_chunking: RetrievalChunking,
+    /// Chat only; `ask` and workflows always retrieve with the latest input.
+    pub retrieval_query_strategy: RetrievalQueryStrategy,
+    /// User turns, current input included, in a `window` query.
+    pub retrieval_query_window: usize,
+    pub retrieval_chunk_size: usize,
+    pub retrieval_chunk_overlap: usize,
+    pub retrieval_watch: RetrievalWatch,
+    pub memory_backend: MemoryBackend,
+    /// Remembered facts injected per prompt; 0 disables recall.
+    pub memory_recall_max_facts: usize,
     pub tool_confirmation_mode: ToolConfirmationMode,
     pub require_confirm_tool: Vec<String>,
     pub approve_tool: Vec<String>,
     pub tool_timeout_secs: u64,
+    /// Seconds before chat prints a "still running" notice for a tool; 0 disables.
+    pub slow_tool_warn_secs: u64,
+    /// Hard ceiling on one command, in seconds; 0 disables. See `watchdog`.
+    pub max_runtime_secs: u64,
+    /// `max_runtime_secs` came from `--max-runtime-secs`; only then does it
+    /// bind chat and servers.
+    pub max_runtime_from_cli: bool,
+    pub chat_show_tool_results: ToolResultDisplay,
+    /// Char limit for `chat_show_tool_results = "full"`.
+    pub chat_tool_result_max_chars: usize,
+    pub chat_tool_annotations: ChatToolAnnotations,
+    pub tool_schema_strategy: ToolSchemaStrategy,
+    /// Declarations picked by relevance in `relevant` mode (at least 1).
+    pub tool_schema_top_k: usize,
+    /// Cap on the JSON of any tool result sent to the model; 0 disables.
+    pub tool_output_max_chars: usize,
+    /// Per-prompt tool call limits; enforced by `BudgetedTool`.
+    pub tool_budgets: ToolBudgets,
+    /// Calls made in the current prompt; re-armed before each runner
+    /// invocation. Clones share the count.
+    pub tool_call_counter: ToolCallCounter,
     pub tool_retry_attempts: u32,
     pub tool_retry_delay_ms: u64,
+    pub tool_arg_retry_hint: bool,
+    /// Consecutive discovery failures that open an MCP server's circuit
+    /// breaker; 0 disables the breaker.
+    pub mcp_failure_threshold: u32,
+    /// Longest an open breaker waits before the next probe.
+    pub mcp_breaker_max_secs: u64,
     pub telemetry_enabled: bool,
     pub telemetry_path: String,
+    /// Attach the allowlisted environment block to `command.started`.
+    pub telemetry_capture_environment: bool,
+    /// Payload fields scrubbed by the guardrail and sqlite redactors before
+    /// an event is written; see `TelemetrySink::emit`.
+    pub telemetry_redact_fields: Vec<String>,
+    pub telemetry_redaction: TelemetryRedaction,
+    /// OTLP log export of every written event; see `telemetry_export`.
+    pub telemetry_export: Option<TelemetryExportConfig>,
+    /// Print export records to stderr instead of sending them.
+    pub telemetry_export_dry_run: bool,
+    /// `--offline`: skip optional network calls.
+    pub offline: bool,
     pub guardrail_input_mode: GuardrailMode,
     pub guardrail_output_mode: GuardrailMode,
+    /// Mode for retrieved context, scanned separately from the user prompt.
+    /// Severity actions do not apply to it.
+    pub guardrail_retrieval_mode: GuardrailMode,
+    /// Screening of retrieved chunks for injected instructions; see
+    /// [`crate::content_injection`].
+    pub content_injection_mode: ContentInjectionMode,
+    /// Extra phrases screened for on top of the built-in heuristics.
+    pub content_injection_patterns: Vec<String>,
+    /// Secret scan of `fs_write`/`apply_patch` content; see
+    /// [`crate::tools::secret_scan`].
+    pub fs_write_secret_policy: FsWriteSecretPolicy,
+    /// Wildcards for files or tokens the secret heuristics let through.
+    pub fs_write_secret_allowlist: Vec<String>,
+    /// Applied to model text after guardrails, before it is printed or saved.
+    pub output_sanitize: OutputSanitize,
     pub guardrail_terms: Vec<String>,
+    /// Severity per term, keyed by the lowercased term; missing terms are
+    /// medium.
+    pub guardrail_term_severities: BTreeMap<String, GuardrailSeverity>,
+    /// Severity → mode overrides for the blanket input/output modes.
+    pub guardrail_severity_actions: BTreeMap<GuardrailSeverity, GuardrailMode>,
     pub guardrail_redact_replacement: String,
+    pub guardrail_matcher_cache: GuardrailMatcherCache,
     pub mcp_servers: Vec<McpServerConfig>,
     pub permission_rules: crate::tool_policy::PermissionRules,
+    pub retention: crate::maintenance::RetentionPolicy,
     pub max_prompt_chars: usize,
     pub server_runner_cache_max: usize,
+    pub server_max_body_bytes: usize,
+    pub server_max_prompt_chars: usize,
+    pub server_legacy_status_codes: bool,
+    /// Requests one user may have in flight in server mode; 0 is unlimited.
+    pub server_per_user_max_concurrent: usize,
+    /// Requests one user may start per minute in server mode; 0 is unlimited.
+    pub server_per_user_requests_per_minute: u32,
+    pub chat_notify_command: Option<String>,
+    pub chat_notify_threshold_secs: u64,
+    pub chat_notify_enabled: bool,
+    /// Seconds of prompt inactivity before chat saves and exits; 0 disables.
+    pub chat_idle_timeout_secs: u64,
+    /// Directory for idle-exit transcripts; empty disables the auto-save.
+    pub chat_idle_transcript_dir: String,
+    /// sqlite URL a memory-backend session is flushed to on idle exit;
+    /// empty disables the snapshot.
+    pub chat_idle_snapshot_db_url: String,
+    /// `-v` count; gates tracing scope and extra CLI detail.
+    pub verbosity: Verbosity,
+    pub daily_token_budget: Option<u64>,
+    pub daily_cost_budget: Option<f64>,
+    pub usage_budget_mode: UsageBudgetMode,
+    pub usage_cost_per_1k_tokens: f64,
+    pub usage_dir: String,
+    /// Daily check for newer releases; see `update_check`.
+    pub update_check: bool,
+    pub update_check_url: String,
+    /// Seconds an `ask --cache` answer stays fresh.
+    pub ask_cache_ttl_secs: u64,
+    /// `ask` runs as if `--ephemeral` were passed.
+    pub ask_default_ephemeral: bool,
+    /// Set for an ephemeral run: the configured session backend, which
+    /// `/persist` copies to. The run itself uses the memory backend.
+    pub ephemeral: Option<SessionBackend>,
+    /// Wrapper template `execute_bash` commands run inside.
+    pub execute_bash_sandbox: Option<String>,
+    /// Add structured summaries to `execute_bash` results for known commands.
+    pub execute_bash_summarize: bool,
+    /// Canned tool responses from `--mock-tools`.
+    pub mock_tools: Option<Arc<MockToolSet>>,
+    /// Gateway/proxy settings per provider, applied in `resolve_model`.
+    pub provider_overrides: BTreeMap<Provider, ProviderOverrideConfig>,
     pub auto_compact_enabled: bool,
     pub compact_interval: u32,
     pub compact_overlap: u32,
     pub compaction_threshold: f64,
     pub compaction_target: f64,
+    /// Context window fraction above which chat leaves the oldest exchanges
+    /// out of a turn (see [`crate::history_trim`]); 0 disables it.
+    pub history_trim_threshold: f64,
 }
 
-#[derive(Debug, Default, Deserialize, Serialize)]
+impl RuntimeConfig {
+    /// Guardrail matcher for the configured terms, built once and reused.
+    pub fn guardrail_matcher(&self) -> Arc<GuardrailMatcher> {
+        self.guardrail_matcher_cache.get(&self.guardrail_terms)
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct ProfilesFile {
+    /// Other profile files merged before this one, relative to its directory.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<String>,
     #[serde(default)]
     pub profiles: HashMap<String, ProfileConfig>,
+    /// Files merged to build this config, in load order (root file last).
+    #[serde(skip)]
+    pub include_chain: Vec<PathBuf>,
+    /// Last file that defined each profile.
+    #[serde(skip)]
+    pub profile_sources: HashMap<String, PathBuf>,
+    /// Profiles that failed to parse, with the reason. They are left out of
+    /// `profiles` so one bad entry only breaks commands that select it.
+    #[serde(skip)]
+    pub broken_profiles: BTreeMap<String, String>,
 }
 
-#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+#[derive(Debug, Default, Clone, Deserialize, Serialize, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct ProfileConfig {
     pub provider: Option<Provider>,
@@ -75,38 +253,185 @@ pub struct ProfileConfig {
     pub session_id: Option<String>,
     pub session_backend: Option<SessionBackend>,
     pub session_db_url: Option<String>,
+    pub session_encryption_key_env: Option<String>,
+    pub session_busy_retry_attempts: Option<u32>,
+    pub session_busy_retry_max_ms: Option<u64>,
+    pub read_only: Option<bool>,
+    pub strict_final_text: Option<bool>,
+    pub pager: Option<bool>,
     pub retrieval_backend: Option<RetrievalBackend>,
     pub retrieval_doc_path: Option<String>,
     pub retrieval_max_chunks: Option<usize>,
     pub retrieval_max_chars: Option<usize>,
     pub retrieval_min_score: Option<usize>,
+    pub retrieval_chunking: Option<RetrievalChunking>,
+    pub retrieval_query_strategy: Option<RetrievalQueryStrategy>,
+    pub retrieval_query_window: Option<usize>,
+    pub retrieval_chunk_size: Option<usize>,
+    pub retrieval_chunk_overlap: Option<usize>,
+    pub retrieval_watch: Option<RetrievalWatch>,
+    /// Parent profile whose fields this one starts from.
+    pub extends: Option<String>,
+    /// Append list fields to the parent's instead of replacing them.
+    pub merge_lists: Option<bool>,
+    pub memory_backend: Option<MemoryBackend>,
+    pub memory_recall_max_facts: Option<usize>,
     pub tool_confirmation_mode: Option<ToolConfirmationMode>,
     #[serde(default)]
     pub require_confirm_tool: Vec<String>,
     #[serde(default)]
     pub approve_tool: Vec<String>,
     pub tool_timeout_secs: Option<u64>,
+    pub slow_tool_warn_secs: Option<u64>,
+    pub max_runtime_secs: Option<u64>,
+    pub chat_show_tool_results: Option<ToolResultDisplay>,
+    pub chat_tool_result_max_chars: Option<usize>,
+    pub chat_tool_annotations: Option<ChatToolAnnotations>,
+    pub tool_schema_strategy: Option<ToolSchemaStrategy>,
+    pub tool_schema_top_k: Option<usize>,
+    pub tool_output_max_chars: Option<usize>,
+    pub max_tool_calls_per_prompt: Option<u32>,
+    /// Tool name or `*` pattern → calls allowed per prompt.
+    #[serde(default)]
+    pub tool_call_budgets: BTreeMap<String, u32>,
     pub tool_retry_attempts: Option<u32>,
     pub tool_retry_delay_ms: Option<u64>,
+    pub tool_arg_retry_hint: Option<bool>,
+    pub mcp_failure_threshold: Option<u32>,
+    pub mcp_breaker_max_secs: Option<u64>,
     pub telemetry_enabled: Option<bool>,
     pub telemetry_path: Option<String>,
+    pub telemetry_capture_environment: Option<bool>,
+    pub telemetry_redact_fields: Option<Vec<String>>,
+    pub telemetry_redaction: Option<TelemetryRedaction>,
+    pub telemetry_export: Option<TelemetryExportConfig>,
     pub guardrail_input_mode: Option<GuardrailMode>,
     pub guardrail_output_mode: Option<GuardrailMode>,
+    pub guardrail_retrieval_mode: Option<GuardrailMode>,
+    pub content_injection_mode: Option<ContentInjectionMode>,
     #[serde(default)]
-    pub guardrail_terms: Vec<String>,
+    pub content_injection_patterns: Vec<String>,
+    pub fs_write_secret_policy: Option<FsWriteSecretPolicy>,
+    #[serde(default)]
+    pub fs_write_secret_allowlist: Vec<String>,
+    pub output_sanitize: Option<OutputSanitize>,
+    #[serde(default)]
+    pub guardrail_terms: Vec<GuardrailTermEntry>,
+    /// `low|medium|high` → guardrail mode.
+    #[serde(default)]
+    pub guardrail_severity_actions: BTreeMap<String, GuardrailMode>,
     pub guardrail_redact_replacement: Option<String>,
+    pub server_max_body_bytes: Option<usize>,
+    pub server_max_prompt_chars: Option<usize>,
+    pub server_legacy_status_codes: Option<bool>,
+    pub server_per_user_max_concurrent: Option<usize>,
+    pub server_per_user_requests_per_minute: Option<u32>,
+    pub chat_notify_command: Option<String>,
+    pub chat_notify_threshold_secs: Option<u64>,
+    pub chat_idle_timeout_secs: Option<u64>,
+    pub chat_idle_transcript_dir: Option<String>,
+    pub chat_idle_snapshot_db_url: Option<String>,
+    pub daily_token_budget: Option<u64>,
+    pub daily_cost_budget: Option<f64>,
+    pub usage_budget_mode: Option<UsageBudgetMode>,
+    pub usage_cost_per_1k_tokens: Option<f64>,
+    pub update_check: Option<bool>,
+    pub update_check_url: Option<String>,
+    pub ask_cache_ttl_secs: Option<u64>,
+    pub ask_default_ephemeral: Option<bool>,
+    pub execute_bash_sandbox: Option<String>,
+    pub execute_bash_summarize: Option<bool>,
+    /// `[profiles.<name>.providers.<provider>]`.
+    #[serde(default)]
+    pub providers: BTreeMap<Provider, ProviderOverrideConfig>,
     #[serde(default)]
     pub mcp_servers: Vec<McpServerConfig>,
+    /// Extra agent catalog files or directories, loaded before
+    /// `ZAVORA_AGENT_PATH` entries.
+    #[serde(default)]
+    pub agent_catalog_paths: Vec<String>,
     #[serde(default)]
     pub permission_rules: crate::tool_policy::PermissionRules,
+    /// `[retention]`: startup maintenance policies (see [`crate::maintenance`]).
+    #[serde(default)]
+    pub retention: crate::maintenance::RetentionPolicy,
     pub compaction_threshold: Option<f64>,
     pub compaction_target: Option<f64>,
+    pub history_trim_threshold: Option<f64>,
+}
+
+/// A profile guardrail term: either `"term"` / `"term:severity"` or
+/// `{ term = "...", severity = "high" }`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(untagged)]
+pub enum GuardrailTermEntry {
+    Inline(String),
+    Structured {
+        term: String,
+        #[serde(default)]
+        severity: GuardrailSeverity,
+    },
+}
+
+impl GuardrailTermEntry {
+    pub fn resolve(&self) -> (String, GuardrailSeverity) {
+        match self {
+            Self::Inline(raw) => parse_guardrail_term(raw),
+            Self::Structured { term, severity } => (term.trim().to_string(), *severity),
+        }
+    }
+}
+
+/// Profile terms then CLI terms, deduplicated, with the highest severity
+/// seen for each term (case-insensitively).
+fn resolve_guardrail_terms(
+    profile_terms: &[GuardrailTermEntry],
+    cli_terms: &[String],
+) -> (Vec<String>, BTreeMap<String, GuardrailSeverity>) {
+    let mut terms = Vec::<String>::new();
+    let mut severities = BTreeMap::<String, GuardrailSeverity>::new();
+    let entries = profile_terms
+        .iter()
+        .map(GuardrailTermEntry::resolve)
+        .chain(cli_terms.iter().map(|raw| parse_guardrail_term(raw)));
+    for (term, severity) in entries {
+        if term.is_empty() {
+            continue;
+        }
+        if !terms.contains(&term) {
+            terms.push(term.clone());
+        }
+        let slot = severities
+            .entry(term.to_ascii_lowercase())
+            .or_insert(severity);
+        *slot = (*slot).max(severity);
+    }
+    (terms, severities)
+}
+
+fn resolve_guardrail_severity_actions(
+    actions: &BTreeMap<String, GuardrailMode>,
+) -> Result<BTreeMap<GuardrailSeverity, GuardrailMode>> {
+    actions
+        .iter()
+        .map(|(severity, mode)| {
+            parse_guardrail_severity(severity)
+                .map(|severity| (severity, *mode))
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "invalid guardrail_severity_actions key '{severity}' (expected low, medium or high)"
+                    )
+                })
+        })
+        .collect()
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AgentSource {
     Implicit,
     Global,
+    /// `agent_catalog_paths` / `ZAVORA_AGENT_PATH`.
+    AgentPath,
     Local,
 }
 
@@ -115,6 +440,7 @@ impl AgentSource {
         match self {
             AgentSource::Implicit => "implicit",
             AgentSource::Global => "global",
+            AgentSource::AgentPath => "agent-path",
             AgentSource::Local => "local",
         }
     }
@@ -122,7 +448,7 @@ impl AgentSource {
 
 use crate::hooks::HookConfig;
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Default, Clone, Deserialize, Serialize, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct AgentFileConfig {
     pub description: Option<String>,
@@ -138,9 +464,16 @@ pub struct AgentFileConfig {
     pub deny_tools: Vec<String>,
     #[serde(default)]
     pub hooks: HashMap<String, Vec<HookConfig>>,
+    /// Merged over the profile's `mcp_servers` when this agent is active.
+    #[serde(default)]
+    pub mcp_servers: Vec<McpServerConfig>,
+    /// Prompt templates offered when chat starts with this agent; see
+    /// [`crate::starters`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub starters: Option<Vec<AgentStarter>>,
 }
 
-#[derive(Debug, Default, Deserialize)]
+#[derive(Debug, Default, Deserialize, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct AgentCatalogFile {
     #[serde(default)]
@@ -153,21 +486,142 @@ pub struct AgentSelectionFile {
     pub agent: Option<String>,
 }
 
+/// `.zavora/profile-selection.toml`, written by `profiles select`.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProfileSelectionFile {
+    pub profile: Option<String>,
+}
+
+pub const DEFAULT_PROFILE: &str = "default";
+
 #[derive(Debug, Clone)]
 pub struct ResolvedAgent {
     pub name: String,
     pub source: AgentSource,
+    /// Catalog file the definition was read from; `None` for built-ins.
+    pub origin: Option<PathBuf>,
     pub config: AgentFileConfig,
 }
 
 #[derive(Debug, Clone)]
 pub struct AgentPaths {
-    pub local_catalog: PathBuf,
+    /// `None` when the workspace is not trusted.
+    pub local_catalog: Option<PathBuf>,
     pub global_catalog: Option<PathBuf>,
+    /// Catalog files or directories merged between global and local, in
+    /// order; later entries override earlier ones by agent name.
+    pub extra_catalogs: Vec<PathBuf>,
     pub selection_file: PathBuf,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+pub const AGENT_PATH_ENV: &str = "ZAVORA_AGENT_PATH";
+
+/// Where a resolved MCP server definition came from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum McpServerSource {
+    #[default]
+    Profile,
+    Agent,
+}
+
+impl McpServerSource {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Profile => "profile",
+            Self::Agent => "agent",
+        }
+    }
+}
+
+/// Wire format of `[profiles.<name>.telemetry_export]`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum TelemetryStreamFormat {
+    /// OTLP/HTTP JSON logs (`ExportLogsServiceRequest`).
+    #[default]
+    OtlpJson,
+}
+
+/// `[profiles.<name>.telemetry_export]`: where written telemetry events are
+/// also sent. Header values usually carry credentials, so `Debug` only shows
+/// header names.
+#[derive(Default, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct TelemetryExportConfig {
+    #[serde(default)]
+    pub format: TelemetryStreamFormat,
+    /// Full logs URL, e.g. `http://localhost:4318/v1/logs`.
+    pub endpoint: String,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+}
+
+impl std::fmt::Debug for TelemetryExportConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TelemetryExportConfig")
+            .field("format", &self.format)
+            .field("endpoint", &self.endpoint)
+            .field("headers", &self.headers.keys().collect::<Vec<&String>>())
+            .finish()
+    }
+}
+
+fn validate_telemetry_export(
+    profile_name: &str,
+    export: TelemetryExportConfig,
+) -> Result<TelemetryExportConfig> {
+    let endpoint = export.endpoint.trim();
+    if !(endpoint.starts_with("http://") || endpoint.starts_with("https://")) {
+        return Err(anyhow::anyhow!(
+            "profile '{profile_name}': telemetry_export.endpoint must be an http(s) URL, got '{endpoint}'"
+        ));
+    }
+    Ok(TelemetryExportConfig {
+        endpoint: endpoint.to_string(),
+        ..export
+    })
+}
+
+/// Base URL and extra request headers for one provider's client, for
+/// deployments that route model traffic through a gateway or proxy. Header
+/// values usually carry credentials (`${VAR}` references are expanded at
+/// load time), so `Debug` only shows header names.
+///
+/// `azure-openai` and `openai-compatible` are configured here too: `base_url`
+/// (alias `endpoint`) is their server, `api_key_env` names the variable that
+/// holds the key, and Azure adds `deployment` and `api_version`.
+#[derive(Default, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ProviderOverrideConfig {
+    #[serde(alias = "endpoint")]
+    pub base_url: Option<String>,
+    #[serde(default)]
+    pub extra_headers: BTreeMap<String, String>,
+    /// Azure deployment name; used instead of a model name.
+    pub deployment: Option<String>,
+    /// Azure `api-version` query parameter.
+    pub api_version: Option<String>,
+    /// Environment variable holding the API key.
+    pub api_key_env: Option<String>,
+}
+
+impl std::fmt::Debug for ProviderOverrideConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProviderOverrideConfig")
+            .field("base_url", &self.base_url)
+            .field(
+                "extra_headers",
+                &self.extra_headers.keys().collect::<Vec<&String>>(),
+            )
+            .field("deployment", &self.deployment)
+            .field("api_version", &self.api_version)
+            .field("api_key_env", &self.api_key_env)
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 pub struct McpServerConfig {
     pub name: String,
     /// HTTP endpoint URL. Required for HTTP transport, omit for stdio.
@@ -190,6 +644,56 @@ pub struct McpServerConfig {
     pub tool_aliases: HashMap<String, String>,
     /// OAuth 2.0 config for authenticated MCP servers (feature: oauth).
     pub oauth: Option<crate::mcp_auth::McpOAuthConfig>,
+    /// Set while resolving the runtime config, never read from files.
+    #[serde(skip)]
+    pub source: McpServerSource,
+}
+
+fn ensure_unique_mcp_server_names(servers: &[McpServerConfig], owner: &str) -> Result<()> {
+    let mut seen = BTreeSet::new();
+    for server in servers {
+        if !seen.insert(server.name.as_str()) {
+            return Err(anyhow::anyhow!(
+                "duplicate MCP server name '{}' in {owner}",
+                server.name
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Profile servers, then the agent's. An agent entry replaces the profile
+/// entry with the same name, so `{ name = "x", enabled = false }` disables
+/// a profile server for that agent.
+pub fn merge_mcp_servers(
+    profile_name: &str,
+    profile_servers: &[McpServerConfig],
+    agent_name: &str,
+    agent_servers: &[McpServerConfig],
+) -> Result<Vec<McpServerConfig>> {
+    ensure_unique_mcp_server_names(profile_servers, &format!("profile '{profile_name}'"))?;
+    ensure_unique_mcp_server_names(agent_servers, &format!("agent '{agent_name}'"))?;
+    let mut merged = profile_servers
+        .iter()
+        .map(|server| McpServerConfig {
+            source: McpServerSource::Profile,
+            ..server.clone()
+        })
+        .collect::<Vec<McpServerConfig>>();
+    for server in agent_servers {
+        let server = McpServerConfig {
+            source: McpServerSource::Agent,
+            ..server.clone()
+        };
+        match merged
+            .iter_mut()
+            .find(|existing| existing.name == server.name)
+        {
+            Some(existing) => *existing = server,
+            None => merged.push(server),
+        }
+    }
+    Ok(merged)
 }
 
 impl McpServerConfig {
@@ -208,15 +712,129 @@ impl McpServerConfig {
     }
 }
 
+/// Load the profiles file, merging `include` files first (later files
+/// override earlier ones, the including file overrides its includes) and
+/// expanding `${ENV_VAR}` references in string values.
 pub fn load_profiles(config_path: &str) -> Result<ProfilesFile> {
     let path = Path::new(config_path);
     if !path.exists() {
         return Ok(ProfilesFile::default());
     }
 
+    let mut loader = ProfileIncludeLoader::default();
+    let mut merged = loader.load(path)?;
+    // Profiles are parsed one by one below, so only the rest of the file
+    // has to be valid for it to load.
+    let raw_profiles = match &mut merged {
+        toml::Value::Table(table) => table.remove("profiles"),
+        _ => None,
+    };
+    let mut profiles = merged.try_into::<ProfilesFile>().with_context(|| {
+        format!(
+            "invalid profile configuration in '{}'. Check provider/session values and field names.",
+            path.display()
+        )
+    })?;
+    match raw_profiles {
+        None => {}
+        Some(toml::Value::Table(raw_profiles)) => {
+            for (name, value) in raw_profiles {
+                match value.try_into::<ProfileConfig>() {
+                    Ok(profile) => {
+                        profiles.profiles.insert(name, profile);
+                    }
+                    Err(err) => {
+                        let source = loader.profile_sources.get(&name).map(PathBuf::as_path);
+                        let reason = broken_profile_reason(&name, &err, source);
+                        profiles.broken_profiles.insert(name, reason);
+                    }
+                }
+            }
+        }
+        Some(_) => {
+            return Err(anyhow::anyhow!(
+                "invalid profile configuration in '{}': profiles must be a table",
+                path.display()
+            ));
+        }
+    }
+    profiles.include = loader.root_include;
+    profiles.include_chain = loader.chain;
+    profiles.profile_sources = loader.profile_sources;
+    Ok(profiles)
+}
+
+/// The parse error plus `file:line` of the profile's table header, since
+/// errors from an already-parsed table carry no position.
+fn broken_profile_reason(name: &str, err: &toml::de::Error, source: Option<&Path>) -> String {
+    let message = err.message().trim().to_string();
+    let Some(source) = source else {
+        return message;
+    };
+    match profile_header_line(source, name) {
+        Some(line) => format!("{message} ({}:{line})", source.display()),
+        None => format!("{message} ({})", source.display()),
+    }
+}
+
+fn profile_header_line(path: &Path, name: &str) -> Option<usize> {
+    let content = std::fs::read_to_string(path).ok()?;
+    let headers = [
+        format!("[profiles.{name}]"),
+        format!("[profiles.\"{name}\"]"),
+        format!("[profiles.{name}."),
+        format!("[profiles.\"{name}\"."),
+    ];
+    content
+        .lines()
+        .position(|line| {
+            let line = line.trim();
+            headers
+                .iter()
+                .any(|header| line.starts_with(header.as_str()))
+        })
+        .map(|index| index + 1)
+}
+
+pub fn broken_profile_error(name: &str, reason: &str, config_path: &str) -> anyhow::Error {
+    anyhow::anyhow!("profile '{name}' in '{config_path}' is invalid: {reason}")
+}
+
+/// `Warning:` text naming the profiles that were skipped, if any.
+pub fn broken_profiles_warning(profiles: &ProfilesFile) -> Option<String> {
+    if profiles.broken_profiles.is_empty() {
+        return None;
+    }
+    let names = profiles
+        .broken_profiles
+        .keys()
+        .cloned()
+        .collect::<Vec<String>>()
+        .join(", ");
+    Some(format!(
+        "skipping invalid profile(s): {names}. Run `zavora-cli doctor` for details."
+    ))
+}
+
+/// Parse the profiles file as written, without includes or `${ENV_VAR}`
+/// expansion. Used when rewriting the file so neither gets flattened.
+pub fn load_profiles_raw(config_path: &str) -> Result<ProfilesFile> {
+    let path = Path::new(config_path);
+    if !path.exists() {
+        return Ok(ProfilesFile::default());
+    }
+    read_profile_toml(path)?.try_into::<ProfilesFile>().with_context(|| {
+        format!(
+            "invalid profile configuration in '{}'. Check provider/session values and field names.",
+            path.display()
+        )
+    })
+}
+
+fn read_profile_toml(path: &Path) -> Result<toml::Value> {
     let content = std::fs::read_to_string(path)
         .with_context(|| format!("failed to read profile config file at '{}'", path.display()))?;
-    toml::from_str::<ProfilesFile>(&content).with_context(|| {
+    toml::from_str::<toml::Value>(&content).with_context(|| {
         format!(
             "invalid profile configuration in '{}'. Check provider/session values and field names.",
             path.display()
@@ -224,18 +842,285 @@ pub fn load_profiles(config_path: &str) -> Result<ProfilesFile> {
     })
 }
 
-pub fn default_agent_paths() -> AgentPaths {
-    let local_catalog = PathBuf::from(".zavora/agents.toml");
-    let selection_file = PathBuf::from(".zavora/agent-selection.toml");
-    let global_catalog = std::env::var("HOME")
-        .ok()
-        .map(PathBuf::from)
-        .map(|home| home.join(".zavora/agents.toml"));
+#[derive(Default)]
+struct ProfileIncludeLoader {
+    root_include: Vec<String>,
+    stack: Vec<PathBuf>,
+    chain: Vec<PathBuf>,
+    profile_sources: HashMap<String, PathBuf>,
+}
+
+impl ProfileIncludeLoader {
+    fn load(&mut self, path: &Path) -> Result<toml::Value> {
+        let canonical = path.canonicalize().with_context(|| {
+            format!("failed to read profile config file at '{}'", path.display())
+        })?;
+        if self.stack.contains(&canonical) {
+            let cycle = self
+                .stack
+                .iter()
+                .chain(std::iter::once(&canonical))
+                .map(|p| p.display().to_string())
+                .collect::<Vec<String>>()
+                .join(" -> ");
+            return Err(anyhow::anyhow!("config include cycle detected: {cycle}"));
+        }
+
+        let mut value = read_profile_toml(path)?;
+        interpolate_env_vars(&mut value, path, "")?;
+
+        let includes = match value.get("include") {
+            None => Vec::new(),
+            Some(raw) => raw.clone().try_into::<Vec<String>>().with_context(|| {
+                format!(
+                    "invalid profile configuration in '{}': include must be a list of file paths",
+                    path.display()
+                )
+            })?,
+        };
+
+        if self.stack.is_empty() {
+            self.root_include = includes.clone();
+        }
+        self.stack.push(canonical);
+        let base_dir = path.parent().unwrap_or_else(|| Path::new("."));
+        let mut merged = toml::Value::Table(toml::map::Map::new());
+        for pattern in &includes {
+            for include_path in expand_include_pattern(base_dir, pattern, path)? {
+                let included = self.load(&include_path)?;
+                merge_toml_values(&mut merged, included);
+            }
+        }
+        self.stack.pop();
+
+        if let Some(profiles) = value.get("profiles").and_then(toml::Value::as_table) {
+            for name in profiles.keys() {
+                self.profile_sources
+                    .insert(name.clone(), path.to_path_buf());
+            }
+        }
+        if let toml::Value::Table(table) = &mut value {
+            table.remove("include");
+        }
+        merge_toml_values(&mut merged, value);
+        if !self.chain.iter().any(|p| p == path) {
+            self.chain.push(path.to_path_buf());
+        }
+        Ok(merged)
+    }
+}
+
+/// Resolve one `include` entry. A `*` in the file name matches files in that
+/// directory (sorted); plain paths must exist.
+fn expand_include_pattern(base_dir: &Path, pattern: &str, from: &Path) -> Result<Vec<PathBuf>> {
+    let candidate = base_dir.join(pattern);
+    let file_name = candidate
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    if !file_name.contains('*') {
+        if !candidate.exists() {
+            return Err(anyhow::anyhow!(
+                "config include '{pattern}' referenced from '{}' does not exist (looked for '{}')",
+                from.display(),
+                candidate.display()
+            ));
+        }
+        return Ok(vec![candidate]);
+    }
+
+    let dir = candidate.parent().unwrap_or(base_dir);
+    let mut matches = match std::fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|entry| entry.is_file())
+            .filter(|entry| {
+                entry
+                    .file_name()
+                    .map(|name| {
+                        crate::tool_policy::matches_wildcard(&file_name, &name.to_string_lossy())
+                    })
+                    .unwrap_or(false)
+            })
+            .collect::<Vec<PathBuf>>(),
+        Err(_) => Vec::new(),
+    };
+    matches.sort();
+    Ok(matches)
+}
+
+/// Deep-merge `overlay` into `base`: tables merge key by key, everything
+/// else (including arrays) is replaced.
+fn merge_toml_values(base: &mut toml::Value, overlay: toml::Value) {
+    match (base, overlay) {
+        (toml::Value::Table(base), toml::Value::Table(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_toml_values(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// Expand `${ENV_VAR}` in every string value; `$${` yields a literal `${`.
+fn interpolate_env_vars(value: &mut toml::Value, file: &Path, key_path: &str) -> Result<()> {
+    match value {
+        toml::Value::String(text) => {
+            *text = interpolate_env_string(text, |name| std::env::var(name).ok()).map_err(|name| {
+                anyhow::anyhow!(
+                    "config file '{}' key '{key_path}' references undefined environment variable '{name}'",
+                    file.display()
+                )
+            })?;
+        }
+        toml::Value::Array(items) => {
+            for (idx, item) in items.iter_mut().enumerate() {
+                interpolate_env_vars(item, file, &format!("{key_path}[{idx}]"))?;
+            }
+        }
+        toml::Value::Table(table) => {
+            for (key, item) in table.iter_mut() {
+                let child = if key_path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{key_path}.{key}")
+                };
+                interpolate_env_vars(item, file, &child)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Substitute `${NAME}` using `lookup`. Returns the first undefined name as
+/// the error.
+pub fn interpolate_env_string(
+    text: &str,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> std::result::Result<String, String> {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(pos) = rest.find('$') {
+        out.push_str(&rest[..pos]);
+        let tail = &rest[pos..];
+        if let Some(escaped) = tail.strip_prefix("$${") {
+            out.push_str("${");
+            rest = escaped;
+        } else if let Some(body) = tail.strip_prefix("${")
+            && let Some(end) = body.find('}')
+        {
+            let name = &body[..end];
+            match lookup(name) {
+                Some(value) => out.push_str(&value),
+                None => return Err(name.to_string()),
+            }
+            rest = &body[end + 1..];
+        } else {
+            out.push('$');
+            rest = &tail[1..];
+        }
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+pub fn default_agent_paths(paths: &ZavoraPaths) -> AgentPaths {
     AgentPaths {
-        local_catalog,
-        global_catalog,
-        selection_file,
+        local_catalog: Some(paths.local_agents_catalog()),
+        global_catalog: Some(paths.global_agents_catalog()),
+        extra_catalogs: Vec::new(),
+        selection_file: paths.agent_selection_file(),
+    }
+}
+
+/// Extra catalog sources: the selected profile's `agent_catalog_paths`, then
+/// the colon-separated `ZAVORA_AGENT_PATH`.
+pub fn agent_catalog_search_paths(
+    cli: &Cli,
+    profiles: &ProfilesFile,
+    agent_path_env: Option<&str>,
+) -> Vec<PathBuf> {
+    let profile_paths = resolve_profile(
+        profiles,
+        cli.profile.as_deref().unwrap_or(DEFAULT_PROFILE).trim(),
+    )
+    .map(|resolved| resolved.config.agent_catalog_paths)
+    .unwrap_or_default();
+    profile_paths
+        .iter()
+        .map(|path| PathBuf::from(path.trim()))
+        .chain(agent_path_env.into_iter().flat_map(std::env::split_paths))
+        .filter(|path| !path.as_os_str().is_empty())
+        .collect()
+}
+
+/// Catalog files for one search-path entry: the file itself, or every
+/// `*.toml` in a directory (sorted by name). Missing entries warn and
+/// contribute nothing.
+pub fn expand_agent_catalog_source(path: &Path) -> Result<Vec<PathBuf>> {
+    if path.is_file() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+    if !path.is_dir() {
+        tracing::warn!(
+            path = %path.display(),
+            "agent catalog path does not exist; skipping"
+        );
+        return Ok(Vec::new());
+    }
+    let mut files = std::fs::read_dir(path)
+        .with_context(|| {
+            format!(
+                "failed to read agent catalog directory '{}'",
+                path.display()
+            )
+        })?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|file| file.is_file() && file.extension().is_some_and(|ext| ext == "toml"))
+        .collect::<Vec<PathBuf>>();
+    files.sort();
+    Ok(files)
+}
+
+fn merge_agent_catalog(
+    resolved: &mut HashMap<String, ResolvedAgent>,
+    path: &Path,
+    source: AgentSource,
+) -> Result<()> {
+    let mut agents = load_agent_catalog_file(path)?
+        .agents
+        .into_iter()
+        .collect::<Vec<(String, AgentFileConfig)>>();
+    agents.sort_by(|(left, _), (right, _)| left.cmp(right));
+    for (name, config) in agents {
+        let replaced = resolved.insert(
+            name.clone(),
+            ResolvedAgent {
+                name: name.clone(),
+                source,
+                origin: Some(path.to_path_buf()),
+                config,
+            },
+        );
+        if let Some(previous) = replaced
+            && let Some(previous_origin) = previous.origin
+        {
+            tracing::info!(
+                agent = %name,
+                winner = %path.display(),
+                overridden = %previous_origin.display(),
+                "agent defined in several catalogs; using the later one"
+            );
+        }
     }
+    Ok(())
 }
 
 pub fn load_agent_catalog_file(path: &Path) -> Result<AgentCatalogFile> {
@@ -245,41 +1130,44 @@ pub fn load_agent_catalog_file(path: &Path) -> Result<AgentCatalogFile> {
 
     let content = std::fs::read_to_string(path)
         .with_context(|| format!("failed to read agent catalog file at '{}'", path.display()))?;
-    toml::from_str::<AgentCatalogFile>(&content).with_context(|| {
+    let catalog = toml::from_str::<AgentCatalogFile>(&content).with_context(|| {
         format!(
             "invalid agent catalog configuration in '{}'. Check field names and provider/tool settings.",
             path.display()
         )
-    })
+    })?;
+    let mut names = catalog.agents.keys().collect::<Vec<&String>>();
+    names.sort();
+    for name in names {
+        if let Some(starters) = &catalog.agents[name].starters {
+            validate_agent_starters(name, starters).with_context(|| {
+                format!(
+                    "invalid agent catalog configuration in '{}'",
+                    path.display()
+                )
+            })?;
+        }
+    }
+    Ok(catalog)
 }
 
 pub fn load_resolved_agents(paths: &AgentPaths) -> Result<HashMap<String, ResolvedAgent>> {
     let mut resolved = implicit_agent_map();
 
-    if let Some(global_path) = paths.global_catalog.as_ref() {
-        let global = load_agent_catalog_file(global_path)?;
-        for (name, config) in global.agents {
-            resolved.insert(
-                name.clone(),
-                ResolvedAgent {
-                    name,
-                    source: AgentSource::Global,
-                    config,
-                },
-            );
+    if let Some(global_path) = paths.global_catalog.as_ref()
+        && global_path.exists()
+    {
+        merge_agent_catalog(&mut resolved, global_path, AgentSource::Global)?;
+    }
+    for source in &paths.extra_catalogs {
+        for file in expand_agent_catalog_source(source)? {
+            merge_agent_catalog(&mut resolved, &file, AgentSource::AgentPath)?;
         }
     }
-
-    let local = load_agent_catalog_file(&paths.local_catalog)?;
-    for (name, config) in local.agents {
-        resolved.insert(
-            name.clone(),
-            ResolvedAgent {
-                name,
-                source: AgentSource::Local,
-                config,
-            },
-        );
+    if let Some(local_path) = paths.local_catalog.as_ref()
+        && local_path.exists()
+    {
+        merge_agent_catalog(&mut resolved, local_path, AgentSource::Local)?;
     }
 
     Ok(resolved)
@@ -292,6 +1180,7 @@ pub fn implicit_agent_map() -> HashMap<String, ResolvedAgent> {
         ResolvedAgent {
             name: "default".to_string(),
             source: AgentSource::Implicit,
+            origin: None,
             config: AgentFileConfig {
                 description: Some("Built-in default assistant".to_string()),
                 instruction: None,
@@ -302,6 +1191,8 @@ pub fn implicit_agent_map() -> HashMap<String, ResolvedAgent> {
                 allow_tools: Vec::new(),
                 deny_tools: Vec::new(),
                 hooks: HashMap::new(),
+                mcp_servers: Vec::new(),
+                starters: None,
             },
         },
     );
@@ -310,6 +1201,7 @@ pub fn implicit_agent_map() -> HashMap<String, ResolvedAgent> {
         ResolvedAgent {
             name: "ralph".to_string(),
             source: AgentSource::Implicit,
+            origin: None,
             config: AgentFileConfig {
                 description: Some(
                     "Ralph autonomous development pipeline (PRD → Architect → Loop)".to_string(),
@@ -322,6 +1214,8 @@ pub fn implicit_agent_map() -> HashMap<String, ResolvedAgent> {
                 allow_tools: Vec::new(),
                 deny_tools: Vec::new(),
                 hooks: HashMap::new(),
+                mcp_servers: Vec::new(),
+                starters: None,
             },
         },
     );
@@ -377,7 +1271,125 @@ pub fn resolve_active_agent_name(
     names.sort();
     names.into_iter().next().ok_or_else(|| {
         anyhow::anyhow!(
-            "no agents available. Add '.zavora/agents.toml' or '~/.zavora/agents.toml'."
+            "no agents available. Add an agents.toml to the workspace or global config directory."
+        )
+    })
+}
+
+/// The configured `execute_bash` sandbox, rooted at the current directory.
+pub fn execute_bash_sandbox(cfg: &RuntimeConfig) -> Option<ExecuteBashSandbox> {
+    cfg.execute_bash_sandbox
+        .as_ref()
+        .map(|template| ExecuteBashSandbox {
+            template: template.clone(),
+            workspace: std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")),
+        })
+}
+
+pub fn execute_bash_options(cfg: &RuntimeConfig) -> ExecuteBashOptions {
+    ExecuteBashOptions {
+        sandbox: execute_bash_sandbox(cfg),
+        summarize: cfg.execute_bash_summarize,
+    }
+}
+
+pub fn secret_scan_options(cfg: &RuntimeConfig) -> SecretScanOptions {
+    SecretScanOptions {
+        policy: cfg.fs_write_secret_policy,
+        allowlist: cfg.fs_write_secret_allowlist.clone(),
+        guardrail_terms: cfg.guardrail_terms.clone(),
+        redact_replacement: cfg.guardrail_redact_replacement.clone(),
+        telemetry: Some(TelemetrySink::new(cfg, "tool".to_string())),
+    }
+}
+
+pub fn profile_not_found_error(
+    name: &str,
+    profiles: &ProfilesFile,
+    config_path: &str,
+) -> anyhow::Error {
+    let mut names = profiles.profiles.keys().cloned().collect::<Vec<String>>();
+    names.sort();
+    if names.is_empty() {
+        anyhow::anyhow!(
+            "profile '{}' not found in '{}'. No profiles are defined yet.",
+            name,
+            config_path
+        )
+    } else {
+        anyhow::anyhow!(
+            "profile '{}' not found in '{}'. Available profiles: {}",
+            name,
+            config_path,
+            names.join(", ")
+        )
+    }
+}
+
+pub fn load_profile_selection(path: &Path) -> Result<Option<String>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read profile selection file '{}'", path.display()))?;
+    let parsed = toml::from_str::<ProfileSelectionFile>(&content)
+        .with_context(|| format!("invalid profile selection config '{}'", path.display()))?;
+    Ok(parsed
+        .profile
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty()))
+}
+
+/// Precedence: `--profile`/`ZAVORA_PROFILE`, then the workspace selection
+/// file, then `default`. A selection naming a profile that is no longer
+/// configured is ignored with a warning.
+pub fn resolve_profile_name(
+    cli_profile: Option<&str>,
+    profiles: &ProfilesFile,
+    selected_profile: Option<&str>,
+) -> String {
+    if let Some(requested) = cli_profile {
+        return requested.trim().to_string();
+    }
+    match selected_profile
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+    {
+        Some(selected)
+            if profiles.profiles.contains_key(selected)
+                || profiles.broken_profiles.contains_key(selected) =>
+        {
+            selected.to_string()
+        }
+        Some(selected) if selected != DEFAULT_PROFILE => {
+            tracing::warn!(
+                profile = selected,
+                "Selected profile is not configured anymore; using '{DEFAULT_PROFILE}'"
+            );
+            DEFAULT_PROFILE.to_string()
+        }
+        _ => DEFAULT_PROFILE.to_string(),
+    }
+}
+
+pub fn persist_profile_selection(path: &Path, profile_name: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).with_context(|| {
+            format!(
+                "failed to create profile selection directory '{}'",
+                parent.display()
+            )
+        })?;
+    }
+    let payload = toml::to_string(&ProfileSelectionFile {
+        profile: Some(profile_name.to_string()),
+    })
+    .context("failed to serialize profile selection file")?;
+    let _lock = acquire_file_lock(path, DEFAULT_FILE_LOCK_TIMEOUT)?;
+    std::fs::write(path, payload).with_context(|| {
+        format!(
+            "failed to write profile selection file '{}'",
+            path.display()
         )
     })
 }
@@ -395,6 +1407,7 @@ pub fn persist_agent_selection(path: &Path, agent_name: &str) -> Result<()> {
         agent: Some(agent_name.to_string()),
     })
     .context("failed to serialize agent selection file")?;
+    let _lock = acquire_file_lock(path, DEFAULT_FILE_LOCK_TIMEOUT)?;
     std::fs::write(path, payload)
         .with_context(|| format!("failed to write agent selection file '{}'", path.display()))
 }
@@ -416,69 +1429,108 @@ fn merge_unique_names(first: &[String], second: &[String]) -> Vec<String> {
     merged
 }
 
+/// Provider and model from the first layer (CLI, agent, profile) that sets
+/// each, with `provider/model` shorthand expanded. A shorthand names the
+/// provider for its layer and must agree with that layer's explicit one. A
+/// known provider is checked against the model here, before MCP discovery and
+/// session setup.
+fn resolve_provider_and_model(
+    layers: [(&str, Option<Provider>, Option<&str>); 3],
+) -> Result<(Provider, Option<String>)> {
+    let mut provider = None;
+    let mut model = None;
+    for (source, explicit, layer_model) in layers {
+        let explicit = explicit.filter(|provider| *provider != Provider::Auto);
+        let (shorthand, layer_model) = match layer_model {
+            Some(value) => {
+                let (shorthand, name) = split_model_shorthand(value)?;
+                (shorthand, Some(name))
+            }
+            None => (None, None),
+        };
+        if let (Some(explicit), Some(shorthand)) = (explicit, shorthand)
+            && explicit != shorthand
+        {
+            let explicit = format!("{explicit:?}").to_ascii_lowercase();
+            let shorthand = format!("{shorthand:?}").to_ascii_lowercase();
+            let model = layer_model.unwrap_or_default();
+            return Err(anyhow::anyhow!(
+                "{source} {explicit} conflicts with the model shorthand '{shorthand}/{model}'; \
+                 drop one of them"
+            ));
+        }
+        provider = provider.or(explicit).or(shorthand);
+        model = model.or(layer_model);
+    }
+    let provider = provider.unwrap_or(Provider::Auto);
+    if let Some(model) = &model {
+        validate_model_for_provider(provider, model)?;
+    }
+    Ok((provider, model))
+}
+
 pub fn resolve_runtime_config_with_agents(
     cli: &Cli,
     profiles: &ProfilesFile,
     resolved_agents: &HashMap<String, ResolvedAgent>,
     selected_agent_name: Option<&str>,
 ) -> Result<RuntimeConfig> {
-    let selected = cli.profile.trim();
+    let selected = cli.profile.as_deref().unwrap_or(DEFAULT_PROFILE).trim();
     if selected.is_empty() {
         return Err(anyhow::anyhow!(
             "profile name cannot be empty. Set --profile <name>."
         ));
     }
 
-    let profile = if selected == "default" && !profiles.profiles.contains_key("default") {
-        ProfileConfig::default()
-    } else {
-        profiles.profiles.get(selected).cloned().ok_or_else(|| {
-            let mut names = profiles.profiles.keys().cloned().collect::<Vec<String>>();
-            names.sort();
-            if names.is_empty() {
-                anyhow::anyhow!(
-                    "profile '{}' not found in '{}'. No profiles are defined yet.",
-                    selected,
-                    cli.config_path
-                )
-            } else {
-                anyhow::anyhow!(
-                    "profile '{}' not found in '{}'. Available profiles: {}",
-                    selected,
-                    cli.config_path,
-                    names.join(", ")
-                )
-            }
-        })?
-    };
+    if let Some(reason) = profiles.broken_profiles.get(selected) {
+        return Err(broken_profile_error(selected, reason, &cli.config_path));
+    }
+    if selected != DEFAULT_PROFILE && !profiles.profiles.contains_key(selected) {
+        return Err(profile_not_found_error(
+            selected,
+            profiles,
+            &cli.config_path,
+        ));
+    }
+    let profile = resolve_profile(profiles, selected)?.config;
 
     let active_agent_name = resolve_active_agent_name(cli, resolved_agents, selected_agent_name)?;
     let active_agent = resolved_agents.get(&active_agent_name).ok_or_else(|| {
         anyhow::anyhow!("resolved active agent '{}' is missing", active_agent_name)
     })?;
 
-    let provider = if cli.provider != Provider::Auto {
-        cli.provider
-    } else {
-        active_agent
-            .config
-            .provider
-            .or(profile.provider)
-            .unwrap_or(Provider::Auto)
-    };
+    let (provider, model) = resolve_provider_and_model([
+        ("--provider", Some(cli.provider), cli.model.as_deref()),
+        (
+            &format!("agent '{}' provider", active_agent.name),
+            active_agent.config.provider,
+            active_agent.config.model.as_deref(),
+        ),
+        (
+            &format!("profile '{selected}' provider"),
+            profile.provider,
+            profile.model.as_deref(),
+        ),
+    ])?;
 
     let require_confirm_tool =
         merge_unique_names(&profile.require_confirm_tool, &cli.require_confirm_tool);
     let approve_tool = merge_unique_names(&profile.approve_tool, &cli.approve_tool);
-    let guardrail_terms = {
-        let merged = merge_unique_names(&profile.guardrail_terms, &cli.guardrail_term);
-        if merged.is_empty() {
-            default_guardrail_terms()
-        } else {
-            merged
-        }
-    };
-    let mcp_servers = profile.mcp_servers.clone();
+    let (mut guardrail_terms, guardrail_term_severities) =
+        resolve_guardrail_terms(&profile.guardrail_terms, &cli.guardrail_term);
+    if guardrail_terms.is_empty() {
+        guardrail_terms = default_guardrail_terms();
+    }
+    let guardrail_severity_actions =
+        resolve_guardrail_severity_actions(&profile.guardrail_severity_actions)?;
+    let guardrail_matcher_cache = GuardrailMatcherCache::with_terms(&guardrail_terms);
+    let mcp_servers = merge_mcp_servers(
+        selected,
+        &profile.mcp_servers,
+        &active_agent.name,
+        &active_agent.config.mcp_servers,
+    )?;
+    let paths = ZavoraPaths::from_env(cli.config_dir.as_deref());
 
     Ok(RuntimeConfig {
         profile: selected.to_string(),
@@ -491,11 +1543,7 @@ pub fn resolve_runtime_config_with_agents(
         agent_allow_tools: active_agent.config.allow_tools.clone(),
         agent_deny_tools: active_agent.config.deny_tools.clone(),
         provider,
-        model: cli
-            .model
-            .clone()
-            .or(active_agent.config.model.clone())
-            .or(profile.model),
+        model,
         api_key: profile.api_key,
         ollama_host: profile.ollama_host,
         app_name: cli
@@ -521,8 +1569,24 @@ pub fn resolve_runtime_config_with_agents(
             .session_db_url
             .clone()
             .or(profile.session_db_url)
-            .unwrap_or_else(|| "sqlite://.zavora/sessions.db".to_string()),
+            .unwrap_or_else(|| paths.sessions_db_url()),
+        session_encryption_key_env: profile
+            .session_encryption_key_env
+            .map(|env| env.trim().to_string())
+            .filter(|env| !env.is_empty()),
+        session_busy_retry_attempts: profile
+            .session_busy_retry_attempts
+            .unwrap_or(crate::session::DEFAULT_SESSION_BUSY_RETRY_ATTEMPTS),
+        session_busy_retry_max_ms: profile
+            .session_busy_retry_max_ms
+            .unwrap_or(crate::session::DEFAULT_SESSION_BUSY_RETRY_MAX_MS),
         show_sensitive_config: cli.show_sensitive_config,
+        fake_script: cli.fake_script.clone(),
+        show_context_budget: cli.show_context_budget,
+        provenance: !cli.no_provenance,
+        read_only: cli.read_only || profile.read_only.unwrap_or(false),
+        strict_final_text: cli.strict_final_text || profile.strict_final_text.unwrap_or(false),
+        pager: !cli.no_pager && profile.pager.unwrap_or(true),
         retrieval_backend: cli
             .retrieval_backend
             .or(profile.retrieval_backend)
@@ -545,6 +1609,38 @@ pub fn resolve_runtime_config_with_agents(
             .retrieval_min_score
             .or(profile.retrieval_min_score)
             .unwrap_or(1),
+        retrieval_chunking: cli
+            .retrieval_chunking
+            .or(profile.retrieval_chunking)
+            .unwrap_or(RetrievalChunking::Paragraph),
+        retrieval_query_strategy: cli
+            .retrieval_query_strategy
+            .or(profile.retrieval_query_strategy)
+            .unwrap_or(RetrievalQueryStrategy::Latest),
+        retrieval_query_window: cli
+            .retrieval_query_window
+            .or(profile.retrieval_query_window)
+            .unwrap_or(crate::retrieval_query::DEFAULT_RETRIEVAL_QUERY_WINDOW)
+            .max(1),
+        retrieval_chunk_size: profile
+            .retrieval_chunk_size
+            .unwrap_or(crate::chunking::DEFAULT_CHUNK_SIZE)
+            .max(crate::chunking::MIN_CHUNK_SIZE),
+        retrieval_chunk_overlap: profile
+            .retrieval_chunk_overlap
+            .unwrap_or(crate::chunking::DEFAULT_CHUNK_OVERLAP),
+        retrieval_watch: cli
+            .retrieval_watch
+            .or(profile.retrieval_watch)
+            .unwrap_or(RetrievalWatch::OnDemand),
+        memory_backend: cli
+            .memory_backend
+            .or(profile.memory_backend)
+            .unwrap_or(MemoryBackend::Disabled),
+        memory_recall_max_facts: cli
+            .memory_recall_max_facts
+            .or(profile.memory_recall_max_facts)
+            .unwrap_or(5),
         tool_confirmation_mode: cli
             .tool_confirmation_mode
             .or(active_agent.config.tool_confirmation_mode)
@@ -557,6 +1653,41 @@ pub fn resolve_runtime_config_with_agents(
             .or(profile.tool_timeout_secs)
             .unwrap_or(45)
             .max(1),
+        slow_tool_warn_secs: cli
+            .slow_tool_warn_secs
+            .or(profile.slow_tool_warn_secs)
+            .unwrap_or(crate::streaming::DEFAULT_SLOW_TOOL_WARN_SECS),
+        max_runtime_secs: cli
+            .max_runtime_secs
+            .or(profile.max_runtime_secs)
+            .unwrap_or(0),
+        max_runtime_from_cli: cli.max_runtime_secs.is_some(),
+        chat_show_tool_results: cli
+            .chat_show_tool_results
+            .or(profile.chat_show_tool_results)
+            .unwrap_or(ToolResultDisplay::Off),
+        chat_tool_result_max_chars: profile
+            .chat_tool_result_max_chars
+            .unwrap_or(crate::tool_output::DEFAULT_TOOL_RESULT_MAX_CHARS)
+            .max(1),
+        chat_tool_annotations: profile.chat_tool_annotations.unwrap_or_default(),
+        tool_schema_strategy: profile.tool_schema_strategy.unwrap_or_default(),
+        tool_schema_top_k: profile
+            .tool_schema_top_k
+            .unwrap_or(crate::tools::schema_pruning::DEFAULT_TOOL_SCHEMA_TOP_K)
+            .max(1),
+        tool_output_max_chars: cli
+            .tool_output_max_chars
+            .or(profile.tool_output_max_chars)
+            .unwrap_or(crate::tools::output_cap::DEFAULT_TOOL_OUTPUT_MAX_CHARS),
+        tool_budgets: ToolBudgets {
+            max_calls_per_prompt: cli
+                .max_tool_calls_per_prompt
+                .or(profile.max_tool_calls_per_prompt)
+                .unwrap_or(0),
+            per_tool: profile.tool_call_budgets.clone(),
+        },
+        tool_call_counter: ToolCallCounter::default(),
         tool_retry_attempts: cli
             .tool_retry_attempts
             .or(profile.tool_retry_attempts)
@@ -566,6 +1697,17 @@ pub fn resolve_runtime_config_with_agents(
             .tool_retry_delay_ms
             .or(profile.tool_retry_delay_ms)
             .unwrap_or(500),
+        tool_arg_retry_hint: cli
+            .tool_arg_retry_hint
+            .or(profile.tool_arg_retry_hint)
+            .unwrap_or(true),
+        mcp_failure_threshold: profile
+            .mcp_failure_threshold
+            .unwrap_or(crate::mcp_breaker::DEFAULT_MCP_FAILURE_THRESHOLD),
+        mcp_breaker_max_secs: profile
+            .mcp_breaker_max_secs
+            .unwrap_or(crate::mcp_breaker::DEFAULT_MCP_BREAKER_MAX_SECS)
+            .max(crate::mcp_breaker::MCP_BREAKER_BASE_SECS),
         telemetry_enabled: cli
             .telemetry_enabled
             .or(profile.telemetry_enabled)
@@ -574,7 +1716,21 @@ pub fn resolve_runtime_config_with_agents(
             .telemetry_path
             .clone()
             .or(profile.telemetry_path)
-            .unwrap_or_else(|| ".zavora/telemetry/events.jsonl".to_string()),
+            .unwrap_or_else(|| paths.telemetry_path().display().to_string()),
+        telemetry_capture_environment: profile.telemetry_capture_environment.unwrap_or(true),
+        telemetry_redact_fields: profile.telemetry_redact_fields.clone().unwrap_or_else(|| {
+            DEFAULT_TELEMETRY_REDACT_FIELDS
+                .iter()
+                .map(|field| field.to_string())
+                .collect()
+        }),
+        telemetry_redaction: profile.telemetry_redaction.unwrap_or_default(),
+        telemetry_export: profile
+            .telemetry_export
+            .map(|export| validate_telemetry_export(selected, export))
+            .transpose()?,
+        telemetry_export_dry_run: cli.telemetry_export_dry_run,
+        offline: cli.offline,
         guardrail_input_mode: cli
             .guardrail_input_mode
             .or(profile.guardrail_input_mode)
@@ -583,21 +1739,117 @@ pub fn resolve_runtime_config_with_agents(
             .guardrail_output_mode
             .or(profile.guardrail_output_mode)
             .unwrap_or(GuardrailMode::Disabled),
+        guardrail_retrieval_mode: cli
+            .guardrail_retrieval_mode
+            .or(profile.guardrail_retrieval_mode)
+            .unwrap_or(GuardrailMode::Observe),
+        content_injection_mode: cli
+            .content_injection_mode
+            .or(profile.content_injection_mode)
+            .unwrap_or(ContentInjectionMode::Observe),
+        content_injection_patterns: profile.content_injection_patterns.clone(),
+        fs_write_secret_policy: cli
+            .fs_write_secret_policy
+            .or(profile.fs_write_secret_policy)
+            .unwrap_or(FsWriteSecretPolicy::Block),
+        fs_write_secret_allowlist: profile.fs_write_secret_allowlist.clone(),
+        output_sanitize: cli
+            .output_sanitize
+            .or(profile.output_sanitize)
+            .unwrap_or(OutputSanitize::Strip),
         guardrail_terms,
+        guardrail_term_severities,
+        guardrail_severity_actions,
         guardrail_redact_replacement: cli
             .guardrail_redact_replacement
             .clone()
             .or(profile.guardrail_redact_replacement)
             .unwrap_or_else(|| "[REDACTED]".to_string()),
+        guardrail_matcher_cache,
         mcp_servers,
         permission_rules: profile.permission_rules.clone(),
+        retention: profile.retention.clone(),
         max_prompt_chars: 32_000,
         server_runner_cache_max: 64,
+        server_max_body_bytes: cli
+            .server_max_body_bytes
+            .or(profile.server_max_body_bytes)
+            .unwrap_or(DEFAULT_SERVER_MAX_BODY_BYTES)
+            .max(1024),
+        server_max_prompt_chars: cli
+            .server_max_prompt_chars
+            .or(profile.server_max_prompt_chars)
+            .unwrap_or(32_000)
+            .max(1),
+        server_legacy_status_codes: cli
+            .server_legacy_status_codes
+            .or(profile.server_legacy_status_codes)
+            .unwrap_or(false),
+        server_per_user_max_concurrent: cli
+            .server_per_user_max_concurrent
+            .or(profile.server_per_user_max_concurrent)
+            .unwrap_or(0),
+        server_per_user_requests_per_minute: cli
+            .server_per_user_requests_per_minute
+            .or(profile.server_per_user_requests_per_minute)
+            .unwrap_or(0),
+        chat_notify_command: cli
+            .chat_notify_command
+            .clone()
+            .or(profile.chat_notify_command)
+            .map(|command| command.trim().to_string())
+            .filter(|command| !command.is_empty()),
+        chat_notify_threshold_secs: cli
+            .chat_notify_threshold_secs
+            .or(profile.chat_notify_threshold_secs)
+            .unwrap_or(30),
+        chat_notify_enabled: true,
+        chat_idle_timeout_secs: cli
+            .chat_idle_timeout_secs
+            .or(profile.chat_idle_timeout_secs)
+            .unwrap_or(0),
+        chat_idle_transcript_dir: profile
+            .chat_idle_transcript_dir
+            .map(|dir| dir.trim().to_string())
+            .unwrap_or_else(|| paths.idle_transcript_dir().display().to_string()),
+        chat_idle_snapshot_db_url: profile
+            .chat_idle_snapshot_db_url
+            .map(|url| url.trim().to_string())
+            .unwrap_or_else(|| paths.idle_snapshot_db_url()),
+        verbosity: Verbosity::from_flags(cli.quiet, cli.verbose),
+        daily_token_budget: profile.daily_token_budget,
+        daily_cost_budget: profile.daily_cost_budget,
+        usage_budget_mode: profile.usage_budget_mode.unwrap_or(UsageBudgetMode::Warn),
+        usage_cost_per_1k_tokens: profile.usage_cost_per_1k_tokens.unwrap_or(0.0),
+        usage_dir: paths.usage_dir().display().to_string(),
+        update_check: profile.update_check.unwrap_or(false),
+        update_check_url: profile
+            .update_check_url
+            .unwrap_or_else(|| crate::update_check::DEFAULT_UPDATE_CHECK_URL.to_string()),
+        ask_cache_ttl_secs: profile
+            .ask_cache_ttl_secs
+            .unwrap_or(crate::answer_cache::DEFAULT_ASK_CACHE_TTL_SECS),
+        ask_default_ephemeral: profile.ask_default_ephemeral.unwrap_or(false),
+        ephemeral: None,
+        execute_bash_sandbox: profile
+            .execute_bash_sandbox
+            .map(|template| template.trim().to_string())
+            .filter(|template| !template.is_empty()),
+        execute_bash_summarize: profile.execute_bash_summarize.unwrap_or(false),
+        mock_tools: cli
+            .mock_tools
+            .as_deref()
+            .map(load_mock_tools)
+            .transpose()?
+            .map(Arc::new),
+        provider_overrides: profile.providers,
         auto_compact_enabled: true,
         compact_interval: 10,
         compact_overlap: 2,
         compaction_threshold: profile.compaction_threshold.unwrap_or(0.75),
         compaction_target: profile.compaction_target.unwrap_or(0.10),
+        history_trim_threshold: profile.history_trim_threshold.unwrap_or(0.9),
+        paths,
     })
 }
 
@@ -0,0 +1,421 @@
+//! `config set <path> <value>` and `config unset <path>`: single-key edits
+//! to the profiles file without opening it.
+//!
+//! The file is edited with `toml_edit`, so comments, ordering and the
+//! formatting of untouched keys survive. Values are coerced to the type the
+//! profiles schema gives the target field: `true`/`false` for booleans,
+//! numbers for integer and float fields, JSON-style literals (`["a", "b"]`,
+//! `{ "k": "v" }`) for arrays and tables, and the raw text for strings.
+//! Unknown keys are refused with the closest known name.
+//!
+//! Before anything is written the edited document goes through the normal
+//! profiles deserialization (the edited profile strictly, so an invalid enum
+//! value is an error here rather than a broken profile on the next run).
+//! The previous file is copied to `<state>/config-backups/` (the newest
+//! [`CONFIG_BACKUP_LIMIT`] are kept) and the new one is written to a temp
+//! file and renamed over the original.
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde_json::Value;
+use toml_edit::{DocumentMut, Item, TableLike};
+
+use crate::config::{ProfileConfig, ProfilesFile};
+use crate::config_schema::profiles_schema;
+use crate::file_lock::{DEFAULT_FILE_LOCK_TIMEOUT, acquire_file_lock};
+use crate::tools::suggestions::closest_name;
+
+/// Backups kept per config file.
+pub const CONFIG_BACKUP_LIMIT: usize = 10;
+
+/// Result of one `config set`/`config unset`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigEdit {
+    pub path: String,
+    /// The value as written to the file; `None` for `unset`.
+    pub value: Option<String>,
+    /// Copy of the previous file, when there was one.
+    pub backup: Option<PathBuf>,
+}
+
+impl ConfigEdit {
+    pub fn summary(&self, config_path: &Path) -> String {
+        let mut line = match &self.value {
+            Some(value) => format!("Set {} = {value} in {}", self.path, config_path.display()),
+            None => format!("Removed {} from {}", self.path, config_path.display()),
+        };
+        if let Some(backup) = &self.backup {
+            line.push_str(&format!(" (backup: {})", backup.display()));
+        }
+        line
+    }
+}
+
+fn split_config_path(path: &str) -> Result<Vec<&str>> {
+    let segments = path.trim().split('.').collect::<Vec<&str>>();
+    if segments.iter().any(|segment| segment.trim().is_empty()) {
+        anyhow::bail!(
+            "invalid config path '{path}': expected dotted keys like profiles.work.model"
+        );
+    }
+    Ok(segments)
+}
+
+fn definition<'a>(schema: &'a Value, node: &'a Value) -> &'a Value {
+    let mut node = node;
+    // `$ref`, `allOf: [$ref]` (a default around a ref) and `anyOf: [T, null]`
+    // (an `Option`) all stand for one type.
+    for _ in 0..8 {
+        if let Some(reference) = node.get("$ref").and_then(Value::as_str) {
+            let pointer = reference.trim_start_matches('#');
+            match schema.pointer(pointer) {
+                Some(target) => node = target,
+                None => return node,
+            }
+            continue;
+        }
+        let wrapped = ["allOf", "anyOf"].iter().find_map(|key| {
+            let variants = node.get(*key)?.as_array()?;
+            let mut types = variants
+                .iter()
+                .filter(|variant| variant.get("type").and_then(Value::as_str) != Some("null"));
+            let only = types.next()?;
+            types.next().is_none().then_some(only)
+        });
+        match wrapped {
+            Some(inner) => node = inner,
+            None => return node,
+        }
+    }
+    node
+}
+
+/// Schema of the field at `segments`, or an error naming the first unknown
+/// key (with a did-you-mean when one is close).
+pub fn config_field_schema(schema: &Value, segments: &[&str]) -> Result<Value> {
+    let mut node = definition(schema, schema);
+    for (depth, segment) in segments.iter().enumerate() {
+        let properties = node.get("properties").and_then(Value::as_object);
+        if let Some(field) = properties.and_then(|properties| properties.get(*segment)) {
+            node = definition(schema, field);
+            continue;
+        }
+        if let Some(values) = node.get("additionalProperties").filter(|v| v.is_object()) {
+            node = definition(schema, values);
+            continue;
+        }
+        let parent = segments[..depth].join(".");
+        let at = if parent.is_empty() {
+            "at the top level".to_string()
+        } else {
+            format!("under '{parent}'")
+        };
+        let hint = properties
+            .and_then(|properties| closest_name(segment, properties.keys().map(String::as_str)))
+            .map(|name| format!("; did you mean '{name}'?"))
+            .unwrap_or_default();
+        anyhow::bail!("unknown config key '{segment}' {at}{hint}");
+    }
+    Ok(node.clone())
+}
+
+/// JSON types the schema allows, without `null`.
+fn schema_types(node: &Value) -> Vec<String> {
+    let mut types = match node.get("type") {
+        Some(Value::String(kind)) => vec![kind.clone()],
+        Some(Value::Array(kinds)) => kinds
+            .iter()
+            .filter_map(Value::as_str)
+            .map(str::to_string)
+            .collect(),
+        _ => Vec::new(),
+    };
+    for key in ["anyOf", "oneOf"] {
+        for variant in node
+            .get(key)
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            types.extend(schema_types(variant));
+        }
+    }
+    types.retain(|kind| kind != "null");
+    types.sort();
+    types.dedup();
+    types
+}
+
+fn json_to_toml(value: &Value) -> Result<toml_edit::Value> {
+    Ok(match value {
+        Value::Null => anyhow::bail!("null has no TOML form; use `config unset` to remove a key"),
+        Value::Bool(flag) => (*flag).into(),
+        Value::Number(number) => match number.as_i64() {
+            Some(integer) => integer.into(),
+            None => number.as_f64().unwrap_or_default().into(),
+        },
+        Value::String(text) => text.as_str().into(),
+        Value::Array(items) => {
+            let mut array = toml_edit::Array::new();
+            for item in items {
+                array.push(json_to_toml(item)?);
+            }
+            toml_edit::Value::Array(array)
+        }
+        Value::Object(entries) => {
+            let mut table = toml_edit::InlineTable::new();
+            for (key, item) in entries {
+                table.insert(key.as_str(), json_to_toml(item)?);
+            }
+            toml_edit::Value::InlineTable(table)
+        }
+    })
+}
+
+/// `raw` as the type `field` (a schema node) expects.
+pub fn coerce_config_value(path: &str, field: &Value, raw: &str) -> Result<toml_edit::Value> {
+    let types = schema_types(field);
+    let has = |kind: &str| types.iter().any(|t| t == kind);
+    let trimmed = raw.trim();
+    let mismatch = |expected: &str| anyhow::anyhow!("{path} expects {expected}, got '{raw}'");
+    if has("boolean") && types.len() == 1 {
+        return match trimmed {
+            "true" => Ok(true.into()),
+            "false" => Ok(false.into()),
+            _ => Err(mismatch("true or false")),
+        };
+    }
+    if has("integer") && !has("string") {
+        return trimmed
+            .parse::<i64>()
+            .map(Into::into)
+            .map_err(|_| mismatch("an integer"));
+    }
+    if has("number") && !has("string") {
+        return trimmed
+            .parse::<f64>()
+            .map(Into::into)
+            .map_err(|_| mismatch("a number"));
+    }
+    let literal = trimmed.starts_with('[') || trimmed.starts_with('{');
+    if (has("array") || has("object")) && (literal || !has("string")) {
+        let expected = if has("array") {
+            "a JSON-style array such as [\"a\", \"b\"]"
+        } else {
+            "a JSON-style table such as {\"key\": \"value\"}"
+        };
+        let parsed = serde_json::from_str::<Value>(trimmed).map_err(|_| mismatch(expected))?;
+        if !((parsed.is_array() && has("array")) || (parsed.is_object() && has("object"))) {
+            return Err(mismatch(expected));
+        }
+        return json_to_toml(&parsed);
+    }
+    Ok(raw.into())
+}
+
+/// The parent table of the last segment, creating missing tables when
+/// `create` is set.
+fn parent_table<'a>(
+    doc: &'a mut DocumentMut,
+    segments: &[&str],
+    create: bool,
+) -> Result<Option<&'a mut dyn TableLike>> {
+    let mut table: &mut dyn TableLike = doc.as_table_mut();
+    for (depth, segment) in segments[..segments.len() - 1].iter().enumerate() {
+        let item = if create {
+            table.entry(segment).or_insert_with(|| {
+                let mut new = toml_edit::Table::new();
+                new.set_implicit(true);
+                Item::Table(new)
+            })
+        } else {
+            match table.get_mut(segment) {
+                Some(item) => item,
+                None => return Ok(None),
+            }
+        };
+        table = item.as_table_like_mut().with_context(|| {
+            format!("'{}' is a value, not a table", segments[..=depth].join("."))
+        })?;
+    }
+    Ok(Some(table))
+}
+
+/// Set `path` to `raw` (coerced to the field's type) in `text`.
+pub fn set_config_value(text: &str, path: &str, raw: &str) -> Result<(String, String)> {
+    let segments = split_config_path(path)?;
+    let field = config_field_schema(&profiles_schema(), &segments)?;
+    let mut value = coerce_config_value(path, &field, raw)?;
+    let mut doc = text
+        .parse::<DocumentMut>()
+        .context("the config file is not valid TOML; fix it by hand first")?;
+    let key = segments[segments.len() - 1];
+    let table = parent_table(&mut doc, &segments, true)?.expect("tables are created");
+    match table.get_mut(key) {
+        Some(Item::Value(existing)) => {
+            // Keep the comment after the old value.
+            *value.decor_mut() = existing.decor().clone();
+            *existing = value.clone();
+        }
+        Some(Item::None) | None => {
+            table.insert(key, Item::Value(value.clone()));
+        }
+        Some(_) => anyhow::bail!("'{path}' is a table; set its keys one at a time"),
+    }
+    let shown = value.clone().decorated("", "").to_string();
+    let text = doc.to_string();
+    validate_profiles_document(&text, &segments)?;
+    Ok((text, shown))
+}
+
+/// Remove `path` from `text`.
+pub fn unset_config_value(text: &str, path: &str) -> Result<String> {
+    let segments = split_config_path(path)?;
+    let mut doc = text
+        .parse::<DocumentMut>()
+        .context("the config file is not valid TOML; fix it by hand first")?;
+    let key = segments[segments.len() - 1];
+    let removed = parent_table(&mut doc, &segments, false)?
+        .and_then(|table| table.remove(key))
+        .is_some();
+    if !removed {
+        anyhow::bail!("'{path}' is not set in the config file");
+    }
+    let text = doc.to_string();
+    validate_profiles_document(&text, &segments)?;
+    Ok(text)
+}
+
+/// Run `text` through profiles deserialization. The profile named by
+/// `segments` (`profiles.<name>...`) is checked strictly; other profiles are
+/// left to the loader, which reports them as broken.
+pub fn validate_profiles_document(text: &str, segments: &[&str]) -> Result<()> {
+    let mut value =
+        toml::from_str::<toml::Value>(text).context("edited config is not valid TOML")?;
+    let profiles = value
+        .as_table_mut()
+        .and_then(|table| table.remove("profiles"));
+    value.try_into::<ProfilesFile>().map_err(|err| {
+        anyhow::anyhow!("the edit would make the config invalid: {}", err.message())
+    })?;
+    let Some(profiles) = profiles else {
+        return Ok(());
+    };
+    let profiles = profiles
+        .as_table()
+        .context("the edit would make the config invalid: profiles must be a table")?;
+    if let ["profiles", name, ..] = segments
+        && let Some(profile) = profiles.get(*name)
+    {
+        profile.clone().try_into::<ProfileConfig>().map_err(|err| {
+            anyhow::anyhow!(
+                "the edit would make profile '{name}' invalid: {}",
+                err.message().trim()
+            )
+        })?;
+    }
+    Ok(())
+}
+
+fn backup_prefix(config_path: &Path) -> String {
+    let name = config_path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "config.toml".to_string());
+    format!("{name}.")
+}
+
+/// Copy `config_path` into `backup_dir` with a timestamp and drop all but
+/// the newest [`CONFIG_BACKUP_LIMIT`] copies of it.
+pub fn backup_config_file(config_path: &Path, backup_dir: &Path) -> Result<PathBuf> {
+    std::fs::create_dir_all(backup_dir)
+        .with_context(|| format!("failed to create {}", backup_dir.display()))?;
+    let prefix = backup_prefix(config_path);
+    let stamp = chrono::Utc::now().format("%Y%m%dT%H%M%S%6fZ").to_string();
+    let mut backup = backup_dir.join(format!("{prefix}{stamp}.bak"));
+    let mut attempt = 1;
+    while backup.exists() {
+        backup = backup_dir.join(format!("{prefix}{stamp}-{attempt}.bak"));
+        attempt += 1;
+    }
+    std::fs::copy(config_path, &backup)
+        .with_context(|| format!("failed to back up {}", config_path.display()))?;
+
+    let mut backups = std::fs::read_dir(backup_dir)
+        .with_context(|| format!("failed to read {}", backup_dir.display()))?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .map(|name| name.to_string_lossy())
+                .is_some_and(|name| name.starts_with(&prefix) && name.ends_with(".bak"))
+        })
+        .collect::<Vec<PathBuf>>();
+    backups.sort();
+    let excess = backups.len().saturating_sub(CONFIG_BACKUP_LIMIT);
+    for old in &backups[..excess] {
+        std::fs::remove_file(old)
+            .with_context(|| format!("failed to remove old backup {}", old.display()))?;
+    }
+    Ok(backup)
+}
+
+/// Back up, then replace `config_path` with `text` via a temp file and rename.
+fn write_config_atomically(
+    config_path: &Path,
+    text: &str,
+    backup_dir: &Path,
+) -> Result<Option<PathBuf>> {
+    if let Some(parent) = config_path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    let _lock = acquire_file_lock(config_path, DEFAULT_FILE_LOCK_TIMEOUT)?;
+    let backup = if config_path.exists() {
+        Some(backup_config_file(config_path, backup_dir)?)
+    } else {
+        None
+    };
+    let mut tmp_name = config_path.as_os_str().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp = PathBuf::from(tmp_name);
+    std::fs::write(&tmp, text).with_context(|| format!("failed to write {}", tmp.display()))?;
+    std::fs::rename(&tmp, config_path)
+        .with_context(|| format!("failed to replace {}", config_path.display()))?;
+    Ok(backup)
+}
+
+fn read_config_text(config_path: &Path) -> Result<String> {
+    if !config_path.exists() {
+        return Ok(String::new());
+    }
+    std::fs::read_to_string(config_path)
+        .with_context(|| format!("failed to read {}", config_path.display()))
+}
+
+/// `config set`: edit, validate and write `config_path`.
+pub fn run_config_set(
+    config_path: &Path,
+    backup_dir: &Path,
+    path: &str,
+    raw: &str,
+) -> Result<ConfigEdit> {
+    let (text, value) = set_config_value(&read_config_text(config_path)?, path, raw)?;
+    let backup = write_config_atomically(config_path, &text, backup_dir)?;
+    Ok(ConfigEdit {
+        path: path.to_string(),
+        value: Some(value),
+        backup,
+    })
+}
+
+/// `config unset`: remove the key, validate and write `config_path`.
+pub fn run_config_unset(config_path: &Path, backup_dir: &Path, path: &str) -> Result<ConfigEdit> {
+    let text = unset_config_value(&read_config_text(config_path)?, path)?;
+    let backup = write_config_atomically(config_path, &text, backup_dir)?;
+    Ok(ConfigEdit {
+        path: path.to_string(),
+        value: None,
+        backup,
+    })
+}
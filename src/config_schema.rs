@@ -0,0 +1,686 @@
+//! `config schema`: JSON Schema documents for the profiles file
+//! (`config.toml`) and agent catalogs (`agents.toml`), for editor validation.
+//!
+//! Structure, enum values and `#[serde(default)]` values come from the serde
+//! types via `schemars`, so they always match what the loader accepts. Field
+//! descriptions and the defaults applied during config resolution live in the
+//! tables below; a test fails when a profile or agent field has no entry.
+//!
+//! Workflows have no file format (they are chosen with `workflow` subcommand
+//! flags), so there is no workflow schema.
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde_json::{Value, json};
+
+use crate::cli::SchemaTarget;
+use crate::config::{AgentCatalogFile, DEFAULT_SERVER_MAX_BODY_BYTES, ProfilesFile};
+use crate::telemetry::DEFAULT_TELEMETRY_REDACT_FIELDS;
+
+/// One documented field: name, description and, when resolution applies one,
+/// the default.
+pub type FieldDoc = (&'static str, &'static str, Option<Value>);
+
+pub const PROFILES_SCHEMA_FILE: &str = "profiles.schema.json";
+pub const AGENTS_SCHEMA_FILE: &str = "agents.schema.json";
+
+pub fn profile_field_docs() -> Vec<FieldDoc> {
+    vec![
+        (
+            "provider",
+            "Model provider; `auto` picks one from the API key environment variables.",
+            Some(json!("auto")),
+        ),
+        (
+            "model",
+            "Model id, or `provider/model` shorthand that also sets `provider`. Defaults to the \
+             provider's default model.",
+            None,
+        ),
+        (
+            "api_key",
+            "Provider API key. Prefer the provider's environment variable or a `${VAR}` reference.",
+            None,
+        ),
+        ("ollama_host", "Ollama server URL.", None),
+        (
+            "app_name",
+            "ADK app name sessions are stored under.",
+            Some(json!("zavora-cli")),
+        ),
+        ("user_id", "Session user id.", Some(json!("local-user"))),
+        (
+            "session_id",
+            "Session used when `--session-id` is not given.",
+            Some(json!("default-session")),
+        ),
+        (
+            "session_backend",
+            "Where chat history is stored.",
+            Some(json!("memory")),
+        ),
+        (
+            "session_db_url",
+            "SQLite URL for the sqlite session backend. Defaults to `sessions.db` in the \
+             workspace `.zavora/`.",
+            None,
+        ),
+        (
+            "session_encryption_key_env",
+            "Environment variable holding the key that encrypts event content in the sqlite \
+             session database (AES-256-GCM). Unset means sessions are stored in plaintext.",
+            None,
+        ),
+        (
+            "session_busy_retry_attempts",
+            "Retries when the sqlite database is locked by another process.",
+            Some(json!(crate::session::DEFAULT_SESSION_BUSY_RETRY_ATTEMPTS)),
+        ),
+        (
+            "session_busy_retry_max_ms",
+            "Backoff cap for those retries, in milliseconds.",
+            Some(json!(crate::session::DEFAULT_SESSION_BUSY_RETRY_MAX_MS)),
+        ),
+        (
+            "read_only",
+            "Refuse mutating tools and commands.",
+            Some(json!(false)),
+        ),
+        (
+            "strict_final_text",
+            "Fail a prompt whose run ends on a tool result instead of final text.",
+            Some(json!(false)),
+        ),
+        (
+            "pager",
+            "Page long `ask` and `sessions show` output through `$PAGER`.",
+            Some(json!(true)),
+        ),
+        (
+            "retrieval_backend",
+            "Source of retrieved prompt context.",
+            Some(json!("disabled")),
+        ),
+        (
+            "retrieval_doc_path",
+            "Document the retrieval backend reads.",
+            None,
+        ),
+        (
+            "retrieval_max_chunks",
+            "Chunks added to each prompt (at least 1).",
+            Some(json!(3)),
+        ),
+        (
+            "retrieval_max_chars",
+            "Cap on retrieved context characters (at least 256).",
+            Some(json!(4000)),
+        ),
+        (
+            "retrieval_min_score",
+            "Minimum chunk score on the 0-100 scale.",
+            Some(json!(1)),
+        ),
+        (
+            "retrieval_chunking",
+            "How documents are split into chunks.",
+            Some(json!("paragraph")),
+        ),
+        (
+            "retrieval_query_strategy",
+            "Chat retrieval query: `latest`, `window` or `summary`.",
+            Some(json!("latest")),
+        ),
+        (
+            "retrieval_query_window",
+            "User turns in a `window` retrieval query (at least 1).",
+            Some(json!(crate::retrieval_query::DEFAULT_RETRIEVAL_QUERY_WINDOW)),
+        ),
+        (
+            "retrieval_chunk_size",
+            "Chunk size in characters for `fixed` chunking.",
+            Some(json!(crate::chunking::DEFAULT_CHUNK_SIZE)),
+        ),
+        (
+            "retrieval_chunk_overlap",
+            "Overlap between `fixed` chunks, in characters.",
+            Some(json!(crate::chunking::DEFAULT_CHUNK_OVERLAP)),
+        ),
+        (
+            "retrieval_watch",
+            "Reload an edited retrieval doc: `off`, `on-demand` or `poll`.",
+            Some(json!("on-demand")),
+        ),
+        (
+            "extends",
+            "Parent profile; its fields apply first and this profile's set fields override them.",
+            None,
+        ),
+        (
+            "merge_lists",
+            "With `extends`, append list fields to the parent's instead of replacing them.",
+            Some(json!(false)),
+        ),
+        (
+            "memory_backend",
+            "Long-term memory store for `remember`/`recall`.",
+            Some(json!("disabled")),
+        ),
+        (
+            "memory_recall_max_facts",
+            "Remembered facts recalled into each prompt.",
+            Some(json!(5)),
+        ),
+        (
+            "tool_confirmation_mode",
+            "Which tool calls ask for confirmation.",
+            Some(json!("mcp-only")),
+        ),
+        (
+            "require_confirm_tool",
+            "Tools that always ask before running.",
+            None,
+        ),
+        ("approve_tool", "Tools approved without asking.", None),
+        (
+            "tool_timeout_secs",
+            "Timeout for one tool call (at least 1).",
+            Some(json!(45)),
+        ),
+        (
+            "slow_tool_warn_secs",
+            "Chat notice when a tool runs longer; 0 disables.",
+            Some(json!(crate::streaming::DEFAULT_SLOW_TOOL_WARN_SECS)),
+        ),
+        (
+            "max_runtime_secs",
+            "Abort a command that runs longer (exit 5); 0 disables. Chat and servers are exempt.",
+            Some(json!(0)),
+        ),
+        (
+            "chat_show_tool_results",
+            "How chat shows tool results.",
+            Some(json!("off")),
+        ),
+        (
+            "chat_tool_result_max_chars",
+            "Truncation limit for `full` tool result display.",
+            Some(json!(crate::tool_output::DEFAULT_TOOL_RESULT_MAX_CHARS)),
+        ),
+        (
+            "chat_tool_annotations",
+            "Inline `· running tool(...)` / `✓ tool done` lines in chat: `on` (inline when stdout \
+             is a terminal, stderr otherwise), `stderr-only` or `off`.",
+            Some(json!("on")),
+        ),
+        (
+            "tool_schema_strategy",
+            "`full` sends every tool declaration with each model request; `relevant` sends the \
+             `tool_schema_top_k` tools that best match the prompt, plus tools it names, tools \
+             already used in the session and the core file and shell tools.",
+            Some(json!("full")),
+        ),
+        (
+            "tool_schema_top_k",
+            "Tools picked by relevance when `tool_schema_strategy = \"relevant\"` (at least 1).",
+            Some(json!(crate::tools::schema_pruning::DEFAULT_TOOL_SCHEMA_TOP_K)),
+        ),
+        (
+            "tool_output_max_chars",
+            "Cap on any tool result sent to the model; 0 disables.",
+            Some(json!(
+                crate::tools::output_cap::DEFAULT_TOOL_OUTPUT_MAX_CHARS
+            )),
+        ),
+        (
+            "max_tool_calls_per_prompt",
+            "Tool calls allowed per prompt; 0 means unlimited.",
+            Some(json!(0)),
+        ),
+        (
+            "tool_call_budgets",
+            "Tool name or `*` pattern to calls allowed per prompt.",
+            None,
+        ),
+        (
+            "tool_retry_attempts",
+            "Attempts for a failing tool call (at least 1).",
+            Some(json!(2)),
+        ),
+        (
+            "tool_retry_delay_ms",
+            "Delay between tool call attempts, in milliseconds.",
+            Some(json!(500)),
+        ),
+        (
+            "tool_arg_retry_hint",
+            "Tell the model how to fix tool arguments that fail schema validation.",
+            Some(json!(true)),
+        ),
+        (
+            "mcp_failure_threshold",
+            "Consecutive discovery failures after which an MCP server is skipped until its \
+             backoff window passes (0 disables the circuit breaker).",
+            Some(json!(crate::mcp_breaker::DEFAULT_MCP_FAILURE_THRESHOLD)),
+        ),
+        (
+            "mcp_breaker_max_secs",
+            "Longest backoff, in seconds, before a skipped MCP server is probed again.",
+            Some(json!(crate::mcp_breaker::DEFAULT_MCP_BREAKER_MAX_SECS)),
+        ),
+        (
+            "telemetry_enabled",
+            "Write telemetry events.",
+            Some(json!(true)),
+        ),
+        (
+            "telemetry_path",
+            "Telemetry JSONL file. Defaults to `telemetry/events.jsonl` in the state dir.",
+            None,
+        ),
+        (
+            "telemetry_capture_environment",
+            "Add the environment block to `command.started` events.",
+            Some(json!(true)),
+        ),
+        (
+            "telemetry_redact_fields",
+            "Payload fields (and `*_<field>` keys) passed through the guardrail and sqlite \
+             redactors before an event is written.",
+            Some(json!(DEFAULT_TELEMETRY_REDACT_FIELDS)),
+        ),
+        (
+            "telemetry_redaction",
+            "`off` skips telemetry redaction, but only together with `--show-sensitive-config`.",
+            Some(json!("on")),
+        ),
+        (
+            "telemetry_export",
+            "Also send written events to an OTLP/HTTP logs `endpoint` (`format = \"otlp-json\"`, \
+             optional `headers`), batched in the background; dropped on overflow or failure.",
+            None,
+        ),
+        (
+            "guardrail_input_mode",
+            "Guardrail action on prompts.",
+            Some(json!("disabled")),
+        ),
+        (
+            "guardrail_output_mode",
+            "Guardrail action on model output.",
+            Some(json!("disabled")),
+        ),
+        (
+            "guardrail_retrieval_mode",
+            "Guardrail action on retrieved context.",
+            Some(json!("observe")),
+        ),
+        (
+            "content_injection_mode",
+            "Action on retrieved chunks that look like prompt injections: `observe` records \
+             them, `strip` removes the matching lines, `quarantine` drops the chunk and warns.",
+            Some(json!("observe")),
+        ),
+        (
+            "content_injection_patterns",
+            "Extra phrases (case-insensitive) screened for in retrieved chunks, on top of the \
+             built-in heuristics.",
+            Some(json!([])),
+        ),
+        (
+            "fs_write_secret_policy",
+            "Action when `fs_write`/`apply_patch` content looks like a secret: `block` refuses \
+             the write, `redact` masks the values, `allow` writes it unchanged.",
+            Some(json!("block")),
+        ),
+        (
+            "fs_write_secret_allowlist",
+            "Wildcard patterns for files (`*.lock`) whose content skips the entropy and \
+             guardrail-term heuristics, or for tokens (`sha512-*`) that are never flagged.",
+            Some(json!([])),
+        ),
+        (
+            "output_sanitize",
+            "How control characters and escape sequences in model output are printed.",
+            Some(json!("strip")),
+        ),
+        (
+            "guardrail_terms",
+            "Guardrail terms: `\"term\"`, `\"term:severity\"` or `{ term, severity }`. Built-in \
+             terms apply when empty.",
+            None,
+        ),
+        (
+            "guardrail_severity_actions",
+            "`low`, `medium` or `high` to the guardrail mode for terms of that severity.",
+            None,
+        ),
+        (
+            "guardrail_redact_replacement",
+            "Text that replaces redacted terms.",
+            Some(json!("[REDACTED]")),
+        ),
+        (
+            "server_max_body_bytes",
+            "Request body limit for `server` (at least 1024).",
+            Some(json!(DEFAULT_SERVER_MAX_BODY_BYTES)),
+        ),
+        (
+            "server_max_prompt_chars",
+            "Prompt length limit for `server`.",
+            Some(json!(32_000)),
+        ),
+        (
+            "server_legacy_status_codes",
+            "Keep the pre-422 status codes for `server` validation failures.",
+            Some(json!(false)),
+        ),
+        (
+            "server_per_user_max_concurrent",
+            "Concurrent `server` requests per user; 0 means unlimited.",
+            Some(json!(0)),
+        ),
+        (
+            "server_per_user_requests_per_minute",
+            "`server` requests per user per minute; 0 means unlimited.",
+            Some(json!(0)),
+        ),
+        (
+            "chat_notify_command",
+            "Command run when a chat response takes longer than `chat_notify_threshold_secs`.",
+            None,
+        ),
+        (
+            "chat_notify_threshold_secs",
+            "Response time that triggers `chat_notify_command`.",
+            Some(json!(30)),
+        ),
+        (
+            "chat_idle_timeout_secs",
+            "End chat after this many idle seconds at the prompt; 0 disables.",
+            Some(json!(0)),
+        ),
+        (
+            "chat_idle_transcript_dir",
+            "Directory for the JSON transcript written on idle exit; empty disables.",
+            None,
+        ),
+        (
+            "chat_idle_snapshot_db_url",
+            "SQLite URL memory-backend sessions are flushed to on idle exit; empty disables.",
+            None,
+        ),
+        (
+            "daily_token_budget",
+            "Tokens this profile may use per UTC day.",
+            None,
+        ),
+        (
+            "daily_cost_budget",
+            "Estimated cost this profile may spend per UTC day.",
+            None,
+        ),
+        (
+            "usage_budget_mode",
+            "What happens once a daily budget is spent.",
+            Some(json!("warn")),
+        ),
+        (
+            "usage_cost_per_1k_tokens",
+            "Price per 1,000 tokens for cost estimates; 0 disables them.",
+            Some(json!(0.0)),
+        ),
+        (
+            "update_check",
+            "Check for a newer release at startup.",
+            Some(json!(false)),
+        ),
+        (
+            "update_check_url",
+            "Release feed the update check reads.",
+            Some(json!(crate::update_check::DEFAULT_UPDATE_CHECK_URL)),
+        ),
+        (
+            "ask_cache_ttl_secs",
+            "Freshness of `ask --cache` answers.",
+            Some(json!(crate::answer_cache::DEFAULT_ASK_CACHE_TTL_SECS)),
+        ),
+        (
+            "ask_default_ephemeral",
+            "Run `ask` in a throwaway in-memory session unless `--no-ephemeral` is passed.",
+            Some(json!(false)),
+        ),
+        (
+            "execute_bash_sandbox",
+            "Command template `execute_bash` runs commands through, with `{workspace}` and \
+             `{command}` placeholders.",
+            None,
+        ),
+        (
+            "execute_bash_summarize",
+            "Attach a structured `summary` (test counts, failing tests, changed files, compile \
+             errors) to `execute_bash` results for cargo, git, pytest and npm/yarn test commands.",
+            Some(json!(false)),
+        ),
+        (
+            "providers",
+            "Per-provider `base_url` and `extra_headers` overrides; `azure-openai` and \
+             `openai-compatible` also take `endpoint` (alias of `base_url`), `deployment`, \
+             `api_version` and `api_key_env`.",
+            None,
+        ),
+        (
+            "mcp_servers",
+            "MCP servers whose tools are offered to the model.",
+            None,
+        ),
+        (
+            "agent_catalog_paths",
+            "Extra agent catalog files or directories, loaded before `ZAVORA_AGENT_PATH` entries.",
+            None,
+        ),
+        (
+            "permission_rules",
+            "`always_deny`, `always_allow` and `always_ask` patterns (`tool` or `tool:content`); \
+             deny wins.",
+            None,
+        ),
+        (
+            "retention",
+            "`telemetry_days`, `telemetry_max_bytes`, `checkpoint_days`, `answer_cache_days`, \
+             `tool_output_days`, `transcript_days` for the daily startup maintenance pass; unset \
+             kinds are kept.",
+            None,
+        ),
+        (
+            "compaction_threshold",
+            "Context window fraction that triggers auto-compaction.",
+            Some(json!(0.75)),
+        ),
+        (
+            "compaction_target",
+            "Context window fraction compaction stops at.",
+            Some(json!(0.10)),
+        ),
+        (
+            "history_trim_threshold",
+            "Context window fraction above which chat leaves the oldest exchanges out of a turn \
+             (kept in the session); 0 disables.",
+            Some(json!(0.9)),
+        ),
+    ]
+}
+
+pub fn agent_field_docs() -> Vec<FieldDoc> {
+    vec![
+        (
+            "description",
+            "One-line summary shown by `agents list`.",
+            None,
+        ),
+        (
+            "instruction",
+            "Agent-specific instruction added to the system prompt.",
+            None,
+        ),
+        ("provider", "Provider, overriding the profile's.", None),
+        (
+            "model",
+            "Model id or `provider/model` shorthand, overriding the profile's.",
+            None,
+        ),
+        (
+            "tool_confirmation_mode",
+            "Overrides the profile's tool confirmation mode.",
+            None,
+        ),
+        (
+            "resource_paths",
+            "Paths listed to the model as resource hints.",
+            None,
+        ),
+        (
+            "allow_tools",
+            "Tool patterns this agent may use; empty allows all.",
+            None,
+        ),
+        (
+            "deny_tools",
+            "Tool patterns this agent may never use.",
+            None,
+        ),
+        (
+            "hooks",
+            "Hook point (`agent_spawn`, `prompt_submit`, `pre_tool`, `post_tool`, `stop`) to \
+             the commands run there.",
+            None,
+        ),
+        (
+            "mcp_servers",
+            "Merged over the profile's `mcp_servers` when this agent is active; an entry with \
+             the same name replaces the profile's.",
+            None,
+        ),
+        (
+            "starters",
+            "Up to 9 `{ label, prompt }` conversation starters shown when chat starts with this \
+             agent and picked with `/starters`; `<name>` in a prompt is asked for first.",
+            None,
+        ),
+    ]
+}
+
+/// Set `description` and `default` on the properties of `definition`.
+fn annotate_fields(schema: &mut Value, definition: &str, docs: &[FieldDoc]) {
+    let pointer = format!("/definitions/{definition}/properties");
+    let Some(properties) = schema.pointer_mut(&pointer).and_then(Value::as_object_mut) else {
+        return;
+    };
+    for (name, description, default) in docs {
+        let Some(property) = properties.get_mut(*name).and_then(Value::as_object_mut) else {
+            continue;
+        };
+        property.insert("description".to_string(), json!(description));
+        if let Some(default) = default {
+            property.insert("default".to_string(), default.clone());
+        }
+    }
+}
+
+pub fn profiles_schema() -> Value {
+    let mut schema = serde_json::to_value(schemars::schema_for!(ProfilesFile))
+        .expect("schemars output is valid JSON");
+    schema["title"] = json!("zavora-cli profiles (config.toml)");
+    annotate_fields(&mut schema, "ProfileConfig", &profile_field_docs());
+    schema
+}
+
+pub fn agents_schema() -> Value {
+    let mut schema = serde_json::to_value(schemars::schema_for!(AgentCatalogFile))
+        .expect("schemars output is valid JSON");
+    schema["title"] = json!("zavora-cli agent catalog (agents.toml)");
+    schema["properties"]["agents"]["description"] = json!("Agent name to its definition.");
+    annotate_fields(&mut schema, "AgentFileConfig", &agent_field_docs());
+    schema
+}
+
+/// File name and schema for each document `target` covers.
+pub fn config_schemas(target: SchemaTarget) -> Vec<(&'static str, Value)> {
+    let mut schemas = Vec::new();
+    if matches!(target, SchemaTarget::Profiles | SchemaTarget::All) {
+        schemas.push((PROFILES_SCHEMA_FILE, profiles_schema()));
+    }
+    if matches!(target, SchemaTarget::Agents | SchemaTarget::All) {
+        schemas.push((AGENTS_SCHEMA_FILE, agents_schema()));
+    }
+    schemas
+}
+
+/// How to point taplo and VS Code (Even Better TOML) at written schemas.
+pub fn format_schema_usage(written: &[PathBuf]) -> String {
+    let find = |file: &str| written.iter().find(|path| path.ends_with(file));
+    let mut directives = String::new();
+    let mut rules = String::new();
+    let mut associations = Vec::new();
+    for (file, toml_file) in [
+        (PROFILES_SCHEMA_FILE, "config.toml"),
+        (AGENTS_SCHEMA_FILE, "agents.toml"),
+    ] {
+        let Some(path) = find(file) else {
+            continue;
+        };
+        let path = path.display();
+        let pattern = toml_file.replace('.', "\\\\.");
+        directives.push_str(&format!(
+            "  #:schema {path}    (first line of {toml_file})\n"
+        ));
+        rules.push_str(&format!(
+            "  [[rule]]\n  include = [\"**/{toml_file}\"]\n  schema = {{ path = \"{path}\" }}\n"
+        ));
+        associations.push(format!("    \".*/{pattern}$\": \"file://{path}\""));
+    }
+    format!(
+        "Reference the schemas with a taplo directive:\n{directives}\nor a rule in \
+         .taplo.toml:\n{rules}\nor in VS Code settings.json (Even Better TOML):\n  \
+         \"evenBetterToml.schema.associations\": {{\n{}\n  }}\n",
+        associations.join(",\n")
+    )
+}
+
+fn write_schema(dir: &Path, file: &str, schema: &Value) -> Result<PathBuf> {
+    let path = dir.join(file);
+    let body = serde_json::to_string_pretty(schema).context("failed to encode schema")?;
+    std::fs::write(&path, format!("{body}\n"))
+        .with_context(|| format!("failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+pub fn run_config_schema(target: SchemaTarget, output: Option<&str>) -> Result<()> {
+    let schemas = config_schemas(target);
+    let Some(dir) = output else {
+        let [(_, schema)] = schemas.as_slice() else {
+            anyhow::bail!(
+                "config schema --target all writes one file per schema; pass --output DIR or \
+                 pick --target profiles|agents"
+            );
+        };
+        println!(
+            "{}",
+            serde_json::to_string_pretty(schema).context("failed to encode schema")?
+        );
+        return Ok(());
+    };
+    let dir = Path::new(dir);
+    std::fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    let dir = std::fs::canonicalize(dir)
+        .with_context(|| format!("failed to resolve {}", dir.display()))?;
+    let mut written = Vec::new();
+    for (file, schema) in &schemas {
+        let path = write_schema(&dir, file, schema)?;
+        println!("Wrote {}", path.display());
+        written.push(path);
+    }
+    println!();
+    print!("{}", format_schema_usage(&written));
+    Ok(())
+}
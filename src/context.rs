@@ -77,7 +77,7 @@ pub struct ContextUsage {
 }
 
 /// Estimated overhead for system prompt + tool declarations not captured in session events.
-const PROMPT_OVERHEAD_TOKENS: usize = 1500;
+pub const PROMPT_OVERHEAD_TOKENS: usize = 1500;
 
 impl ContextUsage {
     pub fn total_chars(&self) -> usize {
@@ -210,6 +210,29 @@ pub enum BudgetLevel {
 
 use adk_rust::Event;
 
+/// Text and function call/response characters in one event.
+pub fn event_part_chars(event: &Event) -> (usize, usize) {
+    let (mut text_chars, mut fn_chars) = (0usize, 0usize);
+    if let Some(content) = &event.llm_response.content {
+        for part in &content.parts {
+            match part {
+                adk_rust::Part::Text { text } => text_chars += text.len(),
+                adk_rust::Part::FunctionCall { name, args, .. } => {
+                    fn_chars += name.len() + args.to_string().len();
+                }
+                adk_rust::Part::FunctionResponse {
+                    function_response, ..
+                } => {
+                    fn_chars +=
+                        function_response.name.len() + function_response.response.to_string().len();
+                }
+                _ => {}
+            }
+        }
+    }
+    (text_chars, fn_chars)
+}
+
 /// Build a `ContextUsage` snapshot from session events and provider name.
 pub fn compute_context_usage(events: &[Event], provider: &str, model: &str) -> ContextUsage {
     let mut user_chars = 0usize;
@@ -219,24 +242,7 @@ pub fn compute_context_usage(events: &[Event], provider: &str, model: &str) -> C
     let mut api_total_tokens = 0usize;
 
     for event in events {
-        let (mut text_chars, mut fn_chars) = (0usize, 0usize);
-        if let Some(content) = &event.llm_response.content {
-            for part in &content.parts {
-                match part {
-                    adk_rust::Part::Text { text } => text_chars += text.len(),
-                    adk_rust::Part::FunctionCall { name, args, .. } => {
-                        fn_chars += name.len() + args.to_string().len();
-                    }
-                    adk_rust::Part::FunctionResponse {
-                        function_response, ..
-                    } => {
-                        fn_chars += function_response.name.len()
-                            + function_response.response.to_string().len();
-                    }
-                    _ => {}
-                }
-            }
-        }
+        let (text_chars, fn_chars) = event_part_chars(event);
 
         // Track the latest API-reported total token count (includes system prompt, tools, full history)
         if let Some(meta) = &event.llm_response.usage_metadata {
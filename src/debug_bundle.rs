@@ -0,0 +1,468 @@
+//! Reproduction bundles for bug reports (`debug bundle`).
+//!
+//! A bundle collects what maintainers need to replay one `ask`/`chat` run:
+//! the resolved config, the run's telemetry events, the assembled prompt,
+//! the session/eval ids involved, build info and an optional `chat --script`
+//! replay file, plus a `manifest.json` hashing each file. Telemetry never
+//! stores prompt text, so the user part of the prompt is only included when
+//! `--prompt` re-supplies it; otherwise the bundle holds the system
+//! instruction alone.
+//!
+//! Every file goes through the existing redaction (sqlite URLs, guardrail
+//! terms and PII for the prompt and replay) and then a final pass replacing
+//! the value of any secret-looking environment variable of this process.
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::build_info::{build_info, format_build_info};
+use crate::cli::GuardrailMode;
+use crate::config::RuntimeConfig;
+use crate::error::{redact_sensitive_text, redact_sqlite_url_value};
+use crate::guardrail::redact_text_with_matcher;
+use crate::prompt_assembly::{
+    PromptEnvironment, assemble_prompt, render_prompt_with_markers, system_instruction_sections,
+};
+use crate::retrieval::RetrievalService;
+use crate::session_bundle::{BUNDLE_MANIFEST_FILE, sha256_hex};
+use crate::telemetry::{TelemetrySink, read_telemetry_lines};
+
+pub const DEBUG_BUNDLE_FORMAT: &str = "zavora-debug-bundle/v1";
+
+/// Commands `--last-run` picks from.
+const BUNDLED_COMMANDS: &[&str] = &["ask", "chat"];
+
+/// Environment variable names containing one of these are treated as secrets.
+const SECRET_ENV_MARKERS: &[&str] = &[
+    "KEY",
+    "TOKEN",
+    "SECRET",
+    "PASSWORD",
+    "PASSWD",
+    "CREDENTIAL",
+    "AUTH",
+    "DATABASE_URL",
+];
+
+/// Shorter values are too likely to collide with ordinary text.
+const MIN_SECRET_ENV_CHARS: usize = 8;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RunSelector {
+    /// The newest `ask`/`chat` run other than the current one.
+    Last,
+    Id(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct DebugBundleFile {
+    pub name: String,
+    pub bytes: u64,
+    pub sha256: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct DebugBundleManifest {
+    pub format: String,
+    pub created_at: String,
+    pub run_id: String,
+    pub command: Option<String>,
+    pub session_ids: Vec<String>,
+    pub eval_datasets: Vec<String>,
+    pub event_count: usize,
+    /// `reassembled` when `--prompt` was given, `system-only` otherwise.
+    pub prompt: String,
+    pub cli_version: String,
+    pub git_sha: String,
+    pub files: Vec<DebugBundleFile>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DebugBundle {
+    pub manifest: DebugBundleManifest,
+    /// Bundle files in manifest order, already redacted.
+    pub files: Vec<(String, String)>,
+}
+
+/// What `build_debug_bundle` reads besides the config.
+#[derive(Debug, Clone, Copy)]
+pub struct DebugBundleInputs<'a> {
+    pub run: &'a RunSelector,
+    pub telemetry_lines: &'a [String],
+    /// Prompt to reassemble the user content from.
+    pub prompt: Option<&'a str>,
+    /// Contents of a `chat --script` replay file.
+    pub replay: Option<&'a str>,
+    /// `(name, value)` pairs from [`secret_env_values`].
+    pub env_secrets: &'a [(String, String)],
+}
+
+/// Secret-looking environment variables with values long enough to scan for,
+/// longest value first so overlapping secrets are replaced whole.
+pub fn secret_env_values(
+    vars: impl IntoIterator<Item = (String, String)>,
+) -> Vec<(String, String)> {
+    let mut secrets = vars
+        .into_iter()
+        .filter(|(name, value)| {
+            let upper = name.to_ascii_uppercase();
+            value.trim().chars().count() >= MIN_SECRET_ENV_CHARS
+                && SECRET_ENV_MARKERS
+                    .iter()
+                    .any(|marker| upper.contains(marker))
+        })
+        .collect::<Vec<(String, String)>>();
+    secrets.sort_by(|left, right| {
+        right
+            .1
+            .len()
+            .cmp(&left.1.len())
+            .then_with(|| left.0.cmp(&right.0))
+    });
+    secrets
+}
+
+/// Replace each secret value with `[REDACTED:<NAME>]`.
+pub fn redact_env_secrets(text: &str, secrets: &[(String, String)]) -> String {
+    secrets
+        .iter()
+        .fold(text.to_string(), |text, (name, value)| {
+            text.replace(value.as_str(), &format!("[REDACTED:{name}]"))
+        })
+}
+
+/// Run id, command and events of the selected run. `exclude_run_id` is the
+/// current `debug bundle` invocation, which `--last-run` must skip.
+pub fn select_run_events(
+    lines: &[String],
+    run: &RunSelector,
+    exclude_run_id: &str,
+) -> Result<(String, Vec<Value>)> {
+    let events = lines
+        .iter()
+        .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+        .collect::<Vec<Value>>();
+    let run_id = match run {
+        RunSelector::Id(id) => id.clone(),
+        RunSelector::Last => events
+            .iter()
+            .rev()
+            .find(|event| {
+                event["run_id"]
+                    .as_str()
+                    .is_some_and(|id| id != exclude_run_id)
+                    && event["command"]
+                        .as_str()
+                        .is_some_and(|command| BUNDLED_COMMANDS.contains(&command))
+            })
+            .and_then(|event| event["run_id"].as_str())
+            .map(str::to_string)
+            .context("no ask or chat run found in telemetry; pass --run-id or enable telemetry")?,
+    };
+    let events = events
+        .into_iter()
+        .filter(|event| event["run_id"].as_str() == Some(run_id.as_str()))
+        .collect::<Vec<Value>>();
+    if events.is_empty() {
+        anyhow::bail!("no telemetry events for run '{run_id}'");
+    }
+    Ok((run_id, events))
+}
+
+fn string_values(events: &[Value], field: &str, event_prefix: &str) -> Vec<String> {
+    events
+        .iter()
+        .filter(|event| {
+            event["event"]
+                .as_str()
+                .is_some_and(|name| name.starts_with(event_prefix))
+        })
+        .filter_map(|event| event[field].as_str())
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+        .collect::<BTreeSet<String>>()
+        .into_iter()
+        .collect()
+}
+
+/// `{cfg:#?}` with MCP server env values and the session DB URL masked
+/// before the generic redaction runs.
+fn redacted_config(cfg: &RuntimeConfig) -> String {
+    let mut cfg = cfg.clone();
+    cfg.session_db_url = redact_sqlite_url_value(&cfg.session_db_url);
+    for server in &mut cfg.mcp_servers {
+        for value in server.env.values_mut() {
+            *value = "<redacted>".to_string();
+        }
+    }
+    format!("{cfg:#?}\n")
+}
+
+/// The prompt with guardrail redaction applied. Guardrails run in redact
+/// mode here so a blocking config still yields a (redacted) bundle.
+fn redacted_prompt(
+    cfg: &RuntimeConfig,
+    telemetry: &TelemetrySink,
+    retrieval: &dyn RetrievalService,
+    prompt: Option<&str>,
+) -> Result<String> {
+    let env = PromptEnvironment::current();
+    let rendered = match prompt {
+        Some(prompt) => {
+            let mut redacting = cfg.clone();
+            redacting.guardrail_input_mode = GuardrailMode::Redact;
+            redacting.guardrail_retrieval_mode = GuardrailMode::Redact;
+            redacting.guardrail_severity_actions.clear();
+            let assembled =
+                assemble_prompt(&redacting, &env, prompt, "prompt", retrieval, telemetry)?;
+            render_prompt_with_markers(&assembled)
+        }
+        None => {
+            let mut out = String::from("===== SYSTEM INSTRUCTION =====\n");
+            for section in system_instruction_sections(Some(cfg), &env) {
+                out.push_str(&format!(
+                    "----- [{}] -----\n{}\n",
+                    section.source, section.text
+                ));
+            }
+            out.push_str("===== USER CONTENT =====\n(not recorded; pass --prompt to include it)\n");
+            out
+        }
+    };
+    Ok(redact_guardrail_terms(cfg, &rendered))
+}
+
+fn redact_guardrail_terms(cfg: &RuntimeConfig, text: &str) -> String {
+    redact_text_with_matcher(
+        text,
+        &cfg.guardrail_matcher(),
+        &cfg.guardrail_redact_replacement,
+    )
+}
+
+/// Collect and redact every bundle file; nothing is written.
+pub fn build_debug_bundle(
+    cfg: &RuntimeConfig,
+    telemetry: &TelemetrySink,
+    retrieval: &dyn RetrievalService,
+    inputs: DebugBundleInputs<'_>,
+) -> Result<DebugBundle> {
+    let (run_id, events) =
+        select_run_events(inputs.telemetry_lines, inputs.run, &telemetry.run_id)?;
+    let events_jsonl = events
+        .iter()
+        .map(|event| format!("{event}\n"))
+        .collect::<String>();
+
+    let mut files = vec![
+        ("config.txt".to_string(), redacted_config(cfg)),
+        ("telemetry.jsonl".to_string(), events_jsonl),
+        (
+            "prompt.txt".to_string(),
+            redacted_prompt(cfg, telemetry, retrieval, inputs.prompt)?,
+        ),
+        ("build.txt".to_string(), format_build_info(&build_info())),
+    ];
+    if let Some(replay) = inputs.replay {
+        files.push((
+            "replay.txt".to_string(),
+            redact_guardrail_terms(cfg, replay),
+        ));
+    }
+    for (_, text) in &mut files {
+        *text = redact_env_secrets(&redact_sensitive_text(text), inputs.env_secrets);
+    }
+
+    let info = build_info();
+    let manifest = DebugBundleManifest {
+        format: DEBUG_BUNDLE_FORMAT.to_string(),
+        created_at: chrono::Utc::now().to_rfc3339(),
+        command: events
+            .iter()
+            .find_map(|event| event["command"].as_str())
+            .map(str::to_string),
+        session_ids: string_values(&events, "session_id", ""),
+        eval_datasets: string_values(&events, "dataset", "eval."),
+        event_count: events.len(),
+        prompt: if inputs.prompt.is_some() {
+            "reassembled"
+        } else {
+            "system-only"
+        }
+        .to_string(),
+        cli_version: info.cli_version.to_string(),
+        git_sha: info.git_sha.to_string(),
+        files: files
+            .iter()
+            .map(|(name, text)| DebugBundleFile {
+                name: name.clone(),
+                bytes: text.len() as u64,
+                sha256: sha256_hex(text.as_bytes()),
+            })
+            .collect(),
+        run_id,
+    };
+    Ok(DebugBundle { manifest, files })
+}
+
+impl DebugBundle {
+    pub fn manifest_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(&self.manifest)
+            .context("failed to serialize debug bundle manifest")
+    }
+
+    /// `--review` output: the manifest and every file, as they would be
+    /// written.
+    pub fn render_review(&self) -> Result<String> {
+        let mut out = format!(
+            "===== {BUNDLE_MANIFEST_FILE} =====\n{}\n",
+            self.manifest_json()?
+        );
+        for (name, text) in &self.files {
+            out.push_str(&format!(
+                "===== {name} ({} bytes) =====\n{text}",
+                text.len()
+            ));
+            if !text.ends_with('\n') {
+                out.push('\n');
+            }
+        }
+        Ok(out)
+    }
+
+    /// Write the `.tar.gz`; refuses to overwrite. Returns its size in bytes.
+    pub fn write(&self, output: &Path) -> Result<u64> {
+        if output.exists() {
+            anyhow::bail!(
+                "debug bundle '{}' already exists; refusing to overwrite",
+                output.display()
+            );
+        }
+        if let Some(parent) = output.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent).with_context(|| {
+                format!("failed to create bundle directory '{}'", parent.display())
+            })?;
+        }
+        let file = File::create(output)
+            .with_context(|| format!("failed to create debug bundle '{}'", output.display()))?;
+        let mut builder = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+        let manifest = self.manifest_json()?;
+        let entries = std::iter::once((BUNDLE_MANIFEST_FILE, manifest.as_str())).chain(
+            self.files
+                .iter()
+                .map(|(name, text)| (name.as_str(), text.as_str())),
+        );
+        for (name, text) in entries {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(text.len() as u64);
+            header.set_mode(0o644);
+            header.set_mtime(chrono::Utc::now().timestamp().max(0) as u64);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, name, text.as_bytes())
+                .with_context(|| format!("failed to append '{name}' to debug bundle"))?;
+        }
+        builder
+            .into_inner()
+            .and_then(|encoder| encoder.finish())
+            .context("failed to finalize debug bundle")?;
+        std::fs::metadata(output)
+            .map(|meta| meta.len())
+            .with_context(|| format!("failed to stat debug bundle '{}'", output.display()))
+    }
+}
+
+pub struct DebugBundleOptions {
+    pub run: RunSelector,
+    pub prompt: Option<String>,
+    pub replay: Option<String>,
+    pub output: Option<String>,
+    pub review: bool,
+}
+
+pub fn run_debug_bundle(
+    cfg: &RuntimeConfig,
+    telemetry: &TelemetrySink,
+    retrieval: &dyn RetrievalService,
+    options: DebugBundleOptions,
+) -> Result<()> {
+    let telemetry_path = PathBuf::from(&cfg.telemetry_path);
+    if !telemetry_path.exists() {
+        anyhow::bail!(
+            "no telemetry file at '{}'; bundles are built from telemetry, so enable it and \
+             reproduce the run",
+            telemetry_path.display()
+        );
+    }
+    let lines = read_telemetry_lines(&telemetry_path)?;
+    let replay = options
+        .replay
+        .as_deref()
+        .map(|path| {
+            std::fs::read_to_string(path)
+                .with_context(|| format!("failed to read replay file '{path}'"))
+        })
+        .transpose()?;
+    let env_secrets = secret_env_values(std::env::vars());
+    let bundle = build_debug_bundle(
+        cfg,
+        telemetry,
+        retrieval,
+        DebugBundleInputs {
+            run: &options.run,
+            telemetry_lines: &lines,
+            prompt: options.prompt.as_deref(),
+            replay: replay.as_deref(),
+            env_secrets: &env_secrets,
+        },
+    )?;
+
+    if options.review {
+        print!("{}", bundle.render_review()?);
+        return Ok(());
+    }
+    let output = options.output.map(PathBuf::from).unwrap_or_else(|| {
+        PathBuf::from(format!("zavora-debug-{}.tar.gz", bundle.manifest.run_id))
+    });
+    let size = bundle.write(&output)?;
+    println!(
+        "Wrote debug bundle {} ({size} bytes, {} files, run {}).",
+        output.display(),
+        bundle.files.len() + 1,
+        bundle.manifest.run_id
+    );
+    Ok(())
+}
+
+/// File name → text of a written bundle, for verification and tests.
+pub fn read_debug_bundle(path: &Path) -> Result<BTreeMap<String, String>> {
+    use std::io::Read;
+
+    let file = File::open(path)
+        .with_context(|| format!("failed to open debug bundle '{}'", path.display()))?;
+    let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(file));
+    let mut files = BTreeMap::new();
+    for entry in archive
+        .entries()
+        .with_context(|| format!("failed to read debug bundle '{}'", path.display()))?
+    {
+        let mut entry = entry.context("failed to read debug bundle entry")?;
+        let name = entry
+            .path()
+            .context("invalid debug bundle entry path")?
+            .display()
+            .to_string();
+        let mut text = String::new();
+        entry
+            .read_to_string(&mut text)
+            .with_context(|| format!("failed to read '{name}' from debug bundle"))?;
+        files.insert(name, text);
+    }
+    Ok(files)
+}
@@ -1,15 +1,44 @@
+use std::ffi::OsStr;
+
 use anyhow::Result;
 
+use crate::build_info::{build_info, format_build_info};
 use crate::cli::SessionBackend;
-use crate::config::{RuntimeConfig, display_session_db_url};
-use crate::provider::{detect_provider, env_present};
+use crate::config::{ProfilesFile, RuntimeConfig, display_session_db_url};
+use crate::provider::{
+    detect_provider, endpoint_provider_doctor_lines, env_present, format_provider_overrides,
+};
+use crate::read_only::read_only_banner;
 use crate::session::open_sqlite_session_service;
+use crate::session_crypto::session_encryption_doctor_line;
+use crate::telemetry_export::{format_telemetry_export_status, load_export_stats};
+use crate::tools::execute_bash::check_sandbox_binary;
+use crate::trust::{format_trust_status, workspace_trust};
+use crate::update_check::{format_update_check_status, load_update_check_cache};
 
-pub async fn run_doctor(cfg: &RuntimeConfig) -> Result<()> {
+pub async fn run_doctor(profiles: &ProfilesFile, cfg: &RuntimeConfig) -> Result<()> {
     println!(
         "Active profile: '{}' (config: {})",
         cfg.profile, cfg.config_path
     );
+    print!("{}", format_broken_profiles(profiles));
+    print!("{}", format_build_info(&build_info()));
+    println!(
+        "{}",
+        format_update_check_status(
+            cfg.update_check,
+            load_update_check_cache(&cfg.paths.update_check_file()).as_ref()
+        )
+    );
+
+    println!("Paths ({}):", cfg.paths.source.label());
+    for (name, path) in cfg.paths.entries() {
+        println!("- {name}: {path}");
+    }
+    match workspace_trust(&cfg.paths) {
+        Ok(trust) => println!("{}", format_trust_status(&trust)),
+        Err(err) => println!("Workspace trust: unknown ({err:#})"),
+    }
 
     let checks = [
         ("GOOGLE_API_KEY", env_present("GOOGLE_API_KEY")),
@@ -33,6 +62,18 @@ pub async fn run_doctor(cfg: &RuntimeConfig) -> Result<()> {
             println!("Tip: export one provider key or run with --provider ollama");
         }
     }
+    let overrides = format_provider_overrides(&cfg.provider_overrides);
+    if overrides.is_empty() {
+        println!("Provider overrides: none");
+    } else {
+        println!("Provider overrides:");
+        for line in overrides {
+            println!("- {line}");
+        }
+    }
+    for line in endpoint_provider_doctor_lines(cfg, &env_present) {
+        println!("Endpoint provider {line}");
+    }
 
     println!(
         "Session backend: {:?} (session_id: {}, app: {}, user: {})",
@@ -69,12 +110,33 @@ pub async fn run_doctor(cfg: &RuntimeConfig) -> Result<()> {
         cfg.telemetry_enabled, cfg.telemetry_path
     );
     println!(
-        "Guardrails: input_mode={:?} output_mode={:?} terms={} redact_replacement={}",
+        "{}",
+        format_telemetry_export_status(
+            cfg.telemetry_export.as_ref(),
+            cfg.offline,
+            load_export_stats(&cfg.paths.telemetry_export_file()).as_ref()
+        )
+    );
+    match read_only_banner(cfg) {
+        Some(banner) => println!("{banner}"),
+        None => println!("Read-only mode: off"),
+    }
+    println!(
+        "Guardrails: input_mode={:?} output_mode={:?} retrieval_mode={:?} terms={} redact_replacement={}",
         cfg.guardrail_input_mode,
         cfg.guardrail_output_mode,
+        cfg.guardrail_retrieval_mode,
         cfg.guardrail_terms.len(),
         cfg.guardrail_redact_replacement
     );
+    if let Some(template) = cfg.execute_bash_sandbox.as_deref() {
+        println!(
+            "{}",
+            sandbox_doctor_line(template, std::env::var_os("PATH").as_deref())
+        );
+    } else {
+        println!("execute_bash sandbox: off");
+    }
     println!(
         "MCP servers: configured={}, enabled={}",
         cfg.mcp_servers.len(),
@@ -91,22 +153,71 @@ pub async fn run_doctor(cfg: &RuntimeConfig) -> Result<()> {
             display_session_db_url(cfg)
         );
     }
+    println!("{}", session_encryption_doctor_line(cfg).await);
 
     Ok(())
 }
 
-pub async fn run_migrate(cfg: &RuntimeConfig) -> Result<()> {
-    match cfg.session_backend {
-        SessionBackend::Memory => {
-            println!("Session backend is memory; no migration required.");
-        }
-        SessionBackend::Sqlite => {
-            let _service = open_sqlite_session_service(&cfg.session_db_url).await?;
-            println!(
-                "SQLite migrations applied successfully: {}",
-                display_session_db_url(cfg)
-            );
+/// One doctor line for `execute_bash_sandbox`: the wrapper binary must be
+/// installed, or every shell command will fail to launch.
+pub fn sandbox_doctor_line(template: &str, path_env: Option<&OsStr>) -> String {
+    match check_sandbox_binary(template, path_env) {
+        Ok(binary) => format!("execute_bash sandbox: ok ({})", binary.display()),
+        Err(problem) => format!("execute_bash sandbox: error: {problem}"),
+    }
+}
+
+/// Profiles skipped at load time, one per line with the parse error. Empty
+/// when every profile parsed.
+pub fn format_broken_profiles(profiles: &ProfilesFile) -> String {
+    if profiles.broken_profiles.is_empty() {
+        return String::new();
+    }
+    let mut out = format!(
+        "Invalid profiles ({}, skipped):\n",
+        profiles.broken_profiles.len()
+    );
+    for (name, reason) in &profiles.broken_profiles {
+        out.push_str(&format!("- {name}: {reason}\n"));
+    }
+    out
+}
+
+/// `doctor --check-config`: the profile file already parsed (includes merged,
+/// env vars expanded) by the time this runs, so report what was resolved.
+pub fn run_doctor_check_config(profiles: &ProfilesFile, cfg: &RuntimeConfig) {
+    if profiles.broken_profiles.is_empty() {
+        println!("Config check: ok ({})", cfg.config_path);
+    } else {
+        println!(
+            "Config check: {} invalid profile(s) ({})",
+            profiles.broken_profiles.len(),
+            cfg.config_path
+        );
+        print!("{}", format_broken_profiles(profiles));
+    }
+    if profiles.include_chain.is_empty() {
+        println!("Include chain: <no config file>");
+    } else {
+        println!("Include chain (load order, later files override earlier):");
+        for (idx, path) in profiles.include_chain.iter().enumerate() {
+            println!("  {}. {}", idx + 1, path.display());
         }
     }
-    Ok(())
+
+    let mut names = profiles.profiles.keys().collect::<Vec<&String>>();
+    names.sort();
+    println!("Profiles:");
+    if names.is_empty() {
+        println!("  <none>");
+    }
+    for name in names {
+        let marker = if *name == cfg.profile { "*" } else { " " };
+        let source = profiles
+            .profile_sources
+            .get(name)
+            .map(|path| path.display().to_string())
+            .unwrap_or_else(|| "<unknown>".to_string());
+        println!("{marker} {name} (from {source})");
+    }
 }
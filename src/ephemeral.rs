@@ -0,0 +1,95 @@
+//! Ephemeral sessions: `ask --ephemeral`, `chat --ephemeral` and the
+//! `ask_default_ephemeral` profile option.
+//!
+//! An ephemeral run gets a fresh `ephemeral-<timestamp>-<pid>` session id on
+//! the memory backend, whatever backend the profile configures, so the
+//! configured store is never opened: no `ensure_session_exists`, no sqlite
+//! file, no idle snapshot or transcript. Telemetry events carry
+//! `ephemeral: true`.
+//!
+//! In chat, `/persist` copies the exchanges so far into a session with the
+//! same id on the configured backend. The chat itself stays ephemeral, so
+//! `/persist` again replaces that copy with the longer conversation;
+//! `chat --session-id <id>` resumes it.
+use std::sync::Arc;
+
+use adk_session::SessionService;
+use anyhow::{Context, Result};
+
+use crate::checkpoint::{restore_session_events, snapshot_session_events};
+use crate::cli::{Commands, SessionBackend};
+use crate::config::RuntimeConfig;
+use crate::session::build_session_service;
+
+pub const EPHEMERAL_SESSION_PREFIX: &str = "ephemeral-";
+
+pub fn ephemeral_session_id() -> String {
+    format!(
+        "{EPHEMERAL_SESSION_PREFIX}{}-{}",
+        chrono::Utc::now().format("%Y%m%dT%H%M%S%3fZ"),
+        std::process::id()
+    )
+}
+
+/// Whether `command` runs ephemeral: the explicit flags first, then
+/// `ask_default_ephemeral` for `ask`.
+pub fn use_ephemeral_session(command: Option<&Commands>, cfg: &RuntimeConfig) -> bool {
+    match command {
+        Some(Commands::Ask {
+            ephemeral,
+            no_ephemeral,
+            ..
+        }) => *ephemeral || (cfg.ask_default_ephemeral && !*no_ephemeral),
+        Some(Commands::Chat { ephemeral, .. }) => *ephemeral,
+        _ => false,
+    }
+}
+
+/// `cfg` switched to a fresh in-memory session, remembering the configured
+/// backend for `/persist`.
+pub fn ephemeral_runtime_config(cfg: &RuntimeConfig) -> RuntimeConfig {
+    let mut ephemeral = cfg.clone();
+    ephemeral.ephemeral = Some(cfg.ephemeral.unwrap_or(cfg.session_backend));
+    ephemeral.session_backend = SessionBackend::Memory;
+    ephemeral.session_id = ephemeral_session_id();
+    ephemeral.chat_idle_snapshot_db_url.clear();
+    ephemeral.chat_idle_transcript_dir.clear();
+    ephemeral
+}
+
+/// One line for the chat banner; `None` for a normal session.
+pub fn ephemeral_banner(cfg: &RuntimeConfig) -> Option<String> {
+    cfg.ephemeral.map(|_| {
+        format!(
+            "EPHEMERAL SESSION '{}': nothing is saved. /persist copies it to the configured \
+             session backend.",
+            cfg.session_id
+        )
+    })
+}
+
+/// Copy the ephemeral session's events into a session with the same id on
+/// the configured backend, replacing an earlier `/persist` copy. Returns the
+/// target backend and the number of events written.
+pub async fn persist_ephemeral_session(
+    session_service: &Arc<dyn SessionService>,
+    cfg: &RuntimeConfig,
+) -> Result<(SessionBackend, usize)> {
+    let backend = cfg
+        .ephemeral
+        .ok_or_else(|| anyhow::anyhow!("this chat is not ephemeral; it is already saved"))?;
+    if backend == SessionBackend::Memory {
+        return Err(anyhow::anyhow!(
+            "the configured session backend is memory; there is nowhere to persist to"
+        ));
+    }
+    let events = snapshot_session_events(session_service, cfg).await?;
+    let mut target_cfg = cfg.clone();
+    target_cfg.session_backend = backend;
+    target_cfg.ephemeral = None;
+    let target = build_session_service(&target_cfg).await?;
+    restore_session_events(&target, &target_cfg, &events)
+        .await
+        .with_context(|| format!("failed to persist session '{}'", cfg.session_id))?;
+    Ok((backend, events.len()))
+}
@@ -6,6 +6,9 @@ pub enum ErrorCategory {
     Session,
     Tooling,
     Input,
+    Guardrail,
+    /// `--max-runtime-secs` ran out.
+    Timeout,
     Internal,
 }
 
@@ -16,10 +19,25 @@ impl ErrorCategory {
             ErrorCategory::Session => "SESSION",
             ErrorCategory::Tooling => "TOOLING",
             ErrorCategory::Input => "INPUT",
+            ErrorCategory::Guardrail => "GUARDRAIL",
+            ErrorCategory::Timeout => "TIMEOUT",
             ErrorCategory::Internal => "INTERNAL",
         }
     }
 
+    /// Process exit status for a failed command. Part of the scripting
+    /// contract documented in the README: 0 success, 1 generic failure,
+    /// 2 input error, 3 guardrail block, 4 provider error, 5 timeout.
+    pub fn exit_code(self) -> i32 {
+        match self {
+            ErrorCategory::Input => 2,
+            ErrorCategory::Guardrail => 3,
+            ErrorCategory::Provider => 4,
+            ErrorCategory::Timeout => 5,
+            ErrorCategory::Session | ErrorCategory::Tooling | ErrorCategory::Internal => 1,
+        }
+    }
+
     pub fn hint(self) -> &'static str {
         match self {
             ErrorCategory::Provider => {
@@ -32,6 +50,12 @@ impl ErrorCategory {
                 "Review tool configuration and retry with RUST_LOG=info for detailed tool/runtime logs."
             }
             ErrorCategory::Input => "Run zavora-cli --help and correct command arguments.",
+            ErrorCategory::Guardrail => {
+                "Content matched guardrail terms; adjust guardrail_terms or use a redact/observe guardrail mode."
+            }
+            ErrorCategory::Timeout => {
+                "The command hit --max-runtime-secs; raise the limit or check the phase it stopped in."
+            }
             ErrorCategory::Internal => {
                 "Retry with RUST_LOG=debug. If it persists, capture logs and open an issue."
             }
@@ -40,8 +64,37 @@ impl ErrorCategory {
 }
 
 pub fn categorize_error(err: &anyhow::Error) -> ErrorCategory {
+    if err
+        .downcast_ref::<crate::watchdog::CommandTimeout>()
+        .is_some()
+    {
+        return ErrorCategory::Timeout;
+    }
+
     let msg = format!("{err:#}").to_ascii_lowercase();
 
+    if msg.contains("failed to acquire file lock") {
+        return ErrorCategory::Internal;
+    }
+
+    if msg.contains("guardrail blocked") {
+        return ErrorCategory::Guardrail;
+    }
+
+    // These messages quote config keys, column names or command names such
+    // as "provider", "tool" and "sessions delete"; classify them before the
+    // keyword checks below.
+    if msg.contains("read_only_mode")
+        || msg.contains("unknown telemetry column")
+        || msg.contains("invalid telemetry aggregation")
+        || msg.contains("undefined environment variable")
+        || msg.contains("config include")
+        || msg.contains("model shorthand")
+        || msg.contains("profile inheritance")
+    {
+        return ErrorCategory::Input;
+    }
+
     if msg.contains("api_key")
         || msg.contains("no provider could be auto-detected")
         || msg.contains("provider")
@@ -54,6 +107,7 @@ pub fn categorize_error(err: &anyhow::Error) -> ErrorCategory {
         || msg.contains("invalid value")
         || msg.contains("unknown argument")
         || msg.contains("failed to read input")
+        || msg.contains("stdin")
         || msg.contains("profile")
     {
         return ErrorCategory::Input;
@@ -1,15 +1,20 @@
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
 use std::time::Instant;
 
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 
+use crate::chunking::{
+    ChunkingStrategy, DEFAULT_CHUNK_OVERLAP, DEFAULT_CHUNK_SIZE, chunk_document,
+};
+use crate::cli::RetrievalChunking;
 use crate::retrieval::{LocalFileRetrievalService, RetrievalService, RetrievedChunk, query_terms};
 use crate::telemetry::{TelemetrySink, unix_ms_now};
 
 pub const DEFAULT_EVAL_DATASET_PATH: &str = "evals/datasets/retrieval-baseline.v1.json";
-pub const DEFAULT_EVAL_OUTPUT_PATH: &str = ".zavora/evals/latest.json";
 
 #[derive(Debug, Deserialize)]
 pub struct EvalDataset {
@@ -30,6 +35,12 @@ pub struct EvalCase {
     #[serde(default = "default_eval_max_chunks")]
     pub max_chunks: usize,
     pub min_term_matches: Option<usize>,
+    /// When set, each entry in `chunks` is a document split with this
+    /// strategy; otherwise entries are used as chunks verbatim.
+    #[serde(default)]
+    pub chunking: Option<RetrievalChunking>,
+    pub chunk_size: Option<usize>,
+    pub chunk_overlap: Option<usize>,
 }
 
 fn default_eval_max_chunks() -> usize {
@@ -43,8 +54,11 @@ pub struct EvalCaseReport {
     pub required_terms: usize,
     pub matched_terms: usize,
     pub retrieved_chunks: usize,
+    /// Best chunk's normalized score (0–100).
     pub top_score: usize,
     pub avg_latency_ms: f64,
+    pub chunking: String,
+    pub corpus_chunks: usize,
 }
 
 #[derive(Debug, Serialize)]
@@ -54,6 +68,8 @@ pub struct EvalRunReport {
     pub dataset_version: String,
     pub dataset_description: String,
     pub benchmark_iterations: usize,
+    /// Cases evaluated concurrently (`eval run --jobs`).
+    pub jobs: usize,
     pub total_cases: usize,
     pub passed_cases: usize,
     pub failed_cases: usize,
@@ -63,6 +79,8 @@ pub struct EvalRunReport {
     pub avg_latency_ms: f64,
     pub p95_latency_ms: f64,
     pub throughput_qps: f64,
+    /// Wall-clock time for the whole batch.
+    pub wall_clock_ms: f64,
     pub case_reports: Vec<EvalCaseReport>,
 }
 
@@ -96,6 +114,41 @@ pub fn normalize_eval_terms(raw_terms: &[String], query: &str) -> Vec<String> {
     terms
 }
 
+pub fn eval_case_strategy(case: &EvalCase) -> Option<ChunkingStrategy> {
+    case.chunking.map(|kind| {
+        ChunkingStrategy::new(
+            kind,
+            case.chunk_size.unwrap_or(DEFAULT_CHUNK_SIZE),
+            case.chunk_overlap.unwrap_or(DEFAULT_CHUNK_OVERLAP),
+        )
+    })
+}
+
+/// Retrieval corpus for a case: verbatim chunks, or each document split by
+/// the case's chunking strategy.
+pub fn eval_case_corpus(case: &EvalCase) -> Vec<RetrievedChunk> {
+    match eval_case_strategy(case) {
+        Some(strategy) => case
+            .chunks
+            .iter()
+            .enumerate()
+            .flat_map(|(idx, doc)| {
+                chunk_document(doc, &format!("eval:{}#{}", case.id, idx + 1), strategy)
+            })
+            .collect(),
+        None => case
+            .chunks
+            .iter()
+            .enumerate()
+            .map(|(idx, chunk)| RetrievedChunk {
+                source: format!("eval:{}#{}", case.id, idx + 1),
+                text: chunk.clone(),
+                score: 0,
+            })
+            .collect(),
+    }
+}
+
 pub fn percentile(values: &[f64], pct: f64) -> f64 {
     if values.is_empty() {
         return 0.0;
@@ -110,99 +163,152 @@ pub fn round_metric(value: f64) -> f64 {
     (value * 1000.0).round() / 1000.0
 }
 
+/// Retrieve and score one case. Latency is measured here, per worker, so it
+/// is unaffected by other cases running concurrently.
+pub fn evaluate_eval_case(case: &EvalCase, iterations: usize) -> Result<EvalCaseReport> {
+    if case.id.trim().is_empty() {
+        return Err(anyhow::anyhow!("eval dataset contains case with empty id"));
+    }
+    if case.query.trim().is_empty() {
+        return Err(anyhow::anyhow!(
+            "eval case '{}' has empty query; each case must include query",
+            case.id
+        ));
+    }
+    if case.chunks.is_empty() {
+        return Err(anyhow::anyhow!(
+            "eval case '{}' has no chunks; each case must include retrieval corpus chunks",
+            case.id
+        ));
+    }
+
+    let retrieval = LocalFileRetrievalService {
+        chunks: eval_case_corpus(case),
+    };
+
+    let case_start = Instant::now();
+    let mut retrieved = Vec::<RetrievedChunk>::new();
+    for _ in 0..iterations {
+        retrieved = retrieval.retrieve(&case.query, case.max_chunks.max(1))?;
+    }
+    let case_elapsed = case_start.elapsed();
+    let case_avg_latency_ms = (case_elapsed.as_secs_f64() * 1000.0) / (iterations as f64);
+
+    let terms = normalize_eval_terms(&case.required_terms, &case.query);
+    if terms.is_empty() {
+        return Err(anyhow::anyhow!(
+            "eval case '{}' produced no required terms; add required_terms or a richer query",
+            case.id
+        ));
+    }
+
+    let joined = retrieved
+        .iter()
+        .map(|chunk| chunk.text.to_ascii_lowercase())
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    let matched_terms = terms
+        .iter()
+        .filter(|term| joined.contains(term.as_str()))
+        .count();
+    let required_terms = terms.len();
+    let min_term_matches = case
+        .min_term_matches
+        .unwrap_or(required_terms)
+        .clamp(1, required_terms);
+
+    Ok(EvalCaseReport {
+        id: case.id.clone(),
+        passed: matched_terms >= min_term_matches,
+        required_terms,
+        matched_terms,
+        retrieved_chunks: retrieved.len(),
+        top_score: retrieved
+            .first()
+            .map(|chunk| chunk.score)
+            .unwrap_or_default(),
+        chunking: eval_case_strategy(case)
+            .map(|strategy| strategy.label())
+            .unwrap_or_else(|| "verbatim".to_string()),
+        corpus_chunks: retrieval.chunks.len(),
+        avg_latency_ms: round_metric(case_avg_latency_ms),
+    })
+}
+
 pub fn run_eval_harness(
     dataset: &EvalDataset,
     benchmark_iterations: usize,
     fail_under: f64,
+    jobs: usize,
 ) -> Result<EvalRunReport> {
+    run_eval_harness_with(
+        dataset,
+        benchmark_iterations,
+        fail_under,
+        jobs,
+        evaluate_eval_case,
+    )
+}
+
+/// Run every case through `evaluate` on `jobs` worker threads. Workers pull
+/// the next case index and send each report back as soon as it is done, so
+/// only the small per-case reports are held. Reports are returned in dataset
+/// order; a failing case does not stop the others, and the first error in
+/// dataset order is returned once all cases have finished.
+pub fn run_eval_harness_with<F>(
+    dataset: &EvalDataset,
+    benchmark_iterations: usize,
+    fail_under: f64,
+    jobs: usize,
+    evaluate: F,
+) -> Result<EvalRunReport>
+where
+    F: Fn(&EvalCase, usize) -> Result<EvalCaseReport> + Sync,
+{
     let iterations = benchmark_iterations.max(1);
+    let jobs = jobs.clamp(1, dataset.cases.len().max(1));
     let suite_start = Instant::now();
-    let mut passed_cases = 0usize;
-    let mut latency_ms = Vec::<f64>::new();
-    let mut case_reports = Vec::<EvalCaseReport>::new();
-
-    for case in &dataset.cases {
-        if case.id.trim().is_empty() {
-            return Err(anyhow::anyhow!("eval dataset contains case with empty id"));
-        }
-        if case.query.trim().is_empty() {
-            return Err(anyhow::anyhow!(
-                "eval case '{}' has empty query; each case must include query",
-                case.id
-            ));
-        }
-        if case.chunks.is_empty() {
-            return Err(anyhow::anyhow!(
-                "eval case '{}' has no chunks; each case must include retrieval corpus chunks",
-                case.id
-            ));
-        }
 
-        let retrieval = LocalFileRetrievalService {
-            chunks: case
-                .chunks
-                .iter()
-                .enumerate()
-                .map(|(idx, chunk)| RetrievedChunk {
-                    source: format!("eval:{}#{}", case.id, idx + 1),
-                    text: chunk.clone(),
-                    score: 0,
-                })
-                .collect::<Vec<RetrievedChunk>>(),
-        };
-
-        let case_start = Instant::now();
-        let mut retrieved = Vec::<RetrievedChunk>::new();
-        for _ in 0..iterations {
-            retrieved = retrieval.retrieve(&case.query, case.max_chunks.max(1))?;
+    let next_case = AtomicUsize::new(0);
+    let mut results = (0..dataset.cases.len())
+        .map(|_| None)
+        .collect::<Vec<Option<Result<EvalCaseReport>>>>();
+    std::thread::scope(|scope| {
+        let (sender, receiver) = mpsc::channel();
+        for _ in 0..jobs {
+            let sender = sender.clone();
+            let (next_case, evaluate) = (&next_case, &evaluate);
+            scope.spawn(move || {
+                loop {
+                    let index = next_case.fetch_add(1, Ordering::Relaxed);
+                    let Some(case) = dataset.cases.get(index) else {
+                        break;
+                    };
+                    if sender.send((index, evaluate(case, iterations))).is_err() {
+                        break;
+                    }
+                }
+            });
         }
-        let case_elapsed = case_start.elapsed();
-        let case_avg_latency_ms = (case_elapsed.as_secs_f64() * 1000.0) / (iterations as f64);
-        latency_ms.push(case_avg_latency_ms);
-
-        let terms = normalize_eval_terms(&case.required_terms, &case.query);
-        if terms.is_empty() {
-            return Err(anyhow::anyhow!(
-                "eval case '{}' produced no required terms; add required_terms or a richer query",
-                case.id
-            ));
+        drop(sender);
+        for (index, result) in receiver {
+            results[index] = Some(result);
         }
+    });
+    let wall_clock = suite_start.elapsed();
 
-        let joined = retrieved
-            .iter()
-            .map(|chunk| chunk.text.to_ascii_lowercase())
-            .collect::<Vec<String>>()
-            .join("\n");
-
-        let matched_terms = terms
-            .iter()
-            .filter(|term| joined.contains(term.as_str()))
-            .count();
-        let required_terms = terms.len();
-        let min_term_matches = case
-            .min_term_matches
-            .unwrap_or(required_terms)
-            .clamp(1, required_terms);
-        let passed = matched_terms >= min_term_matches;
-        if passed {
-            passed_cases += 1;
-        }
-
-        case_reports.push(EvalCaseReport {
-            id: case.id.clone(),
-            passed,
-            required_terms,
-            matched_terms,
-            retrieved_chunks: retrieved.len(),
-            top_score: retrieved
-                .first()
-                .map(|chunk| chunk.score)
-                .unwrap_or_default(),
-            avg_latency_ms: round_metric(case_avg_latency_ms),
-        });
+    let mut case_reports = Vec::<EvalCaseReport>::with_capacity(results.len());
+    for result in results {
+        case_reports.push(result.ok_or_else(|| anyhow::anyhow!("eval worker exited early"))??);
     }
+    let latency_ms = case_reports
+        .iter()
+        .map(|report| report.avg_latency_ms)
+        .collect::<Vec<f64>>();
 
     let total_cases = dataset.cases.len();
+    let passed_cases = case_reports.iter().filter(|report| report.passed).count();
     let failed_cases = total_cases.saturating_sub(passed_cases);
     let pass_rate = if total_cases == 0 {
         0.0
@@ -219,7 +325,8 @@ pub fn run_eval_harness(
     };
     let p95_latency_ms = percentile(&sorted_latencies, 95.0);
 
-    let suite_elapsed_secs = suite_start.elapsed().as_secs_f64();
+    // Batch wall-clock, so concurrent jobs show up as higher throughput.
+    let suite_elapsed_secs = wall_clock.as_secs_f64();
     let throughput_qps = if suite_elapsed_secs <= 0.0 {
         0.0
     } else {
@@ -233,6 +340,7 @@ pub fn run_eval_harness(
         dataset_version: dataset.version.clone(),
         dataset_description: dataset.description.clone(),
         benchmark_iterations: iterations,
+        jobs,
         total_cases,
         passed_cases,
         failed_cases,
@@ -242,6 +350,7 @@ pub fn run_eval_harness(
         avg_latency_ms: round_metric(avg_latency_ms),
         p95_latency_ms: round_metric(p95_latency_ms),
         throughput_qps: round_metric(throughput_qps),
+        wall_clock_ms: round_metric(wall_clock.as_secs_f64() * 1000.0),
         case_reports,
     })
 }
@@ -267,15 +376,15 @@ pub fn write_eval_report(path: &str, report: &EvalRunReport) -> Result<()> {
 
 pub fn run_eval(
     dataset_path: Option<String>,
-    output_path: Option<String>,
+    output_path: String,
     benchmark_iterations: usize,
     fail_under: f64,
+    jobs: usize,
     telemetry: &TelemetrySink,
 ) -> Result<()> {
     let dataset_path = dataset_path.unwrap_or_else(|| DEFAULT_EVAL_DATASET_PATH.to_string());
-    let output_path = output_path.unwrap_or_else(|| DEFAULT_EVAL_OUTPUT_PATH.to_string());
     let dataset = load_eval_dataset(&dataset_path)?;
-    let report = run_eval_harness(&dataset, benchmark_iterations, fail_under)?;
+    let report = run_eval_harness(&dataset, benchmark_iterations, fail_under, jobs)?;
 
     write_eval_report(&output_path, &report)?;
     telemetry.emit(
@@ -284,6 +393,8 @@ pub fn run_eval(
             "dataset": report.dataset_name,
             "dataset_version": report.dataset_version,
             "total_cases": report.total_cases,
+            "jobs": report.jobs,
+            "wall_clock_ms": report.wall_clock_ms,
             "pass_rate": report.pass_rate,
             "passed_threshold": report.passed_threshold,
             "output_path": output_path
@@ -302,6 +413,10 @@ pub fn run_eval(
         "Benchmark: avg_latency_ms={:.3} p95_latency_ms={:.3} throughput_qps={:.3}",
         report.avg_latency_ms, report.p95_latency_ms, report.throughput_qps
     );
+    println!(
+        "Jobs: {} wall_clock_ms={:.3}",
+        report.jobs, report.wall_clock_ms
+    );
     println!("Report written to {}", output_path);
 
     if !report.passed_threshold {
@@ -0,0 +1,248 @@
+//! `--provider fake`: a scripted, credential-free model so the real binary
+//! can be driven end to end by black-box tests and offline demos.
+//!
+//! The script (`--fake-script` / `ZAVORA_FAKE_SCRIPT`) is JSON:
+//!
+//! ```json
+//! {
+//!   "latency_ms": 0,
+//!   "chunk_chars": 16,
+//!   "responses": [
+//!     {"match": "weather", "text": "Sunny all week."},
+//!     {"tool_calls": [{"name": "fs_read", "args": {"path": "notes.txt"}}]},
+//!     {"text": "The notes say hello."},
+//!     {"error": "simulated provider outage", "latency_ms": 250}
+//!   ]
+//! }
+//! ```
+//!
+//! Entries with `match` answer any user turn containing that substring and
+//! may repeat. The others are consumed in order, one per model call, which
+//! includes the follow-up call after a tool result. Running out of entries
+//! is an error so a script never loops silently.
+use std::sync::Mutex;
+use std::time::Duration;
+
+use adk_rust::prelude::*;
+use adk_rust::{LlmRequest, LlmResponse, LlmResponseStream};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::config::RuntimeConfig;
+
+pub const FAKE_MODEL_NAME: &str = "fake-scripted";
+const DEFAULT_CHUNK_CHARS: usize = 16;
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FakeScript {
+    /// Delay before every response unless the entry sets its own.
+    #[serde(default)]
+    pub latency_ms: u64,
+    /// Size of the streamed text deltas; 0 sends each text whole.
+    #[serde(default = "default_chunk_chars")]
+    pub chunk_chars: usize,
+    pub responses: Vec<FakeResponse>,
+}
+
+fn default_chunk_chars() -> usize {
+    DEFAULT_CHUNK_CHARS
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FakeResponse {
+    /// Answer user turns containing this substring instead of taking the
+    /// next entry in order.
+    #[serde(default, rename = "match")]
+    pub match_text: Option<String>,
+    #[serde(default)]
+    pub text: Option<String>,
+    #[serde(default)]
+    pub tool_calls: Vec<FakeToolCall>,
+    /// Fail the model call with this message.
+    #[serde(default)]
+    pub error: Option<String>,
+    #[serde(default)]
+    pub latency_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FakeToolCall {
+    pub name: String,
+    #[serde(default)]
+    pub args: Value,
+}
+
+impl FakeScript {
+    pub fn parse(text: &str) -> Result<Self> {
+        let script =
+            serde_json::from_str::<FakeScript>(text).context("failed to parse fake script")?;
+        for (index, response) in script.responses.iter().enumerate() {
+            let has_output = response.text.is_some() || !response.tool_calls.is_empty();
+            if response.error.is_some() == has_output {
+                return Err(anyhow::anyhow!(
+                    "fake script response #{} needs exactly one of: text/tool_calls, or error",
+                    index + 1
+                ));
+            }
+        }
+        Ok(script)
+    }
+
+    pub fn load(path: &str) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read fake script '{path}'"))?;
+        Self::parse(&text).with_context(|| format!("invalid fake script '{path}'"))
+    }
+}
+
+/// Text of the request's last turn when it is a user message; `None` after
+/// a tool result, so keyed entries never answer a tool follow-up.
+fn latest_user_text(contents: &[Content]) -> Option<String> {
+    let last = contents.last()?;
+    if last.role != "user"
+        || last
+            .parts
+            .iter()
+            .any(|part| matches!(part, Part::FunctionResponse { .. }))
+    {
+        return None;
+    }
+    Some(
+        last.parts
+            .iter()
+            .filter_map(|part| match part {
+                Part::Text { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<&str>>()
+            .join("\n"),
+    )
+}
+
+/// The entry answering `contents`: a keyed match on a user turn, otherwise
+/// the unkeyed entry at `cursor` (which then advances).
+pub fn select_fake_response<'a>(
+    script: &'a FakeScript,
+    cursor: &mut usize,
+    contents: &[Content],
+) -> Option<&'a FakeResponse> {
+    if let Some(text) = latest_user_text(contents)
+        && let Some(keyed) = script.responses.iter().find(|response| {
+            response
+                .match_text
+                .as_deref()
+                .is_some_and(|needle| text.contains(needle))
+        })
+    {
+        return Some(keyed);
+    }
+    let next = script
+        .responses
+        .iter()
+        .filter(|response| response.match_text.is_none())
+        .nth(*cursor)?;
+    *cursor += 1;
+    Some(next)
+}
+
+fn text_chunks(text: &str, chunk_chars: usize) -> Vec<String> {
+    let chars = text.chars().collect::<Vec<char>>();
+    chars
+        .chunks(chunk_chars.max(1))
+        .map(|chunk| chunk.iter().collect())
+        .collect()
+}
+
+/// The model responses for one entry. Streaming text arrives as partial
+/// deltas followed by the full snapshot, like the real streaming clients.
+pub fn fake_llm_responses(
+    response: &FakeResponse,
+    stream: bool,
+    chunk_chars: usize,
+) -> Vec<LlmResponse> {
+    let text = response.text.clone().unwrap_or_default();
+    let mut responses = Vec::new();
+    if stream && chunk_chars > 0 && response.tool_calls.is_empty() {
+        for chunk in text_chunks(&text, chunk_chars) {
+            let mut partial = LlmResponse::new(Content::new("model").with_text(chunk));
+            partial.partial = true;
+            responses.push(partial);
+        }
+    }
+    let mut content = Content::new("model");
+    if !text.is_empty() {
+        content = content.with_text(text);
+    }
+    for call in &response.tool_calls {
+        content.parts.push(Part::FunctionCall {
+            name: call.name.clone(),
+            args: call.args.clone(),
+            id: None,
+        });
+    }
+    responses.push(LlmResponse::new(content));
+    responses
+}
+
+pub struct FakeLlm {
+    name: String,
+    script: FakeScript,
+    cursor: Mutex<usize>,
+}
+
+impl FakeLlm {
+    pub fn new(name: &str, script: FakeScript) -> Self {
+        Self {
+            name: name.to_string(),
+            script,
+            cursor: Mutex::new(0),
+        }
+    }
+
+    pub fn from_config(cfg: &RuntimeConfig, model_name: &str) -> Result<Self> {
+        let path = cfg.fake_script.as_deref().context(
+            "provider 'fake' needs a response script: pass --fake-script <path> or set \
+             ZAVORA_FAKE_SCRIPT",
+        )?;
+        Ok(Self::new(model_name, FakeScript::load(path)?))
+    }
+}
+
+#[async_trait]
+impl Llm for FakeLlm {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn generate_content(
+        &self,
+        req: LlmRequest,
+        stream: bool,
+    ) -> adk_rust::Result<LlmResponseStream> {
+        let response = {
+            let mut cursor = self.cursor.lock().unwrap_or_else(|e| e.into_inner());
+            select_fake_response(&self.script, &mut cursor, &req.contents).cloned()
+        };
+        let Some(response) = response else {
+            return Err(adk_rust::AdkError::model(
+                "fake script exhausted: no response left for this model call",
+            ));
+        };
+        let latency = response.latency_ms.unwrap_or(self.script.latency_ms);
+        if latency > 0 {
+            tokio::time::sleep(Duration::from_millis(latency)).await;
+        }
+        if let Some(message) = &response.error {
+            return Err(adk_rust::AdkError::model(message.clone()));
+        }
+        let responses = fake_llm_responses(&response, stream, self.script.chunk_chars);
+        Ok(Box::pin(adk_rust::futures::stream::iter(
+            responses.into_iter().map(Ok),
+        )))
+    }
+}
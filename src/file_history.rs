@@ -7,7 +7,8 @@ use std::collections::VecDeque;
 use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 
-const HISTORY_DIR: &str = ".zavora/file_history";
+use crate::paths::ZavoraPaths;
+
 const MAX_SNAPSHOTS: usize = 20;
 
 static UNDO_STACK: Mutex<Option<VecDeque<UndoEntry>>> = Mutex::new(None);
@@ -20,7 +21,9 @@ struct UndoEntry {
 
 fn history_dir(file_path: &Path) -> PathBuf {
     let hash = format!("{:x}", md5::compute(file_path.to_string_lossy().as_bytes()));
-    PathBuf::from(HISTORY_DIR).join(hash)
+    // Tool hooks run without a resolved config; the workspace dir does not
+    // depend on `--config-dir`.
+    ZavoraPaths::from_env(None).file_history_dir().join(hash)
 }
 
 /// Snapshot a file before modification.
@@ -0,0 +1,158 @@
+use std::fs::OpenOptions;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+
+/// How long writers wait for a contended lock before giving up.
+pub const DEFAULT_FILE_LOCK_TIMEOUT: Duration = Duration::from_secs(5);
+
+const FILE_LOCK_POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Advisory lock on a workspace file, held as a `<file>.lock` sibling that
+/// records the owning PID. Released (lock file removed) on drop, unless
+/// another process has since taken the lock over.
+#[derive(Debug)]
+pub struct FileLock {
+    lock_path: Option<PathBuf>,
+}
+
+impl FileLock {
+    /// Path of the lock file, or `None` when locking degraded to best-effort.
+    pub fn lock_path(&self) -> Option<&Path> {
+        self.lock_path.as_deref()
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        if let Some(path) = self.lock_path.take()
+            && read_lock_holder(&path) == Some(std::process::id())
+        {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// Sibling lock file used for `target`, e.g. `events.jsonl.lock`.
+pub fn lock_path_for(target: &Path) -> PathBuf {
+    let mut name = target
+        .file_name()
+        .map(|name| name.to_os_string())
+        .unwrap_or_default();
+    name.push(".lock");
+    target.with_file_name(name)
+}
+
+/// Acquire the advisory lock for `target`, waiting up to `timeout`.
+///
+/// A lock whose recorded PID is no longer running is treated as stale and
+/// taken over. If the lock file cannot be created for reasons other than
+/// contention (read-only directory, unsupported filesystem), locking
+/// degrades to best-effort and the caller proceeds unlocked.
+pub fn acquire_file_lock(target: &Path, timeout: Duration) -> Result<FileLock> {
+    let lock_path = lock_path_for(target);
+    let started = Instant::now();
+
+    loop {
+        match OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&lock_path)
+        {
+            Ok(mut file) => {
+                let _ = write!(file, "{}", std::process::id());
+                return Ok(FileLock {
+                    lock_path: Some(lock_path),
+                });
+            }
+            Err(err) if err.kind() == ErrorKind::AlreadyExists => {
+                let holder = read_lock_holder(&lock_path);
+                if lock_is_stale(&lock_path, holder, timeout)
+                    && remove_stale_lock(&lock_path, holder)
+                {
+                    tracing::warn!(
+                        lock = %lock_path.display(),
+                        holder_pid = ?holder,
+                        "Removed stale file lock"
+                    );
+                    continue;
+                }
+                if started.elapsed() >= timeout {
+                    let holder = holder
+                        .map(|pid| format!("pid {pid}"))
+                        .unwrap_or_else(|| "an unknown process".to_string());
+                    return Err(anyhow::anyhow!(
+                        "failed to acquire file lock '{}' within {}ms; held by {holder}",
+                        lock_path.display(),
+                        timeout.as_millis()
+                    ));
+                }
+                std::thread::sleep(FILE_LOCK_POLL_INTERVAL);
+            }
+            Err(err) => {
+                tracing::warn!(
+                    lock = %lock_path.display(),
+                    error = %err,
+                    "File locking unavailable; continuing without a lock"
+                );
+                return Ok(FileLock { lock_path: None });
+            }
+        }
+    }
+}
+
+fn read_lock_holder(lock_path: &Path) -> Option<u32> {
+    std::fs::read_to_string(lock_path)
+        .ok()
+        .and_then(|contents| contents.trim().parse::<u32>().ok())
+}
+
+/// Remove the stale lock `holder` left behind. The holder is read again
+/// right before removing, so a lock another waiter has just taken over is
+/// left alone. Returns whether the lock file is gone.
+pub fn remove_stale_lock(lock_path: &Path, holder: Option<u32>) -> bool {
+    if read_lock_holder(lock_path) != holder {
+        return false;
+    }
+    match std::fs::remove_file(lock_path) {
+        Ok(()) => true,
+        Err(err) => err.kind() == ErrorKind::NotFound,
+    }
+}
+
+fn lock_is_stale(lock_path: &Path, holder: Option<u32>, timeout: Duration) -> bool {
+    match holder {
+        Some(pid) => !process_is_alive(pid),
+        // The holder may still be writing its PID; only treat an unreadable
+        // lock as stale once it has outlived a full wait period.
+        None => std::fs::metadata(lock_path)
+            .and_then(|meta| meta.modified())
+            .ok()
+            .and_then(|modified| modified.elapsed().ok())
+            .is_some_and(|age| age > timeout),
+    }
+}
+
+/// Whether `pid` is a running process on this host.
+#[cfg(unix)]
+pub fn process_is_alive(pid: u32) -> bool {
+    let proc_root = Path::new("/proc");
+    if proc_root.is_dir() {
+        return proc_root.join(pid.to_string()).exists();
+    }
+    std::process::Command::new("kill")
+        .args(["-0", &pid.to_string()])
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(true)
+}
+
+#[cfg(not(unix))]
+pub fn process_is_alive(_pid: u32) -> bool {
+    // No cheap liveness probe: never steal a lock, rely on the timeout.
+    true
+}
@@ -1,7 +1,11 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::{Arc, Mutex};
+
+use aho_corasick::AhoCorasick;
 use anyhow::Result;
 use serde_json::json;
 
-use crate::cli::GuardrailMode;
+use crate::cli::{GuardrailMode, GuardrailSeverity};
 use crate::config::RuntimeConfig;
 use crate::telemetry::TelemetrySink;
 
@@ -29,66 +33,276 @@ pub fn guardrail_mode_label(mode: GuardrailMode) -> &'static str {
     }
 }
 
-/// Check text for guardrail term matches using adk-guardrail ContentFilter.
-pub fn contains_guardrail_terms(text: &str, terms: &[String]) -> Vec<String> {
-    let filter = adk_guardrail::ContentFilter::blocked_keywords(terms.to_vec());
-    let content = adk_rust::Content::new("user").with_text(text);
-    // Run synchronously — ContentFilter::validate is CPU-only (regex)
-    let rt = tokio::runtime::Handle::try_current();
-    let result = if let Ok(handle) = rt {
-        handle.block_on(adk_guardrail::Guardrail::validate(&filter, &content))
-    } else {
-        // Fallback for non-async context
-        let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
-        rt.block_on(adk_guardrail::Guardrail::validate(&filter, &content))
-    };
-    match result {
-        adk_guardrail::GuardrailResult::Fail { .. } => {
-            // Extract matched terms from the reason
-            terms
-                .iter()
-                .filter(|t| text.to_ascii_lowercase().contains(&t.to_ascii_lowercase()))
-                .cloned()
-                .collect()
+pub fn guardrail_severity_label(severity: GuardrailSeverity) -> &'static str {
+    match severity {
+        GuardrailSeverity::Low => "low",
+        GuardrailSeverity::Medium => "medium",
+        GuardrailSeverity::High => "high",
+    }
+}
+
+pub fn parse_guardrail_severity(value: &str) -> Option<GuardrailSeverity> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "low" => Some(GuardrailSeverity::Low),
+        "medium" => Some(GuardrailSeverity::Medium),
+        "high" => Some(GuardrailSeverity::High),
+        _ => None,
+    }
+}
+
+/// Split `term:severity`. A suffix that is not a severity stays part of the
+/// term, so terms containing `:` still work.
+pub fn parse_guardrail_term(raw: &str) -> (String, GuardrailSeverity) {
+    if let Some((term, suffix)) = raw.rsplit_once(':')
+        && let Some(severity) = parse_guardrail_severity(suffix)
+        && !term.trim().is_empty()
+    {
+        return (term.trim().to_string(), severity);
+    }
+    (raw.trim().to_string(), GuardrailSeverity::Medium)
+}
+
+/// Severity of a configured term; unlisted terms are medium.
+pub fn guardrail_term_severity(cfg: &RuntimeConfig, term: &str) -> GuardrailSeverity {
+    cfg.guardrail_term_severities
+        .get(&term.to_ascii_lowercase())
+        .copied()
+        .unwrap_or_default()
+}
+
+/// Mode for hits of `severity`: the `guardrail_severity_actions` entry when
+/// present, otherwise the direction's blanket mode.
+pub fn guardrail_severity_mode(
+    cfg: &RuntimeConfig,
+    severity: GuardrailSeverity,
+    blanket: GuardrailMode,
+) -> GuardrailMode {
+    severity_action_mode(&cfg.guardrail_severity_actions, severity, blanket)
+}
+
+fn severity_action_mode(
+    actions: &BTreeMap<GuardrailSeverity, GuardrailMode>,
+    severity: GuardrailSeverity,
+    blanket: GuardrailMode,
+) -> GuardrailMode {
+    actions.get(&severity).copied().unwrap_or(blanket)
+}
+
+/// Whether `apply_guardrail` can do anything for a direction.
+pub fn guardrail_active(cfg: &RuntimeConfig, blanket: GuardrailMode) -> bool {
+    !matches!(blanket, GuardrailMode::Disabled) || !cfg.guardrail_severity_actions.is_empty()
+}
+
+/// Case-insensitive (ASCII) multi-term matcher backed by Aho-Corasick.
+/// Built once per term list and shared by detection and redaction.
+#[derive(Debug, Clone)]
+pub struct GuardrailMatcher {
+    terms: Vec<String>,
+    /// Pattern of each term; `None` for blank terms, which never match.
+    term_patterns: Vec<Option<usize>>,
+    automaton: Option<AhoCorasick>,
+}
+
+impl GuardrailMatcher {
+    pub fn new(terms: &[String]) -> Self {
+        // Terms that only differ by ASCII case share one pattern. An empty
+        // pattern would match at every offset, so blank terms get none.
+        let mut patterns = Vec::<String>::new();
+        let mut pattern_ids = HashMap::<String, usize>::new();
+        let term_patterns = terms
+            .iter()
+            .map(|term| {
+                if term.trim().is_empty() {
+                    return None;
+                }
+                let key = term.to_ascii_lowercase();
+                Some(*pattern_ids.entry(key.clone()).or_insert_with(|| {
+                    patterns.push(key);
+                    patterns.len() - 1
+                }))
+            })
+            .collect::<Vec<Option<usize>>>();
+        let automaton = if patterns.is_empty() {
+            None
+        } else {
+            AhoCorasick::builder()
+                .ascii_case_insensitive(true)
+                .build(&patterns)
+                .ok()
+        };
+        Self {
+            terms: terms.to_vec(),
+            term_patterns,
+            automaton,
+        }
+    }
+
+    pub fn terms(&self) -> &[String] {
+        &self.terms
+    }
+
+    /// Byte spans of every term occurrence, including overlapping ones.
+    pub fn match_spans(&self, text: &str) -> Vec<(usize, usize, usize)> {
+        let Some(automaton) = &self.automaton else {
+            return Vec::new();
+        };
+        automaton
+            .find_overlapping_iter(text)
+            .map(|m| (m.start(), m.end(), m.pattern().as_usize()))
+            .collect()
+    }
+
+    /// Terms (in configured order) that occur anywhere in `text`.
+    pub fn matched_terms(&self, text: &str) -> Vec<String> {
+        let spans = self.match_spans(text);
+        if spans.is_empty() {
+            return Vec::new();
         }
-        _ => Vec::new(),
+        let found = spans
+            .iter()
+            .map(|(_, _, pattern)| *pattern)
+            .collect::<BTreeSet<usize>>();
+        self.terms
+            .iter()
+            .zip(&self.term_patterns)
+            .filter(|(_, pattern)| pattern.is_some_and(|pattern| found.contains(&pattern)))
+            .map(|(term, _)| term.clone())
+            .collect()
+    }
+
+    /// Replace every region covered by a term occurrence with `replacement`
+    /// in a single pass. Overlapping occurrences collapse into one
+    /// replacement, and replacement text is never re-scanned.
+    pub fn redact(&self, text: &str, replacement: &str) -> String {
+        let mut spans = self
+            .match_spans(text)
+            .into_iter()
+            .map(|(start, end, _)| (start, end))
+            .collect::<Vec<(usize, usize)>>();
+        if spans.is_empty() {
+            return text.to_string();
+        }
+        spans.sort_unstable();
+
+        let mut out = String::with_capacity(text.len());
+        let mut cursor = 0usize;
+        let mut current = spans[0];
+        for &(start, end) in &spans[1..] {
+            if start < current.1 {
+                current.1 = current.1.max(end);
+                continue;
+            }
+            out.push_str(&text[cursor..current.0]);
+            out.push_str(replacement);
+            cursor = current.1;
+            current = (start, end);
+        }
+        out.push_str(&text[cursor..current.0]);
+        out.push_str(replacement);
+        out.push_str(&text[current.1..]);
+        out
     }
 }
 
-/// Redact PII using adk-guardrail PiiRedactor + custom terms.
+/// Lazily-built matchers stored on `RuntimeConfig`. Each is rebuilt only
+/// when its term list changes, so per-call cost is a term-list comparison.
+#[derive(Debug, Clone, Default)]
+pub struct GuardrailMatcherCache(Arc<Mutex<MatcherSlots>>);
+
+#[derive(Debug, Default)]
+struct MatcherSlots {
+    terms: Option<Arc<GuardrailMatcher>>,
+    /// Terms whose severity action is redact, for mixed severity actions.
+    redact: Option<Arc<GuardrailMatcher>>,
+}
+
+fn cached_matcher(
+    slot: &mut Option<Arc<GuardrailMatcher>>,
+    terms: &[String],
+) -> Arc<GuardrailMatcher> {
+    if let Some(matcher) = slot.as_ref()
+        && matcher.terms() == terms
+    {
+        return matcher.clone();
+    }
+    let matcher = Arc::new(GuardrailMatcher::new(terms));
+    *slot = Some(matcher.clone());
+    matcher
+}
+
+impl GuardrailMatcherCache {
+    pub fn with_terms(terms: &[String]) -> Self {
+        Self(Arc::new(Mutex::new(MatcherSlots {
+            terms: Some(Arc::new(GuardrailMatcher::new(terms))),
+            redact: None,
+        })))
+    }
+
+    pub fn get(&self, terms: &[String]) -> Arc<GuardrailMatcher> {
+        let mut slots = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        cached_matcher(&mut slots.terms, terms)
+    }
+
+    /// Matcher for the redact-only subset of the terms. The subset follows
+    /// from the terms, their severities and the severity actions, so it is
+    /// rebuilt whenever any of those change.
+    pub fn get_redact(&self, redact_terms: &[String]) -> Arc<GuardrailMatcher> {
+        let mut slots = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        cached_matcher(&mut slots.redact, redact_terms)
+    }
+}
+
+/// Check text for guardrail term matches (case-insensitive, whole-substring).
+/// Builds a matcher per call; runtime code goes through
+/// [`RuntimeConfig::guardrail_matcher`] instead.
+#[cfg(test)]
+pub fn contains_guardrail_terms(text: &str, terms: &[String]) -> Vec<String> {
+    GuardrailMatcher::new(terms).matched_terms(text)
+}
+
+/// [`redact_text_with_matcher`] with a one-off matcher for `terms`.
+#[cfg(test)]
 pub fn redact_text(text: &str, terms: &[String], replacement: &str) -> String {
+    redact_text_with_matcher(text, &GuardrailMatcher::new(terms), replacement)
+}
+
+/// Redact PII using adk-guardrail PiiRedactor + custom terms.
+pub fn redact_text_with_matcher(
+    text: &str,
+    matcher: &GuardrailMatcher,
+    replacement: &str,
+) -> String {
     // First: PII redaction (emails, phones, SSNs, credit cards)
     let pii = adk_guardrail::PiiRedactor::new();
     let (redacted, _) = pii.redact(text);
 
     // Then: custom term redaction
-    let mut result = redacted;
-    for term in terms {
-        if term.is_empty() { continue; }
-        result = replace_case_insensitive(&result, term, replacement);
-    }
-    result
-}
-
-fn replace_case_insensitive(input: &str, needle: &str, replacement: &str) -> String {
-    if needle.is_empty() { return input.to_string(); }
-    let input_lower = input.to_ascii_lowercase();
-    let needle_lower = needle.to_ascii_lowercase();
-    let mut out = String::new();
-    let mut last = 0;
-    let mut search = 0;
-    while let Some(rel) = input_lower[search..].find(&needle_lower) {
-        let start = search + rel;
-        let end = start + needle.len();
-        out.push_str(&input[last..start]);
-        out.push_str(replacement);
-        last = end;
-        search = end;
+    matcher.redact(&redacted, replacement)
+}
+
+/// Hit counts per severity, with every level present.
+pub fn guardrail_severity_counts(
+    cfg: &RuntimeConfig,
+    hits: &[String],
+) -> BTreeMap<&'static str, usize> {
+    let mut counts = [
+        GuardrailSeverity::Low,
+        GuardrailSeverity::Medium,
+        GuardrailSeverity::High,
+    ]
+    .into_iter()
+    .map(|severity| (guardrail_severity_label(severity), 0usize))
+    .collect::<BTreeMap<&'static str, usize>>();
+    for hit in hits {
+        *counts
+            .entry(guardrail_severity_label(guardrail_term_severity(cfg, hit)))
+            .or_default() += 1;
     }
-    out.push_str(&input[last..]);
-    out
+    counts
 }
 
+/// The highest-severity hit picks the mode. When that mode is redact, only
+/// terms whose own severity maps to redact are replaced.
 pub fn apply_guardrail(
     cfg: &RuntimeConfig,
     telemetry: &TelemetrySink,
@@ -96,17 +310,68 @@ pub fn apply_guardrail(
     mode: GuardrailMode,
     text: &str,
 ) -> Result<String> {
-    if matches!(mode, GuardrailMode::Disabled) {
+    apply_guardrail_with_actions(
+        cfg,
+        telemetry,
+        direction,
+        mode,
+        &cfg.guardrail_severity_actions,
+        text,
+    )
+}
+
+/// Scan retrieved context with `guardrail_retrieval_mode` only. Severity
+/// actions are skipped so trusted local docs that mention a high-severity
+/// term are logged rather than escalated to a block.
+pub fn apply_retrieval_guardrail(
+    cfg: &RuntimeConfig,
+    telemetry: &TelemetrySink,
+    text: &str,
+) -> Result<String> {
+    apply_guardrail_with_actions(
+        cfg,
+        telemetry,
+        "retrieval",
+        cfg.guardrail_retrieval_mode,
+        &BTreeMap::new(),
+        text,
+    )
+}
+
+fn apply_guardrail_with_actions(
+    cfg: &RuntimeConfig,
+    telemetry: &TelemetrySink,
+    direction: &str,
+    mode: GuardrailMode,
+    actions: &BTreeMap<GuardrailSeverity, GuardrailMode>,
+    text: &str,
+) -> Result<String> {
+    if matches!(mode, GuardrailMode::Disabled) && actions.is_empty() {
         return Ok(text.to_string());
     }
 
-    let hits = contains_guardrail_terms(text, &cfg.guardrail_terms);
+    let matcher = cfg.guardrail_matcher();
+    let hits = matcher.matched_terms(text);
     if hits.is_empty() {
         return Ok(text.to_string());
     }
 
+    let severity = hits
+        .iter()
+        .map(|hit| guardrail_term_severity(cfg, hit))
+        .max()
+        .unwrap_or_default();
+    let blanket = mode;
+    let mode = severity_action_mode(actions, severity, blanket);
     let mode_label = guardrail_mode_label(mode);
-    let payload = json!({"direction": direction, "mode": mode_label, "hits": &hits, "hit_count": hits.len()});
+    let payload = json!({
+        "direction": direction,
+        "mode": mode_label,
+        "severity": guardrail_severity_label(severity),
+        "severity_hits": guardrail_severity_counts(cfg, &hits),
+        "hits": &hits,
+        "hit_count": hits.len()
+    });
 
     match mode {
         GuardrailMode::Observe => {
@@ -120,7 +385,24 @@ pub fn apply_guardrail(
             Err(anyhow::anyhow!("guardrail blocked {direction} content due to matched terms"))
         }
         GuardrailMode::Redact => {
-            let redacted = redact_text(text, &hits, &cfg.guardrail_redact_replacement);
+            let redacted = if actions.is_empty() {
+                redact_text_with_matcher(text, &matcher, &cfg.guardrail_redact_replacement)
+            } else {
+                let redact_terms = matcher
+                    .terms()
+                    .iter()
+                    .filter(|term| {
+                        let severity = guardrail_term_severity(cfg, term);
+                        severity_action_mode(actions, severity, blanket) == GuardrailMode::Redact
+                    })
+                    .cloned()
+                    .collect::<Vec<String>>();
+                redact_text_with_matcher(
+                    text,
+                    &cfg.guardrail_matcher_cache.get_redact(&redact_terms),
+                    &cfg.guardrail_redact_replacement,
+                )
+            };
             tracing::warn!(direction, mode = mode_label, hit_count = hits.len(), "Guardrail redacted");
             telemetry.emit(&format!("guardrail.{direction}.redacted"), payload);
             Ok(redacted)
@@ -133,6 +415,16 @@ pub fn buffered_output_required(mode: GuardrailMode) -> bool {
     matches!(mode, GuardrailMode::Block | GuardrailMode::Redact)
 }
 
+/// Output must be buffered when the blanket output mode or any severity
+/// action can block or redact.
+pub fn output_buffering_required(cfg: &RuntimeConfig) -> bool {
+    buffered_output_required(cfg.guardrail_output_mode)
+        || cfg
+            .guardrail_severity_actions
+            .values()
+            .any(|mode| buffered_output_required(*mode))
+}
+
 pub fn enforce_prompt_limit(prompt: &str, max_chars: usize) -> Result<()> {
     if max_chars > 0 && prompt.len() > max_chars {
         return Err(anyhow::anyhow!(
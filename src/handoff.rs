@@ -0,0 +1,311 @@
+//! `/handoff <agent> [instructions]`: pass the conversation to another agent
+//! with a task brief instead of losing the thread.
+//!
+//! The active model writes a short brief (goal, decisions, open questions)
+//! from the session. The runner is then rebuilt for the target agent, the
+//! brief is stored in session state under [`HANDOFF_STATE_KEY`] and goes in
+//! front of the next prompt. `/handoff back` returns to the agent that
+//! handed off last, with the earlier brief as reference.
+//!
+//! Nothing changes until the target runner is built: if the agent is unknown,
+//! the brief fails or the runner cannot be built, the current agent stays.
+//! MCP servers are not rediscovered; the target uses the tools discovered
+//! at chat start.
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+
+use adk_rust::futures::StreamExt;
+use adk_rust::prelude::*;
+use adk_session::SessionService;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::checkpoint::snapshot_session_events;
+use crate::compact::extract_event_text;
+use crate::config::{ResolvedAgent, RuntimeConfig};
+use crate::retrieval_query::user_request_text;
+use crate::session::with_session_busy_retry;
+use crate::telemetry::TelemetrySink;
+
+pub const HANDOFF_STATE_KEY: &str = "zavora.handoff";
+pub const HANDOFF_EVENT: &str = "chat.handoff";
+/// `/handoff back` target.
+pub const HANDOFF_BACK: &str = "back";
+/// Cap on the transcript the brief is written from; older turns drop first.
+pub const HANDOFF_TRANSCRIPT_MAX_CHARS: usize = 12_000;
+
+const HANDOFF_BRIEF_INSTRUCTIONS: &str = "[SYSTEM NOTE: This is an automated handoff request]\n\n\
+Write a concise handoff brief for another agent taking over this conversation. \
+Use exactly these markdown sections, with short bullet points:\n\
+## Current goal\n\
+## Decisions made\n\
+## Open questions\n\n\
+Do not respond conversationally and do not continue the work yourself.";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HandoffRecord {
+    pub from_agent: String,
+    pub to_agent: String,
+    #[serde(default)]
+    pub instructions: Option<String>,
+    pub brief: String,
+    pub created_at: String,
+}
+
+impl HandoffRecord {
+    /// Text put in front of the first prompt the target agent sees.
+    pub fn prompt_context(&self) -> String {
+        let mut out = format!(
+            "Handoff brief from agent '{}' (you are now '{}'):\n{}\n",
+            self.from_agent,
+            self.to_agent,
+            self.brief.trim()
+        );
+        if let Some(instructions) = &self.instructions {
+            out.push_str(&format!("\nHandoff instructions: {instructions}\n"));
+        }
+        out
+    }
+}
+
+/// `<agent> [instructions]` → target and optional instructions.
+pub fn parse_handoff_args(arg: &str) -> Option<(String, Option<String>)> {
+    let mut parts = arg.trim().splitn(2, char::is_whitespace);
+    let target = parts.next().filter(|target| !target.is_empty())?;
+    let instructions = parts
+        .next()
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+        .map(str::to_string);
+    Some((target.to_string(), instructions))
+}
+
+/// `cfg` with `agent` active. Provider and model change only when the agent
+/// sets them.
+pub fn runtime_config_for_agent(cfg: &RuntimeConfig, agent: &ResolvedAgent) -> RuntimeConfig {
+    let mut switched = cfg.clone();
+    switched.agent_name = agent.name.clone();
+    switched.agent_source = agent.source;
+    switched.agent_description = agent.config.description.clone();
+    switched.agent_instruction = agent.config.instruction.clone();
+    switched.agent_resource_paths = agent.config.resource_paths.clone();
+    switched.agent_allow_tools = agent.config.allow_tools.clone();
+    switched.agent_deny_tools = agent.config.deny_tools.clone();
+    if let Some(provider) = agent.config.provider {
+        switched.provider = provider;
+        switched.model = agent.config.model.clone();
+    } else if agent.config.model.is_some() {
+        switched.model = agent.config.model.clone();
+    }
+    switched
+}
+
+/// The agent `target` names; `back` is the agent the last handoff came from.
+pub fn resolve_handoff_target<'a>(
+    target: &str,
+    agents: &'a HashMap<String, ResolvedAgent>,
+    previous: Option<&HandoffRecord>,
+) -> Result<&'a ResolvedAgent> {
+    let name = if target.eq_ignore_ascii_case(HANDOFF_BACK) {
+        previous
+            .map(|record| record.from_agent.as_str())
+            .ok_or_else(|| anyhow::anyhow!("no earlier handoff in this session to go back to"))?
+    } else {
+        target
+    };
+    agents.get(name).ok_or_else(|| {
+        let mut known = agents.keys().map(String::as_str).collect::<Vec<&str>>();
+        known.sort_unstable();
+        anyhow::anyhow!(
+            "unknown agent '{name}'. Available agents: {}",
+            known.join(", ")
+        )
+    })
+}
+
+/// Recent turns as `User:`/`Assistant:` lines, newest kept when the
+/// transcript exceeds `max_chars`.
+pub fn handoff_transcript(events: &[Event], max_chars: usize) -> String {
+    let mut lines = Vec::new();
+    for event in events {
+        let text = extract_event_text(event);
+        let (role, text) = if event.author == "user" {
+            ("User", user_request_text(&text))
+        } else {
+            ("Assistant", text.as_str())
+        };
+        let text = text.trim();
+        if !text.is_empty() {
+            lines.push(format!("{role}: {text}"));
+        }
+    }
+    let mut total = 0;
+    let mut start = lines.len();
+    while start > 0 && total + lines[start - 1].len() + 1 <= max_chars {
+        total += lines[start - 1].len() + 1;
+        start -= 1;
+    }
+    lines[start..].join("\n")
+}
+
+pub fn handoff_brief_prompt(
+    from_agent: &str,
+    to_agent: &str,
+    transcript: &str,
+    instructions: Option<&str>,
+    previous: Option<&HandoffRecord>,
+) -> String {
+    let mut prompt = format!("{HANDOFF_BRIEF_INSTRUCTIONS}\n\n");
+    prompt.push_str(&format!(
+        "Handing off from agent '{from_agent}' to agent '{to_agent}'.\n"
+    ));
+    if let Some(instructions) = instructions {
+        prompt.push_str(&format!(
+            "Instructions for the next agent: {instructions}\n"
+        ));
+    }
+    if let Some(previous) = previous {
+        prompt.push_str(&format!(
+            "\nEarlier handoff brief ('{}' to '{}'):\n{}\n",
+            previous.from_agent,
+            previous.to_agent,
+            previous.brief.trim()
+        ));
+    }
+    prompt.push_str(&format!("\nCONVERSATION:\n\n{transcript}\n"));
+    prompt
+}
+
+/// One call to `model`, no tools and no session.
+pub async fn generate_handoff_brief(model: Arc<dyn Llm>, prompt: &str) -> Result<String> {
+    let request = LlmRequest::new(
+        model.name().to_string(),
+        vec![Content::new("user").with_text(prompt)],
+    );
+    let mut stream = model
+        .generate_content(request, false)
+        .await
+        .context("failed to invoke model for the handoff brief")?;
+    let mut brief = String::new();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.context("handoff brief stream error")?;
+        if let Some(content) = chunk.content {
+            for part in content.parts {
+                if let Part::Text { text } = part {
+                    brief.push_str(&text);
+                }
+            }
+        }
+    }
+    let brief = brief.trim();
+    if brief.is_empty() {
+        return Err(anyhow::anyhow!("the model returned an empty handoff brief"));
+    }
+    Ok(brief.to_string())
+}
+
+/// The last handoff recorded in the session, if any.
+pub async fn load_handoff_record(
+    session_service: &Arc<dyn SessionService>,
+    cfg: &RuntimeConfig,
+) -> Result<Option<HandoffRecord>> {
+    let session = session_service
+        .get(adk_session::GetRequest {
+            app_name: cfg.app_name.clone(),
+            user_id: cfg.user_id.clone(),
+            session_id: cfg.session_id.clone(),
+            num_recent_events: None,
+            after: None,
+        })
+        .await
+        .context("failed to load session for handoff")?;
+    Ok(session
+        .state()
+        .all()
+        .get(HANDOFF_STATE_KEY)
+        .cloned()
+        .and_then(|value| serde_json::from_value(value).ok()))
+}
+
+/// Store `record` under [`HANDOFF_STATE_KEY`] with a content-less event.
+pub async fn save_handoff_record(
+    session_service: &Arc<dyn SessionService>,
+    cfg: &RuntimeConfig,
+    record: &HandoffRecord,
+) -> Result<()> {
+    let value = serde_json::to_value(record).context("failed to encode handoff record")?;
+    let mut event = Event::new("handoff");
+    event.author = "system".to_string();
+    event
+        .actions
+        .state_delta
+        .insert(HANDOFF_STATE_KEY.to_string(), value);
+    with_session_busy_retry(cfg, "append_event", || {
+        session_service.append_event(&cfg.session_id, event.clone())
+    })
+    .await
+    .context("failed to record handoff in session state")
+}
+
+pub fn handoff_event_payload(record: &HandoffRecord) -> Value {
+    json!({
+        "source_agent": record.from_agent,
+        "target_agent": record.to_agent,
+        "brief_chars": record.brief.chars().count(),
+        "has_instructions": record.instructions.is_some(),
+    })
+}
+
+/// Run a handoff: write the brief with `model`, build the target runtime
+/// with `build`, then switch `cfg`, record the brief and emit
+/// [`HANDOFF_EVENT`]. On any error `cfg` and the session are untouched.
+/// Returns what `build` produced and the record.
+#[allow(clippy::too_many_arguments)]
+pub async fn run_handoff<T, F, Fut>(
+    cfg: &mut RuntimeConfig,
+    agents: &HashMap<String, ResolvedAgent>,
+    session_service: &Arc<dyn SessionService>,
+    model: Arc<dyn Llm>,
+    telemetry: &TelemetrySink,
+    target: &str,
+    instructions: Option<String>,
+    build: F,
+) -> Result<(T, HandoffRecord)>
+where
+    F: FnOnce(RuntimeConfig) -> Fut,
+    Fut: Future<Output = Result<(T, RuntimeConfig)>>,
+{
+    let previous = load_handoff_record(session_service, cfg).await?;
+    let agent = resolve_handoff_target(target, agents, previous.as_ref())?;
+    if agent.name == cfg.agent_name {
+        return Err(anyhow::anyhow!(
+            "agent '{}' is already active",
+            cfg.agent_name
+        ));
+    }
+    let events = snapshot_session_events(session_service, cfg).await?;
+    let transcript = handoff_transcript(&events, HANDOFF_TRANSCRIPT_MAX_CHARS);
+    let prompt = handoff_brief_prompt(
+        &cfg.agent_name,
+        &agent.name,
+        &transcript,
+        instructions.as_deref(),
+        previous.as_ref(),
+    );
+    let brief = generate_handoff_brief(model, &prompt).await?;
+    let (built, switched) = build(runtime_config_for_agent(cfg, agent)).await?;
+
+    let record = HandoffRecord {
+        from_agent: cfg.agent_name.clone(),
+        to_agent: agent.name.clone(),
+        instructions,
+        brief,
+        created_at: chrono::Utc::now().to_rfc3339(),
+    };
+    save_handoff_record(session_service, cfg, &record).await?;
+    *cfg = switched;
+    telemetry.emit(HANDOFF_EVENT, handoff_event_payload(&record));
+    Ok((built, record))
+}
@@ -0,0 +1,329 @@
+//! Automatic history trimming for chat turns near the context limit.
+//!
+//! Before each chat prompt the replayed history, the new prompt, the
+//! retrieval allowance (`retrieval_max_chars`) and the instruction overhead
+//! are measured in characters against `history_trim_threshold` of the
+//! model's context window (tokens × [`TOKEN_CHAR_RATIO`]). Over budget, the
+//! oldest exchanges (a user message and everything up to the next one) are
+//! left out of what the runner replays until the rest fits, always keeping
+//! the latest exchange. Nothing is deleted: [`TrimmedHistorySessionService`]
+//! only hides the events from the runner's session read for that turn, and
+//! the first replayed user message starts with a one-line notice. Each
+//! trimmed turn emits `chat.history_trimmed`; `/compact` is the lasting fix.
+use std::sync::{Arc, Mutex};
+
+use adk_rust::prelude::*;
+use adk_session::*;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde_json::json;
+
+use crate::cli::RetrievalBackend;
+use crate::config::RuntimeConfig;
+use crate::context::{
+    PROMPT_OVERHEAD_TOKENS, TOKEN_CHAR_RATIO, event_part_chars, model_context_window,
+    tokens_to_chars,
+};
+use crate::telemetry::TelemetrySink;
+
+/// Which leading events of a session to leave out, and why.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryTrim {
+    /// Events before this index are not replayed.
+    pub skip_events: usize,
+    pub omitted_exchanges: usize,
+    pub kept_exchanges: usize,
+    pub budget_chars: usize,
+    /// Estimated context before and after trimming, in characters.
+    pub chars_before: usize,
+    pub chars_after: usize,
+}
+
+impl HistoryTrim {
+    /// Line prepended to the first replayed user message.
+    pub fn notice(&self) -> String {
+        format!(
+            "[{} earlier exchange{} omitted to fit the context window.]",
+            self.omitted_exchanges,
+            if self.omitted_exchanges == 1 { "" } else { "s" }
+        )
+    }
+
+    pub fn telemetry_payload(&self) -> serde_json::Value {
+        json!({
+            "omitted_exchanges": self.omitted_exchanges,
+            "omitted_events": self.skip_events,
+            "kept_exchanges": self.kept_exchanges,
+            "budget_tokens": self.budget_chars / TOKEN_CHAR_RATIO,
+            "estimated_tokens_before": self.chars_before / TOKEN_CHAR_RATIO,
+            "estimated_tokens_after": self.chars_after / TOKEN_CHAR_RATIO,
+        })
+    }
+}
+
+fn event_chars(event: &Event) -> usize {
+    let (text_chars, fn_chars) = event_part_chars(event);
+    text_chars + fn_chars
+}
+
+fn starts_exchange(event: &Event) -> bool {
+    event.author == "user"
+        && event.llm_response.content.as_ref().is_some_and(|content| {
+            content
+                .parts
+                .iter()
+                .any(|part| matches!(part, Part::Text { .. }))
+        })
+}
+
+/// Trim `events` so they plus `fixed_chars` fit `budget_chars`, dropping
+/// whole exchanges oldest first. `None` when everything fits or there is
+/// only one exchange to keep.
+pub fn plan_history_trim(
+    events: &[Event],
+    fixed_chars: usize,
+    budget_chars: usize,
+) -> Option<HistoryTrim> {
+    // suffix[i] is the size of events[i..].
+    let mut suffix = vec![0usize; events.len() + 1];
+    for index in (0..events.len()).rev() {
+        suffix[index] = suffix[index + 1] + event_chars(&events[index]);
+    }
+    let chars_before = suffix[0] + fixed_chars;
+    if chars_before <= budget_chars {
+        return None;
+    }
+    let starts = events
+        .iter()
+        .enumerate()
+        .filter(|(_, event)| starts_exchange(event))
+        .map(|(index, _)| index)
+        .collect::<Vec<usize>>();
+    let last = starts.len().checked_sub(1)?;
+    // The exchange a trim starts at; events before the first user message
+    // go with the first exchange.
+    let keep_from = (1..=last)
+        .find(|&exchange| suffix[starts[exchange]] + fixed_chars <= budget_chars)
+        .unwrap_or(last);
+    if keep_from == 0 {
+        return None;
+    }
+    let skip_events = starts[keep_from];
+    Some(HistoryTrim {
+        skip_events,
+        omitted_exchanges: keep_from,
+        kept_exchanges: starts.len() - keep_from,
+        budget_chars,
+        chars_before,
+        chars_after: suffix[skip_events] + fixed_chars,
+    })
+}
+
+/// Character budget for a chat turn: `history_trim_threshold` of the
+/// model's context window. `None` when trimming is off.
+pub fn history_trim_budget_chars(
+    cfg: &RuntimeConfig,
+    provider: &str,
+    model: &str,
+) -> Option<usize> {
+    if cfg.history_trim_threshold <= 0.0 {
+        return None;
+    }
+    let window = model_context_window(model, provider) as f64;
+    Some(tokens_to_chars(
+        (window * cfg.history_trim_threshold.min(1.0)) as usize,
+    ))
+}
+
+/// Characters a turn adds on top of the replayed history: the prompt, the
+/// retrieval allowance and the instruction plus tool declarations.
+pub fn history_trim_fixed_chars(cfg: &RuntimeConfig, prompt: &str) -> usize {
+    let retrieval = if cfg.retrieval_backend == RetrievalBackend::Disabled {
+        0
+    } else {
+        cfg.retrieval_max_chars
+    };
+    prompt.len()
+        + retrieval
+        + cfg.agent_instruction.as_deref().map_or(0, str::len)
+        + tokens_to_chars(PROMPT_OVERHEAD_TOKENS)
+}
+
+/// The trim in effect for the runner's next session read, shared between
+/// the chat loop and [`TrimmedHistorySessionService`].
+#[derive(Debug, Clone, Default)]
+pub struct HistoryWindow {
+    active: Arc<Mutex<Option<(String, HistoryTrim)>>>,
+}
+
+impl HistoryWindow {
+    /// Apply `trim` to session `session_id` until the guard drops.
+    pub fn apply(&self, session_id: &str, trim: Option<HistoryTrim>) -> HistoryWindowGuard {
+        *self.active.lock().expect("history window lock") =
+            trim.map(|trim| (session_id.to_string(), trim));
+        HistoryWindowGuard {
+            window: self.clone(),
+        }
+    }
+
+    fn get(&self, session_id: &str) -> Option<HistoryTrim> {
+        self.active
+            .lock()
+            .expect("history window lock")
+            .as_ref()
+            .filter(|(id, _)| id == session_id)
+            .map(|(_, trim)| trim.clone())
+    }
+}
+
+/// Clears the window on drop, so only the turn it was planned for is
+/// trimmed.
+pub struct HistoryWindowGuard {
+    window: HistoryWindow,
+}
+
+impl Drop for HistoryWindowGuard {
+    fn drop(&mut self) {
+        if let Ok(mut active) = self.window.active.lock() {
+            *active = None;
+        }
+    }
+}
+
+/// Plan the trim for the next chat turn from the session `history` and
+/// report it: a `chat.history_trimmed` event and, when more exchanges are
+/// omitted than last turn, a stderr hint.
+pub fn plan_chat_history_trim(
+    cfg: &RuntimeConfig,
+    telemetry: &TelemetrySink,
+    history: &[Event],
+    prompt: &str,
+    provider: &str,
+    model: &str,
+    previously_omitted: usize,
+) -> Option<HistoryTrim> {
+    let budget = history_trim_budget_chars(cfg, provider, model)?;
+    let trim = plan_history_trim(history, history_trim_fixed_chars(cfg, prompt), budget)?;
+    telemetry.emit("chat.history_trimmed", trim.telemetry_payload());
+    if trim.omitted_exchanges > previously_omitted {
+        eprintln!(
+            "Note: left the {} oldest exchange{} out of this turn to stay under {:.0}% of the \
+             context window; /compact summarizes them instead.",
+            trim.omitted_exchanges,
+            if trim.omitted_exchanges == 1 { "" } else { "s" },
+            cfg.history_trim_threshold * 100.0
+        );
+    }
+    Some(trim)
+}
+
+/// Session service whose reads of the windowed session leave out the
+/// trimmed events; writes and every other read pass through.
+pub struct TrimmedHistorySessionService {
+    inner: Arc<dyn SessionService>,
+    window: HistoryWindow,
+}
+
+impl TrimmedHistorySessionService {
+    pub fn new(inner: Arc<dyn SessionService>, window: HistoryWindow) -> Self {
+        Self { inner, window }
+    }
+}
+
+/// `events` without the trimmed prefix, the notice leading the first one.
+pub fn trimmed_events(mut events: Vec<Event>, trim: &HistoryTrim) -> Vec<Event> {
+    let mut kept = events.split_off(trim.skip_events.min(events.len()));
+    if let Some(content) = kept
+        .first_mut()
+        .and_then(|event| event.llm_response.content.as_mut())
+    {
+        content.parts.insert(
+            0,
+            Part::Text {
+                text: format!("{}\n", trim.notice()),
+            },
+        );
+    }
+    kept
+}
+
+#[async_trait]
+impl SessionService for TrimmedHistorySessionService {
+    async fn create(&self, req: CreateRequest) -> adk_rust::Result<Box<dyn Session>> {
+        self.inner.create(req).await
+    }
+
+    async fn get(&self, req: GetRequest) -> adk_rust::Result<Box<dyn Session>> {
+        let trim = (req.num_recent_events.is_none() && req.after.is_none())
+            .then(|| self.window.get(&req.session_id))
+            .flatten();
+        let session = self.inner.get(req).await?;
+        let Some(trim) = trim else {
+            return Ok(session);
+        };
+        let events = TrimmedEvents(trimmed_events(session.events().all(), &trim));
+        Ok(Box::new(TrimmedSession {
+            inner: session,
+            events,
+        }))
+    }
+
+    async fn list(&self, req: ListRequest) -> adk_rust::Result<Vec<Box<dyn Session>>> {
+        self.inner.list(req).await
+    }
+
+    async fn delete(&self, req: DeleteRequest) -> adk_rust::Result<()> {
+        self.inner.delete(req).await
+    }
+
+    async fn append_event(&self, session_id: &str, event: Event) -> adk_rust::Result<()> {
+        self.inner.append_event(session_id, event).await
+    }
+}
+
+struct TrimmedSession {
+    inner: Box<dyn Session>,
+    events: TrimmedEvents,
+}
+
+struct TrimmedEvents(Vec<Event>);
+
+impl Events for TrimmedEvents {
+    fn all(&self) -> Vec<Event> {
+        self.0.clone()
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn at(&self, index: usize) -> Option<&Event> {
+        self.0.get(index)
+    }
+}
+
+impl Session for TrimmedSession {
+    fn id(&self) -> &str {
+        self.inner.id()
+    }
+
+    fn app_name(&self) -> &str {
+        self.inner.app_name()
+    }
+
+    fn user_id(&self) -> &str {
+        self.inner.user_id()
+    }
+
+    fn state(&self) -> &dyn State {
+        self.inner.state()
+    }
+
+    fn events(&self) -> &dyn Events {
+        &self.events
+    }
+
+    fn last_update_time(&self) -> DateTime<Utc> {
+        self.inner.last_update_time()
+    }
+}
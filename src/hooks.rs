@@ -2,6 +2,7 @@ use std::collections::HashMap;
 use std::fmt;
 use std::time::{Duration, Instant};
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::{Value, json};
 
@@ -40,7 +41,7 @@ impl fmt::Display for HookPoint {
 const DEFAULT_TIMEOUT_MS: u64 = 30_000;
 const DEFAULT_MAX_OUTPUT: usize = 10_240;
 
-#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, PartialEq, Eq, Hash)]
 #[serde(deny_unknown_fields)]
 pub struct HookConfig {
     pub command: String,
@@ -283,3 +284,81 @@ pub fn parse_hooks_map(
     }
     map
 }
+
+// ---------------------------------------------------------------------------
+// Chat completion notifications (`chat_notify_command`)
+// ---------------------------------------------------------------------------
+
+/// Upper bound for a notification command; notify-send/osascript return fast.
+pub const CHAT_NOTIFY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Whether a response that took `elapsed` should trigger a notification.
+pub fn chat_notify_due(
+    command: Option<&str>,
+    enabled: bool,
+    threshold_secs: u64,
+    elapsed: Duration,
+) -> bool {
+    enabled && command.is_some() && elapsed >= Duration::from_secs(threshold_secs)
+}
+
+/// Run the notification command with `ZAVORA_DURATION_MS`, `ZAVORA_STATUS`
+/// and `ZAVORA_SESSION_ID` in its environment.
+pub async fn run_chat_notify_command(
+    command: &str,
+    duration: Duration,
+    status: &str,
+    session_id: &str,
+    timeout: Duration,
+) -> Result<(), String> {
+    let mut cmd = tokio::process::Command::new("bash");
+    cmd.arg("-c")
+        .arg(command)
+        .env("ZAVORA_DURATION_MS", duration.as_millis().to_string())
+        .env("ZAVORA_STATUS", status)
+        .env("ZAVORA_SESSION_ID", session_id)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::piped())
+        .kill_on_drop(true);
+
+    let child = cmd
+        .spawn()
+        .map_err(|err| format!("failed to execute: {err}"))?;
+    match tokio::time::timeout(timeout, child.wait_with_output()).await {
+        Ok(Ok(output)) if output.status.success() => Ok(()),
+        Ok(Ok(output)) => Err(format!(
+            "exited with {}: {}",
+            output.status.code().unwrap_or(-1),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )),
+        Ok(Err(err)) => Err(format!("failed to execute: {err}")),
+        Err(_) => Err(format!("timed out after {}ms", timeout.as_millis())),
+    }
+}
+
+/// Fire the notification in the background when the response crossed the
+/// configured threshold. Failures only log a warning.
+pub fn spawn_chat_notification(
+    command: Option<&str>,
+    enabled: bool,
+    threshold_secs: u64,
+    elapsed: Duration,
+    status: &str,
+    session_id: &str,
+) -> Option<tokio::task::JoinHandle<()>> {
+    if !chat_notify_due(command, enabled, threshold_secs, elapsed) {
+        return None;
+    }
+    let command = command?.to_string();
+    let status = status.to_string();
+    let session_id = session_id.to_string();
+    Some(tokio::spawn(async move {
+        if let Err(err) =
+            run_chat_notify_command(&command, elapsed, &status, &session_id, CHAT_NOTIFY_TIMEOUT)
+                .await
+        {
+            tracing::warn!(command = %command, error = %err, "chat notify command failed");
+        }
+    }))
+}
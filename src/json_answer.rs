@@ -0,0 +1,280 @@
+/// Strict JSON answers for `ask --json-schema`.
+///
+/// The schema is summarized into the agent instruction, every answer is
+/// parsed and checked with a small validator (`type`, `required`, `enum`,
+/// `properties`, `items`), and failures are fed back to the model for up to
+/// `--json-repair-attempts` extra turns.
+use std::future::Future;
+
+use anyhow::{Context, Result};
+use serde_json::{Value, json};
+
+use crate::config::RuntimeConfig;
+use crate::telemetry::TelemetrySink;
+
+pub const DEFAULT_JSON_REPAIR_ATTEMPTS: u32 = 2;
+
+pub fn load_json_schema(path: &str) -> Result<Value> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read JSON schema '{path}'"))?;
+    let schema = serde_json::from_str::<Value>(&text)
+        .with_context(|| format!("JSON schema '{path}' is not valid JSON"))?;
+    if !schema.is_object() {
+        return Err(anyhow::anyhow!(
+            "JSON schema '{path}' must be a JSON object"
+        ));
+    }
+    Ok(schema)
+}
+
+fn type_names(schema: &Value) -> Vec<&str> {
+    match schema.get("type") {
+        Some(Value::String(name)) => vec![name.as_str()],
+        Some(Value::Array(names)) => names.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn value_type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(number) if number.is_i64() || number.is_u64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn matches_type(value: &Value, name: &str) -> bool {
+    match name {
+        // Integers are numbers too.
+        "number" => value.is_number(),
+        other => value_type_name(value) == other,
+    }
+}
+
+/// Validation errors for `value`, each prefixed with its `$.path`. Empty
+/// when the value conforms.
+pub fn validate_json(schema: &Value, value: &Value) -> Vec<String> {
+    let mut errors = Vec::new();
+    validate_at(schema, value, "$", &mut errors);
+    errors
+}
+
+fn validate_at(schema: &Value, value: &Value, path: &str, errors: &mut Vec<String>) {
+    let types = type_names(schema);
+    if !types.is_empty() && !types.iter().any(|name| matches_type(value, name)) {
+        errors.push(format!(
+            "{path}: expected {}, got {}",
+            types.join(" or "),
+            value_type_name(value)
+        ));
+        return;
+    }
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array)
+        && !allowed.contains(value)
+    {
+        let allowed = allowed
+            .iter()
+            .map(Value::to_string)
+            .collect::<Vec<String>>()
+            .join(", ");
+        errors.push(format!("{path}: {value} is not one of {allowed}"));
+    }
+    if let Value::Object(object) = value {
+        for field in schema
+            .get("required")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+        {
+            if !object.contains_key(field) {
+                errors.push(format!("{path}: missing required field '{field}'"));
+            }
+        }
+        if let Some(properties) = schema.get("properties").and_then(Value::as_object) {
+            for (name, property) in properties {
+                if let Some(child) = object.get(name) {
+                    validate_at(property, child, &format!("{path}.{name}"), errors);
+                }
+            }
+        }
+    }
+    if let (Value::Array(items), Some(item_schema)) = (value, schema.get("items")) {
+        for (index, item) in items.iter().enumerate() {
+            validate_at(item_schema, item, &format!("{path}[{index}]"), errors);
+        }
+    }
+}
+
+/// One line per schema node, e.g. `$.releases[].name: string (required)`.
+pub fn summarize_json_schema(schema: &Value) -> String {
+    let mut lines = Vec::new();
+    summarize_at(schema, "$", false, &mut lines);
+    lines.join("\n")
+}
+
+fn summarize_at(schema: &Value, path: &str, required: bool, lines: &mut Vec<String>) {
+    let types = type_names(schema);
+    let mut line = format!(
+        "- {path}: {}",
+        if types.is_empty() {
+            "any".to_string()
+        } else {
+            types.join(" or ")
+        }
+    );
+    if required {
+        line.push_str(" (required)");
+    }
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+        let allowed = allowed
+            .iter()
+            .map(Value::to_string)
+            .collect::<Vec<String>>()
+            .join(", ");
+        line.push_str(&format!(", one of {allowed}"));
+    }
+    if let Some(description) = schema.get("description").and_then(Value::as_str) {
+        line.push_str(&format!(" — {description}"));
+    }
+    lines.push(line);
+
+    let required_fields = schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|fields| {
+            fields
+                .iter()
+                .filter_map(Value::as_str)
+                .collect::<Vec<&str>>()
+        })
+        .unwrap_or_default();
+    if let Some(properties) = schema.get("properties").and_then(Value::as_object) {
+        for (name, property) in properties {
+            let child = format!("{path}.{name}");
+            summarize_at(
+                property,
+                &child,
+                required_fields.contains(&name.as_str()),
+                lines,
+            );
+        }
+    }
+    if let Some(items) = schema.get("items") {
+        summarize_at(items, &format!("{path}[]"), false, lines);
+    }
+}
+
+pub fn json_schema_instruction(schema: &Value) -> String {
+    format!(
+        "Answer with a single JSON value and nothing else: no prose, no markdown code fences. \
+         It must conform to this schema:\n{}",
+        summarize_json_schema(schema)
+    )
+}
+
+/// `cfg` with the schema summary appended to the agent instruction, so it
+/// stays in the system instruction for repair turns too.
+pub fn json_schema_runtime_config(cfg: &RuntimeConfig, schema: &Value) -> RuntimeConfig {
+    let instruction = json_schema_instruction(schema);
+    let mut json_cfg = cfg.clone();
+    json_cfg.agent_instruction = Some(match cfg.agent_instruction.as_deref() {
+        Some(existing) if !existing.trim().is_empty() => format!("{existing}\n\n{instruction}"),
+        _ => instruction,
+    });
+    json_cfg
+}
+
+/// Parse an answer as JSON, tolerating a surrounding markdown code fence.
+pub fn parse_json_answer(answer: &str) -> std::result::Result<Value, String> {
+    let trimmed = answer.trim();
+    let unfenced = trimmed
+        .strip_prefix("```json")
+        .or_else(|| trimmed.strip_prefix("```"))
+        .and_then(|rest| rest.trim_end().strip_suffix("```"))
+        .unwrap_or(trimmed)
+        .trim();
+    serde_json::from_str::<Value>(unfenced).map_err(|err| format!("$: not valid JSON ({err})"))
+}
+
+pub fn json_repair_prompt(errors: &[String]) -> String {
+    format!(
+        "Your previous answer did not conform to the required JSON schema:\n{}\n\
+         Reply again with only the corrected JSON value.",
+        errors
+            .iter()
+            .map(|error| format!("- {error}"))
+            .collect::<Vec<String>>()
+            .join("\n")
+    )
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonAnswer {
+    pub value: Value,
+    /// Model turns used, including the first.
+    pub attempts: u32,
+}
+
+/// Ask, validate, and repair. `ask` receives `None` for the first turn and
+/// the repair prompt for follow-ups, and returns the (guarded) answer text.
+/// Fails with the last validation errors once `repair_attempts` follow-ups
+/// are used up.
+pub async fn ask_json_with_repair<F, Fut>(
+    schema: &Value,
+    repair_attempts: u32,
+    telemetry: &TelemetrySink,
+    mut ask: F,
+) -> Result<JsonAnswer>
+where
+    F: FnMut(Option<String>) -> Fut,
+    Fut: Future<Output = Result<String>>,
+{
+    let mut repair_prompt = None;
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        let answer = ask(repair_prompt.take()).await?;
+        let errors = match parse_json_answer(&answer) {
+            Ok(value) => {
+                let errors = validate_json(schema, &value);
+                if errors.is_empty() {
+                    telemetry.emit(
+                        "ask.json_schema",
+                        json!({ "valid": true, "attempts": attempts }),
+                    );
+                    return Ok(JsonAnswer { value, attempts });
+                }
+                errors
+            }
+            Err(parse_error) => vec![parse_error],
+        };
+        if attempts > repair_attempts {
+            telemetry.emit(
+                "ask.json_schema",
+                json!({ "valid": false, "attempts": attempts, "errors": errors }),
+            );
+            return Err(anyhow::anyhow!(
+                "answer did not match the JSON schema after {attempts} attempt(s):\n{}",
+                errors
+                    .iter()
+                    .map(|error| format!("- {error}"))
+                    .collect::<Vec<String>>()
+                    .join("\n")
+            ));
+        }
+        repair_prompt = Some(json_repair_prompt(&errors));
+    }
+}
+
+pub fn render_json_answer(value: &Value, pretty: bool) -> String {
+    if pretty {
+        serde_json::to_string_pretty(value).unwrap_or_else(|_| value.to_string())
+    } else {
+        value.to_string()
+    }
+}
@@ -1,38 +1,90 @@
 pub mod agent_catalog;
+pub mod alias;
 pub mod agents;
+pub mod answer_cache;
+pub mod batch;
 pub mod benchmark;
+pub mod build_info;
+pub mod change_log;
 pub mod chat;
+pub mod chat_input;
+pub mod chat_stats;
+pub mod chunking;
 pub mod checkpoint;
 pub mod cli;
 pub mod compact;
+pub mod compare;
 pub mod config;
+pub mod config_edit;
+pub mod config_schema;
+pub mod content_injection;
 pub mod context;
+pub mod debug_bundle;
 pub mod doctor;
+pub mod ephemeral;
 pub mod error;
 pub mod eval;
+pub mod fake_provider;
 pub mod file_history;
+pub mod file_lock;
 pub mod guardrail;
+pub mod history_trim;
+pub mod handoff;
 pub mod hooks;
+pub mod json_answer;
 pub mod lsp;
+pub mod maintenance;
 pub mod markdown;
 pub mod mcp;
 pub mod mcp_auth;
+pub mod mcp_breaker;
 pub mod mcp_server;
+pub mod memory_facts;
+pub mod migrations;
+pub mod model_catalog;
 pub mod onboarding;
+pub mod paths;
+pub mod pager;
+pub mod palette;
+pub mod profile_inheritance;
+pub mod process_group;
 pub mod profiles;
+pub mod progress;
+pub mod prompt_assembly;
+pub mod prompt_input;
+pub mod provenance;
 pub mod provider;
 pub mod ralph;
+pub mod read_only;
 pub mod retrieval;
+pub mod retrieval_query;
+pub mod retrieval_watch;
+pub mod retrieval_calibration;
 pub mod runner;
+pub mod sanitize;
 pub mod server;
+pub mod server_limits;
 pub mod session;
+pub mod session_bundle;
+pub mod session_crypto;
+pub mod session_lock;
+pub mod starters;
 pub mod streaming;
 pub mod telemetry;
+pub mod telemetry_export;
 pub mod theme;
 pub mod todos;
+pub mod tool_output;
 pub mod tool_policy;
 pub mod tools;
+pub mod trust;
+pub mod ui;
+pub mod update_check;
+pub mod usage;
+pub mod verbosity;
+pub mod watchdog;
 pub mod workflow;
+pub mod workspace_template;
 
 #[cfg(test)]
 mod tests;
@@ -10,6 +10,7 @@ use tokio::process::Command;
 use tokio::sync::Mutex;
 
 use super::client::LspClient;
+use crate::paths::ZavoraPaths;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Language {
@@ -249,8 +250,8 @@ fn path_to_uri(path: &Path) -> String {
 }
 
 /// Load LSP config from .zavora/lsp.json or .kiro/settings/lsp.json.
-pub fn load_lsp_config() -> Option<LspConfig> {
-    let candidates = [".zavora/lsp.json", ".kiro/settings/lsp.json"];
+pub fn load_lsp_config(paths: &ZavoraPaths) -> Option<LspConfig> {
+    let candidates = [paths.lsp_config_file(), PathBuf::from(".kiro/settings/lsp.json")];
     for path in &candidates {
         if let Ok(content) = std::fs::read_to_string(path) {
             if let Ok(config) = serde_json::from_str::<LspConfig>(&content) {
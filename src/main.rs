@@ -1,29 +1,84 @@
+use std::io::IsTerminal;
+use std::path::PathBuf;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
+use adk_session::InMemorySessionService;
 use anyhow::{Context, Result};
 use clap::Parser;
 use serde_json::json;
 
 use zavora_cli::agent_catalog::*;
+use zavora_cli::answer_cache::{
+    AnswerCache, AnswerCacheKeyInput, answer_cache_key, answer_with_cache, ask_cache_params,
+    run_cache_clear, unix_now_secs,
+};
+use zavora_cli::alias::{expand_cli_aliases, run_alias_list, run_alias_rm, run_alias_set};
+use zavora_cli::batch::{
+    BatchOptions, BatchRuntime, format_batch_summary, load_batch_file, run_batch,
+};
+use zavora_cli::build_info::build_telemetry_fields;
 use zavora_cli::chat::*;
 use zavora_cli::cli::*;
+use zavora_cli::compare::*;
 use zavora_cli::config::*;
+use zavora_cli::config_edit::{run_config_set, run_config_unset};
+use zavora_cli::config_schema::run_config_schema;
+use zavora_cli::debug_bundle::{DebugBundleOptions, RunSelector, run_debug_bundle};
 use zavora_cli::doctor::*;
+use zavora_cli::ephemeral::{ephemeral_runtime_config, use_ephemeral_session};
 use zavora_cli::error::*;
 use zavora_cli::eval::*;
 use zavora_cli::guardrail::*;
+use zavora_cli::json_answer::{
+    ask_json_with_repair, json_schema_runtime_config, load_json_schema, render_json_answer,
+};
+use zavora_cli::maintenance::{run_maintenance_command, run_startup_maintenance};
 use zavora_cli::mcp::*;
-use zavora_cli::onboarding::{persist_onboarding_config, run_onboarding_wizard};
+use zavora_cli::memory_facts::{run_memory_add, run_memory_list, run_memory_rm};
+use zavora_cli::migrations::{run_migrate_down, run_migrate_status, run_migrate_up};
+use zavora_cli::model_catalog::run_models_list;
+use zavora_cli::onboarding::{
+    OnboardingTrigger, TerminalPrompter, persist_onboarding_config, record_onboarding_complete,
+    run_onboarding_wizard, should_offer_onboarding,
+};
+use zavora_cli::paths::ZavoraPaths;
 use zavora_cli::profiles::*;
+use zavora_cli::progress::ProgressEmitter;
+use zavora_cli::prompt_assembly::{
+    PromptEnvironment, render_system_instruction, run_debug_render_prompt,
+};
+use zavora_cli::prompt_input::{load_prompt_template, resolve_ask_prompt};
+use zavora_cli::provenance::save_answer_output;
 use zavora_cli::provider::*;
 use zavora_cli::ralph::run_ralph;
+use zavora_cli::read_only::{ensure_command_allowed, read_only_requested};
 use zavora_cli::retrieval::*;
+use zavora_cli::retrieval_calibration::{legacy_min_score_warning, run_retrieval_calibrate};
 use zavora_cli::runner::*;
+use zavora_cli::sanitize::{sanitize_json_strings, sanitize_output};
 use zavora_cli::server::*;
 use zavora_cli::session::*;
+use zavora_cli::session_bundle::{run_sessions_export_all, run_sessions_verify_bundle};
+use zavora_cli::session_crypto::run_sessions_encrypt;
+use zavora_cli::session_lock::claim_session_lock;
 use zavora_cli::streaming::*;
 use zavora_cli::telemetry::*;
+use zavora_cli::telemetry_export::TelemetryExporter;
+use zavora_cli::tool_policy::run_policy_show;
+use zavora_cli::trust::{
+    is_workspace_path, report_workspace_trust, retrust_after_write, run_trust, workspace_trust,
+};
+use zavora_cli::ui::Ui;
+use zavora_cli::update_check::{run_update_check_now, start_update_check};
+use zavora_cli::usage::run_usage_status;
+use zavora_cli::verbosity::{Verbosity, verbosity_log_filter};
+use zavora_cli::process_group::{PROCESS_GROUP_KILL_GRACE, sweep_process_groups};
+use zavora_cli::watchdog::{
+    CommandTimeout, command_runtime_limit, report_command_timeout, run_with_watchdog,
+};
 use zavora_cli::workflow::*;
+use zavora_cli::workspace_template::{InitOptions, run_init_template};
 
 fn init_tracing(log_filter: &str, use_stderr: bool) -> Result<()> {
     use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
@@ -49,9 +104,22 @@ fn init_tracing(log_filter: &str, use_stderr: bool) -> Result<()> {
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let cli = Cli::parse();
+    // Non-UTF-8 argv cannot name an alias; hand it to clap untouched.
+    let utf8_args = std::env::args_os()
+        .map(|arg| arg.into_string().ok())
+        .collect::<Option<Vec<String>>>();
+    let (cli, aliases) = match utf8_args.map(expand_cli_aliases) {
+        Some(Ok(expansion)) => (Cli::parse_from(&expansion.args), expansion.aliases),
+        Some(Err(err)) => {
+            eprintln!("{}", format_cli_error(&err, false));
+            std::process::exit(categorize_error(&err).exit_code());
+        }
+        None => (Cli::parse(), Vec::new()),
+    };
     let show_sensitive_config = cli.show_sensitive_config;
-    if let Err(err) = run_cli(cli).await {
+    let progress = ProgressEmitter::new(cli.progress);
+    if let Err(err) = run_cli(cli, &aliases, &progress).await {
+        progress.failed(&render_error_message(&err, show_sensitive_config));
         eprintln!("{}", format_cli_error(&err, show_sensitive_config));
         tracing::error!(
             category = %categorize_error(&err).code(),
@@ -59,77 +127,178 @@ async fn main() -> Result<()> {
             "command failed"
         );
         adk_telemetry::shutdown_telemetry();
-        std::process::exit(1);
+        std::process::exit(categorize_error(&err).exit_code());
     }
 
+    progress.completed();
     adk_telemetry::shutdown_telemetry();
     Ok(())
 }
 
-async fn run_cli(cli: Cli) -> Result<()> {
+async fn run_cli(mut cli: Cli, aliases: &[String], progress: &ProgressEmitter) -> Result<()> {
+    let verbosity = Verbosity::from_flags(cli.quiet, cli.verbose);
+    let ui = Ui::new(verbosity);
+    // Quiet runs keep stdout for the answer alone.
     init_tracing(
-        &cli.log_filter,
-        matches!(cli.command, Some(Commands::Mcp { command: McpCommands::Serve })),
+        &verbosity_log_filter(verbosity, &cli.log_filter),
+        cli.quiet || matches!(cli.command, Some(Commands::Mcp { command: McpCommands::Serve })),
     )?;
-    let mut profiles = load_profiles(&cli.config_path)?;
+    let zavora_paths = ZavoraPaths::from_env(cli.config_dir.as_deref());
+    let trust = workspace_trust(&zavora_paths)?;
+    let local_config_allowed = trust.allows_local_config();
+    if !matches!(cli.command, Some(Commands::Trust { .. })) {
+        report_workspace_trust(&ui, &trust, &zavora_paths.workspace_dir);
+    }
+    let skip_profiles = !local_config_allowed
+        && is_workspace_path(std::path::Path::new(&cli.config_path), &zavora_paths.workspace_dir);
+    let load_gated_profiles = |config_path: &str| {
+        if skip_profiles {
+            Ok(ProfilesFile::default())
+        } else {
+            load_profiles(config_path)
+        }
+    };
+    let mut profiles = load_gated_profiles(&cli.config_path)?;
+    let selected_profile = if local_config_allowed {
+        load_profile_selection(&zavora_paths.profile_selection_file())?
+    } else {
+        None
+    };
+    cli.profile = Some(resolve_profile_name(
+        cli.profile.as_deref(),
+        &profiles,
+        selected_profile.as_deref(),
+    ));
+    let read_only = read_only_requested(&cli, &profiles, selected_profile.as_deref());
 
     // Initialize SQLite memory (eager, before any tool use)
-    if let Err(e) = zavora_cli::agents::memory::init().await {
+    if let Err(e) = zavora_cli::agents::memory::init(&zavora_paths).await {
         tracing::warn!("Memory init failed: {e}");
     }
 
     // Auto-setup: scaffold .skills/ with sample on first run
-    zavora_cli::onboarding::ensure_skills_dir();
+    if !read_only {
+        zavora_cli::onboarding::ensure_skills_dir();
+    }
 
     // Auto-setup: trigger onboarding wizard for commands that need a provider
     let needs_provider = matches!(
         cli.command,
-        None | Some(Commands::Ask { .. }) | Some(Commands::Chat)
+        None | Some(Commands::Ask { .. }) | Some(Commands::Chat { .. })
             | Some(Commands::Workflow { .. }) | Some(Commands::ReleasePlan { .. })
             | Some(Commands::Ralph { .. })
     );
-    if needs_provider {
-        let workspace = std::env::current_dir().unwrap_or_default();
-        if zavora_cli::theme::is_first_run(&workspace) && !profiles.profiles.contains_key("default") {
-            let result = run_onboarding_wizard(None)?;
+    let mut onboarded = false;
+    if needs_provider && !read_only {
+        let trigger = OnboardingTrigger {
+            stdin_is_terminal: std::io::stdin().is_terminal(),
+            opted_out: cli.no_onboarding,
+            explicit_selection: cli.provider != Provider::Auto
+                || cli.model.is_some()
+                || cli.profile.is_some(),
+            config_exists: std::path::Path::new(&cli.config_path).exists()
+                || profiles.profiles.contains_key("default")
+                || profiles.broken_profiles.contains_key("default"),
+            credentials_found: detect_provider().is_some(),
+            completed: zavora_paths.onboarding_marker_file().exists(),
+        };
+        if should_offer_onboarding(&trigger) {
+            let result = run_onboarding_wizard(&mut TerminalPrompter, None, &env_present)?;
             persist_onboarding_config(&result, &cli.config_path)?;
-            profiles = load_profiles(&cli.config_path)?;
+            record_onboarding_complete(&zavora_paths.onboarding_marker_file(), &result)?;
+            retrust_after_write(&zavora_paths, &trust)?;
+            profiles = load_gated_profiles(&cli.config_path)?;
+            onboarded = !result.skipped;
         }
     }
 
-    let agent_paths = default_agent_paths();
+    let mut agent_paths = default_agent_paths(&zavora_paths);
+    if !local_config_allowed {
+        agent_paths.local_catalog = None;
+    }
+    agent_paths.extra_catalogs = agent_catalog_search_paths(
+        &cli,
+        &profiles,
+        std::env::var(AGENT_PATH_ENV).ok().as_deref(),
+    );
     let resolved_agents = load_resolved_agents(&agent_paths)?;
-    let selected_agent_name = load_agent_selection(&agent_paths.selection_file)?;
+    let selected_agent_name = if local_config_allowed {
+        load_agent_selection(&agent_paths.selection_file)?
+    } else {
+        None
+    };
     let cfg = resolve_runtime_config_with_agents(
         &cli,
         &profiles,
         &resolved_agents,
         selected_agent_name.as_deref(),
     )?;
-    let command = command_label(cli.command.as_ref().unwrap_or(&Commands::Chat));
-    let telemetry = TelemetrySink::new(&cfg, command.clone());
+    if let Some(warning) = broken_profiles_warning(&profiles) {
+        ui.warn(&warning);
+    }
+    if let Some(warning) = legacy_min_score_warning(cfg.retrieval_min_score) {
+        ui.warn(&warning);
+    }
+    if onboarded {
+        // Also creates the sqlite session database when that was chosen.
+        println!();
+        if let Err(err) = run_doctor(&profiles, &cfg).await {
+            ui.warn(&format!("setup check failed: {err:#}"));
+        }
+        println!();
+    }
+    let cfg = if use_ephemeral_session(cli.command.as_ref(), &cfg) {
+        ephemeral_runtime_config(&cfg)
+    } else {
+        cfg
+    };
+    let command = command_label(cli.command.as_ref().unwrap_or(&Commands::default_chat()));
+    let telemetry = TelemetrySink::new(&cfg, command.clone())
+        .with_exporter(TelemetryExporter::from_config(&cfg));
     let started_at = Instant::now();
-    telemetry.emit(
-        "command.started",
-        json!({
-            "profile": cfg.profile,
-            "session_backend": format!("{:?}", cfg.session_backend),
-            "retrieval_backend": format!("{:?}", cfg.retrieval_backend),
-            "telemetry_enabled": cfg.telemetry_enabled,
-            "guardrail_input_mode": guardrail_mode_label(cfg.guardrail_input_mode),
-            "guardrail_output_mode": guardrail_mode_label(cfg.guardrail_output_mode)
-        }),
-    );
+    let mut started_payload = json!({
+        "profile": cfg.profile,
+        "session_backend": format!("{:?}", cfg.session_backend),
+        "retrieval_backend": format!("{:?}", cfg.retrieval_backend),
+        "telemetry_enabled": cfg.telemetry_enabled,
+        "guardrail_input_mode": guardrail_mode_label(cfg.guardrail_input_mode),
+        "guardrail_output_mode": guardrail_mode_label(cfg.guardrail_output_mode),
+        "guardrail_retrieval_mode": guardrail_mode_label(cfg.guardrail_retrieval_mode)
+    });
+    if let Some(fields) = build_telemetry_fields().as_object() {
+        for (key, value) in fields {
+            started_payload[key] = value.clone();
+        }
+    }
+    if let Some(alias) = aliases.first() {
+        started_payload["alias"] = json!(alias);
+        started_payload["alias_chain"] = json!(aliases);
+    }
+    // Skip the git probe entirely when nothing would be written.
+    if cfg.telemetry_enabled
+        && let Some(environment) = telemetry_environment_block(&cfg, || {
+            TelemetryEnvironment::capture(&std::env::current_dir().unwrap_or_default())
+        })
+    {
+        started_payload["environment"] = environment;
+    }
+    telemetry.emit("command.started", started_payload);
+    if !matches!(cli.command, Some(Commands::Doctor { .. })) {
+        start_update_check(&cfg);
+    }
+    if !cli.no_maintenance && !matches!(cli.command, Some(Commands::Maintenance { .. })) {
+        run_startup_maintenance(&cfg, &ui);
+    }
 
     let retrieval_service = if matches!(
         cli.command,
         Some(Commands::Ask { .. })
-            | Some(Commands::Chat)
+            | Some(Commands::Chat { .. })
             | Some(Commands::Workflow { .. })
             | Some(Commands::ReleasePlan { .. })
             | None
     ) {
-        let service = build_retrieval_service(&cfg)?;
+        let service = build_retrieval_service_with_telemetry(&cfg, Some(&telemetry))?;
         tracing::info!(
             backend = service.backend_name(),
             max_chunks = cfg.retrieval_max_chunks,
@@ -142,335 +311,908 @@ async fn run_cli(cli: Cli) -> Result<()> {
         None
     };
 
-    let execution: Result<()> = match cli.command.unwrap_or(Commands::Chat) {
-        Commands::Ask { prompt } => {
-            let (model, resolved_provider, model_name) = resolve_model(&cfg)?;
-            tracing::info!(provider = ?resolved_provider, model = %model_name, "Using model");
-            telemetry.emit(
-                "model.resolved",
-                json!({
-                    "provider": format!("{:?}", resolved_provider).to_ascii_lowercase(),
-                    "model": model_name,
-                    "path": "ask"
-                }),
-            );
-            let runtime_tools = resolve_runtime_tools(&cfg).await;
-            let tool_confirmation = resolve_tool_confirmation_settings(&cfg, &runtime_tools);
-            let agent = build_single_agent_with_tools(
-                model,
-                &runtime_tools.tools,
-                tool_confirmation.policy,
-                Duration::from_secs(cfg.tool_timeout_secs),
-                Some(&cfg),
-            )?;
-            let runner =
-                build_runner_with_run_config(agent, &cfg, Some(tool_confirmation.run_config))
-                    .await?;
-            let prompt = prompt.join(" ");
-            enforce_prompt_limit(&prompt, cfg.max_prompt_chars)?;
-            let prompt =
-                apply_guardrail(&cfg, &telemetry, "input", cfg.guardrail_input_mode, &prompt)?;
-            let retrieval = retrieval_service
-                .as_deref()
-                .context("retrieval service should be initialized for ask command")?;
-            let answer =
-                run_prompt_with_retrieval(&runner, &cfg, &prompt, retrieval, &telemetry).await?;
-            let answer = apply_guardrail(
-                &cfg,
-                &telemetry,
-                "output",
-                cfg.guardrail_output_mode,
-                &answer,
-            )?;
-            println!("{answer}");
-            Ok(())
-        }
-        Commands::Chat => {
-            let runtime_tools = resolve_runtime_tools(&cfg).await;
-            let tool_confirmation = resolve_tool_confirmation_settings(&cfg, &runtime_tools);
-            let retrieval = retrieval_service
-                .as_ref()
-                .context("retrieval service should be initialized for chat command")?
-                .clone();
-            run_chat(
-                cfg.clone(),
-                retrieval,
-                runtime_tools,
-                tool_confirmation,
-                &telemetry,
-            )
-            .await?;
-            Ok(())
-        }
-        Commands::Workflow {
-            mode,
-            prompt,
-            max_iterations,
-        } => {
-            let (model, resolved_provider, model_name) = resolve_model(&cfg)?;
-            tracing::info!(provider = ?resolved_provider, model = %model_name, workflow = ?mode, "Using workflow");
-            telemetry.emit(
-                "model.resolved",
-                json!({
-                    "provider": format!("{:?}", resolved_provider).to_ascii_lowercase(),
-                    "model": model_name,
-                    "path": "workflow",
-                    "workflow_mode": workflow_mode_label(mode)
-                }),
-            );
-            let runtime_tools = resolve_runtime_tools(&cfg).await;
-            let tool_confirmation = resolve_tool_confirmation_settings(&cfg, &runtime_tools);
-            let agent = build_workflow_agent(
-                mode,
-                model,
-                max_iterations,
-                &runtime_tools.tools,
-                tool_confirmation.policy,
-                Duration::from_secs(cfg.tool_timeout_secs),
-                Some(&cfg),
-            )?;
-            let runner =
-                build_runner_with_run_config(agent, &cfg, Some(tool_confirmation.run_config))
-                    .await?;
-            let prompt = prompt.join(" ");
-            enforce_prompt_limit(&prompt, cfg.max_prompt_chars)?;
-            let prompt =
-                apply_guardrail(&cfg, &telemetry, "input", cfg.guardrail_input_mode, &prompt)?;
-            let retrieval = retrieval_service
-                .as_deref()
-                .context("retrieval service should be initialized for workflow command")?;
-            let answer =
-                run_prompt_with_retrieval(&runner, &cfg, &prompt, retrieval, &telemetry).await?;
-            let answer = apply_guardrail(
-                &cfg,
-                &telemetry,
-                "output",
-                cfg.guardrail_output_mode,
-                &answer,
-            )?;
-            println!("{answer}");
-            Ok(())
-        }
-        Commands::ReleasePlan { goal, releases } => {
-            let (model, resolved_provider, model_name) = resolve_model(&cfg)?;
-            tracing::info!(provider = ?resolved_provider, model = %model_name, releases, "Generating release plan");
-            telemetry.emit(
-                "model.resolved",
-                json!({
-                    "provider": format!("{:?}", resolved_provider).to_ascii_lowercase(),
-                    "model": model_name,
-                    "path": "release-plan"
-                }),
-            );
-            let agent = build_release_planning_agent(model, releases)?;
-            let runner = build_runner(agent, &cfg).await?;
-            let prompt = goal.join(" ");
-            let prompt =
-                apply_guardrail(&cfg, &telemetry, "input", cfg.guardrail_input_mode, &prompt)?;
-            let retrieval = retrieval_service
-                .as_deref()
-                .context("retrieval service should be initialized for release-plan command")?;
-            let answer =
-                run_prompt_with_retrieval(&runner, &cfg, &prompt, retrieval, &telemetry).await?;
-            let answer = apply_guardrail(
-                &cfg,
-                &telemetry,
-                "output",
-                cfg.guardrail_output_mode,
-                &answer,
-            )?;
-            println!("{answer}");
-            Ok(())
-        }
-        Commands::Doctor => {
-            run_doctor(&cfg).await?;
-            Ok(())
-        }
-        Commands::Migrate => {
-            run_migrate(&cfg).await?;
-            Ok(())
-        }
-        Commands::Profiles { command } => match command {
-            ProfileCommands::List => {
-                run_profiles_list(&profiles, &cfg)?;
-                Ok(())
-            }
-            ProfileCommands::Show => {
-                run_profiles_show(&cfg)?;
-                Ok(())
-            }
-        },
-        Commands::Agents { command } => match command {
-            AgentCommands::List => {
-                run_agents_list(&resolved_agents, &cfg.agent_name, &agent_paths)?;
-                Ok(())
-            }
-            AgentCommands::Show { name } => {
-                run_agents_show(&resolved_agents, &cfg.agent_name, name)?;
-                Ok(())
-            }
-            AgentCommands::Select { name } => {
-                run_agents_select(&resolved_agents, &agent_paths, name)?;
-                Ok(())
-            }
-        },
-        Commands::Mcp { command } => match command {
-            McpCommands::List => {
-                run_mcp_list(&cfg).await?;
-                Ok(())
-            }
-            McpCommands::Discover { server } => {
-                run_mcp_discover(&cfg, server).await?;
-                Ok(())
+    let long_running = matches!(
+        cli.command,
+        None | Some(Commands::Chat { .. })
+            | Some(Commands::Server { command: ServerCommands::Serve { .. } })
+            | Some(Commands::Mcp { command: McpCommands::Serve })
+    );
+    let runtime_limit = command_runtime_limit(&cfg, long_running);
+    // execute_bash children lead their own process groups and miss the
+    // terminal's SIGINT, so commands without a Ctrl-C handler of their own
+    // sweep them and then exit the way SIGINT would have.
+    let handles_ctrl_c = long_running
+        || matches!(
+            cli.command,
+            Some(Commands::Sessions {
+                command: SessionCommands::Show { follow: true, .. }
+            })
+        );
+    if !handles_ctrl_c {
+        tokio::spawn(async {
+            if tokio::signal::ctrl_c().await.is_ok() {
+                sweep_process_groups(PROCESS_GROUP_KILL_GRACE);
+                std::process::exit(130);
             }
-            McpCommands::Serve => {
-                zavora_cli::mcp_server::run_mcp_server().await?;
+        });
+    }
+    let read_only_check = ensure_command_allowed(&cfg, cli.command.as_ref());
+    // The async block captures whole variables, so move what it needs out
+    // of `cli` first.
+    let config_path = cli.config_path;
+    let steal_session_lock = cli.steal_session_lock;
+    let command_to_run = cli.command.unwrap_or_else(Commands::default_chat);
+    let execution = run_with_watchdog(runtime_limit, progress, async {
+        match command_to_run {
+            _ if read_only_check.is_err() => read_only_check,
+            Commands::Ask {
+                batch_file: Some(batch_file),
+                batch_jobs,
+                batch_output_dir,
+                batch_fail_fast,
+                ..
+            } => {
+                let prompts = load_batch_file(&batch_file)?;
+                let (model, resolved_provider, model_name) = resolve_model(&cfg)?;
+                tracing::info!(provider = ?resolved_provider, model = %model_name, "Using model");
+                telemetry.emit(
+                    "model.resolved",
+                    json!({
+                        "provider": format!("{:?}", resolved_provider).to_ascii_lowercase(),
+                        "model": model_name,
+                        "path": "ask.batch"
+                    }),
+                );
+                let runtime_tools =
+                    resolve_runtime_tools_with_retrieval(&cfg, retrieval_service.clone()).await;
+                let tool_confirmation = resolve_tool_confirmation_settings(&cfg, &runtime_tools);
+                let retrieval = retrieval_service
+                    .as_deref()
+                    .context("retrieval service should be initialized for ask command")?;
+                let runtime = BatchRuntime {
+                    model,
+                    session_service: Arc::new(InMemorySessionService::new()),
+                    runtime_tools: &runtime_tools,
+                    tool_confirmation: &tool_confirmation,
+                    retrieval,
+                    telemetry: &telemetry,
+                };
+                let options = BatchOptions {
+                    jobs: batch_jobs,
+                    output_dir: PathBuf::from(batch_output_dir.unwrap_or_default()),
+                    fail_fast: batch_fail_fast,
+                };
+                let summary = run_batch(&cfg, &runtime, prompts, &options).await?;
+                println!("{}", format_batch_summary(&summary));
                 Ok(())
             }
-        },
-        Commands::Sessions { command } => match command {
-            SessionCommands::List => {
-                run_sessions_list(&cfg).await?;
-                Ok(())
+            Commands::Ask {
+                prompt,
+                stdin,
+                stdin_max_bytes,
+                template,
+                compare_with,
+                compare_concurrent,
+                cache,
+                json_schema,
+                json_repair_attempts,
+                json_pretty,
+                output_file,
+                ephemeral: _,
+                no_ephemeral: _,
+                batch_file: _,
+                batch_jobs: _,
+                batch_output_dir: _,
+                batch_fail_fast: _,
+            } => {
+                let template = template.as_deref().map(load_prompt_template).transpose()?;
+                let prompt = resolve_ask_prompt(
+                    &prompt,
+                    stdin,
+                    template.as_deref(),
+                    std::io::stdin().lock(),
+                    stdin_max_bytes,
+                )?;
+                let (model, resolved_provider, model_name) = resolve_model(&cfg)?;
+                tracing::info!(provider = ?resolved_provider, model = %model_name, "Using model");
+                telemetry.emit(
+                    "model.resolved",
+                    json!({
+                        "provider": format!("{:?}", resolved_provider).to_ascii_lowercase(),
+                        "model": model_name,
+                        "path": "ask"
+                    }),
+                );
+                progress.emit(
+                    "model_resolved",
+                    json!({
+                        "provider": format!("{:?}", resolved_provider).to_ascii_lowercase(),
+                        "model": model_name
+                    }),
+                );
+                let runtime_tools =
+                    resolve_runtime_tools_with_retrieval(&cfg, retrieval_service.clone()).await;
+                progress.emit(
+                    "mcp_discovery",
+                    json!({
+                        "tools": runtime_tools.tools.len(),
+                        "mcp_tools": runtime_tools.mcp_tool_names.len()
+                    }),
+                );
+                let tool_confirmation = resolve_tool_confirmation_settings(&cfg, &runtime_tools);
+                enforce_prompt_limit(&prompt, cfg.max_prompt_chars)?;
+                let prompt =
+                    apply_guardrail(&cfg, &telemetry, "input", cfg.guardrail_input_mode, &prompt)?;
+                let retrieval = retrieval_service
+                    .as_deref()
+                    .context("retrieval service should be initialized for ask command")?;
+
+                if let Some(compare_with) = compare_with {
+                    let (compare_provider, compare_model) = parse_compare_target(&compare_with)?;
+                    let compare_cfg = compare_runtime_config(&cfg, compare_provider, compare_model);
+                    let (other_model, other_provider, other_model_name) = resolve_model(&compare_cfg)?;
+                    let primary = ComparedModel {
+                        model,
+                        provider: resolved_provider,
+                        model_name,
+                    };
+                    let secondary = ComparedModel {
+                        model: other_model,
+                        provider: other_provider,
+                        model_name: other_model_name,
+                    };
+                    let (left, right) = run_model_comparison(
+                        &cfg,
+                        [&primary, &secondary],
+                        [
+                            Arc::new(InMemorySessionService::new()),
+                            Arc::new(InMemorySessionService::new()),
+                        ],
+                        &runtime_tools,
+                        &tool_confirmation,
+                        &prompt,
+                        retrieval,
+                        &telemetry,
+                        compare_concurrent,
+                    )
+                    .await?;
+                    print!("{}", render_comparison_report(&left, &right));
+                    Ok(())
+                } else if let Some(schema_path) = json_schema {
+                    let schema = load_json_schema(&schema_path)?;
+                    let json_cfg = json_schema_runtime_config(&cfg, &schema);
+                    let agent = build_single_agent_with_tools(
+                        model,
+                        &runtime_tools.tools,
+                        tool_confirmation.policy,
+                        Duration::from_secs(json_cfg.tool_timeout_secs),
+                        Some(&json_cfg),
+                    )?;
+                    let runner = build_runner_with_run_config(
+                        agent,
+                        &json_cfg,
+                        Some(tool_confirmation.run_config),
+                    )
+                    .await?;
+                    let (runner, json_cfg, telemetry, prompt) =
+                        (&runner, &json_cfg, &telemetry, prompt.as_str());
+                    let answer = ask_json_with_repair(
+                        &schema,
+                        json_repair_attempts,
+                        telemetry,
+                        |repair_prompt| async move {
+                            let answer = match repair_prompt {
+                                None => {
+                                    run_prompt_with_retrieval_and_progress(
+                                        runner, json_cfg, prompt, retrieval, telemetry, progress,
+                                    )
+                                    .await?
+                                }
+                                Some(repair_prompt) => {
+                                    run_prompt_with_progress(
+                                        runner,
+                                        json_cfg,
+                                        &repair_prompt,
+                                        telemetry,
+                                        progress,
+                                    )
+                                    .await?
+                                }
+                            };
+                            apply_guardrail(
+                                json_cfg,
+                                telemetry,
+                                "output",
+                                json_cfg.guardrail_output_mode,
+                                &answer,
+                            )
+                        },
+                    )
+                    .await?;
+                    let mut value = answer.value;
+                    sanitize_json_strings(&mut value, cfg.output_sanitize);
+                    let rendered = render_json_answer(&value, json_pretty);
+                    println!("{rendered}");
+                    if let Some(path) = &output_file {
+                        save_answer_output(&cfg, &telemetry, path, &rendered)?;
+                    }
+                    Ok(())
+                } else if cache {
+                    let (enriched, _) =
+                        prepare_prompt_with_retrieval(&cfg, retrieval, &prompt, &telemetry)?;
+                    let provider_label = format!("{:?}", resolved_provider).to_ascii_lowercase();
+                    let system_instruction =
+                        render_system_instruction(Some(&cfg), &PromptEnvironment::current());
+                    let tool_names = runtime_tools
+                        .tools
+                        .iter()
+                        .map(|tool| tool.name().to_string())
+                        .collect::<Vec<String>>();
+                    let key = answer_cache_key(&AnswerCacheKeyInput {
+                        provider: &provider_label,
+                        model: &model_name,
+                        system_instruction: &system_instruction,
+                        user_content: &enriched,
+                        params: ask_cache_params(tool_names, &cfg),
+                    });
+                    let answer_cache =
+                        AnswerCache::new(cfg.paths.answer_cache_dir(), cfg.ask_cache_ttl_secs);
+                    let (answer, cached) = answer_with_cache(
+                        &answer_cache,
+                        &key,
+                        &provider_label,
+                        &model_name,
+                        &telemetry,
+                        unix_now_secs(),
+                        async {
+                            let agent = build_single_agent_with_tools(
+                                model,
+                                &runtime_tools.tools,
+                                tool_confirmation.policy,
+                                Duration::from_secs(cfg.tool_timeout_secs),
+                                Some(&cfg),
+                            )?;
+                            let runner = build_runner_with_run_config(
+                                agent,
+                                &cfg,
+                                Some(tool_confirmation.run_config),
+                            )
+                            .await?;
+                            let answer =
+                                run_prompt_with_progress(&runner, &cfg, &enriched, &telemetry, progress)
+                                    .await?;
+                            apply_guardrail(
+                                &cfg,
+                                &telemetry,
+                                "output",
+                                cfg.guardrail_output_mode,
+                                &answer,
+                            )
+                        },
+                    )
+                    .await?;
+                    if cached {
+                        ui.notice("(cached)");
+                    }
+                    let answer = sanitize_output(&cfg, &answer);
+                    ui.answer(&cfg, &format!("{answer}\n"))?;
+                    if let Some(path) = &output_file {
+                        save_answer_output(&cfg, &telemetry, path, &answer)?;
+                    }
+                    Ok(())
+                } else {
+                    let agent = build_single_agent_with_tools(
+                        model,
+                        &runtime_tools.tools,
+                        tool_confirmation.policy,
+                        Duration::from_secs(cfg.tool_timeout_secs),
+                        Some(&cfg),
+                    )?;
+                    let runner =
+                        build_runner_with_run_config(agent, &cfg, Some(tool_confirmation.run_config))
+                            .await?;
+                    let answer = run_prompt_with_retrieval_and_progress(
+                        &runner, &cfg, &prompt, retrieval, &telemetry, progress,
+                    )
+                    .await?;
+                    let answer = apply_guardrail(
+                        &cfg,
+                        &telemetry,
+                        "output",
+                        cfg.guardrail_output_mode,
+                        &answer,
+                    )?;
+                    let answer = sanitize_output(&cfg, &answer);
+                    ui.answer(&cfg, &format!("{answer}\n"))?;
+                    if let Some(path) = &output_file {
+                        save_answer_output(&cfg, &telemetry, path, &answer)?;
+                    }
+                    Ok(())
+                }
             }
-            SessionCommands::Show { session_id, recent } => {
-                run_sessions_show(&cfg, session_id, recent).await?;
+            Commands::Chat {
+                script,
+                script_fail_fast,
+                ephemeral: _,
+            } => {
+                let runtime_tools =
+                    resolve_runtime_tools_with_retrieval(&cfg, retrieval_service.clone()).await;
+                let tool_confirmation = resolve_tool_confirmation_settings(&cfg, &runtime_tools);
+                let retrieval = retrieval_service
+                    .as_ref()
+                    .context("retrieval service should be initialized for chat command")?
+                    .clone();
+                let script = script.map(|path| ChatScriptOptions {
+                    path,
+                    fail_fast: script_fail_fast,
+                });
+                let _session_lock = claim_session_lock(&cfg, "chat", steal_session_lock, &ui)?;
+                run_chat(
+                    cfg.clone(),
+                    retrieval,
+                    runtime_tools,
+                    tool_confirmation,
+                    &telemetry,
+                    script,
+                    &resolved_agents,
+                )
+                .await?;
                 Ok(())
             }
-            SessionCommands::Delete { session_id, force } => {
-                run_sessions_delete(&cfg, session_id, force).await?;
+            Commands::Workflow {
+                mode,
+                prompt,
+                max_iterations,
+            } => {
+                let (model, resolved_provider, model_name) = resolve_model(&cfg)?;
+                tracing::info!(provider = ?resolved_provider, model = %model_name, workflow = ?mode, "Using workflow");
+                telemetry.emit(
+                    "model.resolved",
+                    json!({
+                        "provider": format!("{:?}", resolved_provider).to_ascii_lowercase(),
+                        "model": model_name,
+                        "path": "workflow",
+                        "workflow_mode": workflow_mode_label(mode)
+                    }),
+                );
+                progress.emit(
+                    "model_resolved",
+                    json!({
+                        "provider": format!("{:?}", resolved_provider).to_ascii_lowercase(),
+                        "model": model_name
+                    }),
+                );
+                let runtime_tools =
+                    resolve_runtime_tools_with_retrieval(&cfg, retrieval_service.clone()).await;
+                progress.emit(
+                    "mcp_discovery",
+                    json!({
+                        "tools": runtime_tools.tools.len(),
+                        "mcp_tools": runtime_tools.mcp_tool_names.len()
+                    }),
+                );
+                let tool_confirmation = resolve_tool_confirmation_settings(&cfg, &runtime_tools);
+                let agent = build_workflow_agent(
+                    mode,
+                    model,
+                    max_iterations,
+                    &runtime_tools.tools,
+                    tool_confirmation.policy,
+                    Duration::from_secs(cfg.tool_timeout_secs),
+                    Some(&cfg),
+                )?;
+                let runner =
+                    build_runner_with_run_config(agent, &cfg, Some(tool_confirmation.run_config))
+                        .await?;
+                let prompt = prompt.join(" ");
+                enforce_prompt_limit(&prompt, cfg.max_prompt_chars)?;
+                let prompt =
+                    apply_guardrail(&cfg, &telemetry, "input", cfg.guardrail_input_mode, &prompt)?;
+                let retrieval = retrieval_service
+                    .as_deref()
+                    .context("retrieval service should be initialized for workflow command")?;
+                let answer = run_prompt_with_retrieval_and_progress(
+                    &runner, &cfg, &prompt, retrieval, &telemetry, progress,
+                )
+                .await?;
+                let answer = apply_guardrail(
+                    &cfg,
+                    &telemetry,
+                    "output",
+                    cfg.guardrail_output_mode,
+                    &answer,
+                )?;
+                println!("{}", sanitize_output(&cfg, &answer));
                 Ok(())
             }
-            SessionCommands::Prune {
-                keep,
-                dry_run,
-                force,
+            Commands::ReleasePlan {
+                goal,
+                releases,
+                refine,
+                max_scope_items,
             } => {
-                run_sessions_prune(&cfg, keep, dry_run, force).await?;
+                let (model, resolved_provider, model_name) = resolve_model(&cfg)?;
+                tracing::info!(provider = ?resolved_provider, model = %model_name, releases, "Generating release plan");
+                telemetry.emit(
+                    "model.resolved",
+                    json!({
+                        "provider": format!("{:?}", resolved_provider).to_ascii_lowercase(),
+                        "model": model_name,
+                        "path": "release-plan"
+                    }),
+                );
+                progress.emit(
+                    "model_resolved",
+                    json!({
+                        "provider": format!("{:?}", resolved_provider).to_ascii_lowercase(),
+                        "model": model_name
+                    }),
+                );
+                let review = (refine > 0).then_some(ReleasePlanReview {
+                    iterations: refine,
+                    max_scope_items,
+                });
+                let agent = build_release_planning_agent(model, releases, review)?;
+                let runner = build_runner(agent, &cfg).await?;
+                let prompt = goal.join(" ");
+                let prompt =
+                    apply_guardrail(&cfg, &telemetry, "input", cfg.guardrail_input_mode, &prompt)?;
+                let retrieval = retrieval_service
+                    .as_deref()
+                    .context("retrieval service should be initialized for release-plan command")?;
+                let outcome =
+                    run_release_plan(&runner, &cfg, &prompt, retrieval, &telemetry, progress).await?;
+                let answer = apply_guardrail(
+                    &cfg,
+                    &telemetry,
+                    "output",
+                    cfg.guardrail_output_mode,
+                    &outcome.answer,
+                )?;
+                println!("{}", sanitize_output(&cfg, &answer));
+                if review.is_some() {
+                    println!("\n{}", format_release_plan_refinement_summary(&outcome.verdicts));
+                }
                 Ok(())
             }
-        },
-        Commands::Telemetry { command } => match command {
-            TelemetryCommands::Report { path, limit } => {
-                run_telemetry_report(&cfg, path, limit)?;
+            Commands::Doctor {
+                check_config,
+                check_update,
+            } => {
+                if check_config {
+                    run_doctor_check_config(&profiles, &cfg);
+                } else {
+                    if check_update && run_update_check_now(&cfg).await.is_none() {
+                        Ui::for_config(&cfg).notice("Update check skipped: offline mode is on");
+                    }
+                    run_doctor(&profiles, &cfg).await?;
+                }
                 Ok(())
             }
-        },
-        Commands::Skills { command } => match command {
-            SkillCommands::List => {
-                run_skills_list()?;
+            Commands::Policy {
+                command: PolicyCommands::Show { json },
+            } => {
+                run_policy_show(&cfg, json).await?;
                 Ok(())
             }
-        },
-        #[cfg(feature = "rag")]
-        Commands::Rag { command } => match command {
-            RagCommands::Ingest { path } => {
-                run_rag_ingest(&path).await?;
+            Commands::Migrate { command } => {
+                match command {
+                    None | Some(MigrateCommands::Up) => run_migrate_up(&cfg).await?,
+                    Some(MigrateCommands::Status) => run_migrate_status(&cfg).await?,
+                    Some(MigrateCommands::Down { to, force }) => {
+                        run_migrate_down(&cfg, to, force).await?
+                    }
+                }
                 Ok(())
             }
-        },
-        Commands::Eval { command } => match command {
-            EvalCommands::Run {
-                dataset,
-                output,
-                benchmark_iterations,
-                fail_under,
-            } => {
-                run_eval(
+            Commands::Profiles { command } => match command {
+                ProfileCommands::List => {
+                    run_profiles_list(&profiles, &cfg, selected_profile.as_deref())?;
+                    Ok(())
+                }
+                ProfileCommands::Show => {
+                    run_profiles_show(&profiles, &cfg)?;
+                    Ok(())
+                }
+                ProfileCommands::Select { name } => run_profiles_select(
+                    &profiles,
+                    &cfg.config_path,
+                    &cfg.paths.profile_selection_file(),
+                    &name,
+                ),
+            },
+            Commands::Agents { command } => match command {
+                AgentCommands::List => {
+                    run_agents_list(&resolved_agents, &cfg.agent_name, &agent_paths)?;
+                    Ok(())
+                }
+                AgentCommands::Show { name } => {
+                    run_agents_show(&resolved_agents, &cfg.agent_name, name)?;
+                    Ok(())
+                }
+                AgentCommands::Select { name } => {
+                    run_agents_select(&resolved_agents, &agent_paths, name)?;
+                    Ok(())
+                }
+            },
+            Commands::Alias { command } => match command {
+                AliasCommands::Set { name, args } => run_alias_set(&cfg.paths, &name, &args),
+                AliasCommands::List => run_alias_list(&cfg.paths),
+                AliasCommands::Rm { name } => run_alias_rm(&cfg.paths, &name),
+            },
+            Commands::Memory { command } => match command {
+                MemoryCommands::List => run_memory_list(&cfg),
+                MemoryCommands::Add { fact } => run_memory_add(&cfg, &fact.join(" ")),
+                MemoryCommands::Rm { id } => run_memory_rm(&cfg, &id),
+            },
+            Commands::Mcp { command } => match command {
+                McpCommands::List => {
+                    run_mcp_list(&cfg).await?;
+                    Ok(())
+                }
+                McpCommands::Discover {
+                    server,
+                    reset_breaker,
+                } => {
+                    run_mcp_discover(&cfg, server, reset_breaker).await?;
+                    Ok(())
+                }
+                McpCommands::Serve => {
+                    zavora_cli::mcp_server::run_mcp_server(&cfg).await?;
+                    Ok(())
+                }
+            },
+            Commands::Sessions { command } => match command {
+                SessionCommands::List { preview } => {
+                    run_sessions_list(&cfg, preview).await?;
+                    Ok(())
+                }
+                SessionCommands::Show {
+                    session_id,
+                    recent,
+                    follow,
+                    json,
+                    poll_interval_ms,
+                } => {
+                    let follow = follow.then(|| SessionFollow {
+                        interval: Duration::from_millis(poll_interval_ms.max(50)),
+                        json,
+                    });
+                    run_sessions_show(&cfg, session_id, recent, follow).await?;
+                    Ok(())
+                }
+                SessionCommands::Delete { session_id, force } => {
+                    run_sessions_delete(&cfg, session_id, force).await?;
+                    Ok(())
+                }
+                SessionCommands::Search {
+                    query,
+                    limit,
+                    max_events,
+                    budget_secs,
+                    json,
+                } => {
+                    run_sessions_search(&cfg, &query, limit, max_events, budget_secs, json).await?;
+                    Ok(())
+                }
+                SessionCommands::Prune {
+                    keep,
+                    dry_run,
+                    force,
+                } => {
+                    run_sessions_prune(&cfg, keep, dry_run, force).await?;
+                    Ok(())
+                }
+                SessionCommands::ExportAll { output, compress } => {
+                    run_sessions_export_all(&cfg, &output, compress).await?;
+                    Ok(())
+                }
+                SessionCommands::VerifyBundle { path } => {
+                    run_sessions_verify_bundle(&path)?;
+                    Ok(())
+                }
+                SessionCommands::Encrypt { force } => {
+                    run_sessions_encrypt(&cfg, force).await?;
+                    Ok(())
+                }
+            },
+            Commands::Cache { command } => match command {
+                CacheCommands::Clear { older_than } => {
+                    run_cache_clear(&cfg.paths.answer_cache_dir(), older_than.as_deref())?;
+                    Ok(())
+                }
+            },
+            Commands::Models { command } => match command {
+                ModelsCommands::List {
+                    provider,
+                    json,
+                    refresh,
+                } => {
+                    run_models_list(&cfg, provider, json, refresh).await?;
+                    Ok(())
+                }
+            },
+            Commands::Usage { command } => match command {
+                UsageCommands::Status => {
+                    run_usage_status(&cfg)?;
+                    Ok(())
+                }
+            },
+            Commands::Maintenance { command } => match command {
+                MaintenanceCommands::Run { now } => {
+                    run_maintenance_command(&cfg, now)?;
+                    Ok(())
+                }
+            },
+            Commands::Debug { command } => match command {
+                DebugCommands::RenderPrompt {
+                    prompt,
+                    stdin,
+                    stdin_max_bytes,
+                    template,
+                    json,
+                } => {
+                    let prompt_source = if template.is_some() {
+                        "template"
+                    } else {
+                        "prompt"
+                    };
+                    let template = template.as_deref().map(load_prompt_template).transpose()?;
+                    let prompt = resolve_ask_prompt(
+                        &prompt,
+                        stdin,
+                        template.as_deref(),
+                        std::io::stdin().lock(),
+                        stdin_max_bytes,
+                    )?;
+                    let retrieval = build_retrieval_service(&cfg)?;
+                    run_debug_render_prompt(
+                        &cfg,
+                        &prompt,
+                        prompt_source,
+                        retrieval.as_ref(),
+                        &telemetry,
+                        json,
+                    )?;
+                    Ok(())
+                }
+                DebugCommands::Bundle {
+                    last_run: _,
+                    run_id,
+                    prompt,
+                    replay,
+                    output,
+                    review,
+                } => {
+                    let retrieval = build_retrieval_service(&cfg)?;
+                    run_debug_bundle(
+                        &cfg,
+                        &telemetry,
+                        retrieval.as_ref(),
+                        DebugBundleOptions {
+                            run: run_id.map(RunSelector::Id).unwrap_or(RunSelector::Last),
+                            prompt,
+                            replay,
+                            output,
+                            review,
+                        },
+                    )?;
+                    Ok(())
+                }
+            },
+            Commands::Config { command } => match command {
+                ConfigCommands::Schema { target, output } => {
+                    run_config_schema(target, output.as_deref())?;
+                    Ok(())
+                }
+                ConfigCommands::Set { path, value } => {
+                    let config_path = std::path::Path::new(&cfg.config_path);
+                    let edit = run_config_set(
+                        config_path,
+                        &cfg.paths.config_backup_dir(),
+                        &path,
+                        &value,
+                    )?;
+                    retrust_after_write(&zavora_paths, &trust)?;
+                    println!("{}", edit.summary(config_path));
+                    Ok(())
+                }
+                ConfigCommands::Unset { path } => {
+                    let config_path = std::path::Path::new(&cfg.config_path);
+                    let edit =
+                        run_config_unset(config_path, &cfg.paths.config_backup_dir(), &path)?;
+                    retrust_after_write(&zavora_paths, &trust)?;
+                    println!("{}", edit.summary(config_path));
+                    Ok(())
+                }
+            },
+            Commands::Retrieval { command } => match command {
+                RetrievalCommands::Calibrate {
+                    doc_path,
+                    queries_file,
+                    top_n,
+                } => {
+                    run_retrieval_calibrate(&cfg, doc_path, &queries_file, top_n)?;
+                    Ok(())
+                }
+            },
+            Commands::Telemetry { command } => match command {
+                TelemetryCommands::Report { path, limit, json } => {
+                    run_telemetry_report(&cfg, path, limit, json)?;
+                    Ok(())
+                }
+                TelemetryCommands::Export {
+                    path,
+                    output,
+                    format,
+                    columns,
+                    group_by,
+                    agg,
+                } => {
+                    run_telemetry_export(
+                        &cfg,
+                        path,
+                        &output,
+                        format,
+                        columns.as_deref(),
+                        group_by.as_deref(),
+                        &agg,
+                    )?;
+                    Ok(())
+                }
+            },
+            Commands::Skills { command } => match command {
+                SkillCommands::List => {
+                    run_skills_list()?;
+                    Ok(())
+                }
+            },
+            #[cfg(feature = "rag")]
+            Commands::Rag { command } => match command {
+                RagCommands::Ingest { path } => {
+                    run_rag_ingest(&path).await?;
+                    Ok(())
+                }
+            },
+            Commands::Eval { command } => match command {
+                EvalCommands::Run {
                     dataset,
                     output,
                     benchmark_iterations,
                     fail_under,
-                    &telemetry,
-                )?;
-                Ok(())
-            }
-        },
-        Commands::Server { command } => match command {
-            ServerCommands::Serve { host, port } => {
-                run_server(cfg.clone(), host, port, &telemetry).await?;
-                Ok(())
-            }
-            ServerCommands::A2aSmoke => {
-                run_a2a_smoke(&telemetry)?;
+                    jobs,
+                } => {
+                    run_eval(
+                        dataset,
+                        output.unwrap_or_else(|| cfg.paths.eval_output_path().display().to_string()),
+                        benchmark_iterations,
+                        fail_under,
+                        jobs,
+                        &telemetry,
+                    )?;
+                    Ok(())
+                }
+            },
+            Commands::Server { command } => match command {
+                ServerCommands::Serve { host, port } => {
+                    let _session_lock =
+                        claim_session_lock(&cfg, "server.serve", steal_session_lock, &ui)?;
+                    run_server(cfg.clone(), host, port, &telemetry).await?;
+                    Ok(())
+                }
+                ServerCommands::A2aSmoke => {
+                    run_a2a_smoke(&cfg, &telemetry).await?;
+                    Ok(())
+                }
+            },
+            Commands::Ralph {
+                prompt,
+                phase,
+                resume,
+                output_dir,
+            } => {
+                let prompt = prompt.join(" ");
+                if !resume {
+                    enforce_prompt_limit(&prompt, cfg.max_prompt_chars)?;
+                }
+                telemetry.emit(
+                    "model.resolved",
+                    json!({
+                        "provider": format!("{:?}", cfg.provider).to_ascii_lowercase(),
+                        "model": cfg.model.clone().unwrap_or_default(),
+                        "path": "ralph"
+                    }),
+                );
+                run_ralph(&cfg, prompt, phase, resume, output_dir, &telemetry).await?;
                 Ok(())
             }
-        },
-        Commands::Ralph {
-            prompt,
-            phase,
-            resume,
-            output_dir,
-        } => {
-            let prompt = prompt.join(" ");
-            if !resume {
-                enforce_prompt_limit(&prompt, cfg.max_prompt_chars)?;
-            }
-            telemetry.emit(
-                "model.resolved",
-                json!({
-                    "provider": format!("{:?}", cfg.provider).to_ascii_lowercase(),
-                    "model": cfg.model.clone().unwrap_or_default(),
-                    "path": "ralph"
-                }),
-            );
-            run_ralph(&cfg, prompt, phase, resume, output_dir, &telemetry).await?;
-            Ok(())
-        }
-        Commands::Setup => {
-            let existing_profile = profiles.profiles.get("default");
-            let result = run_onboarding_wizard(existing_profile)?;
-            persist_onboarding_config(&result, &cli.config_path)?;
-            if result.skipped {
-                println!("Minimal configuration saved. Set your provider via environment variables or edit the config file.");
-            } else {
-                println!("Configuration saved! You can start chatting with `zavora`.");
-            }
-            Ok(())
-        }
-        Commands::LspInit => {
-            #[cfg(feature = "lsp")]
-            {
-                let config = zavora_cli::lsp::manager::generate_default_config();
-                if config.servers.is_empty() {
-                    println!("No language servers found in PATH.");
-                    println!("Install one: rust-analyzer, typescript-language-server, pylsp, gopls, clangd");
+            Commands::Setup => {
+                let existing_profile = profiles.profiles.get("default");
+                let result =
+                    run_onboarding_wizard(&mut TerminalPrompter, existing_profile, &env_present)?;
+                persist_onboarding_config(&result, &config_path)?;
+                record_onboarding_complete(&zavora_paths.onboarding_marker_file(), &result)?;
+                retrust_after_write(&zavora_paths, &trust)?;
+                if result.skipped {
+                    println!("Minimal configuration saved. Set your provider via environment variables or edit the config file.");
                 } else {
-                    let path = ".zavora/lsp.json";
-                    std::fs::create_dir_all(".zavora")?;
-                    let json = serde_json::to_string_pretty(&config)?;
-                    std::fs::write(path, &json)?;
-                    println!("LSP config written to {path}:");
-                    for (lang, srv) in &config.servers {
-                        println!("  {lang}: {} {}", srv.command, srv.args.join(" "));
-                    }
-                    println!("\nLSP code intelligence is now enabled.");
+                    println!("Configuration saved! You can start chatting with `zavora`.");
                 }
                 Ok(())
             }
-            #[cfg(not(feature = "lsp"))]
-            {
-                println!("LSP support not compiled. Rebuild with: cargo build --features lsp");
-                Ok(())
+            Commands::Init {
+                template,
+                vars,
+                force,
+                skip_evals,
+                offline,
+            } => run_init_template(
+                &template,
+                &vars,
+                InitOptions { force, skip_evals },
+                offline || cfg.offline,
+            )
+            .and_then(|()| retrust_after_write(&zavora_paths, &trust)),
+            Commands::Trust { revoke } => run_trust(&zavora_paths, revoke),
+            Commands::LspInit => {
+                #[cfg(feature = "lsp")]
+                {
+                    let config = zavora_cli::lsp::manager::generate_default_config();
+                    if config.servers.is_empty() {
+                        println!("No language servers found in PATH.");
+                        println!("Install one: rust-analyzer, typescript-language-server, pylsp, gopls, clangd");
+                    } else {
+                        let path = zavora_paths.lsp_config_file();
+                        std::fs::create_dir_all(&zavora_paths.workspace_dir)?;
+                        let json = serde_json::to_string_pretty(&config)?;
+                        std::fs::write(&path, &json)?;
+                        println!("LSP config written to {}:", path.display());
+                        for (lang, srv) in &config.servers {
+                            println!("  {lang}: {} {}", srv.command, srv.args.join(" "));
+                        }
+                        println!("\nLSP code intelligence is now enabled.");
+                    }
+                    Ok(())
+                }
+                #[cfg(not(feature = "lsp"))]
+                {
+                    println!("LSP support not compiled. Rebuild with: cargo build --features lsp");
+                    Ok(())
+                }
             }
         }
-    };
+    })
+    .await;
 
     let duration_ms = started_at.elapsed().as_millis();
+    if let Some(timeout) = execution
+        .as_ref()
+        .err()
+        .and_then(|err| err.downcast_ref::<CommandTimeout>())
+    {
+        report_command_timeout(timeout, &telemetry, duration_ms, &mut std::io::stdout());
+    }
     match &execution {
         Ok(_) => telemetry.emit(
             "command.completed",
             json!({"duration_ms": duration_ms, "status": "ok"}),
         ),
-        Err(err) => telemetry.emit(
-            "command.failed",
-            json!({
-                "duration_ms": duration_ms,
-                "status": "error",
-                "error": render_error_message(err, cfg.show_sensitive_config)
-            }),
-        ),
+        Err(err) => {
+            let error = render_error_message(err, cfg.show_sensitive_config);
+            telemetry.emit(
+                "command.failed",
+                with_error_fingerprint(
+                    json!({
+                        "duration_ms": duration_ms,
+                        "status": "error",
+                        "error": error
+                    }),
+                    &error,
+                ),
+            )
+        }
     }
+    telemetry.flush_export().await;
 
     execution
 }
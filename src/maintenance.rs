@@ -0,0 +1,651 @@
+//! Startup maintenance: retention for the files zavora accumulates.
+//!
+//! Telemetry, chat checkpoints, `ask --cache` answers, spilled tool output
+//! and idle transcripts all grow without bound. A profile's `[retention]`
+//! table bounds each kind; a kind without a setting is never touched, so
+//! nothing is deleted until a retention is configured:
+//!
+//! - `telemetry_days`: drop older events from the telemetry file and delete
+//!   rotated files last written before the cutoff;
+//! - `telemetry_max_bytes`: rotate the telemetry file to `<file>.1` once it
+//!   is larger, keeping [`TELEMETRY_ROTATED_FILES`] rotations;
+//! - `checkpoint_days`: drop `/checkpoint`s whose last event is older
+//!   (the tangent baseline is always kept);
+//! - `answer_cache_days`, `tool_output_days`, `transcript_days`: delete
+//!   files in those directories last written before the cutoff.
+//!
+//! The pass runs at the start of a command at most once per
+//! [`MAINTENANCE_INTERVAL_SECS`] (tracked in `<state>/maintenance.json`),
+//! stops after [`MAINTENANCE_TIME_BUDGET`] and prints one line when it
+//! cleaned something. `--no-maintenance` and read-only mode skip it;
+//! `maintenance run --now` forces it and prints every kind.
+use std::io::{BufRead, BufReader, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::checkpoint::CheckpointStore;
+use crate::config::RuntimeConfig;
+use crate::file_lock::acquire_file_lock;
+use crate::ui::Ui;
+
+pub const MAINTENANCE_INTERVAL_SECS: u64 = 24 * 60 * 60;
+/// Wall-clock cap on the startup pass; unfinished work waits for the next day.
+pub const MAINTENANCE_TIME_BUDGET: Duration = Duration::from_millis(250);
+/// `<telemetry>.1` .. `<telemetry>.N` kept by `telemetry_max_bytes`.
+pub const TELEMETRY_ROTATED_FILES: usize = 3;
+/// Telemetry is left alone this run when another pass holds its lock longer.
+const MAINTENANCE_LOCK_TIMEOUT: Duration = Duration::from_millis(50);
+const SECS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// `[profiles.<name>.retention]`. Unset fields keep everything.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct RetentionPolicy {
+    pub telemetry_days: Option<u64>,
+    pub telemetry_max_bytes: Option<u64>,
+    pub checkpoint_days: Option<u64>,
+    pub answer_cache_days: Option<u64>,
+    pub tool_output_days: Option<u64>,
+    pub transcript_days: Option<u64>,
+}
+
+impl RetentionPolicy {
+    pub fn is_configured(&self) -> bool {
+        *self != Self::default()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MaintenanceKind {
+    Telemetry,
+    Checkpoints,
+    AnswerCache,
+    ToolOutput,
+    Transcripts,
+}
+
+impl MaintenanceKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Telemetry => "telemetry",
+            Self::Checkpoints => "checkpoints",
+            Self::AnswerCache => "answer cache",
+            Self::ToolOutput => "tool output",
+            Self::Transcripts => "transcripts",
+        }
+    }
+
+    /// What `removed` counts.
+    fn unit(self) -> &'static str {
+        match self {
+            Self::Telemetry => "telemetry event(s)",
+            Self::Checkpoints => "checkpoint(s)",
+            Self::AnswerCache => "cached answer(s)",
+            Self::ToolOutput => "tool output file(s)",
+            Self::Transcripts => "transcript(s)",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MaintenanceStep {
+    pub kind: MaintenanceKind,
+    /// The retention that applied (`30d`, `30d, max 1048576 bytes`), or
+    /// `None` when the kind is not configured.
+    pub policy: Option<String>,
+    pub removed: usize,
+    /// Telemetry files rotated by `telemetry_max_bytes`.
+    pub rotated: usize,
+    /// Rotated telemetry files deleted by `telemetry_days`.
+    pub expired_files: usize,
+    pub freed_bytes: u64,
+    /// Stopped at the time budget before finishing.
+    pub incomplete: bool,
+    pub error: Option<String>,
+}
+
+impl MaintenanceStep {
+    fn new(kind: MaintenanceKind, policy: Option<String>) -> Self {
+        Self {
+            kind,
+            policy,
+            removed: 0,
+            rotated: 0,
+            expired_files: 0,
+            freed_bytes: 0,
+            incomplete: false,
+            error: None,
+        }
+    }
+
+    fn cleaned(&self) -> bool {
+        self.removed > 0 || self.rotated > 0 || self.expired_files > 0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MaintenanceReport {
+    pub steps: Vec<MaintenanceStep>,
+    pub elapsed_ms: u128,
+    pub timed_out: bool,
+}
+
+fn format_bytes(bytes: u64) -> String {
+    match bytes {
+        0..1024 => format!("{bytes} B"),
+        1024..1_048_576 => format!("{:.1} KiB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MiB", bytes as f64 / 1_048_576.0),
+    }
+}
+
+impl MaintenanceReport {
+    pub fn cleaned_anything(&self) -> bool {
+        self.steps.iter().any(MaintenanceStep::cleaned)
+    }
+
+    /// The startup line; `None` when nothing was cleaned.
+    pub fn summary_line(&self) -> Option<String> {
+        if !self.cleaned_anything() {
+            return None;
+        }
+        let mut parts = self
+            .steps
+            .iter()
+            .filter(|step| step.removed > 0)
+            .map(|step| format!("{} {}", step.removed, step.kind.unit()))
+            .collect::<Vec<String>>();
+        let rotated = self.steps.iter().map(|step| step.rotated).sum::<usize>();
+        if rotated > 0 {
+            parts.push(format!("rotated {rotated} telemetry file(s)"));
+        }
+        let expired = self
+            .steps
+            .iter()
+            .map(|step| step.expired_files)
+            .sum::<usize>();
+        if expired > 0 {
+            parts.push(format!("{expired} expired telemetry rotation(s)"));
+        }
+        let freed = self.steps.iter().map(|step| step.freed_bytes).sum::<u64>();
+        Some(format!(
+            "Maintenance: removed {} (freed {})",
+            parts.join(", "),
+            format_bytes(freed)
+        ))
+    }
+
+    /// `maintenance run` report: one line per kind.
+    pub fn detailed(&self) -> String {
+        let mut out = String::from("Maintenance:\n");
+        for step in &self.steps {
+            let Some(policy) = &step.policy else {
+                out.push_str(&format!(
+                    "  {:<13} not configured (kept)\n",
+                    step.kind.label()
+                ));
+                continue;
+            };
+            let result = if let Some(error) = &step.error {
+                format!("failed: {error}")
+            } else {
+                let mut result = format!("removed {} {}", step.removed, step.kind.unit());
+                if step.rotated > 0 {
+                    result.push_str(&format!(", rotated {}", step.rotated));
+                }
+                if step.expired_files > 0 {
+                    result.push_str(&format!(
+                        ", deleted {} expired rotation(s)",
+                        step.expired_files
+                    ));
+                }
+                result.push_str(&format!(", freed {}", format_bytes(step.freed_bytes)));
+                if step.incomplete {
+                    result.push_str(" (stopped at the time budget)");
+                }
+                result
+            };
+            out.push_str(&format!(
+                "  {:<13} retention {policy}: {result}\n",
+                step.kind.label()
+            ));
+        }
+        out.push_str(&format!("Took {} ms", self.elapsed_ms));
+        if self.timed_out {
+            out.push_str(" (stopped at the time budget; the rest runs next time)");
+        }
+        out.push('\n');
+        out
+    }
+}
+
+/// Files the pass looks at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MaintenanceTargets {
+    pub telemetry_path: PathBuf,
+    pub checkpoints_file: PathBuf,
+    pub answer_cache_dir: PathBuf,
+    pub tool_output_dir: PathBuf,
+    /// Empty when idle transcripts are off.
+    pub transcript_dir: PathBuf,
+}
+
+impl MaintenanceTargets {
+    pub fn for_config(cfg: &RuntimeConfig) -> Self {
+        Self {
+            telemetry_path: PathBuf::from(&cfg.telemetry_path),
+            checkpoints_file: cfg.paths.checkpoints_file(),
+            answer_cache_dir: cfg.paths.answer_cache_dir(),
+            tool_output_dir: cfg.paths.tool_output_dir(),
+            transcript_dir: PathBuf::from(&cfg.chat_idle_transcript_dir),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct MaintenanceState {
+    pub last_run_unix_secs: u64,
+}
+
+/// Due when never run or last run at least a day ago.
+pub fn maintenance_due(state_file: &Path, now_unix_secs: u64) -> bool {
+    std::fs::read_to_string(state_file)
+        .ok()
+        .and_then(|text| serde_json::from_str::<MaintenanceState>(&text).ok())
+        .is_none_or(|state| {
+            now_unix_secs.saturating_sub(state.last_run_unix_secs) >= MAINTENANCE_INTERVAL_SECS
+        })
+}
+
+pub fn record_maintenance_run(state_file: &Path, now_unix_secs: u64) -> Result<()> {
+    if let Some(parent) = state_file.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    let state = MaintenanceState {
+        last_run_unix_secs: now_unix_secs,
+    };
+    let text =
+        serde_json::to_string_pretty(&state).context("failed to encode maintenance state")?;
+    std::fs::write(state_file, text)
+        .with_context(|| format!("failed to write {}", state_file.display()))
+}
+
+fn cutoff_secs(now_unix_secs: u64, days: u64) -> u64 {
+    now_unix_secs.saturating_sub(days.saturating_mul(SECS_PER_DAY))
+}
+
+fn modified_unix_secs(meta: &std::fs::Metadata) -> Option<u64> {
+    meta.modified()
+        .ok()?
+        .duration_since(UNIX_EPOCH)
+        .ok()
+        .map(|age| age.as_secs())
+}
+
+/// Delete regular files in `dir` last written before `cutoff`.
+fn prune_dir(dir: &Path, cutoff: u64, deadline: Instant, step: &mut MaintenanceStep) -> Result<()> {
+    if dir.as_os_str().is_empty() || !dir.is_dir() {
+        return Ok(());
+    }
+    let entries =
+        std::fs::read_dir(dir).with_context(|| format!("failed to read '{}'", dir.display()))?;
+    for entry in entries.flatten() {
+        if Instant::now() >= deadline {
+            step.incomplete = true;
+            return Ok(());
+        }
+        let Ok(meta) = entry.metadata() else {
+            continue;
+        };
+        if !meta.is_file() || modified_unix_secs(&meta).is_none_or(|at| at >= cutoff) {
+            continue;
+        }
+        let path = entry.path();
+        std::fs::remove_file(&path)
+            .with_context(|| format!("failed to remove '{}'", path.display()))?;
+        step.removed += 1;
+        step.freed_bytes += meta.len();
+    }
+    Ok(())
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path
+        .file_name()
+        .map(|name| name.to_os_string())
+        .unwrap_or_default();
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+fn rotated_telemetry_path(path: &Path, index: usize) -> PathBuf {
+    with_suffix(path, &format!(".{index}"))
+}
+
+/// Rewrite `path` without events older than `cutoff`. Leaves the file as it
+/// was when the deadline passes mid-read.
+fn trim_telemetry_events(
+    path: &Path,
+    cutoff: u64,
+    deadline: Instant,
+    step: &mut MaintenanceStep,
+) -> Result<()> {
+    let file = std::fs::File::open(path)
+        .with_context(|| format!("failed to open telemetry '{}'", path.display()))?;
+    let cutoff_ms = u128::from(cutoff) * 1000;
+    let mut kept = Vec::new();
+    let mut removed = 0usize;
+    let mut removed_bytes = 0u64;
+    let mut read_bytes = 0u64;
+    let mut reader = BufReader::new(file);
+    let mut line = String::new();
+    for index in 0.. {
+        if index % 1024 == 0 && Instant::now() >= deadline {
+            step.incomplete = true;
+            return Ok(());
+        }
+        line.clear();
+        let read = reader
+            .read_line(&mut line)
+            .with_context(|| format!("failed to read '{}'", path.display()))?;
+        // A line without its newline is an event still being appended;
+        // it is carried over below with anything written after it.
+        if read == 0 || !line.ends_with('\n') {
+            break;
+        }
+        read_bytes += read as u64;
+        let event = line.trim_end_matches('\n');
+        let ts = serde_json::from_str::<Value>(event)
+            .ok()
+            .and_then(|event| event.get("ts_unix_ms").and_then(Value::as_u64));
+        match ts {
+            Some(ts) if u128::from(ts) < cutoff_ms => {
+                removed += 1;
+                removed_bytes += read as u64;
+            }
+            _ => kept.push(event.to_string()),
+        }
+    }
+    if removed == 0 {
+        return Ok(());
+    }
+    let tmp = with_suffix(path, ".tmp");
+    let mut out = std::fs::File::create(&tmp)
+        .with_context(|| format!("failed to write '{}'", tmp.display()))?;
+    for line in &kept {
+        writeln!(out, "{line}").with_context(|| format!("failed to write '{}'", tmp.display()))?;
+    }
+    // Emitters append without taking the lock; keep what they wrote while
+    // the file was being filtered.
+    let mut tail = std::fs::File::open(path)
+        .with_context(|| format!("failed to open telemetry '{}'", path.display()))?;
+    tail.seek(SeekFrom::Start(read_bytes))
+        .with_context(|| format!("failed to read '{}'", path.display()))?;
+    std::io::copy(&mut tail, &mut out)
+        .with_context(|| format!("failed to write '{}'", tmp.display()))?;
+    std::fs::rename(&tmp, path)
+        .with_context(|| format!("failed to replace telemetry '{}'", path.display()))?;
+    step.removed += removed;
+    step.freed_bytes += removed_bytes;
+    Ok(())
+}
+
+fn maintain_telemetry(
+    path: &Path,
+    policy: &RetentionPolicy,
+    now_unix_secs: u64,
+    deadline: Instant,
+    step: &mut MaintenanceStep,
+) -> Result<()> {
+    if !path.exists() && !rotated_telemetry_path(path, 1).exists() {
+        return Ok(());
+    }
+    let _lock = acquire_file_lock(path, MAINTENANCE_LOCK_TIMEOUT)?;
+    if let Some(days) = policy.telemetry_days {
+        let cutoff = cutoff_secs(now_unix_secs, days);
+        for index in 1..=TELEMETRY_ROTATED_FILES {
+            let rotated = rotated_telemetry_path(path, index);
+            let Ok(meta) = std::fs::metadata(&rotated) else {
+                continue;
+            };
+            if modified_unix_secs(&meta).is_some_and(|at| at < cutoff) {
+                std::fs::remove_file(&rotated)
+                    .with_context(|| format!("failed to remove '{}'", rotated.display()))?;
+                step.expired_files += 1;
+                step.freed_bytes += meta.len();
+            }
+        }
+        if path.is_file() {
+            trim_telemetry_events(path, cutoff, deadline, step)?;
+        }
+    }
+    if let Some(max_bytes) = policy.telemetry_max_bytes
+        && std::fs::metadata(path).is_ok_and(|meta| meta.len() > max_bytes)
+    {
+        let oldest = rotated_telemetry_path(path, TELEMETRY_ROTATED_FILES);
+        if let Ok(meta) = std::fs::metadata(&oldest) {
+            std::fs::remove_file(&oldest)
+                .with_context(|| format!("failed to remove '{}'", oldest.display()))?;
+            step.freed_bytes += meta.len();
+        }
+        for index in (1..TELEMETRY_ROTATED_FILES).rev() {
+            let from = rotated_telemetry_path(path, index);
+            if from.exists() {
+                std::fs::rename(&from, rotated_telemetry_path(path, index + 1))
+                    .with_context(|| format!("failed to rotate '{}'", from.display()))?;
+            }
+        }
+        std::fs::rename(path, rotated_telemetry_path(path, 1))
+            .with_context(|| format!("failed to rotate telemetry '{}'", path.display()))?;
+        step.rotated += 1;
+    }
+    Ok(())
+}
+
+fn maintain_checkpoints(
+    file: &Path,
+    days: u64,
+    now_unix_secs: u64,
+    step: &mut MaintenanceStep,
+) -> Result<()> {
+    let Ok(text) = std::fs::read_to_string(file) else {
+        return Ok(());
+    };
+    let mut store = serde_json::from_str::<CheckpointStore>(&text)
+        .with_context(|| format!("failed to parse checkpoints '{}'", file.display()))?;
+    let cutoff = chrono::DateTime::from_timestamp(cutoff_secs(now_unix_secs, days) as i64, 0)
+        .unwrap_or_default();
+    let removed = store.prune_older_than(cutoff);
+    if removed == 0 {
+        return Ok(());
+    }
+    let json = serde_json::to_string_pretty(&store).context("failed to serialize checkpoints")?;
+    std::fs::write(file, &json)
+        .with_context(|| format!("failed to write checkpoints '{}'", file.display()))?;
+    step.removed += removed;
+    step.freed_bytes += (text.len() as u64).saturating_sub(json.len() as u64);
+    Ok(())
+}
+
+fn days_label(days: u64) -> String {
+    format!("{days}d")
+}
+
+/// Each kind with the retention that applies to it, in pass order.
+fn retention_labels(policy: &RetentionPolicy) -> [(MaintenanceKind, Option<String>); 5] {
+    let telemetry_policy = match (policy.telemetry_days, policy.telemetry_max_bytes) {
+        (None, None) => None,
+        (Some(days), None) => Some(days_label(days)),
+        (None, Some(bytes)) => Some(format!("max {bytes} bytes")),
+        (Some(days), Some(bytes)) => Some(format!("{}, max {bytes} bytes", days_label(days))),
+    };
+    [
+        (MaintenanceKind::Telemetry, telemetry_policy),
+        (
+            MaintenanceKind::Checkpoints,
+            policy.checkpoint_days.map(days_label),
+        ),
+        (
+            MaintenanceKind::AnswerCache,
+            policy.answer_cache_days.map(days_label),
+        ),
+        (
+            MaintenanceKind::ToolOutput,
+            policy.tool_output_days.map(days_label),
+        ),
+        (
+            MaintenanceKind::Transcripts,
+            policy.transcript_days.map(days_label),
+        ),
+    ]
+}
+
+/// `profiles show` line for `[retention]`.
+pub fn format_retention_policy(policy: &RetentionPolicy) -> String {
+    if !policy.is_configured() {
+        return "Retention: none (startup maintenance off)".to_string();
+    }
+    let kinds = retention_labels(policy)
+        .into_iter()
+        .filter_map(|(kind, label)| label.map(|label| format!("{}={label}", kind.label())))
+        .collect::<Vec<String>>();
+    format!("Retention: {}", kinds.join(", "))
+}
+
+/// Apply `policy` to `targets`, stopping once `budget` is spent.
+pub fn run_maintenance(
+    targets: &MaintenanceTargets,
+    policy: &RetentionPolicy,
+    now_unix_secs: u64,
+    budget: Duration,
+) -> MaintenanceReport {
+    let started = Instant::now();
+    let deadline = started
+        .checked_add(budget)
+        .unwrap_or(started + Duration::from_secs(SECS_PER_DAY));
+    let mut steps = Vec::new();
+    for (kind, label) in retention_labels(policy) {
+        let mut step = MaintenanceStep::new(kind, label);
+        if step.policy.is_none() {
+            steps.push(step);
+            continue;
+        }
+        if Instant::now() >= deadline {
+            step.incomplete = true;
+            steps.push(step);
+            continue;
+        }
+        let dir_days = |days: Option<u64>| cutoff_secs(now_unix_secs, days.unwrap_or_default());
+        let result = match kind {
+            MaintenanceKind::Telemetry => maintain_telemetry(
+                &targets.telemetry_path,
+                policy,
+                now_unix_secs,
+                deadline,
+                &mut step,
+            ),
+            MaintenanceKind::Checkpoints => maintain_checkpoints(
+                &targets.checkpoints_file,
+                policy.checkpoint_days.unwrap_or_default(),
+                now_unix_secs,
+                &mut step,
+            ),
+            MaintenanceKind::AnswerCache => prune_dir(
+                &targets.answer_cache_dir,
+                dir_days(policy.answer_cache_days),
+                deadline,
+                &mut step,
+            ),
+            MaintenanceKind::ToolOutput => prune_dir(
+                &targets.tool_output_dir,
+                dir_days(policy.tool_output_days),
+                deadline,
+                &mut step,
+            ),
+            MaintenanceKind::Transcripts => prune_dir(
+                &targets.transcript_dir,
+                dir_days(policy.transcript_days),
+                deadline,
+                &mut step,
+            ),
+        };
+        if let Err(err) = result {
+            step.error = Some(format!("{err:#}"));
+        }
+        steps.push(step);
+    }
+    MaintenanceReport {
+        timed_out: steps.iter().any(|step| step.incomplete),
+        steps,
+        elapsed_ms: started.elapsed().as_millis(),
+    }
+}
+
+fn now_unix_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+/// Command-start hook: run the pass when a retention is configured and the
+/// last run was a day or more ago. Never fails the command.
+pub fn run_startup_maintenance(cfg: &RuntimeConfig, ui: &Ui) {
+    if !cfg.retention.is_configured() || cfg.read_only {
+        return;
+    }
+    let state_file = cfg.paths.maintenance_state_file();
+    let now = now_unix_secs();
+    if !maintenance_due(&state_file, now) {
+        return;
+    }
+    let report = run_maintenance(
+        &MaintenanceTargets::for_config(cfg),
+        &cfg.retention,
+        now,
+        MAINTENANCE_TIME_BUDGET,
+    );
+    for step in report.steps.iter().filter(|step| step.error.is_some()) {
+        tracing::warn!(
+            kind = step.kind.label(),
+            error = step.error.as_deref().unwrap_or_default(),
+            "maintenance step failed"
+        );
+    }
+    if let Err(err) = record_maintenance_run(&state_file, now) {
+        tracing::debug!(error = %err, "failed to record maintenance run");
+    }
+    if let Some(line) = report.summary_line() {
+        ui.notice(&line);
+    }
+}
+
+/// `maintenance run`: the pass with a per-kind report. Without `now` it
+/// respects the daily gate like startup does.
+pub fn run_maintenance_command(cfg: &RuntimeConfig, now: bool) -> Result<()> {
+    let state_file = cfg.paths.maintenance_state_file();
+    let now_secs = now_unix_secs();
+    if !now && !maintenance_due(&state_file, now_secs) {
+        println!(
+            "Maintenance already ran in the last 24h ({}); use --now to run it again.",
+            state_file.display()
+        );
+        return Ok(());
+    }
+    // Forced runs are not time-bounded; nothing is waiting on them.
+    let report = run_maintenance(
+        &MaintenanceTargets::for_config(cfg),
+        &cfg.retention,
+        now_secs,
+        Duration::MAX,
+    );
+    record_maintenance_run(&state_file, now_secs)?;
+    print!("{}", report.detailed());
+    Ok(())
+}
@@ -9,7 +9,13 @@ use anyhow::{Context, Result};
 use async_trait::async_trait;
 
 use crate::config::{McpServerConfig, RuntimeConfig};
+use crate::mcp_breaker::{
+    BreakerAdmission, BreakerPolicy, McpBreakerStore, emit_breaker_transition,
+    format_breaker_status, now_unix_secs,
+};
+use crate::telemetry::TelemetrySink;
 use crate::tool_policy::apply_tool_aliases;
+use crate::verbosity::Verbosity;
 
 #[derive(Debug)]
 struct McpDiscoveryContext {
@@ -350,7 +356,34 @@ impl adk_rust::Tool for StdioMcpTool {
 }
 
 pub async fn discover_mcp_tools(cfg: &RuntimeConfig) -> Vec<Arc<dyn Tool>> {
-    let mut all_tools = Vec::<Arc<dyn Tool>>::new();
+    discover_mcp_tools_by_server(cfg)
+        .await
+        .into_iter()
+        .flat_map(|(_, tools)| tools)
+        .collect()
+}
+
+/// `-v` line for one server's discovery: elapsed time and tool count, or
+/// `failed` when it was unreachable.
+pub fn format_mcp_discovery_timing(
+    server: &str,
+    elapsed: Duration,
+    tools: Option<usize>,
+) -> String {
+    let outcome = match tools {
+        Some(count) => format!("{count} tool(s)"),
+        None => "failed".to_string(),
+    };
+    format!("mcp discovery {server}: {outcome} in {}ms", elapsed.as_millis())
+}
+
+/// Discover MCP tools grouped by the server that exposed them. Unavailable
+/// servers are skipped with a warning, and servers whose circuit breaker is
+/// open are not contacted at all.
+pub async fn discover_mcp_tools_by_server(
+    cfg: &RuntimeConfig,
+) -> Vec<(String, Vec<Arc<dyn Tool>>)> {
+    let mut all_tools = Vec::<(String, Vec<Arc<dyn Tool>>)>::new();
     let servers = match select_mcp_servers(cfg, None) {
         Ok(servers) => servers,
         Err(err) => {
@@ -359,14 +392,57 @@ pub async fn discover_mcp_tools(cfg: &RuntimeConfig) -> Vec<Arc<dyn Tool>> {
         }
     };
 
+    let policy = BreakerPolicy::from_config(cfg);
+    let breaker_path = cfg.paths.mcp_breaker_file();
+    let mut breakers = McpBreakerStore::load(&breaker_path);
+    let loaded = breakers.clone();
+    let telemetry = TelemetrySink::new(cfg, "mcp".to_string());
+
     for server in servers {
-        match discover_mcp_tools_for_server(
+        let target = server.display_target().to_string();
+        let (admission, transition) =
+            breakers.admit(&server.name, &target, policy, now_unix_secs());
+        emit_breaker_transition(&telemetry, &breakers, &server, transition);
+        if let BreakerAdmission::Skip { retry_in_secs } = admission {
+            let message = format!(
+                "MCP server '{}' skipped: circuit breaker open after repeated failures, next \
+                 probe in {retry_in_secs}s (reset with `mcp discover --server {} \
+                 --reset-breaker`)",
+                server.name, server.name
+            );
+            if cfg.verbosity == Verbosity::Quiet {
+                tracing::debug!("{message}");
+            } else {
+                tracing::warn!("{message}");
+            }
+            continue;
+        }
+        let started = Instant::now();
+        let discovered = discover_mcp_tools_for_server(
             &server,
             cfg.tool_retry_attempts,
             cfg.tool_retry_delay_ms,
         )
-        .await
-        {
+        .await;
+        let transition = match &discovered {
+            Ok(_) => breakers.record_success(&server.name, &target),
+            Err(err) => breakers.record_failure(
+                &server.name,
+                &target,
+                policy,
+                now_unix_secs(),
+                &format!("{err:#}"),
+            ),
+        };
+        emit_breaker_transition(&telemetry, &breakers, &server, transition);
+        cfg.verbosity.detail(Verbosity::Info, || {
+            format_mcp_discovery_timing(
+                &server.name,
+                started.elapsed(),
+                discovered.as_ref().map(Vec::len).ok(),
+            )
+        });
+        match discovered {
             Ok(mut tools) => {
                 tools = apply_tool_aliases(tools, &server.tool_aliases);
                 tracing::info!(
@@ -376,7 +452,16 @@ pub async fn discover_mcp_tools(cfg: &RuntimeConfig) -> Vec<Arc<dyn Tool>> {
                     aliases = server.tool_aliases.len(),
                     "MCP tools discovered"
                 );
-                all_tools.append(&mut tools);
+                all_tools.push((server.name.clone(), tools));
+            }
+            // Under --quiet an unavailable server is not worth a warning.
+            Err(err) if cfg.verbosity == Verbosity::Quiet => {
+                tracing::debug!(
+                    server = %server.name,
+                    target = %server.display_target(),
+                    error = %err,
+                    "MCP server unavailable; continuing without its tools"
+                );
             }
             Err(err) => {
                 tracing::warn!(
@@ -389,6 +474,11 @@ pub async fn discover_mcp_tools(cfg: &RuntimeConfig) -> Vec<Arc<dyn Tool>> {
         }
     }
 
+    if breakers != loaded
+        && let Err(err) = breakers.save(&breaker_path)
+    {
+        tracing::debug!(error = %err, "failed to save MCP breaker state");
+    }
     all_tools
 }
 
@@ -407,6 +497,8 @@ pub async fn run_mcp_list(cfg: &RuntimeConfig) -> Result<()> {
         "Runtime MCP reliability policy: retry_attempts={} retry_delay_ms={}",
         cfg.tool_retry_attempts, cfg.tool_retry_delay_ms
     );
+    let breakers = McpBreakerStore::load(&cfg.paths.mcp_breaker_file());
+    let now = now_unix_secs();
     for server in servers {
         let auth = server.auth_bearer_env.as_deref().unwrap_or("<none>");
         let allowlist = if server.tool_allowlist.is_empty() {
@@ -426,9 +518,19 @@ pub async fn run_mcp_list(cfg: &RuntimeConfig) -> Result<()> {
             format!(" aliases={}", server.tool_aliases.len())
         };
         let transport_label = if server.is_stdio() { "stdio" } else { "http" };
+        let breaker = if cfg.mcp_failure_threshold == 0 {
+            "off".to_string()
+        } else {
+            format_breaker_status(
+                breakers.get(&server.name, server.display_target()),
+                now,
+            )
+        };
         println!(
-            "- {} transport={} target={} timeout={}s auth_env={}{} allowlist={}{}",
+            "- {} source={} transport={} target={} timeout={}s auth_env={}{} allowlist={}{} \
+             breaker={}",
             server.name,
+            server.source.label(),
             transport_label,
             server.display_target(),
             server.timeout_secs.unwrap_or(15),
@@ -436,23 +538,67 @@ pub async fn run_mcp_list(cfg: &RuntimeConfig) -> Result<()> {
             auth_status,
             allowlist,
             aliases,
+            breaker,
         );
     }
 
     Ok(())
 }
 
-pub async fn run_mcp_discover(cfg: &RuntimeConfig, server_name: Option<String>) -> Result<()> {
+/// `mcp discover`: servers with an open circuit breaker count as failures
+/// without being contacted unless `reset_breaker` clears their state first.
+pub async fn run_mcp_discover(
+    cfg: &RuntimeConfig,
+    server_name: Option<String>,
+    reset_breaker: bool,
+) -> Result<()> {
     let servers = select_mcp_servers(cfg, server_name.as_deref())?;
     if servers.is_empty() {
         println!("No enabled MCP servers configured for discovery.");
         return Ok(());
     }
 
+    let policy = BreakerPolicy::from_config(cfg);
+    let breaker_path = cfg.paths.mcp_breaker_file();
+    let mut breakers = McpBreakerStore::load(&breaker_path);
+    let telemetry = TelemetrySink::new(cfg, "mcp".to_string());
     let mut failures = 0usize;
     for server in &servers {
+        let target = server.display_target().to_string();
+        if reset_breaker {
+            let transition = breakers.reset(&server.name);
+            emit_breaker_transition(&telemetry, &breakers, server, transition);
+        }
+        let (admission, transition) =
+            breakers.admit(&server.name, &target, policy, now_unix_secs());
+        emit_breaker_transition(&telemetry, &breakers, server, transition);
+        if let BreakerAdmission::Skip { retry_in_secs } = admission {
+            failures += 1;
+            eprintln!(
+                "✗ '{}' skipped: circuit breaker open (next probe in {}s); pass \
+                 --reset-breaker to retry now",
+                server.name, retry_in_secs
+            );
+            continue;
+        }
         let diag =
             diagnose_mcp_server(server, cfg.tool_retry_attempts, cfg.tool_retry_delay_ms).await;
+        let transition = match &diag.state {
+            McpServerState::Reachable { .. } => breakers.record_success(&server.name, &target),
+            // A missing token is a local config problem, not server health.
+            McpServerState::AuthFailure { .. } => None,
+            McpServerState::Timeout { timeout_secs } => breakers.record_failure(
+                &server.name,
+                &target,
+                policy,
+                now_unix_secs(),
+                &format!("timed out after {timeout_secs}s"),
+            ),
+            McpServerState::Unreachable { error } => {
+                breakers.record_failure(&server.name, &target, policy, now_unix_secs(), error)
+            }
+        };
+        emit_breaker_transition(&telemetry, &breakers, server, transition);
         match &diag.state {
             McpServerState::Reachable {
                 tool_count,
@@ -496,6 +642,9 @@ pub async fn run_mcp_discover(cfg: &RuntimeConfig, server_name: Option<String>)
         }
     }
 
+    if let Err(err) = breakers.save(&breaker_path) {
+        tracing::debug!(error = %err, "failed to save MCP breaker state");
+    }
     if failures > 0 {
         return Err(anyhow::anyhow!(
             "MCP discovery completed with {} failure(s) out of {} server(s).",
@@ -2,10 +2,11 @@
 
 #[cfg(feature = "oauth")]
 use anyhow::Result;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 /// OAuth config for an MCP server.
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 pub struct McpOAuthConfig {
     pub client_id: Option<String>,
     pub callback_port: Option<u16>,
@@ -246,7 +247,7 @@ fn save_tokens(server_name: &str, tokens: &OAuthTokens) {
         match keyring::Entry::new(&key, "oauth-tokens") {
             Ok(entry) => { let _ = entry.set_password(&json); }
             Err(_) => {
-                // Fallback: write to .zavora/tokens/<server>.json
+                // Fallback: write to <workspace>/tokens/<server>.json
                 let _ = save_tokens_file(server_name, &json);
             }
         }
@@ -265,15 +266,16 @@ fn load_tokens(server_name: &str) -> Option<OAuthTokens> {
 
 #[cfg(feature = "oauth")]
 fn save_tokens_file(server_name: &str, json: &str) -> Result<()> {
-    let dir = std::path::Path::new(".zavora/tokens");
-    std::fs::create_dir_all(dir)?;
+    let dir = crate::paths::ZavoraPaths::from_env(None).mcp_tokens_dir();
+    std::fs::create_dir_all(&dir)?;
     std::fs::write(dir.join(format!("{}.json", server_name)), json)?;
     Ok(())
 }
 
 #[cfg(feature = "oauth")]
 fn load_tokens_file(server_name: &str) -> Option<String> {
-    std::fs::read_to_string(format!(".zavora/tokens/{}.json", server_name)).ok()
+    let dir = crate::paths::ZavoraPaths::from_env(None).mcp_tokens_dir();
+    std::fs::read_to_string(dir.join(format!("{}.json", server_name))).ok()
 }
 
 #[cfg(feature = "oauth")]
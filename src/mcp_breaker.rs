@@ -0,0 +1,323 @@
+//! Per-server circuit breaker for MCP discovery.
+//!
+//! A server that fails discovery `mcp_failure_threshold` times in a row is
+//! marked open and skipped, so a dead endpoint stops adding its timeout to
+//! every command. The backoff starts at [`MCP_BREAKER_BASE_SECS`] and doubles
+//! each time a probe fails, up to `mcp_breaker_max_secs`. Once it passes, the
+//! next invocation makes one half-open probe: success closes the breaker,
+//! failure opens it again for longer.
+//!
+//! State lives in `<state>/mcp-breakers.json`, keyed by server name and
+//! remembering the endpoint it was recorded for, so pointing a server at a
+//! new endpoint starts it closed again. Time is passed in as unix seconds so
+//! tests can drive the state machine without sleeping.
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::config::{McpServerConfig, RuntimeConfig};
+use crate::telemetry::TelemetrySink;
+
+pub const DEFAULT_MCP_FAILURE_THRESHOLD: u32 = 3;
+pub const DEFAULT_MCP_BREAKER_MAX_SECS: u64 = 3600;
+/// Backoff after the breaker first opens.
+pub const MCP_BREAKER_BASE_SECS: u64 = 30;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BreakerState {
+    #[default]
+    Closed,
+    /// Skipped until `open_until_unix_secs`.
+    Open,
+    /// Backoff passed; the next discovery is a probe.
+    HalfOpen,
+}
+
+impl BreakerState {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Closed => "closed",
+            Self::Open => "open",
+            Self::HalfOpen => "half-open",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ServerBreaker {
+    /// Endpoint or command the state was recorded for.
+    pub endpoint: String,
+    pub state: BreakerState,
+    pub consecutive_failures: u32,
+    /// Times the breaker opened since it was last closed; sets the backoff.
+    #[serde(default)]
+    pub trips: u32,
+    #[serde(default)]
+    pub open_until_unix_secs: u64,
+    #[serde(default)]
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BreakerPolicy {
+    /// 0 disables the breaker.
+    pub failure_threshold: u32,
+    pub max_secs: u64,
+}
+
+impl BreakerPolicy {
+    pub fn from_config(cfg: &RuntimeConfig) -> Self {
+        Self {
+            failure_threshold: cfg.mcp_failure_threshold,
+            max_secs: cfg.mcp_breaker_max_secs,
+        }
+    }
+
+    /// Backoff after the `trips`-th opening: the base doubled per earlier
+    /// trip, capped at `max_secs`.
+    pub fn backoff_secs(&self, trips: u32) -> u64 {
+        let doublings = trips.saturating_sub(1).min(32);
+        MCP_BREAKER_BASE_SECS
+            .saturating_mul(1u64 << doublings)
+            .min(self.max_secs.max(MCP_BREAKER_BASE_SECS))
+    }
+}
+
+/// Whether discovery should contact a server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BreakerAdmission {
+    Allow,
+    /// Half-open: one attempt decides whether the breaker closes.
+    Probe,
+    Skip {
+        retry_in_secs: u64,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BreakerTransition {
+    Opened { retry_in_secs: u64 },
+    HalfOpened,
+    Closed,
+    Reset,
+}
+
+impl BreakerTransition {
+    pub fn event_name(self) -> &'static str {
+        match self {
+            Self::Opened { .. } => "mcp.breaker.opened",
+            Self::HalfOpened => "mcp.breaker.half_open",
+            Self::Closed => "mcp.breaker.closed",
+            Self::Reset => "mcp.breaker.reset",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct McpBreakerStore {
+    #[serde(default)]
+    pub servers: BTreeMap<String, ServerBreaker>,
+}
+
+impl McpBreakerStore {
+    /// A missing or unreadable file is an empty store: losing breaker state
+    /// only costs one more discovery attempt.
+    pub fn load(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+        let text =
+            serde_json::to_string_pretty(self).context("failed to encode MCP breaker state")?;
+        std::fs::write(path, text).with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    /// State for `name` recorded against `endpoint`; state recorded for a
+    /// different endpoint does not count.
+    pub fn get(&self, name: &str, endpoint: &str) -> Option<&ServerBreaker> {
+        self.servers
+            .get(name)
+            .filter(|breaker| breaker.endpoint == endpoint)
+    }
+
+    fn entry(&mut self, name: &str, endpoint: &str) -> &mut ServerBreaker {
+        let breaker = self.servers.entry(name.to_string()).or_default();
+        if breaker.endpoint != endpoint {
+            *breaker = ServerBreaker {
+                endpoint: endpoint.to_string(),
+                ..ServerBreaker::default()
+            };
+        }
+        breaker
+    }
+
+    /// Decide whether to contact the server at `now`. An open breaker whose
+    /// backoff has passed moves to half-open and admits one probe.
+    pub fn admit(
+        &mut self,
+        name: &str,
+        endpoint: &str,
+        policy: BreakerPolicy,
+        now_unix_secs: u64,
+    ) -> (BreakerAdmission, Option<BreakerTransition>) {
+        if policy.failure_threshold == 0 {
+            return (BreakerAdmission::Allow, None);
+        }
+        let Some(state) = self.get(name, endpoint).map(|breaker| breaker.state) else {
+            return (BreakerAdmission::Allow, None);
+        };
+        match state {
+            BreakerState::Closed => (BreakerAdmission::Allow, None),
+            BreakerState::HalfOpen => (BreakerAdmission::Probe, None),
+            BreakerState::Open => {
+                let breaker = self.entry(name, endpoint);
+                if now_unix_secs < breaker.open_until_unix_secs {
+                    let retry_in_secs = breaker.open_until_unix_secs - now_unix_secs;
+                    return (BreakerAdmission::Skip { retry_in_secs }, None);
+                }
+                breaker.state = BreakerState::HalfOpen;
+                (BreakerAdmission::Probe, Some(BreakerTransition::HalfOpened))
+            }
+        }
+    }
+
+    pub fn record_success(&mut self, name: &str, endpoint: &str) -> Option<BreakerTransition> {
+        let previous = self
+            .servers
+            .remove(name)
+            .filter(|breaker| breaker.endpoint == endpoint)?
+            .state;
+        (previous != BreakerState::Closed).then_some(BreakerTransition::Closed)
+    }
+
+    /// Count a failure; opens the breaker at the threshold, and re-opens it
+    /// with a longer backoff when a half-open probe fails.
+    pub fn record_failure(
+        &mut self,
+        name: &str,
+        endpoint: &str,
+        policy: BreakerPolicy,
+        now_unix_secs: u64,
+        error: &str,
+    ) -> Option<BreakerTransition> {
+        if policy.failure_threshold == 0 {
+            return None;
+        }
+        let breaker = self.entry(name, endpoint);
+        breaker.consecutive_failures = breaker.consecutive_failures.saturating_add(1);
+        breaker.last_error = Some(error.to_string());
+        let opens = match breaker.state {
+            BreakerState::HalfOpen => true,
+            BreakerState::Closed => breaker.consecutive_failures >= policy.failure_threshold,
+            BreakerState::Open => false,
+        };
+        if !opens {
+            return None;
+        }
+        breaker.trips = breaker.trips.saturating_add(1);
+        let retry_in_secs = policy.backoff_secs(breaker.trips);
+        breaker.state = BreakerState::Open;
+        breaker.open_until_unix_secs = now_unix_secs.saturating_add(retry_in_secs);
+        Some(BreakerTransition::Opened { retry_in_secs })
+    }
+
+    /// Forget the server's state (`mcp discover --reset-breaker`).
+    pub fn reset(&mut self, name: &str) -> Option<BreakerTransition> {
+        self.servers.remove(name).map(|_| BreakerTransition::Reset)
+    }
+}
+
+/// Telemetry payload for a transition.
+pub fn breaker_event_payload(
+    server: &McpServerConfig,
+    transition: BreakerTransition,
+    breaker: Option<&ServerBreaker>,
+) -> Value {
+    let mut payload = json!({
+        "server": server.name,
+        "endpoint": server.display_target(),
+        "state": match transition {
+            BreakerTransition::Opened { .. } => BreakerState::Open.label(),
+            BreakerTransition::HalfOpened => BreakerState::HalfOpen.label(),
+            BreakerTransition::Closed | BreakerTransition::Reset => BreakerState::Closed.label(),
+        },
+    });
+    if let BreakerTransition::Opened { retry_in_secs } = transition {
+        payload["retry_in_secs"] = json!(retry_in_secs);
+    }
+    if let Some(breaker) = breaker {
+        payload["consecutive_failures"] = json!(breaker.consecutive_failures);
+        payload["error"] = json!(breaker.last_error);
+    }
+    payload
+}
+
+pub fn emit_breaker_transition(
+    telemetry: &TelemetrySink,
+    store: &McpBreakerStore,
+    server: &McpServerConfig,
+    transition: Option<BreakerTransition>,
+) {
+    let Some(transition) = transition else {
+        return;
+    };
+    let breaker = store.get(&server.name, server.display_target());
+    telemetry.emit(
+        transition.event_name(),
+        breaker_event_payload(server, transition, breaker),
+    );
+}
+
+/// `mcp list` column: `closed`, `open (next probe in 2m 5s, 3 failures)` or
+/// `half-open (probing on next use)`.
+pub fn format_breaker_status(breaker: Option<&ServerBreaker>, now_unix_secs: u64) -> String {
+    let Some(breaker) = breaker else {
+        return BreakerState::Closed.label().to_string();
+    };
+    match breaker.state {
+        BreakerState::Closed if breaker.consecutive_failures > 0 => {
+            format!(
+                "closed ({} recent failure(s))",
+                breaker.consecutive_failures
+            )
+        }
+        BreakerState::Closed => BreakerState::Closed.label().to_string(),
+        BreakerState::HalfOpen => "half-open (probing on next use)".to_string(),
+        BreakerState::Open if now_unix_secs >= breaker.open_until_unix_secs => format!(
+            "open (probing on next use, {} failure(s))",
+            breaker.consecutive_failures
+        ),
+        BreakerState::Open => format!(
+            "open (next probe in {}, {} failure(s))",
+            format_wait(breaker.open_until_unix_secs - now_unix_secs),
+            breaker.consecutive_failures
+        ),
+    }
+}
+
+fn format_wait(secs: u64) -> String {
+    match (secs / 60, secs % 60) {
+        (0, secs) => format!("{secs}s"),
+        (mins, 0) => format!("{mins}m"),
+        (mins, secs) => format!("{mins}m {secs}s"),
+    }
+}
+
+pub fn now_unix_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
@@ -1,3 +1,4 @@
+use std::collections::BTreeSet;
 use std::sync::Arc;
 
 use rmcp::model::{
@@ -7,7 +8,11 @@ use rmcp::model::{
 use rmcp::{ErrorData as McpError, ServerHandler, ServiceExt, transport::stdio};
 use serde_json::Value;
 
-use crate::tools::build_builtin_tools;
+use crate::config::{RuntimeConfig, execute_bash_options, secret_scan_options};
+use crate::read_only::apply_read_only_tool_policy;
+use crate::tools::execute_bash::ExecuteBashOptions;
+use crate::tools::secret_scan::SecretScanOptions;
+use crate::tools::{apply_execute_bash_options, apply_secret_scan_options, build_builtin_tools};
 
 /// MCP server that exposes zavora-cli's built-in tools over stdio.
 pub struct ZavoraMcpServer {
@@ -16,11 +21,35 @@ pub struct ZavoraMcpServer {
 
 impl ZavoraMcpServer {
     pub fn new() -> Self {
+        Self::with_options(ExecuteBashOptions::default(), SecretScanOptions::default())
+    }
+
+    pub fn with_options(options: ExecuteBashOptions, secret_scan: SecretScanOptions) -> Self {
+        let mut tools = build_builtin_tools();
+        apply_execute_bash_options(&mut tools, options);
+        apply_secret_scan_options(&mut tools, secret_scan);
+        Self { tools }
+    }
+
+    /// Tools for `mcp serve` under `cfg`, including read-only mode: the same
+    /// checkpoint as the agent's tools removes or guards the mutating ones.
+    pub fn for_config(cfg: &RuntimeConfig) -> Self {
+        let Self { tools } =
+            Self::with_options(execute_bash_options(cfg), secret_scan_options(cfg));
         Self {
-            tools: build_builtin_tools(),
+            tools: apply_read_only_tool_policy(cfg, tools, &BTreeSet::new()),
         }
     }
 
+    /// Tool by name, as `call_tool` would dispatch it.
+    pub fn tool(&self, name: &str) -> Option<&Arc<dyn adk_rust::Tool>> {
+        self.tools.iter().find(|tool| tool.name() == name)
+    }
+
+    pub fn tool_names(&self) -> Vec<String> {
+        self.tools.iter().map(|tool| tool.name().to_string()).collect()
+    }
+
     fn adk_tool_to_mcp(&self, tool: &dyn adk_rust::Tool) -> rmcp::model::Tool {
         let schema = tool.parameters_schema().unwrap_or_else(|| {
             serde_json::json!({ "type": "object", "properties": {} })
@@ -73,9 +102,7 @@ impl ServerHandler for ZavoraMcpServer {
 
         async move {
             let tool = self
-                .tools
-                .iter()
-                .find(|t| t.name() == tool_name)
+                .tool(&tool_name)
                 .ok_or_else(|| {
                     McpError::invalid_params(format!("tool '{}' not found", tool_name), None)
                 })?;
@@ -100,8 +127,8 @@ impl ServerHandler for ZavoraMcpServer {
 }
 
 /// Run the MCP server on stdio.
-pub async fn run_mcp_server() -> anyhow::Result<()> {
-    let server = ZavoraMcpServer::new();
+pub async fn run_mcp_server(cfg: &RuntimeConfig) -> anyhow::Result<()> {
+    let server = ZavoraMcpServer::for_config(cfg);
     let service = server.serve(stdio()).await.map_err(|e| {
         anyhow::anyhow!("MCP server error: {:?}", e)
     })?;
@@ -0,0 +1,392 @@
+//! Long-term workspace memory (`memory_backend = local`).
+//!
+//! Durable facts ("we use sqlx, not diesel") are kept one JSON object per
+//! line in `.zavora/memory/facts.jsonl` with a timestamp and the session that
+//! added them. The agent writes with `remember` (confirmation-gated like other
+//! writes) and searches with `recall`; `memory list|add|rm` curates the file
+//! by hand. Before each prompt, the `memory_recall_max_facts` facts sharing
+//! the most terms with it are injected as a labelled user-content section,
+//! scored the same way as local retrieval. A fact the input guardrail would
+//! block is never injected.
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use adk_rust::ReadonlyContext;
+use adk_rust::prelude::*;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::cli::MemoryBackend;
+use crate::config::RuntimeConfig;
+use crate::file_lock::{DEFAULT_FILE_LOCK_TIMEOUT, acquire_file_lock};
+use crate::guardrail::apply_guardrail;
+use crate::retrieval::{lexical_score, query_terms};
+use crate::session_bundle::sha256_hex;
+use crate::telemetry::TelemetrySink;
+
+pub const REMEMBER_TOOL_NAME: &str = "remember";
+pub const RECALL_TOOL_NAME: &str = "recall";
+/// Longest fact accepted; memory is for one-line facts, not documents.
+pub const MAX_FACT_CHARS: usize = 500;
+/// Most facts one `recall` call returns.
+pub const MAX_RECALL_LIMIT: usize = 20;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryFact {
+    pub id: String,
+    pub text: String,
+    pub created_at: String,
+    /// Session that added the fact; `None` for `memory add`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// `tool` or `cli`.
+    pub source: String,
+}
+
+/// One line per fact, trimmed and with whitespace runs collapsed.
+pub fn normalize_fact(text: &str) -> Result<String> {
+    let fact = text.split_whitespace().collect::<Vec<&str>>().join(" ");
+    if fact.is_empty() {
+        anyhow::bail!("fact is empty");
+    }
+    let chars = fact.chars().count();
+    if chars > MAX_FACT_CHARS {
+        anyhow::bail!("fact is {chars} characters; the limit is {MAX_FACT_CHARS}");
+    }
+    Ok(fact)
+}
+
+#[derive(Debug, Clone)]
+pub struct FactStore {
+    path: PathBuf,
+}
+
+impl FactStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn from_config(cfg: &RuntimeConfig) -> Self {
+        Self::new(cfg.paths.memory_facts_file())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Every fact in file order. Unparseable lines are skipped with a warning.
+    pub fn load(&self) -> Result<Vec<MemoryFact>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        let raw = std::fs::read_to_string(&self.path)
+            .with_context(|| format!("failed to read {}", self.path.display()))?;
+        Ok(raw
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .filter_map(|(index, line)| match serde_json::from_str(line) {
+                Ok(fact) => Some(fact),
+                Err(err) => {
+                    tracing::warn!(
+                        path = %self.path.display(),
+                        line = index + 1,
+                        error = %err,
+                        "Skipping malformed memory fact"
+                    );
+                    None
+                }
+            })
+            .collect())
+    }
+
+    /// Append `text`. Returns the stored fact and whether it already existed
+    /// (same text), in which case nothing is written.
+    pub fn add(
+        &self,
+        text: &str,
+        session_id: Option<&str>,
+        source: &str,
+    ) -> Result<(MemoryFact, bool)> {
+        let text = normalize_fact(text)?;
+        self.ensure_parent()?;
+        let _lock = acquire_file_lock(&self.path, DEFAULT_FILE_LOCK_TIMEOUT)?;
+        if let Some(existing) = self
+            .load()?
+            .into_iter()
+            .find(|fact| fact.text.eq_ignore_ascii_case(&text))
+        {
+            return Ok((existing, true));
+        }
+        let created_at = chrono::Utc::now().to_rfc3339();
+        let fact = MemoryFact {
+            id: sha256_hex(format!("{created_at}\n{text}").as_bytes())[..8].to_string(),
+            text,
+            created_at,
+            session_id: session_id.map(str::to_string),
+            source: source.to_string(),
+        };
+        let line = serde_json::to_string(&fact).context("failed to encode memory fact")?;
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|| format!("failed to open {}", self.path.display()))?;
+        writeln!(file, "{line}")
+            .with_context(|| format!("failed to write {}", self.path.display()))?;
+        Ok((fact, false))
+    }
+
+    /// Remove the fact with `id`; `None` when there is none.
+    pub fn remove(&self, id: &str) -> Result<Option<MemoryFact>> {
+        if !self.path.exists() {
+            return Ok(None);
+        }
+        let _lock = acquire_file_lock(&self.path, DEFAULT_FILE_LOCK_TIMEOUT)?;
+        let mut facts = self.load()?;
+        let Some(index) = facts.iter().position(|fact| fact.id == id.trim()) else {
+            return Ok(None);
+        };
+        let removed = facts.remove(index);
+        let mut body = String::new();
+        for fact in &facts {
+            body.push_str(&serde_json::to_string(fact).context("failed to encode memory fact")?);
+            body.push('\n');
+        }
+        let tmp = self.path.with_extension("jsonl.tmp");
+        std::fs::write(&tmp, body).with_context(|| format!("failed to write {}", tmp.display()))?;
+        std::fs::rename(&tmp, &self.path)
+            .with_context(|| format!("failed to replace {}", self.path.display()))?;
+        Ok(Some(removed))
+    }
+
+    fn ensure_parent(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+        Ok(())
+    }
+}
+
+/// Facts sharing terms with `query`, best first; ties go to the newer fact.
+pub fn rank_facts<'a>(
+    facts: &'a [MemoryFact],
+    query: &str,
+    limit: usize,
+) -> Vec<(usize, &'a MemoryFact)> {
+    let terms = query_terms(query);
+    if terms.is_empty() || limit == 0 {
+        return Vec::new();
+    }
+    let mut scored = facts
+        .iter()
+        .map(|fact| (lexical_score(&terms, &fact.text), fact))
+        .filter(|(score, _)| *score > 0)
+        .collect::<Vec<(usize, &MemoryFact)>>();
+    scored.sort_by(|(left_score, left), (right_score, right)| {
+        right_score
+            .cmp(left_score)
+            .then_with(|| right.created_at.cmp(&left.created_at))
+    });
+    scored.truncate(limit);
+    scored
+}
+
+/// The "Remembered facts" preamble, or `None` without facts.
+pub fn render_memory_context(facts: &[(usize, MemoryFact)]) -> Option<String> {
+    if facts.is_empty() {
+        return None;
+    }
+    let mut out = String::from("Remembered facts (from earlier sessions; use if relevant):\n");
+    for (_, fact) in facts {
+        let date = fact.created_at.get(..10).unwrap_or(&fact.created_at);
+        out.push_str(&format!("- {} ({date})\n", fact.text));
+    }
+    Some(out)
+}
+
+/// Facts to inject for `prompt`: top matches, run through the input
+/// guardrail. A blocked fact is dropped and a redacted one is injected
+/// redacted. `None` when memory is off, empty or nothing matches.
+pub fn recall_for_prompt(
+    cfg: &RuntimeConfig,
+    telemetry: &TelemetrySink,
+    prompt: &str,
+) -> Option<String> {
+    if cfg.memory_backend != MemoryBackend::Local || cfg.memory_recall_max_facts == 0 {
+        return None;
+    }
+    let facts = match FactStore::from_config(cfg).load() {
+        Ok(facts) => facts,
+        Err(err) => {
+            tracing::warn!(error = %format!("{err:#}"), "Memory recall skipped");
+            return None;
+        }
+    };
+    let guarded = rank_facts(&facts, prompt, cfg.memory_recall_max_facts)
+        .into_iter()
+        .filter_map(|(score, fact)| {
+            match apply_guardrail(
+                cfg,
+                telemetry,
+                "memory",
+                cfg.guardrail_input_mode,
+                &fact.text,
+            ) {
+                Ok(text) => Some((
+                    score,
+                    MemoryFact {
+                        text,
+                        ..fact.clone()
+                    },
+                )),
+                Err(_) => None,
+            }
+        })
+        .collect::<Vec<(usize, MemoryFact)>>();
+    render_memory_context(&guarded)
+}
+
+fn memory_error(kind: &str, code: &str, error: impl std::fmt::Display) -> Value {
+    json!({
+        "status": "error",
+        "kind": kind,
+        "code": code,
+        "error": error.to_string(),
+    })
+}
+
+pub fn remember_tool_response(store: &FactStore, args: &Value, session_id: Option<&str>) -> Value {
+    let Some(text) = args.get("fact").and_then(Value::as_str) else {
+        return memory_error(REMEMBER_TOOL_NAME, "invalid_args", "'fact' is required");
+    };
+    if let Err(err) = normalize_fact(text) {
+        return memory_error(REMEMBER_TOOL_NAME, "invalid_args", err);
+    }
+    match store.add(text, session_id, "tool") {
+        Ok((fact, duplicate)) => json!({
+            "status": "ok",
+            "kind": REMEMBER_TOOL_NAME,
+            "id": fact.id,
+            "fact": fact.text,
+            "duplicate": duplicate,
+        }),
+        Err(err) => memory_error(REMEMBER_TOOL_NAME, "io_error", format!("{err:#}")),
+    }
+}
+
+pub fn recall_tool_response(store: &FactStore, args: &Value, default_limit: usize) -> Value {
+    let Some(query) = args.get("query").and_then(Value::as_str) else {
+        return memory_error(RECALL_TOOL_NAME, "invalid_args", "'query' is required");
+    };
+    let limit = args
+        .get("limit")
+        .and_then(Value::as_u64)
+        .map(|limit| limit as usize)
+        .unwrap_or(default_limit)
+        .clamp(1, MAX_RECALL_LIMIT);
+    let facts = match store.load() {
+        Ok(facts) => facts,
+        Err(err) => return memory_error(RECALL_TOOL_NAME, "io_error", format!("{err:#}")),
+    };
+    let matches = rank_facts(&facts, query, limit)
+        .into_iter()
+        .map(|(score, fact)| {
+            json!({
+                "id": fact.id,
+                "fact": fact.text,
+                "created_at": fact.created_at,
+                "score": score,
+            })
+        })
+        .collect::<Vec<Value>>();
+    json!({
+        "status": "ok",
+        "kind": RECALL_TOOL_NAME,
+        "query": query,
+        "facts": matches,
+    })
+}
+
+/// `remember` and `recall`, or nothing when `memory_backend` is disabled.
+pub fn build_memory_tools(cfg: &RuntimeConfig) -> Vec<Arc<dyn Tool>> {
+    if cfg.memory_backend != MemoryBackend::Local {
+        return Vec::new();
+    }
+    let store = Arc::new(FactStore::from_config(cfg));
+    let remember_store = store.clone();
+    let remember = FunctionTool::new(
+        REMEMBER_TOOL_NAME,
+        "Stores a durable fact about this project or the user's preferences in long-term \
+         memory, so later sessions can recall it (e.g. 'releases are cut on Thursdays'). \
+         Args: fact (required, one line). Only store high-signal, lasting facts.",
+        move |ctx, args| {
+            let store = remember_store.clone();
+            async move {
+                Ok(remember_tool_response(
+                    &store,
+                    &args,
+                    Some(ctx.session_id()),
+                ))
+            }
+        },
+    );
+    let default_limit = cfg.memory_recall_max_facts.max(1);
+    let recall = FunctionTool::new(
+        RECALL_TOOL_NAME,
+        "Searches long-term memory for facts stored in earlier sessions. \
+         Args: query (required), limit (optional, default from memory_recall_max_facts). \
+         Returns matching facts with id, created_at and score, best first.",
+        move |_ctx, args| {
+            let store = store.clone();
+            async move { Ok(recall_tool_response(&store, &args, default_limit)) }
+        },
+    )
+    .with_read_only(true)
+    .with_concurrency_safe(true);
+    vec![Arc::new(remember), Arc::new(recall)]
+}
+
+pub fn run_memory_list(cfg: &RuntimeConfig) -> Result<()> {
+    let store = FactStore::from_config(cfg);
+    let facts = store.load()?;
+    if facts.is_empty() {
+        println!("No facts in {}.", store.path().display());
+    }
+    for fact in &facts {
+        let origin = match &fact.session_id {
+            Some(session) => format!("{}, session {session}", fact.source),
+            None => fact.source.clone(),
+        };
+        println!("{}  {}  {} ({origin})", fact.id, fact.created_at, fact.text);
+    }
+    if cfg.memory_backend != MemoryBackend::Local {
+        println!("Note: memory_backend is disabled, so these facts are not recalled.");
+    }
+    Ok(())
+}
+
+pub fn run_memory_add(cfg: &RuntimeConfig, text: &str) -> Result<()> {
+    let (fact, duplicate) = FactStore::from_config(cfg).add(text, None, "cli")?;
+    if duplicate {
+        println!("Already remembered as {}.", fact.id);
+    } else {
+        println!("Remembered {}: {}", fact.id, fact.text);
+    }
+    Ok(())
+}
+
+pub fn run_memory_rm(cfg: &RuntimeConfig, id: &str) -> Result<()> {
+    match FactStore::from_config(cfg).remove(id)? {
+        Some(fact) => {
+            println!("Forgot {}: {}", fact.id, fact.text);
+            Ok(())
+        }
+        None => Err(anyhow::anyhow!(
+            "no memory fact with id '{id}'; see `zavora-cli memory list`"
+        )),
+    }
+}
@@ -0,0 +1,383 @@
+/// Session database migrations with a zavora-side ledger.
+///
+/// adk-session only exposes an idempotent `migrate()`, so applied versions
+/// and timestamps are recorded in a `zavora_schema_migrations` table in the
+/// same sqlite database. `migrate status|up|down` read and update it.
+use adk_session::SqliteSessionService;
+use anyhow::{Context, Result};
+use sqlx::sqlite::SqliteConnectOptions;
+use sqlx::{Row, SqlitePool};
+use std::str::FromStr;
+
+use crate::cli::SessionBackend;
+use crate::config::{RuntimeConfig, display_session_db_url};
+use crate::session::{ensure_parent_dir_for_sqlite_url, sqlite_path_from_url};
+
+pub const MIGRATION_LEDGER_TABLE: &str = "zavora_schema_migrations";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrationAction {
+    /// The adk-session base schema, applied through `SqliteSessionService::migrate`.
+    /// adk-session has no down migration, so this step cannot be rolled back.
+    AdkSessionSchema,
+    /// Zavora-owned schema change; `down: None` marks it irreversible.
+    Sql {
+        up: &'static str,
+        down: Option<&'static str>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SessionMigration {
+    pub version: i64,
+    pub description: &'static str,
+    pub action: MigrationAction,
+}
+
+impl SessionMigration {
+    pub fn reversible(&self) -> bool {
+        matches!(self.action, MigrationAction::Sql { down: Some(_), .. })
+    }
+}
+
+/// Known migrations, in ascending version order.
+pub const SESSION_MIGRATIONS: &[SessionMigration] = &[SessionMigration {
+    version: 1,
+    description: "adk-session base schema (sessions, events, app/user state)",
+    action: MigrationAction::AdkSessionSchema,
+}];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppliedMigration {
+    pub version: i64,
+    pub description: String,
+    pub applied_at: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationStatus {
+    pub database_exists: bool,
+    /// False when the database predates the ledger table; the current
+    /// version is then unknown until `migrate up` records it.
+    pub ledger_recorded: bool,
+    pub current_version: i64,
+    pub applied: Vec<AppliedMigration>,
+    pub pending: Vec<SessionMigration>,
+}
+
+async fn open_ledger_pool(db_url: &str) -> Result<SqlitePool> {
+    ensure_parent_dir_for_sqlite_url(db_url)?;
+    let pool = SqlitePool::connect(db_url)
+        .await
+        .context("failed to open sqlite session database for migrations")?;
+    sqlx::query(&format!(
+        "CREATE TABLE IF NOT EXISTS {MIGRATION_LEDGER_TABLE} (
+            version INTEGER PRIMARY KEY,
+            description TEXT NOT NULL,
+            applied_at TEXT NOT NULL
+        )"
+    ))
+    .execute(&pool)
+    .await
+    .context("failed to create migration ledger table")?;
+    Ok(pool)
+}
+
+async fn read_ledger(pool: &SqlitePool) -> Result<Vec<AppliedMigration>> {
+    let rows = sqlx::query(&format!(
+        "SELECT version, description, applied_at FROM {MIGRATION_LEDGER_TABLE} ORDER BY version"
+    ))
+    .fetch_all(pool)
+    .await
+    .context("failed to read migration ledger")?;
+    Ok(rows
+        .iter()
+        .map(|row| AppliedMigration {
+            version: row.get("version"),
+            description: row.get("description"),
+            applied_at: row.get("applied_at"),
+        })
+        .collect())
+}
+
+async fn read_ledger_if_recorded(pool: &SqlitePool) -> Result<Option<Vec<AppliedMigration>>> {
+    let table = sqlx::query("SELECT name FROM sqlite_master WHERE type = 'table' AND name = ?")
+        .bind(MIGRATION_LEDGER_TABLE)
+        .fetch_optional(pool)
+        .await
+        .context("failed to read session database schema")?;
+    if table.is_none() {
+        return Ok(None);
+    }
+    read_ledger(pool).await.map(Some)
+}
+
+async fn record_migration(pool: &SqlitePool, migration: &SessionMigration) -> Result<()> {
+    sqlx::query(&format!(
+        "INSERT OR IGNORE INTO {MIGRATION_LEDGER_TABLE} (version, description, applied_at) VALUES (?, ?, ?)"
+    ))
+    .bind(migration.version)
+    .bind(migration.description)
+    .bind(chrono::Utc::now().to_rfc3339())
+    .execute(pool)
+    .await
+    .with_context(|| format!("failed to record migration {} in ledger", migration.version))?;
+    Ok(())
+}
+
+/// Record the adk-session base schema after `SqliteSessionService::migrate`
+/// created it for a new database, so status is accurate from the start.
+pub async fn record_adk_schema_migration(db_url: &str) -> Result<()> {
+    let pool = open_ledger_pool(db_url).await?;
+    for migration in SESSION_MIGRATIONS
+        .iter()
+        .filter(|migration| migration.action == MigrationAction::AdkSessionSchema)
+    {
+        record_migration(&pool, migration).await?;
+    }
+    pool.close().await;
+    Ok(())
+}
+
+/// Current version and pending migrations. Read-only: never creates the
+/// database or the ledger table.
+pub async fn migration_status(
+    db_url: &str,
+    migrations: &[SessionMigration],
+) -> Result<MigrationStatus> {
+    let unrecorded = |database_exists| MigrationStatus {
+        database_exists,
+        ledger_recorded: false,
+        current_version: 0,
+        applied: Vec::new(),
+        pending: migrations.to_vec(),
+    };
+    if sqlite_path_from_url(db_url).is_some_and(|path| !path.exists()) {
+        return Ok(unrecorded(false));
+    }
+
+    let options = SqliteConnectOptions::from_str(db_url)
+        .context("invalid sqlite session database url")?
+        .read_only(true)
+        .create_if_missing(false);
+    let pool = SqlitePool::connect_with(options)
+        .await
+        .context("failed to open sqlite session database for migrations")?;
+    let applied = read_ledger_if_recorded(&pool).await;
+    pool.close().await;
+    let Some(applied) = applied? else {
+        return Ok(unrecorded(true));
+    };
+
+    let pending = migrations
+        .iter()
+        .filter(|migration| !applied.iter().any(|done| done.version == migration.version))
+        .copied()
+        .collect();
+    Ok(MigrationStatus {
+        database_exists: true,
+        ledger_recorded: true,
+        current_version: applied.iter().map(|done| done.version).max().unwrap_or(0),
+        applied,
+        pending,
+    })
+}
+
+/// Apply every pending migration in version order, recording each in the
+/// ledger. Returns the migrations applied by this call.
+pub async fn migrate_up(
+    db_url: &str,
+    migrations: &[SessionMigration],
+) -> Result<Vec<SessionMigration>> {
+    let pool = open_ledger_pool(db_url).await?;
+    let applied = read_ledger(&pool).await?;
+    let mut newly_applied = Vec::new();
+
+    for migration in migrations {
+        if applied.iter().any(|done| done.version == migration.version) {
+            continue;
+        }
+        match migration.action {
+            MigrationAction::AdkSessionSchema => {
+                let service = SqliteSessionService::new(db_url)
+                    .await
+                    .context("failed to open sqlite session database")?;
+                service
+                    .migrate()
+                    .await
+                    .context("failed to run sqlite session migrations")?;
+            }
+            MigrationAction::Sql { up, .. } => {
+                sqlx::raw_sql(up)
+                    .execute(&pool)
+                    .await
+                    .with_context(|| format!("failed to apply migration {}", migration.version))?;
+            }
+        }
+        record_migration(&pool, migration).await?;
+        newly_applied.push(*migration);
+    }
+
+    pool.close().await;
+    Ok(newly_applied)
+}
+
+/// Roll back applied migrations above `to_version`, newest first. Fails
+/// before changing anything if one of them is irreversible.
+pub async fn migrate_down(
+    db_url: &str,
+    migrations: &[SessionMigration],
+    to_version: i64,
+) -> Result<Vec<SessionMigration>> {
+    let pool = open_ledger_pool(db_url).await?;
+    let applied = read_ledger(&pool).await?;
+
+    let mut targets = Vec::new();
+    for done in applied
+        .iter()
+        .rev()
+        .filter(|done| done.version > to_version)
+    {
+        let migration = migrations
+            .iter()
+            .find(|migration| migration.version == done.version)
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "migrate down: version {} is in the ledger but unknown to this build; upgrade zavora-cli first",
+                    done.version
+                )
+            })?;
+        if !migration.reversible() {
+            return Err(anyhow::anyhow!(
+                "migrate down: migration {} ({}) cannot be rolled back; lowest reachable version is {}",
+                migration.version,
+                migration.description,
+                migration.version
+            ));
+        }
+        targets.push(*migration);
+    }
+
+    for migration in &targets {
+        if let MigrationAction::Sql {
+            down: Some(down), ..
+        } = migration.action
+        {
+            sqlx::raw_sql(down)
+                .execute(&pool)
+                .await
+                .with_context(|| format!("failed to roll back migration {}", migration.version))?;
+        }
+        sqlx::query(&format!(
+            "DELETE FROM {MIGRATION_LEDGER_TABLE} WHERE version = ?"
+        ))
+        .bind(migration.version)
+        .execute(&pool)
+        .await
+        .with_context(|| {
+            format!(
+                "failed to remove migration {} from ledger",
+                migration.version
+            )
+        })?;
+    }
+
+    pool.close().await;
+    Ok(targets)
+}
+
+fn memory_backend_notice(cfg: &RuntimeConfig) -> bool {
+    if cfg.session_backend == SessionBackend::Memory {
+        println!("Session backend is memory; there is no schema to migrate.");
+        return true;
+    }
+    false
+}
+
+pub async fn run_migrate_status(cfg: &RuntimeConfig) -> Result<()> {
+    if memory_backend_notice(cfg) {
+        return Ok(());
+    }
+    let status = migration_status(&cfg.session_db_url, SESSION_MIGRATIONS).await?;
+    println!("Database: {}", display_session_db_url(cfg));
+    if !status.database_exists {
+        println!("Database file does not exist yet; it is created on first use.");
+        println!("Current schema version: {}", status.current_version);
+    } else if !status.ledger_recorded {
+        println!(
+            "Current schema version: unknown (not yet recorded; run `migrate up` to record it)"
+        );
+    } else {
+        println!("Current schema version: {}", status.current_version);
+    }
+    for done in &status.applied {
+        println!(
+            "  applied  {:>3}  {}  ({})",
+            done.version, done.description, done.applied_at
+        );
+    }
+    if status.pending.is_empty() {
+        println!("No pending migrations.");
+    } else {
+        println!("Pending migrations:");
+        for migration in &status.pending {
+            println!(
+                "  pending  {:>3}  {}",
+                migration.version, migration.description
+            );
+        }
+    }
+    Ok(())
+}
+
+pub async fn run_migrate_up(cfg: &RuntimeConfig) -> Result<()> {
+    if memory_backend_notice(cfg) {
+        return Ok(());
+    }
+    let applied = migrate_up(&cfg.session_db_url, SESSION_MIGRATIONS).await?;
+    if applied.is_empty() {
+        println!(
+            "Schema is up to date; no migrations applied: {}",
+            display_session_db_url(cfg)
+        );
+        return Ok(());
+    }
+    for migration in &applied {
+        println!(
+            "Applied migration {}: {}",
+            migration.version, migration.description
+        );
+    }
+    println!(
+        "SQLite migrations applied successfully: {}",
+        display_session_db_url(cfg)
+    );
+    Ok(())
+}
+
+pub async fn run_migrate_down(cfg: &RuntimeConfig, to_version: i64, force: bool) -> Result<()> {
+    if memory_backend_notice(cfg) {
+        return Ok(());
+    }
+    if !force {
+        return Err(anyhow::anyhow!(
+            "migrate down is destructive. Re-run with --force to roll back to version {to_version}"
+        ));
+    }
+    let rolled_back = migrate_down(&cfg.session_db_url, SESSION_MIGRATIONS, to_version).await?;
+    if rolled_back.is_empty() {
+        println!("Schema is already at or below version {to_version}; nothing to roll back.");
+        return Ok(());
+    }
+    for migration in &rolled_back {
+        println!(
+            "Rolled back migration {}: {}",
+            migration.version, migration.description
+        );
+    }
+    println!(
+        "Schema rolled back to version {to_version}: {}",
+        display_session_db_url(cfg)
+    );
+    Ok(())
+}
@@ -0,0 +1,481 @@
+//! `models list`: live model catalogs from each provider's listing endpoint.
+//!
+//! Listings are cached per provider under `<state>/models/` for
+//! [`MODEL_CATALOG_TTL_SECS`], and the chat `/model` picker merges a fresh
+//! cache into its built-in table without calling out itself.
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::chat::{ModelPickerOption, model_picker_options};
+use crate::cli::Provider;
+use crate::config::RuntimeConfig;
+use crate::provider::{AZURE_OPENAI_API_KEY_ENV, provider_label};
+
+pub const MODEL_CATALOG_TTL_SECS: u64 = 60 * 60;
+pub const MODEL_CATALOG_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Providers `models list` covers when `--provider` is not given.
+pub const CATALOG_PROVIDERS: &[Provider] = &[
+    Provider::Openai,
+    Provider::Anthropic,
+    Provider::Gemini,
+    Provider::Groq,
+    Provider::Deepseek,
+    Provider::Ollama,
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct CatalogModel {
+    pub id: String,
+    /// Input tokens, when the endpoint reports it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_window: Option<u64>,
+    /// `None` when the endpoint says nothing about deprecation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deprecated: Option<bool>,
+}
+
+#[async_trait]
+pub trait ModelCatalog: Send + Sync {
+    fn provider(&self) -> Provider;
+    /// Why the catalog cannot be queried (usually a missing API key).
+    fn unavailable_reason(&self) -> Option<String>;
+    async fn list_models(&self) -> Result<Vec<CatalogModel>>;
+}
+
+/// A provider's model-listing endpoint, honoring `providers.<name>.base_url`
+/// and extra headers like model calls do.
+pub struct HttpModelCatalog {
+    pub provider: Provider,
+    pub base_url: String,
+    pub api_key: Option<String>,
+    pub extra_headers: Vec<(String, String)>,
+}
+
+fn credential_env(provider: Provider) -> Option<&'static str> {
+    match provider {
+        Provider::Openai => Some("OPENAI_API_KEY"),
+        Provider::Anthropic => Some("ANTHROPIC_API_KEY"),
+        Provider::Gemini => Some("GOOGLE_API_KEY"),
+        Provider::Groq => Some("GROQ_API_KEY"),
+        Provider::Deepseek => Some("DEEPSEEK_API_KEY"),
+        Provider::AzureOpenai => Some(AZURE_OPENAI_API_KEY_ENV),
+        // Optional: local OpenAI-compatible servers often run without keys.
+        Provider::OpenaiCompatible | Provider::Ollama | Provider::Fake | Provider::Auto => None,
+    }
+}
+
+fn default_catalog_base_url(provider: Provider) -> String {
+    match provider {
+        Provider::Openai => "https://api.openai.com/v1".to_string(),
+        Provider::Anthropic => "https://api.anthropic.com/v1".to_string(),
+        Provider::Gemini => "https://generativelanguage.googleapis.com/v1beta".to_string(),
+        Provider::Groq => "https://api.groq.com/openai/v1".to_string(),
+        Provider::Deepseek => "https://api.deepseek.com".to_string(),
+        // Only reachable through `providers.<name>.base_url`.
+        Provider::AzureOpenai | Provider::OpenaiCompatible => String::new(),
+        Provider::Ollama | Provider::Fake | Provider::Auto => std::env::var("OLLAMA_HOST")
+            .ok()
+            .filter(|host| !host.trim().is_empty())
+            .unwrap_or_else(|| "http://localhost:11434".to_string()),
+    }
+}
+
+impl HttpModelCatalog {
+    pub fn for_config(cfg: &RuntimeConfig, provider: Provider) -> Self {
+        let overrides = cfg.provider_overrides.get(&provider);
+        Self {
+            provider,
+            base_url: overrides
+                .and_then(|overrides| overrides.base_url.clone())
+                .unwrap_or_else(|| default_catalog_base_url(provider)),
+            api_key: overrides
+                .and_then(|overrides| overrides.api_key_env.as_deref())
+                .or(credential_env(provider))
+                .and_then(|key| std::env::var(key).ok())
+                .filter(|value| !value.trim().is_empty()),
+            extra_headers: overrides
+                .map(|overrides| {
+                    overrides
+                        .extra_headers
+                        .iter()
+                        .map(|(name, value)| (name.clone(), value.clone()))
+                        .collect()
+                })
+                .unwrap_or_default(),
+        }
+    }
+
+    fn listing_url(&self) -> String {
+        let base = self.base_url.trim_end_matches('/');
+        match self.provider {
+            Provider::Ollama => format!("{base}/api/tags"),
+            _ => format!("{base}/models"),
+        }
+    }
+}
+
+#[async_trait]
+impl ModelCatalog for HttpModelCatalog {
+    fn provider(&self) -> Provider {
+        self.provider
+    }
+
+    fn unavailable_reason(&self) -> Option<String> {
+        if self.provider == Provider::AzureOpenai {
+            return Some(
+                "skipped: Azure OpenAI routes by deployment; the /model picker shows \
+                 providers.azure-openai.deployment"
+                    .to_string(),
+            );
+        }
+        if self.base_url.trim().is_empty() {
+            return Some(format!(
+                "skipped: providers.{}.base_url is not set",
+                provider_label(self.provider)
+            ));
+        }
+        let key = credential_env(self.provider)?;
+        self.api_key
+            .is_none()
+            .then(|| format!("skipped: {key} is not set"))
+    }
+
+    #[cfg(feature = "model-catalog")]
+    async fn list_models(&self) -> Result<Vec<CatalogModel>> {
+        let client = reqwest::Client::builder()
+            .timeout(MODEL_CATALOG_TIMEOUT)
+            .user_agent(concat!("zavora-cli/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .context("failed to build model catalog client")?;
+        let url = self.listing_url();
+        let mut request = client.get(&url);
+        if let Some(key) = &self.api_key {
+            request = match self.provider {
+                Provider::Anthropic => request
+                    .header("x-api-key", key)
+                    .header("anthropic-version", "2023-06-01"),
+                Provider::Gemini => request.query(&[("key", key)]),
+                _ => request.bearer_auth(key),
+            };
+        }
+        for (name, value) in &self.extra_headers {
+            request = request.header(name, value);
+        }
+        let payload = request
+            .send()
+            .await
+            .and_then(|resp| resp.error_for_status())
+            .with_context(|| format!("failed to list models from {url}"))?
+            .json::<Value>()
+            .await
+            .context("failed to parse model listing")?;
+        Ok(parse_model_listing(self.provider, &payload))
+    }
+
+    #[cfg(not(feature = "model-catalog"))]
+    async fn list_models(&self) -> Result<Vec<CatalogModel>> {
+        Err(anyhow::anyhow!(
+            "live model listing requires feature 'model-catalog'. Rebuild with: cargo build --features model-catalog"
+        ))
+    }
+}
+
+/// Models from a listing response: `data[]` (OpenAI-style, Anthropic),
+/// `models[]` with `models/` names (Gemini) or plain names (Ollama tags).
+pub fn parse_model_listing(provider: Provider, payload: &Value) -> Vec<CatalogModel> {
+    let entries = payload["data"]
+        .as_array()
+        .or_else(|| payload["models"].as_array())
+        .cloned()
+        .unwrap_or_default();
+    let mut models = entries
+        .iter()
+        .filter_map(|entry| {
+            let id = entry["id"].as_str().or_else(|| entry["name"].as_str())?;
+            let id = match provider {
+                Provider::Gemini => id.trim_start_matches("models/"),
+                _ => id,
+            };
+            let context_window = ["context_window", "context_length", "inputTokenLimit"]
+                .iter()
+                .find_map(|key| entry[*key].as_u64());
+            let deprecated = entry["deprecated"]
+                .as_bool()
+                .or_else(|| entry["active"].as_bool().map(|active| !active));
+            Some(CatalogModel {
+                id: id.to_string(),
+                context_window,
+                deprecated,
+            })
+        })
+        .collect::<Vec<CatalogModel>>();
+    models.sort_by(|a, b| a.id.cmp(&b.id));
+    models.dedup_by(|a, b| a.id == b.id);
+    models
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ModelCatalogCache {
+    pub fetched_unix_secs: u64,
+    pub models: Vec<CatalogModel>,
+}
+
+pub fn model_catalog_cache_path(dir: &Path, provider: Provider) -> PathBuf {
+    dir.join(format!("{}.json", provider_label(provider)))
+}
+
+/// The cached listing for `provider`, only while it is younger than the TTL.
+pub fn load_fresh_catalog(
+    dir: &Path,
+    provider: Provider,
+    now_unix_secs: u64,
+) -> Option<Vec<CatalogModel>> {
+    let text = std::fs::read_to_string(model_catalog_cache_path(dir, provider)).ok()?;
+    let cache = serde_json::from_str::<ModelCatalogCache>(&text).ok()?;
+    (now_unix_secs.saturating_sub(cache.fetched_unix_secs) < MODEL_CATALOG_TTL_SECS)
+        .then_some(cache.models)
+}
+
+pub fn save_catalog(
+    dir: &Path,
+    provider: Provider,
+    models: &[CatalogModel],
+    now_unix_secs: u64,
+) -> Result<()> {
+    std::fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    let cache = ModelCatalogCache {
+        fetched_unix_secs: now_unix_secs,
+        models: models.to_vec(),
+    };
+    let path = model_catalog_cache_path(dir, provider);
+    let text = serde_json::to_string_pretty(&cache).context("failed to encode model catalog")?;
+    std::fs::write(&path, text).with_context(|| format!("failed to write {}", path.display()))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CatalogListing {
+    Listed {
+        models: Vec<CatalogModel>,
+        cached: bool,
+    },
+    Skipped(String),
+    Failed(String),
+}
+
+/// One provider's listing: the fresh cache unless `refresh`, otherwise the
+/// endpoint (and the cache is rewritten). Failures never abort the command.
+pub async fn list_catalog_models(
+    catalog: &dyn ModelCatalog,
+    cache_dir: &Path,
+    now_unix_secs: u64,
+    refresh: bool,
+) -> CatalogListing {
+    if let Some(reason) = catalog.unavailable_reason() {
+        return CatalogListing::Skipped(reason);
+    }
+    let provider = catalog.provider();
+    if !refresh && let Some(models) = load_fresh_catalog(cache_dir, provider, now_unix_secs) {
+        return CatalogListing::Listed {
+            models,
+            cached: true,
+        };
+    }
+    match catalog.list_models().await {
+        Ok(models) => {
+            if let Err(err) = save_catalog(cache_dir, provider, &models, now_unix_secs) {
+                tracing::warn!(error = %err, "failed to cache model catalog");
+            }
+            CatalogListing::Listed {
+                models,
+                cached: false,
+            }
+        }
+        Err(err) => CatalogListing::Failed(format!("{err:#}")),
+    }
+}
+
+/// `128000` → `128k`, `1048576` → `1M`.
+pub fn format_context_window(tokens: u64) -> String {
+    if tokens >= 1_000_000 {
+        format!("{}M", tokens / 1_000_000)
+    } else if tokens >= 1_000 {
+        format!("{}k", tokens / 1_000)
+    } else {
+        tokens.to_string()
+    }
+}
+
+/// Built-in options first (their descriptions are curated), marked when the
+/// live catalog calls them deprecated; then live-only models in id order.
+pub fn merge_model_picker_options(
+    builtin: Vec<ModelPickerOption>,
+    live: &[CatalogModel],
+) -> Vec<ModelPickerOption> {
+    let mut merged = builtin
+        .into_iter()
+        .map(|mut option| {
+            if live
+                .iter()
+                .any(|model| model.id == option.id && model.deprecated == Some(true))
+            {
+                option.description = format!("{} (deprecated)", option.description);
+            }
+            option
+        })
+        .collect::<Vec<ModelPickerOption>>();
+    for model in live {
+        if merged.iter().any(|option| option.id == model.id) {
+            continue;
+        }
+        merged.push(ModelPickerOption::new(
+            &model.id,
+            &model
+                .context_window
+                .map(format_context_window)
+                .unwrap_or_else(|| "?".to_string()),
+            if model.deprecated == Some(true) {
+                "live catalog, deprecated"
+            } else {
+                "live catalog"
+            },
+        ));
+    }
+    merged
+}
+
+/// Picker options for chat `/model`: the built-in table (or, for Azure
+/// OpenAI, the configured deployment) plus a fresh cached listing, if any.
+/// Never calls the network.
+pub fn chat_model_picker_options(
+    cfg: &RuntimeConfig,
+    provider: Provider,
+) -> Vec<ModelPickerOption> {
+    let mut builtin = model_picker_options(provider);
+    if provider == Provider::AzureOpenai
+        && let Some(deployment) = cfg
+            .provider_overrides
+            .get(&provider)
+            .and_then(|overrides| overrides.deployment.as_deref())
+            .map(str::trim)
+            .filter(|deployment| !deployment.is_empty())
+    {
+        builtin.push(ModelPickerOption::new(
+            deployment,
+            "deployment",
+            "configured Azure deployment",
+        ));
+    }
+    match load_fresh_catalog(&cfg.paths.model_catalog_dir(), provider, now_unix_secs()) {
+        Some(live) => merge_model_picker_options(builtin, &live),
+        None => builtin,
+    }
+}
+
+pub fn format_catalog_listing(provider: Provider, listing: &CatalogListing) -> String {
+    let label = provider_label(provider);
+    match listing {
+        CatalogListing::Skipped(reason) | CatalogListing::Failed(reason) => {
+            let status = if matches!(listing, CatalogListing::Failed(_)) {
+                "failed: "
+            } else {
+                ""
+            };
+            format!("{label}: {status}{reason}\n")
+        }
+        CatalogListing::Listed { models, cached } => {
+            let mut out = format!(
+                "{label}: {} model(s){}\n",
+                models.len(),
+                if *cached { " (cached)" } else { "" }
+            );
+            for model in models {
+                let mut line = format!("  {}", model.id);
+                if let Some(tokens) = model.context_window {
+                    line.push_str(&format!("  ctx={}", format_context_window(tokens)));
+                }
+                if model.deprecated == Some(true) {
+                    line.push_str("  deprecated");
+                }
+                out.push_str(&line);
+                out.push('\n');
+            }
+            out
+        }
+    }
+}
+
+pub fn catalog_listing_json(provider: Provider, listing: &CatalogListing) -> Value {
+    let provider = provider_label(provider);
+    match listing {
+        CatalogListing::Listed { models, cached } => json!({
+            "provider": provider,
+            "status": "listed",
+            "cached": cached,
+            "models": models,
+        }),
+        CatalogListing::Skipped(note) => json!({
+            "provider": provider,
+            "status": "skipped",
+            "note": note,
+        }),
+        CatalogListing::Failed(error) => json!({
+            "provider": provider,
+            "status": "failed",
+            "error": error,
+        }),
+    }
+}
+
+fn now_unix_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+pub async fn run_models_list(
+    cfg: &RuntimeConfig,
+    provider: Option<Provider>,
+    json_output: bool,
+    refresh: bool,
+) -> Result<()> {
+    let providers = match provider {
+        Some(Provider::Auto | Provider::Fake) => anyhow::bail!(
+            "models list needs a concrete provider; use one of openai, anthropic, gemini, groq, \
+             deepseek, ollama, or omit --provider to list them all"
+        ),
+        Some(provider) => vec![provider],
+        None => CATALOG_PROVIDERS.to_vec(),
+    };
+    let cache_dir = cfg.paths.model_catalog_dir();
+    let now = now_unix_secs();
+    let mut listings = Vec::new();
+    for provider in providers {
+        let catalog = HttpModelCatalog::for_config(cfg, provider);
+        listings.push((
+            provider,
+            list_catalog_models(&catalog, &cache_dir, now, refresh).await,
+        ));
+    }
+    if json_output {
+        let value = listings
+            .iter()
+            .map(|(provider, listing)| catalog_listing_json(*provider, listing))
+            .collect::<Vec<Value>>();
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&value).context("failed to encode model listing")?
+        );
+    } else {
+        for (provider, listing) in &listings {
+            print!("{}", format_catalog_listing(*provider, listing));
+        }
+    }
+    Ok(())
+}
@@ -36,18 +36,114 @@ when their description matches the user's request.
 }
 
 use crate::chat::ModelPickerOption;
-use crate::cli::Provider;
-use crate::config::{ProfileConfig, load_profiles};
+use crate::cli::{Provider, SessionBackend};
+use crate::config::{ProfileConfig, load_profiles_raw};
 
 /// Captures the user's selections from the onboarding wizard.
 pub struct OnboardingResult {
     pub provider: Provider,
     pub model: String,
+    /// `None` when the key comes from the environment instead of the config.
     pub api_key: Option<String>,
     pub ollama_host: Option<String>,
+    pub session_backend: SessionBackend,
+    pub telemetry_enabled: bool,
     pub skipped: bool,
 }
 
+/// Terminal I/O for the wizard. Every step goes through this so tests can
+/// script the answers.
+pub trait OnboardingPrompter {
+    /// Print one line.
+    fn say(&mut self, line: &str);
+    /// Print `prompt` and read one line of input.
+    fn ask(&mut self, prompt: &str) -> Result<String>;
+    /// Like `ask`, but typed characters are masked.
+    fn ask_secret(&mut self, prompt: &str) -> Result<String>;
+}
+
+/// Prompter backed by stdin/stdout.
+pub struct TerminalPrompter;
+
+impl OnboardingPrompter for TerminalPrompter {
+    fn say(&mut self, line: &str) {
+        println!("{line}");
+    }
+
+    fn ask(&mut self, prompt: &str) -> Result<String> {
+        print!("{prompt}");
+        io::stdout().flush()?;
+        let mut line = String::new();
+        if io::stdin().lock().read_line(&mut line)? == 0 {
+            bail!("input closed before setup finished");
+        }
+        Ok(line)
+    }
+
+    fn ask_secret(&mut self, prompt: &str) -> Result<String> {
+        print!("{prompt}");
+        io::stdout().flush()?;
+        let key = read_masked_input()?;
+        println!();
+        Ok(key)
+    }
+}
+
+/// Environment variable that supplies credentials (or the host, for
+/// Ollama) for `provider`.
+pub fn provider_env_key(provider: Provider) -> Option<&'static str> {
+    match provider {
+        Provider::Openai => Some("OPENAI_API_KEY"),
+        Provider::Anthropic => Some("ANTHROPIC_API_KEY"),
+        Provider::Gemini => Some("GOOGLE_API_KEY"),
+        Provider::Deepseek => Some("DEEPSEEK_API_KEY"),
+        Provider::Groq => Some("GROQ_API_KEY"),
+        Provider::Ollama => Some("OLLAMA_HOST"),
+        Provider::AzureOpenai => Some("AZURE_OPENAI_API_KEY"),
+        Provider::OpenaiCompatible => Some("OPENAI_COMPATIBLE_API_KEY"),
+        Provider::Fake | Provider::Auto => None,
+    }
+}
+
+/// What `main` knows when deciding whether to offer the first-run wizard.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OnboardingTrigger {
+    pub stdin_is_terminal: bool,
+    /// `--no-onboarding` / `ZAVORA_NO_ONBOARDING`.
+    pub opted_out: bool,
+    /// `--provider`, `--model` or `--profile` was given explicitly.
+    pub explicit_selection: bool,
+    pub config_exists: bool,
+    pub credentials_found: bool,
+    /// The completion marker exists in the state dir.
+    pub completed: bool,
+}
+
+/// The wizard only runs on an interactive terminal with nothing configured
+/// yet; any config, credential, explicit selection or earlier run skips it.
+pub fn should_offer_onboarding(trigger: &OnboardingTrigger) -> bool {
+    trigger.stdin_is_terminal
+        && !trigger.opted_out
+        && !trigger.explicit_selection
+        && !trigger.config_exists
+        && !trigger.credentials_found
+        && !trigger.completed
+}
+
+/// Record that onboarding ran (or was skipped) so it never triggers again.
+pub fn record_onboarding_complete(marker: &Path, result: &OnboardingResult) -> Result<()> {
+    if let Some(parent) = marker.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    let body = serde_json::json!({
+        "completed_at": chrono::Utc::now().to_rfc3339(),
+        "skipped": result.skipped,
+    });
+    std::fs::write(marker, format!("{body}\n"))
+        .with_context(|| format!("failed to write {}", marker.display()))
+}
+
 /// Parses provider selection input.
 ///
 /// Returns `Ok(Some(Provider))` for valid numeric input 1–6,
@@ -71,8 +167,13 @@ pub fn parse_provider_selection(input: &str) -> Result<Option<Provider>> {
 ///
 /// Returns `Ok(Some(Provider))` when a valid provider is selected,
 /// or `Ok(None)` when the user chooses to skip.
-/// If `default` is `Some`, the corresponding provider is marked with `[current]`.
-pub fn prompt_provider_selection(default: Option<Provider>) -> Result<Option<Provider>> {
+/// If `default` is `Some`, the corresponding provider is marked with `[current]`;
+/// providers whose environment variable is set are marked too.
+pub fn prompt_provider_selection(
+    prompter: &mut dyn OnboardingPrompter,
+    default: Option<Provider>,
+    key_present: &dyn Fn(&str) -> bool,
+) -> Result<Option<Provider>> {
     let providers: &[(Provider, &str)] = &[
         (Provider::Openai, "OpenAI"),
         (Provider::Anthropic, "Anthropic"),
@@ -82,32 +183,29 @@ pub fn prompt_provider_selection(default: Option<Provider>) -> Result<Option<Pro
         (Provider::Ollama, "Ollama (local)"),
     ];
 
-    let stdin = io::stdin();
-    let mut reader = stdin.lock();
-
     loop {
-        println!("Select your AI provider:");
+        prompter.say("Select your AI provider:");
         for (i, (provider, label)) in providers.iter().enumerate() {
-            let marker = if default == Some(*provider) {
-                " [current]"
-            } else {
-                ""
-            };
-            println!("  {}. {}{}", i + 1, label, marker);
+            let mut marker = String::new();
+            if default == Some(*provider) {
+                marker.push_str(" [current]");
+            }
+            if let Some(key) = provider_env_key(*provider)
+                && key_present(key)
+            {
+                marker.push_str(&format!(" [{key} set]"));
+            }
+            prompter.say(&format!("  {}. {}{}", i + 1, label, marker));
         }
-        println!("  s. Skip setup");
-        println!();
-        print!("Enter selection [1-6, s]: ");
-        io::stdout().flush()?;
-
-        let mut line = String::new();
-        reader.read_line(&mut line)?;
+        prompter.say("  s. Skip setup");
+        prompter.say("");
 
+        let line = prompter.ask("Enter selection [1-6, s]: ")?;
         match parse_provider_selection(&line) {
             Ok(result) => return Ok(result),
             Err(e) => {
-                println!("{}", e);
-                println!();
+                prompter.say(&e.to_string());
+                prompter.say("");
             }
         }
     }
@@ -143,7 +241,11 @@ pub fn parse_model_selection(input: &str, options: &[ModelPickerOption]) -> Resu
 /// Returns the model ID string for the selected model.
 /// If the user presses Enter without input, returns the default model
 /// (the one matching `default_model`, or the first model in the list).
-pub fn prompt_model_selection(provider: Provider, default_model: Option<&str>) -> Result<String> {
+pub fn prompt_model_selection(
+    prompter: &mut dyn OnboardingPrompter,
+    provider: Provider,
+    default_model: Option<&str>,
+) -> Result<String> {
     let options = crate::chat::model_picker_options(provider);
     if options.is_empty() {
         bail!("No models available for provider {:?}.", provider);
@@ -160,41 +262,37 @@ pub fn prompt_model_selection(provider: Provider, default_model: Option<&str>) -
         Provider::Deepseek => "DeepSeek",
         Provider::Groq => "Groq",
         Provider::Ollama => "Ollama",
+        Provider::AzureOpenai => "Azure OpenAI",
+        Provider::OpenaiCompatible => "OpenAI-compatible",
+        Provider::Fake => "Fake (scripted)",
         Provider::Auto => "Auto",
     };
 
-    let stdin = io::stdin();
-    let mut reader = stdin.lock();
-
     loop {
-        println!("Select a model for {}:", provider_name);
+        prompter.say(&format!("Select a model for {}:", provider_name));
         for (i, option) in options.iter().enumerate() {
             let marker = if i == default_index { " [default]" } else { "" };
-            println!(
+            prompter.say(&format!(
                 "  {}. {} (ctx={}, {}){}",
                 i + 1,
                 option.id,
                 option.context_window,
                 option.description,
                 marker,
-            );
+            ));
         }
-        println!();
-        print!(
+        prompter.say("");
+        let line = prompter.ask(&format!(
             "Enter selection [1-{}] or press Enter for default: ",
             options.len()
-        );
-        io::stdout().flush()?;
-
-        let mut line = String::new();
-        reader.read_line(&mut line)?;
+        ))?;
 
         match parse_model_selection(&line, &options) {
             Ok(None) => return Ok(options[default_index].id.to_string()),
             Ok(Some(idx)) => return Ok(options[idx].id.to_string()),
             Err(e) => {
-                println!("{}", e);
-                println!();
+                prompter.say(&e.to_string());
+                prompter.say("");
             }
         }
     }
@@ -210,7 +308,7 @@ pub fn validate_api_key(input: &str) -> bool {
 /// Characters are displayed as `*` while typing. Backspace removes the last
 /// character. Enter submits the key (must be non-empty). Ctrl+C cancels.
 /// Raw mode is always disabled before returning, even on error.
-pub fn prompt_api_key(provider: Provider) -> Result<String> {
+pub fn prompt_api_key(prompter: &mut dyn OnboardingPrompter, provider: Provider) -> Result<String> {
     let provider_name = match provider {
         Provider::Openai => "OpenAI",
         Provider::Anthropic => "Anthropic",
@@ -218,39 +316,28 @@ pub fn prompt_api_key(provider: Provider) -> Result<String> {
         Provider::Deepseek => "DeepSeek",
         Provider::Groq => "Groq",
         Provider::Ollama => "Ollama",
+        Provider::AzureOpenai => "Azure OpenAI",
+        Provider::OpenaiCompatible => "OpenAI-compatible",
+        Provider::Fake => "Fake (scripted)",
         Provider::Auto => "Auto",
     };
 
     loop {
-        print!("Enter your API key for {}: ", provider_name);
-        io::stdout().flush()?;
-
-        let key = read_masked_input()?;
-
-        println!();
-
+        let key = prompter.ask_secret(&format!("Enter your API key for {}: ", provider_name))?;
         if validate_api_key(&key) {
             return Ok(key);
         }
 
-        println!("API key cannot be empty. Please enter your key.");
-        println!();
+        prompter.say("API key cannot be empty. Please enter your key.");
+        prompter.say("");
     }
 }
 /// Prompts the user for the Ollama host URL with a default value.
 ///
 /// Displays the default URL and returns it if the user presses Enter
 /// without typing anything. Otherwise returns the trimmed input.
-pub fn prompt_ollama_host(default: &str) -> Result<String> {
-    let stdin = io::stdin();
-    let mut reader = stdin.lock();
-
-    print!("Enter Ollama host URL [{}]: ", default);
-    io::stdout().flush()?;
-
-    let mut line = String::new();
-    reader.read_line(&mut line)?;
-
+pub fn prompt_ollama_host(prompter: &mut dyn OnboardingPrompter, default: &str) -> Result<String> {
+    let line = prompter.ask(&format!("Enter Ollama host URL [{}]: ", default))?;
     let trimmed = line.trim();
     if trimmed.is_empty() {
         Ok(default.to_string())
@@ -259,6 +346,69 @@ pub fn prompt_ollama_host(default: &str) -> Result<String> {
     }
 }
 
+/// Parses session backend input: empty keeps the default, `1` is memory,
+/// `2` is sqlite.
+pub fn parse_session_backend_selection(input: &str) -> Result<Option<SessionBackend>> {
+    match input.trim() {
+        "" => Ok(None),
+        "1" => Ok(Some(SessionBackend::Memory)),
+        "2" => Ok(Some(SessionBackend::Sqlite)),
+        other => bail!("Invalid selection '{}'. Please enter 1 or 2.", other),
+    }
+}
+
+/// Asks where chat sessions live. The sqlite database itself is created by
+/// the `doctor` run that ends the first-run wizard.
+pub fn prompt_session_backend(
+    prompter: &mut dyn OnboardingPrompter,
+    default: SessionBackend,
+) -> Result<SessionBackend> {
+    let options = [
+        (SessionBackend::Memory, "memory (sessions end with the process)"),
+        (SessionBackend::Sqlite, "sqlite (sessions persist across runs)"),
+    ];
+    loop {
+        prompter.say("Where should sessions be stored?");
+        for (i, (backend, label)) in options.iter().enumerate() {
+            let marker = if *backend == default { " [default]" } else { "" };
+            prompter.say(&format!("  {}. {}{}", i + 1, label, marker));
+        }
+        prompter.say("");
+        let line = prompter.ask("Enter selection [1-2] or press Enter for default: ")?;
+        match parse_session_backend_selection(&line) {
+            Ok(selection) => return Ok(selection.unwrap_or(default)),
+            Err(e) => {
+                prompter.say(&e.to_string());
+                prompter.say("");
+            }
+        }
+    }
+}
+
+/// Parses a yes/no answer; empty input takes `default`.
+pub fn parse_yes_no(input: &str, default: bool) -> Option<bool> {
+    match input.trim().to_lowercase().as_str() {
+        "" => Some(default),
+        "y" | "yes" => Some(true),
+        "n" | "no" => Some(false),
+        _ => None,
+    }
+}
+
+fn prompt_yes_no(
+    prompter: &mut dyn OnboardingPrompter,
+    question: &str,
+    default: bool,
+) -> Result<bool> {
+    let hint = if default { "[Y/n]" } else { "[y/N]" };
+    loop {
+        let line = prompter.ask(&format!("{question} {hint}: "))?;
+        match parse_yes_no(&line, default) {
+            Some(answer) => return Ok(answer),
+            None => prompter.say("Please answer 'y' or 'n'."),
+        }
+    }
+}
 
 /// Reads a line of input with characters masked as `*`.
 ///
@@ -333,6 +483,9 @@ pub fn format_summary(result: &OnboardingResult) -> String {
         Provider::Deepseek => "DeepSeek",
         Provider::Groq => "Groq",
         Provider::Ollama => "Ollama",
+        Provider::AzureOpenai => "Azure OpenAI",
+        Provider::OpenaiCompatible => "OpenAI-compatible",
+        Provider::Fake => "Fake (scripted)",
         Provider::Auto => "Auto",
     };
 
@@ -342,34 +495,52 @@ pub fn format_summary(result: &OnboardingResult) -> String {
     lines.push(format!("  Model:    {}", result.model));
 
     if result.provider == Provider::Ollama {
-        let host = result
-            .ollama_host
-            .as_deref()
-            .unwrap_or("http://localhost:11434");
-        lines.push(format!("  Host:     {}", host));
+        match result.ollama_host.as_deref() {
+            Some(host) => lines.push(format!("  Host:     {}", host)),
+            None => lines.push("  Host:     from OLLAMA_HOST".to_string()),
+        }
     } else if let Some(ref key) = result.api_key {
         lines.push(format!("  API Key:  {}", mask_api_key(key)));
+    } else if let Some(key) = provider_env_key(result.provider) {
+        lines.push(format!("  API Key:  from {}", key));
     }
+    let sessions = match result.session_backend {
+        SessionBackend::Memory => "memory",
+        SessionBackend::Sqlite => "sqlite",
+    };
+    lines.push(format!("  Sessions: {}", sessions));
+    let telemetry = if result.telemetry_enabled { "on" } else { "off" };
+    lines.push(format!("  Telemetry: {}", telemetry));
 
     lines.join("\n")
 }
 
 /// Runs the interactive onboarding wizard.
 ///
-/// Orchestrates the full flow: provider → credential → model → summary → confirm.
+/// Orchestrates the full flow: provider → credential → model → sessions →
+/// telemetry → summary → confirm. A credential whose environment variable is
+/// already set (per `key_present`) is not asked for or written to the config.
 /// If `existing` is `Some`, pre-populates selections for re-run via `setup` command.
 /// Returns `OnboardingResult` with `skipped=true` if the user chooses to skip.
-pub fn run_onboarding_wizard(existing: Option<&ProfileConfig>) -> Result<OnboardingResult> {
-    println!();
-    println!("Welcome to zavora! Let's set up your AI provider.");
-    println!();
+pub fn run_onboarding_wizard(
+    prompter: &mut dyn OnboardingPrompter,
+    existing: Option<&ProfileConfig>,
+    key_present: &dyn Fn(&str) -> bool,
+) -> Result<OnboardingResult> {
+    prompter.say("");
+    prompter.say("Welcome to zavora! Let's set up your AI provider.");
+    prompter.say("");
 
     let default_provider = existing.and_then(|p| p.provider);
     let default_model = existing.and_then(|p| p.model.clone());
+    let default_backend = existing
+        .and_then(|p| p.session_backend)
+        .unwrap_or(SessionBackend::Memory);
+    let default_telemetry = existing.and_then(|p| p.telemetry_enabled).unwrap_or(true);
 
     loop {
         // Step 1: Provider selection
-        let provider = match prompt_provider_selection(default_provider)? {
+        let provider = match prompt_provider_selection(prompter, default_provider, key_present)? {
             Some(p) => p,
             None => {
                 // User chose to skip
@@ -378,52 +549,59 @@ pub fn run_onboarding_wizard(existing: Option<&ProfileConfig>) -> Result<Onboard
                     model: String::new(),
                     api_key: None,
                     ollama_host: None,
+                    session_backend: default_backend,
+                    telemetry_enabled: default_telemetry,
                     skipped: true,
                 });
             }
         };
 
-        // Step 2: Credential (API key or Ollama host)
-        let (api_key, ollama_host) = if provider == Provider::Ollama {
-            let host = prompt_ollama_host("http://localhost:11434")?;
+        // Step 2: Credential (API key or Ollama host), unless the environment has it
+        let env_key = provider_env_key(provider).filter(|key| key_present(key));
+        let (api_key, ollama_host) = if let Some(key) = env_key {
+            prompter.say(&format!("Using {} from the environment.", key));
+            (None, None)
+        } else if provider == Provider::Ollama {
+            let host = prompt_ollama_host(prompter, "http://localhost:11434")?;
             (None, Some(host))
         } else {
-            let key = prompt_api_key(provider)?;
+            let key = prompt_api_key(prompter, provider)?;
             (Some(key), None)
         };
 
         // Step 3: Model selection
-        let model = prompt_model_selection(provider, default_model.as_deref())?;
+        let model = prompt_model_selection(prompter, provider, default_model.as_deref())?;
+
+        // Step 4: Sessions and telemetry
+        let session_backend = prompt_session_backend(prompter, default_backend)?;
+        let telemetry_enabled = prompt_yes_no(
+            prompter,
+            "Record local usage telemetry (a JSONL file in the state dir)?",
+            default_telemetry,
+        )?;
 
-        // Step 4: Build result and show summary
+        // Step 5: Build result and show summary
         let result = OnboardingResult {
             provider,
             model,
             api_key,
             ollama_host,
+            session_backend,
+            telemetry_enabled,
             skipped: false,
         };
 
-        println!();
-        println!("{}", format_summary(&result));
-        println!();
-
-        // Step 5: Confirm
-        print!("Save this configuration? [Y/n]: ");
-        io::stdout().flush()?;
+        prompter.say("");
+        prompter.say(&format_summary(&result));
+        prompter.say("");
 
-        let stdin = io::stdin();
-        let mut reader = stdin.lock();
-        let mut line = String::new();
-        reader.read_line(&mut line)?;
-
-        let answer = line.trim().to_lowercase();
-        if answer.is_empty() || answer == "y" || answer == "yes" {
+        // Step 6: Confirm
+        if prompt_yes_no(prompter, "Save this configuration?", true)? {
             return Ok(result);
         }
 
-        println!("No problem, let's start over.");
-        println!();
+        prompter.say("No problem, let's start over.");
+        prompter.say("");
     }
 }
 
@@ -444,8 +622,9 @@ pub fn persist_onboarding_config(result: &OnboardingResult, config_path: &str) -
         })?;
     }
 
-    // Load existing profiles to preserve other entries.
-    let mut profiles_file = load_profiles(config_path).unwrap_or_default();
+    // Load existing profiles to preserve other entries. Read the file as
+    // written so includes and `${ENV_VAR}` references survive the rewrite.
+    let mut profiles_file = load_profiles_raw(config_path).unwrap_or_default();
 
     // Get or create the "default" profile.
     let profile = profiles_file
@@ -463,6 +642,8 @@ pub fn persist_onboarding_config(result: &OnboardingResult, config_path: &str) -
         profile.model = Some(result.model.clone());
         profile.api_key = result.api_key.clone();
         profile.ollama_host = result.ollama_host.clone();
+        profile.session_backend = Some(result.session_backend);
+        profile.telemetry_enabled = Some(result.telemetry_enabled);
     }
 
     let toml_str = toml::to_string_pretty(&profiles_file)
@@ -590,6 +771,8 @@ mod tests {
             model: "gpt-4.1".to_string(),
             api_key: Some("sk-abcdefghijklmnop".to_string()),
             ollama_host: None,
+            session_backend: SessionBackend::Memory,
+            telemetry_enabled: false,
             skipped: false,
         };
         let summary = format_summary(&result);
@@ -606,6 +789,8 @@ mod tests {
             model: "llama4".to_string(),
             api_key: None,
             ollama_host: Some("http://localhost:11434".to_string()),
+            session_backend: SessionBackend::Memory,
+            telemetry_enabled: false,
             skipped: false,
         };
         let summary = format_summary(&result);
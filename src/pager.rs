@@ -0,0 +1,140 @@
+/// Pager integration for long non-streaming output (`ask`, `sessions show`).
+///
+/// Output is paged only when paging is enabled, stdout is a TTY and the text
+/// is taller than the terminal. If the pager cannot be started the text is
+/// printed instead; once it has started, write and exit failures are only
+/// logged so the text is never shown twice.
+use std::io::{self, IsTerminal, Write};
+use std::process::{Command, Stdio};
+
+use anyhow::{Context, Result};
+
+use crate::config::RuntimeConfig;
+
+pub const DEFAULT_PAGER: &str = "less -R";
+
+/// Where a piece of output should go.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PagerDecision {
+    Print,
+    Page(String),
+}
+
+/// Receives the text to page. Implemented by [`CommandPager`]; tests inject
+/// a recording fake. An error means the pager never started and the text was
+/// not shown, so the caller prints it instead.
+pub trait Pager {
+    fn page(&mut self, command: &str, text: &str) -> Result<()>;
+}
+
+/// Spawns the pager command, writes the text to its stdin and waits for it.
+pub struct CommandPager;
+
+impl Pager for CommandPager {
+    fn page(&mut self, command: &str, text: &str) -> Result<()> {
+        let argv = shlex::split(command)
+            .filter(|argv| !argv.is_empty())
+            .with_context(|| format!("invalid pager command '{command}'"))?;
+        let mut child = Command::new(&argv[0])
+            .args(&argv[1..])
+            .stdin(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("failed to spawn pager '{command}'"))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            // The user may quit the pager before reading everything; a broken
+            // pipe here is expected. Other write errors are logged, and the
+            // pager is still waited for below.
+            if let Err(err) = stdin.write_all(text.as_bytes())
+                && err.kind() != io::ErrorKind::BrokenPipe
+            {
+                tracing::warn!(pager = command, error = %err, "Failed to write to pager");
+            }
+        }
+        match child.wait() {
+            Ok(status) if !status.success() => {
+                tracing::debug!(pager = command, status = %status, "Pager exited unsuccessfully");
+            }
+            Ok(_) => {}
+            Err(err) => {
+                tracing::warn!(pager = command, error = %err, "Failed to wait for pager");
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Pager command from `$PAGER`, defaulting to `less -R`.
+pub fn pager_command(pager_env: Option<String>) -> String {
+    pager_env
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+        .unwrap_or_else(|| DEFAULT_PAGER.to_string())
+}
+
+/// Rows `text` occupies once long lines wrap at `width` columns.
+pub fn estimated_rows(text: &str, width: usize) -> usize {
+    let width = width.max(1);
+    text.lines()
+        .map(|line| line.chars().count().div_ceil(width).max(1))
+        .sum()
+}
+
+pub fn pager_decision(
+    enabled: bool,
+    is_tty: bool,
+    terminal_size: Option<(usize, usize)>,
+    text: &str,
+    pager_env: Option<String>,
+) -> PagerDecision {
+    if !enabled || !is_tty {
+        return PagerDecision::Print;
+    }
+    let Some((width, height)) = terminal_size else {
+        return PagerDecision::Print;
+    };
+    if estimated_rows(text, width) < height {
+        return PagerDecision::Print;
+    }
+    PagerDecision::Page(pager_command(pager_env))
+}
+
+/// Send `text` to `pager` when `decision` says so, otherwise (or when the
+/// pager could not be started) write it to `out`. Returns whether the pager
+/// showed it.
+pub fn emit_paged(
+    decision: &PagerDecision,
+    text: &str,
+    pager: &mut dyn Pager,
+    out: &mut dyn Write,
+) -> Result<bool> {
+    if let PagerDecision::Page(command) = decision {
+        match pager.page(command, text) {
+            Ok(()) => return Ok(true),
+            Err(err) => {
+                tracing::warn!(error = %err, "Pager unavailable; printing output directly");
+            }
+        }
+    }
+    out.write_all(text.as_bytes())
+        .context("failed to write output")?;
+    out.flush().context("failed to flush output")?;
+    Ok(false)
+}
+
+/// Print `text` (which should end with a newline), paging it when stdout is
+/// a TTY and it does not fit on screen.
+pub fn page_or_print(cfg: &RuntimeConfig, text: &str) -> Result<()> {
+    let stdout = io::stdout();
+    let terminal_size = crossterm::terminal::size()
+        .ok()
+        .map(|(width, height)| (width as usize, height as usize));
+    let decision = pager_decision(
+        cfg.pager,
+        stdout.is_terminal(),
+        terminal_size,
+        text,
+        std::env::var("PAGER").ok(),
+    );
+    emit_paged(&decision, text, &mut CommandPager, &mut stdout.lock())?;
+    Ok(())
+}
@@ -0,0 +1,265 @@
+//! `/find` command palette: one ranked search over slash commands, tools,
+//! agents and MCP servers.
+//!
+//! The index is collected once when chat starts and rebuilt after `/provider`
+//! or `/model` switches, so tool gates reflect the config in use.
+use std::collections::HashMap;
+
+use crate::config::{ResolvedAgent, RuntimeConfig};
+use crate::runner::ResolvedRuntimeTools;
+use crate::theme::COMMAND_PALETTE;
+use crate::tool_policy::{ToolConfirmation, ToolOrigin, ToolPolicy};
+
+pub const PALETTE_MAX_RESULTS: usize = 10;
+
+/// How a query matched a candidate; later variants rank higher.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FuzzyMatch {
+    /// Query characters appear in order, with gaps.
+    Subsequence,
+    Substring,
+    Prefix,
+}
+
+/// Case-insensitive prefix > substring > subsequence match of `query` in
+/// `candidate`. An empty query matches nothing.
+pub fn fuzzy_match(query: &str, candidate: &str) -> Option<FuzzyMatch> {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return None;
+    }
+    let candidate = candidate.to_lowercase();
+    if candidate.starts_with(&query) {
+        return Some(FuzzyMatch::Prefix);
+    }
+    if candidate.contains(&query) {
+        return Some(FuzzyMatch::Substring);
+    }
+    let mut rest = candidate.chars();
+    query
+        .chars()
+        .all(|wanted| rest.any(|c| c == wanted))
+        .then_some(FuzzyMatch::Subsequence)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PaletteCategory {
+    Command,
+    Tool,
+    Agent,
+    McpServer,
+}
+
+impl PaletteCategory {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Command => "command",
+            Self::Tool => "tool",
+            Self::Agent => "agent",
+            Self::McpServer => "mcp",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaletteEntry {
+    pub category: PaletteCategory,
+    pub name: String,
+    pub description: String,
+    /// How to use the hit, e.g. `gate: confirm`.
+    pub hint: String,
+}
+
+impl PaletteEntry {
+    /// Rank against `query`: name prefix, name substring, description
+    /// substring, then name subsequence. `None` when nothing matches.
+    fn score(&self, query: &str) -> Option<u8> {
+        let name = self.name.trim_start_matches('/');
+        let name_score = match fuzzy_match(query, name) {
+            Some(FuzzyMatch::Prefix) => Some(4),
+            Some(FuzzyMatch::Substring) => Some(3),
+            Some(FuzzyMatch::Subsequence) => Some(1),
+            None => None,
+        };
+        let description_score = matches!(
+            fuzzy_match(query, &self.description),
+            Some(FuzzyMatch::Prefix | FuzzyMatch::Substring)
+        )
+        .then_some(2);
+        name_score.max(description_score)
+    }
+}
+
+fn first_line(text: &str) -> String {
+    text.lines().next().unwrap_or_default().trim().to_string()
+}
+
+/// `(name, description)` for every catalog agent, sorted by name.
+pub fn palette_agents(agents: &HashMap<String, ResolvedAgent>) -> Vec<(String, String)> {
+    let mut out = agents
+        .values()
+        .map(|agent| {
+            (
+                agent.name.clone(),
+                first_line(agent.config.description.as_deref().unwrap_or_default()),
+            )
+        })
+        .collect::<Vec<(String, String)>>();
+    out.sort();
+    out
+}
+
+fn gate_label(gate: ToolConfirmation) -> &'static str {
+    match gate {
+        ToolConfirmation::Auto => "none",
+        ToolConfirmation::DisplayOnly => "display",
+        ToolConfirmation::Confirm => "confirm",
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PaletteIndex {
+    agents: Vec<(String, String)>,
+    entries: Vec<PaletteEntry>,
+}
+
+impl PaletteIndex {
+    /// Collect every searchable item; `agents` come from the resolved catalog.
+    pub fn build(
+        cfg: &RuntimeConfig,
+        runtime_tools: &ResolvedRuntimeTools,
+        agents: Vec<(String, String)>,
+    ) -> Self {
+        let mut index = Self {
+            agents,
+            entries: Vec::new(),
+        };
+        index.refresh(cfg, runtime_tools);
+        index
+    }
+
+    /// Rebuild from the current config and tools, keeping the agent list.
+    pub fn refresh(&mut self, cfg: &RuntimeConfig, runtime_tools: &ResolvedRuntimeTools) {
+        let mut entries = COMMAND_PALETTE
+            .iter()
+            .map(|(name, description)| PaletteEntry {
+                category: PaletteCategory::Command,
+                name: format!("/{name}"),
+                description: description.to_string(),
+                hint: format!("type /{name}"),
+            })
+            .collect::<Vec<PaletteEntry>>();
+
+        let policy = ToolPolicy::from_config(cfg);
+        for tool in &runtime_tools.tools {
+            let name = tool.name();
+            let mcp = runtime_tools.mcp_tool_names.contains(name);
+            let origin = if mcp {
+                ToolOrigin::Mcp
+            } else {
+                ToolOrigin::BuiltIn
+            };
+            let mut hint = format!("gate: {}", gate_label(policy.confirmation(name, origin)));
+            if let Some(info) = runtime_tools
+                .mcp_tools
+                .iter()
+                .find(|info| info.name == name)
+            {
+                hint.push_str(&format!("; from MCP server {}", info.server));
+            }
+            entries.push(PaletteEntry {
+                category: PaletteCategory::Tool,
+                name: name.to_string(),
+                description: first_line(tool.description()),
+                hint,
+            });
+        }
+
+        for (name, description) in &self.agents {
+            let hint = if *name == cfg.agent_name {
+                "active agent".to_string()
+            } else {
+                format!("select: zavora-cli agents select --name {name}")
+            };
+            entries.push(PaletteEntry {
+                category: PaletteCategory::Agent,
+                name: name.clone(),
+                description: description.clone(),
+                hint,
+            });
+        }
+
+        for server in cfg
+            .mcp_servers
+            .iter()
+            .filter(|server| server.enabled.unwrap_or(true))
+        {
+            let tool_count = runtime_tools
+                .mcp_tools
+                .iter()
+                .filter(|info| info.server == server.name)
+                .count();
+            let target = match &server.command {
+                Some(command) if server.endpoint.is_empty() => command,
+                _ => &server.endpoint,
+            };
+            entries.push(PaletteEntry {
+                category: PaletteCategory::McpServer,
+                name: server.name.clone(),
+                description: format!("MCP server at {target}"),
+                hint: format!("{tool_count} tools; details: /mcp {}", server.name),
+            });
+        }
+        self.entries = entries;
+    }
+
+    pub fn entries(&self) -> &[PaletteEntry] {
+        &self.entries
+    }
+
+    /// Best `limit` matches for `query`. Ties go to the shorter name, then
+    /// category (commands, tools, agents, MCP servers), then name.
+    pub fn search(&self, query: &str, limit: usize) -> Vec<&PaletteEntry> {
+        let query = query.trim().trim_start_matches('/');
+        let mut hits = self
+            .entries
+            .iter()
+            .filter_map(|entry| entry.score(query).map(|score| (score, entry)))
+            .collect::<Vec<(u8, &PaletteEntry)>>();
+        hits.sort_by(|(left_score, left), (right_score, right)| {
+            right_score
+                .cmp(left_score)
+                .then_with(|| left.name.len().cmp(&right.name.len()))
+                .then_with(|| left.category.cmp(&right.category))
+                .then_with(|| left.name.cmp(&right.name))
+        });
+        hits.into_iter()
+            .take(limit)
+            .map(|(_, entry)| entry)
+            .collect()
+    }
+}
+
+/// One `/find` result line: `tool fs_read — read files; gate: none`.
+pub fn format_palette_hit(entry: &PaletteEntry) -> String {
+    if entry.description.is_empty() {
+        format!("{} {}; {}", entry.category.label(), entry.name, entry.hint)
+    } else {
+        format!(
+            "{} {} — {}; {}",
+            entry.category.label(),
+            entry.name,
+            entry.description,
+            entry.hint
+        )
+    }
+}
+
+pub fn format_palette_results(query: &str, hits: &[&PaletteEntry]) -> String {
+    if hits.is_empty() {
+        return format!("No matches for '{}'.\n", query.trim());
+    }
+    hits.iter()
+        .map(|entry| format!("  {}\n", format_palette_hit(entry)))
+        .collect()
+}
@@ -0,0 +1,280 @@
+//! Where zavora keeps its files.
+//!
+//! Workspace-scoped files (local agents, agent selection, sessions db, idle
+//! transcripts) live in `.zavora/` under the cwd. Global config (the user
+//! agents catalog) and state (telemetry, eval reports, usage counters) go to
+//! `--config-dir` / `ZAVORA_HOME` when set, otherwise to
+//! `$XDG_CONFIG_HOME/zavora` and `$XDG_STATE_HOME/zavora` (falling back to
+//! `~/.config` and `~/.local/state`), and to `.zavora/` without a home dir.
+use std::path::{Path, PathBuf};
+
+pub const WORKSPACE_DIR: &str = ".zavora";
+const APP_DIR: &str = "zavora";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathSource {
+    /// `--config-dir` / `ZAVORA_HOME`.
+    Override,
+    Xdg,
+    /// No home directory: everything stays in the workspace.
+    Workspace,
+}
+
+impl PathSource {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Override => "override",
+            Self::Xdg => "xdg",
+            Self::Workspace => "workspace",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ZavoraPaths {
+    pub workspace_dir: PathBuf,
+    pub config_dir: PathBuf,
+    pub state_dir: PathBuf,
+    pub source: PathSource,
+    /// Pre-XDG `~/.zavora`, still read for the global agents catalog.
+    pub legacy_home_dir: Option<PathBuf>,
+}
+
+impl ZavoraPaths {
+    /// Precedence: `config_dir_override` (the CLI flag, which clap already
+    /// fills from `ZAVORA_HOME`), then XDG variables, then `$HOME`.
+    pub fn resolve(
+        config_dir_override: Option<&str>,
+        env: impl Fn(&str) -> Option<String>,
+    ) -> Self {
+        let non_empty = |key: &str| env(key).filter(|value| !value.trim().is_empty());
+        if let Some(dir) = config_dir_override
+            .map(str::trim)
+            .filter(|dir| !dir.is_empty())
+        {
+            return Self::under(dir);
+        }
+
+        let home = non_empty("HOME").map(PathBuf::from);
+        let xdg_dir = |key: &str, fallback: &[&str]| {
+            non_empty(key).map(PathBuf::from).or_else(|| {
+                home.as_ref().map(|home| {
+                    fallback
+                        .iter()
+                        .fold(home.clone(), |dir, part| dir.join(part))
+                })
+            })
+        };
+        match (
+            xdg_dir("XDG_CONFIG_HOME", &[".config"]),
+            xdg_dir("XDG_STATE_HOME", &[".local", "state"]),
+        ) {
+            (Some(config_home), Some(state_home)) => Self {
+                workspace_dir: PathBuf::from(WORKSPACE_DIR),
+                config_dir: config_home.join(APP_DIR),
+                state_dir: state_home.join(APP_DIR),
+                source: PathSource::Xdg,
+                legacy_home_dir: home.map(|home| home.join(WORKSPACE_DIR)),
+            },
+            _ => Self {
+                source: PathSource::Workspace,
+                ..Self::under(WORKSPACE_DIR)
+            },
+        }
+    }
+
+    pub fn from_env(config_dir_override: Option<&str>) -> Self {
+        Self::resolve(config_dir_override, |key| std::env::var(key).ok())
+    }
+
+    /// Config and state both under `dir`, with the workspace in `.zavora/`.
+    pub fn under(dir: impl AsRef<Path>) -> Self {
+        Self {
+            workspace_dir: PathBuf::from(WORKSPACE_DIR),
+            config_dir: dir.as_ref().to_path_buf(),
+            state_dir: dir.as_ref().to_path_buf(),
+            source: PathSource::Override,
+            legacy_home_dir: None,
+        }
+    }
+
+    pub fn local_agents_catalog(&self) -> PathBuf {
+        self.workspace_dir.join("agents.toml")
+    }
+
+    pub fn agent_selection_file(&self) -> PathBuf {
+        self.workspace_dir.join("agent-selection.toml")
+    }
+
+    pub fn profile_selection_file(&self) -> PathBuf {
+        self.workspace_dir.join("profile-selection.toml")
+    }
+
+    /// `<config>/agents.toml`, or the legacy `~/.zavora/agents.toml` when
+    /// only that one exists.
+    pub fn global_agents_catalog(&self) -> PathBuf {
+        let current = self.config_dir.join("agents.toml");
+        match &self.legacy_home_dir {
+            Some(legacy) if !current.exists() && legacy.join("agents.toml").exists() => {
+                legacy.join("agents.toml")
+            }
+            _ => current,
+        }
+    }
+
+    /// Command aliases (`alias set`), global like the agents catalog.
+    pub fn aliases_file(&self) -> PathBuf {
+        self.config_dir.join("aliases.toml")
+    }
+
+    /// `ask --cache` entries, kept with the workspace they were asked in.
+    pub fn answer_cache_dir(&self) -> PathBuf {
+        self.workspace_dir.join("cache").join("answers")
+    }
+
+    pub fn sessions_db_url(&self) -> String {
+        sqlite_url(&self.workspace_dir.join("sessions.db"))
+    }
+
+    pub fn idle_transcript_dir(&self) -> PathBuf {
+        self.workspace_dir.join("transcripts")
+    }
+
+    pub fn idle_snapshot_db_url(&self) -> String {
+        sqlite_url(&self.workspace_dir.join("idle-snapshot.db"))
+    }
+
+    pub fn telemetry_path(&self) -> PathBuf {
+        self.state_dir.join("telemetry").join("events.jsonl")
+    }
+
+    pub fn eval_output_path(&self) -> PathBuf {
+        self.state_dir.join("evals").join("latest.json")
+    }
+
+    pub fn usage_dir(&self) -> PathBuf {
+        self.state_dir.join("usage")
+    }
+
+    /// Cached result of the daily release check (`update_check`).
+    pub fn update_check_file(&self) -> PathBuf {
+        self.state_dir.join("update-check.json")
+    }
+
+    /// Cached `models list` results, one file per provider.
+    pub fn model_catalog_dir(&self) -> PathBuf {
+        self.state_dir.join("models")
+    }
+
+    /// Full tool results that exceeded `tool_output_max_chars`.
+    pub fn tool_output_dir(&self) -> PathBuf {
+        self.workspace_dir.join("tool-output")
+    }
+
+    /// Written once the first-run wizard finishes or is skipped.
+    pub fn onboarding_marker_file(&self) -> PathBuf {
+        self.state_dir.join("onboarding.json")
+    }
+
+    /// Long-term memory facts (`memory_backend = local`).
+    pub fn memory_facts_file(&self) -> PathBuf {
+        self.workspace_dir.join("memory").join("facts.jsonl")
+    }
+
+    /// SQLite database behind the `/memory` commands.
+    pub fn memory_db_url(&self) -> String {
+        sqlite_url(&self.workspace_dir.join("memory.db"))
+    }
+
+    /// Snapshots taken before `fs_write`/`file_edit`, for `/undo`.
+    pub fn file_history_dir(&self) -> PathBuf {
+        self.workspace_dir.join("file_history")
+    }
+
+    /// MCP OAuth tokens when the OS keychain is unavailable.
+    pub fn mcp_tokens_dir(&self) -> PathBuf {
+        self.workspace_dir.join("tokens")
+    }
+
+    /// Language server config written by `lsp-init`.
+    pub fn lsp_config_file(&self) -> PathBuf {
+        self.workspace_dir.join("lsp.json")
+    }
+
+    /// Trusted workspace roots and their config hashes (`zavora-cli trust`).
+    pub fn trust_store_file(&self) -> PathBuf {
+        self.state_dir.join("trusted-workspaces.json")
+    }
+
+    /// MCP circuit breaker state per server.
+    pub fn mcp_breaker_file(&self) -> PathBuf {
+        self.state_dir.join("mcp-breakers.json")
+    }
+
+    /// `/checkpoint` snapshots for the workspace.
+    pub fn checkpoints_file(&self) -> PathBuf {
+        self.workspace_dir.join("checkpoints.json")
+    }
+
+    /// Copies of the profiles file made by `config set`/`config unset`.
+    pub fn config_backup_dir(&self) -> PathBuf {
+        self.state_dir.join("config-backups")
+    }
+
+    /// Last startup maintenance run (`maintenance`).
+    pub fn maintenance_state_file(&self) -> PathBuf {
+        self.state_dir.join("maintenance.json")
+    }
+
+    /// Advisory locks on sessions held by `chat` and `server serve`.
+    pub fn session_locks_dir(&self) -> PathBuf {
+        self.state_dir.join("session-locks")
+    }
+
+    /// Delivery counters for `telemetry_export`, shown by `doctor`.
+    pub fn telemetry_export_file(&self) -> PathBuf {
+        self.state_dir.join("telemetry-export.json")
+    }
+
+    /// Every resolved location, for `doctor`.
+    pub fn entries(&self) -> Vec<(&'static str, String)> {
+        let show = |path: PathBuf| path.display().to_string();
+        vec![
+            ("workspace_dir", show(self.workspace_dir.clone())),
+            ("config_dir", show(self.config_dir.clone())),
+            ("state_dir", show(self.state_dir.clone())),
+            ("local_agents", show(self.local_agents_catalog())),
+            ("agent_selection", show(self.agent_selection_file())),
+            ("profile_selection", show(self.profile_selection_file())),
+            ("global_agents", show(self.global_agents_catalog())),
+            ("aliases", show(self.aliases_file())),
+            ("sessions_db", self.sessions_db_url()),
+            ("answer_cache", show(self.answer_cache_dir())),
+            ("idle_transcripts", show(self.idle_transcript_dir())),
+            ("idle_snapshot_db", self.idle_snapshot_db_url()),
+            ("telemetry", show(self.telemetry_path())),
+            ("eval_output", show(self.eval_output_path())),
+            ("usage", show(self.usage_dir())),
+            ("tool_output", show(self.tool_output_dir())),
+            ("memory_facts", show(self.memory_facts_file())),
+            ("memory_db", self.memory_db_url()),
+            ("file_history", show(self.file_history_dir())),
+            ("mcp_tokens", show(self.mcp_tokens_dir())),
+            ("lsp_config", show(self.lsp_config_file())),
+            ("update_check", show(self.update_check_file())),
+            ("model_catalog", show(self.model_catalog_dir())),
+            ("onboarding", show(self.onboarding_marker_file())),
+            ("trust_store", show(self.trust_store_file())),
+            ("mcp_breakers", show(self.mcp_breaker_file())),
+            ("telemetry_export", show(self.telemetry_export_file())),
+            ("checkpoints", show(self.checkpoints_file())),
+            ("maintenance", show(self.maintenance_state_file())),
+            ("config_backups", show(self.config_backup_dir())),
+            ("session_locks", show(self.session_locks_dir())),
+        ]
+    }
+}
+
+fn sqlite_url(path: &Path) -> String {
+    format!("sqlite://{}", path.display())
+}
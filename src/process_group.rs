@@ -0,0 +1,339 @@
+//! Process groups of running `execute_bash` commands.
+//!
+//! Each command's shell is started as the leader of its own process group,
+//! so a timeout or cancellation can signal the whole subtree (a `cargo
+//! build` and its rustc children) instead of just the shell. Killing sends
+//! SIGTERM to the group, waits up to a grace period for its members to exit
+//! and then sends SIGKILL to whatever is left.
+//!
+//! Live groups are kept in a process-wide registry. A [`ProcessGroupGuard`]
+//! removes its group when the command completes, and kills it when dropped
+//! mid-run (the tool future was cancelled). The `--max-runtime-secs`
+//! watchdog and Ctrl-C sweep the registry before the process exits, since a
+//! child in its own group no longer receives the terminal's SIGINT.
+//!
+//! Signals are sent with the `kill` utility and liveness is read from
+//! `/proc` where available. On Windows the shell is put in a job object with
+//! `JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE` instead, registered under the
+//! shell's pid: both signals terminate the job, liveness is its active
+//! process count, and if zavora itself dies the system closes the handle and
+//! the job's processes go with it. Children the shell starts before it is
+//! assigned to the job (a window of microseconds) are not covered.
+use std::collections::BTreeSet;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How long a group gets to exit after SIGTERM before SIGKILL.
+pub const PROCESS_GROUP_KILL_GRACE: Duration = Duration::from_millis(500);
+const PROCESS_GROUP_POLL_INTERVAL: Duration = Duration::from_millis(25);
+
+static PROCESS_GROUPS: Mutex<BTreeSet<u32>> = Mutex::new(BTreeSet::new());
+
+/// Process group ids of commands still running.
+pub fn registered_process_groups() -> Vec<u32> {
+    PROCESS_GROUPS
+        .lock()
+        .map(|groups| groups.iter().copied().collect())
+        .unwrap_or_default()
+}
+
+fn unregister(pgid: u32) {
+    if let Ok(mut groups) = PROCESS_GROUPS.lock() {
+        groups.remove(&pgid);
+    }
+    #[cfg(windows)]
+    job::close(pgid);
+}
+
+/// Register the group `child` leads, if it has one: on Unix the child must
+/// have been spawned with `process_group(0)`; on Windows it is assigned to a
+/// new job object here.
+pub fn register_child(child: &tokio::process::Child) -> Option<ProcessGroupGuard> {
+    let pid = child.id()?;
+    #[cfg(windows)]
+    if !job::assign(pid, child.raw_handle()?) {
+        return None;
+    }
+    cfg!(any(unix, windows)).then(|| ProcessGroupGuard::register(pid))
+}
+
+/// Send `signal` (`TERM`, `KILL`) to every process in group `pgid`.
+#[cfg(unix)]
+pub fn signal_process_group(pgid: u32, signal: &str) -> bool {
+    std::process::Command::new("kill")
+        .args(["-s", signal, "--", &format!("-{pgid}")])
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+/// Windows has no graceful signal for a job; both terminate it.
+#[cfg(windows)]
+pub fn signal_process_group(pgid: u32, _signal: &str) -> bool {
+    job::terminate(pgid)
+}
+
+#[cfg(not(any(unix, windows)))]
+pub fn signal_process_group(_pgid: u32, _signal: &str) -> bool {
+    false
+}
+
+/// State and process group of `pid` from `/proc/<pid>/stat`.
+#[cfg(unix)]
+fn proc_stat(pid: &str) -> Option<(char, u32)> {
+    let stat = std::fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+    // The command name is parenthesized and may contain spaces.
+    let mut fields = stat.get(stat.rfind(')')? + 1..)?.split_whitespace();
+    let state = fields.next()?.chars().next()?;
+    let pgrp = fields.nth(1)?.parse().ok()?;
+    Some((state, pgrp))
+}
+
+/// Whether any process in group `pgid` is still running. Zombies waiting to
+/// be reaped do not count.
+#[cfg(unix)]
+pub fn process_group_alive(pgid: u32) -> bool {
+    if let Ok(entries) = std::fs::read_dir("/proc") {
+        return entries.flatten().any(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            name.bytes().all(|byte| byte.is_ascii_digit())
+                && proc_stat(&name).is_some_and(|(state, pgrp)| pgrp == pgid && state != 'Z')
+        });
+    }
+    std::process::Command::new("kill")
+        .args(["-0", "--", &format!("-{pgid}")])
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+#[cfg(windows)]
+pub fn process_group_alive(pgid: u32) -> bool {
+    job::active_processes(pgid) > 0
+}
+
+#[cfg(not(any(unix, windows)))]
+pub fn process_group_alive(_pgid: u32) -> bool {
+    false
+}
+
+/// SIGTERM, then SIGKILL after `grace` if anything survived. Returns the
+/// last signal sent.
+pub async fn kill_process_group(pgid: u32, grace: Duration) -> &'static str {
+    signal_process_group(pgid, "TERM");
+    let deadline = Instant::now() + grace;
+    while Instant::now() < deadline {
+        if !process_group_alive(pgid) {
+            return "SIGTERM";
+        }
+        tokio::time::sleep(PROCESS_GROUP_POLL_INTERVAL).await;
+    }
+    if !process_group_alive(pgid) {
+        return "SIGTERM";
+    }
+    signal_process_group(pgid, "KILL");
+    "SIGKILL"
+}
+
+/// Kill every registered group, blocking for at most `grace` between
+/// SIGTERM and SIGKILL. For exit paths; returns how many groups there were.
+pub fn sweep_process_groups(grace: Duration) -> usize {
+    let groups = match PROCESS_GROUPS.lock() {
+        Ok(mut groups) => std::mem::take(&mut *groups),
+        Err(_) => return 0,
+    };
+    for pgid in &groups {
+        signal_process_group(*pgid, "TERM");
+    }
+    let deadline = Instant::now() + grace;
+    while Instant::now() < deadline && groups.iter().any(|pgid| process_group_alive(*pgid)) {
+        std::thread::sleep(PROCESS_GROUP_POLL_INTERVAL);
+    }
+    for pgid in groups.iter().filter(|pgid| process_group_alive(**pgid)) {
+        signal_process_group(*pgid, "KILL");
+    }
+    #[cfg(windows)]
+    for pgid in &groups {
+        job::close(*pgid);
+    }
+    if !groups.is_empty() {
+        tracing::info!(
+            groups = groups.len(),
+            "Killed running execute_bash process groups"
+        );
+    }
+    groups.len()
+}
+
+/// A registered process group. Call [`release`](Self::release) when the
+/// command finished or [`kill`](Self::kill) to end it; dropping the guard
+/// otherwise kills the group in the background.
+#[derive(Debug)]
+pub struct ProcessGroupGuard {
+    pgid: u32,
+    armed: bool,
+}
+
+impl ProcessGroupGuard {
+    pub fn register(pgid: u32) -> Self {
+        if let Ok(mut groups) = PROCESS_GROUPS.lock() {
+            groups.insert(pgid);
+        }
+        Self { pgid, armed: true }
+    }
+
+    pub fn pgid(&self) -> u32 {
+        self.pgid
+    }
+
+    /// The command completed: forget the group and leave it alone.
+    pub fn release(mut self) {
+        self.armed = false;
+        unregister(self.pgid);
+    }
+
+    /// Kill the group (see [`kill_process_group`]) and forget it.
+    pub async fn kill(mut self, grace: Duration) -> &'static str {
+        self.armed = false;
+        let signal = kill_process_group(self.pgid, grace).await;
+        unregister(self.pgid);
+        signal
+    }
+}
+
+impl Drop for ProcessGroupGuard {
+    fn drop(&mut self) {
+        if !self.armed {
+            return;
+        }
+        let pgid = self.pgid;
+        signal_process_group(pgid, "TERM");
+        // Drop cannot wait; a short-lived thread sends the SIGKILL.
+        std::thread::spawn(move || {
+            let deadline = Instant::now() + PROCESS_GROUP_KILL_GRACE;
+            while Instant::now() < deadline && process_group_alive(pgid) {
+                std::thread::sleep(PROCESS_GROUP_POLL_INTERVAL);
+            }
+            if process_group_alive(pgid) {
+                signal_process_group(pgid, "KILL");
+            }
+            unregister(pgid);
+        });
+    }
+}
+
+/// Job objects backing the process groups on Windows, by the shell's pid.
+#[cfg(windows)]
+mod job {
+    use std::collections::BTreeMap;
+    use std::ffi::c_void;
+    use std::os::windows::io::RawHandle;
+    use std::sync::Mutex;
+
+    use windows_sys::Win32::Foundation::{CloseHandle, HANDLE};
+    use windows_sys::Win32::System::JobObjects::{
+        AssignProcessToJobObject, CreateJobObjectW, JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE,
+        JOBOBJECT_BASIC_ACCOUNTING_INFORMATION, JOBOBJECT_EXTENDED_LIMIT_INFORMATION,
+        JobObjectBasicAccountingInformation, JobObjectExtendedLimitInformation,
+        QueryInformationJobObject, SetInformationJobObject, TerminateJobObject,
+    };
+
+    /// Handles are stored as integers so the map is `Send`.
+    static JOBS: Mutex<BTreeMap<u32, usize>> = Mutex::new(BTreeMap::new());
+
+    /// Run `call` with the job for `pgid`, holding the registry lock so the
+    /// handle cannot be closed underneath it.
+    fn with_job<T>(pgid: u32, call: impl FnOnce(HANDLE) -> T) -> Option<T> {
+        let jobs = JOBS.lock().ok()?;
+        jobs.get(&pgid).map(|job| call(*job as HANDLE))
+    }
+
+    fn set_limit_flags(job: HANDLE, flags: u32) -> bool {
+        // SAFETY: `info` is a plain C struct, zero is a valid value for every
+        // field, and the size passed matches the type.
+        unsafe {
+            let mut info: JOBOBJECT_EXTENDED_LIMIT_INFORMATION = std::mem::zeroed();
+            info.BasicLimitInformation.LimitFlags = flags;
+            SetInformationJobObject(
+                job,
+                JobObjectExtendedLimitInformation,
+                &info as *const JOBOBJECT_EXTENDED_LIMIT_INFORMATION as *const c_void,
+                std::mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+            ) != 0
+        }
+    }
+
+    /// Put `process` in a new kill-on-close job registered under `pgid`.
+    pub fn assign(pgid: u32, process: RawHandle) -> bool {
+        // SAFETY: a null name and null security attributes create an
+        // anonymous job; `process` is a live handle owned by the child.
+        let job = unsafe { CreateJobObjectW(std::ptr::null(), std::ptr::null()) };
+        if job.is_null() {
+            return false;
+        }
+        let assigned = set_limit_flags(job, JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE)
+            // SAFETY: both handles are valid for the duration of the call.
+            && unsafe { AssignProcessToJobObject(job, process as HANDLE) } != 0;
+        if !assigned {
+            // SAFETY: `job` was created above and is not stored anywhere.
+            unsafe { CloseHandle(job) };
+            return false;
+        }
+        match JOBS.lock() {
+            Ok(mut jobs) => {
+                jobs.insert(pgid, job as usize);
+                true
+            }
+            Err(_) => {
+                // SAFETY: as above; closing kills the just-assigned shell.
+                unsafe { CloseHandle(job) };
+                false
+            }
+        }
+    }
+
+    pub fn terminate(pgid: u32) -> bool {
+        // SAFETY: registered handles stay open until `close`.
+        with_job(pgid, |job| unsafe { TerminateJobObject(job, 1) } != 0).unwrap_or(false)
+    }
+
+    pub fn active_processes(pgid: u32) -> u32 {
+        with_job(pgid, |job| {
+            // SAFETY: `info` is a plain C struct written by the call, and the
+            // size passed matches the type.
+            unsafe {
+                let mut info: JOBOBJECT_BASIC_ACCOUNTING_INFORMATION = std::mem::zeroed();
+                let queried = QueryInformationJobObject(
+                    job,
+                    JobObjectBasicAccountingInformation,
+                    &mut info as *mut JOBOBJECT_BASIC_ACCOUNTING_INFORMATION as *mut c_void,
+                    std::mem::size_of::<JOBOBJECT_BASIC_ACCOUNTING_INFORMATION>() as u32,
+                    std::ptr::null_mut(),
+                );
+                if queried != 0 {
+                    info.ActiveProcesses
+                } else {
+                    0
+                }
+            }
+        })
+        .unwrap_or(0)
+    }
+
+    /// Forget the job. Kill-on-close is cleared first, so a completed
+    /// command's background processes are left alone as on Unix.
+    pub fn close(pgid: u32) {
+        let Some(job) = JOBS.lock().ok().and_then(|mut jobs| jobs.remove(&pgid)) else {
+            return;
+        };
+        let job = job as HANDLE;
+        set_limit_flags(job, 0);
+        // SAFETY: the handle was removed from the map, so this is its last use.
+        unsafe { CloseHandle(job) };
+    }
+}
@@ -0,0 +1,279 @@
+//! Profile inheritance: `extends = "<profile>"`.
+//!
+//! A profile's chain is itself, its parent, the parent's parent and so on,
+//! up to [`MAX_PROFILE_EXTENDS_DEPTH`] profiles. Resolution applies the root
+//! first and each descendant over it: a field the child sets replaces the
+//! inherited value. With `merge_lists = true` on the child, list fields
+//! (`guardrail_terms`, `approve_tool`, `mcp_servers`, ...) are appended to
+//! the parent's instead; entries with a `name` (MCP servers) replace the
+//! parent's entry of the same name, and tables such as `permission_rules`
+//! merge key by key. An empty list or table counts as unset, so it inherits
+//! the parent's value rather than clearing it.
+//!
+//! `extends = "default"` works even when `default` is not configured; it is
+//! then an empty profile. Cycles, unknown parents and over-deep chains are
+//! input errors naming the chain.
+use std::collections::BTreeMap;
+
+use anyhow::{Context, Result};
+use serde_json::{Map, Value};
+
+use crate::config::{DEFAULT_PROFILE, ProfileConfig, ProfilesFile};
+
+/// Profiles in one chain, the profile itself included.
+pub const MAX_PROFILE_EXTENDS_DEPTH: usize = 8;
+
+/// Keys that describe the profile's own place in the chain; never inherited.
+const INHERITANCE_KEYS: [&str; 2] = ["extends", "merge_lists"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldOrigin {
+    /// Set by the profile, with no inherited value underneath.
+    Own,
+    /// Set by the profile over the value `parent` provided.
+    Overridden { parent: String },
+    /// Taken unchanged from an ancestor.
+    Inherited { from: String },
+    /// A list appended across these profiles, root first.
+    Merged { from: Vec<String> },
+}
+
+impl FieldOrigin {
+    pub fn label(&self) -> String {
+        match self {
+            Self::Own => "set here".to_string(),
+            Self::Overridden { parent } => format!("overrides '{parent}'"),
+            Self::Inherited { from } => format!("inherited from '{from}'"),
+            Self::Merged { from } => format!("merged from {}", quoted_list(from)),
+        }
+    }
+}
+
+fn quoted_list(names: &[String]) -> String {
+    names
+        .iter()
+        .map(|name| format!("'{name}'"))
+        .collect::<Vec<String>>()
+        .join(" + ")
+}
+
+#[derive(Debug, Clone)]
+pub struct ResolvedProfile {
+    pub config: ProfileConfig,
+    /// The profile first, then each ancestor.
+    pub chain: Vec<String>,
+    /// Where every field that ended up set came from.
+    pub fields: BTreeMap<String, FieldOrigin>,
+}
+
+/// A configured profile, or the implicit empty `default`.
+fn lookup_profile<'a>(
+    profiles: &'a ProfilesFile,
+    name: &str,
+    child: Option<&str>,
+) -> Result<Option<&'a ProfileConfig>> {
+    if let Some(profile) = profiles.profiles.get(name) {
+        return Ok(Some(profile));
+    }
+    let Some(child) = child else {
+        return Ok(None);
+    };
+    if let Some(reason) = profiles.broken_profiles.get(name) {
+        return Err(anyhow::anyhow!(
+            "profile inheritance: '{child}' extends invalid profile '{name}': {reason}"
+        ));
+    }
+    if name == DEFAULT_PROFILE {
+        return Ok(None);
+    }
+    Err(anyhow::anyhow!(
+        "profile inheritance: '{child}' extends unknown profile '{name}'"
+    ))
+}
+
+/// `name` followed by its ancestors.
+pub fn profile_extends_chain(profiles: &ProfilesFile, name: &str) -> Result<Vec<String>> {
+    let mut chain = vec![name.to_string()];
+    let mut current = lookup_profile(profiles, name, None)?;
+    while let Some(parent) = current
+        .and_then(|profile| profile.extends.as_deref())
+        .map(str::trim)
+    {
+        let child = chain.last().cloned().unwrap_or_default();
+        if chain.iter().any(|seen| seen == parent) {
+            chain.push(parent.to_string());
+            return Err(anyhow::anyhow!(
+                "profile inheritance cycle: {}",
+                chain.join(" -> ")
+            ));
+        }
+        chain.push(parent.to_string());
+        if chain.len() > MAX_PROFILE_EXTENDS_DEPTH {
+            return Err(anyhow::anyhow!(
+                "profile inheritance for '{name}' is deeper than {MAX_PROFILE_EXTENDS_DEPTH} \
+                 profiles: {}",
+                chain.join(" -> ")
+            ));
+        }
+        current = lookup_profile(profiles, parent, Some(&child))?;
+    }
+    Ok(chain)
+}
+
+/// Null, an empty list or table, or a table of only those.
+fn is_unset(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::Array(items) => items.is_empty(),
+        Value::Object(map) => map.values().all(is_unset),
+        _ => false,
+    }
+}
+
+/// Append `extra` to `base`, replacing same-`name` entries and skipping
+/// exact duplicates.
+fn merge_list(base: &mut Vec<Value>, extra: Vec<Value>) {
+    for item in extra {
+        let name = item.get("name").and_then(Value::as_str).map(str::to_string);
+        let same_name = name.as_deref().and_then(|name| {
+            base.iter()
+                .position(|existing| existing.get("name").and_then(Value::as_str) == Some(name))
+        });
+        match same_name {
+            Some(index) => base[index] = item,
+            None if !base.contains(&item) => base.push(item),
+            None => {}
+        }
+    }
+}
+
+fn mergeable(base: &Value, extra: &Value) -> bool {
+    matches!(
+        (base, extra),
+        (Value::Array(_), Value::Array(_)) | (Value::Object(_), Value::Object(_))
+    )
+}
+
+/// Lists append; tables (`permission_rules`, `providers`) merge key by key
+/// with their lists appended; anything else is replaced.
+fn merge_value(base: &mut Value, extra: Value) {
+    match (base, extra) {
+        (Value::Array(base), Value::Array(extra)) => merge_list(base, extra),
+        (Value::Object(base), Value::Object(extra)) => {
+            for (key, value) in extra {
+                match base.get_mut(&key) {
+                    Some(existing) if mergeable(existing, &value) => merge_value(existing, value),
+                    _ if is_unset(&value) => {}
+                    _ => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, extra) => *base = extra,
+    }
+}
+
+#[derive(Default)]
+struct FieldTrail {
+    /// Profiles whose value is in the field now, root first.
+    owners: Vec<String>,
+    /// Owner of the value the current one replaced.
+    replaced: Option<String>,
+    merged: bool,
+}
+
+/// `name` with its chain applied. An unconfigured `default` resolves to an
+/// empty profile.
+pub fn resolve_profile(profiles: &ProfilesFile, name: &str) -> Result<ResolvedProfile> {
+    let chain = profile_extends_chain(profiles, name)?;
+    let own = profiles.profiles.get(name).cloned().unwrap_or_default();
+    if chain.len() == 1 {
+        let fields = profile_fields(&own)?
+            .into_iter()
+            .map(|(key, _)| (key, FieldOrigin::Own))
+            .collect();
+        return Ok(ResolvedProfile {
+            config: own,
+            chain,
+            fields,
+        });
+    }
+
+    let mut merged = Map::new();
+    let mut trails = BTreeMap::<String, FieldTrail>::new();
+    for layer in chain.iter().rev() {
+        let profile = profiles.profiles.get(layer).cloned().unwrap_or_default();
+        let merge_lists = profile.merge_lists.unwrap_or(false);
+        for (key, value) in profile_fields(&profile)? {
+            let trail = trails.entry(key.clone()).or_default();
+            match (merged.get_mut(&key), value) {
+                (Some(base), extra) if merge_lists && mergeable(base, &extra) => {
+                    merge_value(base, extra);
+                    trail.owners.push(layer.clone());
+                    trail.merged = true;
+                }
+                (existing, value) => {
+                    trail.replaced = existing.and(trail.owners.last().cloned());
+                    trail.owners = vec![layer.clone()];
+                    trail.merged = false;
+                    merged.insert(key, value);
+                }
+            }
+        }
+    }
+
+    let mut config = serde_json::from_value::<ProfileConfig>(Value::Object(merged))
+        .with_context(|| format!("profile inheritance: failed to merge profile '{name}'"))?;
+    config.extends = own.extends.clone();
+    config.merge_lists = own.merge_lists;
+    let fields = trails
+        .into_iter()
+        .map(|(key, trail)| {
+            let origin = if trail.merged && trail.owners.len() > 1 {
+                FieldOrigin::Merged { from: trail.owners }
+            } else if trail.owners.last().map(String::as_str) == Some(name) {
+                match trail.replaced {
+                    Some(parent) => FieldOrigin::Overridden { parent },
+                    None => FieldOrigin::Own,
+                }
+            } else {
+                FieldOrigin::Inherited {
+                    from: trail.owners.last().cloned().unwrap_or_default(),
+                }
+            };
+            (key, origin)
+        })
+        .collect();
+    Ok(ResolvedProfile {
+        config,
+        chain,
+        fields,
+    })
+}
+
+/// The profile's set fields as JSON, without `extends`/`merge_lists`.
+fn profile_fields(profile: &ProfileConfig) -> Result<Vec<(String, Value)>> {
+    let value = serde_json::to_value(profile).context("failed to encode profile")?;
+    let Value::Object(map) = value else {
+        return Ok(Vec::new());
+    };
+    Ok(map
+        .into_iter()
+        .filter(|(key, value)| !INHERITANCE_KEYS.contains(&key.as_str()) && !is_unset(value))
+        .collect())
+}
+
+/// `profiles show` section: the chain and one line per set field.
+pub fn format_profile_provenance(resolved: &ResolvedProfile) -> String {
+    let mut out = format!("Inheritance: {}\n", resolved.chain.join(" -> "));
+    if resolved.fields.is_empty() {
+        out.push_str("Profile fields: <none set>\n");
+        return out;
+    }
+    out.push_str("Profile fields:\n");
+    for (field, origin) in &resolved.fields {
+        out.push_str(&format!("  {field}: {}\n", origin.label()));
+    }
+    out
+}
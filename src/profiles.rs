@@ -1,36 +1,139 @@
+use std::path::Path;
+
 use anyhow::Result;
 
-use crate::config::{ProfilesFile, RuntimeConfig, display_session_db_url};
+use crate::config::{
+    DEFAULT_PROFILE, ProfilesFile, RuntimeConfig, broken_profile_error, display_session_db_url,
+    persist_profile_selection, profile_not_found_error,
+};
+use crate::maintenance::format_retention_policy;
+use crate::profile_inheritance::{format_profile_provenance, resolve_profile};
+use crate::tools::budget::format_tool_budgets;
+use crate::tools::output_cap::format_tool_output_cap;
 
-pub fn run_profiles_list(profiles: &ProfilesFile, cfg: &RuntimeConfig) -> Result<()> {
-    let mut names = profiles.profiles.keys().cloned().collect::<Vec<String>>();
-    if !names.iter().any(|name| name == "default") {
-        names.push("default".to_string());
+/// `*` marks the active profile; `+` marks the workspace selection when an
+/// explicit `--profile`/`ZAVORA_PROFILE` overrides it. Profiles that failed
+/// to parse are listed with their error.
+pub fn format_profiles_list(
+    profiles: &ProfilesFile,
+    active: &str,
+    selected: Option<&str>,
+) -> String {
+    let mut names = profiles
+        .profiles
+        .keys()
+        .chain(profiles.broken_profiles.keys())
+        .cloned()
+        .collect::<Vec<String>>();
+    if !names.iter().any(|name| name == DEFAULT_PROFILE) {
+        names.push(DEFAULT_PROFILE.to_string());
     }
     names.sort();
 
-    println!("Configured profiles (active='{}'):", cfg.profile);
+    let mut out = format!("Configured profiles (active='{active}'):\n");
     for name in names {
-        let marker = if name == cfg.profile { "*" } else { " " };
+        let marker = if name == active {
+            "*"
+        } else if selected == Some(name.as_str()) {
+            "+"
+        } else {
+            " "
+        };
         let source = if profiles.profiles.contains_key(&name) {
-            "configured"
+            "configured".to_string()
+        } else if let Some(reason) = profiles.broken_profiles.get(&name) {
+            format!("invalid: {reason}")
+        } else {
+            "implicit".to_string()
+        };
+        let parent = profiles
+            .profiles
+            .get(&name)
+            .and_then(|profile| profile.extends.as_deref())
+            .map(|parent| format!(", extends {}", parent.trim()))
+            .unwrap_or_default();
+        let selection = if selected == Some(name.as_str()) {
+            ", workspace selection"
         } else {
-            "implicit"
+            ""
         };
-        println!("{marker} {name} ({source})");
+        out.push_str(&format!("{marker} {name} ({source}{parent}{selection})\n"));
     }
+    if let Some(selected) = selected
+        && !profiles.profiles.contains_key(selected)
+        && !profiles.broken_profiles.contains_key(selected)
+        && selected != DEFAULT_PROFILE
+    {
+        out.push_str(&format!(
+            "Workspace selection '{selected}' is not configured; using '{DEFAULT_PROFILE}'.\n"
+        ));
+    }
+    out
+}
 
+pub fn run_profiles_list(
+    profiles: &ProfilesFile,
+    cfg: &RuntimeConfig,
+    selected: Option<&str>,
+) -> Result<()> {
+    print!("{}", format_profiles_list(profiles, &cfg.profile, selected));
     Ok(())
 }
 
-pub fn run_profiles_show(cfg: &RuntimeConfig) -> Result<()> {
+pub fn run_profiles_select(
+    profiles: &ProfilesFile,
+    config_path: &str,
+    selection_file: &Path,
+    name: &str,
+) -> Result<()> {
+    let trimmed = name.trim();
+    if trimmed.is_empty() {
+        return Err(anyhow::anyhow!("profile name cannot be empty"));
+    }
+    if let Some(reason) = profiles.broken_profiles.get(trimmed) {
+        return Err(broken_profile_error(trimmed, reason, config_path));
+    }
+    if !profiles.profiles.contains_key(trimmed) && trimmed != DEFAULT_PROFILE {
+        return Err(profile_not_found_error(trimmed, profiles, config_path));
+    }
+    persist_profile_selection(selection_file, trimmed)?;
+    println!(
+        "Selected profile '{}' (selection file: {}).",
+        trimmed,
+        selection_file.display()
+    );
+    Ok(())
+}
+
+pub fn run_profiles_show(profiles: &ProfilesFile, cfg: &RuntimeConfig) -> Result<()> {
     println!("Active profile: {}", cfg.profile);
     println!("Config path: {}", cfg.config_path);
+    println!(
+        "Profile source: {}",
+        profiles
+            .profile_sources
+            .get(&cfg.profile)
+            .map(|path| path.display().to_string())
+            .unwrap_or_else(|| "<implicit>".to_string())
+    );
+    let resolved = resolve_profile(profiles, &cfg.profile)?;
+    if resolved.chain.len() > 1 {
+        print!("{}", format_profile_provenance(&resolved));
+    }
     println!("Provider: {:?}", cfg.provider);
     println!(
         "Model: {}",
         cfg.model.as_deref().unwrap_or("<provider-default>")
     );
+    let overrides = crate::provider::format_provider_overrides(&cfg.provider_overrides);
+    println!(
+        "Provider overrides: {}",
+        if overrides.is_empty() {
+            "<none>".to_string()
+        } else {
+            overrides.join("; ")
+        }
+    );
     println!("App: {}", cfg.app_name);
     println!("User: {}", cfg.user_id);
     println!(
@@ -53,6 +156,13 @@ pub fn run_profiles_show(cfg: &RuntimeConfig) -> Result<()> {
     println!("Session ID: {}", cfg.session_id);
     println!("Session backend: {:?}", cfg.session_backend);
     println!("Session DB URL: {}", display_session_db_url(cfg));
+    println!(
+        "Session encryption: {}",
+        cfg.session_encryption_key_env
+            .as_deref()
+            .map(|env| format!("key from {env}"))
+            .unwrap_or_else(|| "off".to_string())
+    );
     println!("Retrieval backend: {:?}", cfg.retrieval_backend);
     println!(
         "Retrieval doc path: {}",
@@ -63,6 +173,19 @@ pub fn run_profiles_show(cfg: &RuntimeConfig) -> Result<()> {
     println!("Retrieval max chunks: {}", cfg.retrieval_max_chunks);
     println!("Retrieval max chars: {}", cfg.retrieval_max_chars);
     println!("Retrieval min score: {}", cfg.retrieval_min_score);
+    println!(
+        "Retrieval query: {:?} (window {})",
+        cfg.retrieval_query_strategy, cfg.retrieval_query_window
+    );
+    println!("Retrieval watch: {:?}", cfg.retrieval_watch);
+    println!(
+        "Retrieval chunking: {}",
+        crate::chunking::ChunkingStrategy::from_config(cfg).label()
+    );
+    println!(
+        "Memory backend: {:?} (recall max facts: {})",
+        cfg.memory_backend, cfg.memory_recall_max_facts
+    );
     println!("Tool confirmation mode: {:?}", cfg.tool_confirmation_mode);
     println!(
         "Tool confirmation required list: {}",
@@ -81,17 +204,118 @@ pub fn run_profiles_show(cfg: &RuntimeConfig) -> Result<()> {
         }
     );
     println!("Tool timeout (secs): {}", cfg.tool_timeout_secs);
+    println!("Slow tool warning (secs): {}", cfg.slow_tool_warn_secs);
+    println!("Max runtime (secs): {}", cfg.max_runtime_secs);
+    println!(
+        "Chat tool results: {:?} (max {} chars)",
+        cfg.chat_show_tool_results, cfg.chat_tool_result_max_chars
+    );
+    println!("Chat tool annotations: {:?}", cfg.chat_tool_annotations);
+    println!(
+        "Tool schema strategy: {:?} (top {})",
+        cfg.tool_schema_strategy, cfg.tool_schema_top_k
+    );
+    println!(
+        "{}",
+        format_tool_output_cap(cfg.tool_output_max_chars, &cfg.paths.tool_output_dir())
+    );
+    println!("{}", format_tool_budgets(&cfg.tool_budgets));
     println!("Tool retry attempts: {}", cfg.tool_retry_attempts);
     println!("Tool retry delay (ms): {}", cfg.tool_retry_delay_ms);
+    println!("Tool arg retry hint: {}", cfg.tool_arg_retry_hint);
+    println!(
+        "MCP circuit breaker: after {} failure(s), backoff up to {}s",
+        cfg.mcp_failure_threshold, cfg.mcp_breaker_max_secs
+    );
+    println!(
+        "Session busy retries: {} (max {} ms)",
+        cfg.session_busy_retry_attempts, cfg.session_busy_retry_max_ms
+    );
     println!("Telemetry enabled: {}", cfg.telemetry_enabled);
     println!("Telemetry path: {}", cfg.telemetry_path);
     println!(
-        "Guardrails: input_mode={:?} output_mode={:?} terms={} redact_replacement={}",
+        "Telemetry environment capture: {}",
+        cfg.telemetry_capture_environment
+    );
+    println!(
+        "Telemetry redaction: {:?} fields={}",
+        cfg.telemetry_redaction,
+        cfg.telemetry_redact_fields.join(",")
+    );
+    println!(
+        "Telemetry export: {}",
+        cfg.telemetry_export
+            .as_ref()
+            .map(|export| format!("{:?} {}", export.format, export.endpoint))
+            .unwrap_or_else(|| "off".to_string())
+    );
+    println!("{}", format_retention_policy(&cfg.retention));
+    println!(
+        "Guardrails: input_mode={:?} output_mode={:?} retrieval_mode={:?} terms={} redact_replacement={}",
         cfg.guardrail_input_mode,
         cfg.guardrail_output_mode,
+        cfg.guardrail_retrieval_mode,
         cfg.guardrail_terms.len(),
         cfg.guardrail_redact_replacement
     );
+    println!(
+        "Content injection: mode={:?} extra_patterns={}",
+        cfg.content_injection_mode,
+        cfg.content_injection_patterns.len()
+    );
+    println!(
+        "fs_write secrets: policy={:?} allowlist={}",
+        cfg.fs_write_secret_policy,
+        cfg.fs_write_secret_allowlist.len()
+    );
+    println!("Output sanitize: {:?}", cfg.output_sanitize);
+    println!(
+        "Server limits: max_body_bytes={} max_prompt_chars={} legacy_status_codes={}",
+        cfg.server_max_body_bytes, cfg.server_max_prompt_chars, cfg.server_legacy_status_codes
+    );
+    println!(
+        "Server per-user limits: max_concurrent={} requests_per_minute={} (0 = unlimited)",
+        cfg.server_per_user_max_concurrent, cfg.server_per_user_requests_per_minute
+    );
+    println!(
+        "Chat notify: command={} threshold_secs={}",
+        cfg.chat_notify_command
+            .as_deref()
+            .unwrap_or("<not configured>"),
+        cfg.chat_notify_threshold_secs
+    );
+    println!(
+        "Chat idle timeout (secs): {} transcript_dir={} snapshot_db={}",
+        cfg.chat_idle_timeout_secs,
+        if cfg.chat_idle_transcript_dir.is_empty() {
+            "<disabled>"
+        } else {
+            &cfg.chat_idle_transcript_dir
+        },
+        if cfg.chat_idle_snapshot_db_url.is_empty() {
+            "<disabled>"
+        } else {
+            &cfg.chat_idle_snapshot_db_url
+        }
+    );
+    println!(
+        "Usage budget: mode={:?} daily_tokens={} daily_cost={} cost_per_1k_tokens={}",
+        cfg.usage_budget_mode,
+        cfg.daily_token_budget
+            .map(|budget| budget.to_string())
+            .unwrap_or_else(|| "<none>".to_string()),
+        cfg.daily_cost_budget
+            .map(|budget| format!("{budget:.2}"))
+            .unwrap_or_else(|| "<none>".to_string()),
+        cfg.usage_cost_per_1k_tokens
+    );
+    println!("Ask cache TTL (secs): {}", cfg.ask_cache_ttl_secs);
+    println!("Ask default ephemeral: {}", cfg.ask_default_ephemeral);
+    println!(
+        "execute_bash sandbox: {}",
+        cfg.execute_bash_sandbox.as_deref().unwrap_or("<off>")
+    );
+    println!("execute_bash summaries: {}", cfg.execute_bash_summarize);
     println!("MCP servers: {}", cfg.mcp_servers.len());
     Ok(())
 }
@@ -0,0 +1,138 @@
+use std::io::{self, IsTerminal, Write};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use serde_json::{Value, json};
+
+use crate::cli::ProgressMode;
+
+const PROGRESS_SPINNER_FRAMES: &[&str] = &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
+pub const PROGRESS_PHASE_COMPLETED: &str = "completed";
+pub const PROGRESS_PHASE_FAILED: &str = "failed";
+/// Reported before any phase was emitted.
+pub const PROGRESS_PHASE_STARTUP: &str = "startup";
+
+type ProgressWriter = Arc<Mutex<Box<dyn Write + Send>>>;
+
+/// Emits coarse run phases (MCP discovery, model resolution, generation, ...)
+/// to stderr. Independent of telemetry so scripts can watch progress without
+/// enabling the telemetry log.
+#[derive(Clone)]
+pub struct ProgressEmitter {
+    mode: ProgressMode,
+    started_at: Instant,
+    writer: ProgressWriter,
+    frame: Arc<Mutex<usize>>,
+    /// Last phase emitted, kept in every mode for the runtime watchdog.
+    phase: Arc<Mutex<Option<String>>>,
+}
+
+impl ProgressEmitter {
+    /// Build an emitter writing to stderr. `bar` mode is silently disabled
+    /// when stderr is not a terminal so redirected logs stay clean.
+    pub fn new(mode: ProgressMode) -> Self {
+        let mode = if mode == ProgressMode::Bar && !io::stderr().is_terminal() {
+            ProgressMode::None
+        } else {
+            mode
+        };
+        Self::with_writer(mode, Box::new(io::stderr()))
+    }
+
+    pub fn with_writer(mode: ProgressMode, writer: Box<dyn Write + Send>) -> Self {
+        Self {
+            mode,
+            started_at: Instant::now(),
+            writer: Arc::new(Mutex::new(writer)),
+            frame: Arc::new(Mutex::new(0)),
+            phase: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    pub fn disabled() -> Self {
+        Self::with_writer(ProgressMode::None, Box::new(io::sink()))
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.mode != ProgressMode::None
+    }
+
+    pub fn emit(&self, phase: &str, detail: Value) {
+        *self.phase.lock().unwrap_or_else(|e| e.into_inner()) = Some(phase.to_string());
+        match self.mode {
+            ProgressMode::None => {}
+            ProgressMode::Json => self.write_json_record(phase, detail),
+            ProgressMode::Bar => self.write_bar_line(phase, &detail),
+        }
+    }
+
+    /// The phase the run is in: the last one emitted, or `startup`.
+    pub fn current_phase(&self) -> String {
+        self.phase
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+            .unwrap_or_else(|| PROGRESS_PHASE_STARTUP.to_string())
+    }
+
+    pub fn completed(&self) {
+        self.emit(PROGRESS_PHASE_COMPLETED, json!({}));
+    }
+
+    pub fn failed(&self, error: &str) {
+        self.emit(PROGRESS_PHASE_FAILED, json!({ "error": error }));
+    }
+
+    fn elapsed_ms(&self) -> u128 {
+        self.started_at.elapsed().as_millis()
+    }
+
+    fn write_json_record(&self, phase: &str, detail: Value) {
+        let mut record = serde_json::Map::new();
+        record.insert("phase".to_string(), json!(phase));
+        record.insert("elapsed_ms".to_string(), json!(self.elapsed_ms()));
+        if let Value::Object(map) = detail {
+            for (key, value) in map {
+                record.insert(key, value);
+            }
+        }
+        let line = Value::Object(record).to_string();
+        if let Ok(mut writer) = self.writer.lock() {
+            let _ = writeln!(writer, "{line}");
+            let _ = writer.flush();
+        }
+    }
+
+    fn write_bar_line(&self, phase: &str, detail: &Value) {
+        let terminal = phase == PROGRESS_PHASE_COMPLETED || phase == PROGRESS_PHASE_FAILED;
+        let frame = {
+            let mut idx = self.frame.lock().unwrap_or_else(|e| e.into_inner());
+            *idx = idx.wrapping_add(1);
+            PROGRESS_SPINNER_FRAMES[*idx % PROGRESS_SPINNER_FRAMES.len()]
+        };
+        let marker = match phase {
+            PROGRESS_PHASE_COMPLETED => "✓",
+            PROGRESS_PHASE_FAILED => "✗",
+            _ => frame,
+        };
+        let suffix = detail
+            .get("tool")
+            .or_else(|| detail.get("model"))
+            .and_then(Value::as_str)
+            .map(|value| format!(" {value}"))
+            .unwrap_or_default();
+        let elapsed = self.elapsed_ms() as f64 / 1000.0;
+        if let Ok(mut writer) = self.writer.lock() {
+            let _ = write!(
+                writer,
+                "\r\x1b[2K{marker} {}{suffix} ({elapsed:.1}s)",
+                phase.replace('_', " ")
+            );
+            if terminal {
+                let _ = writeln!(writer);
+            }
+            let _ = writer.flush();
+        }
+    }
+}
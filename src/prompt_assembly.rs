@@ -0,0 +1,612 @@
+/// Prompt assembly shared by the runner and `debug render-prompt`.
+///
+/// The system instruction and user content are built here as labelled
+/// sections, so the real path and the debug renderer cannot drift apart.
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde_json::{Value, json};
+
+use crate::config::RuntimeConfig;
+use crate::content_injection::screen_retrieved_chunks;
+use crate::context::estimate_tokens;
+use crate::guardrail::{apply_guardrail, apply_retrieval_guardrail, enforce_prompt_limit};
+use crate::memory_facts::recall_for_prompt;
+use crate::retrieval::{
+    RetrievalPolicy, RetrievalService, RetrievedChunk, render_retrieval_context_with_note,
+    select_retrieval_chunks_with_truncation,
+};
+use crate::telemetry::TelemetrySink;
+use crate::tool_policy::{ToolOrigin, ToolPolicy};
+use crate::tools::policy_check::POLICY_CHECK_TOOL_NAME;
+
+const ORCHESTRATOR_INSTRUCTION: &str = "\
+You are the orchestrator. You coordinate specialist agents to accomplish complex tasks.
+
+CAPABILITY AGENTS (call as tools when you need their unique skills):
+- time_agent: Get current time, parse relative dates (\"next Friday\", \"in 2 days\")
+- memory_agent: Recall/store USER preferences, decisions, and learnings (NOT for general knowledge)
+
+SUBAGENTS (automatically available when conditions met):
+- search_agent: For news, current events, and web searches (enabled only with --provider gemini)
+- ralph_agent: For greenfield projects and multi-phase development (enabled only in agent mode)
+
+WORKFLOW AGENTS (use for complex multi-step work):
+- file_search_agent: Comprehensive file discovery with saturation detection
+- sequential_agent: Create plans and execute steps with progress tracking
+- quality_agent: Verify work against acceptance criteria
+
+RULES:
+- For news/web searches: delegate to search_agent
+- For greenfield projects, multi-file scaffolding, or multi-phase development: delegate to ralph_agent (agent mode only)
+- memory_agent is ONLY for user preferences/decisions, NOT for facts or general knowledge
+- For simple tasks, use your built-in tools directly
+- For complex multi-step tasks, use sequential_agent
+- Store only high-signal learnings: user preferences, decisions, patterns (not facts)
+";
+
+const SYSTEM_GUIDELINES: &str = "\n\
+    <tone>\n\
+    You talk like a human, not like a bot. You are conversational and natural.\n\
+    - Mirror the user's style: short question gets a short answer, detailed question \
+    gets a detailed answer\n\
+    - NEVER present menus, numbered option lists, or \"quick options\" unless the user \
+    asks for choices\n\
+    - NEVER start responses with flattery (\"Great question!\", \"That's a good idea!\")\n\
+    - For greetings like \"hello\" or \"hi\", respond briefly and naturally — don't list \
+    capabilities or suggest actions\n\
+    - When you don't know something, say so directly\n\
+    - Use neutral acknowledgments: \"Let me look at that\" not \"Absolutely! I'd love to \
+    help!\"\n\
+    </tone>\n\
+    \n\
+    <coding_standards>\n\
+    PROJECT AWARENESS: Before writing code, understand the project's existing patterns, \
+    dependencies, and conventions. Use what's already there.\n\
+    - If the project uses a library or framework, USE IT. Don't build custom solutions \
+    when the existing stack provides them.\n\
+    - Match the project's code style, naming conventions, and file organization.\n\
+    - Every line of code must have a purpose. If it doesn't contribute to the solution, \
+    remove it.\n\
+    MINIMAL CHANGES: Write the absolute minimum code needed. Don't refactor surrounding \
+    code unless asked. Don't add features that weren't requested.\n\
+    VERIFY: Read files before modifying them. Check that builds pass after changes. \
+    Don't assume — verify.\n\
+    </coding_standards>\n\
+    \n\
+    <tool_guidelines>\n\
+    - Use fs_read to examine files before modifying them\n\
+    - Use file_edit for surgical text replacements in existing files (preferred over fs_write for edits)\n\
+    - Use fs_write only for creating new files or full rewrites\n\
+    - Use apply_patch for coordinated changes across several files (one unified diff)\n\
+    - Use glob to find files by name pattern (e.g. '**/*.rs') — faster and safer than shell find\n\
+    - Use grep to search file contents by regex — faster and safer than shell grep\n\
+    - Use web_fetch to read web pages or API docs (requires confirmation since it makes network requests)\n\
+    - When editing files, show only the minimal diff needed\n\
+    - For shell commands, prefer simple composable commands over complex one-liners\n\
+    - Consider the operating system when providing paths and commands\n\
+    - Be aware of the current working directory for relative paths\n\
+    - After making code changes, compile/build to verify they work\n\
+    </tool_guidelines>\n\
+    \n\
+    <git_guidelines>\n\
+    COMMIT DISCIPLINE:\n\
+    - Make atomic commits: one logical change per commit. Don't bundle unrelated changes.\n\
+    - Always verify the build passes (compile, tests) BEFORE committing.\n\
+    - Use conventional commit prefixes: feat:, fix:, refactor:, docs:, test:, chore:\n\
+    - Write a concise summary line (<72 chars). For complex changes, add a blank line \
+    then a body explaining what and why.\n\
+    - Stage with `git add -A` unless selectively staging specific files.\n\
+    - Push after committing unless the user says otherwise.\n\
+    \n\
+    WORKFLOW:\n\
+    - Check `git status` before starting work to understand the current state.\n\
+    - Don't amend or force-push unless explicitly asked.\n\
+    - When making multiple related changes, commit after each logical step — not all \
+    at the end.\n\
+    - If a build or test fails after changes, fix it before committing.\n\
+    </git_guidelines>\n\
+    \n\
+    <response_format>\n\
+    FOR QUICK TASKS: Just do it. Minimal or no explanation.\n\
+    FOR CODE CHANGES: Brief rationale (1-2 sentences), then the code.\n\
+    FOR COMPLEX TASKS: Break into steps, execute each one, report results.\n\
+    FOR ANALYSIS/REVIEW: Be thorough — examine deeply, consider edge cases, provide \
+    actionable recommendations.\n\
+    AFTER TOOL USE: When you've already written files or executed commands via tools, \
+    do NOT repeat the file contents or command output in your response. The user already \
+    saw the diffs and results. Just summarize what was done in 1-2 sentences.\n\
+    ALWAYS: Use markdown code blocks with language tags. Don't use headers unless \
+    multi-step. Don't bold excessively. Bullet points only for genuinely parallel items.\n\
+    </response_format>\n\
+    \n\
+    <rules>\n\
+    - Never include secrets or API keys in code unless explicitly asked\n\
+    - Substitute PII with generic placeholders\n\
+    - Do not modify or remove tests unless explicitly requested\n\
+    - Do not add tests unless explicitly requested\n\
+    - Decline requests for malicious code\n\
+    - When uncertain, ask for clarification rather than guessing\n\
+    </rules>";
+
+/// Standing hint while `policy_check` is in the toolset.
+const POLICY_CHECK_HINT: &str = "Before an fs_write, or an execute_bash command you are unsure \
+about, call policy_check to learn whether it is allowed, needs the user's approval or is denied, \
+and pick a permitted path or command instead of retrying refused calls.";
+
+const FALLBACK_INSTRUCTION: &str = "You are Zavora, an AI assistant in the user's terminal. Be concise and direct. \
+     Prioritize actionable output. When planning work, prefer release-oriented increments.";
+
+/// Host details embedded in the system instruction. Injected so renders are
+/// reproducible in tests.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PromptEnvironment {
+    pub os_name: String,
+    pub cwd: String,
+    pub shell: String,
+}
+
+impl PromptEnvironment {
+    pub fn current() -> Self {
+        Self {
+            os_name: std::env::consts::OS.to_string(),
+            cwd: std::env::current_dir()
+                .map(|p| p.display().to_string())
+                .unwrap_or_else(|_| ".".to_string()),
+            shell: std::env::var("SHELL").unwrap_or_else(|_| "sh".to_string()),
+        }
+    }
+}
+
+/// One contiguous piece of the prompt and the feature that contributed it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PromptSection {
+    pub source: &'static str,
+    pub text: String,
+    /// Bytes a cap cut from this section's input (e.g. `retrieval_max_chars`).
+    #[serde(skip_serializing_if = "is_zero")]
+    pub truncated_bytes: usize,
+    /// Documents the retrieval section quotes, for answer provenance.
+    #[serde(skip)]
+    pub chunk_sources: Vec<String>,
+}
+
+fn is_zero(value: &usize) -> bool {
+    *value == 0
+}
+
+impl PromptSection {
+    fn new(source: &'static str, text: impl Into<String>) -> Self {
+        Self {
+            source,
+            text: text.into(),
+            truncated_bytes: 0,
+            chunk_sources: Vec::new(),
+        }
+    }
+}
+
+/// System instruction sections, joined with a blank line when sent.
+pub fn system_instruction_sections(
+    runtime_cfg: Option<&RuntimeConfig>,
+    env: &PromptEnvironment,
+) -> Vec<PromptSection> {
+    let Some(cfg) = runtime_cfg else {
+        return vec![PromptSection::new("base", FALLBACK_INSTRUCTION)];
+    };
+    let PromptEnvironment {
+        os_name,
+        cwd,
+        shell,
+    } = env;
+
+    let mut sections = vec![
+        PromptSection::new(
+            "base",
+            format!(
+                "You are Zavora, an AI assistant in the user's terminal. You help with coding, \
+                 debugging, system administration, writing, analysis, and any professional task.\n\
+                 \n\
+                 <system_context>\n\
+                 - Operating System: {os_name}\n\
+                 - Current Directory: {cwd}\n\
+                 - Shell: {shell}\n\
+                 </system_context>\n\
+                 \n\
+                 <operational_directives>\n\
+                 EXECUTE IMMEDIATELY. When the user asks you to do something, do it. Don't narrate \
+                 what you would do — use your tools and produce the result.\n\
+                 OUTPUT FIRST. Lead with code, results, or actions. Explanations come after, and only \
+                 if needed.\n\
+                 ZERO FLUFF. No philosophical preambles, no unsolicited advice, no filler. Every \
+                 sentence must earn its place.\n\
+                 STAY FOCUSED. Answer what was asked. Don't wander into tangents or related topics \
+                 unless directly relevant.\n\
+                 </operational_directives>\n\
+                 "
+            ),
+        ),
+        PromptSection::new("orchestrator", ORCHESTRATOR_INSTRUCTION),
+        PromptSection::new("guidelines", SYSTEM_GUIDELINES),
+    ];
+    if ToolPolicy::from_config(cfg).is_tool_available(POLICY_CHECK_TOOL_NAME, ToolOrigin::BuiltIn) {
+        sections.push(PromptSection::new("policy-check", POLICY_CHECK_HINT));
+    }
+    if let Some(agent_instruction) = cfg
+        .agent_instruction
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+    {
+        sections.push(PromptSection::new(
+            "agent-instruction",
+            format!("Agent-specific instruction:\n{agent_instruction}"),
+        ));
+    }
+    if !cfg.agent_resource_paths.is_empty() {
+        sections.push(PromptSection::new(
+            "agent-resources",
+            format!(
+                "Agent resource hints:\n{}",
+                cfg.agent_resource_paths
+                    .iter()
+                    .map(|path| format!("- {}", path))
+                    .collect::<Vec<String>>()
+                    .join("\n")
+            ),
+        ));
+    }
+    sections
+}
+
+pub fn render_system_instruction(
+    runtime_cfg: Option<&RuntimeConfig>,
+    env: &PromptEnvironment,
+) -> String {
+    join_system_sections(&system_instruction_sections(runtime_cfg, env))
+}
+
+fn join_system_sections(sections: &[PromptSection]) -> String {
+    sections
+        .iter()
+        .map(|section| section.text.as_str())
+        .collect::<Vec<&str>>()
+        .join(SYSTEM_SECTION_SEPARATOR)
+}
+
+/// User content sections: the retrieval preamble (when any chunk qualifies)
+/// followed by the prompt. Concatenated without separators when sent.
+pub fn user_content_sections(
+    retrieval: &dyn RetrievalService,
+    prompt: &str,
+    prompt_source: &'static str,
+    policy: RetrievalPolicy,
+) -> Result<Vec<PromptSection>> {
+    let (chunks, truncated_bytes) =
+        select_retrieval_chunks_with_truncation(retrieval, prompt, policy)?;
+    Ok(sections_for_chunks(
+        retrieval,
+        &chunks,
+        truncated_bytes,
+        prompt,
+        prompt_source,
+    ))
+}
+
+/// [`user_content_sections`] with the retrieved chunks screened for prompt
+/// injection (`content_injection_mode`) first. `prompt` is never screened.
+pub fn screened_user_content_sections(
+    cfg: &RuntimeConfig,
+    telemetry: &TelemetrySink,
+    retrieval: &dyn RetrievalService,
+    prompt: &str,
+    prompt_source: &'static str,
+) -> Result<Vec<PromptSection>> {
+    let (chunks, truncated_bytes) =
+        select_retrieval_chunks_with_truncation(retrieval, prompt, retrieval_policy(cfg))?;
+    let chunks = screen_retrieved_chunks(cfg, telemetry, chunks);
+    Ok(sections_for_chunks(
+        retrieval,
+        &chunks,
+        truncated_bytes,
+        prompt,
+        prompt_source,
+    ))
+}
+
+fn sections_for_chunks(
+    retrieval: &dyn RetrievalService,
+    chunks: &[RetrievedChunk],
+    truncated_bytes: usize,
+    prompt: &str,
+    prompt_source: &'static str,
+) -> Vec<PromptSection> {
+    let mut sections = Vec::new();
+    let note = retrieval.query_note();
+    if let Some(context) = render_retrieval_context_with_note(chunks, note.as_deref()) {
+        let mut chunk_sources = Vec::<String>::new();
+        for chunk in chunks {
+            if !chunk_sources.contains(&chunk.source) {
+                chunk_sources.push(chunk.source.clone());
+            }
+        }
+        sections.push(PromptSection {
+            truncated_bytes,
+            chunk_sources,
+            ..PromptSection::new("retrieval", format!("{context}\nUser request:\n"))
+        });
+    }
+    sections.push(PromptSection::new(prompt_source, prompt));
+    sections
+}
+
+/// Put the remembered-facts section (`memory_backend = local`) ahead of the
+/// rest of the user content. Without a retrieval section it carries the
+/// `User request:` lead-in itself.
+pub fn with_memory_section(
+    cfg: &RuntimeConfig,
+    telemetry: &TelemetrySink,
+    prompt: &str,
+    mut sections: Vec<PromptSection>,
+) -> Vec<PromptSection> {
+    let Some(facts) = recall_for_prompt(cfg, telemetry, prompt) else {
+        return sections;
+    };
+    let lead_in = if sections.iter().any(|section| section.source == "retrieval") {
+        "\n"
+    } else {
+        "\nUser request:\n"
+    };
+    sections.insert(0, PromptSection::new("memory", format!("{facts}{lead_in}")));
+    sections
+}
+
+/// Run the retrieval guardrail over the retrieval section alone, so a
+/// redaction never touches the user's prompt. The prompt itself has already
+/// been through the input guardrail.
+pub fn guard_retrieval_sections(
+    cfg: &RuntimeConfig,
+    telemetry: &TelemetrySink,
+    sections: Vec<PromptSection>,
+) -> Result<Vec<PromptSection>> {
+    sections
+        .into_iter()
+        .map(|section| {
+            if section.source != "retrieval" {
+                return Ok(section);
+            }
+            let guarded = apply_retrieval_guardrail(cfg, telemetry, &section.text)?;
+            if guarded == section.text {
+                return Ok(section);
+            }
+            Ok(PromptSection {
+                source: "guardrail-redacted-retrieval",
+                text: guarded,
+                ..section
+            })
+        })
+        .collect()
+}
+
+pub fn render_user_content(sections: &[PromptSection]) -> String {
+    sections
+        .iter()
+        .map(|section| section.text.as_str())
+        .collect()
+}
+
+/// Everything the runner would send for one prompt.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AssembledPrompt {
+    pub system_sections: Vec<PromptSection>,
+    pub user_sections: Vec<PromptSection>,
+    pub system_instruction: String,
+    pub user_content: String,
+}
+
+pub fn retrieval_policy(cfg: &RuntimeConfig) -> RetrievalPolicy {
+    RetrievalPolicy {
+        max_chunks: cfg.retrieval_max_chunks,
+        max_chars: cfg.retrieval_max_chars,
+        min_score: cfg.retrieval_min_score,
+    }
+}
+
+/// Assemble the full prompt for `prompt` (template already rendered): input
+/// guardrail, then memory recall, retrieval and the retrieval guardrail,
+/// alongside the system instruction. Fails the same way the real path does
+/// when the input guardrail blocks.
+pub fn assemble_prompt(
+    cfg: &RuntimeConfig,
+    env: &PromptEnvironment,
+    prompt: &str,
+    prompt_source: &'static str,
+    retrieval: &dyn RetrievalService,
+    telemetry: &TelemetrySink,
+) -> Result<AssembledPrompt> {
+    let guarded = apply_guardrail(cfg, telemetry, "input", cfg.guardrail_input_mode, prompt)?;
+    let prompt_source = if guarded != prompt {
+        "guardrail-redacted-prompt"
+    } else {
+        prompt_source
+    };
+    let system_sections = system_instruction_sections(Some(cfg), env);
+    let user_sections = guard_retrieval_sections(
+        cfg,
+        telemetry,
+        with_memory_section(
+            cfg,
+            telemetry,
+            &guarded,
+            screened_user_content_sections(cfg, telemetry, retrieval, &guarded, prompt_source)?,
+        ),
+    )?;
+    Ok(AssembledPrompt {
+        system_instruction: join_system_sections(&system_sections),
+        user_content: render_user_content(&user_sections),
+        system_sections,
+        user_sections,
+    })
+}
+
+/// Size of one prompt component and what its cap cut.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BudgetEntry {
+    /// `system` or `user`.
+    pub part: &'static str,
+    pub source: &'static str,
+    pub bytes: usize,
+    pub est_tokens: usize,
+    pub truncated_bytes: usize,
+}
+
+/// Where the prompt's bytes went. Entries (including the blank lines joining
+/// system sections) sum to the bytes of the system instruction plus the
+/// user content.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ContextBudget {
+    pub entries: Vec<BudgetEntry>,
+    pub total_bytes: usize,
+    pub est_tokens: usize,
+    pub truncated_bytes: usize,
+}
+
+const SYSTEM_SECTION_SEPARATOR: &str = "\n\n";
+
+pub fn context_budget(
+    system_sections: &[PromptSection],
+    user_sections: &[PromptSection],
+) -> ContextBudget {
+    let entry = |part, section: &PromptSection| BudgetEntry {
+        part,
+        source: section.source,
+        bytes: section.text.len(),
+        est_tokens: estimate_tokens(section.text.chars().count()),
+        truncated_bytes: section.truncated_bytes,
+    };
+    let mut entries = system_sections
+        .iter()
+        .map(|section| entry("system", section))
+        .collect::<Vec<BudgetEntry>>();
+    if system_sections.len() > 1 {
+        let bytes = SYSTEM_SECTION_SEPARATOR.len() * (system_sections.len() - 1);
+        entries.push(BudgetEntry {
+            part: "system",
+            source: "separators",
+            bytes,
+            est_tokens: estimate_tokens(bytes),
+            truncated_bytes: 0,
+        });
+    }
+    entries.extend(user_sections.iter().map(|section| entry("user", section)));
+
+    let total_chars = system_sections
+        .iter()
+        .chain(user_sections)
+        .map(|section| section.text.chars().count())
+        .sum::<usize>();
+    ContextBudget {
+        total_bytes: entries.iter().map(|entry| entry.bytes).sum(),
+        est_tokens: estimate_tokens(total_chars),
+        truncated_bytes: entries.iter().map(|entry| entry.truncated_bytes).sum(),
+        entries,
+    }
+}
+
+impl ContextBudget {
+    pub fn to_value(&self) -> Value {
+        serde_json::to_value(self).unwrap_or(Value::Null)
+    }
+
+    /// `/v1/ask?debug=true` form: totals plus bytes per `part.source`.
+    pub fn compact(&self) -> Value {
+        let mut bytes = serde_json::Map::new();
+        let mut truncated = serde_json::Map::new();
+        for entry in &self.entries {
+            let key = format!("{}.{}", entry.part, entry.source);
+            bytes.insert(key.clone(), json!(entry.bytes));
+            if entry.truncated_bytes > 0 {
+                truncated.insert(key, json!(entry.truncated_bytes));
+            }
+        }
+        json!({
+            "total_bytes": self.total_bytes,
+            "est_tokens": self.est_tokens,
+            "truncated_bytes": self.truncated_bytes,
+            "bytes": bytes,
+            "truncated": truncated
+        })
+    }
+
+    /// `--show-context-budget` table.
+    pub fn render_table(&self) -> String {
+        let mut out = format!(
+            "{:<7} {:<20} {:>8} {:>8} {:>10}\n",
+            "part", "source", "bytes", "~tokens", "truncated"
+        );
+        for entry in &self.entries {
+            out.push_str(&format!(
+                "{:<7} {:<20} {:>8} {:>8} {:>10}\n",
+                entry.part, entry.source, entry.bytes, entry.est_tokens, entry.truncated_bytes
+            ));
+        }
+        out.push_str(&format!(
+            "{:<7} {:<20} {:>8} {:>8} {:>10}\n",
+            "total", "", self.total_bytes, self.est_tokens, self.truncated_bytes
+        ));
+        out
+    }
+}
+
+impl AssembledPrompt {
+    pub fn budget(&self) -> ContextBudget {
+        context_budget(&self.system_sections, &self.user_sections)
+    }
+}
+
+/// Human-readable render with `----- [source] -----` markers before each
+/// section.
+pub fn render_prompt_with_markers(prompt: &AssembledPrompt) -> String {
+    let mut out = String::from("===== SYSTEM INSTRUCTION =====\n");
+    for section in &prompt.system_sections {
+        out.push_str(&format!(
+            "----- [{}] -----\n{}\n",
+            section.source, section.text
+        ));
+    }
+    out.push_str("===== USER CONTENT =====\n");
+    for section in &prompt.user_sections {
+        out.push_str(&format!(
+            "----- [{}] -----\n{}\n",
+            section.source, section.text
+        ));
+    }
+    out
+}
+
+pub fn run_debug_render_prompt(
+    cfg: &RuntimeConfig,
+    prompt: &str,
+    prompt_source: &'static str,
+    retrieval: &dyn RetrievalService,
+    telemetry: &TelemetrySink,
+    json: bool,
+) -> Result<()> {
+    enforce_prompt_limit(prompt, cfg.max_prompt_chars)?;
+    let assembled = assemble_prompt(
+        cfg,
+        &PromptEnvironment::current(),
+        prompt,
+        prompt_source,
+        retrieval,
+        telemetry,
+    )?;
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&assembled).context("failed to serialize prompt")?
+        );
+    } else {
+        print!("{}", render_prompt_with_markers(&assembled));
+    }
+    Ok(())
+}
@@ -0,0 +1,98 @@
+use std::io::Read;
+
+use anyhow::{Context, Result};
+
+/// Default cap for prompts piped through stdin (1 MiB).
+pub const DEFAULT_STDIN_MAX_BYTES: usize = 1024 * 1024;
+
+/// Positional argument that asks `ask` to read its prompt from stdin.
+pub const STDIN_SENTINEL: &str = "-";
+
+/// Placeholder a `--template` file uses to receive the stdin body.
+pub const TEMPLATE_STDIN_VAR: &str = "{{stdin}}";
+
+/// Placeholder a `--template` file uses to receive the positional args.
+pub const TEMPLATE_ARGS_VAR: &str = "{{args}}";
+
+/// Read the whole of `reader` as a UTF-8 prompt. Errors on empty input,
+/// on input larger than `max_bytes`, and on non-UTF-8 bytes instead of
+/// converting lossily.
+pub fn read_stdin_prompt<R: Read>(reader: R, max_bytes: usize) -> Result<String> {
+    let mut bytes = Vec::new();
+    reader
+        .take(max_bytes as u64 + 1)
+        .read_to_end(&mut bytes)
+        .context("failed to read prompt from stdin")?;
+    if bytes.len() > max_bytes {
+        return Err(anyhow::anyhow!(
+            "stdin prompt exceeds maximum size ({max_bytes} bytes); raise --stdin-max-bytes to allow larger input"
+        ));
+    }
+    let text = String::from_utf8(bytes).map_err(|err| {
+        anyhow::anyhow!(
+            "stdin prompt is not valid UTF-8 (invalid byte at offset {})",
+            err.utf8_error().valid_up_to()
+        )
+    })?;
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(anyhow::anyhow!(
+            "stdin prompt is empty; pipe content into `ask -` or pass the prompt as arguments"
+        ));
+    }
+    Ok(text.to_string())
+}
+
+/// Build the final `ask` prompt from positional args, optional stdin and an
+/// optional template body. A `-` arg or `use_stdin` reads stdin; remaining
+/// args become a prefix separated from the stdin body by a blank line. With a
+/// template, `{{args}}` and `{{stdin}}` are substituted instead.
+pub fn resolve_ask_prompt<R: Read>(
+    args: &[String],
+    use_stdin: bool,
+    template: Option<&str>,
+    stdin: R,
+    max_bytes: usize,
+) -> Result<String> {
+    let reads_stdin = use_stdin || args.iter().any(|arg| arg == STDIN_SENTINEL);
+    let prefix = args
+        .iter()
+        .filter(|arg| arg.as_str() != STDIN_SENTINEL)
+        .map(String::as_str)
+        .collect::<Vec<&str>>()
+        .join(" ");
+    let body = if reads_stdin {
+        Some(read_stdin_prompt(stdin, max_bytes)?)
+    } else {
+        None
+    };
+
+    if let Some(template) = template {
+        if body.is_some() && !template.contains(TEMPLATE_STDIN_VAR) {
+            return Err(anyhow::anyhow!(
+                "failed to read input template: stdin was supplied but the template has no {TEMPLATE_STDIN_VAR} placeholder"
+            ));
+        }
+        let rendered = template
+            .replace(TEMPLATE_ARGS_VAR, &prefix)
+            .replace(TEMPLATE_STDIN_VAR, body.as_deref().unwrap_or_default());
+        return Ok(rendered.trim().to_string());
+    }
+
+    let prompt = match body {
+        Some(body) if prefix.trim().is_empty() => body,
+        Some(body) => format!("{}\n\n{body}", prefix.trim()),
+        None => prefix,
+    };
+    if prompt.trim().is_empty() {
+        return Err(anyhow::anyhow!(
+            "failed to read input: prompt is empty; pass prompt arguments or use `ask -`"
+        ));
+    }
+    Ok(prompt)
+}
+
+/// Load a `--template` file for `ask`.
+pub fn load_prompt_template(path: &str) -> Result<String> {
+    std::fs::read_to_string(path).with_context(|| format!("failed to read input template '{path}'"))
+}
@@ -0,0 +1,229 @@
+//! Answer provenance: the run, model, retrieval sources, tools and guardrail
+//! modes behind a saved answer, so a file found months later can be traced
+//! back to the run that produced it.
+//!
+//! Every [`TelemetrySink`] carries a [`ProvenanceRecorder`] that sees the
+//! events `run_prompt*` already emit (`model.resolved`, `retrieval.sources`,
+//! `tool.requested`), whether or not telemetry is written. [`Provenance`]
+//! combines that with the runtime config when an answer is saved:
+//!
+//! - markdown files get an HTML comment footer;
+//! - other files get a sibling `<file>.provenance.json`;
+//! - JSON documents (transcripts) get a `provenance` field.
+//!
+//! Strings pass through the telemetry redactor before they are written.
+//! `--no-provenance` turns all of it off.
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::config::RuntimeConfig;
+use crate::guardrail::guardrail_mode_label;
+use crate::telemetry::{TelemetryRedactor, TelemetrySink, scrub_telemetry_value};
+use crate::ui::Ui;
+
+pub const PROVENANCE_FORMAT: &str = "zavora-provenance/v1";
+/// First line of the markdown footer.
+pub const PROVENANCE_FOOTER_OPEN: &str = "<!-- zavora-provenance";
+pub const PROVENANCE_SIDECAR_SUFFIX: &str = ".provenance.json";
+
+#[derive(Debug, Default)]
+struct ProvenanceLog {
+    provider: Option<String>,
+    model: Option<String>,
+    retrieval_sources: Vec<String>,
+    tools: Vec<String>,
+}
+
+fn push_unique(list: &mut Vec<String>, value: &str) {
+    if !list.iter().any(|existing| existing == value) {
+        list.push(value.to_string());
+    }
+}
+
+/// Collects what one run's telemetry events say about the answer. Clones
+/// share the log.
+#[derive(Debug, Clone, Default)]
+pub struct ProvenanceRecorder(Arc<Mutex<ProvenanceLog>>);
+
+impl ProvenanceRecorder {
+    pub fn record(&self, event: &str, payload: &Value) {
+        let mut log = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let text = |key: &str| payload.get(key).and_then(Value::as_str);
+        match event {
+            "model.resolved" => {
+                log.provider = text("provider").map(str::to_string);
+                log.model = text("model").map(str::to_string);
+            }
+            "retrieval.sources" => {
+                for source in payload["sources"].as_array().into_iter().flatten() {
+                    if let Some(source) = source.as_str() {
+                        push_unique(&mut log.retrieval_sources, source);
+                    }
+                }
+            }
+            "tool.requested" => {
+                if let Some(tool) = text("tool") {
+                    push_unique(&mut log.tools, tool);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProvenanceGuardrails {
+    pub input: String,
+    pub output: String,
+    pub retrieval: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Provenance {
+    pub format: String,
+    /// Telemetry `run_id`, the key for finding the run's events.
+    pub run_id: String,
+    pub generated_at: String,
+    pub command: String,
+    pub profile: String,
+    pub agent: String,
+    pub session_id: String,
+    pub provider: String,
+    pub model: String,
+    pub retrieval_backend: String,
+    /// Documents retrieved context was quoted from, in first-use order.
+    pub retrieval_sources: Vec<String>,
+    /// Tools the model called, in first-call order.
+    pub tools: Vec<String>,
+    pub guardrails: ProvenanceGuardrails,
+}
+
+impl Provenance {
+    /// Provenance for the run `telemetry` belongs to. The provider and model
+    /// come from `model.resolved` when it was emitted, else from `cfg`.
+    pub fn for_run(cfg: &RuntimeConfig, telemetry: &TelemetrySink) -> Self {
+        let log = telemetry
+            .provenance
+            .0
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        Self {
+            format: PROVENANCE_FORMAT.to_string(),
+            run_id: telemetry.run_id.clone(),
+            generated_at: chrono::Utc::now().to_rfc3339(),
+            command: telemetry.command.clone(),
+            profile: cfg.profile.clone(),
+            agent: cfg.agent_name.clone(),
+            session_id: cfg.session_id.clone(),
+            provider: log
+                .provider
+                .clone()
+                .unwrap_or_else(|| format!("{:?}", cfg.provider).to_ascii_lowercase()),
+            model: log
+                .model
+                .clone()
+                .or_else(|| cfg.model.clone())
+                .unwrap_or_else(|| "default".to_string()),
+            retrieval_backend: format!("{:?}", cfg.retrieval_backend).to_ascii_lowercase(),
+            retrieval_sources: log.retrieval_sources.clone(),
+            tools: log.tools.clone(),
+            guardrails: ProvenanceGuardrails {
+                input: guardrail_mode_label(cfg.guardrail_input_mode).to_string(),
+                output: guardrail_mode_label(cfg.guardrail_output_mode).to_string(),
+                retrieval: guardrail_mode_label(cfg.guardrail_retrieval_mode).to_string(),
+            },
+        }
+    }
+
+    /// The JSON that gets written: every string redacted like a telemetry
+    /// field and capped.
+    pub fn to_redacted_value(&self, cfg: &RuntimeConfig) -> Value {
+        let value = serde_json::to_value(self).unwrap_or_default();
+        let redactor = TelemetryRedactor::for_config(cfg);
+        scrub_telemetry_value(&value, redactor.is_some(), redactor.as_ref())
+    }
+}
+
+/// `None` under `--no-provenance`.
+pub fn answer_provenance(cfg: &RuntimeConfig, telemetry: &TelemetrySink) -> Option<Value> {
+    cfg.provenance
+        .then(|| Provenance::for_run(cfg, telemetry).to_redacted_value(cfg))
+}
+
+pub fn is_markdown_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| matches!(ext.to_ascii_lowercase().as_str(), "md" | "markdown"))
+}
+
+/// `answer.txt` → `answer.txt.provenance.json`.
+pub fn provenance_sidecar_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(PROVENANCE_SIDECAR_SUFFIX);
+    PathBuf::from(name)
+}
+
+/// HTML comment block appended to markdown answers. `-->` inside a value is
+/// escaped as `--\u003e` so it cannot close the comment early.
+pub fn provenance_markdown_footer(provenance: &Value) -> String {
+    let json = serde_json::to_string_pretty(provenance)
+        .unwrap_or_default()
+        .replace("-->", "--\\u003e");
+    format!("\n{PROVENANCE_FOOTER_OPEN}\n{json}\n-->\n")
+}
+
+/// Write `answer` to `path` with its provenance: a footer for markdown, a
+/// sidecar file otherwise. Returns every file written.
+pub fn write_answer_file(
+    path: &Path,
+    answer: &str,
+    provenance: Option<&Value>,
+) -> Result<Vec<PathBuf>> {
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    let mut body = answer.to_string();
+    if !body.ends_with('\n') {
+        body.push('\n');
+    }
+    let markdown = is_markdown_path(path);
+    if let Some(provenance) = provenance.filter(|_| markdown) {
+        body.push_str(&provenance_markdown_footer(provenance));
+    }
+    std::fs::write(path, body).with_context(|| format!("failed to write {}", path.display()))?;
+    let mut written = vec![path.to_path_buf()];
+    if let Some(provenance) = provenance.filter(|_| !markdown) {
+        let sidecar = provenance_sidecar_path(path);
+        let text =
+            serde_json::to_string_pretty(provenance).context("failed to serialize provenance")?;
+        std::fs::write(&sidecar, format!("{text}\n"))
+            .with_context(|| format!("failed to write {}", sidecar.display()))?;
+        written.push(sidecar);
+    }
+    Ok(written)
+}
+
+/// `ask --output-file`: save the answer as printed and say where it went.
+pub fn save_answer_output(
+    cfg: &RuntimeConfig,
+    telemetry: &TelemetrySink,
+    path: &str,
+    answer: &str,
+) -> Result<()> {
+    let provenance = answer_provenance(cfg, telemetry);
+    let written = write_answer_file(Path::new(path), answer, provenance.as_ref())?;
+    let written = written
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect::<Vec<String>>();
+    Ui::for_config(cfg).notice(&format!("Saved answer to {}", written.join(" and ")));
+    Ok(())
+}
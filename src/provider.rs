@@ -1,11 +1,28 @@
 use clap::ValueEnum;
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
+use adk_rust::model::openai::{AzureConfig, AzureOpenAIClient};
 use adk_rust::prelude::*;
 use anyhow::{Context, Result};
 
 use crate::cli::Provider;
-use crate::config::RuntimeConfig;
+use crate::config::{ProviderOverrideConfig, RuntimeConfig};
+use crate::fake_provider::{FAKE_MODEL_NAME, FakeLlm};
+
+/// `api-version` sent to Azure OpenAI when the profile sets none.
+pub const AZURE_OPENAI_DEFAULT_API_VERSION: &str = "2024-10-21";
+pub const AZURE_OPENAI_API_KEY_ENV: &str = "AZURE_OPENAI_API_KEY";
+/// Read when set; servers such as vLLM and LM Studio often need no key.
+pub const OPENAI_COMPATIBLE_API_KEY_ENV: &str = "OPENAI_COMPATIBLE_API_KEY";
+
+/// Azure deployment names: letters, digits, `-`, `_` and `.`.
+fn is_azure_deployment_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
 
 pub fn validate_model_for_provider(provider: Provider, model_name: &str) -> Result<()> {
     let is_valid = match provider {
@@ -18,13 +35,22 @@ pub fn validate_model_for_provider(provider: Provider, model_name: &str) -> Resu
         Provider::Anthropic => model_name.starts_with("claude"),
         Provider::Deepseek => model_name.starts_with("deepseek"),
         Provider::Groq => !model_name.trim().is_empty(),
-        Provider::Ollama => !model_name.trim().is_empty(),
+        Provider::Ollama | Provider::OpenaiCompatible | Provider::Fake => {
+            !model_name.trim().is_empty()
+        }
+        Provider::AzureOpenai => is_azure_deployment_name(model_name),
         Provider::Auto => true,
     };
 
     if is_valid {
         return Ok(());
     }
+    if provider == Provider::AzureOpenai {
+        return Err(anyhow::anyhow!(
+            "'{model_name}' is not a valid Azure OpenAI deployment name (letters, digits, '-', \
+             '_' and '.')"
+        ));
+    }
 
     Err(anyhow::anyhow!(
         "model '{}' is not compatible with provider '{:?}'",
@@ -33,6 +59,299 @@ pub fn validate_model_for_provider(provider: Provider, model_name: &str) -> Resu
     ))
 }
 
+/// Split `provider/model` shorthand such as `openai/gpt-4.1`. Only a known
+/// provider name before the first `/` counts, so model ids that contain a
+/// slash themselves (`meta-llama/llama-4-scout` on Groq) pass through whole.
+pub fn split_model_shorthand(value: &str) -> Result<(Option<Provider>, String)> {
+    let value = value.trim();
+    if let Some((prefix, model)) = value.split_once('/')
+        && let Ok(provider) = Provider::from_str(prefix.trim(), true)
+        && provider != Provider::Auto
+    {
+        let model = model.trim();
+        if model.is_empty() {
+            return Err(anyhow::anyhow!(
+                "model shorthand '{value}' has no model after '/'; expected provider/model"
+            ));
+        }
+        return Ok((Some(provider), model.to_string()));
+    }
+    Ok((None, value.to_string()))
+}
+
+/// Which gateway overrides each provider's adk client config accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProviderOverrideSupport {
+    pub base_url: bool,
+    pub extra_headers: bool,
+    /// `deployment` and `api_version` (Azure only).
+    pub deployment: bool,
+    pub api_key_env: bool,
+}
+
+pub fn provider_override_support(provider: Provider) -> ProviderOverrideSupport {
+    match provider {
+        Provider::Openai | Provider::Anthropic | Provider::Ollama => ProviderOverrideSupport {
+            base_url: true,
+            extra_headers: false,
+            deployment: false,
+            api_key_env: false,
+        },
+        Provider::AzureOpenai => ProviderOverrideSupport {
+            base_url: true,
+            extra_headers: false,
+            deployment: true,
+            api_key_env: true,
+        },
+        Provider::OpenaiCompatible => ProviderOverrideSupport {
+            base_url: true,
+            extra_headers: false,
+            deployment: false,
+            api_key_env: true,
+        },
+        Provider::Gemini
+        | Provider::Deepseek
+        | Provider::Groq
+        | Provider::Fake
+        | Provider::Auto => {
+            ProviderOverrideSupport {
+                base_url: false,
+                extra_headers: false,
+                deployment: false,
+                api_key_env: false,
+            }
+        }
+    }
+}
+
+/// Where `provider` reads its API key when the profile names no
+/// `api_key_env`.
+pub fn default_api_key_env(provider: Provider) -> Option<&'static str> {
+    match provider {
+        Provider::Gemini => Some("GOOGLE_API_KEY"),
+        Provider::Openai => Some("OPENAI_API_KEY"),
+        Provider::Anthropic => Some("ANTHROPIC_API_KEY"),
+        Provider::Deepseek => Some("DEEPSEEK_API_KEY"),
+        Provider::Groq => Some("GROQ_API_KEY"),
+        Provider::AzureOpenai => Some(AZURE_OPENAI_API_KEY_ENV),
+        Provider::OpenaiCompatible => Some(OPENAI_COMPATIBLE_API_KEY_ENV),
+        Provider::Ollama | Provider::Fake | Provider::Auto => None,
+    }
+}
+
+pub fn default_model_name(provider: Provider) -> &'static str {
+    match provider {
+        Provider::Gemini => "gemini-2.5-flash",
+        Provider::Openai => "gpt-5-mini",
+        Provider::Anthropic => "claude-sonnet-4-20250514",
+        Provider::Deepseek => "deepseek-chat",
+        Provider::Groq => "llama-3.3-70b-versatile",
+        Provider::Ollama => "llama4",
+        Provider::Fake => FAKE_MODEL_NAME,
+        // No sensible default: the deployment or served model must be named.
+        Provider::AzureOpenai | Provider::OpenaiCompatible | Provider::Auto => "",
+    }
+}
+
+/// Everything `resolve_model` passes to a client constructor apart from the
+/// API key, so the profile plumbing can be checked without credentials.
+/// For Azure OpenAI `model_name` is the deployment and `base_url` the
+/// resource endpoint.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ProviderClientSettings {
+    pub provider: Provider,
+    pub model_name: String,
+    pub base_url: Option<String>,
+    pub extra_headers: BTreeMap<String, String>,
+    /// Azure `api-version`; `None` for other providers.
+    pub api_version: Option<String>,
+    /// Variable the API key is read from; `None` for Ollama and fake.
+    pub api_key_env: Option<String>,
+}
+
+impl ProviderClientSettings {
+    /// Chat completions URL for clients with a configured server, with the
+    /// Azure deployment and `api-version` applied.
+    pub fn chat_completions_url(&self) -> Option<String> {
+        let base_url = self.base_url.as_deref()?;
+        Some(match self.provider {
+            Provider::AzureOpenai => format!(
+                "{base_url}/openai/deployments/{}/chat/completions?api-version={}",
+                self.model_name,
+                self.api_version
+                    .as_deref()
+                    .unwrap_or(AZURE_OPENAI_DEFAULT_API_VERSION)
+            ),
+            _ => format!("{base_url}/chat/completions"),
+        })
+    }
+
+    /// Header the API key travels in: Azure uses `api-key` instead of a
+    /// bearer token.
+    pub fn auth_header(&self) -> &'static str {
+        match self.provider {
+            Provider::AzureOpenai => "api-key",
+            Provider::Anthropic => "x-api-key",
+            Provider::Gemini => "x-goog-api-key",
+            _ => "Authorization",
+        }
+    }
+}
+
+impl std::fmt::Debug for ProviderClientSettings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProviderClientSettings")
+            .field("provider", &self.provider)
+            .field("model_name", &self.model_name)
+            .field("base_url", &self.base_url)
+            .field(
+                "extra_headers",
+                &self.extra_headers.keys().collect::<Vec<&String>>(),
+            )
+            .field("api_version", &self.api_version)
+            .field("api_key_env", &self.api_key_env)
+            .finish()
+    }
+}
+
+/// Model and the profile's `providers.<provider>` overrides for an already
+/// resolved (non-auto) provider. Overrides the client cannot apply are an
+/// error rather than being silently dropped.
+pub fn provider_client_settings(
+    cfg: &RuntimeConfig,
+    provider: Provider,
+) -> Result<ProviderClientSettings> {
+    let overrides = cfg
+        .provider_overrides
+        .get(&provider)
+        .cloned()
+        .unwrap_or_default();
+    let label = provider_label(provider);
+    let support = provider_override_support(provider);
+    let trimmed = |value: Option<String>| {
+        value
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+
+    // `--model` (or `/model`) picks another deployment over the profile's.
+    let model_name = match provider {
+        Provider::AzureOpenai => trimmed(cfg.model.clone())
+            .or_else(|| trimmed(overrides.deployment.clone()))
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "profile '{}' uses azure-openai but names no deployment: set \
+                     providers.azure-openai.deployment or pass --model <deployment>",
+                    cfg.profile
+                )
+            })?,
+        Provider::OpenaiCompatible => trimmed(cfg.model.clone()).ok_or_else(|| {
+            anyhow::anyhow!(
+                "profile '{}' uses openai-compatible but names no model: set model or pass \
+                 --model <served model>",
+                cfg.profile
+            )
+        })?,
+        _ => cfg
+            .model
+            .clone()
+            .unwrap_or_else(|| default_model_name(provider).to_string()),
+    };
+    validate_model_for_provider(provider, &model_name)?;
+
+    let base_url = overrides
+        .base_url
+        .map(|url| url.trim().trim_end_matches('/').to_string())
+        .filter(|url| !url.is_empty());
+    if let Some(url) = &base_url {
+        if !support.base_url {
+            return Err(anyhow::anyhow!(
+                "profile '{}' sets providers.{label}.base_url, but the {label} client does not \
+                 support a custom base URL",
+                cfg.profile
+            ));
+        }
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            return Err(anyhow::anyhow!(
+                "profile '{}' has an invalid providers.{label}.base_url '{url}': expected an \
+                 http:// or https:// URL",
+                cfg.profile
+            ));
+        }
+    } else if matches!(
+        provider,
+        Provider::AzureOpenai | Provider::OpenaiCompatible
+    ) {
+        let hint = if provider == Provider::AzureOpenai {
+            "endpoint = \"https://<resource>.openai.azure.com\""
+        } else {
+            "base_url = \"http://localhost:8000/v1\""
+        };
+        return Err(anyhow::anyhow!(
+            "profile '{}' uses {label} but sets no endpoint: add {hint} under \
+             [profiles.{}.providers.{label}]",
+            cfg.profile,
+            cfg.profile
+        ));
+    }
+    if !overrides.extra_headers.is_empty() && !support.extra_headers {
+        let names = overrides
+            .extra_headers
+            .keys()
+            .cloned()
+            .collect::<Vec<String>>()
+            .join(", ");
+        return Err(anyhow::anyhow!(
+            "profile '{}' sets providers.{label}.extra_headers ({names}), but the {label} client \
+             does not support extra request headers",
+            cfg.profile
+        ));
+    }
+    let unsupported = [
+        ("deployment", overrides.deployment.is_some() && !support.deployment),
+        ("api_version", overrides.api_version.is_some() && !support.deployment),
+        ("api_key_env", overrides.api_key_env.is_some() && !support.api_key_env),
+    ];
+    if let Some((field, _)) = unsupported.iter().find(|(_, set)| *set) {
+        return Err(anyhow::anyhow!(
+            "profile '{}' sets providers.{label}.{field}, which only applies to azure-openai{}",
+            cfg.profile,
+            if *field == "api_key_env" {
+                " and openai-compatible"
+            } else {
+                ""
+            }
+        ));
+    }
+
+    let api_version = (provider == Provider::AzureOpenai).then(|| {
+        trimmed(overrides.api_version)
+            .unwrap_or_else(|| AZURE_OPENAI_DEFAULT_API_VERSION.to_string())
+    });
+    let api_key_env = trimmed(overrides.api_key_env)
+        .or_else(|| default_api_key_env(provider).map(str::to_string));
+
+    Ok(ProviderClientSettings {
+        provider,
+        model_name,
+        base_url,
+        extra_headers: overrides.extra_headers,
+        api_version,
+        api_key_env,
+    })
+}
+
+/// The key from `settings.api_key_env`, or an error naming the variable.
+fn required_api_key(settings: &ProviderClientSettings, name: &str) -> Result<String> {
+    let env = settings.api_key_env.as_deref().unwrap_or_default();
+    std::env::var(env)
+        .ok()
+        .filter(|key| !key.trim().is_empty())
+        .with_context(|| {
+            format!("{env} is required for {name}. Run 'zavora-cli setup' to configure.")
+        })
+}
+
 pub fn resolve_model(cfg: &RuntimeConfig) -> Result<(Arc<dyn Llm>, Provider, String)> {
     let provider = match cfg.provider {
         Provider::Auto => detect_provider().context(
@@ -42,75 +361,168 @@ pub fn resolve_model(cfg: &RuntimeConfig) -> Result<(Arc<dyn Llm>, Provider, Str
         )?,
         p => p,
     };
+    let settings = provider_client_settings(cfg, provider)?;
+    let model_name = settings.model_name.clone();
 
     match provider {
         Provider::Gemini => {
             let api_key = std::env::var("GOOGLE_API_KEY")
                 .context("GOOGLE_API_KEY is required for Gemini. Run 'zavora-cli setup' to configure.")?;
-            let model_name = cfg
-                .model
-                .clone()
-                .unwrap_or_else(|| "gemini-2.5-flash".to_string());
-            validate_model_for_provider(provider, &model_name)?;
             let model = GeminiModel::new(api_key, model_name.clone())?;
             Ok((Arc::new(model), provider, model_name))
         }
         Provider::Openai => {
             let api_key = std::env::var("OPENAI_API_KEY")
                 .context("OPENAI_API_KEY is required for OpenAI. Run 'zavora-cli setup' to configure.")?;
-            let model_name = cfg
-                .model
-                .clone()
-                .unwrap_or_else(|| "gpt-5-mini".to_string());
-            validate_model_for_provider(provider, &model_name)?;
-            let model = OpenAIClient::new(OpenAIConfig::new(api_key, model_name.clone()))?;
+            let config = match settings.base_url {
+                Some(base_url) => OpenAIConfig::compatible(api_key, base_url, model_name.clone()),
+                None => OpenAIConfig::new(api_key, model_name.clone()),
+            };
+            let model = OpenAIClient::new(config)?;
             Ok((Arc::new(model), provider, model_name))
         }
         Provider::Anthropic => {
             let api_key = std::env::var("ANTHROPIC_API_KEY")
                 .context("ANTHROPIC_API_KEY is required for Anthropic. Run 'zavora-cli setup' to configure.")?;
-            let model_name = cfg
-                .model
-                .clone()
-                .unwrap_or_else(|| "claude-sonnet-4-20250514".to_string());
-            validate_model_for_provider(provider, &model_name)?;
-            let model = AnthropicClient::new(AnthropicConfig::new(api_key, model_name.clone()))?;
+            let mut config = AnthropicConfig::new(api_key, model_name.clone());
+            if let Some(base_url) = settings.base_url {
+                config = config.with_base_url(base_url);
+            }
+            let model = AnthropicClient::new(config)?;
             Ok((Arc::new(model), provider, model_name))
         }
         Provider::Deepseek => {
             let api_key = std::env::var("DEEPSEEK_API_KEY")
                 .context("DEEPSEEK_API_KEY is required for DeepSeek. Run 'zavora-cli setup' to configure.")?;
-            let model_name = cfg
-                .model
-                .clone()
-                .unwrap_or_else(|| "deepseek-chat".to_string());
-            validate_model_for_provider(provider, &model_name)?;
             let model = DeepSeekClient::new(DeepSeekConfig::new(api_key, model_name.clone()))?;
             Ok((Arc::new(model), provider, model_name))
         }
         Provider::Groq => {
             let api_key = std::env::var("GROQ_API_KEY")
                 .context("GROQ_API_KEY is required for Groq. Run 'zavora-cli setup' to configure.")?;
-            let model_name = cfg
-                .model
-                .clone()
-                .unwrap_or_else(|| "llama-3.3-70b-versatile".to_string());
-            validate_model_for_provider(provider, &model_name)?;
             let model = GroqClient::new(GroqConfig::new(api_key, model_name.clone()))?;
             Ok((Arc::new(model), provider, model_name))
         }
         Provider::Ollama => {
-            let host = std::env::var("OLLAMA_HOST")
-                .unwrap_or_else(|_| "http://localhost:11434".to_string());
-            let model_name = cfg.model.clone().unwrap_or_else(|| "llama4".to_string());
-            validate_model_for_provider(provider, &model_name)?;
+            let host = settings.base_url.unwrap_or_else(|| {
+                std::env::var("OLLAMA_HOST")
+                    .unwrap_or_else(|_| "http://localhost:11434".to_string())
+            });
             let model = OllamaModel::new(OllamaConfig::with_host(host, model_name.clone()))?;
             Ok((Arc::new(model), provider, model_name))
         }
+        Provider::AzureOpenai => {
+            let api_key = required_api_key(&settings, "Azure OpenAI")?;
+            let config = AzureConfig::new(
+                api_key,
+                settings.base_url.unwrap_or_default(),
+                settings.api_version.unwrap_or_default(),
+                model_name.clone(),
+            );
+            let model = AzureOpenAIClient::new(config)?;
+            Ok((Arc::new(model), provider, model_name))
+        }
+        Provider::OpenaiCompatible => {
+            // Local servers usually accept any key; send an empty one when unset.
+            let api_key = settings
+                .api_key_env
+                .as_deref()
+                .and_then(|env| std::env::var(env).ok())
+                .unwrap_or_default();
+            let config = OpenAIConfig::compatible(
+                api_key,
+                settings.base_url.unwrap_or_default(),
+                model_name.clone(),
+            );
+            let model = OpenAIClient::new(config)?;
+            Ok((Arc::new(model), provider, model_name))
+        }
+        Provider::Fake => {
+            let model = FakeLlm::from_config(cfg, &model_name)?;
+            Ok((Arc::new(model), provider, model_name))
+        }
         Provider::Auto => unreachable!("auto provider must be resolved before matching"),
     }
 }
 
+/// The provider's name as written in config and on the command line.
+pub fn provider_label(provider: Provider) -> String {
+    provider
+        .to_possible_value()
+        .map(|value| value.get_name().to_string())
+        .unwrap_or_else(|| format!("{provider:?}").to_ascii_lowercase())
+}
+
+/// One line per provider with overrides: base URLs as configured, header
+/// names only (values are never printed).
+pub fn format_provider_overrides(
+    overrides: &BTreeMap<Provider, ProviderOverrideConfig>,
+) -> Vec<String> {
+    overrides
+        .iter()
+        .filter(|(_, config)| **config != ProviderOverrideConfig::default())
+        .map(|(provider, config)| {
+            let mut line = provider_label(*provider);
+            if let Some(url) = &config.base_url {
+                line.push_str(&format!(" base_url={url}"));
+            }
+            if let Some(deployment) = &config.deployment {
+                line.push_str(&format!(" deployment={deployment}"));
+            }
+            if let Some(api_version) = &config.api_version {
+                line.push_str(&format!(" api_version={api_version}"));
+            }
+            if let Some(env) = &config.api_key_env {
+                line.push_str(&format!(" api_key_env={env}"));
+            }
+            if !config.extra_headers.is_empty() {
+                let headers = config
+                    .extra_headers
+                    .keys()
+                    .map(|name| format!("{name}=<redacted>"))
+                    .collect::<Vec<String>>()
+                    .join(", ");
+                line.push_str(&format!(" headers=[{headers}]"));
+            }
+            line
+        })
+        .collect()
+}
+
+/// `doctor` lines for the providers configured by endpoint (`azure-openai`,
+/// `openai-compatible`) that the profile uses or configures: the request
+/// the client will make and whether its key variable is set, or what is
+/// missing from the profile.
+pub fn endpoint_provider_doctor_lines(
+    cfg: &RuntimeConfig,
+    env_present: &dyn Fn(&str) -> bool,
+) -> Vec<String> {
+    [Provider::AzureOpenai, Provider::OpenaiCompatible]
+        .into_iter()
+        .filter(|provider| {
+            cfg.provider == *provider || cfg.provider_overrides.contains_key(provider)
+        })
+        .map(|provider| {
+            let label = provider_label(provider);
+            let settings = match provider_client_settings(cfg, provider) {
+                Ok(settings) => settings,
+                Err(err) => return format!("{label}: invalid: {err}"),
+            };
+            let env = settings.api_key_env.as_deref().unwrap_or_default();
+            let key = match (env_present(env), provider) {
+                (true, _) => "set",
+                (false, Provider::AzureOpenai) => "missing",
+                (false, _) => "not set (optional)",
+            };
+            format!(
+                "{label}: POST {} ({} from {env}: {key})",
+                settings.chat_completions_url().unwrap_or_default(),
+                settings.auth_header()
+            )
+        })
+        .collect()
+}
+
 pub fn detect_provider() -> Option<Provider> {
     if env_present("OPENAI_API_KEY") {
         return Some(Provider::Openai);
@@ -143,7 +555,7 @@ pub fn parse_provider_name(value: &str) -> Result<Provider> {
     Provider::from_str(value, true)
         .map_err(|_| {
             anyhow::anyhow!(
-                "invalid provider '{}'. Supported values: auto, gemini, openai, anthropic, deepseek, groq, ollama",
+                "invalid provider '{}'. Supported values: auto, gemini, openai, anthropic, deepseek, groq, ollama, azure-openai, openai-compatible",
                 value
             )
         })
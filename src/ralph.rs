@@ -20,6 +20,15 @@ pub fn map_provider(provider: Provider) -> Result<String> {
         Provider::Groq => Err(anyhow!(
             "provider 'groq' is not supported by Ralph. Supported: openai, anthropic, gemini, ollama"
         )),
+        Provider::AzureOpenai => Err(anyhow!(
+            "provider 'azure-openai' is not supported by Ralph. Supported: openai, anthropic, gemini, ollama"
+        )),
+        Provider::OpenaiCompatible => Err(anyhow!(
+            "provider 'openai-compatible' is not supported by Ralph. Supported: openai, anthropic, gemini, ollama"
+        )),
+        Provider::Fake => Err(anyhow!(
+            "provider 'fake' is not supported by Ralph. Supported: openai, anthropic, gemini, ollama"
+        )),
         Provider::Auto => Err(anyhow!(
             "auto provider must be resolved before ralph invocation"
         )),
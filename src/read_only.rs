@@ -0,0 +1,260 @@
+/// Read-only mode: the single checkpoint for mutating tools and commands.
+///
+/// `resolve_runtime_tools` calls [`apply_read_only_tool_policy`] (which asks
+/// `ToolPolicy` what read-only mode removes) and the CLI dispatcher calls
+/// [`ensure_command_allowed`]; apart from `ToolPolicy`, nothing else consults
+/// `cfg.read_only` to decide whether an operation may write.
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+use adk_rust::Result as AdkResult;
+use adk_rust::prelude::*;
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::{Value, json};
+
+use crate::cli::{
+    AgentCommands, AliasCommands, CacheCommands, Cli, Commands, ConfigCommands,
+    MaintenanceCommands, MemoryCommands, MigrateCommands, ProfileCommands, SessionCommands,
+};
+use crate::config::{ProfilesFile, RuntimeConfig, resolve_profile_name};
+use crate::tool_policy::{ToolOrigin, ToolPolicy};
+use crate::tools::execute_bash::is_read_only_command;
+
+/// Error code carried by every read-only refusal.
+pub const READ_ONLY_MODE_CODE: &str = "input.read_only_mode";
+
+/// Built-in tools removed from the toolset entirely in read-only mode.
+pub const READ_ONLY_REMOVED_TOOLS: &[&str] = &["fs_write", "file_edit", "apply_patch", "remember"];
+
+/// Built-in tools kept in read-only mode but checked call by call.
+pub const READ_ONLY_GUARDED_TOOLS: &[&str] =
+    &["execute_bash", "github_ops", "todo_list", "memory_agent"];
+
+pub fn read_only_error(operation: &str) -> anyhow::Error {
+    anyhow::anyhow!(
+        "{READ_ONLY_MODE_CODE}: '{operation}' is disabled in read-only mode (unset --read-only / ZAVORA_READ_ONLY to allow it)"
+    )
+}
+
+pub fn ensure_writable(cfg: &RuntimeConfig, operation: &str) -> Result<()> {
+    if cfg.read_only {
+        return Err(read_only_error(operation));
+    }
+    Ok(())
+}
+
+/// Read-only flag before the runtime config is resolved, so startup
+/// scaffolding (skills dir, onboarding wizard) can be skipped. The profile
+/// is picked like [`resolve_profile_name`] does, including the workspace
+/// selection (`selected_profile`).
+pub fn read_only_requested(
+    cli: &Cli,
+    profiles: &ProfilesFile,
+    selected_profile: Option<&str>,
+) -> bool {
+    cli.read_only
+        || profiles
+            .profiles
+            .get(&resolve_profile_name(
+                cli.profile.as_deref(),
+                profiles,
+                selected_profile,
+            ))
+            .and_then(|profile| profile.read_only)
+            .unwrap_or(false)
+}
+
+/// Name of the mutating operation a CLI command performs, if any.
+pub fn mutating_command_operation(command: &Commands) -> Option<&'static str> {
+    match command {
+        Commands::Sessions {
+            command: SessionCommands::Delete { .. },
+        } => Some("sessions delete"),
+        Commands::Sessions {
+            command: SessionCommands::Prune { .. },
+        } => Some("sessions prune"),
+        Commands::Sessions {
+            command: SessionCommands::Encrypt { .. },
+        } => Some("sessions encrypt"),
+        Commands::Agents {
+            command: AgentCommands::Select { .. },
+        } => Some("agents select"),
+        Commands::Alias {
+            command: AliasCommands::Set { .. },
+        } => Some("alias set"),
+        Commands::Memory {
+            command: MemoryCommands::Add { .. },
+        } => Some("memory add"),
+        Commands::Memory {
+            command: MemoryCommands::Rm { .. },
+        } => Some("memory rm"),
+        Commands::Alias {
+            command: AliasCommands::Rm { .. },
+        } => Some("alias rm"),
+        Commands::Profiles {
+            command: ProfileCommands::Select { .. },
+        } => Some("profiles select"),
+        Commands::Cache {
+            command: CacheCommands::Clear { .. },
+        } => Some("cache clear"),
+        Commands::Maintenance {
+            command: MaintenanceCommands::Run { .. },
+        } => Some("maintenance run"),
+        Commands::Config {
+            command: ConfigCommands::Set { .. },
+        } => Some("config set"),
+        Commands::Config {
+            command: ConfigCommands::Unset { .. },
+        } => Some("config unset"),
+        Commands::Migrate {
+            command: None | Some(MigrateCommands::Up),
+        } => Some("migrate up"),
+        Commands::Migrate {
+            command: Some(MigrateCommands::Down { .. }),
+        } => Some("migrate down"),
+        #[cfg(feature = "rag")]
+        Commands::Rag {
+            command: crate::cli::RagCommands::Ingest { .. },
+        } => Some("rag ingest"),
+        Commands::Setup => Some("setup"),
+        Commands::Init { .. } => Some("init"),
+        Commands::Trust { .. } => Some("trust"),
+        Commands::LspInit => Some("lsp-init"),
+        Commands::Ralph { .. } => Some("ralph"),
+        _ => None,
+    }
+}
+
+/// CLI checkpoint: refuse mutating commands in read-only mode.
+pub fn ensure_command_allowed(cfg: &RuntimeConfig, command: Option<&Commands>) -> Result<()> {
+    match command.and_then(mutating_command_operation) {
+        Some(operation) => ensure_writable(cfg, operation),
+        None => Ok(()),
+    }
+}
+
+/// Why a call to a guarded tool would mutate state, or `None` when the call
+/// is read-only.
+pub fn read_only_tool_violation(tool: &str, args: &Value) -> Option<String> {
+    let action = args
+        .get("action")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    match tool {
+        "fs_write" | "file_edit" | "apply_patch" => Some("file writes are disabled".to_string()),
+        "remember" => Some("memory writes are disabled".to_string()),
+        "execute_bash" => {
+            let command = args
+                .get("command")
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+            (!is_read_only_command(command)).then(|| {
+                format!("only read-only commands may run, regardless of approval (got '{command}')")
+            })
+        }
+        "github_ops" => Some(format!("github_ops action '{action}' changes GitHub state")),
+        "todo_list" => (!matches!(action, "view" | "list"))
+            .then(|| format!("todo_list action '{action}' writes to .zavora/todos")),
+        "memory_agent" => (!matches!(action, "" | "recall"))
+            .then(|| format!("memory_agent action '{action}' writes to the memory store")),
+        _ => None,
+    }
+}
+
+pub fn read_only_tool_payload(tool: &str, reason: &str) -> Value {
+    json!({
+        "status": "error",
+        "code": READ_ONLY_MODE_CODE,
+        "error": format!("{tool} refused in read-only mode: {reason}"),
+        "tool": tool
+    })
+}
+
+/// Refuses mutating calls to a guarded tool before it (or its confirmation
+/// prompt) runs.
+pub struct ReadOnlyGuardTool {
+    inner: Arc<dyn Tool>,
+}
+
+impl ReadOnlyGuardTool {
+    pub fn wrap(inner: Arc<dyn Tool>) -> Arc<dyn Tool> {
+        Arc::new(Self { inner })
+    }
+}
+
+#[async_trait]
+impl Tool for ReadOnlyGuardTool {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn description(&self) -> &str {
+        self.inner.description()
+    }
+
+    fn enhanced_description(&self) -> String {
+        self.inner.enhanced_description()
+    }
+
+    fn is_long_running(&self) -> bool {
+        self.inner.is_long_running()
+    }
+
+    fn parameters_schema(&self) -> Option<Value> {
+        self.inner.parameters_schema()
+    }
+
+    fn response_schema(&self) -> Option<Value> {
+        self.inner.response_schema()
+    }
+
+    async fn execute(&self, ctx: Arc<dyn ToolContext>, args: Value) -> AdkResult<Value> {
+        if let Some(reason) = read_only_tool_violation(self.inner.name(), &args) {
+            tracing::info!(tool = self.inner.name(), reason = %reason, "Read-only mode refused tool call");
+            return Ok(read_only_tool_payload(self.inner.name(), &reason));
+        }
+        self.inner.execute(ctx, args).await
+    }
+}
+
+/// Tool checkpoint: in read-only mode drop write tools and MCP tools (their
+/// side effects are unknown) and guard the remaining mutating built-ins.
+pub fn apply_read_only_tool_policy(
+    cfg: &RuntimeConfig,
+    tools: Vec<Arc<dyn Tool>>,
+    mcp_tool_names: &BTreeSet<String>,
+) -> Vec<Arc<dyn Tool>> {
+    if !cfg.read_only {
+        return tools;
+    }
+    let policy = ToolPolicy::from_config(cfg);
+    tools
+        .into_iter()
+        .filter(|tool| {
+            let origin = if mcp_tool_names.contains(tool.name()) {
+                ToolOrigin::Mcp
+            } else {
+                ToolOrigin::BuiltIn
+            };
+            !policy.removed_by_read_only(tool.name(), origin)
+        })
+        .map(|tool| {
+            if READ_ONLY_GUARDED_TOOLS.contains(&tool.name()) {
+                ReadOnlyGuardTool::wrap(tool)
+            } else {
+                tool
+            }
+        })
+        .collect()
+}
+
+/// One-line notice shown by `/tools`, `doctor` and the chat banner.
+pub fn read_only_banner(cfg: &RuntimeConfig) -> Option<String> {
+    cfg.read_only.then(|| {
+        format!(
+            "READ-ONLY MODE: {} removed; MCP tools disabled; execute_bash limited to read-only commands; mutating commands refused",
+            READ_ONLY_REMOVED_TOOLS.join(", ")
+        )
+    })
+}
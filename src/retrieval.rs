@@ -1,20 +1,47 @@
+//! Retrieval backends and the context-injection policy.
+//!
+//! Every backend reports `RetrievedChunk.score` on the same 0–100 scale, so
+//! one `retrieval_min_score` works across backends:
+//!
+//! - `local`: `100 × coverage × hits / (hits + 1)`, where coverage is the
+//!   share of distinct query terms found in the chunk and hits counts every
+//!   occurrence. One hit of a one-word query scores 50; any hit scores at
+//!   least 1.
+//! - `semantic`: the mean of the Jaro-Winkler similarity between query and
+//!   chunk (0–1) and the `local` lexical relevance, times 100.
+//!
+//! `retrieval calibrate` prints the distribution for sample queries and
+//! suggests a threshold.
+use std::path::PathBuf;
 use std::sync::Arc;
 
 use anyhow::{Context, Result};
 
-use crate::cli::RetrievalBackend;
+use crate::chunking::{ChunkingStrategy, chunk_document, is_markdown_path};
+use crate::cli::{RetrievalBackend, RetrievalWatch};
 use crate::config::RuntimeConfig;
+use crate::prompt_assembly::{render_user_content, user_content_sections};
+use crate::retrieval_watch::{RetrievalLoader, WatchedRetrievalService};
+use crate::telemetry::TelemetrySink;
 
 #[derive(Debug, Clone)]
 pub struct RetrievedChunk {
     pub source: String,
     pub text: String,
+    /// Normalized relevance, 0–100 (see the module docs).
     pub score: usize,
 }
 
+pub const MAX_RETRIEVAL_SCORE: usize = 100;
+
 pub trait RetrievalService: Send + Sync {
     fn backend_name(&self) -> &'static str;
     fn retrieve(&self, query: &str, max_chunks: usize) -> Result<Vec<RetrievedChunk>>;
+    /// Line naming how the query was built, shown in the retrieved-context
+    /// block (see [`crate::retrieval_query`]).
+    fn query_note(&self) -> Option<String> {
+        None
+    }
 }
 
 pub struct DisabledRetrievalService;
@@ -33,21 +60,62 @@ pub struct LocalFileRetrievalService {
     pub chunks: Vec<RetrievedChunk>,
 }
 
-pub fn load_retrieval_chunks(path: &str, source_prefix: &str) -> Result<Vec<RetrievedChunk>> {
+/// Read and chunk a retrieval doc. `heading` chunking only applies to
+/// markdown files; other files fall back to paragraphs.
+pub fn load_retrieval_chunks(
+    path: &str,
+    source_prefix: &str,
+    strategy: ChunkingStrategy,
+) -> Result<Vec<RetrievedChunk>> {
     let content = std::fs::read_to_string(path)
         .with_context(|| format!("failed to read retrieval doc at '{}'", path))?;
-    let chunks = content
-        .split("\n\n")
-        .map(str::trim)
-        .filter(|chunk| !chunk.is_empty())
-        .enumerate()
-        .map(|(index, text)| RetrievedChunk {
-            source: format!("{source_prefix}:{path}#{}", index + 1),
-            text: text.to_string(),
-            score: 0,
-        })
-        .collect::<Vec<RetrievedChunk>>();
-    Ok(chunks)
+    let strategy = if strategy == ChunkingStrategy::Heading && !is_markdown_path(path) {
+        ChunkingStrategy::Paragraph
+    } else {
+        strategy
+    };
+    Ok(chunk_document(
+        &content,
+        &format!("{source_prefix}:{path}"),
+        strategy,
+    ))
+}
+
+/// Total occurrences of `terms` in `text`, case-insensitively.
+pub fn lexical_score(terms: &[String], text: &str) -> usize {
+    let body = text.to_ascii_lowercase();
+    terms
+        .iter()
+        .map(|term| body.matches(term.as_str()).count())
+        .sum::<usize>()
+}
+
+/// Lexical relevance in `[0, 1]`: distinct-term coverage damped by total
+/// hits (`hits / (hits + 1)`). 0 when no term occurs.
+pub fn lexical_relevance(terms: &[String], text: &str) -> f64 {
+    let body = text.to_ascii_lowercase();
+    let mut distinct = terms.iter().map(String::as_str).collect::<Vec<&str>>();
+    distinct.sort_unstable();
+    distinct.dedup();
+    if distinct.is_empty() {
+        return 0.0;
+    }
+    let counts = distinct
+        .iter()
+        .map(|term| body.matches(term).count())
+        .collect::<Vec<usize>>();
+    let hits = counts.iter().sum::<usize>() as f64;
+    let coverage = counts.iter().filter(|count| **count > 0).count() as f64 / counts.len() as f64;
+    coverage * hits / (hits + 1.0)
+}
+
+/// Map a `[0, 1]` relevance onto the 0–100 score scale. Any positive
+/// relevance scores at least 1 so it is never mistaken for no match.
+pub fn normalized_score(relevance: f64) -> usize {
+    if relevance <= 0.0 {
+        return 0;
+    }
+    ((relevance * MAX_RETRIEVAL_SCORE as f64).round() as usize).clamp(1, MAX_RETRIEVAL_SCORE)
 }
 
 pub fn query_terms(query: &str) -> Vec<String> {
@@ -61,8 +129,12 @@ pub fn query_terms(query: &str) -> Vec<String> {
 
 impl LocalFileRetrievalService {
     pub fn load(path: &str) -> Result<Self> {
+        Self::load_with_chunking(path, ChunkingStrategy::Paragraph)
+    }
+
+    pub fn load_with_chunking(path: &str, strategy: ChunkingStrategy) -> Result<Self> {
         Ok(Self {
-            chunks: load_retrieval_chunks(path, "local")?,
+            chunks: load_retrieval_chunks(path, "local", strategy)?,
         })
     }
 }
@@ -83,11 +155,7 @@ impl RetrievalService for LocalFileRetrievalService {
             .chunks
             .iter()
             .filter_map(|chunk| {
-                let body = chunk.text.to_ascii_lowercase();
-                let score = terms
-                    .iter()
-                    .map(|term| body.matches(term.as_str()).count())
-                    .sum::<usize>();
+                let score = normalized_score(lexical_relevance(&terms, &chunk.text));
                 (score > 0).then_some(RetrievedChunk {
                     source: chunk.source.clone(),
                     text: chunk.text.clone(),
@@ -102,6 +170,12 @@ impl RetrievalService for LocalFileRetrievalService {
     }
 }
 
+/// `semantic` backend score: the mean of similarity and lexical relevance,
+/// both clamped to `[0, 1]`, on the 0–100 scale.
+pub fn semantic_score(similarity: f64, lexical_relevance: f64) -> usize {
+    normalized_score((similarity.clamp(0.0, 1.0) + lexical_relevance.clamp(0.0, 1.0)) / 2.0)
+}
+
 #[cfg(feature = "semantic-search")]
 pub struct SemanticLocalRetrievalService {
     pub chunks: Vec<RetrievedChunk>,
@@ -110,8 +184,12 @@ pub struct SemanticLocalRetrievalService {
 #[cfg(feature = "semantic-search")]
 impl SemanticLocalRetrievalService {
     pub fn load(path: &str) -> Result<Self> {
+        Self::load_with_chunking(path, ChunkingStrategy::Paragraph)
+    }
+
+    pub fn load_with_chunking(path: &str, strategy: ChunkingStrategy) -> Result<Self> {
         Ok(Self {
-            chunks: load_retrieval_chunks(path, "semantic")?,
+            chunks: load_retrieval_chunks(path, "semantic", strategy)?,
         })
     }
 }
@@ -135,11 +213,7 @@ impl RetrievalService for SemanticLocalRetrievalService {
             .filter_map(|chunk| {
                 let body = chunk.text.to_ascii_lowercase();
                 let similarity = strsim::jaro_winkler(&query_lower, &body);
-                let lexical_hits = terms
-                    .iter()
-                    .map(|term| body.matches(term.as_str()).count())
-                    .sum::<usize>();
-                let score = ((similarity * 1000.0) as usize) + (lexical_hits * 25);
+                let score = semantic_score(similarity, lexical_relevance(&terms, &body));
                 (score > 0).then_some(RetrievedChunk {
                     source: chunk.source.clone(),
                     text: chunk.text.clone(),
@@ -155,6 +229,37 @@ impl RetrievalService for SemanticLocalRetrievalService {
 }
 
 pub fn build_retrieval_service(cfg: &RuntimeConfig) -> Result<Arc<dyn RetrievalService>> {
+    build_retrieval_service_with_telemetry(cfg, None)
+}
+
+/// [`build_retrieval_service`], wrapped in a [`WatchedRetrievalService`]
+/// unless `retrieval_watch = off`; reloads are reported to `telemetry`.
+pub fn build_retrieval_service_with_telemetry(
+    cfg: &RuntimeConfig,
+    telemetry: Option<&TelemetrySink>,
+) -> Result<Arc<dyn RetrievalService>> {
+    let service = load_retrieval_service(cfg)?;
+    let Some(path) = cfg.retrieval_doc_path.as_deref() else {
+        return Ok(service);
+    };
+    if cfg.retrieval_watch == RetrievalWatch::Off
+        || cfg.retrieval_backend == RetrievalBackend::Disabled
+    {
+        return Ok(service);
+    }
+    let reload_cfg = cfg.clone();
+    let loader: RetrievalLoader = Box::new(move || load_retrieval_service(&reload_cfg));
+    let watched = WatchedRetrievalService::new(
+        cfg.retrieval_watch,
+        &[PathBuf::from(path)],
+        service,
+        loader,
+        telemetry.cloned(),
+    )?;
+    Ok(Arc::new(watched))
+}
+
+fn load_retrieval_service(cfg: &RuntimeConfig) -> Result<Arc<dyn RetrievalService>> {
     match cfg.retrieval_backend {
         RetrievalBackend::Disabled => Ok(Arc::new(DisabledRetrievalService)),
         RetrievalBackend::Local => {
@@ -163,7 +268,10 @@ pub fn build_retrieval_service(cfg: &RuntimeConfig) -> Result<Arc<dyn RetrievalS
                     "retrieval backend 'local' requires --retrieval-doc-path or profile.retrieval_doc_path"
                 )
             })?;
-            let service = LocalFileRetrievalService::load(path)?;
+            let service = LocalFileRetrievalService::load_with_chunking(
+                path,
+                ChunkingStrategy::from_config(cfg),
+            )?;
             Ok(Arc::new(service))
         }
         RetrievalBackend::Semantic => {
@@ -175,7 +283,10 @@ pub fn build_retrieval_service(cfg: &RuntimeConfig) -> Result<Arc<dyn RetrievalS
 
             #[cfg(feature = "semantic-search")]
             {
-                let service = SemanticLocalRetrievalService::load(path)?;
+                let service = SemanticLocalRetrievalService::load_with_chunking(
+                    path,
+                    ChunkingStrategy::from_config(cfg),
+                )?;
                 Ok(Arc::new(service))
             }
 
@@ -194,6 +305,7 @@ pub fn build_retrieval_service(cfg: &RuntimeConfig) -> Result<Arc<dyn RetrievalS
 pub struct RetrievalPolicy {
     pub max_chunks: usize,
     pub max_chars: usize,
+    /// Chunks scoring below this (0–100) are not injected.
     pub min_score: usize,
 }
 
@@ -202,8 +314,43 @@ pub fn augment_prompt_with_retrieval(
     prompt: &str,
     policy: RetrievalPolicy,
 ) -> Result<String> {
+    let sections = user_content_sections(retrieval, prompt, "prompt", policy)?;
+    Ok(render_user_content(&sections))
+}
+
+/// `-vv` listing of the chunks retrieval picked: source, score and size.
+pub fn describe_selected_chunks(backend: &str, chunks: &[RetrievedChunk]) -> String {
+    let mut out = format!("retrieval {backend}: {} chunk(s) selected", chunks.len());
+    for chunk in chunks {
+        out.push_str(&format!(
+            "\n  score={} chars={} {}",
+            chunk.score,
+            chunk.text.len(),
+            chunk.source
+        ));
+    }
+    out
+}
+
+/// Chunks that pass `min_score`, trimmed to the `max_chars` budget.
+pub fn select_retrieval_chunks(
+    retrieval: &dyn RetrievalService,
+    prompt: &str,
+    policy: RetrievalPolicy,
+) -> Result<Vec<RetrievedChunk>> {
+    select_retrieval_chunks_with_truncation(retrieval, prompt, policy).map(|(chunks, _)| chunks)
+}
+
+/// [`select_retrieval_chunks`] plus the bytes of qualifying chunks the
+/// `max_chars` budget cut or dropped.
+pub fn select_retrieval_chunks_with_truncation(
+    retrieval: &dyn RetrievalService,
+    prompt: &str,
+    policy: RetrievalPolicy,
+) -> Result<(Vec<RetrievedChunk>, usize)> {
     let chunks = retrieval.retrieve(prompt, policy.max_chunks)?;
     let mut used_chars = 0usize;
+    let mut truncated_bytes = 0usize;
     let mut filtered = Vec::new();
 
     for chunk in chunks {
@@ -211,17 +358,15 @@ pub fn augment_prompt_with_retrieval(
             continue;
         }
 
-        if used_chars >= policy.max_chars {
-            break;
-        }
-
-        let remaining = policy.max_chars - used_chars;
+        let remaining = policy.max_chars.saturating_sub(used_chars);
         if remaining == 0 {
-            break;
+            truncated_bytes += chunk.text.len();
+            continue;
         }
 
         let mut text = chunk.text;
         if text.len() > remaining {
+            truncated_bytes += text.len() - remaining;
             text.truncate(remaining);
         }
 
@@ -237,13 +382,30 @@ pub fn augment_prompt_with_retrieval(
         });
     }
 
-    if filtered.is_empty() {
-        return Ok(prompt.to_string());
+    Ok((filtered, truncated_bytes))
+}
+
+/// The "Retrieved context" preamble, or `None` when no chunk qualified.
+pub fn render_retrieval_context(chunks: &[RetrievedChunk]) -> Option<String> {
+    render_retrieval_context_with_note(chunks, None)
+}
+
+/// [`render_retrieval_context`] with `note` on the line after the header.
+pub fn render_retrieval_context_with_note(
+    chunks: &[RetrievedChunk],
+    note: Option<&str>,
+) -> Option<String> {
+    if chunks.is_empty() {
+        return None;
     }
 
     let mut out = String::new();
     out.push_str("Retrieved context (use if relevant):\n");
-    for (index, chunk) in filtered.iter().enumerate() {
+    if let Some(note) = note {
+        out.push_str(note);
+        out.push('\n');
+    }
+    for (index, chunk) in chunks.iter().enumerate() {
         out.push_str(&format!(
             "[{}] {} (score={})\n{}\n",
             index + 1,
@@ -252,7 +414,5 @@ pub fn augment_prompt_with_retrieval(
             chunk.text
         ));
     }
-    out.push_str("\nUser request:\n");
-    out.push_str(prompt);
-    Ok(out)
+    Some(out)
 }
@@ -0,0 +1,196 @@
+//! `retrieval calibrate`: score distributions for sample queries and a
+//! suggested `retrieval_min_score`.
+//!
+//! For each query the chunks are retrieved unfiltered and their normalized
+//! scores summarized. A threshold keeps a query's top N chunks when it is at
+//! or below the query's N-th best score, so the suggestion is the highest
+//! threshold that does that for [`CALIBRATION_COVERAGE`] of the queries with
+//! any hit.
+use anyhow::{Context, Result};
+
+use crate::cli::RetrievalBackend;
+use crate::config::RuntimeConfig;
+use crate::retrieval::{MAX_RETRIEVAL_SCORE, RetrievalService, build_retrieval_service};
+
+/// Share of queries whose top N chunks the suggested threshold must keep.
+pub const CALIBRATION_COVERAGE: f64 = 0.8;
+
+/// Before scores were normalized, the semantic backend used a ~1000-point
+/// scale; dividing an old threshold by this lands near the same cut-off.
+const LEGACY_SEMANTIC_SCALE: usize = 20;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryScoreStats {
+    pub query: String,
+    /// Chunks with a non-zero score.
+    pub chunks: usize,
+    pub min: usize,
+    pub median: usize,
+    pub p90: usize,
+    /// Score of the N-th best chunk (the worst one when fewer matched).
+    pub nth_best: usize,
+}
+
+/// Nearest-rank percentile of ascending `sorted` scores.
+fn score_percentile(sorted: &[usize], pct: f64) -> usize {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = ((pct.clamp(0.0, 100.0) / 100.0) * (sorted.len() - 1) as f64).round() as usize;
+    sorted[rank.min(sorted.len() - 1)]
+}
+
+pub fn query_score_stats(query: &str, scores: &[usize], top_n: usize) -> QueryScoreStats {
+    let mut sorted = scores
+        .iter()
+        .copied()
+        .filter(|score| *score > 0)
+        .collect::<Vec<usize>>();
+    sorted.sort_unstable();
+    let nth_best = sorted
+        .len()
+        .checked_sub(top_n.max(1))
+        .and_then(|index| sorted.get(index))
+        .or(sorted.first())
+        .copied()
+        .unwrap_or_default();
+    QueryScoreStats {
+        query: query.to_string(),
+        chunks: sorted.len(),
+        min: sorted.first().copied().unwrap_or_default(),
+        median: score_percentile(&sorted, 50.0),
+        p90: score_percentile(&sorted, 90.0),
+        nth_best,
+    }
+}
+
+/// Highest threshold keeping the top N chunks for `coverage` of the queries
+/// that matched anything; `None` when none did.
+pub fn suggest_min_score(stats: &[QueryScoreStats], coverage: f64) -> Option<usize> {
+    let mut cutoffs = stats
+        .iter()
+        .filter(|stats| stats.chunks > 0)
+        .map(|stats| stats.nth_best)
+        .collect::<Vec<usize>>();
+    if cutoffs.is_empty() {
+        return None;
+    }
+    cutoffs.sort_unstable();
+    let must_keep =
+        ((coverage.clamp(0.0, 1.0) * cutoffs.len() as f64).ceil() as usize).clamp(1, cutoffs.len());
+    Some(cutoffs[cutoffs.len() - must_keep].max(1))
+}
+
+/// Warning for a `retrieval_min_score` written for the old unnormalized
+/// scale, with a suggested replacement.
+pub fn legacy_min_score_warning(min_score: usize) -> Option<String> {
+    (min_score > MAX_RETRIEVAL_SCORE).then(|| {
+        let suggested = (min_score / LEGACY_SEMANTIC_SCALE).clamp(1, MAX_RETRIEVAL_SCORE);
+        format!(
+            "retrieval_min_score = {min_score} is above the 0-100 score scale, so no chunk can \
+             pass; try retrieval_min_score = {suggested}, or run `zavora-cli retrieval calibrate`"
+        )
+    })
+}
+
+/// Sample queries: one per line, blank lines and `#` comments skipped.
+pub fn parse_calibration_queries(raw: &str) -> Vec<String> {
+    raw.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect()
+}
+
+pub fn calibrate_retrieval(
+    retrieval: &dyn RetrievalService,
+    queries: &[String],
+    top_n: usize,
+) -> Result<Vec<QueryScoreStats>> {
+    queries
+        .iter()
+        .map(|query| {
+            let scores = retrieval
+                .retrieve(query, usize::MAX)
+                .with_context(|| format!("retrieval failed for calibration query '{query}'"))?
+                .iter()
+                .map(|chunk| chunk.score)
+                .collect::<Vec<usize>>();
+            Ok(query_score_stats(query, &scores, top_n))
+        })
+        .collect()
+}
+
+pub fn format_calibration_report(
+    stats: &[QueryScoreStats],
+    top_n: usize,
+    suggestion: Option<usize>,
+    current: usize,
+) -> String {
+    let mut out = format!(
+        "{:<40} {:>6} {:>4} {:>6} {:>4} {:>6}\n",
+        "query", "chunks", "min", "median", "p90", "top-n"
+    );
+    for stats in stats {
+        let query = if stats.query.chars().count() > 40 {
+            format!("{}…", stats.query.chars().take(39).collect::<String>())
+        } else {
+            stats.query.clone()
+        };
+        out.push_str(&format!(
+            "{:<40} {:>6} {:>4} {:>6} {:>4} {:>6}\n",
+            query, stats.chunks, stats.min, stats.median, stats.p90, stats.nth_best
+        ));
+    }
+    let unmatched = stats.iter().filter(|stats| stats.chunks == 0).count();
+    if unmatched > 0 {
+        out.push_str(&format!("{unmatched} query(ies) matched no chunk.\n"));
+    }
+    match suggestion {
+        Some(value) => out.push_str(&format!(
+            "Suggested retrieval_min_score = {value} (keeps the top {top_n} chunk(s) for at \
+             least {:.0}% of matching queries; current: {current})\n",
+            CALIBRATION_COVERAGE * 100.0
+        )),
+        None => out.push_str("No suggestion: no query matched any chunk.\n"),
+    }
+    out
+}
+
+pub fn run_retrieval_calibrate(
+    cfg: &RuntimeConfig,
+    doc_path: Option<String>,
+    queries_file: &str,
+    top_n: usize,
+) -> Result<()> {
+    let mut cfg = cfg.clone();
+    if let Some(path) = doc_path {
+        cfg.retrieval_doc_path = Some(path);
+    }
+    if cfg.retrieval_backend == RetrievalBackend::Disabled {
+        cfg.retrieval_backend = RetrievalBackend::Local;
+    }
+    let raw = std::fs::read_to_string(queries_file)
+        .with_context(|| format!("failed to read queries file '{queries_file}'"))?;
+    let queries = parse_calibration_queries(&raw);
+    if queries.is_empty() {
+        anyhow::bail!("queries file '{queries_file}' has no queries; put one per line");
+    }
+    let retrieval = build_retrieval_service(&cfg)?;
+    let stats = calibrate_retrieval(retrieval.as_ref(), &queries, top_n)?;
+    println!(
+        "Retrieval calibration ({} backend, {} queries, 0-100 scale)",
+        retrieval.backend_name(),
+        queries.len()
+    );
+    print!(
+        "{}",
+        format_calibration_report(
+            &stats,
+            top_n,
+            suggest_min_score(&stats, CALIBRATION_COVERAGE),
+            cfg.retrieval_min_score
+        )
+    );
+    Ok(())
+}
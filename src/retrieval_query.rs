@@ -0,0 +1,199 @@
+//! Chat-aware retrieval queries (`retrieval_query_strategy`).
+//!
+//! A follow-up like "what about the second option?" shares no terms with the
+//! chunk it is about, so retrieving with the latest input alone finds
+//! nothing. In chat the query can instead be built from the conversation:
+//!
+//! - `latest`: the current input only (the default, and what `ask` and
+//!   workflows always use);
+//! - `window`: the last `retrieval_query_window` user turns, current input
+//!   included;
+//! - `summary`: a rolling extract of recent turns, the same per-turn text the
+//!   compaction summary uses, capped at [`SUMMARY_QUERY_MAX_CHARS`].
+//!
+//! Only the retrieval query changes; the prompt sent to the model is still
+//! the user's input. The retrieved-context block names the strategy when it
+//! is not `latest`. With no earlier user turns every strategy falls back to
+//! `latest`.
+use adk_rust::Event;
+use anyhow::Result;
+
+use crate::cli::RetrievalQueryStrategy;
+use crate::compact::extract_event_text;
+use crate::config::RuntimeConfig;
+use crate::retrieval::{RetrievalService, RetrievedChunk};
+use crate::tool_output::truncate_at_char_boundary;
+
+pub const DEFAULT_RETRIEVAL_QUERY_WINDOW: usize = 3;
+/// Cap on one turn's contribution to a `summary` query.
+pub const SUMMARY_TURN_MAX_CHARS: usize = 160;
+/// Cap on the rolling summary; the oldest turns drop out first.
+pub const SUMMARY_QUERY_MAX_CHARS: usize = 800;
+
+/// Lead-in the prompt assembly puts before the user's own text.
+const USER_REQUEST_MARKER: &str = "User request:\n";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetrievalQuery {
+    /// The strategy that produced `text`; `Latest` after a fallback.
+    pub strategy: RetrievalQueryStrategy,
+    pub text: String,
+    /// User turns `text` was built from, current input included.
+    pub turns: usize,
+}
+
+impl RetrievalQuery {
+    pub fn latest(prompt: &str) -> Self {
+        Self {
+            strategy: RetrievalQueryStrategy::Latest,
+            text: prompt.to_string(),
+            turns: 1,
+        }
+    }
+
+    /// Line added to the retrieved-context block; `None` for `latest`.
+    pub fn note(&self) -> Option<String> {
+        match self.strategy {
+            RetrievalQueryStrategy::Latest => None,
+            RetrievalQueryStrategy::Window => Some(format!(
+                "(retrieved with query strategy 'window': last {} user turns)",
+                self.turns
+            )),
+            RetrievalQueryStrategy::Summary => Some(format!(
+                "(retrieved with query strategy 'summary': rolling summary of {} user turns)",
+                self.turns
+            )),
+        }
+    }
+}
+
+/// What the user typed in a stored user turn, without the retrieval and
+/// memory sections the prompt assembly put in front of it.
+pub fn user_request_text(content: &str) -> &str {
+    content
+        .rsplit_once(USER_REQUEST_MARKER)
+        .map_or(content, |(_, request)| request)
+}
+
+/// Text of the user turns in `history`, oldest first.
+pub fn user_turns(history: &[Event]) -> Vec<String> {
+    history
+        .iter()
+        .filter(|event| event.author == "user")
+        .map(|event| {
+            user_request_text(&extract_event_text(event))
+                .trim()
+                .to_string()
+        })
+        .filter(|text| !text.is_empty())
+        .collect()
+}
+
+/// The last `window` user turns: up to `window - 1` from `history`, then
+/// `prompt`.
+pub fn window_query(history: &[Event], prompt: &str, window: usize) -> RetrievalQuery {
+    let earlier = user_turns(history);
+    let keep = window.max(1) - 1;
+    let earlier = &earlier[earlier.len().saturating_sub(keep)..];
+    if earlier.is_empty() {
+        return RetrievalQuery::latest(prompt);
+    }
+    let mut turns = earlier.to_vec();
+    turns.push(prompt.to_string());
+    RetrievalQuery {
+        strategy: RetrievalQueryStrategy::Window,
+        text: turns.join("\n"),
+        turns: turns.len(),
+    }
+}
+
+/// A short rolling summary of `history` followed by `prompt`. Each turn is
+/// clipped to [`SUMMARY_TURN_MAX_CHARS`] and the oldest turns are dropped
+/// until the summary fits [`SUMMARY_QUERY_MAX_CHARS`].
+pub fn summary_query(history: &[Event], prompt: &str) -> RetrievalQuery {
+    let mut lines = Vec::new();
+    let mut user_count = 0;
+    for event in history {
+        let text = extract_event_text(event);
+        let is_user = event.author == "user";
+        let text = if is_user {
+            user_request_text(&text)
+        } else {
+            &text
+        };
+        let (text, _) = truncate_at_char_boundary(text.trim(), SUMMARY_TURN_MAX_CHARS);
+        if text.is_empty() {
+            continue;
+        }
+        lines.push((is_user, text.to_string()));
+        user_count += usize::from(is_user);
+    }
+    if user_count == 0 {
+        return RetrievalQuery::latest(prompt);
+    }
+    let mut total = lines.iter().map(|(_, line)| line.len() + 1).sum::<usize>();
+    let mut start = 0;
+    while total > SUMMARY_QUERY_MAX_CHARS && start + 1 < lines.len() {
+        total -= lines[start].1.len() + 1;
+        start += 1;
+    }
+    let kept = &lines[start..];
+    let mut text = kept
+        .iter()
+        .map(|(_, line)| line.as_str())
+        .collect::<Vec<&str>>()
+        .join("\n");
+    text.push('\n');
+    text.push_str(prompt);
+    RetrievalQuery {
+        strategy: RetrievalQueryStrategy::Summary,
+        text,
+        turns: kept.iter().filter(|(is_user, _)| *is_user).count() + 1,
+    }
+}
+
+/// The retrieval query for a chat turn under the configured strategy.
+/// `history` is the session before `prompt` is sent.
+pub fn chat_retrieval_query(
+    cfg: &RuntimeConfig,
+    history: &[Event],
+    prompt: &str,
+) -> RetrievalQuery {
+    match cfg.retrieval_query_strategy {
+        RetrievalQueryStrategy::Latest => RetrievalQuery::latest(prompt),
+        RetrievalQueryStrategy::Window => window_query(history, prompt, cfg.retrieval_query_window),
+        RetrievalQueryStrategy::Summary => summary_query(history, prompt),
+    }
+}
+
+/// A retrieval service that answers every lookup with `query` instead of
+/// the text it is asked about, so the normal prompt assembly injects
+/// context for the conversation while the prompt itself is left alone.
+pub struct ConversationRetrieval<'a> {
+    inner: &'a dyn RetrievalService,
+    query: RetrievalQuery,
+}
+
+impl<'a> ConversationRetrieval<'a> {
+    pub fn new(inner: &'a dyn RetrievalService, query: RetrievalQuery) -> Self {
+        Self { inner, query }
+    }
+
+    pub fn query(&self) -> &RetrievalQuery {
+        &self.query
+    }
+}
+
+impl RetrievalService for ConversationRetrieval<'_> {
+    fn backend_name(&self) -> &'static str {
+        self.inner.backend_name()
+    }
+
+    fn retrieve(&self, _query: &str, max_chunks: usize) -> Result<Vec<RetrievedChunk>> {
+        self.inner.retrieve(&self.query.text, max_chunks)
+    }
+
+    fn query_note(&self) -> Option<String> {
+        self.query.note()
+    }
+}
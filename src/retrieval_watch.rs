@@ -0,0 +1,172 @@
+//! Retrieval doc freshness (`retrieval_watch`).
+//!
+//! Chat and `server serve` load retrieval chunks once, so an edit to the doc
+//! mid-session would otherwise keep answers citing the old text.
+//! [`WatchedRetrievalService`] fingerprints the doc at load (mtime, size and
+//! a SHA-256 of the contents) and compares before retrieving:
+//!
+//! - `on-demand` (default): before every `retrieve()`;
+//! - `poll`: at most once per [`RETRIEVAL_WATCH_POLL_INTERVAL`], so a busy
+//!   server stats the file a few times a minute rather than per request;
+//! - `off`: no wrapper at all, the chunks loaded at startup are kept.
+//!
+//! Unchanged mtime and size skip the hash. A changed hash rebuilds the
+//! inner service and emits `retrieval.reloaded` with the changed paths. The
+//! chunk store sits behind an `RwLock`, so concurrent requests keep reading
+//! the old chunks until the swap. A failed reload keeps the old chunks and
+//! is retried on the next check.
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant, SystemTime};
+
+use anyhow::{Context, Result};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+
+use crate::cli::RetrievalWatch;
+use crate::retrieval::{RetrievalService, RetrievedChunk};
+use crate::telemetry::TelemetrySink;
+
+pub const RETRIEVAL_RELOADED_EVENT: &str = "retrieval.reloaded";
+pub const RETRIEVAL_WATCH_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocFingerprint {
+    pub modified: Option<SystemTime>,
+    pub len: u64,
+    pub sha256: [u8; 32],
+}
+
+pub fn doc_fingerprint(path: &Path) -> Result<DocFingerprint> {
+    let bytes = std::fs::read(path)
+        .with_context(|| format!("failed to read retrieval doc at '{}'", path.display()))?;
+    let modified = std::fs::metadata(path)
+        .ok()
+        .and_then(|meta| meta.modified().ok());
+    Ok(DocFingerprint {
+        modified,
+        len: bytes.len() as u64,
+        sha256: Sha256::digest(&bytes).into(),
+    })
+}
+
+/// Whether `path` changed since `known`: mtime and size first, the hash
+/// only when one of them moved. Returns the new fingerprint when it did.
+pub fn changed_fingerprint(path: &Path, known: &DocFingerprint) -> Result<Option<DocFingerprint>> {
+    let meta = std::fs::metadata(path)
+        .with_context(|| format!("failed to stat retrieval doc at '{}'", path.display()))?;
+    if meta.len() == known.len && meta.modified().ok() == known.modified {
+        return Ok(None);
+    }
+    let current = doc_fingerprint(path)?;
+    Ok((current != *known).then_some(current))
+}
+
+pub type RetrievalLoader = Box<dyn Fn() -> Result<Arc<dyn RetrievalService>> + Send + Sync>;
+
+struct WatchState {
+    fingerprints: Vec<(PathBuf, DocFingerprint)>,
+    last_check: Instant,
+}
+
+/// A retrieval service that reloads its docs when they change on disk.
+pub struct WatchedRetrievalService {
+    backend: &'static str,
+    mode: RetrievalWatch,
+    loader: RetrievalLoader,
+    inner: RwLock<Arc<dyn RetrievalService>>,
+    state: Mutex<WatchState>,
+    telemetry: Option<TelemetrySink>,
+}
+
+impl WatchedRetrievalService {
+    /// Watch `paths`, which `inner` was just loaded from; `loader` rebuilds
+    /// it after a change.
+    pub fn new(
+        mode: RetrievalWatch,
+        paths: &[PathBuf],
+        inner: Arc<dyn RetrievalService>,
+        loader: RetrievalLoader,
+        telemetry: Option<TelemetrySink>,
+    ) -> Result<Self> {
+        let fingerprints = paths
+            .iter()
+            .map(|path| Ok((path.clone(), doc_fingerprint(path)?)))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            backend: inner.backend_name(),
+            mode,
+            loader,
+            inner: RwLock::new(inner),
+            state: Mutex::new(WatchState {
+                fingerprints,
+                last_check: Instant::now(),
+            }),
+            telemetry,
+        })
+    }
+
+    /// Compare the docs now and reload if any changed. Returns the changed
+    /// paths; on a failed reload the old chunks stay and the fingerprints
+    /// are left alone so the next check tries again.
+    pub fn refresh(&self) -> Result<Vec<String>> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.last_check = Instant::now();
+        let mut changed = Vec::new();
+        for (path, known) in &state.fingerprints {
+            if let Some(current) = changed_fingerprint(path, known)? {
+                changed.push((path.clone(), current));
+            }
+        }
+        if changed.is_empty() {
+            return Ok(Vec::new());
+        }
+        let reloaded = (self.loader)()?;
+        *self.inner.write().unwrap_or_else(|e| e.into_inner()) = reloaded;
+        let mut paths = Vec::new();
+        for (path, current) in changed {
+            if let Some(entry) = state
+                .fingerprints
+                .iter_mut()
+                .find(|(known, _)| *known == path)
+            {
+                entry.1 = current;
+            }
+            paths.push(path.display().to_string());
+        }
+        if let Some(telemetry) = &self.telemetry {
+            telemetry.emit(
+                RETRIEVAL_RELOADED_EVENT,
+                json!({ "backend": self.backend, "paths": paths }),
+            );
+        }
+        Ok(paths)
+    }
+
+    fn check_due(&self) -> bool {
+        match self.mode {
+            RetrievalWatch::Off => false,
+            RetrievalWatch::OnDemand => true,
+            RetrievalWatch::Poll => {
+                let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+                state.last_check.elapsed() >= RETRIEVAL_WATCH_POLL_INTERVAL
+            }
+        }
+    }
+}
+
+impl RetrievalService for WatchedRetrievalService {
+    fn backend_name(&self) -> &'static str {
+        self.backend
+    }
+
+    fn retrieve(&self, query: &str, max_chunks: usize) -> Result<Vec<RetrievedChunk>> {
+        if self.check_due()
+            && let Err(err) = self.refresh()
+        {
+            tracing::warn!(error = %err, "retrieval doc reload failed; keeping loaded chunks");
+        }
+        let inner = self.inner.read().unwrap_or_else(|e| e.into_inner()).clone();
+        inner.retrieve(query, max_chunks)
+    }
+}
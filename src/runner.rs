@@ -9,42 +9,25 @@ use adk_session::SessionService;
 use anyhow::{Context, Result};
 use serde_json::json;
 
-use crate::cli::ToolConfirmationMode;
-use crate::config::RuntimeConfig;
-use crate::mcp::discover_mcp_tools;
+use crate::cli::ToolSchemaStrategy;
+use crate::config::{RuntimeConfig, execute_bash_options, secret_scan_options};
+use crate::mcp::discover_mcp_tools_by_server;
+use crate::prompt_assembly::{PromptEnvironment, render_system_instruction, retrieval_policy};
 use crate::provider::resolve_model;
+use crate::retrieval::RetrievalService;
 use crate::session::{build_session_service, ensure_session_exists};
 use crate::telemetry::TelemetrySink;
-use crate::tool_policy::filter_tools_by_policy;
-use crate::tools::{
-
-    build_builtin_tools,
+use crate::tool_policy::{
+    PermissionDecision, ToolConfirmation, ToolOrigin, ToolPolicy, ToolUnavailable,
+    permission_decision_label,
 };
-
-const ORCHESTRATOR_INSTRUCTION: &str = "\
-You are the orchestrator. You coordinate specialist agents to accomplish complex tasks.
-
-CAPABILITY AGENTS (call as tools when you need their unique skills):
-- time_agent: Get current time, parse relative dates (\"next Friday\", \"in 2 days\")
-- memory_agent: Recall/store USER preferences, decisions, and learnings (NOT for general knowledge)
-
-SUBAGENTS (automatically available when conditions met):
-- search_agent: For news, current events, and web searches (enabled only with --provider gemini)
-- ralph_agent: For greenfield projects and multi-phase development (enabled only in agent mode)
-
-WORKFLOW AGENTS (use for complex multi-step work):
-- file_search_agent: Comprehensive file discovery with saturation detection
-- sequential_agent: Create plans and execute steps with progress tracking
-- quality_agent: Verify work against acceptance criteria
-
-RULES:
-- For news/web searches: delegate to search_agent
-- For greenfield projects, multi-file scaffolding, or multi-phase development: delegate to ralph_agent (agent mode only)
-- memory_agent is ONLY for user preferences/decisions, NOT for facts or general knowledge
-- For simple tasks, use your built-in tools directly
-- For complex multi-step tasks, use sequential_agent
-- Store only high-signal learnings: user preferences, decisions, patterns (not facts)
-";
+use crate::tools::mock::apply_mock_tools;
+use crate::tools::budget::wrap_with_tool_budgets;
+use crate::tools::doc_search::build_doc_search_tool;
+use crate::tools::output_cap::{ToolOutputCap, wrap_with_output_cap};
+use crate::tools::schema_pruning::ToolSchemaPruning;
+use crate::tools::{apply_execute_bash_options, apply_secret_scan_options, build_builtin_tools};
+use crate::verbosity::Verbosity;
 
 #[cfg(test)]
 pub fn build_single_agent(model: Arc<dyn Llm>) -> Result<Arc<dyn Agent>> {
@@ -83,146 +66,7 @@ pub fn build_single_agent_with_tools_and_telemetry(
     runtime_cfg: Option<&RuntimeConfig>,
     telemetry: Option<&TelemetrySink>,
 ) -> Result<Arc<dyn Agent>> {
-    let instruction = if let Some(cfg) = runtime_cfg {
-        let os_name = std::env::consts::OS;
-        let cwd = std::env::current_dir()
-            .map(|p| p.display().to_string())
-            .unwrap_or_else(|_| ".".to_string());
-        let shell = std::env::var("SHELL").unwrap_or_else(|_| "sh".to_string());
-
-        let mut sections = vec![
-            format!(
-                "You are Zavora, an AI assistant in the user's terminal. You help with coding, \
-                 debugging, system administration, writing, analysis, and any professional task.\n\
-                 \n\
-                 <system_context>\n\
-                 - Operating System: {os_name}\n\
-                 - Current Directory: {cwd}\n\
-                 - Shell: {shell}\n\
-                 </system_context>\n\
-                 \n\
-                 <operational_directives>\n\
-                 EXECUTE IMMEDIATELY. When the user asks you to do something, do it. Don't narrate \
-                 what you would do — use your tools and produce the result.\n\
-                 OUTPUT FIRST. Lead with code, results, or actions. Explanations come after, and only \
-                 if needed.\n\
-                 ZERO FLUFF. No philosophical preambles, no unsolicited advice, no filler. Every \
-             sentence must earn its place.\n\
-             STAY FOCUSED. Answer what was asked. Don't wander into tangents or related topics \
-             unless directly relevant.\n\
-             </operational_directives>\n\
-             "
-            ),
-            ORCHESTRATOR_INSTRUCTION.to_string(),
-            format!(
-                "\n\
-             <tone>\n\
-             You talk like a human, not like a bot. You are conversational and natural.\n\
-             - Mirror the user's style: short question gets a short answer, detailed question \
-             gets a detailed answer\n\
-             - NEVER present menus, numbered option lists, or \"quick options\" unless the user \
-             asks for choices\n\
-             - NEVER start responses with flattery (\"Great question!\", \"That's a good idea!\")\n\
-             - For greetings like \"hello\" or \"hi\", respond briefly and naturally — don't list \
-             capabilities or suggest actions\n\
-             - When you don't know something, say so directly\n\
-             - Use neutral acknowledgments: \"Let me look at that\" not \"Absolutely! I'd love to \
-             help!\"\n\
-             </tone>\n\
-             \n\
-             <coding_standards>\n\
-             PROJECT AWARENESS: Before writing code, understand the project's existing patterns, \
-             dependencies, and conventions. Use what's already there.\n\
-             - If the project uses a library or framework, USE IT. Don't build custom solutions \
-             when the existing stack provides them.\n\
-             - Match the project's code style, naming conventions, and file organization.\n\
-             - Every line of code must have a purpose. If it doesn't contribute to the solution, \
-             remove it.\n\
-             MINIMAL CHANGES: Write the absolute minimum code needed. Don't refactor surrounding \
-             code unless asked. Don't add features that weren't requested.\n\
-             VERIFY: Read files before modifying them. Check that builds pass after changes. \
-             Don't assume — verify.\n\
-             </coding_standards>\n\
-             \n\
-             <tool_guidelines>\n\
-             - Use fs_read to examine files before modifying them\n\
-             - Use file_edit for surgical text replacements in existing files (preferred over fs_write for edits)\n\
-             - Use fs_write only for creating new files or full rewrites\n\
-             - Use glob to find files by name pattern (e.g. '**/*.rs') — faster and safer than shell find\n\
-             - Use grep to search file contents by regex — faster and safer than shell grep\n\
-             - Use web_fetch to read web pages or API docs (requires confirmation since it makes network requests)\n\
-             - When editing files, show only the minimal diff needed\n\
-             - For shell commands, prefer simple composable commands over complex one-liners\n\
-             - Consider the operating system when providing paths and commands\n\
-             - Be aware of the current working directory for relative paths\n\
-             - After making code changes, compile/build to verify they work\n\
-             </tool_guidelines>\n\
-             \n\
-             <git_guidelines>\n\
-             COMMIT DISCIPLINE:\n\
-             - Make atomic commits: one logical change per commit. Don't bundle unrelated changes.\n\
-             - Always verify the build passes (compile, tests) BEFORE committing.\n\
-             - Use conventional commit prefixes: feat:, fix:, refactor:, docs:, test:, chore:\n\
-             - Write a concise summary line (<72 chars). For complex changes, add a blank line \
-             then a body explaining what and why.\n\
-             - Stage with `git add -A` unless selectively staging specific files.\n\
-             - Push after committing unless the user says otherwise.\n\
-             \n\
-             WORKFLOW:\n\
-             - Check `git status` before starting work to understand the current state.\n\
-             - Don't amend or force-push unless explicitly asked.\n\
-             - When making multiple related changes, commit after each logical step — not all \
-             at the end.\n\
-             - If a build or test fails after changes, fix it before committing.\n\
-             </git_guidelines>\n\
-             \n\
-             <response_format>\n\
-             FOR QUICK TASKS: Just do it. Minimal or no explanation.\n\
-             FOR CODE CHANGES: Brief rationale (1-2 sentences), then the code.\n\
-             FOR COMPLEX TASKS: Break into steps, execute each one, report results.\n\
-             FOR ANALYSIS/REVIEW: Be thorough — examine deeply, consider edge cases, provide \
-             actionable recommendations.\n\
-             AFTER TOOL USE: When you've already written files or executed commands via tools, \
-             do NOT repeat the file contents or command output in your response. The user already \
-             saw the diffs and results. Just summarize what was done in 1-2 sentences.\n\
-             ALWAYS: Use markdown code blocks with language tags. Don't use headers unless \
-             multi-step. Don't bold excessively. Bullet points only for genuinely parallel items.\n\
-             </response_format>\n\
-             \n\
-             <rules>\n\
-             - Never include secrets or API keys in code unless explicitly asked\n\
-             - Substitute PII with generic placeholders\n\
-             - Do not modify or remove tests unless explicitly requested\n\
-             - Do not add tests unless explicitly requested\n\
-             - Decline requests for malicious code\n\
-             - When uncertain, ask for clarification rather than guessing\n\
-             </rules>"
-            ),
-        ];
-        if let Some(agent_instruction) = cfg
-            .agent_instruction
-            .as_deref()
-            .map(str::trim)
-            .filter(|value| !value.is_empty())
-        {
-            sections.push(format!("Agent-specific instruction:\n{agent_instruction}"));
-        }
-        if !cfg.agent_resource_paths.is_empty() {
-            sections.push(format!(
-                "Agent resource hints:\n{}",
-                cfg.agent_resource_paths
-                    .iter()
-                    .map(|path| format!("- {}", path))
-                    .collect::<Vec<String>>()
-                    .join("\n")
-            ));
-        }
-        sections.join("\n\n")
-    } else {
-        "You are Zavora, an AI assistant in the user's terminal. Be concise and direct. \
-         Prioritize actionable output. When planning work, prefer release-oriented increments."
-            .to_string()
-    };
+    let instruction = render_system_instruction(runtime_cfg, &PromptEnvironment::current());
 
     // Intentional: search sub-agent is only enabled when the invocation explicitly
     // runs with --provider gemini. Auto-detected provider mode does not attach it.
@@ -232,6 +76,17 @@ pub fn build_single_agent_with_tools_and_telemetry(
     let ralph_subagent =
         build_ralph_subagent_if_agent_mode(runtime_cfg, model.clone(), telemetry);
 
+    // `relevant` trims the declarations on each request; `full` skips this.
+    let schema_pruning = runtime_cfg
+        .filter(|cfg| cfg.tool_schema_strategy != ToolSchemaStrategy::Full)
+        .map(|cfg| ToolSchemaPruning {
+            strategy: cfg.tool_schema_strategy,
+            top_k: cfg.tool_schema_top_k,
+            telemetry: telemetry
+                .cloned()
+                .unwrap_or_else(|| TelemetrySink::new(cfg, "tool".to_string())),
+        });
+
     let mut builder = LlmAgentBuilder::new("assistant")
         .description("General purpose engineering assistant")
         .instruction(instruction)
@@ -239,7 +94,8 @@ pub fn build_single_agent_with_tools_and_telemetry(
         .tool_confirmation_policy(tool_confirmation_policy)
         .tool_timeout(tool_timeout)
         .tool_execution_strategy(adk_rust::ToolExecutionStrategy::Auto)
-        .before_model_callback(Box::new(|_ctx, mut request| {
+        .before_model_callback(Box::new(move |_ctx, mut request| {
+            let schema_pruning = schema_pruning.clone();
             Box::pin(async move {
                 // Fix tool response roles: conversation_history() maps all non-user
                 // events to "model", but tool responses must be "function" for OpenAI.
@@ -253,6 +109,9 @@ pub fn build_single_agent_with_tools_and_telemetry(
                         content.role = "function".to_string();
                     }
                 }
+                if let Some(pruning) = &schema_pruning {
+                    pruning.apply(&mut request);
+                }
                 Ok(adk_rust::prelude::BeforeModelResult::Continue(request))
             })
         }));
@@ -328,6 +187,31 @@ fn build_ralph_subagent_if_agent_mode(
 pub struct ResolvedRuntimeTools {
     pub tools: Vec<Arc<dyn Tool>>,
     pub mcp_tool_names: BTreeSet<String>,
+    /// Every discovered MCP tool, including ones the agent allow/deny filter
+    /// removed, so `/mcp <server>` can show what a server actually exposes.
+    pub mcp_tools: Vec<McpToolInfo>,
+}
+
+/// Per-tool MCP discovery record kept alongside the resolved toolset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct McpToolInfo {
+    pub server: String,
+    pub name: String,
+    pub description: String,
+    /// Wrapped in a confirmation prompt at runtime.
+    pub requires_confirmation: bool,
+    /// Removed by the agent allow/deny tool filter.
+    pub excluded: bool,
+}
+
+impl ResolvedRuntimeTools {
+    /// MCP tools discovered from `server`, in discovery order.
+    pub fn mcp_tools_for_server<'a>(
+        &'a self,
+        server: &'a str,
+    ) -> impl Iterator<Item = &'a McpToolInfo> + 'a {
+        self.mcp_tools.iter().filter(move |tool| tool.server == server)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -351,25 +235,14 @@ pub fn resolve_tool_confirmation_settings(
 ) -> ToolConfirmationSettings {
     // Confirmation is now handled by ConfirmingTool wrappers applied in
     // resolve_runtime_tools(). The ADK-level policy is always Never.
-    let available_tool_names = runtime_tools
-        .tools
-        .iter()
-        .map(|tool| tool.name().to_string())
-        .collect::<BTreeSet<String>>();
-
-    let mut approved_tools = BTreeSet::<String>::new();
-    for tool_name in &cfg.approve_tool {
-        let trimmed = tool_name.trim();
-        if !trimmed.is_empty() && available_tool_names.contains(trimmed) {
-            approved_tools.insert(trimmed.to_string());
-        }
-    }
-
+    let policy = ToolPolicy::from_config(cfg);
     let mut run_config = RunConfig::default();
-    for tool_name in &approved_tools {
-        run_config
-            .tool_confirmation_decisions
-            .insert(tool_name.clone(), ToolConfirmationDecision::Approve);
+    for tool in &runtime_tools.tools {
+        if policy.is_approved(tool.name()) {
+            run_config
+                .tool_confirmation_decisions
+                .insert(tool.name().to_string(), ToolConfirmationDecision::Approve);
+        }
     }
 
     ToolConfirmationSettings {
@@ -378,106 +251,122 @@ pub fn resolve_tool_confirmation_settings(
     }
 }
 
+/// `-vvv` line for one tool: the permission rule that matched and whether
+/// calls will prompt.
+pub fn format_tool_confirmation_decision(
+    name: &str,
+    decision: PermissionDecision,
+    requires_confirmation: bool,
+) -> String {
+    let rule = permission_decision_label(decision);
+    let outcome = if requires_confirmation {
+        "confirm"
+    } else {
+        "auto"
+    };
+    format!("tool confirmation {name}: rule={rule} -> {outcome}")
+}
+
 pub async fn resolve_runtime_tools(cfg: &RuntimeConfig) -> ResolvedRuntimeTools {
+    resolve_runtime_tools_with_retrieval(cfg, None).await
+}
+
+/// [`resolve_runtime_tools`] plus `doc_search` over `retrieval`, the
+/// service the command already built, when retrieval is enabled.
+pub async fn resolve_runtime_tools_with_retrieval(
+    cfg: &RuntimeConfig,
+    retrieval: Option<Arc<dyn RetrievalService>>,
+) -> ResolvedRuntimeTools {
     use crate::tools::confirming::ConfirmingTool;
-    use crate::tool_policy::is_read_only_tool;
 
     let mut tools = build_builtin_tools();
+    apply_execute_bash_options(&mut tools, execute_bash_options(cfg));
+    apply_secret_scan_options(&mut tools, secret_scan_options(cfg));
+    tools.extend(crate::memory_facts::build_memory_tools(cfg));
+    tools.push(Arc::new(crate::tools::policy_check::build_policy_check_tool(cfg)));
+    if let Some(doc_search) = retrieval
+        .and_then(|retrieval| build_doc_search_tool(cfg, retrieval, retrieval_policy(cfg)))
+    {
+        tools.push(doc_search);
+    }
     let built_in_count = tools.len();
-    let mut mcp_tools = discover_mcp_tools(cfg).await;
-    let mcp_count = mcp_tools.len();
-    let discovered_mcp_tool_names = mcp_tools
-        .iter()
-        .map(|tool| tool.name().to_string())
-        .collect::<BTreeSet<String>>();
-    tools.append(&mut mcp_tools);
-
-    tools = filter_tools_by_policy(tools, &cfg.agent_allow_tools, &cfg.agent_deny_tools);
-
-    // Build effective permission rules: profile rules + backward-compat mapping
-    let rules = &cfg.permission_rules;
-
-    // Legacy approve_tool → always_allow, require_confirm_tool → always_ask
-    let mut effective_allow: Vec<crate::tool_policy::ToolPattern> = rules.always_allow.clone();
-    let effective_deny: Vec<crate::tool_policy::ToolPattern> = rules.always_deny.clone();
-    let mut effective_ask: Vec<crate::tool_policy::ToolPattern> = rules.always_ask.clone();
-
-    for name in &cfg.approve_tool {
-        let trimmed = name.trim();
-        if !trimmed.is_empty() {
-            effective_allow.push(crate::tool_policy::ToolPattern(trimmed.to_string()));
+    let mut mcp_tool_origins = Vec::<(String, String, String)>::new();
+    for (server, server_tools) in discover_mcp_tools_by_server(cfg).await {
+        for tool in server_tools {
+            mcp_tool_origins.push((
+                server.clone(),
+                tool.name().to_string(),
+                tool.description().to_string(),
+            ));
+            tools.push(tool);
         }
     }
-    for name in &cfg.require_confirm_tool {
-        let trimmed = name.trim();
-        if !trimmed.is_empty() {
-            effective_ask.push(crate::tool_policy::ToolPattern(trimmed.to_string()));
-        }
+    let mcp_count = mcp_tool_origins.len();
+    if let Some(mocks) = &cfg.mock_tools {
+        let telemetry = TelemetrySink::new(cfg, "tool".to_string());
+        tools = apply_mock_tools(tools, mocks, &telemetry);
     }
+    let discovered_mcp_tool_names = mcp_tool_origins
+        .iter()
+        .map(|(_, name, _)| name.clone())
+        .collect::<BTreeSet<String>>();
 
-    let effective_rules = crate::tool_policy::PermissionRules {
-        always_allow: effective_allow,
-        always_deny: effective_deny,
-        always_ask: effective_ask,
+    let policy = ToolPolicy::from_config(cfg);
+    let origin = |name: &str| {
+        if discovered_mcp_tool_names.contains(name) {
+            ToolOrigin::Mcp
+        } else {
+            ToolOrigin::BuiltIn
+        }
     };
+    tools.retain(|tool| {
+        let filtered = policy.agent_filter(tool.name());
+        if filtered == Some(ToolUnavailable::Denied) {
+            tracing::debug!(tool = tool.name(), "Tool denied by deny_tools policy");
+        }
+        filtered.is_none()
+    });
 
-    // Determine wrapping per tool using layered rules
+    // Deny rules that target content patterns keep the tool; ConfirmingTool
+    // judges each call at runtime.
+    let mut confirmation_required = BTreeSet::<String>::new();
+    let mut rule_decisions = Vec::<(String, PermissionDecision)>::new();
     tools = tools
         .into_iter()
         .map(|tool| {
-            let name = tool.name();
-            let decision = effective_rules.evaluate(name, None);
-
-            match decision {
-                crate::tool_policy::PermissionDecision::Allow => {
-                    // Explicitly allowed — no confirmation, but show display for reads
-                    if is_read_only_tool(name) {
-                        ConfirmingTool::wrap_display_only(tool)
-                    } else {
-                        tool
-                    }
-                }
-                crate::tool_policy::PermissionDecision::Deny => {
-                    // Denied tools are already filtered by filter_tools_by_policy,
-                    // but if a deny rule targets content patterns, the tool stays
-                    // and ConfirmingTool handles per-call denial at runtime.
+            let name = tool.name().to_string();
+            rule_decisions.push((name.clone(), policy.permission_decision(&name)));
+            match policy.confirmation(&name, origin(&name)) {
+                ToolConfirmation::Auto => tool,
+                ToolConfirmation::DisplayOnly => ConfirmingTool::wrap_display_only(tool),
+                ToolConfirmation::Confirm => {
+                    confirmation_required.insert(name);
                     ConfirmingTool::wrap(tool)
                 }
-                crate::tool_policy::PermissionDecision::Ask => {
-                    ConfirmingTool::wrap(tool)
-                }
-                crate::tool_policy::PermissionDecision::NoMatch => {
-                    // Default behavior: read-only tools auto-approve (display-only),
-                    // guarded built-ins and MCP tools require confirmation
-                    if is_read_only_tool(name) {
-                        ConfirmingTool::wrap_display_only(tool)
-                    } else {
-                        match cfg.tool_confirmation_mode {
-                            ToolConfirmationMode::Always => ConfirmingTool::wrap(tool),
-                            ToolConfirmationMode::McpOnly => {
-                                if discovered_mcp_tool_names.contains(name)
-                                    || matches!(name, "fs_write" | "file_edit" | "execute_bash" | "github_ops")
-                                {
-                                    ConfirmingTool::wrap(tool)
-                                } else {
-                                    tool
-                                }
-                            }
-                            ToolConfirmationMode::Never => {
-                                // Still wrap guarded built-ins
-                                if matches!(name, "fs_write" | "file_edit" | "execute_bash" | "github_ops") {
-                                    ConfirmingTool::wrap(tool)
-                                } else {
-                                    tool
-                                }
-                            }
-                        }
-                    }
-                }
             }
         })
         .collect();
 
+    cfg.verbosity.detail(Verbosity::Trace, || {
+        rule_decisions
+            .iter()
+            .map(|(name, decision)| {
+                format_tool_confirmation_decision(
+                    name,
+                    *decision,
+                    confirmation_required.contains(name),
+                )
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    });
+
+    tools = crate::read_only::apply_read_only_tool_policy(cfg, tools, &discovered_mcp_tool_names);
+
+    // Validate built-in tool arguments outside the confirmation wrapper so
+    // malformed calls are rejected before the user is prompted.
+    tools = crate::tools::schemas::wrap_with_arg_validation(tools, cfg.tool_arg_retry_hint);
+
     let mcp_tool_names = tools
         .iter()
         .map(|tool| tool.name().to_string())
@@ -524,9 +413,35 @@ pub async fn resolve_runtime_tools(cfg: &RuntimeConfig) -> ResolvedRuntimeTools
         }
     }
 
+    tools = wrap_with_tool_budgets(
+        tools,
+        &cfg.tool_budgets,
+        &cfg.tool_call_counter,
+        &TelemetrySink::new(cfg, "tool".to_string()),
+    );
+
+    let output_cap = ToolOutputCap {
+        max_chars: cfg.tool_output_max_chars,
+        spillover_dir: cfg.paths.tool_output_dir(),
+        telemetry: TelemetrySink::new(cfg, "tool".to_string()),
+    };
+    tools = wrap_with_output_cap(tools, &output_cap);
+
+    let mcp_tools = mcp_tool_origins
+        .into_iter()
+        .map(|(server, name, description)| McpToolInfo {
+            requires_confirmation: confirmation_required.contains(&name),
+            excluded: !mcp_tool_names.contains(&name),
+            server,
+            name,
+            description,
+        })
+        .collect();
+
     ResolvedRuntimeTools {
         tools,
         mcp_tool_names,
+        mcp_tools,
     }
 }
 
@@ -0,0 +1,242 @@
+//! Terminal-safe model output (`output_sanitize = strip|escape|off`).
+//!
+//! Model text can carry ANSI escape sequences and control characters that
+//! recolor the terminal, move the cursor, rewrite the window title or plant
+//! hyperlinks. Every final print path runs text through this module after
+//! guardrails, so redaction markers are kept while CSI/OSC sequences, C0/C1
+//! controls (other than newline and tab) and lone carriage returns are
+//! stripped or rendered visibly. Printable unicode is never touched.
+//!
+//! [`StreamSanitizer`] keeps state between chunks, so a sequence split across
+//! two streamed deltas is still recognized.
+use serde_json::Value;
+
+use crate::cli::OutputSanitize;
+use crate::config::RuntimeConfig;
+
+/// An unterminated OSC/DCS string longer than this is treated as text: only
+/// its introducer is removed, so a stray `ESC ]` cannot swallow a reply.
+const MAX_CONTROL_STRING_BYTES: usize = 4096;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Text,
+    /// Saw `\r`; kept only when `\n` follows.
+    Cr,
+    /// Saw `ESC`.
+    Esc,
+    /// `ESC` plus intermediate bytes (e.g. `ESC ( B`).
+    EscIntermediate,
+    /// Control sequence: `ESC [` or C1 `CSI`, until a final byte.
+    Csi,
+    /// OSC/DCS/SOS/PM/APC string, until `BEL` or `ST`.
+    ControlString,
+    /// Saw `ESC` inside a control string; `\` completes `ST`.
+    ControlStringEsc,
+}
+
+/// Incremental sanitizer for streamed text.
+#[derive(Debug, Clone)]
+pub struct StreamSanitizer {
+    mode: OutputSanitize,
+    state: State,
+    pending: String,
+}
+
+fn escape_control(c: char) -> String {
+    match c {
+        '\r' => "\\r".to_string(),
+        c if (c as u32) < 0x80 => format!("\\x{:02x}", c as u32),
+        c => format!("\\u{{{:x}}}", c as u32),
+    }
+}
+
+impl StreamSanitizer {
+    pub fn new(mode: OutputSanitize) -> Self {
+        Self {
+            mode,
+            state: State::Text,
+            pending: String::new(),
+        }
+    }
+
+    /// Sanitize the next chunk. Text that may still start a sequence is held
+    /// back until the following chunk or [`StreamSanitizer::finish`].
+    pub fn push(&mut self, chunk: &str) -> String {
+        if self.mode == OutputSanitize::Off {
+            return chunk.to_string();
+        }
+        let mut out = String::with_capacity(chunk.len());
+        for c in chunk.chars() {
+            self.step(c, &mut out);
+        }
+        out
+    }
+
+    /// Flush held-back text at the end of the stream. An unfinished sequence
+    /// is dropped (or shown, in escape mode); a trailing `\r` is a lone CR.
+    pub fn finish(&mut self) -> String {
+        let mut out = String::new();
+        match self.state {
+            State::Text => {}
+            State::Cr => self.control('\r', &mut out),
+            _ => self.end_sequence(&mut out),
+        }
+        self.state = State::Text;
+        out
+    }
+
+    fn control(&self, c: char, out: &mut String) {
+        if self.mode == OutputSanitize::Escape {
+            out.push_str(&escape_control(c));
+        }
+    }
+
+    fn start_sequence(&mut self, c: char, state: State) {
+        self.pending.push(c);
+        self.state = state;
+    }
+
+    fn end_sequence(&mut self, out: &mut String) {
+        let sequence = std::mem::take(&mut self.pending);
+        self.state = State::Text;
+        if self.mode != OutputSanitize::Escape {
+            return;
+        }
+        for c in sequence.chars() {
+            if c.is_control() {
+                out.push_str(&escape_control(c));
+            } else {
+                out.push(c);
+            }
+        }
+    }
+
+    /// `c` cannot continue the current sequence: close it and reread `c`.
+    fn abort_sequence(&mut self, c: char, out: &mut String) {
+        self.end_sequence(out);
+        self.text(c, out);
+    }
+
+    fn text(&mut self, c: char, out: &mut String) {
+        match c {
+            '\n' | '\t' => out.push(c),
+            '\r' => self.state = State::Cr,
+            '\x1b' => self.start_sequence(c, State::Esc),
+            '\u{9b}' => self.start_sequence(c, State::Csi),
+            '\u{90}' | '\u{98}' | '\u{9d}' | '\u{9e}' | '\u{9f}' => {
+                self.start_sequence(c, State::ControlString)
+            }
+            c if c.is_control() => self.control(c, out),
+            c => out.push(c),
+        }
+    }
+
+    fn step(&mut self, c: char, out: &mut String) {
+        match self.state {
+            State::Text => self.text(c, out),
+            State::Cr => {
+                self.state = State::Text;
+                if c == '\n' {
+                    out.push_str("\r\n");
+                } else {
+                    self.control('\r', out);
+                    self.text(c, out);
+                }
+            }
+            State::Esc => match c {
+                '[' => self.start_sequence(c, State::Csi),
+                ']' | 'P' | 'X' | '^' | '_' => self.start_sequence(c, State::ControlString),
+                '\x20'..='\x2f' => self.start_sequence(c, State::EscIntermediate),
+                '\x30'..='\x7e' => {
+                    self.pending.push(c);
+                    self.end_sequence(out);
+                }
+                _ => self.abort_sequence(c, out),
+            },
+            State::EscIntermediate => match c {
+                '\x20'..='\x2f' => self.pending.push(c),
+                '\x30'..='\x7e' => {
+                    self.pending.push(c);
+                    self.end_sequence(out);
+                }
+                _ => self.abort_sequence(c, out),
+            },
+            State::Csi => match c {
+                '\x20'..='\x3f' => self.pending.push(c),
+                '\x40'..='\x7e' => {
+                    self.pending.push(c);
+                    self.end_sequence(out);
+                }
+                _ => self.abort_sequence(c, out),
+            },
+            State::ControlString => match c {
+                '\x07' | '\u{9c}' => {
+                    self.pending.push(c);
+                    self.end_sequence(out);
+                }
+                '\x1b' => self.start_sequence(c, State::ControlStringEsc),
+                c => {
+                    self.pending.push(c);
+                    if self.pending.len() > MAX_CONTROL_STRING_BYTES {
+                        self.release_control_string(out);
+                    }
+                }
+            },
+            State::ControlStringEsc => {
+                if c == '\\' {
+                    self.pending.push(c);
+                    self.end_sequence(out);
+                } else {
+                    // Any other escape ends the string and starts a new sequence.
+                    self.pending.pop();
+                    self.end_sequence(out);
+                    self.text('\x1b', out);
+                    self.step(c, out);
+                }
+            }
+        }
+    }
+
+    /// Give up on an overlong control string: drop its introducer and
+    /// reprocess the rest as ordinary text.
+    fn release_control_string(&mut self, out: &mut String) {
+        let body = std::mem::take(&mut self.pending);
+        let introducer_chars = if body.starts_with('\x1b') { 2 } else { 1 };
+        self.pending = body.chars().take(introducer_chars).collect();
+        self.end_sequence(out);
+        for c in body.chars().skip(introducer_chars) {
+            self.step(c, out);
+        }
+    }
+}
+
+/// Sanitize a complete string.
+pub fn sanitize_text(text: &str, mode: OutputSanitize) -> String {
+    let mut sanitizer = StreamSanitizer::new(mode);
+    let mut out = sanitizer.push(text);
+    out.push_str(&sanitizer.finish());
+    out
+}
+
+/// Sanitize `text` with the configured `output_sanitize` mode.
+pub fn sanitize_output(cfg: &RuntimeConfig, text: &str) -> String {
+    sanitize_text(text, cfg.output_sanitize)
+}
+
+/// Sanitize every string in a JSON document (transcripts, JSON answers).
+pub fn sanitize_json_strings(value: &mut Value, mode: OutputSanitize) {
+    if mode == OutputSanitize::Off {
+        return;
+    }
+    match value {
+        Value::String(text) => *text = sanitize_text(text, mode),
+        Value::Array(items) => items
+            .iter_mut()
+            .for_each(|item| sanitize_json_strings(item, mode)),
+        Value::Object(map) => map
+            .values_mut()
+            .for_each(|item| sanitize_json_strings(item, mode)),
+        _ => {}
+    }
+}
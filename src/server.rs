@@ -1,12 +1,15 @@
 use std::collections::HashMap;
+use std::future::Future;
 use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
+use adk_rust::ToolConfirmationPolicy;
 use adk_rust::prelude::*;
 use adk_session::SessionService;
 use anyhow::{Context, Result};
-use axum::extract::State;
+use axum::extract::rejection::JsonRejection;
+use axum::extract::{DefaultBodyLimit, Query, State};
 use axum::http::StatusCode;
 use axum::routing::{get, post};
 use axum::{Json, Router as AxumRouter};
@@ -15,16 +18,22 @@ use serde_json::{Value, json};
 
 use crate::config::RuntimeConfig;
 use crate::eval::round_metric;
-use crate::guardrail::{apply_guardrail, enforce_prompt_limit};
+use crate::guardrail::apply_guardrail;
+use crate::progress::ProgressEmitter;
 use crate::provider::resolve_model;
-use crate::retrieval::{RetrievalService, build_retrieval_service};
+use crate::retrieval::{
+    DisabledRetrievalService, RetrievalService, build_retrieval_service_with_telemetry,
+};
 use crate::runner::{
-    build_runner_with_session_service, build_single_agent_with_tools, resolve_runtime_tools,
-    resolve_tool_confirmation_settings,
+    build_runner_with_session_service, build_single_agent_with_tools,
+    resolve_runtime_tools_with_retrieval, resolve_tool_confirmation_settings,
 };
+use crate::server_limits::{UserLimitExceeded, UserLimiter, hashed_user_id};
 use crate::session::build_session_service;
-use crate::streaming::run_prompt_with_retrieval;
+use crate::streaming::{AuthorTextTracker, prepare_prompt_with_retrieval, run_prompt_tracked};
 use crate::telemetry::TelemetrySink;
+use crate::usage::BudgetExceeded;
+
 #[derive(Clone)]
 pub struct ServerState {
     pub cfg: RuntimeConfig,
@@ -38,6 +47,7 @@ pub struct ServerState {
     pub runner_cache: Arc<tokio::sync::RwLock<HashMap<String, Arc<Runner>>>>,
     pub auth_token: Option<String>,
     pub runner_cache_max: usize,
+    pub user_limiter: Arc<UserLimiter>,
 }
 
 #[derive(Debug, Serialize)]
@@ -45,6 +55,11 @@ pub struct ServerHealthResponse {
     pub status: &'static str,
     pub app_name: String,
     pub profile: String,
+    pub cli_version: &'static str,
+    pub git_sha: &'static str,
+    /// Per-user limits; omitted when none are configured.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limits: Option<Value>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -54,6 +69,13 @@ pub struct ServerAskRequest {
     pub user_id: Option<String>,
 }
 
+/// `/v1/ask` query string; `debug=true` adds the context budget.
+#[derive(Debug, Default, Deserialize)]
+pub struct ServerAskQuery {
+    #[serde(default)]
+    pub debug: bool,
+}
+
 #[derive(Debug, Serialize)]
 pub struct ServerAskResponse {
     pub answer: String,
@@ -61,6 +83,8 @@ pub struct ServerAskResponse {
     pub model: String,
     pub session_id: String,
     pub user_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context_budget: Option<Value>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -84,6 +108,35 @@ pub struct A2aPingResponse {
     pub payload: Value,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct A2aTaskRequest {
+    pub from_agent: String,
+    pub to_agent: String,
+    pub task_id: String,
+    pub instruction: String,
+    pub correlation_id: Option<String>,
+    #[serde(default)]
+    pub context: Option<Value>,
+    pub deadline_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum A2aTaskStatus {
+    Completed,
+    Failed,
+    Timeout,
+}
+
+#[derive(Debug, Serialize)]
+pub struct A2aTaskResponse {
+    pub task_id: String,
+    pub status: A2aTaskStatus,
+    pub result: String,
+    pub duration_ms: u128,
+    pub correlation_id: String,
+}
+
 pub type ApiError = (StatusCode, Json<Value>);
 pub type ApiResult<T> = std::result::Result<Json<T>, ApiError>;
 
@@ -91,6 +144,128 @@ pub fn api_error(status: StatusCode, message: impl Into<String>) -> ApiError {
     (status, Json(json!({ "error": message.into() })))
 }
 
+/// Structured error body: `error` + machine-readable `code` + any `extra` fields.
+pub fn api_error_with_code(
+    status: StatusCode,
+    code: &str,
+    message: impl Into<String>,
+    extra: Value,
+) -> ApiError {
+    let mut body = serde_json::Map::new();
+    body.insert("error".to_string(), json!(message.into()));
+    body.insert("code".to_string(), json!(code));
+    if let Value::Object(map) = extra {
+        body.extend(map);
+    }
+    (status, Json(Value::Object(body)))
+}
+
+/// Pick the status code for a validation failure. `server_legacy_status_codes`
+/// keeps the pre-422 codes for clients that have not migrated yet.
+pub fn server_validation_status(cfg: &RuntimeConfig, legacy: StatusCode) -> StatusCode {
+    if cfg.server_legacy_status_codes {
+        legacy
+    } else {
+        StatusCode::UNPROCESSABLE_ENTITY
+    }
+}
+
+/// Map a JSON extractor rejection to a structured error; body-limit hits
+/// become 413 `body_too_large`.
+pub fn json_rejection_error(cfg: &RuntimeConfig, rejection: JsonRejection) -> ApiError {
+    let status = rejection.status();
+    if status == StatusCode::PAYLOAD_TOO_LARGE {
+        return api_error_with_code(
+            status,
+            "body_too_large",
+            format!(
+                "request body exceeds server_max_body_bytes ({} bytes)",
+                cfg.server_max_body_bytes
+            ),
+            json!({ "max_body_bytes": cfg.server_max_body_bytes }),
+        );
+    }
+    api_error_with_code(status, "invalid_request", rejection.body_text(), json!({}))
+}
+
+pub fn guardrail_blocked_error(
+    cfg: &RuntimeConfig,
+    direction: &str,
+    legacy: StatusCode,
+    err: anyhow::Error,
+) -> ApiError {
+    api_error_with_code(
+        server_validation_status(cfg, legacy),
+        "guardrail_blocked",
+        err.to_string(),
+        json!({ "direction": direction }),
+    )
+}
+
+/// Map a prompt-run failure to an API error; budget refusals become 429
+/// with the reset time so clients can back off.
+pub fn server_run_error(err: &anyhow::Error) -> ApiError {
+    if let Some(exceeded) = err.downcast_ref::<BudgetExceeded>() {
+        let retry_after_secs = (exceeded.reset_at - chrono::Utc::now())
+            .num_seconds()
+            .max(0);
+        return api_error_with_code(
+            StatusCode::TOO_MANY_REQUESTS,
+            "budget_exceeded",
+            exceeded.to_string(),
+            json!({
+                "reset_at": exceeded.reset_at.to_rfc3339(),
+                "retry_after_secs": retry_after_secs
+            }),
+        );
+    }
+    api_error(StatusCode::INTERNAL_SERVER_ERROR, err.to_string())
+}
+
+/// Admit a request from `user_id` under the per-user limits, or refuse it
+/// with 429 and emit `server.rate_limited`.
+pub fn enforce_user_limits(
+    state: &ServerState,
+    user_id: &str,
+    endpoint: &str,
+) -> Result<crate::server_limits::UserPermit, ApiError> {
+    state.user_limiter.try_acquire(user_id).map_err(|exceeded| {
+        state.telemetry.emit(
+            "server.rate_limited",
+            json!({
+                "user_hash": hashed_user_id(user_id),
+                "endpoint": endpoint,
+                "limit_kind": exceeded.kind.label(),
+                "limit": exceeded.limit,
+                "retry_after_secs": exceeded.retry_after_secs
+            }),
+        );
+        user_limit_error(&exceeded)
+    })
+}
+
+pub fn user_limit_error(exceeded: &UserLimitExceeded) -> ApiError {
+    let setting = match exceeded.kind {
+        crate::server_limits::UserLimitKind::Concurrency => "server_per_user_max_concurrent",
+        crate::server_limits::UserLimitKind::RequestsPerMinute => {
+            "server_per_user_requests_per_minute"
+        }
+    };
+    api_error_with_code(
+        StatusCode::TOO_MANY_REQUESTS,
+        "rate_limited",
+        format!(
+            "per-user limit exceeded: {setting} is {}; retry in {}s",
+            exceeded.limit, exceeded.retry_after_secs
+        ),
+        json!({
+            "limit_kind": exceeded.kind.label(),
+            "limit": exceeded.limit,
+            "retry_after_secs": exceeded.retry_after_secs
+        }),
+    )
+}
+
 pub fn server_runner_cache_key(cfg: &RuntimeConfig) -> String {
     format!("{}::{}", cfg.user_id, cfg.session_id)
 }
@@ -166,15 +341,20 @@ pub async fn handle_server_health(
         status: "ok",
         app_name: state.cfg.app_name.clone(),
         profile: state.cfg.profile.clone(),
+        cli_version: crate::build_info::CLI_VERSION,
+        git_sha: crate::build_info::GIT_SHA,
+        limits: state.user_limiter.status(),
     })
 }
 
 pub async fn handle_server_ask(
     State(state): State<Arc<ServerState>>,
     headers: axum::http::HeaderMap,
-    Json(request): Json<ServerAskRequest>,
+    Query(query): Query<ServerAskQuery>,
+    payload: std::result::Result<Json<ServerAskRequest>, JsonRejection>,
 ) -> ApiResult<ServerAskResponse> {
     check_server_auth(&state, &headers)?;
+    let Json(request) = payload.map_err(|rejection| json_rejection_error(&state.cfg, rejection))?;
     let started_at = Instant::now();
     let mut cfg = state.cfg.clone();
     if let Some(session_id) = request.session_id {
@@ -186,14 +366,30 @@ pub async fn handle_server_ask(
 
     let prompt = request.prompt.trim().to_string();
     if prompt.is_empty() {
-        return Err(api_error(
+        return Err(api_error_with_code(
             StatusCode::BAD_REQUEST,
+            "invalid_request",
             "prompt cannot be empty for /v1/ask",
+            json!({}),
         ));
     }
 
-    enforce_prompt_limit(&prompt, cfg.max_prompt_chars)
-        .map_err(|err| api_error(StatusCode::BAD_REQUEST, err.to_string()))?;
+    let prompt_chars = prompt.chars().count();
+    if prompt_chars > cfg.server_max_prompt_chars {
+        return Err(api_error_with_code(
+            server_validation_status(&cfg, StatusCode::BAD_REQUEST),
+            "prompt_too_long",
+            format!(
+                "prompt exceeds maximum length ({} chars > {} limit)",
+                prompt_chars, cfg.server_max_prompt_chars
+            ),
+            json!({
+                "prompt_chars": prompt_chars,
+                "max_prompt_chars": cfg.server_max_prompt_chars
+            }),
+        ));
+    }
+    let _permit = enforce_user_limits(&state, &cfg.user_id, "/v1/ask")?;
 
     let guarded_prompt = apply_guardrail(
         &cfg,
@@ -202,21 +398,33 @@ pub async fn handle_server_ask(
         cfg.guardrail_input_mode,
         &prompt,
     )
-    .map_err(|err| api_error(StatusCode::BAD_REQUEST, err.to_string()))?;
+    .map_err(|err| guardrail_blocked_error(&cfg, "input", StatusCode::BAD_REQUEST, err))?;
 
     let (runner, cache_status) = get_or_build_server_runner(&state, &cfg)
         .await
         .map_err(|err| api_error(StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))?;
 
-    let answer = run_prompt_with_retrieval(
-        runner.as_ref(),
+    let (enriched, budget) = prepare_prompt_with_retrieval(
         &cfg,
-        &guarded_prompt,
         state.retrieval.as_ref(),
+        &guarded_prompt,
+        &state.telemetry,
+    )
+    .map_err(|err| server_run_error(&err))?;
+    let mut tracker = AuthorTextTracker::default();
+    let answer = run_prompt_tracked(
+        runner.as_ref(),
+        &cfg,
+        &enriched,
         &state.telemetry,
+        &ProgressEmitter::disabled(),
+        &mut tracker,
     )
     .await
-    .map_err(|err| api_error(StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))?;
+    .map_err(|err| server_run_error(&err))?;
+    let first_token_ms = tracker
+        .stream_stats()
+        .and_then(|stats| stats.first_token_ms);
     let answer = apply_guardrail(
         &cfg,
         &state.telemetry,
@@ -224,7 +432,7 @@ pub async fn handle_server_ask(
         cfg.guardrail_output_mode,
         &answer,
     )
-    .map_err(|err| api_error(StatusCode::FORBIDDEN, err.to_string()))?;
+    .map_err(|err| guardrail_blocked_error(&cfg, "output", StatusCode::FORBIDDEN, err))?;
 
     state.telemetry.emit(
         "server.ask.completed",
@@ -234,7 +442,8 @@ pub async fn handle_server_ask(
             "session_id": cfg.session_id.clone(),
             "user_id": cfg.user_id.clone(),
             "runner_cache": cache_status,
-            "latency_ms": round_metric(started_at.elapsed().as_secs_f64() * 1000.0)
+            "latency_ms": round_metric(started_at.elapsed().as_secs_f64() * 1000.0),
+            "first_token_ms": first_token_ms
         }),
     );
 
@@ -244,6 +453,7 @@ pub async fn handle_server_ask(
         model: state.model_name.clone(),
         session_id: cfg.session_id,
         user_id: cfg.user_id,
+        context_budget: query.debug.then(|| budget.compact()),
     }))
 }
 
@@ -280,9 +490,10 @@ pub fn process_a2a_ping(request: A2aPingRequest) -> Result<A2aPingResponse> {
 pub async fn handle_a2a_ping(
     State(state): State<Arc<ServerState>>,
     headers: axum::http::HeaderMap,
-    Json(request): Json<A2aPingRequest>,
+    payload: std::result::Result<Json<A2aPingRequest>, JsonRejection>,
 ) -> ApiResult<A2aPingResponse> {
     check_server_auth(&state, &headers)?;
+    let Json(request) = payload.map_err(|rejection| json_rejection_error(&state.cfg, rejection))?;
     state.telemetry.emit(
         "a2a.ping.received",
         json!({
@@ -303,11 +514,246 @@ pub async fn handle_a2a_ping(
     Ok(Json(response))
 }
 
+pub fn validate_a2a_task(request: &A2aTaskRequest) -> Result<()> {
+    if request.from_agent.trim().is_empty() {
+        return Err(anyhow::anyhow!("from_agent is required for A2A task"));
+    }
+    if request.to_agent.trim().is_empty() {
+        return Err(anyhow::anyhow!("to_agent is required for A2A task"));
+    }
+    if request.task_id.trim().is_empty() {
+        return Err(anyhow::anyhow!("task_id is required for A2A task"));
+    }
+    if request.instruction.trim().is_empty() {
+        return Err(anyhow::anyhow!("instruction is required for A2A task"));
+    }
+    if request.deadline_ms == Some(0) {
+        return Err(anyhow::anyhow!(
+            "deadline_ms must be greater than 0 for A2A task"
+        ));
+    }
+    Ok(())
+}
+
+/// Session used for a delegated task, so tasks never share history with
+/// `/v1/ask` sessions or with each other.
+pub fn a2a_task_session_id(task_id: &str) -> String {
+    let sanitized: String = task_id
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("a2a-task-{sanitized}")
+}
+
+pub fn a2a_task_prompt(request: &A2aTaskRequest) -> String {
+    let instruction = request.instruction.trim();
+    match &request.context {
+        Some(context) if !context.is_null() => format!(
+            "Task delegated by agent '{}':\n{instruction}\n\nContext:\n{}",
+            request.from_agent.trim(),
+            serde_json::to_string_pretty(context).unwrap_or_else(|_| context.to_string())
+        ),
+        _ => format!(
+            "Task delegated by agent '{}':\n{instruction}",
+            request.from_agent.trim()
+        ),
+    }
+}
+
+/// Await `run`, giving up after `deadline`. `None` means the deadline passed.
+pub async fn run_with_deadline<F: Future>(deadline: Option<Duration>, run: F) -> Option<F::Output> {
+    match deadline {
+        Some(deadline) => tokio::time::timeout(deadline, run).await.ok(),
+        None => Some(run.await),
+    }
+}
+
+/// Map a task run to its status and result text. `run` is `None` when the
+/// deadline passed; `partial` is whatever text was produced before that.
+pub fn a2a_task_outcome(
+    run: Option<Result<String>>,
+    partial: Option<String>,
+) -> (A2aTaskStatus, String) {
+    match run {
+        Some(Ok(answer)) => (A2aTaskStatus::Completed, answer),
+        Some(Err(err)) => (A2aTaskStatus::Failed, format!("{err:#}")),
+        None => (A2aTaskStatus::Timeout, partial.unwrap_or_default()),
+    }
+}
+
+/// Run the output guardrail over text produced before a deadline cut the
+/// task short. A blocked partial answer is dropped rather than returned.
+pub fn guard_a2a_partial(
+    cfg: &RuntimeConfig,
+    telemetry: &TelemetrySink,
+    partial: Option<String>,
+) -> Option<String> {
+    let partial = partial?;
+    apply_guardrail(
+        cfg,
+        telemetry,
+        "output",
+        cfg.guardrail_output_mode,
+        &partial,
+    )
+    .ok()
+}
+
+/// Run a validated task through the server agent with guardrails and
+/// retrieval, in its own session, honoring `deadline_ms`.
+pub async fn execute_a2a_task(state: &ServerState, request: A2aTaskRequest) -> A2aTaskResponse {
+    let started_at = Instant::now();
+    let mut cfg = state.cfg.clone();
+    cfg.session_id = a2a_task_session_id(&request.task_id);
+    let correlation_id = request
+        .correlation_id
+        .clone()
+        .unwrap_or_else(|| request.task_id.clone());
+
+    let mut tracker = AuthorTextTracker::default();
+    let run = run_with_deadline(
+        request.deadline_ms.map(Duration::from_millis),
+        run_a2a_task_prompt(state, &cfg, a2a_task_prompt(&request), &mut tracker),
+    )
+    .await;
+    let partial = if run.is_none() {
+        guard_a2a_partial(&cfg, &state.telemetry, tracker.resolve_text())
+    } else {
+        None
+    };
+    let (status, result) = a2a_task_outcome(run, partial);
+    let duration_ms = started_at.elapsed().as_millis();
+
+    let event = match status {
+        A2aTaskStatus::Completed => "a2a.task.completed",
+        A2aTaskStatus::Failed => "a2a.task.failed",
+        A2aTaskStatus::Timeout => "a2a.task.timeout",
+    };
+    state.telemetry.emit(
+        event,
+        json!({
+            "task_id": request.task_id.clone(),
+            "from_agent": request.from_agent,
+            "to_agent": request.to_agent,
+            "session_id": cfg.session_id,
+            "correlation_id": correlation_id.clone(),
+            "duration_ms": duration_ms,
+            "result_chars": result.chars().count()
+        }),
+    );
+
+    A2aTaskResponse {
+        task_id: request.task_id,
+        status,
+        result,
+        duration_ms,
+        correlation_id,
+    }
+}
+
+async fn run_a2a_task_prompt(
+    state: &ServerState,
+    cfg: &RuntimeConfig,
+    prompt: String,
+    tracker: &mut AuthorTextTracker,
+) -> Result<String> {
+    enforce_server_prompt_limit(cfg, &prompt)?;
+    let guarded_prompt = apply_guardrail(
+        cfg,
+        &state.telemetry,
+        "input",
+        cfg.guardrail_input_mode,
+        &prompt,
+    )?;
+    let (enriched, _) = prepare_prompt_with_retrieval(
+        cfg,
+        state.retrieval.as_ref(),
+        &guarded_prompt,
+        &state.telemetry,
+    )?;
+    let runner = build_runner_with_session_service(
+        state.server_agent.clone(),
+        cfg,
+        state.session_service.clone(),
+        Some(state.run_config.clone()),
+    )
+    .await?;
+    let answer = run_prompt_tracked(
+        &runner,
+        cfg,
+        &enriched,
+        &state.telemetry,
+        &ProgressEmitter::disabled(),
+        tracker,
+    )
+    .await?;
+    apply_guardrail(
+        cfg,
+        &state.telemetry,
+        "output",
+        cfg.guardrail_output_mode,
+        &answer,
+    )
+}
+
+fn enforce_server_prompt_limit(cfg: &RuntimeConfig, prompt: &str) -> Result<()> {
+    let prompt_chars = prompt.chars().count();
+    if prompt_chars > cfg.server_max_prompt_chars {
+        return Err(anyhow::anyhow!(
+            "prompt exceeds maximum length ({} chars > {} limit)",
+            prompt_chars,
+            cfg.server_max_prompt_chars
+        ));
+    }
+    Ok(())
+}
+
+pub async fn handle_a2a_task(
+    State(state): State<Arc<ServerState>>,
+    headers: axum::http::HeaderMap,
+    payload: std::result::Result<Json<A2aTaskRequest>, JsonRejection>,
+) -> ApiResult<A2aTaskResponse> {
+    check_server_auth(&state, &headers)?;
+    let Json(request) = payload.map_err(|rejection| json_rejection_error(&state.cfg, rejection))?;
+    state.telemetry.emit(
+        "a2a.task.received",
+        json!({
+            "from_agent": request.from_agent.clone(),
+            "to_agent": request.to_agent.clone(),
+            "task_id": request.task_id.clone(),
+            "deadline_ms": request.deadline_ms
+        }),
+    );
+    if let Err(err) = validate_a2a_task(&request) {
+        state.telemetry.emit(
+            "a2a.task.rejected",
+            json!({ "task_id": request.task_id, "error": err.to_string() }),
+        );
+        return Err(api_error_with_code(
+            server_validation_status(&state.cfg, StatusCode::BAD_REQUEST),
+            "invalid_request",
+            err.to_string(),
+            json!({}),
+        ));
+    }
+    Ok(Json(execute_a2a_task(&state, request).await))
+}
+
 pub fn build_server_router(state: Arc<ServerState>) -> AxumRouter {
+    let max_body_bytes = state.cfg.server_max_body_bytes;
     AxumRouter::new()
         .route("/healthz", get(handle_server_health))
         .route("/v1/ask", post(handle_server_ask))
         .route("/v1/a2a/ping", post(handle_a2a_ping))
+        .route("/v1/a2a/task", post(handle_a2a_task))
+        .layer(DefaultBodyLimit::max(max_body_bytes))
         .with_state(state)
 }
 
@@ -320,8 +766,8 @@ pub async fn run_server(
     let addr = format!("{host}:{port}")
         .parse::<SocketAddr>()
         .with_context(|| format!("invalid server bind address '{}:{}'", host, port))?;
-    let retrieval = build_retrieval_service(&cfg)?;
-    let runtime_tools = resolve_runtime_tools(&cfg).await;
+    let retrieval = build_retrieval_service_with_telemetry(&cfg, Some(telemetry))?;
+    let runtime_tools = resolve_runtime_tools_with_retrieval(&cfg, Some(retrieval.clone())).await;
     let tool_confirmation = resolve_tool_confirmation_settings(&cfg, &runtime_tools);
     let (model, resolved_provider, model_name) = resolve_model(&cfg)?;
     let provider_label = format!("{:?}", resolved_provider).to_ascii_lowercase();
@@ -367,6 +813,7 @@ pub async fn run_server(
             .map(|v| v.trim().to_string())
             .filter(|v| !v.is_empty()),
         runner_cache_max: cfg.server_runner_cache_max.max(1),
+        user_limiter: Arc::new(UserLimiter::from_config(&cfg)),
     });
 
     telemetry.emit(
@@ -382,7 +829,7 @@ pub async fn run_server(
     );
 
     println!(
-        "Server mode listening on http://{} (health: /healthz, ask: /v1/ask, a2a: /v1/a2a/ping)",
+        "Server mode listening on http://{} (health: /healthz, ask: /v1/ask, a2a: /v1/a2a/ping, /v1/a2a/task)",
         addr
     );
 
@@ -419,7 +866,7 @@ pub async fn shutdown_signal() {
     }
 }
 
-pub fn run_a2a_smoke(telemetry: &TelemetrySink) -> Result<()> {
+pub async fn run_a2a_smoke(cfg: &RuntimeConfig, telemetry: &TelemetrySink) -> Result<()> {
     let request = A2aPingRequest {
         from_agent: "sales-agent".to_string(),
         to_agent: "procurement-agent".to_string(),
@@ -443,6 +890,8 @@ pub fn run_a2a_smoke(telemetry: &TelemetrySink) -> Result<()> {
         ));
     }
 
+    run_a2a_task_smoke(cfg, telemetry).await?;
+
     telemetry.emit(
         "a2a.smoke.passed",
         json!({
@@ -451,6 +900,66 @@ pub fn run_a2a_smoke(telemetry: &TelemetrySink) -> Result<()> {
             "message_id": request.message_id
         }),
     );
-    println!("A2A smoke passed: basic request/ack contract is valid.");
+    println!("A2A smoke passed: ping/ack and task delegation contracts are valid.");
+    Ok(())
+}
+
+/// Delegate a fixture task to a MockLlm-backed agent in memory; no provider
+/// or network is involved.
+async fn run_a2a_task_smoke(cfg: &RuntimeConfig, telemetry: &TelemetrySink) -> Result<()> {
+    const EXPECTED: &str = "Supply check complete: 42 units available.";
+    let model: Arc<dyn Llm> = Arc::new(
+        adk_rust::model::MockLlm::new("a2a-smoke")
+            .with_response(LlmResponse::new(Content::new("model").with_text(EXPECTED))),
+    );
+    let server_agent = build_single_agent_with_tools(
+        model,
+        &[],
+        ToolConfirmationPolicy::Never,
+        Duration::from_secs(cfg.tool_timeout_secs),
+        None,
+    )?;
+    let mut smoke_cfg = cfg.clone();
+    smoke_cfg.session_backend = crate::cli::SessionBackend::Memory;
+    let state = ServerState {
+        cfg: smoke_cfg,
+        retrieval: Arc::new(DisabledRetrievalService),
+        telemetry: telemetry.clone(),
+        server_agent,
+        session_service: Arc::new(adk_session::InMemorySessionService::new()),
+        run_config: RunConfig::default(),
+        provider_label: "mock".to_string(),
+        model_name: "a2a-smoke".to_string(),
+        runner_cache: Arc::new(tokio::sync::RwLock::new(HashMap::new())),
+        auth_token: None,
+        runner_cache_max: 1,
+        user_limiter: Arc::new(UserLimiter::new(0, 0)),
+    };
+    let request = A2aTaskRequest {
+        from_agent: "sales-agent".to_string(),
+        to_agent: "procurement-agent".to_string(),
+        task_id: "task-001".to_string(),
+        instruction: "Check supply for SKU-42".to_string(),
+        correlation_id: Some("corr-task-001".to_string()),
+        context: Some(json!({ "sku": "SKU-42" })),
+        deadline_ms: Some(30_000),
+    };
+    validate_a2a_task(&request)?;
+    let response = execute_a2a_task(&state, request).await;
+
+    if response.status != A2aTaskStatus::Completed {
+        return Err(anyhow::anyhow!(
+            "a2a smoke failed: task finished with status {:?}: {}",
+            response.status,
+            response.result
+        ));
+    }
+    if response.result != EXPECTED || response.correlation_id != "corr-task-001" {
+        return Err(anyhow::anyhow!(
+            "a2a smoke failed: unexpected task response (result='{}', correlation_id='{}')",
+            response.result,
+            response.correlation_id
+        ));
+    }
     Ok(())
 }
@@ -0,0 +1,196 @@
+//! Per-user limits for server mode (`server_per_user_max_concurrent`,
+//! `server_per_user_requests_per_minute`).
+//!
+//! Requests are keyed by the resolved `user_id`. Concurrency is a semaphore
+//! per user; the rate is a sliding one-minute window of accepted requests.
+//! Refused requests are not counted. Idle users (nothing in flight, nothing
+//! in the window) are swept at most once per window, and immediately when
+//! the map reaches `MAX_TRACKED_USERS`, so the map only holds users active in
+//! the last minute.
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde_json::{Value, json};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::config::RuntimeConfig;
+
+pub const RATE_WINDOW: Duration = Duration::from_secs(60);
+pub const MAX_TRACKED_USERS: usize = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UserLimitKind {
+    Concurrency,
+    RequestsPerMinute,
+}
+
+impl UserLimitKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            UserLimitKind::Concurrency => "concurrency",
+            UserLimitKind::RequestsPerMinute => "requests_per_minute",
+        }
+    }
+}
+
+/// Why a request was refused.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserLimitExceeded {
+    pub kind: UserLimitKind,
+    pub limit: u64,
+    pub retry_after_secs: u64,
+}
+
+#[derive(Debug)]
+struct UserState {
+    in_flight: Arc<Semaphore>,
+    window: VecDeque<Instant>,
+}
+
+impl UserState {
+    fn prune(&mut self, now: Instant) {
+        while let Some(oldest) = self.window.front()
+            && now.saturating_duration_since(*oldest) >= RATE_WINDOW
+        {
+            self.window.pop_front();
+        }
+    }
+}
+
+#[derive(Debug)]
+struct LimiterState {
+    users: HashMap<String, UserState>,
+    last_sweep: Instant,
+}
+
+/// Held for the duration of a request; releases the concurrency slot on drop.
+#[derive(Debug)]
+pub struct UserPermit {
+    _slot: Option<OwnedSemaphorePermit>,
+}
+
+/// Limits shared by every request handler. 0 disables a limit.
+#[derive(Debug)]
+pub struct UserLimiter {
+    pub max_concurrent: usize,
+    pub requests_per_minute: u32,
+    state: Mutex<LimiterState>,
+}
+
+impl UserLimiter {
+    pub fn new(max_concurrent: usize, requests_per_minute: u32) -> Self {
+        Self {
+            max_concurrent,
+            requests_per_minute,
+            state: Mutex::new(LimiterState {
+                users: HashMap::new(),
+                last_sweep: Instant::now(),
+            }),
+        }
+    }
+
+    pub fn from_config(cfg: &RuntimeConfig) -> Self {
+        Self::new(
+            cfg.server_per_user_max_concurrent,
+            cfg.server_per_user_requests_per_minute,
+        )
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.max_concurrent > 0 || self.requests_per_minute > 0
+    }
+
+    pub fn try_acquire(&self, user_id: &str) -> Result<UserPermit, UserLimitExceeded> {
+        self.try_acquire_at(user_id, Instant::now())
+    }
+
+    /// Admit a request from `user_id` at `now`, or report the limit it hits.
+    pub fn try_acquire_at(
+        &self,
+        user_id: &str,
+        now: Instant,
+    ) -> Result<UserPermit, UserLimitExceeded> {
+        if !self.is_enabled() {
+            return Ok(UserPermit { _slot: None });
+        }
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if now.saturating_duration_since(state.last_sweep) >= RATE_WINDOW
+            || state.users.len() >= MAX_TRACKED_USERS
+        {
+            self.sweep(&mut state, now);
+        }
+        let max_concurrent = self.max_concurrent;
+        let user = state
+            .users
+            .entry(user_id.to_string())
+            .or_insert_with(|| UserState {
+                in_flight: Arc::new(Semaphore::new(max_concurrent.max(1))),
+                window: VecDeque::new(),
+            });
+        user.prune(now);
+
+        let rpm = self.requests_per_minute as usize;
+        if rpm > 0
+            && user.window.len() >= rpm
+            && let Some(oldest) = user.window.front()
+        {
+            let reopens_in = RATE_WINDOW.saturating_sub(now.saturating_duration_since(*oldest));
+            return Err(UserLimitExceeded {
+                kind: UserLimitKind::RequestsPerMinute,
+                limit: rpm as u64,
+                retry_after_secs: reopens_in.as_secs_f64().ceil().max(1.0) as u64,
+            });
+        }
+        let slot = if max_concurrent > 0 {
+            let Ok(slot) = user.in_flight.clone().try_acquire_owned() else {
+                return Err(UserLimitExceeded {
+                    kind: UserLimitKind::Concurrency,
+                    limit: max_concurrent as u64,
+                    retry_after_secs: 1,
+                });
+            };
+            Some(slot)
+        } else {
+            None
+        };
+        if rpm > 0 {
+            user.window.push_back(now);
+        }
+        Ok(UserPermit { _slot: slot })
+    }
+
+    /// Drop users with nothing in flight and an empty window.
+    fn sweep(&self, state: &mut LimiterState, now: Instant) {
+        let slots = self.max_concurrent.max(1);
+        state.users.retain(|_, user| {
+            user.prune(now);
+            !user.window.is_empty() || user.in_flight.available_permits() < slots
+        });
+        state.last_sweep = now;
+    }
+
+    pub fn tracked_users(&self) -> usize {
+        self.state
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .users
+            .len()
+    }
+
+    /// Limits as reported by `/healthz`; `None` when both are off.
+    pub fn status(&self) -> Option<Value> {
+        self.is_enabled().then(|| {
+            json!({
+                "per_user_max_concurrent": self.max_concurrent,
+                "per_user_requests_per_minute": self.requests_per_minute,
+                "tracked_users": self.tracked_users(),
+            })
+        })
+    }
+}
+
+/// Stable short hash of a user id for telemetry.
+pub fn hashed_user_id(user_id: &str) -> String {
+    crate::session_bundle::sha256_hex(user_id.as_bytes())[..16].to_string()
+}
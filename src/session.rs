@@ -1,26 +1,47 @@
 use std::collections::HashMap;
+use std::future::Future;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use adk_rust::prelude::*;
 use adk_session::*;
 use anyhow::{Context, Result};
+use serde_json::json;
 
 use crate::cli::SessionBackend;
 use crate::config::RuntimeConfig;
+use crate::error::redact_sensitive_text;
+use crate::guardrail::redact_text_with_matcher;
+use crate::pager::page_or_print;
+use crate::retrieval::query_terms;
+use crate::session_crypto::{EncryptedSessionService, check_session_key, session_key};
 use crate::streaming::event_text;
+use crate::telemetry::TelemetrySink;
+use crate::ui::Ui;
+
+pub const DEFAULT_SESSION_BUSY_RETRY_ATTEMPTS: u32 = 5;
+pub const DEFAULT_SESSION_BUSY_RETRY_MAX_MS: u64 = 2_000;
+const SESSION_BUSY_RETRY_BASE_MS: u64 = 50;
 
 pub async fn build_session_service(cfg: &RuntimeConfig) -> Result<Arc<dyn SessionService>> {
     match cfg.session_backend {
         SessionBackend::Memory => Ok(Arc::new(InMemorySessionService::new())),
         SessionBackend::Sqlite => {
-            let service = open_sqlite_session_service(&cfg.session_db_url).await?;
-            Ok(Arc::new(service))
+            let service = Arc::new(open_sqlite_session_service(&cfg.session_db_url).await?);
+            match session_key(cfg)? {
+                Some(key) => {
+                    let (cipher, _) = check_session_key(&cfg.session_db_url, &key).await?;
+                    Ok(Arc::new(EncryptedSessionService::new(service, cipher)))
+                }
+                None => Ok(service),
+            }
         }
     }
 }
 
 pub async fn open_sqlite_session_service(db_url: &str) -> Result<SqliteSessionService> {
+    let creating = sqlite_path_from_url(db_url).is_some_and(|path| !path.exists());
     ensure_parent_dir_for_sqlite_url(db_url)?;
     let service = SqliteSessionService::new(db_url)
         .await
@@ -29,41 +50,133 @@ pub async fn open_sqlite_session_service(db_url: &str) -> Result<SqliteSessionSe
         .migrate()
         .await
         .context("failed to run sqlite session migrations")?;
+    // Existing databases are recorded by `migrate up`; only a schema created
+    // here is recorded right away, so normal commands stay off the ledger.
+    if creating && let Err(err) = crate::migrations::record_adk_schema_migration(db_url).await {
+        tracing::warn!(error = %err, "failed to update migration ledger");
+    }
     Ok(service)
 }
 
+/// Whether a session error is sqlite lock contention (`SQLITE_BUSY` /
+/// `SQLITE_LOCKED`) that is worth retrying.
+pub fn is_sqlite_busy_error(message: &str) -> bool {
+    let message = message.to_ascii_lowercase();
+    [
+        "database is locked",
+        "database table is locked",
+        "database is busy",
+        "sqlite_busy",
+        "sqlite_locked",
+    ]
+    .iter()
+    .any(|needle| message.contains(needle))
+}
+
+/// Delay before retry `attempt` (1-based): 50ms doubling, capped at `max_ms`.
+pub fn session_busy_backoff(attempt: u32, max_ms: u64) -> Duration {
+    let factor = 1u64 << attempt.saturating_sub(1).min(16);
+    Duration::from_millis(
+        SESSION_BUSY_RETRY_BASE_MS
+            .saturating_mul(factor)
+            .min(max_ms),
+    )
+}
+
+/// Run a session call, retrying up to `session_busy_retry_attempts` times
+/// while `is_busy` classifies the error as lock contention. Each retry emits
+/// `session.retry`; other errors are returned immediately.
+pub async fn retry_session_busy<T, E, F, Fut>(
+    cfg: &RuntimeConfig,
+    telemetry: &TelemetrySink,
+    operation: &str,
+    is_busy: impl Fn(&E) -> bool,
+    mut call: F,
+) -> std::result::Result<T, E>
+where
+    E: std::fmt::Display,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = std::result::Result<T, E>>,
+{
+    let mut retries = 0;
+    loop {
+        match call().await {
+            Err(err) if retries < cfg.session_busy_retry_attempts && is_busy(&err) => {
+                retries += 1;
+                let delay = session_busy_backoff(retries, cfg.session_busy_retry_max_ms);
+                telemetry.emit(
+                    "session.retry",
+                    json!({
+                        "operation": operation,
+                        "attempt": retries,
+                        "max_attempts": cfg.session_busy_retry_attempts,
+                        "delay_ms": delay.as_millis() as u64,
+                        "error": err.to_string()
+                    }),
+                );
+                tokio::time::sleep(delay).await;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// `retry_session_busy` with the sqlite lock classifier.
+pub async fn with_session_busy_retry<T, E, F, Fut>(
+    cfg: &RuntimeConfig,
+    operation: &str,
+    call: F,
+) -> std::result::Result<T, E>
+where
+    E: std::fmt::Display,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = std::result::Result<T, E>>,
+{
+    let telemetry = TelemetrySink::new(cfg, "session".to_string());
+    retry_session_busy(
+        cfg,
+        &telemetry,
+        operation,
+        |err: &E| is_sqlite_busy_error(&err.to_string()),
+        call,
+    )
+    .await
+}
+
 pub async fn ensure_session_exists(
     session_service: &Arc<dyn SessionService>,
     cfg: &RuntimeConfig,
 ) -> Result<()> {
-    let session = session_service
-        .get(GetRequest {
+    let session = with_session_busy_retry(cfg, "get", || {
+        session_service.get(GetRequest {
             app_name: cfg.app_name.clone(),
             user_id: cfg.user_id.clone(),
             session_id: cfg.session_id.clone(),
             num_recent_events: None,
             after: None,
         })
-        .await;
+    })
+    .await;
 
     if session.is_ok() {
         return Ok(());
     }
 
-    session_service
-        .create(CreateRequest {
+    with_session_busy_retry(cfg, "create", || {
+        session_service.create(CreateRequest {
             app_name: cfg.app_name.clone(),
             user_id: cfg.user_id.clone(),
             session_id: Some(cfg.session_id.clone()),
             state: HashMap::new(),
         })
-        .await
-        .with_context(|| {
-            format!(
-                "failed to create session '{}' for app '{}'",
-                cfg.session_id, cfg.app_name
-            )
-        })?;
+    })
+    .await
+    .with_context(|| {
+        format!(
+            "failed to create session '{}' for app '{}'",
+            cfg.session_id, cfg.app_name
+        )
+    })?;
 
     Ok(())
 }
@@ -118,22 +231,148 @@ pub fn sqlite_path_from_url(db_url: &str) -> Option<PathBuf> {
     Some(Path::new(path_without_params).to_path_buf())
 }
 
-pub async fn run_sessions_list(cfg: &RuntimeConfig) -> Result<()> {
+pub const SESSION_PREVIEW_RECENT_EVENTS: usize = 4;
+pub const SESSION_PREVIEW_CONCURRENCY: usize = 8;
+pub const SESSION_PREVIEW_BUDGET: Duration = Duration::from_secs(5);
+/// Char limit for each side of a `sessions list --preview` line.
+pub const SESSION_PREVIEW_MAX_CHARS: usize = 60;
+pub const SESSION_PREVIEW_UNAVAILABLE: &str = "<preview unavailable>";
+
+/// Latest user prompt and assistant reply of a session.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SessionPreview {
+    pub user: Option<String>,
+    pub assistant: Option<String>,
+}
+
+/// Most recent user and assistant text, newest first. Events without text
+/// (tool calls and responses) are skipped.
+pub fn session_preview(events: &[Event]) -> SessionPreview {
+    let mut preview = SessionPreview::default();
+    for event in events.iter().rev() {
+        let text = event_text(event);
+        if text.trim().is_empty() {
+            continue;
+        }
+        let slot = if event.author == "user" {
+            &mut preview.user
+        } else {
+            &mut preview.assistant
+        };
+        if slot.is_none() {
+            *slot = Some(text);
+        }
+        if preview.user.is_some() && preview.assistant.is_some() {
+            break;
+        }
+    }
+    preview
+}
+
+fn preview_snippet(text: &str, max_chars: usize) -> String {
+    let line = text.split_whitespace().collect::<Vec<&str>>().join(" ");
+    if line.chars().count() > max_chars {
+        format!("{}...", line.chars().take(max_chars).collect::<String>())
+    } else {
+        line
+    }
+}
+
+/// `you: ... / assistant: ...`, with guardrail terms and sqlite URLs redacted
+/// the same way as other printed session text.
+pub fn format_session_preview(
+    cfg: &RuntimeConfig,
+    preview: &SessionPreview,
+    max_chars: usize,
+) -> String {
+    let matcher = cfg.guardrail_matcher();
+    let redact = |text: &str| {
+        let text = if cfg.guardrail_terms.is_empty() {
+            text.to_string()
+        } else {
+            redact_text_with_matcher(text, &matcher, &cfg.guardrail_redact_replacement)
+        };
+        let text = if cfg.show_sensitive_config {
+            text
+        } else {
+            redact_sensitive_text(&text)
+        };
+        preview_snippet(&text, max_chars)
+    };
+    let parts = [("you", &preview.user), ("assistant", &preview.assistant)]
+        .into_iter()
+        .filter_map(|(who, text)| {
+            text.as_deref()
+                .map(|text| format!("{who}: {}", redact(text)))
+        })
+        .collect::<Vec<String>>();
+    if parts.is_empty() {
+        "<no messages>".to_string()
+    } else {
+        parts.join(" / ")
+    }
+}
+
+/// Load previews for `session_ids`, at most `concurrency` at a time. Every
+/// fetch shares one deadline; sessions that fail or miss it map to `None`.
+pub async fn load_session_previews(
+    session_service: &Arc<dyn SessionService>,
+    cfg: &RuntimeConfig,
+    session_ids: &[String],
+    concurrency: usize,
+    budget: Duration,
+) -> HashMap<String, Option<SessionPreview>> {
+    use adk_rust::futures::StreamExt;
+
+    let deadline = tokio::time::Instant::now() + budget;
+    adk_rust::futures::stream::iter(session_ids.iter().cloned())
+        .map(|session_id| async move {
+            if tokio::time::Instant::now() >= deadline {
+                return (session_id, None);
+            }
+            let fetch = session_service.get(GetRequest {
+                app_name: cfg.app_name.clone(),
+                user_id: cfg.user_id.clone(),
+                session_id: session_id.clone(),
+                num_recent_events: Some(SESSION_PREVIEW_RECENT_EVENTS),
+                after: None,
+            });
+            let preview = match tokio::time::timeout_at(deadline, fetch).await {
+                Ok(Ok(session)) => Some(session_preview(&session.events().all())),
+                Ok(Err(err)) => {
+                    tracing::debug!(
+                        session_id = %session_id,
+                        error = %err,
+                        "session preview failed"
+                    );
+                    None
+                }
+                Err(_) => None,
+            };
+            (session_id, preview)
+        })
+        .buffer_unordered(concurrency.max(1))
+        .collect()
+        .await
+}
+
+pub async fn run_sessions_list(cfg: &RuntimeConfig, preview: bool) -> Result<()> {
     let session_service = build_session_service(cfg).await?;
-    let mut sessions = session_service
-        .list(ListRequest {
+    let mut sessions = with_session_busy_retry(cfg, "list", || {
+        session_service.list(ListRequest {
             app_name: cfg.app_name.clone(),
             user_id: cfg.user_id.clone(),
             limit: None,
             offset: None,
         })
-        .await
-        .with_context(|| {
-            format!(
-                "failed to list sessions for app '{}' and user '{}'",
-                cfg.app_name, cfg.user_id
-            )
-        })?;
+    })
+    .await
+    .with_context(|| {
+        format!(
+            "failed to list sessions for app '{}' and user '{}'",
+            cfg.app_name, cfg.user_id
+        )
+    })?;
 
     if sessions.is_empty() {
         println!(
@@ -145,6 +384,23 @@ pub async fn run_sessions_list(cfg: &RuntimeConfig) -> Result<()> {
 
     sessions.sort_by_key(|session| std::cmp::Reverse(session.last_update_time()));
 
+    let previews = if preview {
+        let session_ids = sessions
+            .iter()
+            .map(|session| session.id().to_string())
+            .collect::<Vec<String>>();
+        load_session_previews(
+            &session_service,
+            cfg,
+            &session_ids,
+            SESSION_PREVIEW_CONCURRENCY,
+            SESSION_PREVIEW_BUDGET,
+        )
+        .await
+    } else {
+        HashMap::new()
+    };
+
     println!(
         "Sessions for app '{}' and user '{}':",
         cfg.app_name, cfg.user_id
@@ -155,8 +411,93 @@ pub async fn run_sessions_list(cfg: &RuntimeConfig) -> Result<()> {
             session.id(),
             session.last_update_time().to_rfc3339()
         );
+        if preview {
+            let line = match previews.get(session.id()) {
+                Some(Some(preview)) => {
+                    format_session_preview(cfg, preview, SESSION_PREVIEW_MAX_CHARS)
+                }
+                _ => SESSION_PREVIEW_UNAVAILABLE.to_string(),
+            };
+            println!("    {line}");
+        }
+    }
+
+    Ok(())
+}
+
+/// `sessions show --follow` settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SessionFollow {
+    pub interval: Duration,
+    /// NDJSON event records instead of the text format.
+    pub json: bool,
+}
+
+const SESSION_FOLLOW_OVERLAP_MS: i64 = 1_000;
+
+/// Tracks what `sessions show --follow` has printed. Polls ask for events
+/// after the newest seen timestamp minus a small overlap, because backends
+/// differ on whether `after` is inclusive and on timestamp precision; ids
+/// seen inside that window filter out the repeats.
+#[derive(Debug, Default)]
+pub struct SessionFollowCursor {
+    newest: Option<chrono::DateTime<chrono::Utc>>,
+    recent_ids: HashMap<String, chrono::DateTime<chrono::Utc>>,
+}
+
+impl SessionFollowCursor {
+    pub fn after(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.newest
+            .map(|newest| newest - chrono::Duration::milliseconds(SESSION_FOLLOW_OVERLAP_MS))
+    }
+
+    /// The events not returned before, oldest first.
+    pub fn take_new(&mut self, mut events: Vec<Event>) -> Vec<Event> {
+        events.sort_by_key(|event| event.timestamp);
+        let fresh = events
+            .into_iter()
+            .filter(|event| !self.recent_ids.contains_key(&event.id))
+            .collect::<Vec<Event>>();
+        for event in &fresh {
+            self.recent_ids.insert(event.id.clone(), event.timestamp);
+            self.newest = self.newest.max(Some(event.timestamp));
+        }
+        if let Some(cutoff) = self.after() {
+            self.recent_ids.retain(|_, timestamp| *timestamp >= cutoff);
+        }
+        fresh
     }
+}
 
+/// One follow poll: events appended since the cursor, oldest first.
+pub async fn poll_session_follow(
+    session_service: &Arc<dyn SessionService>,
+    cfg: &RuntimeConfig,
+    session_id: &str,
+    cursor: &mut SessionFollowCursor,
+) -> Result<Vec<Event>> {
+    let after = cursor.after();
+    let session = with_session_busy_retry(cfg, "get", || {
+        session_service.get(GetRequest {
+            app_name: cfg.app_name.clone(),
+            user_id: cfg.user_id.clone(),
+            session_id: session_id.to_string(),
+            num_recent_events: None,
+            after,
+        })
+    })
+    .await
+    .with_context(|| format!("failed to poll session '{session_id}' for new events"))?;
+    Ok(cursor.take_new(session.events().all()))
+}
+
+fn print_followed_event(event: &Event, json: bool) -> Result<()> {
+    if json {
+        let line = serde_json::to_string(event).context("failed to serialize session event")?;
+        println!("{line}");
+    } else {
+        print!("{}", format_session_event(event));
+    }
     Ok(())
 }
 
@@ -164,27 +505,29 @@ pub async fn run_sessions_show(
     cfg: &RuntimeConfig,
     session_id_override: Option<String>,
     recent: usize,
+    follow: Option<SessionFollow>,
 ) -> Result<()> {
     let session_id = session_id_override.unwrap_or_else(|| cfg.session_id.clone());
     let session_service = build_session_service(cfg).await?;
-    let session = session_service
-        .get(GetRequest {
+    let session = with_session_busy_retry(cfg, "get", || {
+        session_service.get(GetRequest {
             app_name: cfg.app_name.clone(),
             user_id: cfg.user_id.clone(),
             session_id: session_id.clone(),
             num_recent_events: (recent > 0).then_some(recent),
             after: None,
         })
-        .await
-        .with_context(|| {
-            format!(
-                "failed to load session '{}' for app '{}' and user '{}'",
-                session_id, cfg.app_name, cfg.user_id
-            )
-        })?;
+    })
+    .await
+    .with_context(|| {
+        format!(
+            "failed to load session '{}' for app '{}' and user '{}'",
+            session_id, cfg.app_name, cfg.user_id
+        )
+    })?;
 
-    println!(
-        "Session '{}' (app='{}', user='{}', events={}):",
+    let mut output = format!(
+        "Session '{}' (app='{}', user='{}', events={}):\n",
         session.id(),
         session.app_name(),
         session.user_id(),
@@ -192,16 +535,44 @@ pub async fn run_sessions_show(
     );
 
     let events = session.events().all();
-    if events.is_empty() {
-        println!("No events in this session.");
+    let Some(follow) = follow else {
+        if events.is_empty() {
+            output.push_str("No events in this session.\n");
+        }
+        for event in events {
+            output.push_str(&format_session_event(&event));
+        }
+        return page_or_print(cfg, &output);
+    };
+
+    if !follow.json {
+        print!("{output}");
+    }
+    let mut cursor = SessionFollowCursor::default();
+    for event in cursor.take_new(events) {
+        print_followed_event(&event, follow.json)?;
+    }
+    if cfg.session_backend == SessionBackend::Memory {
+        Ui::for_config(cfg).warn(
+            "session_backend is memory, so events written by other processes cannot be \
+             followed. Use the sqlite backend to follow a running session.",
+        );
         return Ok(());
     }
-
-    for event in events {
-        print_session_event(&event);
+    if !follow.json {
+        Ui::for_config(cfg).notice(&format!(
+            "Following session '{session_id}' (Ctrl-C to stop)..."
+        ));
+    }
+    loop {
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+            _ = tokio::time::sleep(follow.interval) => {}
+        }
+        for event in poll_session_follow(&session_service, cfg, &session_id, &mut cursor).await? {
+            print_followed_event(&event, follow.json)?;
+        }
     }
-
-    Ok(())
 }
 
 pub async fn run_sessions_delete(
@@ -218,19 +589,20 @@ pub async fn run_sessions_delete(
     }
 
     let session_service = build_session_service(cfg).await?;
-    session_service
-        .delete(DeleteRequest {
+    with_session_busy_retry(cfg, "delete", || {
+        session_service.delete(DeleteRequest {
             app_name: cfg.app_name.clone(),
             user_id: cfg.user_id.clone(),
             session_id: session_id.clone(),
         })
-        .await
-        .with_context(|| {
-            format!(
-                "failed to delete session '{}' for app '{}' and user '{}'",
-                session_id, cfg.app_name, cfg.user_id
-            )
-        })?;
+    })
+    .await
+    .with_context(|| {
+        format!(
+            "failed to delete session '{}' for app '{}' and user '{}'",
+            session_id, cfg.app_name, cfg.user_id
+        )
+    })?;
 
     println!(
         "Deleted session '{}' for app '{}' and user '{}'.",
@@ -247,20 +619,21 @@ pub async fn run_sessions_prune(
 ) -> Result<()> {
     let keep = keep.max(1);
     let session_service = build_session_service(cfg).await?;
-    let mut sessions = session_service
-        .list(ListRequest {
+    let mut sessions = with_session_busy_retry(cfg, "list", || {
+        session_service.list(ListRequest {
             app_name: cfg.app_name.clone(),
             user_id: cfg.user_id.clone(),
             limit: None,
             offset: None,
         })
-        .await
-        .with_context(|| {
-            format!(
-                "failed to list sessions for prune in app '{}' and user '{}'",
-                cfg.app_name, cfg.user_id
-            )
-        })?;
+    })
+    .await
+    .with_context(|| {
+        format!(
+            "failed to list sessions for prune in app '{}' and user '{}'",
+            cfg.app_name, cfg.user_id
+        )
+    })?;
 
     sessions.sort_by_key(|session| std::cmp::Reverse(session.last_update_time()));
     let prune_ids = sessions
@@ -297,19 +670,20 @@ pub async fn run_sessions_prune(
     }
 
     for session_id in &prune_ids {
-        session_service
-            .delete(DeleteRequest {
+        with_session_busy_retry(cfg, "delete", || {
+            session_service.delete(DeleteRequest {
                 app_name: cfg.app_name.clone(),
                 user_id: cfg.user_id.clone(),
                 session_id: session_id.clone(),
             })
-            .await
-            .with_context(|| {
-                format!(
-                    "failed to delete pruned session '{}' for app '{}' and user '{}'",
-                    session_id, cfg.app_name, cfg.user_id
-                )
-            })?;
+        })
+        .await
+        .with_context(|| {
+            format!(
+                "failed to delete pruned session '{}' for app '{}' and user '{}'",
+                session_id, cfg.app_name, cfg.user_id
+            )
+        })?;
     }
 
     println!(
@@ -320,18 +694,369 @@ pub async fn run_sessions_prune(
     Ok(())
 }
 
-fn print_session_event(event: &Event) {
-    let mut header = format!("[{}] {}", event.timestamp.to_rfc3339(), event.author);
+/// Characters of context kept on each side of a search match in snippets.
+const SESSION_SEARCH_SNIPPET_RADIUS: usize = 60;
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct SessionSearchHit {
+    pub session_id: String,
+    pub title: String,
+    pub last_update: String,
+    pub match_count: usize,
+    pub snippet: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SessionSearchOutcome {
+    pub hits: Vec<SessionSearchHit>,
+    pub sessions_scanned: usize,
+    pub sessions_total: usize,
+    pub budget_exhausted: bool,
+}
+
+fn floor_char_boundary(text: &str, mut index: usize) -> usize {
+    index = index.min(text.len());
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+fn ceil_char_boundary(text: &str, mut index: usize) -> usize {
+    index = index.min(text.len());
+    while !text.is_char_boundary(index) {
+        index += 1;
+    }
+    index
+}
+
+/// One-line excerpt of `text` centered on the first occurrence of any term.
+pub fn session_match_snippet(text: &str, terms: &[String], radius: usize) -> String {
+    let lowered = text.to_ascii_lowercase();
+    let Some(position) = terms
+        .iter()
+        .filter_map(|term| lowered.find(term.as_str()))
+        .min()
+    else {
+        return String::new();
+    };
+
+    let start = floor_char_boundary(text, position.saturating_sub(radius));
+    let end = ceil_char_boundary(text, position.saturating_add(radius));
+    let mut snippet = text[start..end]
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ");
+    if start > 0 {
+        snippet.insert_str(0, "…");
+    }
+    if end < text.len() {
+        snippet.push('…');
+    }
+    snippet
+}
+
+/// Title for a session: the first line of its first user message.
+fn session_title(events: &[Event]) -> String {
+    events
+        .iter()
+        .filter(|event| event.author == "user")
+        .map(event_text)
+        .find(|text| !text.trim().is_empty())
+        .and_then(|text| text.lines().next().map(|line| line.trim().to_string()))
+        .map(|line| {
+            if line.chars().count() > 60 {
+                format!("{}…", line.chars().take(60).collect::<String>())
+            } else {
+                line
+            }
+        })
+        .unwrap_or_else(|| "<untitled>".to_string())
+}
+
+/// Score events against query terms. Returns the total match count and a
+/// snippet from the event with the most matches.
+pub fn score_session_events(events: &[Event], terms: &[String]) -> (usize, String) {
+    let mut total = 0usize;
+    let mut best: Option<(usize, String)> = None;
+    for event in events {
+        let text = event_text(event);
+        if text.is_empty() {
+            continue;
+        }
+        let lowered = text.to_ascii_lowercase();
+        let count = terms
+            .iter()
+            .map(|term| lowered.matches(term.as_str()).count())
+            .sum::<usize>();
+        if count == 0 {
+            continue;
+        }
+        total += count;
+        if best
+            .as_ref()
+            .is_none_or(|(best_count, _)| count > *best_count)
+        {
+            best = Some((count, text));
+        }
+    }
+    let snippet = best
+        .map(|(_, text)| session_match_snippet(&text, terms, SESSION_SEARCH_SNIPPET_RADIUS))
+        .unwrap_or_default();
+    (total, snippet)
+}
+
+/// Search sessions one at a time so memory stays bounded by a single
+/// session's (capped) event list. Stops early once `time_budget` is spent.
+pub async fn search_sessions(
+    session_service: &Arc<dyn SessionService>,
+    cfg: &RuntimeConfig,
+    query: &str,
+    limit: usize,
+    max_events_per_session: usize,
+    time_budget: Duration,
+) -> Result<SessionSearchOutcome> {
+    let terms = query_terms(query);
+    if terms.is_empty() {
+        return Err(anyhow::anyhow!(
+            "search query '{}' has no searchable terms (terms must be longer than 2 characters)",
+            query
+        ));
+    }
+
+    let mut sessions = with_session_busy_retry(cfg, "list", || {
+        session_service.list(ListRequest {
+            app_name: cfg.app_name.clone(),
+            user_id: cfg.user_id.clone(),
+            limit: None,
+            offset: None,
+        })
+    })
+    .await
+    .with_context(|| {
+        format!(
+            "failed to list sessions for search in app '{}' and user '{}'",
+            cfg.app_name, cfg.user_id
+        )
+    })?;
+    sessions.sort_by_key(|session| std::cmp::Reverse(session.last_update_time()));
+
+    let started = Instant::now();
+    let mut outcome = SessionSearchOutcome {
+        sessions_total: sessions.len(),
+        ..Default::default()
+    };
+    let session_ids = sessions
+        .iter()
+        .map(|session| session.id().to_string())
+        .collect::<Vec<String>>();
+    drop(sessions);
+
+    for session_id in session_ids {
+        if started.elapsed() >= time_budget {
+            outcome.budget_exhausted = true;
+            break;
+        }
+        let session = with_session_busy_retry(cfg, "get", || {
+            session_service.get(GetRequest {
+                app_name: cfg.app_name.clone(),
+                user_id: cfg.user_id.clone(),
+                session_id: session_id.clone(),
+                num_recent_events: (max_events_per_session > 0).then_some(max_events_per_session),
+                after: None,
+            })
+        })
+        .await
+        .with_context(|| format!("failed to load session '{}' for search", session_id))?;
+        outcome.sessions_scanned += 1;
+
+        let events = session.events().all();
+        let (match_count, snippet) = score_session_events(&events, &terms);
+        if match_count == 0 {
+            continue;
+        }
+        outcome.hits.push(SessionSearchHit {
+            session_id,
+            title: session_title(&events),
+            last_update: session.last_update_time().to_rfc3339(),
+            match_count,
+            snippet,
+        });
+    }
+
+    outcome
+        .hits
+        .sort_by_key(|hit| std::cmp::Reverse(hit.match_count));
+    outcome.hits.truncate(limit.max(1));
+    Ok(outcome)
+}
+
+pub async fn run_sessions_search(
+    cfg: &RuntimeConfig,
+    query: &str,
+    limit: usize,
+    max_events_per_session: usize,
+    budget_secs: u64,
+    json_output: bool,
+) -> Result<()> {
+    let session_service = build_session_service(cfg).await?;
+    let outcome = search_sessions(
+        &session_service,
+        cfg,
+        query,
+        limit,
+        max_events_per_session,
+        Duration::from_secs(budget_secs.max(1)),
+    )
+    .await?;
+
+    if json_output {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({
+                "query": query,
+                "sessions_scanned": outcome.sessions_scanned,
+                "sessions_total": outcome.sessions_total,
+                "budget_exhausted": outcome.budget_exhausted,
+                "hits": outcome.hits,
+            }))
+            .context("failed to serialize session search results")?
+        );
+        return Ok(());
+    }
+
+    if outcome.hits.is_empty() {
+        println!(
+            "No sessions matched '{}' (scanned {}/{}).",
+            query, outcome.sessions_scanned, outcome.sessions_total
+        );
+    } else {
+        println!(
+            "Sessions matching '{}' (scanned {}/{}):",
+            query, outcome.sessions_scanned, outcome.sessions_total
+        );
+        for hit in &outcome.hits {
+            println!(
+                "- {} \"{}\" (updated: {}, matches: {})",
+                hit.session_id, hit.title, hit.last_update, hit.match_count
+            );
+            if !hit.snippet.is_empty() {
+                println!("    {}", hit.snippet);
+            }
+        }
+    }
+    if outcome.budget_exhausted {
+        println!(
+            "Time budget of {}s exhausted; results cover the most recently updated sessions only.",
+            budget_secs.max(1)
+        );
+    }
+    Ok(())
+}
+
+/// Format tag written into every exported session document.
+pub const SESSION_EXPORT_FORMAT: &str = "zavora-session/v1";
+
+/// Serialize one session (metadata, state and full event list) to the
+/// portable JSON export format.
+pub fn session_export_document(session: &dyn Session) -> Result<serde_json::Value> {
+    let events = session.events().all();
+    let events_json = events
+        .iter()
+        .map(serde_json::to_value)
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .with_context(|| format!("failed to serialize events of session '{}'", session.id()))?;
+    Ok(serde_json::json!({
+        "format": SESSION_EXPORT_FORMAT,
+        "session_id": session.id(),
+        "app_name": session.app_name(),
+        "user_id": session.user_id(),
+        "last_update": session.last_update_time().to_rfc3339(),
+        "state": session.state().all(),
+        "event_count": events.len(),
+        "first_event_at": events.first().map(|event| event.timestamp.to_rfc3339()),
+        "last_event_at": events.last().map(|event| event.timestamp.to_rfc3339()),
+        "events": events_json,
+    }))
+}
+
+/// Write the current session as a `zavora-session/v1` JSON document into
+/// `dir`, named after the session and the current time.
+pub async fn save_session_transcript(
+    session_service: &Arc<dyn SessionService>,
+    cfg: &RuntimeConfig,
+    dir: &Path,
+    provenance: Option<&serde_json::Value>,
+) -> Result<PathBuf> {
+    let session = with_session_busy_retry(cfg, "get", || {
+        session_service.get(GetRequest {
+            app_name: cfg.app_name.clone(),
+            user_id: cfg.user_id.clone(),
+            session_id: cfg.session_id.clone(),
+            num_recent_events: None,
+            after: None,
+        })
+    })
+    .await
+    .with_context(|| format!("failed to load session '{}' for transcript", cfg.session_id))?;
+    let mut document = session_export_document(session.as_ref())?;
+    crate::sanitize::sanitize_json_strings(&mut document, cfg.output_sanitize);
+    if let Some(provenance) = provenance {
+        document["provenance"] = provenance.clone();
+    }
+
+    std::fs::create_dir_all(dir)
+        .with_context(|| format!("failed to create transcript directory '{}'", dir.display()))?;
+    let safe_id = cfg
+        .session_id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect::<String>();
+    let path = dir.join(format!(
+        "{safe_id}-{}.json",
+        chrono::Utc::now().format("%Y%m%dT%H%M%SZ")
+    ));
+    let body = serde_json::to_string_pretty(&document).context("failed to serialize transcript")?;
+    std::fs::write(&path, body)
+        .with_context(|| format!("failed to write transcript '{}'", path.display()))?;
+    Ok(path)
+}
+
+/// Copy the current session's events into the sqlite database at `db_url`,
+/// replacing any earlier snapshot of the same session. Returns the number of
+/// events written.
+pub async fn snapshot_session_to_sqlite(
+    session_service: &Arc<dyn SessionService>,
+    cfg: &RuntimeConfig,
+    db_url: &str,
+) -> Result<usize> {
+    let events = crate::checkpoint::snapshot_session_events(session_service, cfg).await?;
+    let target: Arc<dyn SessionService> = Arc::new(open_sqlite_session_service(db_url).await?);
+    crate::checkpoint::restore_session_events(&target, cfg, &events)
+        .await
+        .context("failed to write session snapshot")?;
+    Ok(events.len())
+}
+
+fn format_session_event(event: &Event) -> String {
+    let mut out = format!("[{}] {}", event.timestamp.to_rfc3339(), event.author);
     if event.is_final_response() {
-        header.push_str(" [final]");
+        out.push_str(" [final]");
     }
-    println!("{header}");
+    out.push('\n');
 
     let text = event_text(event);
     if !text.is_empty() {
-        println!("{text}");
+        out.push_str(&text);
+        out.push('\n');
     } else {
-        println!("<non-text event>");
+        out.push_str("<non-text event>\n");
     }
 
     if !event.actions.state_delta.is_empty() {
@@ -342,8 +1067,9 @@ fn print_session_event(event: &Event) {
             .cloned()
             .collect::<Vec<String>>();
         keys.sort();
-        println!("state_delta keys: {}", keys.join(", "));
+        out.push_str(&format!("state_delta keys: {}\n", keys.join(", ")));
     }
 
-    println!();
+    out.push('\n');
+    out
 }
@@ -0,0 +1,406 @@
+/// Archival session bundles for `sessions export-all` / `sessions verify-bundle`.
+///
+/// A bundle is a directory (or `.tar.gz` of the same layout) holding one
+/// `sessions/<id>.json` file per session plus a `manifest.json` with event
+/// counts, time ranges and the SHA-256 of every session file.
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use adk_session::{GetRequest, ListRequest, SessionService};
+use anyhow::{Context, Result};
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::config::RuntimeConfig;
+use crate::session::{build_session_service, session_export_document};
+
+pub const SESSION_BUNDLE_FORMAT: &str = "zavora-session-bundle/v1";
+pub const BUNDLE_MANIFEST_FILE: &str = "manifest.json";
+const BUNDLE_SESSIONS_DIR: &str = "sessions";
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct BundleEntry {
+    pub session_id: String,
+    /// Path of the session file relative to the bundle root.
+    pub file: String,
+    pub event_count: usize,
+    pub first_event_at: Option<String>,
+    pub last_event_at: Option<String>,
+    pub bytes: u64,
+    pub sha256: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct BundleManifest {
+    pub format: String,
+    pub created_at: String,
+    pub app_name: String,
+    pub user_id: String,
+    pub tool_versions: BTreeMap<String, String>,
+    pub sessions: Vec<BundleEntry>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BundleVerification {
+    pub checked: usize,
+    /// Files whose content no longer matches the manifest hash.
+    pub mismatched: Vec<String>,
+    /// Files listed in the manifest but absent from the bundle.
+    pub missing: Vec<String>,
+    /// Session files present in the bundle but not in the manifest.
+    pub unexpected: Vec<String>,
+}
+
+impl BundleVerification {
+    pub fn is_ok(&self) -> bool {
+        self.mismatched.is_empty() && self.missing.is_empty() && self.unexpected.is_empty()
+    }
+}
+
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// File name for a session inside the bundle; ids are kept readable but
+/// anything outside `[A-Za-z0-9._-]` is replaced so ids cannot escape the
+/// `sessions/` directory.
+pub fn bundle_session_file(session_id: &str) -> String {
+    let stem = session_id
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || matches!(ch, '.' | '_' | '-') {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+    let stem = stem.trim_start_matches('.');
+    let stem = if stem.is_empty() { "session" } else { stem };
+    format!("{BUNDLE_SESSIONS_DIR}/{stem}.json")
+}
+
+enum BundleWriter {
+    Directory(PathBuf),
+    TarGz(tar::Builder<GzEncoder<File>>),
+}
+
+impl BundleWriter {
+    fn create(output: &Path, compress: bool) -> Result<Self> {
+        if compress {
+            if output.exists() {
+                return Err(anyhow::anyhow!(
+                    "bundle output '{}' already exists; refusing to overwrite",
+                    output.display()
+                ));
+            }
+            if let Some(parent) = output.parent().filter(|p| !p.as_os_str().is_empty()) {
+                std::fs::create_dir_all(parent).with_context(|| {
+                    format!("failed to create bundle directory '{}'", parent.display())
+                })?;
+            }
+            let file = File::create(output)
+                .with_context(|| format!("failed to create bundle '{}'", output.display()))?;
+            return Ok(Self::TarGz(tar::Builder::new(GzEncoder::new(
+                file,
+                Compression::default(),
+            ))));
+        }
+
+        if output.exists()
+            && std::fs::read_dir(output)
+                .with_context(|| format!("failed to read bundle directory '{}'", output.display()))?
+                .next()
+                .is_some()
+        {
+            return Err(anyhow::anyhow!(
+                "bundle directory '{}' is not empty; refusing to overwrite",
+                output.display()
+            ));
+        }
+        std::fs::create_dir_all(output.join(BUNDLE_SESSIONS_DIR))
+            .with_context(|| format!("failed to create bundle directory '{}'", output.display()))?;
+        Ok(Self::Directory(output.to_path_buf()))
+    }
+
+    fn write_file(&mut self, name: &str, bytes: &[u8]) -> Result<()> {
+        match self {
+            Self::Directory(root) => {
+                let path = root.join(name);
+                std::fs::write(&path, bytes)
+                    .with_context(|| format!("failed to write bundle file '{}'", path.display()))
+            }
+            Self::TarGz(builder) => {
+                let mut header = tar::Header::new_gnu();
+                header.set_size(bytes.len() as u64);
+                header.set_mode(0o644);
+                header.set_mtime(chrono::Utc::now().timestamp().max(0) as u64);
+                header.set_cksum();
+                builder
+                    .append_data(&mut header, name, bytes)
+                    .with_context(|| format!("failed to append '{name}' to bundle archive"))
+            }
+        }
+    }
+
+    fn finish(self) -> Result<()> {
+        match self {
+            Self::Directory(_) => Ok(()),
+            Self::TarGz(builder) => {
+                builder
+                    .into_inner()
+                    .and_then(|encoder| encoder.finish())
+                    .context("failed to finalize bundle archive")?;
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Export every session for the app/user into a bundle at `output`.
+/// Sessions are loaded, written and hashed one at a time so memory stays
+/// bounded by the largest single session.
+pub async fn export_all_sessions(
+    session_service: &Arc<dyn SessionService>,
+    cfg: &RuntimeConfig,
+    output: &Path,
+    compress: bool,
+) -> Result<BundleManifest> {
+    let sessions = session_service
+        .list(ListRequest {
+            app_name: cfg.app_name.clone(),
+            user_id: cfg.user_id.clone(),
+            limit: None,
+            offset: None,
+        })
+        .await
+        .with_context(|| {
+            format!(
+                "failed to list sessions for export in app '{}' and user '{}'",
+                cfg.app_name, cfg.user_id
+            )
+        })?;
+    let mut session_ids = sessions
+        .iter()
+        .map(|session| session.id().to_string())
+        .collect::<Vec<String>>();
+    drop(sessions);
+    session_ids.sort();
+
+    let mut writer = BundleWriter::create(output, compress)?;
+    let mut entries = Vec::with_capacity(session_ids.len());
+    let mut used_files = BTreeSet::new();
+
+    for session_id in session_ids {
+        let session = session_service
+            .get(GetRequest {
+                app_name: cfg.app_name.clone(),
+                user_id: cfg.user_id.clone(),
+                session_id: session_id.clone(),
+                num_recent_events: None,
+                after: None,
+            })
+            .await
+            .with_context(|| format!("failed to load session '{session_id}' for export"))?;
+        let document = session_export_document(session.as_ref())?;
+        drop(session);
+
+        let mut file = bundle_session_file(&session_id);
+        let mut suffix = 1;
+        while !used_files.insert(file.clone()) {
+            suffix += 1;
+            file = bundle_session_file(&format!("{session_id}-{suffix}"));
+        }
+
+        let bytes = serde_json::to_vec_pretty(&document)
+            .with_context(|| format!("failed to serialize session '{session_id}'"))?;
+        writer.write_file(&file, &bytes)?;
+        entries.push(BundleEntry {
+            event_count: document["event_count"].as_u64().unwrap_or_default() as usize,
+            first_event_at: document["first_event_at"].as_str().map(str::to_string),
+            last_event_at: document["last_event_at"].as_str().map(str::to_string),
+            bytes: bytes.len() as u64,
+            sha256: sha256_hex(&bytes),
+            session_id,
+            file,
+        });
+    }
+
+    let manifest = BundleManifest {
+        format: SESSION_BUNDLE_FORMAT.to_string(),
+        created_at: chrono::Utc::now().to_rfc3339(),
+        app_name: cfg.app_name.clone(),
+        user_id: cfg.user_id.clone(),
+        tool_versions: BTreeMap::from([
+            (
+                "zavora-cli".to_string(),
+                env!("CARGO_PKG_VERSION").to_string(),
+            ),
+            (
+                "session_backend".to_string(),
+                format!("{:?}", cfg.session_backend).to_ascii_lowercase(),
+            ),
+        ]),
+        sessions: entries,
+    };
+    let manifest_bytes =
+        serde_json::to_vec_pretty(&manifest).context("failed to serialize bundle manifest")?;
+    writer.write_file(BUNDLE_MANIFEST_FILE, &manifest_bytes)?;
+    writer.finish()?;
+    Ok(manifest)
+}
+
+fn parse_manifest(bytes: &[u8], origin: &Path) -> Result<BundleManifest> {
+    let manifest: BundleManifest = serde_json::from_slice(bytes)
+        .with_context(|| format!("invalid bundle manifest in '{}'", origin.display()))?;
+    if manifest.format != SESSION_BUNDLE_FORMAT {
+        return Err(anyhow::anyhow!(
+            "unsupported bundle format '{}' in '{}' (expected '{SESSION_BUNDLE_FORMAT}')",
+            manifest.format,
+            origin.display()
+        ));
+    }
+    Ok(manifest)
+}
+
+/// Hash every session file in the bundle, streaming archive entries, and
+/// return the manifest with the observed `file -> sha256` map.
+fn hash_bundle_contents(path: &Path) -> Result<(BundleManifest, BTreeMap<String, String>)> {
+    let mut hashes = BTreeMap::new();
+
+    if path.is_dir() {
+        let manifest_path = path.join(BUNDLE_MANIFEST_FILE);
+        let manifest_bytes = std::fs::read(&manifest_path).with_context(|| {
+            format!(
+                "failed to read bundle manifest '{}'",
+                manifest_path.display()
+            )
+        })?;
+        let manifest = parse_manifest(&manifest_bytes, path)?;
+
+        let sessions_dir = path.join(BUNDLE_SESSIONS_DIR);
+        if sessions_dir.is_dir() {
+            for entry in std::fs::read_dir(&sessions_dir).with_context(|| {
+                format!(
+                    "failed to read bundle directory '{}'",
+                    sessions_dir.display()
+                )
+            })? {
+                let entry = entry?;
+                if !entry.file_type()?.is_file() {
+                    continue;
+                }
+                let name = format!(
+                    "{BUNDLE_SESSIONS_DIR}/{}",
+                    entry.file_name().to_string_lossy()
+                );
+                let mut file = File::open(entry.path()).with_context(|| {
+                    format!("failed to open bundle file '{}'", entry.path().display())
+                })?;
+                let mut hasher = Sha256::new();
+                std::io::copy(&mut file, &mut hasher)?;
+                hashes.insert(name, format!("{:x}", hasher.finalize()));
+            }
+        }
+        return Ok((manifest, hashes));
+    }
+
+    let file =
+        File::open(path).with_context(|| format!("failed to open bundle '{}'", path.display()))?;
+    let mut archive = tar::Archive::new(GzDecoder::new(file));
+    let mut manifest = None;
+    for entry in archive
+        .entries()
+        .with_context(|| format!("failed to read bundle archive '{}'", path.display()))?
+    {
+        let mut entry =
+            entry.with_context(|| format!("corrupt bundle archive '{}'", path.display()))?;
+        let name = entry.path()?.to_string_lossy().to_string();
+        if name == BUNDLE_MANIFEST_FILE {
+            let mut bytes = Vec::new();
+            entry.read_to_end(&mut bytes)?;
+            manifest = Some(parse_manifest(&bytes, path)?);
+            continue;
+        }
+        let mut hasher = Sha256::new();
+        std::io::copy(&mut entry, &mut hasher)?;
+        hashes.insert(name, format!("{:x}", hasher.finalize()));
+    }
+    let manifest = manifest.ok_or_else(|| {
+        anyhow::anyhow!("bundle '{}' has no {BUNDLE_MANIFEST_FILE}", path.display())
+    })?;
+    Ok((manifest, hashes))
+}
+
+/// Re-hash a bundle's session files and compare them against its manifest.
+pub fn verify_session_bundle(path: &Path) -> Result<BundleVerification> {
+    let (manifest, mut hashes) = hash_bundle_contents(path)?;
+    let mut report = BundleVerification::default();
+
+    for entry in &manifest.sessions {
+        match hashes.remove(&entry.file) {
+            Some(actual) => {
+                report.checked += 1;
+                if actual != entry.sha256 {
+                    report.mismatched.push(entry.file.clone());
+                }
+            }
+            None => report.missing.push(entry.file.clone()),
+        }
+    }
+    report.unexpected = hashes.into_keys().collect();
+    Ok(report)
+}
+
+pub async fn run_sessions_export_all(
+    cfg: &RuntimeConfig,
+    output: &str,
+    compress: bool,
+) -> Result<()> {
+    let session_service = build_session_service(cfg).await?;
+    let manifest = export_all_sessions(&session_service, cfg, Path::new(output), compress).await?;
+    let events = manifest
+        .sessions
+        .iter()
+        .map(|entry| entry.event_count)
+        .sum::<usize>();
+    println!(
+        "Exported {} session(s), {events} event(s) for app '{}' and user '{}' to {}",
+        manifest.sessions.len(),
+        cfg.app_name,
+        cfg.user_id,
+        output
+    );
+    println!("Verify with: zavora-cli sessions verify-bundle {output}");
+    Ok(())
+}
+
+pub fn run_sessions_verify_bundle(path: &str) -> Result<()> {
+    let report = verify_session_bundle(Path::new(path))?;
+    println!("Checked {} session file(s) in {path}", report.checked);
+    for file in &report.mismatched {
+        println!("MISMATCH {file}: content does not match manifest sha256");
+    }
+    for file in &report.missing {
+        println!("MISSING  {file}: listed in manifest but not in bundle");
+    }
+    for file in &report.unexpected {
+        println!("EXTRA    {file}: present in bundle but not in manifest");
+    }
+    if !report.is_ok() {
+        return Err(anyhow::anyhow!(
+            "bundle verification failed: {} mismatched, {} missing, {} unexpected file(s)",
+            report.mismatched.len(),
+            report.missing.len(),
+            report.unexpected.len()
+        ));
+    }
+    println!("Bundle OK: all hashes match the manifest.");
+    Ok(())
+}
@@ -0,0 +1,692 @@
+//! Session encryption at rest (`session_encryption_key_env`).
+//!
+//! [`EncryptedSessionService`] wraps the sqlite session service: before an
+//! event is stored its content (and any compaction summary) is serialized
+//! and sealed with AES-256-GCM under a fresh nonce, leaving a single text
+//! part `zavora-enc:v1:<hex>` in its place, and events read back are opened
+//! again. Everything else (session ids, timestamps, authors, roles, state)
+//! stays readable, so the adk-session schema is untouched and `sessions
+//! list` needs no key. The key is derived with HKDF-SHA256 from the named
+//! environment variable's value and a random salt, and each sealed part
+//! carries its session id, event id and slot (content or compaction) as
+//! associated data, so it cannot be moved to another event undetected.
+//!
+//! The salt and a key-check record live in `zavora_session_encryption`; the
+//! check lets a wrong key fail when the service is built instead of on the
+//! first event read. Events
+//! written before encryption was enabled stay plaintext until `sessions
+//! encrypt --force` rewrites them.
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use adk_rust::prelude::*;
+use adk_session::*;
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use hkdf::Hkdf;
+use sha2::Sha256;
+use sqlx::sqlite::SqliteConnectOptions;
+use sqlx::{Row, SqlitePool};
+
+use crate::cli::SessionBackend;
+use crate::config::RuntimeConfig;
+use crate::session::{open_sqlite_session_service, with_session_busy_retry};
+
+pub const SESSION_ENCRYPTION_PREFIX: &str = "zavora-enc:v1:";
+pub const SESSION_KEY_CHECK_TABLE: &str = "zavora_session_encryption";
+const KEY_CHECK_PLAINTEXT: &[u8] = b"zavora-session-key-check";
+const KEY_CHECK_AAD: &[u8] = b"zavora-session-key-check";
+const KEY_DERIVATION_INFO: &[u8] = b"zavora-cli session encryption v1";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+/// The passphrase from `session_encryption_key_env`. The cipher needs the
+/// database's salt as well, see [`check_session_key`].
+#[derive(Clone)]
+pub struct SessionKey {
+    secret: String,
+}
+
+impl std::fmt::Debug for SessionKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SessionKey(<redacted>)")
+    }
+}
+
+impl SessionKey {
+    pub fn new(secret: &str) -> Result<Self> {
+        if secret.trim().is_empty() {
+            anyhow::bail!("session encryption key is empty");
+        }
+        Ok(Self {
+            secret: secret.to_string(),
+        })
+    }
+
+    /// The AES-256-GCM cipher for this key under `salt`.
+    pub fn derive(&self, salt: &[u8]) -> Result<SessionCipher> {
+        let mut key = [0u8; 32];
+        Hkdf::<Sha256>::new(Some(salt), self.secret.as_bytes())
+            .expand(KEY_DERIVATION_INFO, &mut key)
+            .map_err(|_| anyhow::anyhow!("failed to derive the session encryption key"))?;
+        let cipher = Aes256Gcm::new_from_slice(&key)
+            .map_err(|_| anyhow::anyhow!("invalid session encryption key length"))?;
+        Ok(SessionCipher { cipher })
+    }
+}
+
+#[derive(Clone)]
+pub struct SessionCipher {
+    cipher: Aes256Gcm,
+}
+
+impl std::fmt::Debug for SessionCipher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SessionCipher(<redacted>)")
+    }
+}
+
+impl SessionCipher {
+    /// `zavora-enc:v1:` + hex(nonce || ciphertext), authenticating `aad`.
+    pub fn seal(&self, plaintext: &[u8], aad: &[u8]) -> Result<String> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: plaintext,
+                    aad,
+                },
+            )
+            .map_err(|_| anyhow::anyhow!("failed to encrypt session data"))?;
+        let mut sealed = nonce.to_vec();
+        sealed.extend_from_slice(&ciphertext);
+        Ok(format!(
+            "{SESSION_ENCRYPTION_PREFIX}{}",
+            hex_encode(&sealed)
+        ))
+    }
+
+    pub fn open(&self, sealed: &str, aad: &[u8]) -> Result<Vec<u8>> {
+        let bytes = sealed
+            .strip_prefix(SESSION_ENCRYPTION_PREFIX)
+            .and_then(hex_decode)
+            .filter(|bytes| bytes.len() > NONCE_LEN)
+            .context("encrypted session data is malformed")?;
+        let (nonce, ciphertext) = bytes.split_at(NONCE_LEN);
+        self.cipher
+            .decrypt(
+                Nonce::from_slice(nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad,
+                },
+            )
+            .map_err(|_| {
+                anyhow::anyhow!(
+                    "failed to decrypt session data: wrong session encryption key, data moved \
+                     from another session or event, or corrupted data"
+                )
+            })
+    }
+
+    fn seal_content(&self, content: &Content, aad: &[u8]) -> Result<Content> {
+        let plaintext = serde_json::to_vec(content).context("failed to serialize event content")?;
+        Ok(Content {
+            role: content.role.clone(),
+            parts: vec![Part::Text {
+                text: self.seal(&plaintext, aad)?,
+            }],
+        })
+    }
+
+    fn open_content(&self, content: &Content, aad: &[u8]) -> Result<Option<Content>> {
+        let Some(sealed) = sealed_text(content) else {
+            return Ok(None);
+        };
+        let plaintext = self.open(sealed, aad)?;
+        serde_json::from_slice(&plaintext)
+            .map(Some)
+            .context("decrypted event content is not valid JSON")
+    }
+}
+
+fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn hex_decode(text: &str) -> Option<Vec<u8>> {
+    if text.len() % 2 != 0 {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(text.get(index..index + 2)?, 16).ok())
+        .collect()
+}
+
+/// Associated data binding a sealed part to its place: session, event and
+/// which of the event's contents it is.
+fn event_aad(session_id: &str, event: &Event, slot: &str) -> Vec<u8> {
+    format!("zavora-session\n{session_id}\n{}\n{slot}", event.id).into_bytes()
+}
+
+fn sealed_text(content: &Content) -> Option<&str> {
+    match content.parts.as_slice() {
+        [Part::Text { text }] if text.starts_with(SESSION_ENCRYPTION_PREFIX) => Some(text),
+        _ => None,
+    }
+}
+
+/// Whether `event` carries no plaintext content.
+pub fn is_encrypted_event(event: &Event) -> bool {
+    let sealed_or_absent =
+        |content: Option<&Content>| content.is_none_or(|c| sealed_text(c).is_some());
+    sealed_or_absent(event.llm_response.content.as_ref())
+        && sealed_or_absent(
+            event
+                .actions
+                .compaction
+                .as_ref()
+                .map(|compaction| &compaction.compacted_content),
+        )
+}
+
+/// `event` of session `session_id` with its content and compaction summary
+/// sealed. Already sealed content is left as is.
+pub fn encrypt_event(cipher: &SessionCipher, session_id: &str, event: &Event) -> Result<Event> {
+    let mut sealed = event.clone();
+    if let Some(content) = sealed.llm_response.content.as_mut()
+        && sealed_text(content).is_none()
+    {
+        *content = cipher.seal_content(content, &event_aad(session_id, event, "content"))?;
+    }
+    if let Some(compaction) = sealed.actions.compaction.as_mut()
+        && sealed_text(&compaction.compacted_content).is_none()
+    {
+        compaction.compacted_content = cipher.seal_content(
+            &compaction.compacted_content,
+            &event_aad(session_id, event, "compaction"),
+        )?;
+    }
+    Ok(sealed)
+}
+
+/// `event` of session `session_id` with sealed content opened; plaintext
+/// content passes through.
+pub fn decrypt_event(cipher: &SessionCipher, session_id: &str, mut event: Event) -> Result<Event> {
+    if let Some(content) = event.llm_response.content.as_ref()
+        && let Some(opened) =
+            cipher.open_content(content, &event_aad(session_id, &event, "content"))?
+    {
+        event.llm_response.content = Some(opened);
+    }
+    let aad = event_aad(session_id, &event, "compaction");
+    if let Some(compaction) = event.actions.compaction.as_mut()
+        && let Some(opened) = cipher.open_content(&compaction.compacted_content, &aad)?
+    {
+        compaction.compacted_content = opened;
+    }
+    Ok(event)
+}
+
+/// The key from `session_encryption_key_env`, or `None` when encryption is
+/// not configured.
+pub fn session_key(cfg: &RuntimeConfig) -> Result<Option<SessionKey>> {
+    let Some(env) = cfg.session_encryption_key_env.as_deref() else {
+        return Ok(None);
+    };
+    let secret = std::env::var(env).unwrap_or_default();
+    if secret.trim().is_empty() {
+        anyhow::bail!(
+            "session_encryption_key_env is '{env}' but that environment variable is not set. \
+             Export the session key (for example `export {env}=$(openssl rand -hex 32)` the first \
+             time) or remove session_encryption_key_env from the profile."
+        );
+    }
+    SessionKey::new(&secret).map(Some)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyCheck {
+    /// First use: the record was written with this key.
+    Created,
+    Matched,
+}
+
+/// Derive the cipher for `key` from the database's salt and compare it with
+/// the key-check record, writing both on first use. A different key fails
+/// here instead of on the first event.
+pub async fn check_session_key(
+    db_url: &str,
+    key: &SessionKey,
+) -> Result<(SessionCipher, KeyCheck)> {
+    let pool = SqlitePool::connect(db_url)
+        .await
+        .context("failed to open sqlite session database for the encryption key check")?;
+    let result = check_session_key_in(&pool, key).await;
+    pool.close().await;
+    result
+}
+
+async fn check_session_key_in(
+    pool: &SqlitePool,
+    key: &SessionKey,
+) -> Result<(SessionCipher, KeyCheck)> {
+    sqlx::query(&format!(
+        "CREATE TABLE IF NOT EXISTS {SESSION_KEY_CHECK_TABLE} (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            salt TEXT NOT NULL,
+            key_check TEXT NOT NULL,
+            created_at TEXT NOT NULL
+        )"
+    ))
+    .execute(pool)
+    .await
+    .context("failed to create session encryption key-check table")?;
+    if let Some(cipher) = match_key_check_record(pool, key).await? {
+        return Ok((cipher, KeyCheck::Matched));
+    }
+
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let cipher = key.derive(&salt)?;
+    sqlx::query(&format!(
+        "INSERT INTO {SESSION_KEY_CHECK_TABLE} (id, salt, key_check, created_at) \
+         VALUES (1, ?, ?, ?)"
+    ))
+    .bind(hex_encode(&salt))
+    .bind(cipher.seal(KEY_CHECK_PLAINTEXT, KEY_CHECK_AAD)?)
+    .bind(Utc::now().to_rfc3339())
+    .execute(pool)
+    .await
+    .context("failed to write session encryption key check")?;
+    Ok((cipher, KeyCheck::Created))
+}
+
+/// Read-only counterpart of [`check_session_key`] for `doctor`: compares
+/// `key` with the key-check record without creating the table, the salt or
+/// the record. `None` means the database or its record does not exist yet.
+pub async fn verify_session_key(db_url: &str, key: &SessionKey) -> Result<Option<KeyCheck>> {
+    let options = SqliteConnectOptions::from_str(db_url)
+        .context("invalid sqlite session database url")?
+        .read_only(true)
+        .create_if_missing(false);
+    if !options.get_filename().exists() {
+        return Ok(None);
+    }
+    let pool = SqlitePool::connect_with(options)
+        .await
+        .context("failed to open sqlite session database for the encryption key check")?;
+    let result = verify_session_key_in(&pool, key).await;
+    pool.close().await;
+    result
+}
+
+async fn verify_session_key_in(pool: &SqlitePool, key: &SessionKey) -> Result<Option<KeyCheck>> {
+    let table = sqlx::query("SELECT name FROM sqlite_master WHERE type = 'table' AND name = ?")
+        .bind(SESSION_KEY_CHECK_TABLE)
+        .fetch_optional(pool)
+        .await
+        .context("failed to read session database schema")?;
+    if table.is_none() {
+        return Ok(None);
+    }
+    Ok(match_key_check_record(pool, key)
+        .await?
+        .map(|_| KeyCheck::Matched))
+}
+
+/// The cipher for `key` when the key-check record exists and opens with it;
+/// `None` when there is no record yet, an error when the key is wrong.
+async fn match_key_check_record(
+    pool: &SqlitePool,
+    key: &SessionKey,
+) -> Result<Option<SessionCipher>> {
+    let existing = sqlx::query(&format!(
+        "SELECT salt, key_check FROM {SESSION_KEY_CHECK_TABLE} WHERE id = 1"
+    ))
+    .fetch_optional(pool)
+    .await
+    .context("failed to read session encryption key check")?;
+    let Some(row) = existing else {
+        return Ok(None);
+    };
+    let salt: String = row.get("salt");
+    let salt = hex_decode(&salt).context("session encryption salt is malformed")?;
+    let cipher = key.derive(&salt)?;
+    let sealed: String = row.get("key_check");
+    match cipher.open(&sealed, KEY_CHECK_AAD) {
+        Ok(plaintext) if plaintext == KEY_CHECK_PLAINTEXT => Ok(Some(cipher)),
+        _ => anyhow::bail!(
+            "session encryption key does not match the key this session database was \
+             encrypted with; check the variable named by session_encryption_key_env"
+        ),
+    }
+}
+
+/// [`SessionService`] adapter that encrypts event content on append and
+/// decrypts it on read.
+pub struct EncryptedSessionService {
+    inner: Arc<dyn SessionService>,
+    cipher: SessionCipher,
+}
+
+impl EncryptedSessionService {
+    pub fn new(inner: Arc<dyn SessionService>, cipher: SessionCipher) -> Self {
+        Self { inner, cipher }
+    }
+
+    fn decrypt_session(&self, session: Box<dyn Session>) -> adk_rust::Result<Box<dyn Session>> {
+        let session_id = session.id().to_string();
+        let events = session
+            .events()
+            .all()
+            .into_iter()
+            .map(|event| decrypt_event(&self.cipher, &session_id, event))
+            .collect::<Result<Vec<Event>>>()
+            .map_err(|err| adk_rust::AdkError::session(format!("{err:#}")))?;
+        Ok(Box::new(DecryptedSession {
+            inner: session,
+            events: DecryptedEvents(events),
+        }))
+    }
+}
+
+#[async_trait]
+impl SessionService for EncryptedSessionService {
+    async fn create(&self, req: CreateRequest) -> adk_rust::Result<Box<dyn Session>> {
+        let session = self.inner.create(req).await?;
+        self.decrypt_session(session)
+    }
+
+    async fn get(&self, req: GetRequest) -> adk_rust::Result<Box<dyn Session>> {
+        let session = self.inner.get(req).await?;
+        self.decrypt_session(session)
+    }
+
+    async fn list(&self, req: ListRequest) -> adk_rust::Result<Vec<Box<dyn Session>>> {
+        self.inner
+            .list(req)
+            .await?
+            .into_iter()
+            .map(|session| self.decrypt_session(session))
+            .collect()
+    }
+
+    async fn delete(&self, req: DeleteRequest) -> adk_rust::Result<()> {
+        self.inner.delete(req).await
+    }
+
+    async fn append_event(&self, session_id: &str, event: Event) -> adk_rust::Result<()> {
+        let event = encrypt_event(&self.cipher, session_id, &event)
+            .map_err(|err| adk_rust::AdkError::session(format!("{err:#}")))?;
+        self.inner.append_event(session_id, event).await
+    }
+}
+
+/// A stored session whose events were decrypted on read.
+struct DecryptedSession {
+    inner: Box<dyn Session>,
+    events: DecryptedEvents,
+}
+
+struct DecryptedEvents(Vec<Event>);
+
+impl Events for DecryptedEvents {
+    fn all(&self) -> Vec<Event> {
+        self.0.clone()
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn at(&self, index: usize) -> Option<&Event> {
+        self.0.get(index)
+    }
+}
+
+impl Session for DecryptedSession {
+    fn id(&self) -> &str {
+        self.inner.id()
+    }
+
+    fn app_name(&self) -> &str {
+        self.inner.app_name()
+    }
+
+    fn user_id(&self) -> &str {
+        self.inner.user_id()
+    }
+
+    fn state(&self) -> &dyn State {
+        self.inner.state()
+    }
+
+    fn events(&self) -> &dyn Events {
+        &self.events
+    }
+
+    fn last_update_time(&self) -> DateTime<Utc> {
+        self.inner.last_update_time()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EncryptReport {
+    pub sessions_rewritten: usize,
+    pub events_encrypted: usize,
+}
+
+/// Suffix of the sealed copy written before a session is replaced, so an
+/// interrupted `sessions encrypt` can be finished from it.
+pub const ENCRYPT_STAGING_SUFFIX: &str = ".encrypting";
+/// State key on a staged copy holding how many events it should have; a
+/// copy with fewer was cut short and its original is still intact.
+const ENCRYPT_STAGED_EVENTS_KEY: &str = "_zavora_encrypt_staged_events";
+
+/// Rewrite every session in `plain` whose events still carry plaintext,
+/// sealing them with `cipher`. Sessions are recreated with their state and
+/// events in order, the same way compaction rewrites a session.
+///
+/// The sealed events are first written to a `<id>.encrypting` copy and the
+/// original is only deleted once that copy is complete; the copy is removed
+/// after the original has been rewritten. A run that stopped halfway is
+/// finished from the copies it left behind.
+pub async fn encrypt_existing_sessions(
+    plain: &Arc<dyn SessionService>,
+    cipher: &SessionCipher,
+    cfg: &RuntimeConfig,
+) -> Result<EncryptReport> {
+    let sessions = with_session_busy_retry(cfg, "list", || {
+        plain.list(ListRequest {
+            app_name: cfg.app_name.clone(),
+            user_id: cfg.user_id.clone(),
+            limit: None,
+            offset: None,
+        })
+    })
+    .await
+    .context("failed to list sessions to encrypt")?;
+    let (staged_ids, session_ids): (Vec<String>, Vec<String>) = sessions
+        .iter()
+        .map(|session| session.id().to_string())
+        .partition(|id| id.ends_with(ENCRYPT_STAGING_SUFFIX));
+    drop(sessions);
+
+    let mut report = EncryptReport::default();
+    for staged_id in staged_ids {
+        let session_id = staged_id
+            .strip_suffix(ENCRYPT_STAGING_SUFFIX)
+            .unwrap_or(&staged_id)
+            .to_string();
+        let (mut state, events) = load_session_for_rewrite(plain, cfg, &staged_id).await?;
+        let expected = state
+            .remove(ENCRYPT_STAGED_EVENTS_KEY)
+            .and_then(|count| count.as_u64());
+        if expected == Some(events.len() as u64) {
+            if session_ids.contains(&session_id) {
+                delete_session(plain, cfg, &session_id).await?;
+            }
+            write_session(plain, cfg, &session_id, &state, &events).await?;
+            report.sessions_rewritten += 1;
+        }
+        delete_session(plain, cfg, &staged_id).await?;
+    }
+
+    for session_id in session_ids {
+        let (state, events) = load_session_for_rewrite(plain, cfg, &session_id).await?;
+        let plaintext = events
+            .iter()
+            .filter(|event| !is_encrypted_event(event))
+            .count();
+        if plaintext == 0 {
+            continue;
+        }
+        let sealed = events
+            .iter()
+            .map(|event| encrypt_event(cipher, &session_id, event))
+            .collect::<Result<Vec<Event>>>()?;
+
+        let staged_id = format!("{session_id}{ENCRYPT_STAGING_SUFFIX}");
+        let mut staged_state = state.clone();
+        staged_state.insert(
+            ENCRYPT_STAGED_EVENTS_KEY.to_string(),
+            serde_json::Value::from(sealed.len()),
+        );
+        write_session(plain, cfg, &staged_id, &staged_state, &sealed).await?;
+        delete_session(plain, cfg, &session_id).await?;
+        write_session(plain, cfg, &session_id, &state, &sealed).await?;
+        delete_session(plain, cfg, &staged_id).await?;
+        report.sessions_rewritten += 1;
+        report.events_encrypted += plaintext;
+    }
+    Ok(report)
+}
+
+async fn load_session_for_rewrite(
+    plain: &Arc<dyn SessionService>,
+    cfg: &RuntimeConfig,
+    session_id: &str,
+) -> Result<(HashMap<String, serde_json::Value>, Vec<Event>)> {
+    let session = with_session_busy_retry(cfg, "get", || {
+        plain.get(GetRequest {
+            app_name: cfg.app_name.clone(),
+            user_id: cfg.user_id.clone(),
+            session_id: session_id.to_string(),
+            num_recent_events: None,
+            after: None,
+        })
+    })
+    .await
+    .with_context(|| format!("failed to load session '{session_id}' to encrypt"))?;
+    Ok((session.state().all(), session.events().all()))
+}
+
+async fn delete_session(
+    plain: &Arc<dyn SessionService>,
+    cfg: &RuntimeConfig,
+    session_id: &str,
+) -> Result<()> {
+    with_session_busy_retry(cfg, "delete", || {
+        plain.delete(DeleteRequest {
+            app_name: cfg.app_name.clone(),
+            user_id: cfg.user_id.clone(),
+            session_id: session_id.to_string(),
+        })
+    })
+    .await
+    .with_context(|| format!("failed to delete session '{session_id}' while re-writing"))
+}
+
+/// Create `session_id` holding `state` and `events`.
+async fn write_session(
+    plain: &Arc<dyn SessionService>,
+    cfg: &RuntimeConfig,
+    session_id: &str,
+    state: &HashMap<String, serde_json::Value>,
+    events: &[Event],
+) -> Result<()> {
+    with_session_busy_retry(cfg, "create", || {
+        plain.create(CreateRequest {
+            app_name: cfg.app_name.clone(),
+            user_id: cfg.user_id.clone(),
+            session_id: Some(session_id.to_string()),
+            state: state.clone(),
+        })
+    })
+    .await
+    .with_context(|| format!("failed to recreate session '{session_id}'"))?;
+    for event in events {
+        with_session_busy_retry(cfg, "append_event", || {
+            plain.append_event(session_id, event.clone())
+        })
+        .await
+        .with_context(|| format!("failed to write encrypted event to '{session_id}'"))?;
+    }
+    Ok(())
+}
+
+/// `sessions encrypt`: encrypt plaintext events already in the sqlite
+/// session database with the configured key.
+pub async fn run_sessions_encrypt(cfg: &RuntimeConfig, force: bool) -> Result<()> {
+    if cfg.session_backend != SessionBackend::Sqlite {
+        anyhow::bail!("sessions encrypt only applies to the sqlite session backend");
+    }
+    let key = session_key(cfg)?
+        .context("sessions encrypt needs session_encryption_key_env set in the active profile")?;
+    if !force {
+        anyhow::bail!(
+            "sessions encrypt rewrites every session for app '{}' and user '{}'. Back up the \
+             database, then re-run with --force",
+            cfg.app_name,
+            cfg.user_id
+        );
+    }
+    let plain: Arc<dyn SessionService> =
+        Arc::new(open_sqlite_session_service(&cfg.session_db_url).await?);
+    let (cipher, _) = check_session_key(&cfg.session_db_url, &key).await?;
+    let report = encrypt_existing_sessions(&plain, &cipher, cfg).await?;
+    // Deleted plaintext rows linger in free pages until the file is rebuilt.
+    let pool = SqlitePool::connect(&cfg.session_db_url)
+        .await
+        .context("failed to open sqlite session database to vacuum")?;
+    let vacuumed = sqlx::query("VACUUM").execute(&pool).await;
+    pool.close().await;
+    vacuumed.context("failed to vacuum the session database after encrypting")?;
+    println!(
+        "Encrypted {} event(s) across {} session(s) for app '{}' and user '{}'.",
+        report.events_encrypted, report.sessions_rewritten, cfg.app_name, cfg.user_id
+    );
+    Ok(())
+}
+
+/// Doctor line for `session_encryption_key_env`: the variable must be set
+/// and match the database's key-check record. Never writes to the database.
+pub async fn session_encryption_doctor_line(cfg: &RuntimeConfig) -> String {
+    let Some(env) = cfg.session_encryption_key_env.as_deref() else {
+        return "Session encryption: off".to_string();
+    };
+    if cfg.session_backend != SessionBackend::Sqlite {
+        return format!("Session encryption: not applied (key env {env}; memory backend)");
+    }
+    let key = match session_key(cfg) {
+        Ok(Some(key)) => key,
+        Ok(None) => return "Session encryption: off".to_string(),
+        Err(err) => return format!("Session encryption: error: {err:#}"),
+    };
+    match verify_session_key(&cfg.session_db_url, &key).await {
+        Ok(Some(_)) => format!("Session encryption: ok (key from {env})"),
+        Ok(None) => format!(
+            "Session encryption: ok (key from {env}; key check recorded on first session use)"
+        ),
+        Err(err) => format!("Session encryption: error: {err:#}"),
+    }
+}
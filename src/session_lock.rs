@@ -0,0 +1,290 @@
+//! Advisory locks on chat sessions.
+//!
+//! Two chats appending to one sqlite session interleave their events and
+//! confuse both conversations, so `chat` and `server serve` claim the session
+//! they attach to with a record (pid, hostname, started_at) in
+//! `<state>/session-locks/`, keyed by backend, database and session id. A
+//! second claimant is shown the holder and needs `--steal-session-lock` (or a
+//! yes at the TTY prompt) to take over; the old holder no longer owns the
+//! record afterwards and leaves it alone when it exits. Records left by a
+//! process that is no longer running on this host are reclaimed silently.
+//!
+//! Memory sessions are private to their process and are never locked, and
+//! one-shot commands (`ask`, `workflow`, ...) ignore locks altogether.
+use std::fs::OpenOptions;
+use std::io::{self, ErrorKind, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::cli::SessionBackend;
+use crate::config::RuntimeConfig;
+use crate::file_lock::process_is_alive;
+use crate::telemetry_export::host_name;
+use crate::ui::Ui;
+
+/// Creation races retried before giving up.
+const SESSION_LOCK_ATTEMPTS: usize = 3;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionLockRecord {
+    pub pid: u32,
+    pub hostname: String,
+    /// RFC 3339.
+    pub started_at: String,
+    /// Command label of the holder (`chat`, `server.serve`).
+    pub command: String,
+    pub backend: String,
+    pub session_id: String,
+}
+
+impl SessionLockRecord {
+    pub fn for_current_process(cfg: &RuntimeConfig, command: &str) -> Self {
+        Self {
+            pid: std::process::id(),
+            hostname: host_name(),
+            started_at: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            command: command.to_string(),
+            backend: format!("{:?}", cfg.session_backend).to_ascii_lowercase(),
+            session_id: cfg.session_id.clone(),
+        }
+    }
+
+    /// `pid 4242 on devbox (chat, since 2026-01-02T03:04:05Z)`.
+    pub fn describe(&self) -> String {
+        format!(
+            "pid {} on {} ({}, since {})",
+            self.pid, self.hostname, self.command, self.started_at
+        )
+    }
+}
+
+/// Whether `cfg`'s session can be shared with another process at all.
+pub fn session_lock_applies(cfg: &RuntimeConfig) -> bool {
+    cfg.session_backend != SessionBackend::Memory
+}
+
+/// Lock file for `cfg`'s session under `dir`. The name hashes the database
+/// URL with the session id, so `default` in another workspace's database is
+/// a different lock.
+pub fn session_lock_path(dir: &Path, cfg: &RuntimeConfig) -> PathBuf {
+    let backend = format!("{:?}", cfg.session_backend).to_ascii_lowercase();
+    let mut hasher = Sha256::new();
+    hasher.update(backend.as_bytes());
+    hasher.update([0]);
+    hasher.update(cfg.session_db_url.as_bytes());
+    hasher.update([0]);
+    hasher.update(cfg.session_id.as_bytes());
+    let digest = hasher
+        .finalize()
+        .iter()
+        .take(8)
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<String>();
+    dir.join(format!("{backend}-{digest}.json"))
+}
+
+/// The record in `path`; `None` when there is none or it cannot be parsed.
+pub fn read_session_lock(path: &Path) -> Option<SessionLockRecord> {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+}
+
+/// How an earlier holder's record was replaced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplacedSessionLock {
+    /// The holder was no longer running on this host.
+    Reclaimed(SessionLockRecord),
+    /// The holder was live (or on another host) and the lock was stolen.
+    Stolen(SessionLockRecord),
+}
+
+/// A held session lock; the record is removed on drop unless another
+/// process has stolen it since.
+#[derive(Debug)]
+pub struct SessionLock {
+    path: PathBuf,
+    record: SessionLockRecord,
+    pub replaced: Option<ReplacedSessionLock>,
+}
+
+impl SessionLock {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether the record on disk is still ours.
+    pub fn is_held(&self) -> bool {
+        read_session_lock(&self.path).as_ref() == Some(&self.record)
+    }
+}
+
+impl Drop for SessionLock {
+    fn drop(&mut self) {
+        if self.is_held() {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SessionLockAttempt {
+    Acquired(SessionLock),
+    /// Someone else holds the session.
+    Held(SessionLockRecord),
+}
+
+fn write_new_session_lock(path: &Path, record: &SessionLockRecord) -> io::Result<()> {
+    let mut file = OpenOptions::new().write(true).create_new(true).open(path)?;
+    file.write_all(serde_json::to_string_pretty(record)?.as_bytes())
+}
+
+fn replace_session_lock(path: &Path, record: &SessionLockRecord) -> Result<()> {
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, serde_json::to_string_pretty(record)?)
+        .with_context(|| format!("failed to write session lock '{}'", tmp.display()))?;
+    std::fs::rename(&tmp, path)
+        .with_context(|| format!("failed to write session lock '{}'", path.display()))
+}
+
+/// Take the lock at `path` for `record` unless a live holder has it.
+/// Records of dead processes on the same host (per `is_alive`) are
+/// reclaimed; `steal` takes the lock from any other holder.
+pub fn try_acquire_session_lock(
+    path: &Path,
+    record: SessionLockRecord,
+    steal: bool,
+    is_alive: &dyn Fn(u32) -> bool,
+) -> Result<SessionLockAttempt> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).with_context(|| {
+            format!(
+                "failed to create session lock directory '{}'",
+                dir.display()
+            )
+        })?;
+    }
+    for _ in 0..SESSION_LOCK_ATTEMPTS {
+        let replaced = match read_session_lock(path) {
+            None if !path.exists() => match write_new_session_lock(path, &record) {
+                Ok(()) => {
+                    return Ok(SessionLockAttempt::Acquired(SessionLock {
+                        path: path.to_path_buf(),
+                        record,
+                        replaced: None,
+                    }));
+                }
+                // Another process created it between the read and the write.
+                Err(err) if err.kind() == ErrorKind::AlreadyExists => continue,
+                Err(err) => {
+                    return Err(err).with_context(|| {
+                        format!("failed to write session lock '{}'", path.display())
+                    });
+                }
+            },
+            // Unreadable (possibly half-written) records are replaced.
+            None => None,
+            Some(holder) if holder.hostname == record.hostname && holder.pid == record.pid => None,
+            Some(holder) if holder.hostname == record.hostname && !is_alive(holder.pid) => {
+                Some(ReplacedSessionLock::Reclaimed(holder))
+            }
+            Some(holder) if steal => Some(ReplacedSessionLock::Stolen(holder)),
+            Some(holder) => return Ok(SessionLockAttempt::Held(holder)),
+        };
+        replace_session_lock(path, &record)?;
+        return Ok(SessionLockAttempt::Acquired(SessionLock {
+            path: path.to_path_buf(),
+            record,
+            replaced,
+        }));
+    }
+    Err(anyhow::anyhow!(
+        "failed to acquire session lock '{}': it kept changing under us",
+        path.display()
+    ))
+}
+
+/// [`claim_session_lock`] with the liveness probe and the takeover prompt
+/// supplied by the caller.
+pub fn claim_session_lock_with(
+    path: &Path,
+    record: SessionLockRecord,
+    steal: bool,
+    is_alive: &dyn Fn(u32) -> bool,
+    confirm_steal: impl FnOnce(&SessionLockRecord) -> Result<bool>,
+) -> Result<SessionLock> {
+    let holder = match try_acquire_session_lock(path, record.clone(), steal, is_alive)? {
+        SessionLockAttempt::Acquired(lock) => return Ok(lock),
+        SessionLockAttempt::Held(holder) => holder,
+    };
+    if confirm_steal(&holder)? {
+        match try_acquire_session_lock(path, record, true, is_alive)? {
+            SessionLockAttempt::Acquired(lock) => return Ok(lock),
+            SessionLockAttempt::Held(holder) => {
+                unreachable!("stealing never reports a holder ({})", holder.describe())
+            }
+        }
+    }
+    Err(anyhow::anyhow!(
+        "session '{}' is already open in {}. Two chats on one session interleave their \
+         events; pass --steal-session-lock to take it over anyway, or use another --session-id.",
+        holder.session_id,
+        holder.describe()
+    ))
+}
+
+fn confirm_steal_on_tty(holder: &SessionLockRecord, ui: &Ui) -> Result<bool> {
+    if !io::stdin().is_terminal() {
+        return Ok(false);
+    }
+    ui.warn(&format!(
+        "session '{}' is already open in {}",
+        holder.session_id,
+        holder.describe()
+    ));
+    eprint!("Take it over? The other chat's turns will interleave with yours. [y/N]: ");
+    io::stderr().flush().ok();
+    let mut answer = String::new();
+    io::stdin()
+        .read_line(&mut answer)
+        .context("failed to read session lock answer")?;
+    Ok(matches!(
+        answer.trim().to_ascii_lowercase().as_str(),
+        "y" | "yes"
+    ))
+}
+
+/// Claim `cfg`'s session for `command` (`chat`, `server.serve`), asking on a
+/// TTY before stealing a live holder's lock. `None` for memory sessions.
+pub fn claim_session_lock(
+    cfg: &RuntimeConfig,
+    command: &str,
+    steal: bool,
+    ui: &Ui,
+) -> Result<Option<SessionLock>> {
+    if !session_lock_applies(cfg) {
+        return Ok(None);
+    }
+    let path = session_lock_path(&cfg.paths.session_locks_dir(), cfg);
+    let record = SessionLockRecord::for_current_process(cfg, command);
+    let lock = claim_session_lock_with(&path, record, steal, &process_is_alive, |holder| {
+        confirm_steal_on_tty(holder, ui)
+    })?;
+    match &lock.replaced {
+        Some(ReplacedSessionLock::Reclaimed(holder)) => tracing::info!(
+            lock = %path.display(),
+            holder = %holder.describe(),
+            "Reclaimed session lock from a process that is no longer running"
+        ),
+        Some(ReplacedSessionLock::Stolen(holder)) => ui.warn(&format!(
+            "took over session '{}' from {}",
+            cfg.session_id,
+            holder.describe()
+        )),
+        None => {}
+    }
+    Ok(Some(lock))
+}
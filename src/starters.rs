@@ -0,0 +1,208 @@
+//! Conversation starters: short labeled prompt templates offered by an agent.
+//!
+//! An agent's `starters` are listed when chat starts with that agent active
+//! ("Try: 1) ... 2) ...") and picked by number or label with `/starters`. A
+//! `<placeholder>` in the prompt is asked for before the filled-in prompt is
+//! sent exactly like a typed one, through the input guardrail and retrieval.
+//! Catalogs are checked at load: an empty list, more than
+//! [`MAX_AGENT_STARTERS`] entries or an over-long label or prompt is an error.
+use std::collections::HashMap;
+
+use anyhow::Result;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::chat_input::{ChatInput, ChatInputEvent};
+use crate::config::ResolvedAgent;
+
+/// Starters per agent; one digit each in the picker.
+pub const MAX_AGENT_STARTERS: usize = 9;
+pub const MAX_STARTER_LABEL_CHARS: usize = 60;
+pub const MAX_STARTER_PROMPT_CHARS: usize = 2000;
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AgentStarter {
+    /// Shown in the chat banner and the `/starters` picker.
+    pub label: String,
+    /// Sent when picked; each `<name>` is asked for first.
+    pub prompt: String,
+}
+
+/// Check `agent`'s starters against the catalog limits.
+pub fn validate_agent_starters(agent: &str, starters: &[AgentStarter]) -> Result<()> {
+    if starters.is_empty() {
+        return Err(anyhow::anyhow!(
+            "agent '{agent}' has an empty starters list; remove the field or add a starter"
+        ));
+    }
+    if starters.len() > MAX_AGENT_STARTERS {
+        return Err(anyhow::anyhow!(
+            "agent '{agent}' has {} starters; at most {MAX_AGENT_STARTERS} are allowed",
+            starters.len()
+        ));
+    }
+    for (index, starter) in starters.iter().enumerate() {
+        let number = index + 1;
+        let label = starter.label.trim();
+        if label.is_empty() {
+            return Err(anyhow::anyhow!(
+                "agent '{agent}' starter {number} has an empty label"
+            ));
+        }
+        if label.chars().count() > MAX_STARTER_LABEL_CHARS {
+            return Err(anyhow::anyhow!(
+                "agent '{agent}' starter '{label}' has a label longer than \
+                 {MAX_STARTER_LABEL_CHARS} characters"
+            ));
+        }
+        if starter.prompt.trim().is_empty() {
+            return Err(anyhow::anyhow!(
+                "agent '{agent}' starter '{label}' has an empty prompt"
+            ));
+        }
+        if starter.prompt.chars().count() > MAX_STARTER_PROMPT_CHARS {
+            return Err(anyhow::anyhow!(
+                "agent '{agent}' starter '{label}' has a prompt longer than \
+                 {MAX_STARTER_PROMPT_CHARS} characters"
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// The starters of agent `name`, if it has any.
+pub fn agent_starters<'a>(
+    agents: &'a HashMap<String, ResolvedAgent>,
+    name: &str,
+) -> Option<&'a [AgentStarter]> {
+    agents
+        .get(name)
+        .and_then(|agent| agent.config.starters.as_deref())
+        .filter(|starters| !starters.is_empty())
+}
+
+/// Chat banner line: `Try: 1) Review a file  2) Draft release notes`.
+pub fn format_starters_hint(starters: &[AgentStarter]) -> String {
+    let items = starters
+        .iter()
+        .enumerate()
+        .map(|(index, starter)| format!("{}) {}", index + 1, starter.label.trim()))
+        .collect::<Vec<String>>();
+    format!("Try: {}  (/starters <n> to pick one)", items.join("  "))
+}
+
+/// The starter picked by a 1-based number or a label (case-insensitive);
+/// `None` when the selection is empty or `cancel`.
+pub fn resolve_starter_selection<'a>(
+    starters: &'a [AgentStarter],
+    selection: &str,
+) -> Result<Option<&'a AgentStarter>> {
+    let trimmed = selection.trim();
+    if trimmed.is_empty() || trimmed.eq_ignore_ascii_case("cancel") {
+        return Ok(None);
+    }
+    if let Ok(number) = trimmed.parse::<usize>() {
+        return match number.checked_sub(1).and_then(|index| starters.get(index)) {
+            Some(starter) => Ok(Some(starter)),
+            None => Err(anyhow::anyhow!(
+                "invalid starter '{trimmed}'; expected 1-{}",
+                starters.len()
+            )),
+        };
+    }
+    starters
+        .iter()
+        .find(|starter| starter.label.trim().eq_ignore_ascii_case(trimmed))
+        .map(Some)
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "invalid starter '{trimmed}'; expected 1-{} or a starter label",
+                starters.len()
+            )
+        })
+}
+
+fn is_placeholder_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with(' ')
+        && !name.ends_with(' ')
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | ' '))
+}
+
+/// `<name>` placeholders in `prompt`, each once, in order of appearance.
+/// Angle brackets around anything else (`a < b > c`, `<a href=..>`) are
+/// left alone.
+pub fn starter_placeholders(prompt: &str) -> Vec<String> {
+    let mut names = Vec::<String>::new();
+    let mut rest = prompt;
+    while let Some(start) = rest.find('<') {
+        let after = &rest[start + 1..];
+        let Some(end) = after.find('>') else {
+            break;
+        };
+        let candidate = &after[..end];
+        if is_placeholder_name(candidate) {
+            if !names.iter().any(|name| name == candidate) {
+                names.push(candidate.to_string());
+            }
+            rest = &after[end + 1..];
+        } else {
+            rest = after;
+        }
+    }
+    names
+}
+
+/// `prompt` with every placeholder replaced by `value_for(name)`, asked once
+/// per name. `None` when `value_for` cancels (returns `None`).
+pub fn fill_starter_placeholders(
+    prompt: &str,
+    mut value_for: impl FnMut(&str) -> Result<Option<String>>,
+) -> Result<Option<String>> {
+    let mut filled = prompt.to_string();
+    for name in starter_placeholders(prompt) {
+        let Some(value) = value_for(&name)? else {
+            return Ok(None);
+        };
+        filled = filled.replace(&format!("<{name}>"), value.trim());
+    }
+    Ok(Some(filled))
+}
+
+/// `/starters [n|label]`: pick a starter (listing them and asking when no
+/// selection is given), then ask for its placeholders on `input`. `None`
+/// when the user cancels; an empty placeholder value cancels too.
+pub fn pick_starter(
+    starters: &[AgentStarter],
+    selection: &str,
+    input: &mut dyn ChatInput,
+) -> Result<Option<String>> {
+    let selection = if selection.trim().is_empty() {
+        for (index, starter) in starters.iter().enumerate() {
+            println!(
+                "{}. {}: {}",
+                index + 1,
+                starter.label.trim(),
+                starter.prompt
+            );
+        }
+        match input.read_line("Select starter number or label (Enter to cancel): ")? {
+            ChatInputEvent::Line(line) => line,
+            _ => return Ok(None),
+        }
+    } else {
+        selection.to_string()
+    };
+    let Some(starter) = resolve_starter_selection(starters, &selection)? else {
+        return Ok(None);
+    };
+    fill_starter_placeholders(&starter.prompt, |name| {
+        match input.read_line(&format!("{name} (Enter to cancel): "))? {
+            ChatInputEvent::Line(value) if !value.trim().is_empty() => Ok(Some(value)),
+            _ => Ok(None),
+        }
+    })
+}
@@ -1,5 +1,7 @@
-use std::collections::HashMap;
-use std::io::{self, Write};
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, IsTerminal, Write};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
 use adk_rust::futures::StreamExt;
 use adk_rust::prelude::*;
@@ -7,19 +9,99 @@ use anyhow::{Context, Result};
 use serde_json::Value;
 
 use crate::config::RuntimeConfig;
+use crate::context::estimate_tokens;
 use crate::markdown::{ParseState, parse_markdown};
-use crate::retrieval::{RetrievalPolicy, RetrievalService, augment_prompt_with_retrieval};
-use crate::telemetry::TelemetrySink;
+use crate::progress::ProgressEmitter;
+use crate::prompt_assembly::{
+    ContextBudget, PromptEnvironment, context_budget, guard_retrieval_sections,
+    render_user_content, retrieval_policy, screened_user_content_sections,
+    system_instruction_sections, with_memory_section,
+};
+use crate::retrieval::{
+    RetrievalPolicy, RetrievalService, describe_selected_chunks, select_retrieval_chunks,
+};
+use crate::sanitize::{StreamSanitizer, sanitize_output};
+use crate::telemetry::{TelemetrySink, with_error_fingerprint, with_mocked_marker};
 use crate::theme::Spinner;
+use crate::tool_output::{
+    format_tool_call_annotation, format_tool_done_annotation, render_tool_result,
+    render_tool_result_full, tool_annotation_target, write_tool_annotation,
+};
+use crate::usage::{UsageMeter, check_usage_budget, record_model_usage};
+use crate::verbosity::Verbosity;
 
 pub const NO_TEXTUAL_RESPONSE: &str = "No textual response produced by the agent.";
 
+/// Label that opens a synthesized answer built from the last tool result.
+pub const TOOL_RESULT_FALLBACK_LABEL: &str = "[tool-result fallback]";
+
+/// Pretty-printed chars of the last tool result kept in the fallback.
+pub const TOOL_RESULT_FALLBACK_MAX_CHARS: usize = 2_000;
+
 #[derive(Default, Debug)]
 pub struct AuthorTextTracker {
     pub latest_final_text: Option<String>,
     pub latest_final_author: Option<String>,
     pub last_textful_author: Option<String>,
     pub by_author: HashMap<String, String>,
+    /// Tool names in the order their responses arrived.
+    pub tools_run: Vec<String>,
+    /// The most recent `FunctionResponse`: tool name and payload.
+    pub last_tool_result: Option<(String, Value)>,
+    clock: StreamClock,
+}
+
+/// Timing marks for one prompt run, fed by the tracker so buffered and
+/// streaming runs measure the same thing.
+#[derive(Default, Debug, Clone, Copy)]
+struct StreamClock {
+    started_at: Option<Instant>,
+    first_delta_at: Option<Instant>,
+    last_delta_at: Option<Instant>,
+    finished_at: Option<Instant>,
+    deltas: u64,
+    bytes: u64,
+}
+
+/// Per-call stream health, emitted as `model.stream_stats`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StreamStats {
+    /// `None` when the run produced no text.
+    pub first_token_ms: Option<u64>,
+    pub total_ms: u64,
+    pub deltas: u64,
+    pub bytes: u64,
+}
+
+impl StreamStats {
+    /// `1.2s to first token, 8.4s total, 3.1 KB`.
+    pub fn summary(&self) -> String {
+        let first = match self.first_token_ms {
+            Some(ms) => format!("{} to first token", format_stream_secs(ms)),
+            None => "no text".to_string(),
+        };
+        format!(
+            "{first}, {} total, {}",
+            format_stream_secs(self.total_ms),
+            format_stream_bytes(self.bytes)
+        )
+    }
+}
+
+fn format_stream_secs(ms: u64) -> String {
+    format!("{:.1}s", ms as f64 / 1000.0)
+}
+
+fn format_stream_bytes(bytes: u64) -> String {
+    if bytes < 1024 {
+        format!("{bytes} B")
+    } else {
+        format!("{:.1} KB", bytes as f64 / 1024.0)
+    }
+}
+
+fn elapsed_ms(from: Instant, to: Instant) -> u64 {
+    to.saturating_duration_since(from).as_millis() as u64
 }
 
 impl AuthorTextTracker {
@@ -29,6 +111,18 @@ impl AuthorTextTracker {
         text: &str,
         partial: bool,
         is_final: bool,
+    ) -> String {
+        self.ingest_parts_at(author, text, partial, is_final, Instant::now())
+    }
+
+    /// [`Self::ingest_parts`] with an explicit clock reading.
+    pub fn ingest_parts_at(
+        &mut self,
+        author: &str,
+        text: &str,
+        partial: bool,
+        is_final: bool,
+        now: Instant,
     ) -> String {
         if text.is_empty() {
             return String::new();
@@ -43,9 +137,91 @@ impl AuthorTextTracker {
             self.latest_final_author = Some(author.to_string());
         }
 
+        if !delta.is_empty() {
+            self.clock.first_delta_at.get_or_insert(now);
+            self.clock.last_delta_at = Some(now);
+            self.clock.deltas += 1;
+            self.clock.bytes += delta.len() as u64;
+        }
+
         delta
     }
 
+    pub fn observe_tool_response(&mut self, name: &str, response: &Value) {
+        self.tools_run.push(name.to_string());
+        self.last_tool_result = Some((name.to_string(), response.clone()));
+    }
+
+    /// Record every `FunctionResponse` in `event`.
+    pub fn observe_tool_responses(&mut self, event: &Event) {
+        let Some(content) = event.content() else {
+            return;
+        };
+        for part in &content.parts {
+            if let Part::FunctionResponse {
+                function_response, ..
+            } = part
+            {
+                self.observe_tool_response(&function_response.name, &function_response.response);
+            }
+        }
+    }
+
+    /// Answer for a run that produced tool results but no text: the last
+    /// tool's name and a bounded excerpt of its payload, clearly labeled.
+    pub fn tool_result_fallback(&self) -> Option<String> {
+        let (name, response) = self.last_tool_result.as_ref()?;
+        Some(format!(
+            "{TOOL_RESULT_FALLBACK_LABEL} The agent finished without a final answer. \
+             Last tool result:\n{}\nTools run: {}",
+            render_tool_result_full(name, response, TOOL_RESULT_FALLBACK_MAX_CHARS),
+            self.distinct_tools_run().join(", ")
+        ))
+    }
+
+    /// [`Self::tools_run`] without repeats, in first-seen order.
+    pub fn distinct_tools_run(&self) -> Vec<&str> {
+        let mut tools = Vec::<&str>::new();
+        for tool in &self.tools_run {
+            if !tools.contains(&tool.as_str()) {
+                tools.push(tool);
+            }
+        }
+        tools
+    }
+
+    /// Mark the request as sent; resets earlier stream stats.
+    pub fn start_at(&mut self, now: Instant) {
+        self.clock = StreamClock {
+            started_at: Some(now),
+            ..StreamClock::default()
+        };
+    }
+
+    pub fn finish_at(&mut self, now: Instant) {
+        self.clock.finished_at = Some(now);
+    }
+
+    /// Stats between [`Self::start_at`] and [`Self::finish_at`] (or the
+    /// last delta while still running). `None` before the run started.
+    pub fn stream_stats(&self) -> Option<StreamStats> {
+        let started_at = self.clock.started_at?;
+        let ended_at = self
+            .clock
+            .finished_at
+            .or(self.clock.last_delta_at)
+            .unwrap_or(started_at);
+        Some(StreamStats {
+            first_token_ms: self
+                .clock
+                .first_delta_at
+                .map(|first| elapsed_ms(started_at, first)),
+            total_ms: elapsed_ms(started_at, ended_at),
+            deltas: self.clock.deltas,
+            bytes: self.clock.bytes,
+        })
+    }
+
     pub fn resolve_text(&self) -> Option<String> {
         if let Some(final_text) = &self.latest_final_text {
             return Some(final_text.clone());
@@ -62,6 +238,133 @@ impl AuthorTextTracker {
     }
 }
 
+/// The answer for a finished run: its final text; otherwise the tool-result
+/// fallback, or an error under `strict_final_text`. Emits
+/// `prompt.no_final_text` whenever the run ended without text.
+pub fn resolve_answer(
+    tracker: &AuthorTextTracker,
+    cfg: &RuntimeConfig,
+    telemetry: &TelemetrySink,
+) -> Result<String> {
+    if let Some(text) = tracker.resolve_text() {
+        return Ok(text);
+    }
+    let tools = tracker.distinct_tools_run();
+    telemetry.emit(
+        "prompt.no_final_text",
+        serde_json::json!({
+            "tools": tools,
+            "tool_calls": tracker.tools_run.len(),
+            "last_tool": tracker.last_tool_result.as_ref().map(|(name, _)| name),
+            "strict": cfg.strict_final_text
+        }),
+    );
+    if cfg.strict_final_text {
+        let ran = if tools.is_empty() {
+            "none".to_string()
+        } else {
+            tools.join(", ")
+        };
+        return Err(anyhow::anyhow!(
+            "{NO_TEXTUAL_RESPONSE} (strict_final_text is set; tools run: {ran})"
+        ));
+    }
+    Ok(tracker
+        .tool_result_fallback()
+        .unwrap_or_else(|| NO_TEXTUAL_RESPONSE.to_string()))
+}
+
+const STREAM_STATS_HISTORY_LEN: usize = 20;
+
+/// Recent [`StreamStats`] of this process, i.e. the chat session, for
+/// `/status`.
+#[derive(Debug, Default)]
+pub struct StreamStatsHistory {
+    recent: VecDeque<StreamStats>,
+}
+
+impl StreamStatsHistory {
+    pub const fn new() -> Self {
+        Self {
+            recent: VecDeque::new(),
+        }
+    }
+
+    pub fn record(&mut self, stats: StreamStats) {
+        if self.recent.len() == STREAM_STATS_HISTORY_LEN {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(stats);
+    }
+
+    pub fn last(&self) -> Option<StreamStats> {
+        self.recent.back().copied()
+    }
+
+    /// `last response: ...` plus a rolling average once there are several.
+    pub fn status_lines(&self) -> Vec<String> {
+        let Some(last) = self.last() else {
+            return Vec::new();
+        };
+        let mut lines = vec![format!("last response: {}", last.summary())];
+        let first_tokens = self
+            .recent
+            .iter()
+            .filter_map(|stats| stats.first_token_ms)
+            .collect::<Vec<u64>>();
+        if self.recent.len() > 1 && !first_tokens.is_empty() {
+            let avg_first = first_tokens.iter().sum::<u64>() / first_tokens.len() as u64;
+            let avg_total = self.recent.iter().map(|stats| stats.total_ms).sum::<u64>()
+                / self.recent.len() as u64;
+            lines.push(format!(
+                "last {} responses: {} to first token, {} total on average",
+                self.recent.len(),
+                format_stream_secs(avg_first),
+                format_stream_secs(avg_total)
+            ));
+        }
+        lines
+    }
+}
+
+static STREAM_STATS_HISTORY: Mutex<StreamStatsHistory> = Mutex::new(StreamStatsHistory::new());
+
+pub fn stream_stats_status_lines() -> Vec<String> {
+    STREAM_STATS_HISTORY
+        .lock()
+        .map(|history| history.status_lines())
+        .unwrap_or_default()
+}
+
+/// Close the tracker's clock, emit `model.stream_stats` and remember the
+/// stats for `/status`.
+fn finish_stream_stats(
+    tracker: &mut AuthorTextTracker,
+    cfg: &RuntimeConfig,
+    telemetry: &TelemetrySink,
+    mode: &str,
+    tokens: u64,
+) -> Option<StreamStats> {
+    tracker.finish_at(Instant::now());
+    let stats = tracker.stream_stats()?;
+    telemetry.emit(
+        "model.stream_stats",
+        serde_json::json!({
+            "mode": mode,
+            "session_id": cfg.session_id,
+            "first_token_ms": stats.first_token_ms,
+            "total_ms": stats.total_ms,
+            "deltas": stats.deltas,
+            "bytes": stats.bytes,
+            "tokens": tokens
+        }),
+    );
+    if let Ok(mut history) = STREAM_STATS_HISTORY.lock() {
+        history.record(stats);
+    }
+    Some(stats)
+}
+
 pub fn ingest_author_text(
     buffer: &mut String,
     text: &str,
@@ -183,14 +486,112 @@ pub fn extract_tool_failure_message(response: &Value) -> Option<String> {
     None
 }
 
-pub fn emit_tool_lifecycle_events(event: &Event, telemetry: &TelemetrySink) {
+/// Default `slow_tool_warn_secs`.
+pub const DEFAULT_SLOW_TOOL_WARN_SECS: u64 = 10;
+
+/// How often chat checks in-flight tools against the slow-tool threshold.
+const SLOW_TOOL_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone)]
+struct PendingToolCall {
+    name: String,
+    invocation: u64,
+    started_at: Instant,
+    warned: bool,
+}
+
+/// A tool response paired with its call. `duration` is `None` when the
+/// response arrived without a matching call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolCompletion {
+    pub name: String,
+    pub invocation: Option<u64>,
+    pub duration: Option<Duration>,
+    pub failed: bool,
+}
+
+/// Pairs `FunctionCall` and `FunctionResponse` events to time tool runs.
+/// Calls are keyed by tool name plus an invocation counter; responses match
+/// the oldest pending call of the same name, so interleaved calls to
+/// different tools are timed independently.
+#[derive(Debug, Default)]
+pub struct ToolTimingTracker {
+    pending: Vec<PendingToolCall>,
+    next_invocation: u64,
+}
+
+impl ToolTimingTracker {
+    pub fn call_started(&mut self, name: &str, at: Instant) -> u64 {
+        self.next_invocation += 1;
+        self.pending.push(PendingToolCall {
+            name: name.to_string(),
+            invocation: self.next_invocation,
+            started_at: at,
+            warned: false,
+        });
+        self.next_invocation
+    }
+
+    pub fn call_finished(&mut self, name: &str, failed: bool, at: Instant) -> ToolCompletion {
+        let matched = self
+            .pending
+            .iter()
+            .position(|call| call.name == name)
+            .map(|idx| self.pending.remove(idx));
+        ToolCompletion {
+            name: name.to_string(),
+            invocation: matched.as_ref().map(|call| call.invocation),
+            duration: matched.map(|call| at.saturating_duration_since(call.started_at)),
+            failed,
+        }
+    }
+
+    pub fn has_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// Pending calls that crossed `threshold` since the last check; each call
+    /// is reported once.
+    pub fn newly_slow(&mut self, threshold: Duration, now: Instant) -> Vec<(String, Duration)> {
+        self.pending
+            .iter_mut()
+            .filter(|call| !call.warned)
+            .filter_map(|call| {
+                let elapsed = now.saturating_duration_since(call.started_at);
+                (elapsed >= threshold).then(|| {
+                    call.warned = true;
+                    (call.name.clone(), elapsed)
+                })
+            })
+            .collect()
+    }
+}
+
+pub fn emit_tool_lifecycle_events(
+    event: &Event,
+    telemetry: &TelemetrySink,
+    timing: &mut ToolTimingTracker,
+) -> Vec<ToolCompletion> {
+    emit_tool_lifecycle_events_at(event, telemetry, timing, Instant::now())
+}
+
+/// Emit `tool.*` telemetry for one event, timing tool runs at `now`.
+/// Returns the tool completions seen in the event.
+pub fn emit_tool_lifecycle_events_at(
+    event: &Event,
+    telemetry: &TelemetrySink,
+    timing: &mut ToolTimingTracker,
+    now: Instant,
+) -> Vec<ToolCompletion> {
+    let mut completions = Vec::new();
     let Some(content) = event.content() else {
-        return;
+        return completions;
     };
 
     for part in &content.parts {
         match part {
             Part::FunctionCall { name, .. } => {
+                timing.call_started(name, now);
                 tracing::info!(
                     tool = %name,
                     author = %event.author,
@@ -208,43 +609,80 @@ pub fn emit_tool_lifecycle_events(event: &Event, telemetry: &TelemetrySink) {
             Part::FunctionResponse {
                 function_response, ..
             } => {
-                if let Some(error_message) =
-                    extract_tool_failure_message(&function_response.response)
-                {
+                let failure = extract_tool_failure_message(&function_response.response);
+                let completion =
+                    timing.call_finished(&function_response.name, failure.is_some(), now);
+                let duration_ms = completion.duration.map(|d| d.as_millis() as u64);
+                if let Some(error_message) = failure {
                     tracing::warn!(
                         tool = %function_response.name,
                         author = %event.author,
                         lifecycle = "failed",
                         error = %error_message,
+                        duration_ms = ?duration_ms,
                         "Tool execution failed"
                     );
                     telemetry.emit(
                         "tool.failed",
-                        serde_json::json!({
-                            "tool": function_response.name,
-                            "author": event.author,
-                            "error": error_message
-                        }),
+                        with_mocked_marker(
+                            with_error_fingerprint(
+                                serde_json::json!({
+                                    "tool": function_response.name,
+                                    "author": event.author,
+                                    "error": error_message,
+                                    "duration_ms": duration_ms
+                                }),
+                                &error_message,
+                            ),
+                            &function_response.response,
+                        ),
                     );
                 } else {
                     tracing::info!(
                         tool = %function_response.name,
                         author = %event.author,
                         lifecycle = "succeeded",
+                        duration_ms = ?duration_ms,
                         "Tool execution completed"
                     );
                     telemetry.emit(
                         "tool.succeeded",
-                        serde_json::json!({
-                            "tool": function_response.name,
-                            "author": event.author
-                        }),
+                        with_mocked_marker(
+                            serde_json::json!({
+                                "tool": function_response.name,
+                                "author": event.author,
+                                "duration_ms": duration_ms
+                            }),
+                            &function_response.response,
+                        ),
                     );
                 }
+                completions.push(completion);
             }
             _ => {}
         }
     }
+    completions
+}
+
+pub fn emit_tool_progress(event: &Event, progress: &ProgressEmitter) {
+    if !progress.enabled() {
+        return;
+    }
+    let Some(content) = event.content() else {
+        return;
+    };
+    for part in &content.parts {
+        if let Part::FunctionCall { name, .. } = part {
+            progress.emit(
+                "tool_call",
+                serde_json::json!({
+                    "tool": name,
+                    "author": event.author
+                }),
+            );
+        }
+    }
 }
 
 pub async fn run_prompt(
@@ -253,6 +691,61 @@ pub async fn run_prompt(
     prompt: &str,
     telemetry: &TelemetrySink,
 ) -> Result<String> {
+    run_prompt_with_progress(runner, cfg, prompt, telemetry, &ProgressEmitter::disabled()).await
+}
+
+pub async fn run_prompt_with_progress(
+    runner: &Runner,
+    cfg: &RuntimeConfig,
+    prompt: &str,
+    telemetry: &TelemetrySink,
+    progress: &ProgressEmitter,
+) -> Result<String> {
+    let mut tracker = AuthorTextTracker::default();
+    run_prompt_tracked(runner, cfg, prompt, telemetry, progress, &mut tracker).await
+}
+
+/// Like [`run_prompt_with_progress`], but text accumulates in a caller-owned
+/// tracker so it can still be read if the future is dropped mid-run (e.g. on
+/// a deadline).
+pub async fn run_prompt_tracked(
+    runner: &Runner,
+    cfg: &RuntimeConfig,
+    prompt: &str,
+    telemetry: &TelemetrySink,
+    progress: &ProgressEmitter,
+    tracker: &mut AuthorTextTracker,
+) -> Result<String> {
+    run_prompt_observed(
+        runner,
+        cfg,
+        prompt,
+        telemetry,
+        progress,
+        tracker,
+        &mut |_| {},
+    )
+    .await
+}
+
+/// Like [`run_prompt_tracked`], but hands every non-user event to
+/// `observer` as it arrives.
+pub async fn run_prompt_observed(
+    runner: &Runner,
+    cfg: &RuntimeConfig,
+    prompt: &str,
+    telemetry: &TelemetrySink,
+    progress: &ProgressEmitter,
+    tracker: &mut AuthorTextTracker,
+    observer: &mut (dyn FnMut(&Event) + Send),
+) -> Result<String> {
+    progress.emit(
+        "generation_started",
+        serde_json::json!({ "prompt_chars": prompt.chars().count() }),
+    );
+    check_usage_budget(cfg, estimate_tokens(prompt.chars().count()) as u64)?;
+    cfg.tool_call_counter.rearm();
+    tracker.start_at(Instant::now());
     let mut stream = runner
         .run_str(
             &cfg.user_id,
@@ -262,7 +755,8 @@ pub async fn run_prompt(
         .await
         .context("failed to start runner stream")?;
 
-    let mut tracker = AuthorTextTracker::default();
+    let mut meter = UsageMeter::new(prompt);
+    let mut timing = ToolTimingTracker::default();
 
     while let Some(event_result) = stream.next().await {
         let event = match event_result {
@@ -286,7 +780,11 @@ pub async fn run_prompt(
             continue;
         }
 
-        emit_tool_lifecycle_events(&event, telemetry);
+        meter.observe(&event, &text);
+        emit_tool_lifecycle_events(&event, telemetry, &mut timing);
+        emit_tool_progress(&event, progress);
+        tracker.observe_tool_responses(&event);
+        observer(&event);
 
         let _ = tracker.ingest_parts(
             &event.author,
@@ -296,9 +794,14 @@ pub async fn run_prompt(
         );
     }
 
-    Ok(tracker
-        .resolve_text()
-        .unwrap_or_else(|| NO_TEXTUAL_RESPONSE.to_string()))
+    record_model_usage(cfg, meter.tokens());
+    finish_stream_stats(tracker, cfg, telemetry, "buffered", meter.tokens());
+    let answer = resolve_answer(tracker, cfg, telemetry)?;
+    progress.emit(
+        "generation_done",
+        serde_json::json!({ "answer_chars": answer.chars().count() }),
+    );
+    Ok(answer)
 }
 
 pub async fn run_prompt_with_retrieval(
@@ -308,13 +811,81 @@ pub async fn run_prompt_with_retrieval(
     retrieval: &dyn RetrievalService,
     telemetry: &TelemetrySink,
 ) -> Result<String> {
-    let policy = RetrievalPolicy {
-        max_chunks: cfg.retrieval_max_chunks,
-        max_chars: cfg.retrieval_max_chars,
-        min_score: cfg.retrieval_min_score,
-    };
-    let enriched = augment_prompt_with_retrieval(retrieval, prompt, policy)?;
-    run_prompt(runner, cfg, &enriched, telemetry).await
+    run_prompt_with_retrieval_and_progress(
+        runner,
+        cfg,
+        prompt,
+        retrieval,
+        telemetry,
+        &ProgressEmitter::disabled(),
+    )
+    .await
+}
+
+pub async fn run_prompt_with_retrieval_and_progress(
+    runner: &Runner,
+    cfg: &RuntimeConfig,
+    prompt: &str,
+    retrieval: &dyn RetrievalService,
+    telemetry: &TelemetrySink,
+    progress: &ProgressEmitter,
+) -> Result<String> {
+    run_prompt_with_retrieval_observed(
+        runner,
+        cfg,
+        prompt,
+        retrieval,
+        telemetry,
+        progress,
+        &mut |_| {},
+    )
+    .await
+}
+
+pub async fn run_prompt_with_retrieval_observed(
+    runner: &Runner,
+    cfg: &RuntimeConfig,
+    prompt: &str,
+    retrieval: &dyn RetrievalService,
+    telemetry: &TelemetrySink,
+    progress: &ProgressEmitter,
+    observer: &mut (dyn FnMut(&Event) + Send),
+) -> Result<String> {
+    let (enriched, _) = prepare_prompt_with_retrieval(cfg, retrieval, prompt, telemetry)?;
+    progress.emit(
+        "retrieval",
+        serde_json::json!({
+            "backend": retrieval.backend_name(),
+            "added_chars": enriched.len().saturating_sub(prompt.len())
+        }),
+    );
+    let mut tracker = AuthorTextTracker::default();
+    run_prompt_observed(
+        runner,
+        cfg,
+        &enriched,
+        telemetry,
+        progress,
+        &mut tracker,
+        observer,
+    )
+    .await
+}
+
+/// The answer for a turn cancelled with Ctrl-C: whatever text arrived so
+/// far, with the tokens already spent still counted toward the usage budget.
+pub fn cancelled_stream_answer(
+    cfg: &RuntimeConfig,
+    tracker: &AuthorTextTracker,
+    meter: &UsageMeter,
+) -> String {
+    record_model_usage(cfg, meter.tokens());
+    let partial = tracker.resolve_text().unwrap_or_default();
+    if partial.is_empty() {
+        "(cancelled)".to_string()
+    } else {
+        partial
+    }
 }
 
 pub async fn run_prompt_streaming(
@@ -323,6 +894,10 @@ pub async fn run_prompt_streaming(
     prompt: &str,
     telemetry: &TelemetrySink,
 ) -> Result<String> {
+    check_usage_budget(cfg, estimate_tokens(prompt.chars().count()) as u64)?;
+    cfg.tool_call_counter.rearm();
+    let mut tracker = AuthorTextTracker::default();
+    tracker.start_at(Instant::now());
     let mut stream = runner
         .run_str(
             &cfg.user_id,
@@ -332,7 +907,7 @@ pub async fn run_prompt_streaming(
         .await
         .context("failed to start runner stream")?;
 
-    let mut tracker = AuthorTextTracker::default();
+    let mut meter = UsageMeter::new(prompt);
     let mut emitted_text_by_author: HashMap<String, String> = HashMap::new();
     let mut printed_any_output = false;
     let mut spinner = Some(Spinner::start("Thinking..."));
@@ -341,22 +916,50 @@ pub async fn run_prompt_streaming(
 
     // Winnow streaming markdown state
     let mut md_buf = String::new();
+    let mut output_sanitizer = StreamSanitizer::new(cfg.output_sanitize);
     let mut md_offset: usize = 0;
     let mut md_state = ParseState::new();
     let mut stdout = io::stdout();
 
-    while let Some(event_result) = tokio::select! {
-        event = stream.next() => event,
-        _ = tokio::signal::ctrl_c() => {
-            drop(spinner);
-            eprintln!("\n{}  ⏹ Cancelled{}", crate::theme::DIM, crate::theme::RESET);
-            println!();
-            let partial = tracker
-                .resolve_text()
-                .unwrap_or_default();
-            return Ok(if partial.is_empty() { "(cancelled)".to_string() } else { partial });
-        }
-    } {
+    // Slow-tool notices
+    let slow_tool_threshold =
+        (cfg.slow_tool_warn_secs > 0).then(|| Duration::from_secs(cfg.slow_tool_warn_secs));
+    let mut timing = ToolTimingTracker::default();
+    let annotation_target = tool_annotation_target(cfg.chat_tool_annotations, stdout.is_terminal());
+
+    loop {
+        let event_result = tokio::select! {
+            event = stream.next() => match event {
+                Some(event) => event,
+                None => break,
+            },
+            _ = tokio::signal::ctrl_c() => {
+                drop(spinner);
+                // Running commands sit in their own process groups.
+                crate::process_group::sweep_process_groups(
+                    crate::process_group::PROCESS_GROUP_KILL_GRACE,
+                );
+                eprintln!("\n{}  ⏹ Cancelled{}", crate::theme::DIM, crate::theme::RESET);
+                println!();
+                return Ok(cancelled_stream_answer(cfg, &tracker, &meter));
+            }
+            _ = tokio::time::sleep(SLOW_TOOL_POLL_INTERVAL), if slow_tool_threshold.is_some() && timing.has_pending() => {
+                let threshold = slow_tool_threshold.unwrap_or_default();
+                for (name, elapsed) in timing.newly_slow(threshold, Instant::now()) {
+                    if let Some(s) = spinner.take() {
+                        s.stop();
+                    }
+                    eprintln!(
+                        "{}  ⏳ {name} still running after {}s...{}",
+                        crate::theme::DIM,
+                        elapsed.as_secs(),
+                        crate::theme::RESET
+                    );
+                    spinner = Some(Spinner::start("Running..."));
+                }
+                continue;
+            }
+        };
         let event = match event_result {
             Ok(e) => e,
             Err(e) => {
@@ -372,7 +975,66 @@ pub async fn run_prompt_streaming(
             continue;
         }
 
-        emit_tool_lifecycle_events(&event, telemetry);
+        meter.observe(&event, &text);
+        let completions = emit_tool_lifecycle_events(&event, telemetry, &mut timing);
+        tracker.observe_tool_responses(&event);
+        // The done annotation already carries the duration
+        if let Some(threshold) = slow_tool_threshold.filter(|_| annotation_target.is_none()) {
+            for completion in &completions {
+                let Some(duration) = completion.duration.filter(|d| *d >= threshold) else {
+                    continue;
+                };
+                if let Some(s) = spinner.take() {
+                    s.stop();
+                }
+                eprintln!(
+                    "{}  {} {} {} {:.1}s{}",
+                    crate::theme::DIM,
+                    if completion.failed { "✗" } else { "✓" },
+                    completion.name,
+                    if completion.failed {
+                        "failed after"
+                    } else {
+                        "finished in"
+                    },
+                    duration.as_secs_f64(),
+                    crate::theme::RESET
+                );
+                spinner = Some(Spinner::start("Working..."));
+            }
+        }
+        if annotation_target.is_some() && !completions.is_empty() {
+            let failures = event
+                .content()
+                .map(|content| {
+                    content
+                        .parts
+                        .iter()
+                        .filter_map(|part| match part {
+                            Part::FunctionResponse {
+                                function_response, ..
+                            } => Some(extract_tool_failure_message(&function_response.response)),
+                            _ => None,
+                        })
+                        .collect::<Vec<Option<String>>>()
+                })
+                .unwrap_or_default();
+            if let Some(s) = spinner.take() {
+                s.stop();
+            }
+            for (completion, failure) in completions.iter().zip(failures) {
+                if completion.name == "transfer_to_agent" {
+                    continue;
+                }
+                let line = format_tool_done_annotation(
+                    &completion.name,
+                    completion.duration,
+                    failure.as_deref(),
+                );
+                write_tool_annotation(annotation_target, &line, &mut stdout, &mut io::stderr());
+            }
+            spinner = Some(Spinner::start("Working..."));
+        }
 
         // Track token count on spinner (~4 chars per token estimate)
         if !text.is_empty() {
@@ -402,13 +1064,18 @@ pub async fn run_prompt_streaming(
         if let Some(content) = event.content() {
             for part in &content.parts {
                 if let Part::FunctionCall { name, args, .. } = part {
-                    if name != "transfer_to_agent" {
+                    if name != "transfer_to_agent" && annotation_target.is_some() {
                         if let Some(s) = spinner.take() {
                             s.stop();
                         }
 
-                        // Show tool name
-                        eprintln!("{}  ⚡ {}{}", crate::theme::DIM, name, crate::theme::RESET);
+                        // Show tool name and args
+                        write_tool_annotation(
+                            annotation_target,
+                            &format_tool_call_annotation(name, args),
+                            &mut stdout,
+                            &mut io::stderr(),
+                        );
 
                         // Show what the tool is doing
                         if let Some(args_obj) = args.as_object() {
@@ -473,11 +1140,11 @@ pub async fn run_prompt_streaming(
                             };
 
                             if let Some(msg) = summary {
-                                eprintln!(
-                                    "{}     {}{}",
-                                    crate::theme::DIM,
-                                    msg,
-                                    crate::theme::RESET
+                                write_tool_annotation(
+                                    annotation_target,
+                                    &format!("   {msg}"),
+                                    &mut stdout,
+                                    &mut io::stderr(),
                                 );
                             }
                         }
@@ -487,6 +1154,32 @@ pub async fn run_prompt_streaming(
                 }
             }
         }
+        // Show tool results per `chat_show_tool_results`
+        if let Some(content) = event.content() {
+            for part in &content.parts {
+                let Part::FunctionResponse {
+                    function_response, ..
+                } = part
+                else {
+                    continue;
+                };
+                let Some(rendered) = render_tool_result(
+                    &function_response.name,
+                    &function_response.response,
+                    cfg.chat_show_tool_results,
+                    cfg.chat_tool_result_max_chars,
+                ) else {
+                    continue;
+                };
+                if let Some(s) = spinner.take() {
+                    s.stop();
+                }
+                for line in rendered.lines() {
+                    eprintln!("{}  ↳ {}{}", crate::theme::DIM, line, crate::theme::RESET);
+                }
+                spinner = Some(Spinner::start("Working..."));
+            }
+        }
 
         let delta = tracker.ingest_parts(
             &event.author,
@@ -499,7 +1192,7 @@ pub async fn run_prompt_streaming(
                 s.stop();
             }
 
-            md_buf.push_str(&delta);
+            md_buf.push_str(&output_sanitizer.push(&delta));
 
             // Parse as much as possible from the buffer
             loop {
@@ -528,6 +1221,7 @@ pub async fn run_prompt_streaming(
     drop(spinner);
 
     // Flush remaining buffer: append newline to force parser to complete (Q CLI hack)
+    md_buf.push_str(&output_sanitizer.finish());
     md_buf.push('\n');
     loop {
         let input = winnow::Partial::new(&md_buf[md_offset..]);
@@ -541,6 +1235,8 @@ pub async fn run_prompt_streaming(
             _ => break,
         }
     }
+    record_model_usage(cfg, meter.tokens());
+    finish_stream_stats(&mut tracker, cfg, telemetry, "streaming", meter.tokens());
 
     if printed_any_output {
         if let (Some(final_text), Some(final_author)) = (
@@ -553,22 +1249,18 @@ pub async fn run_prompt_streaming(
                 .unwrap_or_default();
 
             if let Some(suffix) = final_stream_suffix(emitted, final_text) {
-                print!("{suffix}");
+                print!("{}", sanitize_output(cfg, &suffix));
                 io::stdout().flush().context("failed to flush stdout")?;
             }
         }
 
         println!();
-        return Ok(tracker
-            .resolve_text()
-            .unwrap_or_else(|| NO_TEXTUAL_RESPONSE.to_string()));
+        return resolve_answer(&tracker, cfg, telemetry);
     }
 
-    let fallback = tracker
-        .resolve_text()
-        .unwrap_or_else(|| NO_TEXTUAL_RESPONSE.to_string());
+    let fallback = resolve_answer(&tracker, cfg, telemetry)?;
 
-    println!("{fallback}");
+    println!("{}", sanitize_output(cfg, &fallback));
     Ok(fallback)
 }
 
@@ -579,11 +1271,65 @@ pub async fn run_prompt_streaming_with_retrieval(
     retrieval: &dyn RetrievalService,
     telemetry: &TelemetrySink,
 ) -> Result<String> {
-    let policy = RetrievalPolicy {
-        max_chunks: cfg.retrieval_max_chunks,
-        max_chars: cfg.retrieval_max_chars,
-        min_score: cfg.retrieval_min_score,
-    };
-    let enriched = augment_prompt_with_retrieval(retrieval, prompt, policy)?;
+    let (enriched, _) = prepare_prompt_with_retrieval(cfg, retrieval, prompt, telemetry)?;
     run_prompt_streaming(runner, cfg, &enriched, telemetry).await
 }
+
+/// User content for `prompt` with remembered facts and retrieval context,
+/// built by the same section assembly as `debug render-prompt`, with
+/// retrieved chunks run through `guardrail_retrieval_mode`. Emits `prompt.context_budget`
+/// and, with `--show-context-budget`, prints the breakdown to stderr.
+pub fn prepare_prompt_with_retrieval(
+    cfg: &RuntimeConfig,
+    retrieval: &dyn RetrievalService,
+    prompt: &str,
+    telemetry: &TelemetrySink,
+) -> Result<(String, ContextBudget)> {
+    let policy = retrieval_policy(cfg);
+    print_selected_retrieval_chunks(cfg, retrieval, prompt, policy);
+    let user_sections = guard_retrieval_sections(
+        cfg,
+        telemetry,
+        with_memory_section(
+            cfg,
+            telemetry,
+            prompt,
+            screened_user_content_sections(cfg, telemetry, retrieval, prompt, "prompt")?,
+        ),
+    )?;
+    let sources = user_sections
+        .iter()
+        .flat_map(|section| section.chunk_sources.iter().cloned())
+        .collect::<Vec<String>>();
+    if !sources.is_empty() {
+        telemetry.emit(
+            "retrieval.sources",
+            serde_json::json!({ "backend": retrieval.backend_name(), "sources": sources }),
+        );
+    }
+    let system_sections = system_instruction_sections(Some(cfg), &PromptEnvironment::current());
+    let budget = context_budget(&system_sections, &user_sections);
+    telemetry.emit("prompt.context_budget", budget.to_value());
+    if cfg.show_context_budget {
+        eprint!("{}", budget.render_table());
+    }
+    Ok((render_user_content(&user_sections), budget))
+}
+
+/// At `-vv`, list the chunks retrieval will add to `prompt`. Selection
+/// errors are left for the real augmentation to report.
+fn print_selected_retrieval_chunks(
+    cfg: &RuntimeConfig,
+    retrieval: &dyn RetrievalService,
+    prompt: &str,
+    policy: RetrievalPolicy,
+) {
+    if !cfg.verbosity.enabled(Verbosity::Debug) {
+        return;
+    }
+    if let Ok(chunks) = select_retrieval_chunks(retrieval, prompt, policy) {
+        cfg.verbosity.detail(Verbosity::Debug, || {
+            describe_selected_chunks(retrieval.backend_name(), &chunks)
+        });
+    }
+}
@@ -1,14 +1,30 @@
-use std::collections::{BTreeSet, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fs::OpenOptions;
-use std::io::{self, BufRead, Write};
-use std::path::PathBuf;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
 use std::sync::Arc;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use anyhow::{Context, Result};
 use serde_json::{Value, json};
 
+use crate::chat_stats::SharedChatStats;
+use crate::cli::{OutputSanitize, TelemetryExportFormat, TelemetryRedaction};
 use crate::config::RuntimeConfig;
+use crate::error::redact_sensitive_text;
+use crate::guardrail::{GuardrailMatcher, redact_text_with_matcher};
+use crate::provenance::ProvenanceRecorder;
+use crate::sanitize::sanitize_text;
+use crate::telemetry_export::{TELEMETRY_EXPORT_SHUTDOWN_GRACE, TelemetryExporter};
+use crate::tool_output::truncate_at_char_boundary;
+
+/// Fields scrubbed when `telemetry_redact_fields` is not set. Each entry also
+/// covers `*_<field>` keys, e.g. `tool_error`.
+pub const DEFAULT_TELEMETRY_REDACT_FIELDS: &[&str] = &["error", "prompt", "text"];
+
+/// Longest string any written telemetry field may hold, redaction or not.
+pub const TELEMETRY_MAX_FIELD_CHARS: usize = 2000;
 
 pub fn unix_ms_now() -> u128 {
     SystemTime::now()
@@ -17,6 +33,98 @@ pub fn unix_ms_now() -> u128 {
         .as_millis()
 }
 
+fn is_error_field(key: &str) -> bool {
+    field_matches(key, "error")
+}
+
+fn field_matches(key: &str, field: &str) -> bool {
+    key == field
+        || key
+            .strip_suffix(field)
+            .is_some_and(|prefix| prefix.ends_with('_'))
+}
+
+/// Scrubs configured payload fields before they reach the JSONL file:
+/// guardrail terms and PII through the guardrail redactor, then sqlite URLs.
+/// Providers sometimes echo the offending input back in error messages, so
+/// these fields can carry prompt fragments.
+#[derive(Debug, Clone)]
+pub struct TelemetryRedactor {
+    pub fields: Vec<String>,
+    pub matcher: Arc<GuardrailMatcher>,
+    pub replacement: String,
+}
+
+impl TelemetryRedactor {
+    /// `None` only for `telemetry_redaction = "off"` together with
+    /// `--show-sensitive-config`; either one alone keeps redaction on.
+    pub fn for_config(cfg: &RuntimeConfig) -> Option<Self> {
+        if cfg.telemetry_redaction == TelemetryRedaction::Off && cfg.show_sensitive_config {
+            return None;
+        }
+        Some(Self {
+            fields: cfg.telemetry_redact_fields.clone(),
+            matcher: cfg.guardrail_matcher(),
+            replacement: cfg.guardrail_redact_replacement.clone(),
+        })
+    }
+
+    pub fn covers(&self, key: &str) -> bool {
+        self.fields.iter().any(|field| field_matches(key, field))
+    }
+
+    pub fn redact(&self, text: &str) -> String {
+        redact_sensitive_text(&redact_text_with_matcher(
+            text,
+            &self.matcher,
+            &self.replacement,
+        ))
+    }
+}
+
+/// Cut `text` to [`TELEMETRY_MAX_FIELD_CHARS`], noting how much was dropped.
+pub fn cap_telemetry_string(text: &str) -> String {
+    let (kept, dropped) = truncate_at_char_boundary(text, TELEMETRY_MAX_FIELD_CHARS);
+    if dropped == 0 {
+        text.to_string()
+    } else {
+        format!("{kept}…[truncated {dropped} bytes]")
+    }
+}
+
+/// One payload value as written: strings under a redacted key (at any depth)
+/// are scrubbed, and every string is capped.
+pub fn scrub_telemetry_value(
+    value: &Value,
+    redact: bool,
+    redactor: Option<&TelemetryRedactor>,
+) -> Value {
+    match value {
+        Value::String(text) => {
+            let text = match redactor {
+                Some(redactor) if redact => redactor.redact(text),
+                _ => text.clone(),
+            };
+            Value::String(cap_telemetry_string(&text))
+        }
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .map(|item| scrub_telemetry_value(item, redact, redactor))
+                .collect(),
+        ),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, item)| {
+                    let redact = redact || redactor.is_some_and(|r| r.covers(key));
+                    (key.clone(), scrub_telemetry_value(item, redact, redactor))
+                })
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct TelemetrySink {
     pub enabled: bool,
@@ -25,6 +133,19 @@ pub struct TelemetrySink {
     pub command: String,
     pub session_id: String,
     pub file_lock: Arc<std::sync::Mutex<()>>,
+    /// Applied to `error` strings, which often quote model or tool output.
+    pub output_sanitize: OutputSanitize,
+    /// Chat `/stats` counters; fed every event, even when disabled.
+    pub chat_stats: Option<SharedChatStats>,
+    /// Shared by CLI and server events; `None` only when explicitly off.
+    pub redactor: Option<TelemetryRedactor>,
+    /// Model, retrieval sources and tools seen this run; fed every event,
+    /// even when disabled.
+    pub provenance: ProvenanceRecorder,
+    /// `telemetry_export`: every written event is also queued here.
+    pub exporter: Option<TelemetryExporter>,
+    /// Tags every event with `ephemeral: true` (`--ephemeral` runs).
+    pub ephemeral: bool,
 }
 
 impl TelemetrySink {
@@ -37,10 +158,40 @@ impl TelemetrySink {
             command,
             session_id: cfg.session_id.clone(),
             file_lock: Arc::new(std::sync::Mutex::new(())),
+            output_sanitize: cfg.output_sanitize,
+            chat_stats: None,
+            redactor: TelemetryRedactor::for_config(cfg),
+            provenance: ProvenanceRecorder::default(),
+            exporter: None,
+            ephemeral: cfg.ephemeral.is_some(),
+        }
+    }
+
+    pub fn with_chat_stats(mut self, stats: SharedChatStats) -> Self {
+        self.chat_stats = Some(stats);
+        self
+    }
+
+    pub fn with_exporter(mut self, exporter: Option<TelemetryExporter>) -> Self {
+        self.exporter = exporter;
+        self
+    }
+
+    /// Give queued export records a bounded chance to go out before exit.
+    pub async fn flush_export(&self) {
+        if let Some(exporter) = &self.exporter {
+            exporter.flush(TELEMETRY_EXPORT_SHUTDOWN_GRACE).await;
         }
     }
 
     pub fn emit(&self, event: &str, payload: Value) {
+        if let Some(stats) = &self.chat_stats {
+            stats
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .record(event, &payload);
+        }
+        self.provenance.record(event, &payload);
         if !self.enabled {
             return;
         }
@@ -51,14 +202,33 @@ impl TelemetrySink {
         record.insert("run_id".to_string(), json!(self.run_id));
         record.insert("command".to_string(), json!(self.command));
         record.insert("session_id".to_string(), json!(self.session_id));
+        if self.ephemeral {
+            record.insert("ephemeral".to_string(), json!(true));
+        }
 
         if let Some(map) = payload.as_object() {
             for (key, value) in map {
-                record.insert(key.clone(), value.clone());
+                let value = match value {
+                    Value::String(text) if is_error_field(key) => {
+                        Value::String(sanitize_text(text, self.output_sanitize))
+                    }
+                    other => other.clone(),
+                };
+                let redact = self
+                    .redactor
+                    .as_ref()
+                    .is_some_and(|redactor| redactor.covers(key));
+                record.insert(
+                    key.clone(),
+                    scrub_telemetry_value(&value, redact, self.redactor.as_ref()),
+                );
             }
         }
 
         let value = Value::Object(record);
+        if let Some(exporter) = &self.exporter {
+            exporter.export(&value);
+        }
         if let Err(err) = self.append_event_line(&value) {
             tracing::warn!(
                 event = event,
@@ -81,19 +251,398 @@ impl TelemetrySink {
             })?;
         }
 
-        let _guard = self.file_lock.lock().unwrap_or_else(|e| e.into_inner());
+        let mut line = serde_json::to_vec(value).with_context(|| {
+            format!("failed to serialize telemetry event for '{}'", self.command)
+        })?;
+        line.push(b'\n');
 
+        // Each event is one append-mode write, so lines from concurrent
+        // processes never interleave and emit never waits on the advisory
+        // file lock; only maintenance rotation takes that.
+        let _guard = self.file_lock.lock().unwrap_or_else(|e| e.into_inner());
         let mut file = OpenOptions::new()
             .create(true)
             .append(true)
             .open(&self.path)
             .with_context(|| format!("failed to open telemetry path '{}'", self.path.display()))?;
+        file.write_all(&line)
+            .context("failed to write telemetry event")
+    }
+}
 
-        serde_json::to_writer(&mut file, value).with_context(|| {
-            format!("failed to serialize telemetry event for '{}'", self.command)
-        })?;
-        writeln!(file).context("failed to write telemetry newline")
+// ---------------------------------------------------------------------------
+// Environment capture for `command.started`
+// ---------------------------------------------------------------------------
+
+/// Every key the environment block may contain. Nothing outside this list is
+/// recorded, and no environment variable is copied verbatim except the
+/// locale variables read by [`detect_locale`].
+pub const TELEMETRY_ENVIRONMENT_KEYS: &[&str] = &[
+    "zavora_version",
+    "os",
+    "arch",
+    "stdout_tty",
+    "git_branch",
+    "git_dirty",
+    "features",
+    "locale",
+];
+
+const GIT_PROBE_TIMEOUT: Duration = Duration::from_millis(300);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TelemetryEnvironment {
+    pub zavora_version: String,
+    pub os: String,
+    pub arch: String,
+    pub stdout_tty: bool,
+    pub git_branch: Option<String>,
+    pub git_dirty: Option<bool>,
+    pub features: Vec<String>,
+    pub locale: Option<String>,
+}
+
+impl TelemetryEnvironment {
+    pub fn capture(workspace: &Path) -> Self {
+        let git = git_workspace_state(workspace, GIT_PROBE_TIMEOUT);
+        Self {
+            zavora_version: env!("CARGO_PKG_VERSION").to_string(),
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            stdout_tty: io::stdout().is_terminal(),
+            git_branch: git.as_ref().map(|(branch, _)| branch.clone()),
+            git_dirty: git.map(|(_, dirty)| dirty),
+            features: enabled_features(),
+            locale: detect_locale(|key| std::env::var(key).ok()),
+        }
+    }
+
+    pub fn to_value(&self) -> Value {
+        json!({
+            "zavora_version": self.zavora_version,
+            "os": self.os,
+            "arch": self.arch,
+            "stdout_tty": self.stdout_tty,
+            "git_branch": self.git_branch,
+            "git_dirty": self.git_dirty,
+            "features": self.features,
+            "locale": self.locale
+        })
+    }
+}
+
+/// Cargo features compiled into this binary.
+pub fn enabled_features() -> Vec<String> {
+    [
+        ("semantic-search", cfg!(feature = "semantic-search")),
+        ("checkpoints", cfg!(feature = "checkpoints")),
+        ("web-fetch", cfg!(feature = "web-fetch")),
+        ("oauth", cfg!(feature = "oauth")),
+        ("browser", cfg!(feature = "browser")),
+        ("sandbox", cfg!(feature = "sandbox")),
+        ("rag", cfg!(feature = "rag")),
+    ]
+    .into_iter()
+    .filter(|(_, enabled)| *enabled)
+    .map(|(name, _)| name.to_string())
+    .collect()
+}
+
+/// Locale from `LC_ALL`, `LC_MESSAGES` or `LANG`, in POSIX precedence order.
+pub fn detect_locale(lookup: impl Fn(&str) -> Option<String>) -> Option<String> {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .into_iter()
+        .filter_map(lookup)
+        .map(|value| value.trim().to_string())
+        .find(|value| !value.is_empty())
+}
+
+/// Run `git` in `workspace`, killing it after `timeout`. `None` on any failure.
+fn git_output_with_timeout(workspace: &Path, args: &[&str], timeout: Duration) -> Option<String> {
+    let mut child = Command::new("git")
+        .args(args)
+        .current_dir(workspace)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    let deadline = Instant::now() + timeout;
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) if status.success() => break,
+            Ok(Some(_)) | Err(_) => return None,
+            Ok(None) if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return None;
+            }
+            Ok(None) => std::thread::sleep(Duration::from_millis(5)),
+        }
+    }
+    let output = child.wait_with_output().ok()?;
+    Some(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Current branch and dirty state, or `None` outside a git work tree.
+pub fn git_workspace_state(workspace: &Path, timeout: Duration) -> Option<(String, bool)> {
+    let branch =
+        git_output_with_timeout(workspace, &["rev-parse", "--abbrev-ref", "HEAD"], timeout)?;
+    let status = git_output_with_timeout(
+        workspace,
+        &["status", "--porcelain", "--untracked-files=no"],
+        timeout,
+    )?;
+    Some((branch.trim().to_string(), !status.trim().is_empty()))
+}
+
+/// The `environment` block for `command.started`, or `None` when
+/// `telemetry_capture_environment` is off.
+pub fn telemetry_environment_block(
+    cfg: &RuntimeConfig,
+    capture: impl FnOnce() -> TelemetryEnvironment,
+) -> Option<Value> {
+    cfg.telemetry_capture_environment
+        .then(|| capture().to_value())
+}
+
+/// Placeholder rules for [`normalize_error_message`], tried in order on each
+/// token (and on each quoted value as a whole).
+const ERROR_NORMALIZATION_RULES: &[(&str, fn(&str) -> bool)] = &[
+    ("<url>", is_url_token),
+    ("<path>", is_path_token),
+    ("<uuid>", is_uuid_token),
+    ("<hex>", is_hex_token),
+    ("<n>", is_number_token),
+    ("<id>", is_id_token),
+];
+
+/// Characters that end a token without being part of it.
+const ERROR_TOKEN_DELIMITERS: &[char] = &['(', ')', '[', ']', '{', '}', ',', ';', '='];
+const ERROR_QUOTES: &[char] = &['\'', '"', '`'];
+
+fn is_url_token(token: &str) -> bool {
+    ["http://", "https://", "sqlite:", "file://"]
+        .iter()
+        .any(|scheme| token.starts_with(scheme))
+}
+
+fn is_path_token(token: &str) -> bool {
+    token.len() > 1 && (token.contains('/') || token.contains('\\') || token.starts_with('~'))
+}
+
+fn is_uuid_token(token: &str) -> bool {
+    let groups = token.split('-').map(str::len).collect::<Vec<usize>>();
+    groups == [8, 4, 4, 4, 12] && token.chars().all(|ch| ch == '-' || ch.is_ascii_hexdigit())
+}
+
+fn is_hex_token(token: &str) -> bool {
+    token.len() >= 8
+        && token.chars().all(|ch| ch.is_ascii_hexdigit())
+        && token.chars().any(|ch| ch.is_ascii_digit())
+}
+
+/// Numbers, optionally with a short unit: `42`, `-1`, `0.75`, `30s`, `150ms`.
+fn is_number_token(token: &str) -> bool {
+    let digits = token.trim_end_matches(|ch: char| ch.is_ascii_alphabetic());
+    token.len() - digits.len() <= 3
+        && digits.chars().any(|ch| ch.is_ascii_digit())
+        && digits
+            .chars()
+            .all(|ch| ch.is_ascii_digit() || matches!(ch, '.' | '-' | '_' | '+'))
+}
+
+/// Generated names such as `session-20240105-1`, `run_7f3a` or `ask2`.
+fn is_id_token(token: &str) -> bool {
+    token.chars().any(|ch| ch.is_ascii_digit())
+        && token.chars().any(|ch| ch.is_ascii_alphabetic())
+        && token
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_' | '.' | ':'))
+}
+
+fn error_token_placeholder(token: &str) -> Option<&'static str> {
+    ERROR_NORMALIZATION_RULES
+        .iter()
+        .find(|(_, matches)| matches(token))
+        .map(|(placeholder, _)| *placeholder)
+}
+
+fn push_normalized_token(out: &mut String, token: &str) {
+    // Sentence punctuation stays outside the value.
+    let core = token.trim_end_matches(['.', ':', '!', '?']);
+    out.push_str(error_token_placeholder(core).unwrap_or(core));
+    out.push_str(&token[core.len()..]);
+}
+
+/// Error message with the parts that vary between occurrences (paths, URLs,
+/// ids, numbers, quoted values) replaced by placeholders, and whitespace
+/// collapsed, so equal causes share one template.
+pub fn normalize_error_message(message: &str) -> String {
+    let chars = message.chars().collect::<Vec<char>>();
+    let mut out = String::with_capacity(message.len());
+    let mut token = String::new();
+    let mut idx = 0;
+    while idx < chars.len() {
+        let ch = chars[idx];
+        // A quote opens a value only at a token start, so apostrophes in
+        // words like "doesn't" are left alone.
+        if ERROR_QUOTES.contains(&ch)
+            && token.is_empty()
+            && let Some(len) = chars[idx + 1..].iter().position(|next| *next == ch)
+        {
+            let value = chars[idx + 1..idx + 1 + len].iter().collect::<String>();
+            out.push(ch);
+            out.push_str(error_token_placeholder(&value).unwrap_or("<value>"));
+            out.push(ch);
+            idx += len + 2;
+            continue;
+        }
+        if ch.is_whitespace() {
+            push_normalized_token(&mut out, &token);
+            token.clear();
+            if !out.is_empty() && !out.ends_with(' ') {
+                out.push(' ');
+            }
+        } else if ERROR_TOKEN_DELIMITERS.contains(&ch) {
+            push_normalized_token(&mut out, &token);
+            token.clear();
+            out.push(ch);
+        } else {
+            token.push(ch);
+        }
+        idx += 1;
     }
+    push_normalized_token(&mut out, &token);
+    out.trim_end().to_string()
+}
+
+/// Stable short hash of the normalized template.
+pub fn error_fingerprint(template: &str) -> String {
+    crate::session_bundle::sha256_hex(template.as_bytes())[..16].to_string()
+}
+
+/// Adds `error_fingerprint` and `error_template` for the (already redacted)
+/// `error` message of a failure event payload.
+pub fn with_error_fingerprint(mut payload: Value, error: &str) -> Value {
+    let template = normalize_error_message(error);
+    payload["error_fingerprint"] = json!(error_fingerprint(&template));
+    payload["error_template"] = json!(template);
+    payload
+}
+
+/// Adds `mocked: true` to a tool event whose response came from
+/// `--mock-tools`.
+pub fn with_mocked_marker(mut payload: Value, response: &Value) -> Value {
+    if crate::tools::mock::is_mocked_response(response) {
+        payload["mocked"] = json!(true);
+    }
+    payload
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FailureFingerprint {
+    pub count: usize,
+    pub template: String,
+}
+
+/// Latency samples `telemetry report` keeps per command.
+pub const TELEMETRY_LATENCY_SAMPLE_CAP: usize = 1024;
+
+/// A latency distribution over a bounded reservoir (algorithm R). Count,
+/// sum and max are exact; percentiles come from the retained samples. The
+/// sampler is seeded, so a report over the same file is reproducible.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LatencyReservoir {
+    pub count: usize,
+    pub sum: f64,
+    pub max: f64,
+    samples: Vec<f64>,
+    cap: usize,
+    rng: u64,
+}
+
+impl Default for LatencyReservoir {
+    fn default() -> Self {
+        Self::new(TELEMETRY_LATENCY_SAMPLE_CAP)
+    }
+}
+
+impl LatencyReservoir {
+    pub fn new(cap: usize) -> Self {
+        Self {
+            count: 0,
+            sum: 0.0,
+            max: 0.0,
+            samples: Vec::new(),
+            cap: cap.max(1),
+            rng: 0x9e37_79b9_7f4a_7c15,
+        }
+    }
+
+    /// splitmix64; good enough to pick reservoir slots.
+    fn next_random(&mut self) -> u64 {
+        self.rng = self.rng.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.rng;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    pub fn record(&mut self, value: f64) {
+        self.count += 1;
+        self.sum += value;
+        self.max = self.max.max(value);
+        if self.samples.len() < self.cap {
+            self.samples.push(value);
+            return;
+        }
+        let slot = (self.next_random() % self.count as u64) as usize;
+        if slot < self.cap {
+            self.samples[slot] = value;
+        }
+    }
+
+    pub fn samples(&self) -> &[f64] {
+        &self.samples
+    }
+
+    pub fn average(&self) -> f64 {
+        if self.count == 0 {
+            0.0
+        } else {
+            self.sum / self.count as f64
+        }
+    }
+
+    pub fn percentile(&self, pct: f64) -> f64 {
+        let mut sorted = self.samples.clone();
+        sorted.sort_by(f64::total_cmp);
+        crate::eval::percentile(&sorted, pct)
+    }
+}
+
+/// `tool.requested`/`tool.succeeded`/`tool.failed` counts for one tool.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ToolReliability {
+    pub requested: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+}
+
+impl ToolReliability {
+    /// Share of finished calls that failed; `None` before any finished.
+    pub fn failure_ratio(&self) -> Option<f64> {
+        let finished = self.succeeded + self.failed;
+        (finished > 0).then(|| self.failed as f64 / finished as f64)
+    }
+}
+
+/// Guardrail events and matched terms for one direction and mode.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GuardrailActivity {
+    pub events: usize,
+    pub hits: usize,
 }
 
 #[derive(Debug, Default)]
@@ -109,6 +658,50 @@ pub struct TelemetrySummary {
     pub tool_succeeded: usize,
     pub tool_failed: usize,
     pub last_event_ts_unix_ms: Option<u128>,
+    /// `cli_version (git_sha)` from `command.started`, falling back to the
+    /// environment block's `zavora_version` for older runs, per run.
+    pub versions: BTreeMap<String, usize>,
+    /// `os/arch` from `command.started` environment blocks, per run.
+    pub platforms: BTreeMap<String, usize>,
+    /// `command.failed`/`tool.failed` events by error fingerprint. Events
+    /// logged before fingerprints existed are fingerprinted from `error`.
+    pub failure_fingerprints: BTreeMap<String, FailureFingerprint>,
+    /// `duration_ms` of `command.completed` per command, and
+    /// `server.ask.completed` latencies under `server.ask`.
+    pub command_latency: BTreeMap<String, LatencyReservoir>,
+    pub tool_reliability: BTreeMap<String, ToolReliability>,
+    /// Keyed by (direction, mode).
+    pub guardrail_activity: BTreeMap<(String, String), GuardrailActivity>,
+}
+
+impl TelemetrySummary {
+    /// Most frequent failure fingerprints first (ties by fingerprint).
+    pub fn top_failure_fingerprints(&self, limit: usize) -> Vec<(&String, &FailureFingerprint)> {
+        let mut entries = self.failure_fingerprints.iter().collect::<Vec<_>>();
+        entries.sort_by_key(|(fingerprint, entry)| (std::cmp::Reverse(entry.count), *fingerprint));
+        entries.truncate(limit);
+        entries
+    }
+
+    /// Commands with the most latency samples first.
+    pub fn latency_by_command(&self) -> Vec<(&String, &LatencyReservoir)> {
+        let mut entries = self.command_latency.iter().collect::<Vec<_>>();
+        entries.sort_by_key(|(command, latency)| (std::cmp::Reverse(latency.count), *command));
+        entries
+    }
+
+    /// Tools with the highest failure ratio first, then the most failures.
+    pub fn tools_by_failure_ratio(&self) -> Vec<(&String, &ToolReliability)> {
+        let mut entries = self.tool_reliability.iter().collect::<Vec<_>>();
+        entries.sort_by(|(left_name, left), (right_name, right)| {
+            let ratio = |tool: &ToolReliability| tool.failure_ratio().unwrap_or(0.0);
+            ratio(right)
+                .total_cmp(&ratio(left))
+                .then(right.failed.cmp(&left.failed))
+                .then(left_name.cmp(right_name))
+        });
+        entries
+    }
 }
 
 pub fn summarize_telemetry_lines(lines: Vec<String>, limit: usize) -> TelemetrySummary {
@@ -157,11 +750,62 @@ pub fn summarize_telemetry_lines(lines: Vec<String>, limit: usize) -> TelemetryS
             );
         }
 
-        match parsed
+        let event = parsed
             .get("event")
             .and_then(Value::as_str)
-            .unwrap_or_default()
+            .unwrap_or_default();
+        if event == "command.started" {
+            // `cli_version`/`git_sha` are always stamped; older runs only
+            // have the version inside the optional environment block.
+            let version = parsed
+                .get("cli_version")
+                .and_then(Value::as_str)
+                .or_else(|| {
+                    parsed
+                        .get("environment")
+                        .and_then(|environment| environment.get("zavora_version"))
+                        .and_then(Value::as_str)
+                });
+            if let Some(version) = version {
+                let build = match parsed.get("git_sha").and_then(Value::as_str) {
+                    Some(sha) if sha != "unknown" => format!("{version} ({sha})"),
+                    _ => version.to_string(),
+                };
+                *summary.versions.entry(build).or_insert(0) += 1;
+            }
+        }
+        if event == "command.started"
+            && let Some(environment) = parsed.get("environment")
         {
+            if let (Some(os), Some(arch)) = (
+                environment.get("os").and_then(Value::as_str),
+                environment.get("arch").and_then(Value::as_str),
+            ) {
+                *summary.platforms.entry(format!("{os}/{arch}")).or_insert(0) += 1;
+            }
+        }
+
+        if matches!(event, "command.failed" | "tool.failed")
+            && let Some(error) = parsed.get("error").and_then(Value::as_str)
+        {
+            let template = parsed
+                .get("error_template")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .unwrap_or_else(|| normalize_error_message(error));
+            let fingerprint = parsed
+                .get("error_fingerprint")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .unwrap_or_else(|| error_fingerprint(&template));
+            let entry = summary
+                .failure_fingerprints
+                .entry(fingerprint)
+                .or_insert_with(|| FailureFingerprint { count: 0, template });
+            entry.count += 1;
+        }
+
+        match event {
             "command.completed" => summary.command_completed += 1,
             "command.failed" => summary.command_failed += 1,
             "tool.requested" => summary.tool_requested += 1,
@@ -169,31 +813,221 @@ pub fn summarize_telemetry_lines(lines: Vec<String>, limit: usize) -> TelemetryS
             "tool.failed" => summary.tool_failed += 1,
             _ => {}
         }
+
+        let latency_command = match event {
+            "command.completed" => parsed.get("command").and_then(Value::as_str),
+            "server.ask.completed" => Some("server.ask"),
+            _ => None,
+        };
+        if let Some(command) = latency_command
+            && let Some(duration_ms) = parsed
+                .get("duration_ms")
+                .or_else(|| parsed.get("latency_ms"))
+                .and_then(Value::as_f64)
+        {
+            summary
+                .command_latency
+                .entry(command.to_string())
+                .or_default()
+                .record(duration_ms);
+        }
+
+        if matches!(event, "tool.requested" | "tool.succeeded" | "tool.failed")
+            && let Some(tool) = parsed.get("tool").and_then(Value::as_str)
+        {
+            let entry = summary
+                .tool_reliability
+                .entry(tool.to_string())
+                .or_default();
+            match event {
+                "tool.requested" => entry.requested += 1,
+                "tool.succeeded" => entry.succeeded += 1,
+                _ => entry.failed += 1,
+            }
+        }
+
+        if let Some(rest) = event.strip_prefix("guardrail.")
+            && let Some((direction, action)) = rest.split_once('.')
+        {
+            let direction = parsed
+                .get("direction")
+                .and_then(Value::as_str)
+                .unwrap_or(direction);
+            let mode = parsed.get("mode").and_then(Value::as_str).unwrap_or(action);
+            let entry = summary
+                .guardrail_activity
+                .entry((direction.to_string(), mode.to_string()))
+                .or_default();
+            entry.events += 1;
+            entry.hits += parsed
+                .get("hit_count")
+                .and_then(Value::as_u64)
+                .map(|count| count as usize)
+                .unwrap_or(1);
+        }
     }
 
     summary
 }
 
+pub fn read_telemetry_lines(path: &Path) -> Result<Vec<String>> {
+    let file = std::fs::File::open(path)
+        .with_context(|| format!("failed to open telemetry file '{}'", path.display()))?;
+    let reader = io::BufReader::new(file);
+    reader
+        .lines()
+        .collect::<std::result::Result<Vec<String>, std::io::Error>>()
+        .with_context(|| format!("failed to read telemetry file '{}'", path.display()))
+}
+
+/// `250ms`, or `1.25s` from a second up.
+fn format_latency_ms(ms: f64) -> String {
+    if ms >= 1000.0 {
+        format!("{:.2}s", ms / 1000.0)
+    } else {
+        format!("{}ms", ms.round())
+    }
+}
+
+/// Report lines for the latency, tool and guardrail sections; empty
+/// sections are left out.
+pub fn telemetry_report_sections(summary: &TelemetrySummary) -> Vec<String> {
+    let mut lines = Vec::new();
+    let latency = summary.latency_by_command();
+    if !latency.is_empty() {
+        lines.push("Latency by command:".to_string());
+        for (command, latency) in latency {
+            lines.push(format!(
+                "- {command}: n={} avg={} p50={} p95={} max={}",
+                latency.count,
+                format_latency_ms(latency.average()),
+                format_latency_ms(latency.percentile(50.0)),
+                format_latency_ms(latency.percentile(95.0)),
+                format_latency_ms(latency.max)
+            ));
+        }
+    }
+    let tools = summary.tools_by_failure_ratio();
+    if !tools.is_empty() {
+        lines.push("Tool reliability:".to_string());
+        for (tool, counts) in tools {
+            let failure = counts
+                .failure_ratio()
+                .map(|ratio| format!("{:.1}%", ratio * 100.0))
+                .unwrap_or_else(|| "n/a".to_string());
+            lines.push(format!(
+                "- {tool}: requested={} succeeded={} failed={} failure_rate={failure}",
+                counts.requested, counts.succeeded, counts.failed
+            ));
+        }
+    }
+    if !summary.guardrail_activity.is_empty() {
+        lines.push("Guardrail activity:".to_string());
+        for ((direction, mode), activity) in &summary.guardrail_activity {
+            lines.push(format!(
+                "- {direction}/{mode}: events={} hits={}",
+                activity.events, activity.hits
+            ));
+        }
+    }
+    lines
+}
+
+/// `telemetry report --json`.
+pub fn telemetry_report_json(path: &Path, summary: &TelemetrySummary) -> Value {
+    let round = crate::eval::round_metric;
+    let latency = summary
+        .latency_by_command()
+        .into_iter()
+        .map(|(command, latency)| {
+            json!({
+                "command": command,
+                "count": latency.count,
+                "samples": latency.samples().len(),
+                "avg_ms": round(latency.average()),
+                "p50_ms": round(latency.percentile(50.0)),
+                "p95_ms": round(latency.percentile(95.0)),
+                "max_ms": round(latency.max),
+            })
+        })
+        .collect::<Vec<Value>>();
+    let tools = summary
+        .tools_by_failure_ratio()
+        .into_iter()
+        .map(|(tool, counts)| {
+            json!({
+                "tool": tool,
+                "requested": counts.requested,
+                "succeeded": counts.succeeded,
+                "failed": counts.failed,
+                "failure_ratio": counts.failure_ratio().map(round),
+            })
+        })
+        .collect::<Vec<Value>>();
+    let guardrails = summary
+        .guardrail_activity
+        .iter()
+        .map(|((direction, mode), activity)| {
+            json!({
+                "direction": direction,
+                "mode": mode,
+                "events": activity.events,
+                "hits": activity.hits,
+            })
+        })
+        .collect::<Vec<Value>>();
+    json!({
+        "path": path.display().to_string(),
+        "total_lines": summary.total_lines,
+        "events_analyzed": summary.parsed_events,
+        "parse_errors": summary.parse_errors,
+        "unique_runs": summary.unique_runs.len(),
+        "command_outcomes": {
+            "completed": summary.command_completed,
+            "failed": summary.command_failed,
+        },
+        "tool_lifecycle": {
+            "requested": summary.tool_requested,
+            "succeeded": summary.tool_succeeded,
+            "failed": summary.tool_failed,
+        },
+        "command_counts": summary.command_counts,
+        "last_event_ts_unix_ms": summary.last_event_ts_unix_ms.map(|ts| ts as u64),
+        "latency_by_command": latency,
+        "tool_reliability": tools,
+        "guardrail_activity": guardrails,
+    })
+}
+
 pub fn run_telemetry_report(
     cfg: &RuntimeConfig,
     path_override: Option<String>,
     limit: usize,
+    json_output: bool,
 ) -> Result<()> {
     let path = PathBuf::from(path_override.unwrap_or_else(|| cfg.telemetry_path.clone()));
     if !path.exists() {
-        println!("No telemetry file found at '{}'.", path.display());
+        if json_output {
+            println!(
+                "{}",
+                json!({ "path": path.display().to_string(), "found": false })
+            );
+        } else {
+            println!("No telemetry file found at '{}'.", path.display());
+        }
         return Ok(());
     }
 
-    let file = std::fs::File::open(&path)
-        .with_context(|| format!("failed to open telemetry file '{}'", path.display()))?;
-    let reader = io::BufReader::new(file);
-    let lines = reader
-        .lines()
-        .collect::<std::result::Result<Vec<String>, std::io::Error>>()
-        .with_context(|| format!("failed to read telemetry file '{}'", path.display()))?;
+    let lines = read_telemetry_lines(&path)?;
 
     let summary = summarize_telemetry_lines(lines, limit);
+    if json_output {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&telemetry_report_json(&path, &summary))?
+        );
+        return Ok(());
+    }
     let mut commands = summary.command_counts.iter().collect::<Vec<_>>();
     commands.sort_by_key(|(name, count)| (std::cmp::Reverse(**count), (*name).clone()));
 
@@ -221,9 +1055,391 @@ pub fn run_telemetry_report(
         }
     }
 
+    let failures = summary.top_failure_fingerprints(5);
+    if !failures.is_empty() {
+        println!("Top failure fingerprints:");
+        for (fingerprint, entry) in failures {
+            println!("- {fingerprint} x{}: {}", entry.count, entry.template);
+        }
+    }
+
+    if !summary.versions.is_empty() {
+        println!("Versions: {}", format_distribution(&summary.versions));
+    }
+    if !summary.platforms.is_empty() {
+        println!("Platforms: {}", format_distribution(&summary.platforms));
+    }
+    for line in telemetry_report_sections(&summary) {
+        println!("{line}");
+    }
+
     if let Some(last_ts) = summary.last_event_ts_unix_ms {
         println!("Last event ts_unix_ms: {last_ts}");
     }
 
     Ok(())
 }
+
+/// `name=count` pairs, most frequent first.
+pub fn format_distribution(counts: &BTreeMap<String, usize>) -> String {
+    let mut entries = counts.iter().collect::<Vec<_>>();
+    entries.sort_by_key(|(name, count)| (std::cmp::Reverse(**count), (*name).clone()));
+    entries
+        .into_iter()
+        .map(|(name, count)| format!("{name}={count}"))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+// ---------------------------------------------------------------------------
+// Telemetry export (CSV / parquet-lite)
+// ---------------------------------------------------------------------------
+
+/// Columns `telemetry export` knows how to flatten, in default order.
+pub const TELEMETRY_EXPORT_COLUMNS: &[&str] = &[
+    "ts",
+    "event",
+    "command",
+    "run_id",
+    "session_id",
+    "duration_ms",
+    "tool",
+    "provider",
+    "model",
+    "error",
+];
+
+/// Flattened telemetry rows ready to be written as CSV or parquet-lite.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TelemetryTable {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<Value>>,
+    pub parse_errors: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TelemetryAggregation {
+    Count,
+    Sum(String),
+    Avg(String),
+    Min(String),
+    Max(String),
+}
+
+impl TelemetryAggregation {
+    pub fn header(&self) -> String {
+        match self {
+            TelemetryAggregation::Count => "count".to_string(),
+            TelemetryAggregation::Sum(column) => format!("sum_{column}"),
+            TelemetryAggregation::Avg(column) => format!("avg_{column}"),
+            TelemetryAggregation::Min(column) => format!("min_{column}"),
+            TelemetryAggregation::Max(column) => format!("max_{column}"),
+        }
+    }
+
+    fn column(&self) -> Option<&str> {
+        match self {
+            TelemetryAggregation::Count => None,
+            TelemetryAggregation::Sum(column)
+            | TelemetryAggregation::Avg(column)
+            | TelemetryAggregation::Min(column)
+            | TelemetryAggregation::Max(column) => Some(column),
+        }
+    }
+}
+
+fn validate_telemetry_column(column: &str) -> Result<String> {
+    let column = column.trim();
+    if TELEMETRY_EXPORT_COLUMNS.contains(&column) {
+        Ok(column.to_string())
+    } else {
+        Err(anyhow::anyhow!(
+            "unknown telemetry column '{column}'; valid columns: {}",
+            TELEMETRY_EXPORT_COLUMNS.join(", ")
+        ))
+    }
+}
+
+/// Parse a `--columns` list. `None` selects every known column.
+pub fn parse_telemetry_columns(spec: Option<&str>) -> Result<Vec<String>> {
+    let Some(spec) = spec else {
+        return Ok(TELEMETRY_EXPORT_COLUMNS
+            .iter()
+            .map(|column| column.to_string())
+            .collect());
+    };
+    let columns = spec
+        .split(',')
+        .filter(|column| !column.trim().is_empty())
+        .map(validate_telemetry_column)
+        .collect::<Result<Vec<String>>>()?;
+    if columns.is_empty() {
+        return Err(anyhow::anyhow!(
+            "unknown telemetry column ''; valid columns: {}",
+            TELEMETRY_EXPORT_COLUMNS.join(", ")
+        ));
+    }
+    Ok(columns)
+}
+
+/// Parse an `--agg` list such as `count,avg:duration_ms`.
+pub fn parse_telemetry_aggregations(spec: &str) -> Result<Vec<TelemetryAggregation>> {
+    let mut aggregations = Vec::new();
+    for raw in spec.split(',').map(str::trim).filter(|raw| !raw.is_empty()) {
+        let aggregation = match raw.split_once(':') {
+            None if raw == "count" => TelemetryAggregation::Count,
+            Some((op, column)) => {
+                let column = validate_telemetry_column(column)?;
+                match op.trim() {
+                    "sum" => TelemetryAggregation::Sum(column),
+                    "avg" => TelemetryAggregation::Avg(column),
+                    "min" => TelemetryAggregation::Min(column),
+                    "max" => TelemetryAggregation::Max(column),
+                    _ => {
+                        return Err(anyhow::anyhow!(
+                            "invalid telemetry aggregation '{raw}'; use count, sum:<column>, avg:<column>, min:<column> or max:<column>"
+                        ));
+                    }
+                }
+            }
+            None => {
+                return Err(anyhow::anyhow!(
+                    "invalid telemetry aggregation '{raw}'; use count, sum:<column>, avg:<column>, min:<column> or max:<column>"
+                ));
+            }
+        };
+        aggregations.push(aggregation);
+    }
+    if aggregations.is_empty() {
+        aggregations.push(TelemetryAggregation::Count);
+    }
+    Ok(aggregations)
+}
+
+fn telemetry_column_value(event: &Value, column: &str) -> Value {
+    let key = match column {
+        "ts" => "ts_unix_ms",
+        other => other,
+    };
+    event.get(key).cloned().unwrap_or(Value::Null)
+}
+
+fn telemetry_numeric_value(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(number) => number.as_f64(),
+        Value::String(text) => text.trim().parse::<f64>().ok(),
+        _ => None,
+    }
+}
+
+fn parse_telemetry_events(lines: &[String]) -> (Vec<Value>, usize) {
+    let mut events = Vec::new();
+    let mut parse_errors = 0;
+    for line in lines {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<Value>(line) {
+            Ok(value) => events.push(value),
+            Err(_) => parse_errors += 1,
+        }
+    }
+    (events, parse_errors)
+}
+
+/// Flatten raw telemetry lines into one row per event. Events missing a
+/// column get a null (empty) cell.
+pub fn telemetry_events_table(lines: &[String], columns: &[String]) -> TelemetryTable {
+    let (events, parse_errors) = parse_telemetry_events(lines);
+    let rows = events
+        .iter()
+        .map(|event| {
+            columns
+                .iter()
+                .map(|column| telemetry_column_value(event, column))
+                .collect()
+        })
+        .collect();
+    TelemetryTable {
+        columns: columns.to_vec(),
+        rows,
+        parse_errors,
+    }
+}
+
+/// Group events by `group_by` and compute each aggregation per group.
+/// Groups are sorted by key; non-numeric values are ignored by sum/avg/min/max.
+pub fn aggregate_telemetry_events(
+    lines: &[String],
+    group_by: &str,
+    aggregations: &[TelemetryAggregation],
+) -> TelemetryTable {
+    let (events, parse_errors) = parse_telemetry_events(lines);
+    let mut groups = BTreeMap::<String, Vec<&Value>>::new();
+    for event in &events {
+        let key = match telemetry_column_value(event, group_by) {
+            Value::Null => String::new(),
+            Value::String(text) => text,
+            other => other.to_string(),
+        };
+        groups.entry(key).or_default().push(event);
+    }
+
+    let mut columns = vec![group_by.to_string()];
+    columns.extend(aggregations.iter().map(TelemetryAggregation::header));
+
+    let rows = groups
+        .into_iter()
+        .map(|(key, group)| {
+            let mut row = vec![json!(key)];
+            for aggregation in aggregations {
+                let values = aggregation
+                    .column()
+                    .map(|column| {
+                        group
+                            .iter()
+                            .filter_map(|event| {
+                                telemetry_numeric_value(&telemetry_column_value(event, column))
+                            })
+                            .collect::<Vec<f64>>()
+                    })
+                    .unwrap_or_default();
+                let cell = match aggregation {
+                    TelemetryAggregation::Count => json!(group.len()),
+                    _ if values.is_empty() => Value::Null,
+                    TelemetryAggregation::Sum(_) => json!(values.iter().sum::<f64>()),
+                    TelemetryAggregation::Avg(_) => {
+                        json!(values.iter().sum::<f64>() / values.len() as f64)
+                    }
+                    TelemetryAggregation::Min(_) => {
+                        json!(values.iter().copied().fold(f64::INFINITY, f64::min))
+                    }
+                    TelemetryAggregation::Max(_) => {
+                        json!(values.iter().copied().fold(f64::NEG_INFINITY, f64::max))
+                    }
+                };
+                row.push(cell);
+            }
+            row
+        })
+        .collect();
+
+    TelemetryTable {
+        columns,
+        rows,
+        parse_errors,
+    }
+}
+
+/// Quote a CSV field when it contains a delimiter, quote, or line break.
+pub fn csv_escape_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn csv_cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => csv_escape_field(text),
+        other => csv_escape_field(&other.to_string()),
+    }
+}
+
+pub fn render_telemetry_csv(table: &TelemetryTable) -> String {
+    let mut out = table
+        .columns
+        .iter()
+        .map(|column| csv_escape_field(column))
+        .collect::<Vec<String>>()
+        .join(",");
+    out.push('\n');
+    for row in &table.rows {
+        out.push_str(&row.iter().map(csv_cell).collect::<Vec<String>>().join(","));
+        out.push('\n');
+    }
+    out
+}
+
+/// Column-oriented JSON: one array per column, values kept typed.
+pub fn render_telemetry_parquet_lite(table: &TelemetryTable) -> Value {
+    let mut data = serde_json::Map::new();
+    for (idx, column) in table.columns.iter().enumerate() {
+        let values = table
+            .rows
+            .iter()
+            .map(|row| row.get(idx).cloned().unwrap_or(Value::Null))
+            .collect::<Vec<Value>>();
+        data.insert(column.clone(), Value::Array(values));
+    }
+    json!({
+        "format": "parquet-lite",
+        "columns": table.columns,
+        "row_count": table.rows.len(),
+        "data": data
+    })
+}
+
+pub fn run_telemetry_export(
+    cfg: &RuntimeConfig,
+    path_override: Option<String>,
+    output: &str,
+    format: TelemetryExportFormat,
+    columns: Option<&str>,
+    group_by: Option<&str>,
+    agg: &str,
+) -> Result<()> {
+    // Validate the column spec before touching the events file.
+    let grouping = match group_by {
+        Some(group_by) => Some((
+            validate_telemetry_column(group_by)?,
+            parse_telemetry_aggregations(agg)?,
+        )),
+        None => None,
+    };
+    let columns = parse_telemetry_columns(columns)?;
+
+    let path = PathBuf::from(path_override.unwrap_or_else(|| cfg.telemetry_path.clone()));
+    let lines = if path.exists() {
+        read_telemetry_lines(&path)?
+    } else {
+        Vec::new()
+    };
+
+    let table = match grouping {
+        Some((group_by, aggregations)) => {
+            aggregate_telemetry_events(&lines, &group_by, &aggregations)
+        }
+        None => telemetry_events_table(&lines, &columns),
+    };
+
+    let rendered = match format {
+        TelemetryExportFormat::Csv => render_telemetry_csv(&table),
+        TelemetryExportFormat::ParquetLite => {
+            serde_json::to_string_pretty(&render_telemetry_parquet_lite(&table))
+                .context("failed to serialize parquet-lite export")?
+        }
+    };
+
+    let output_path = Path::new(output);
+    if let Some(parent) = output_path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create export directory '{}'", parent.display()))?;
+    }
+    std::fs::write(output_path, rendered)
+        .with_context(|| format!("failed to write telemetry export '{output}'"))?;
+
+    println!(
+        "Exported {} row(s) from '{}' to '{}' (parse_errors={})",
+        table.rows.len(),
+        path.display(),
+        output,
+        table.parse_errors
+    );
+    Ok(())
+}
@@ -0,0 +1,483 @@
+//! `telemetry_export`: ship telemetry to an observability pipeline as OTLP
+//! logs (`format = "otlp-json"`, the OTLP/HTTP JSON encoding), e.g. an
+//! OpenTelemetry Collector in front of Loki or Grafana Cloud.
+//!
+//! Every event the sink writes to the JSONL file, already redacted, is also
+//! converted to a log record: the event name is the body, payload fields
+//! become attributes, and the severity comes from the event name. Records
+//! go through a bounded queue to a background task that POSTs them in
+//! batches. The command never waits on the network: a full queue drops the
+//! record, and a failed batch is dropped after one attempt. Drops and
+//! failures are counted in `<state>/telemetry-export.json` for `doctor`.
+//! The JSONL file is written either way.
+//!
+//! `--telemetry-export-dry-run` prints records to stderr instead, and
+//! `--offline` turns sending off.
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use tokio::sync::{mpsc, oneshot};
+
+use crate::build_info::CLI_VERSION;
+use crate::config::{RuntimeConfig, TelemetryExportConfig};
+
+pub const OTLP_SERVICE_NAME: &str = "zavora-cli";
+/// Records waiting for the background task; more are dropped.
+pub const TELEMETRY_EXPORT_QUEUE_CAPACITY: usize = 512;
+pub const TELEMETRY_EXPORT_BATCH_SIZE: usize = 64;
+/// Longest a record waits for its batch to fill.
+pub const TELEMETRY_EXPORT_FLUSH_INTERVAL: Duration = Duration::from_millis(500);
+pub const TELEMETRY_EXPORT_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long the end of a command waits for queued records to go out.
+pub const TELEMETRY_EXPORT_SHUTDOWN_GRACE: Duration = Duration::from_millis(750);
+
+/// Envelope fields every telemetry line carries; everything else is payload.
+const ENVELOPE_FIELDS: &[&str] = &["ts_unix_ms", "event"];
+
+/// OTLP severity number and text for an event name: failures, timeouts and
+/// errors are ERROR; blocks, denials, drops and warnings are WARN.
+pub fn otlp_severity(event: &str) -> (u8, &'static str) {
+    let last = event.rsplit('.').next().unwrap_or(event);
+    if matches!(last, "failed" | "error" | "timeout") || event.contains("error") {
+        (17, "ERROR")
+    } else if matches!(last, "blocked" | "denied" | "dropped" | "opened") || event.contains("warn")
+    {
+        (13, "WARN")
+    } else {
+        (9, "INFO")
+    }
+}
+
+/// OTLP `AnyValue` for a JSON value. Integers are strings, as the OTLP JSON
+/// encoding requires for 64-bit fields.
+pub fn otlp_any_value(value: &Value) -> Value {
+    match value {
+        Value::Null => json!({}),
+        Value::Bool(flag) => json!({ "boolValue": flag }),
+        Value::Number(number) if number.is_i64() || number.is_u64() => {
+            json!({ "intValue": number.to_string() })
+        }
+        Value::Number(number) => json!({ "doubleValue": number.as_f64() }),
+        Value::String(text) => json!({ "stringValue": text }),
+        Value::Array(items) => {
+            let values = items.iter().map(otlp_any_value).collect::<Vec<Value>>();
+            json!({ "arrayValue": { "values": values } })
+        }
+        Value::Object(map) => json!({
+            "kvlistValue": {
+                "values": map
+                    .iter()
+                    .map(|(key, value)| otlp_key_value(key, value))
+                    .collect::<Vec<Value>>()
+            }
+        }),
+    }
+}
+
+fn otlp_key_value(key: &str, value: &Value) -> Value {
+    json!({ "key": key, "value": otlp_any_value(value) })
+}
+
+/// One OTLP log record for a telemetry line as written to the JSONL file.
+pub fn otlp_log_record(record: &Value) -> Value {
+    let event = record["event"].as_str().unwrap_or_default();
+    let (severity_number, severity_text) = otlp_severity(event);
+    let time_unix_nano = record["ts_unix_ms"]
+        .as_u64()
+        .map(|ms| u128::from(ms) * 1_000_000)
+        .unwrap_or_default();
+    let attributes = record
+        .as_object()
+        .into_iter()
+        .flatten()
+        .filter(|(key, value)| !ENVELOPE_FIELDS.contains(&key.as_str()) && !value.is_null())
+        .map(|(key, value)| otlp_key_value(key, value))
+        .collect::<Vec<Value>>();
+    json!({
+        "timeUnixNano": time_unix_nano.to_string(),
+        "severityNumber": severity_number,
+        "severityText": severity_text,
+        "body": { "stringValue": event },
+        "attributes": attributes,
+    })
+}
+
+/// `HOSTNAME`, then `/etc/hostname`, then `unknown`.
+pub fn host_name() -> String {
+    std::env::var("HOSTNAME")
+        .ok()
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+pub fn otlp_resource_attributes(host: &str) -> Vec<Value> {
+    vec![
+        otlp_key_value("service.name", &json!(OTLP_SERVICE_NAME)),
+        otlp_key_value("service.version", &json!(CLI_VERSION)),
+        otlp_key_value("host.name", &json!(host)),
+    ]
+}
+
+/// `ExportLogsServiceRequest` body for one batch.
+pub fn otlp_logs_request(resource_attributes: &[Value], records: Vec<Value>) -> Value {
+    json!({
+        "resourceLogs": [{
+            "resource": { "attributes": resource_attributes },
+            "scopeLogs": [{
+                "scope": { "name": OTLP_SERVICE_NAME, "version": CLI_VERSION },
+                "logRecords": records,
+            }],
+        }],
+    })
+}
+
+#[async_trait]
+pub trait LogBatchSender: Send + Sync {
+    async fn send(&self, body: &Value) -> Result<()>;
+}
+
+/// POSTs batches to the configured endpoint with its headers.
+pub struct HttpLogBatchSender {
+    pub config: TelemetryExportConfig,
+}
+
+#[async_trait]
+impl LogBatchSender for HttpLogBatchSender {
+    #[cfg(feature = "telemetry-export")]
+    async fn send(&self, body: &Value) -> Result<()> {
+        let client = reqwest::Client::builder()
+            .timeout(TELEMETRY_EXPORT_TIMEOUT)
+            .user_agent(concat!("zavora-cli/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .context("failed to build telemetry export client")?;
+        let mut request = client.post(&self.config.endpoint).json(body);
+        for (name, value) in &self.config.headers {
+            request = request.header(name, value);
+        }
+        request
+            .send()
+            .await
+            .and_then(|resp| resp.error_for_status())
+            .with_context(|| format!("failed to export telemetry to {}", self.config.endpoint))?;
+        Ok(())
+    }
+
+    #[cfg(not(feature = "telemetry-export"))]
+    async fn send(&self, _body: &Value) -> Result<()> {
+        Err(anyhow::anyhow!(
+            "telemetry export requires feature 'telemetry-export'. Rebuild with: cargo build --features telemetry-export"
+        ))
+    }
+}
+
+/// In-process counts for this run.
+#[derive(Debug, Default)]
+pub struct ExportCounters {
+    pub sent: AtomicU64,
+    /// Records dropped because the queue was full.
+    pub dropped: AtomicU64,
+    /// Records lost with a failed batch.
+    pub failed: AtomicU64,
+}
+
+/// Totals kept in `<state>/telemetry-export.json` across runs.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ExportStats {
+    pub dropped: u64,
+    pub failed: u64,
+    #[serde(default)]
+    pub last_error: Option<String>,
+    #[serde(default)]
+    pub last_failure_unix_secs: Option<u64>,
+}
+
+pub fn load_export_stats(path: &Path) -> Option<ExportStats> {
+    let text = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&text).ok()
+}
+
+fn save_export_stats(path: &Path, stats: &ExportStats) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    let text = serde_json::to_string_pretty(stats).context("failed to encode export stats")?;
+    std::fs::write(path, text).with_context(|| format!("failed to write {}", path.display()))
+}
+
+/// Add this batch's losses to the stored totals.
+fn record_export_losses(path: &Path, dropped: u64, failed: u64, error: Option<String>) {
+    let mut stats = load_export_stats(path).unwrap_or_default();
+    stats.dropped += dropped;
+    stats.failed += failed;
+    if let Some(error) = error {
+        stats.last_error = Some(error);
+        stats.last_failure_unix_secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .ok()
+            .map(|elapsed| elapsed.as_secs());
+    }
+    if let Err(err) = save_export_stats(path, &stats) {
+        tracing::debug!(error = %err, "failed to save telemetry export stats");
+    }
+}
+
+/// `doctor` line for the export block.
+pub fn format_telemetry_export_status(
+    export: Option<&TelemetryExportConfig>,
+    offline: bool,
+    stats: Option<&ExportStats>,
+) -> String {
+    let Some(export) = export else {
+        return "Telemetry export: off".to_string();
+    };
+    let mut line = format!(
+        "Telemetry export: {:?} -> {}",
+        export.format, export.endpoint
+    );
+    if offline {
+        line.push_str(" (offline: not sending)");
+    }
+    match stats {
+        Some(stats) if stats.dropped > 0 || stats.failed > 0 => {
+            line.push_str(&format!(
+                "; {} record(s) dropped on overflow, {} lost to failed sends",
+                stats.dropped, stats.failed
+            ));
+            if let Some(error) = &stats.last_error {
+                line.push_str(&format!(" (last error: {error})"));
+            }
+        }
+        _ => line.push_str("; no losses recorded"),
+    }
+    line
+}
+
+enum ExportMessage {
+    Record(Value),
+    Flush(oneshot::Sender<()>),
+}
+
+type DryRunWriter = Arc<Mutex<Box<dyn Write + Send>>>;
+
+#[derive(Clone)]
+enum ExportMode {
+    DryRun(DryRunWriter),
+    Queue(mpsc::Sender<ExportMessage>),
+}
+
+/// Handle shared by every clone of the CLI's telemetry sink.
+#[derive(Clone)]
+pub struct TelemetryExporter {
+    mode: ExportMode,
+    pub counters: Arc<ExportCounters>,
+}
+
+impl std::fmt::Debug for TelemetryExporter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mode = match self.mode {
+            ExportMode::DryRun(_) => "dry-run",
+            ExportMode::Queue(_) => "queue",
+        };
+        f.debug_struct("TelemetryExporter")
+            .field("mode", &mode)
+            .finish()
+    }
+}
+
+/// Batching knobs; tests shrink them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExportQueueSettings {
+    pub capacity: usize,
+    pub batch_size: usize,
+    pub flush_interval: Duration,
+}
+
+impl Default for ExportQueueSettings {
+    fn default() -> Self {
+        Self {
+            capacity: TELEMETRY_EXPORT_QUEUE_CAPACITY,
+            batch_size: TELEMETRY_EXPORT_BATCH_SIZE,
+            flush_interval: TELEMETRY_EXPORT_FLUSH_INTERVAL,
+        }
+    }
+}
+
+impl TelemetryExporter {
+    /// The exporter for `cfg`, if any: none without a `telemetry_export`
+    /// block, disabled telemetry, `--offline` (unless dry-running) or a
+    /// tokio runtime to send from.
+    pub fn from_config(cfg: &RuntimeConfig) -> Option<Self> {
+        let export = cfg.telemetry_export.as_ref()?;
+        if !cfg.telemetry_enabled {
+            return None;
+        }
+        if cfg.telemetry_export_dry_run {
+            return Some(Self::dry_run(Box::new(io::stderr())));
+        }
+        if cfg.offline {
+            return None;
+        }
+        Self::spawn(
+            Arc::new(HttpLogBatchSender {
+                config: export.clone(),
+            }),
+            ExportQueueSettings::default(),
+            Some(cfg.paths.telemetry_export_file()),
+        )
+    }
+
+    /// Print each record as one JSON line to `writer`.
+    pub fn dry_run(writer: Box<dyn Write + Send>) -> Self {
+        Self {
+            mode: ExportMode::DryRun(Arc::new(Mutex::new(writer))),
+            counters: Arc::default(),
+        }
+    }
+
+    /// Start the background task on the current runtime; `None` outside
+    /// one. Losses are added to `stats_path` when given.
+    pub fn spawn(
+        sender: Arc<dyn LogBatchSender>,
+        settings: ExportQueueSettings,
+        stats_path: Option<PathBuf>,
+    ) -> Option<Self> {
+        let runtime = tokio::runtime::Handle::try_current().ok()?;
+        let (tx, rx) = mpsc::channel(settings.capacity.max(1));
+        let counters = Arc::new(ExportCounters::default());
+        runtime.spawn(run_export_loop(
+            rx,
+            sender,
+            settings,
+            Arc::clone(&counters),
+            stats_path,
+        ));
+        Some(Self {
+            mode: ExportMode::Queue(tx),
+            counters,
+        })
+    }
+
+    /// Queue one written telemetry line. Never blocks: a full queue drops it.
+    pub fn export(&self, record: &Value) {
+        let record = otlp_log_record(record);
+        match &self.mode {
+            ExportMode::DryRun(writer) => {
+                let mut writer = writer.lock().unwrap_or_else(|e| e.into_inner());
+                let _ = writeln!(writer, "{record}");
+                let _ = writer.flush();
+            }
+            ExportMode::Queue(tx) => {
+                if tx.try_send(ExportMessage::Record(record)).is_err() {
+                    self.counters.dropped.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        }
+    }
+
+    /// Send whatever is queued, waiting at most `grace`.
+    pub async fn flush(&self, grace: Duration) {
+        let ExportMode::Queue(tx) = &self.mode else {
+            return;
+        };
+        let (done_tx, done_rx) = oneshot::channel();
+        let flushed = async {
+            if tx.send(ExportMessage::Flush(done_tx)).await.is_ok() {
+                let _ = done_rx.await;
+            }
+        };
+        let _ = tokio::time::timeout(grace, flushed).await;
+    }
+}
+
+async fn run_export_loop(
+    mut rx: mpsc::Receiver<ExportMessage>,
+    sender: Arc<dyn LogBatchSender>,
+    settings: ExportQueueSettings,
+    counters: Arc<ExportCounters>,
+    stats_path: Option<PathBuf>,
+) {
+    let resource = otlp_resource_attributes(&host_name());
+    let mut batch = Vec::new();
+    let mut flushes = Vec::new();
+    let mut stored = StoredLosses {
+        path: stats_path,
+        dropped: 0,
+    };
+    loop {
+        let message = if batch.is_empty() {
+            rx.recv().await
+        } else {
+            match tokio::time::timeout(settings.flush_interval, rx.recv()).await {
+                Ok(message) => message,
+                Err(_) => {
+                    send_batch(&*sender, &resource, &mut batch, &counters, &mut stored).await;
+                    continue;
+                }
+            }
+        };
+        match message {
+            Some(ExportMessage::Record(record)) => batch.push(record),
+            Some(ExportMessage::Flush(done)) => flushes.push(done),
+            None => {
+                send_batch(&*sender, &resource, &mut batch, &counters, &mut stored).await;
+                return;
+            }
+        }
+        if batch.len() >= settings.batch_size || !flushes.is_empty() {
+            send_batch(&*sender, &resource, &mut batch, &counters, &mut stored).await;
+            for done in flushes.drain(..) {
+                let _ = done.send(());
+            }
+        }
+    }
+}
+
+/// Where losses are stored, and how many drops are already counted there.
+struct StoredLosses {
+    path: Option<PathBuf>,
+    dropped: u64,
+}
+
+async fn send_batch(
+    sender: &dyn LogBatchSender,
+    resource: &[Value],
+    batch: &mut Vec<Value>,
+    counters: &ExportCounters,
+    stored: &mut StoredLosses,
+) {
+    let dropped_total = counters.dropped.load(Ordering::Relaxed);
+    let dropped = dropped_total.saturating_sub(stored.dropped);
+    stored.dropped = dropped_total;
+    let mut failed = 0;
+    let mut error = None;
+    if !batch.is_empty() {
+        let records = std::mem::take(batch);
+        let count = records.len() as u64;
+        match sender.send(&otlp_logs_request(resource, records)).await {
+            Ok(()) => {
+                counters.sent.fetch_add(count, Ordering::Relaxed);
+            }
+            Err(err) => {
+                tracing::debug!(error = %format!("{err:#}"), "telemetry export failed");
+                counters.failed.fetch_add(count, Ordering::Relaxed);
+                failed = count;
+                error = Some(format!("{err:#}"));
+            }
+        }
+    }
+    if (dropped > 0 || failed > 0)
+        && let Some(path) = &stored.path
+    {
+        record_export_losses(path, dropped, failed, error);
+    }
+}
@@ -9,6 +9,7 @@ use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 
 use crate::checkpoint::CheckpointStore;
 use crate::context::{BudgetLevel, ContextUsage};
+use crate::paths::WORKSPACE_DIR;
 
 // ---------------------------------------------------------------------------
 // ANSI color helpers
@@ -36,13 +37,20 @@ pub const BOLD_MAGENTA: &str = "\x1b[1;35m";
 /// All registered slash commands with descriptions.
 pub const COMMAND_PALETTE: &[(&str, &str)] = &[
     ("help", "show command quick reference"),
+    ("find", "search commands, tools, agents and MCP servers"),
     ("status", "show active profile/provider/model/session"),
     ("provider", "switch provider and rebuild runtime"),
     ("model", "pick a model interactively or switch by id"),
     ("tools", "show active tools and confirmation policy"),
-    ("mcp", "show MCP server and tool summary"),
+    ("mcp", "show MCP server summary or per-server tool detail"),
     ("usage", "show context usage and token breakdown"),
+    ("stats", "session activity: exchanges, tokens, cost, tools, switches"),
     ("compact", "summarize conversation to free context space"),
+    ("autocompact", "toggle automatic compaction"),
+    ("memory", "recall, remember or forget learnings"),
+    ("time", "get time context or parse dates"),
+    ("orchestrate", "run full agent orchestration loop"),
+    ("ralph", "run Ralph autonomous dev pipeline"),
     (
         "checkpoint",
         "manage conversation snapshots (save|list|restore)",
@@ -50,6 +58,15 @@ pub const COMMAND_PALETTE: &[(&str, &str)] = &[
     ("tangent", "enter/exit exploratory branch"),
     ("todos", "view/delete/clear-finished task lists"),
     ("delegate", "(experimental) run isolated sub-agent task"),
+    ("handoff", "hand the conversation to another agent with a brief"),
+    ("persist", "save an ephemeral chat to the session store"),
+    ("starters", "send one of the active agent's conversation starters"),
+    ("allow", "auto-approve a tool pattern for this session"),
+    ("deny", "deny a tool pattern for this session"),
+    ("agent", "toggle agent mode (auto-approve tools)"),
+    ("undo", "restore last modified file"),
+    ("diff", "show workspace changes made this session"),
+    ("notify", "toggle or test completion notifications"),
     ("exit", "end interactive chat"),
 ];
 
@@ -288,7 +305,7 @@ pub fn suggest_command(input: &str) -> Option<String> {
 
 /// Check if this is the first run (no .zavora directory exists).
 pub fn is_first_run(workspace: &Path) -> bool {
-    !workspace.join(".zavora").exists()
+    !workspace.join(WORKSPACE_DIR).exists()
 }
 
 /// Print first-run onboarding help.
@@ -7,6 +7,9 @@ use anyhow::{Context as _, Result};
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 
+use crate::file_lock::{DEFAULT_FILE_LOCK_TIMEOUT, acquire_file_lock};
+use crate::paths::WORKSPACE_DIR;
+
 // ---------------------------------------------------------------------------
 // Todo data model
 // ---------------------------------------------------------------------------
@@ -90,7 +93,7 @@ impl TodoList {
 
 /// Directory for todo list storage.
 pub fn todos_dir(workspace: &Path) -> PathBuf {
-    workspace.join(".zavora").join("todos")
+    workspace.join(WORKSPACE_DIR).join("todos")
 }
 
 /// Save a todo list to disk.
@@ -99,6 +102,7 @@ pub fn save_todo(workspace: &Path, todo: &TodoList) -> Result<()> {
     std::fs::create_dir_all(&dir).context("failed to create todos directory")?;
     let path = dir.join(format!("{}.json", todo.id));
     let json = serde_json::to_string_pretty(todo).context("failed to serialize todo")?;
+    let _lock = acquire_file_lock(&path, DEFAULT_FILE_LOCK_TIMEOUT)?;
     std::fs::write(&path, json).context("failed to write todo file")?;
     Ok(())
 }
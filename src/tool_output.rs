@@ -0,0 +1,279 @@
+/// Rendering of tool results for chat (`chat_show_tool_results`) and the
+/// inline tool annotations (`chat_tool_annotations`).
+///
+/// The renderers are pure functions over the `FunctionCall` args and the
+/// `FunctionResponse` value so the streaming loop only decides *when* to
+/// print.
+use std::io::Write;
+
+use serde_json::Value;
+
+use crate::cli::{ChatToolAnnotations, ToolResultDisplay};
+use crate::streaming::extract_tool_failure_message;
+use crate::tools::mock::is_mocked_response;
+
+pub const DEFAULT_TOOL_RESULT_MAX_CHARS: usize = 2_000;
+
+/// Top-level scalar fields worth surfacing in the summary line, in order.
+const SUMMARY_FIELDS: &[&str] = &[
+    "path",
+    "file_path",
+    "command",
+    "action",
+    "exit_code",
+    "code",
+    "url",
+    "count",
+];
+
+const SUMMARY_FIELD_MAX_CHARS: usize = 60;
+
+/// Truncate `text` to at most `max_chars` chars, never splitting a char.
+/// Returns the kept prefix and the number of bytes dropped.
+pub fn truncate_at_char_boundary(text: &str, max_chars: usize) -> (&str, usize) {
+    match text.char_indices().nth(max_chars) {
+        Some((byte_index, _)) => (&text[..byte_index], text.len() - byte_index),
+        None => (text, 0),
+    }
+}
+
+pub fn format_payload_size(bytes: usize) -> String {
+    if bytes < 1024 {
+        format!("{bytes} B")
+    } else if bytes < 1024 * 1024 {
+        format!("{:.1} KB", bytes as f64 / 1024.0)
+    } else {
+        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+    }
+}
+
+fn tool_result_status(response: &Value) -> String {
+    let status = if extract_tool_failure_message(response).is_some() {
+        "error".to_string()
+    } else {
+        response
+            .get("status")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .unwrap_or_else(|| "ok".to_string())
+    };
+    if is_mocked_response(response) {
+        format!("{status} [mocked]")
+    } else {
+        status
+    }
+}
+
+fn summary_field(value: &Value) -> Option<String> {
+    let text = match value {
+        Value::String(text) => text.clone(),
+        Value::Number(number) => number.to_string(),
+        Value::Bool(flag) => flag.to_string(),
+        _ => return None,
+    };
+    let (kept, dropped) = truncate_at_char_boundary(&text, SUMMARY_FIELD_MAX_CHARS);
+    Some(if dropped > 0 {
+        format!("{kept}…")
+    } else {
+        kept.to_string()
+    })
+}
+
+/// One line: `name status key=value … (size)`.
+pub fn summarize_tool_result(name: &str, response: &Value) -> String {
+    let size = serde_json::to_string(response)
+        .map(|text| text.len())
+        .unwrap_or_default();
+    let mut line = format!("{name} {}", tool_result_status(response));
+    if let Some(object) = response.as_object() {
+        for field in SUMMARY_FIELDS {
+            if let Some(value) = object.get(*field).and_then(summary_field) {
+                line.push_str(&format!(" {field}={value}"));
+            }
+        }
+    }
+    line.push_str(&format!(" ({})", format_payload_size(size)));
+    line
+}
+
+/// Pretty-printed JSON, cut at `max_chars` with a marker naming the bytes
+/// left out.
+pub fn render_tool_result_full(name: &str, response: &Value, max_chars: usize) -> String {
+    let pretty = serde_json::to_string_pretty(response).unwrap_or_else(|_| response.to_string());
+    let (kept, dropped) = truncate_at_char_boundary(&pretty, max_chars);
+    let mut rendered = format!("{name} {}\n{kept}", tool_result_status(response));
+    if dropped > 0 {
+        rendered.push_str(&format!("\n… (truncated, {dropped} more bytes)"));
+    }
+    rendered
+}
+
+/// Text to print for a tool result, or `None` when display is off.
+pub fn render_tool_result(
+    name: &str,
+    response: &Value,
+    display: ToolResultDisplay,
+    max_chars: usize,
+) -> Option<String> {
+    match display {
+        ToolResultDisplay::Off => None,
+        ToolResultDisplay::Summary => Some(summarize_tool_result(name, response)),
+        ToolResultDisplay::Full => Some(render_tool_result_full(name, response, max_chars)),
+    }
+}
+
+/// Arg names whose values are never shown (matched case-insensitively as
+/// substrings, so `github_token` and `X-Api-Key` are covered).
+const SENSITIVE_ARG_NAMES: &[&str] = &[
+    "password",
+    "passwd",
+    "secret",
+    "token",
+    "api_key",
+    "apikey",
+    "api-key",
+    "authorization",
+    "credential",
+    "private_key",
+];
+
+/// Value prefixes of well-known credentials, redacted whatever the arg name.
+const SECRET_VALUE_PREFIXES: &[&str] = &[
+    "sk-",
+    "ghp_",
+    "gho_",
+    "github_pat_",
+    "xox",
+    "AKIA",
+    "Bearer ",
+];
+
+const REDACTED_ARG: &str = "[REDACTED]";
+const ARG_VALUE_MAX_CHARS: usize = 40;
+pub const TOOL_ANNOTATION_ARGS_MAX_CHARS: usize = 80;
+const TOOL_ANNOTATION_ERROR_MAX_CHARS: usize = 100;
+
+fn is_sensitive_arg(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    SENSITIVE_ARG_NAMES
+        .iter()
+        .any(|needle| name.contains(needle))
+}
+
+fn clip_chars(text: &str, max_chars: usize) -> String {
+    let (kept, dropped) = truncate_at_char_boundary(text, max_chars);
+    if dropped > 0 {
+        format!("{kept}…")
+    } else {
+        kept.to_string()
+    }
+}
+
+fn summarize_arg_value(name: &str, value: &Value) -> String {
+    if is_sensitive_arg(name) {
+        return REDACTED_ARG.to_string();
+    }
+    let text = match value {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    };
+    if SECRET_VALUE_PREFIXES
+        .iter()
+        .any(|prefix| text.trim_start().starts_with(prefix))
+    {
+        return REDACTED_ARG.to_string();
+    }
+    let text = crate::error::redact_sensitive_text(&text.replace(['\n', '\r'], "⏎"));
+    clip_chars(&text, ARG_VALUE_MAX_CHARS)
+}
+
+/// One-line `key=value, …` summary of tool args, with sensitive values
+/// redacted and long values cut. Shared by the chat tool annotations and
+/// the approval prompt.
+pub fn summarize_tool_args(args: &Value, max_chars: usize) -> String {
+    let summary = match args {
+        Value::Object(object) => object
+            .iter()
+            .map(|(name, value)| format!("{name}={}", summarize_arg_value(name, value)))
+            .collect::<Vec<String>>()
+            .join(", "),
+        Value::Null => String::new(),
+        other => summarize_arg_value("", other),
+    };
+    clip_chars(&summary, max_chars)
+}
+
+/// `· running fs_read(path=src/main.rs)…`
+pub fn format_tool_call_annotation(name: &str, args: &Value) -> String {
+    format!(
+        "· running {name}({})…",
+        summarize_tool_args(args, TOOL_ANNOTATION_ARGS_MAX_CHARS)
+    )
+}
+
+fn format_annotation_duration(duration: std::time::Duration) -> String {
+    let ms = duration.as_millis();
+    if ms < 1000 {
+        format!("{ms}ms")
+    } else {
+        format!("{:.1}s", duration.as_secs_f64())
+    }
+}
+
+/// `✓ fs_read done (120ms)` or `✗ fs_read failed: <first line of error>`.
+pub fn format_tool_done_annotation(
+    name: &str,
+    duration: Option<std::time::Duration>,
+    error: Option<&str>,
+) -> String {
+    match error {
+        Some(error) => {
+            let first_line = error.lines().next().unwrap_or_default().trim();
+            let error = crate::error::redact_sensitive_text(first_line);
+            format!(
+                "✗ {name} failed: {}",
+                clip_chars(&error, TOOL_ANNOTATION_ERROR_MAX_CHARS)
+            )
+        }
+        None => match duration {
+            Some(duration) => format!("✓ {name} done ({})", format_annotation_duration(duration)),
+            None => format!("✓ {name} done"),
+        },
+    }
+}
+
+/// Where tool annotations go, from `chat_tool_annotations` and whether
+/// stdout is a terminal. `None` when they are off.
+pub fn tool_annotation_target(
+    mode: ChatToolAnnotations,
+    stdout_is_terminal: bool,
+) -> Option<AnnotationTarget> {
+    match mode {
+        ChatToolAnnotations::Off => None,
+        ChatToolAnnotations::On if stdout_is_terminal => Some(AnnotationTarget::Stdout),
+        ChatToolAnnotations::On | ChatToolAnnotations::StderrOnly => Some(AnnotationTarget::Stderr),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnnotationTarget {
+    Stdout,
+    Stderr,
+}
+
+/// Write one dim annotation line to the stream `target` picks. Piped
+/// stdout never receives annotations, so it keeps only the answer text.
+pub fn write_tool_annotation(
+    target: Option<AnnotationTarget>,
+    line: &str,
+    stdout: &mut dyn Write,
+    stderr: &mut dyn Write,
+) {
+    let out = match target {
+        Some(AnnotationTarget::Stdout) => stdout,
+        Some(AnnotationTarget::Stderr) => stderr,
+        None => return,
+    };
+    let _ = writeln!(out, "{}  {line}{}", crate::theme::DIM, crate::theme::RESET);
+    let _ = out.flush();
+}
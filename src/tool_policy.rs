@@ -167,11 +167,12 @@ pub fn filter_tools_by_policy(
 // Permission rules (layered permission system)
 // ---------------------------------------------------------------------------
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 /// A permission rule pattern in the form "tool_name:content_pattern" or just "tool_name".
 /// Examples: "fs_read:*", "execute_bash:git status*", "fs_write:/etc/*"
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 #[serde(transparent)]
 pub struct ToolPattern(pub String);
 
@@ -201,7 +202,7 @@ pub enum PermissionDecision {
 }
 
 /// Layered permission rules. First match wins across always_deny → always_allow → always_ask.
-#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
 pub struct PermissionRules {
     #[serde(default)]
     pub always_allow: Vec<ToolPattern>,
@@ -253,6 +254,8 @@ pub const READ_ONLY_TOOLS: &[&str] = &[
     "release_template",
     "todo_list",
     "lsp",
+    "policy_check",
+    "doc_search",
 ];
 
 /// Check if a tool is read-only by name.
@@ -261,3 +264,421 @@ pub fn is_read_only_tool(name: &str) -> bool {
 }
 
 // StubTool moved to tests.rs — not needed in production code.
+
+// ---------------------------------------------------------------------------
+// Effective tool policy
+// ---------------------------------------------------------------------------
+
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use crate::cli::ToolConfirmationMode;
+use crate::config::RuntimeConfig;
+use crate::read_only::READ_ONLY_REMOVED_TOOLS;
+use crate::tools::execute_bash::{
+    DANGEROUS_PATTERNS, EXECUTE_BASH_DENIED_PATTERNS, ExecuteBashPolicyDecision,
+    ExecuteBashRequest, ExecuteBashToolError, READONLY_COMMANDS, evaluate_execute_bash_policy,
+    is_read_only_command,
+};
+use crate::tools::fs_read::{
+    FS_READ_DENIED_FILE_NAMES, FS_READ_DENIED_SEGMENTS, FsReadToolError,
+    enforce_workspace_path_policy,
+};
+
+/// Built-ins that prompt unless a rule allows them, whatever
+/// `tool_confirmation_mode` says.
+pub const CONFIRMATION_GUARDED_TOOLS: &[&str] = &[
+    "fs_write",
+    "file_edit",
+    "apply_patch",
+    "execute_bash",
+    "github_ops",
+    "remember",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ToolOrigin {
+    BuiltIn,
+    Mcp,
+}
+
+/// How calls to an available tool are wrapped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ToolConfirmation {
+    /// Runs as is.
+    Auto,
+    /// Runs without a prompt; the call is still displayed.
+    DisplayOnly,
+    /// Prompts before every call.
+    Confirm,
+}
+
+/// Why a tool is left out of the toolset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ToolUnavailable {
+    /// Agent `allow_tools` is set and no pattern matches.
+    NotAllowed,
+    /// Matches an agent `deny_tools` pattern.
+    Denied,
+    /// Write or MCP tool in read-only mode.
+    ReadOnly,
+}
+
+/// Every tool gating decision, built once from the runtime config:
+/// agent allow/deny filtering, permission rules (with the legacy
+/// `approve_tool` / `require_confirm_tool` lists folded in), the
+/// confirmation mode, read-only removals, and the `execute_bash` command
+/// and workspace path rules.
+#[derive(Debug, Clone)]
+pub struct ToolPolicy {
+    allow_patterns: Vec<String>,
+    deny_patterns: Vec<String>,
+    rules: PermissionRules,
+    approved: BTreeSet<String>,
+    confirmation_mode: ToolConfirmationMode,
+    read_only: bool,
+}
+
+fn trimmed_non_empty(values: &[String]) -> impl Iterator<Item = &str> {
+    values
+        .iter()
+        .map(|value| value.trim())
+        .filter(|value| !value.is_empty())
+}
+
+impl ToolPolicy {
+    pub fn from_config(cfg: &RuntimeConfig) -> Self {
+        let mut rules = cfg.permission_rules.clone();
+        rules.always_allow.extend(
+            trimmed_non_empty(&cfg.approve_tool).map(|name| ToolPattern(name.to_string())),
+        );
+        rules.always_ask.extend(
+            trimmed_non_empty(&cfg.require_confirm_tool)
+                .map(|name| ToolPattern(name.to_string())),
+        );
+        Self {
+            allow_patterns: trimmed_non_empty(&cfg.agent_allow_tools)
+                .map(str::to_string)
+                .collect(),
+            deny_patterns: trimmed_non_empty(&cfg.agent_deny_tools)
+                .map(str::to_string)
+                .collect(),
+            rules,
+            approved: trimmed_non_empty(&cfg.approve_tool)
+                .map(str::to_string)
+                .collect(),
+            confirmation_mode: cfg.tool_confirmation_mode,
+            read_only: cfg.read_only,
+        }
+    }
+
+    /// Permission rules in effect, legacy lists included.
+    pub fn rules(&self) -> &PermissionRules {
+        &self.rules
+    }
+
+    pub fn confirmation_mode(&self) -> ToolConfirmationMode {
+        self.confirmation_mode
+    }
+
+    pub fn read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Agent allow/deny filter only; deny wins over allow.
+    pub fn agent_filter(&self, name: &str) -> Option<ToolUnavailable> {
+        let patterns = |list: &[String]| list.iter().map(String::as_str).collect::<Vec<&str>>();
+        if !self.deny_patterns.is_empty()
+            && any_pattern_matches(&patterns(&self.deny_patterns), name)
+        {
+            return Some(ToolUnavailable::Denied);
+        }
+        if !self.allow_patterns.is_empty()
+            && !any_pattern_matches(&patterns(&self.allow_patterns), name)
+        {
+            return Some(ToolUnavailable::NotAllowed);
+        }
+        None
+    }
+
+    /// Read-only mode drops write tools and MCP tools (their side effects
+    /// are unknown).
+    pub fn removed_by_read_only(&self, name: &str, origin: ToolOrigin) -> bool {
+        self.read_only && (origin == ToolOrigin::Mcp || READ_ONLY_REMOVED_TOOLS.contains(&name))
+    }
+
+    pub fn unavailable_reason(&self, name: &str, origin: ToolOrigin) -> Option<ToolUnavailable> {
+        self.agent_filter(name).or_else(|| {
+            self.removed_by_read_only(name, origin)
+                .then_some(ToolUnavailable::ReadOnly)
+        })
+    }
+
+    pub fn is_tool_available(&self, name: &str, origin: ToolOrigin) -> bool {
+        self.unavailable_reason(name, origin).is_none()
+    }
+
+    pub fn permission_decision(&self, name: &str) -> PermissionDecision {
+        self.rules.evaluate(name, None)
+    }
+
+    /// Named in `approve_tool`; pre-approved in the ADK run config.
+    pub fn is_approved(&self, name: &str) -> bool {
+        self.approved.contains(name)
+    }
+
+    /// Wrapping for `name`: a matching rule decides first (deny rules with
+    /// content patterns still prompt so the call can be judged at runtime),
+    /// then read-only tools are display-only, guarded built-ins always
+    /// prompt, and the confirmation mode decides the rest.
+    pub fn confirmation(&self, name: &str, origin: ToolOrigin) -> ToolConfirmation {
+        match self.permission_decision(name) {
+            PermissionDecision::Allow => {
+                // github_ops keeps the wrapper so merges and approvals still
+                // prompt; the write tools keep it for allow_secrets calls.
+                if is_read_only_tool(name)
+                    || matches!(name, "github_ops" | "fs_write" | "apply_patch")
+                {
+                    ToolConfirmation::DisplayOnly
+                } else {
+                    ToolConfirmation::Auto
+                }
+            }
+            PermissionDecision::Deny | PermissionDecision::Ask => ToolConfirmation::Confirm,
+            PermissionDecision::NoMatch => {
+                if is_read_only_tool(name) {
+                    return ToolConfirmation::DisplayOnly;
+                }
+                let guarded = CONFIRMATION_GUARDED_TOOLS.contains(&name);
+                let confirm = match self.confirmation_mode {
+                    ToolConfirmationMode::Always => true,
+                    ToolConfirmationMode::McpOnly => guarded || origin == ToolOrigin::Mcp,
+                    ToolConfirmationMode::Never => guarded,
+                };
+                if confirm {
+                    ToolConfirmation::Confirm
+                } else {
+                    ToolConfirmation::Auto
+                }
+            }
+        }
+    }
+
+    pub fn requires_confirmation(&self, name: &str, origin: ToolOrigin) -> bool {
+        self.confirmation(name, origin) == ToolConfirmation::Confirm
+    }
+
+    /// `execute_bash` command rules. Read-only mode only admits read-only
+    /// commands, like the guard wrapper does per call.
+    pub fn evaluate_command(
+        &self,
+        request: &ExecuteBashRequest,
+    ) -> Result<ExecuteBashPolicyDecision, ExecuteBashToolError> {
+        if self.read_only && !is_read_only_command(&request.command) {
+            return Err(ExecuteBashToolError {
+                code: crate::read_only::READ_ONLY_MODE_CODE,
+                message: format!(
+                    "only read-only commands may run, regardless of approval (got '{}')",
+                    request.command
+                ),
+                killed_with: None,
+            });
+        }
+        evaluate_execute_bash_policy(request)
+    }
+
+    /// Workspace path rules shared by the file tools.
+    pub fn evaluate_path(
+        &self,
+        requested_path: &str,
+        resolved: &Path,
+        workspace_root: &Path,
+    ) -> Result<(), FsReadToolError> {
+        enforce_workspace_path_policy(requested_path, resolved, workspace_root)
+    }
+
+    pub fn report(&self, tools: &[(String, ToolOrigin)]) -> ToolPolicyReport {
+        ToolPolicyReport {
+            confirmation_mode: self.confirmation_mode,
+            read_only: self.read_only,
+            tools: tools
+                .iter()
+                .map(|(name, origin)| {
+                    let unavailable = self.unavailable_reason(name, *origin);
+                    ToolPolicyEntry {
+                        name: name.clone(),
+                        origin: *origin,
+                        available: unavailable.is_none(),
+                        unavailable,
+                        rule: permission_decision_label(self.permission_decision(name)),
+                        confirmation: unavailable
+                            .is_none()
+                            .then(|| self.confirmation(name, *origin)),
+                    }
+                })
+                .collect(),
+            rules: self.rules.clone(),
+            agent_allow_tools: self.allow_patterns.clone(),
+            agent_deny_tools: self.deny_patterns.clone(),
+            command_rules: CommandRules {
+                denied_patterns: EXECUTE_BASH_DENIED_PATTERNS,
+                shell_metacharacters: DANGEROUS_PATTERNS,
+                read_only_commands: READONLY_COMMANDS,
+            },
+            path_denials: PathDenials {
+                outside_workspace: true,
+                segments: FS_READ_DENIED_SEGMENTS,
+                file_names: FS_READ_DENIED_FILE_NAMES,
+            },
+        }
+    }
+}
+
+pub fn permission_decision_label(decision: PermissionDecision) -> &'static str {
+    match decision {
+        PermissionDecision::Allow => "allow",
+        PermissionDecision::Deny => "deny",
+        PermissionDecision::Ask => "ask",
+        PermissionDecision::NoMatch => "default",
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolPolicyEntry {
+    pub name: String,
+    pub origin: ToolOrigin,
+    pub available: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unavailable: Option<ToolUnavailable>,
+    /// Permission rule that matched: allow, deny, ask or default.
+    pub rule: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confirmation: Option<ToolConfirmation>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CommandRules {
+    pub denied_patterns: &'static [&'static str],
+    /// Need approval unless the security validator clears the command.
+    pub shell_metacharacters: &'static [&'static str],
+    pub read_only_commands: &'static [&'static str],
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PathDenials {
+    pub outside_workspace: bool,
+    pub segments: &'static [&'static str],
+    pub file_names: &'static [&'static str],
+}
+
+/// `policy show` output.
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolPolicyReport {
+    pub confirmation_mode: ToolConfirmationMode,
+    pub read_only: bool,
+    pub tools: Vec<ToolPolicyEntry>,
+    pub rules: PermissionRules,
+    pub agent_allow_tools: Vec<String>,
+    pub agent_deny_tools: Vec<String>,
+    pub command_rules: CommandRules,
+    pub path_denials: PathDenials,
+}
+
+fn kebab_label<T: Serialize>(value: &T) -> String {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+pub fn format_tool_policy_report(report: &ToolPolicyReport) -> String {
+    let list = |values: &[&str]| values.join(", ");
+    let names = |values: &[String]| {
+        if values.is_empty() {
+            "(none)".to_string()
+        } else {
+            values.join(", ")
+        }
+    };
+    let patterns = |values: &[ToolPattern]| {
+        if values.is_empty() {
+            "(none)".to_string()
+        } else {
+            values
+                .iter()
+                .map(|pattern| pattern.0.as_str())
+                .collect::<Vec<&str>>()
+                .join(", ")
+        }
+    };
+    let mut out = format!(
+        "Confirmation mode: {}\nRead-only mode: {}\nTools:\n",
+        kebab_label(&report.confirmation_mode),
+        if report.read_only { "on" } else { "off" }
+    );
+    for entry in &report.tools {
+        let status = match (entry.unavailable, entry.confirmation) {
+            (Some(reason), _) => format!("unavailable ({})", kebab_label(&reason)),
+            (None, Some(confirmation)) => kebab_label(&confirmation),
+            (None, None) => "available".to_string(),
+        };
+        out.push_str(&format!(
+            "- {} [{}] rule={} {status}\n",
+            entry.name,
+            kebab_label(&entry.origin),
+            entry.rule
+        ));
+    }
+    out.push_str(&format!(
+        "Permission rules:\n- allow: {}\n- deny: {}\n- ask: {}\n",
+        patterns(&report.rules.always_allow),
+        patterns(&report.rules.always_deny),
+        patterns(&report.rules.always_ask)
+    ));
+    if !report.agent_allow_tools.is_empty() || !report.agent_deny_tools.is_empty() {
+        out.push_str(&format!(
+            "Agent tool filter:\n- allow: {}\n- deny: {}\n",
+            names(&report.agent_allow_tools),
+            names(&report.agent_deny_tools)
+        ));
+    }
+    out.push_str(&format!(
+        "Command rules (execute_bash):\n- denied patterns: {}\n- need approval: {}\n- read-only commands: {}\n",
+        list(report.command_rules.denied_patterns),
+        list(report.command_rules.shell_metacharacters).escape_debug(),
+        list(report.command_rules.read_only_commands)
+    ));
+    out.push_str(&format!(
+        "Path denials:\n- outside workspace root\n- segments: {}\n- file names: {}\n",
+        list(report.path_denials.segments),
+        list(report.path_denials.file_names)
+    ));
+    out
+}
+
+/// `policy show`: the effective policy for every built-in tool and every
+/// tool the configured MCP servers expose.
+pub async fn run_policy_show(cfg: &RuntimeConfig, json: bool) -> anyhow::Result<()> {
+    let mut tools = crate::tools::build_builtin_tools()
+        .iter()
+        .map(|tool| (tool.name().to_string(), ToolOrigin::BuiltIn))
+        .collect::<Vec<_>>();
+    for (_, server_tools) in crate::mcp::discover_mcp_tools_by_server(cfg).await {
+        tools.extend(
+            server_tools
+                .iter()
+                .map(|tool| (tool.name().to_string(), ToolOrigin::Mcp)),
+        );
+    }
+    let report = ToolPolicy::from_config(cfg).report(&tools);
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!("Tool policy (profile '{}'):", cfg.profile);
+        print!("{}", format_tool_policy_report(&report));
+    }
+    Ok(())
+}
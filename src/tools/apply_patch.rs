@@ -0,0 +1,780 @@
+//! `apply_patch`: apply a unified diff that may touch several files.
+//!
+//! The diff is parsed up front; a malformed diff is rejected before any file
+//! is read. Each file is then patched in memory and written only when every
+//! one of its hunks applied, so a file is either fully patched or untouched.
+//! Files are independent: one failing file does not stop the others.
+//!
+//! Hunks are located by their context and removed lines, starting at the
+//! line the header names (shifted by earlier hunks) and searching outward, so
+//! a file that drifted since the diff was made still patches cleanly. Files
+//! with CRLF line endings keep them.
+//!
+//! Added lines are scanned for secrets per target file before anything is
+//! applied (see `secret_scan`); a refusal rejects the whole patch.
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use serde_json::{Value, json};
+
+use super::fs_read::{enforce_workspace_path_policy, fs_read_display_path, fs_read_workspace_root};
+use super::fs_write::resolve_fs_write_path;
+use super::secret_scan::{
+    SecretScanOptions, SecretWriteDecision, decide_secret_write, secret_findings_json,
+};
+use crate::session_bundle::sha256_hex;
+
+const MAX_FILE_SIZE: u64 = 10 * 1024 * 1024; // 10 MB
+const DEV_NULL: &str = "/dev/null";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatchOperation {
+    Create,
+    Delete,
+    Modify,
+}
+
+impl PatchOperation {
+    pub fn label(self) -> &'static str {
+        match self {
+            PatchOperation::Create => "create",
+            PatchOperation::Delete => "delete",
+            PatchOperation::Modify => "modify",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HunkLine {
+    Context(String),
+    Remove(String),
+    Add(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hunk {
+    /// 1-based start line on the old side (0 for an empty old side).
+    pub old_start: usize,
+    pub lines: Vec<HunkLine>,
+    /// `\ No newline at end of file` followed the last old-side line.
+    pub old_missing_newline: bool,
+    /// `\ No newline at end of file` followed the last new-side line.
+    pub new_missing_newline: bool,
+}
+
+impl Hunk {
+    fn old_lines(&self) -> Vec<&str> {
+        self.lines
+            .iter()
+            .filter_map(|line| match line {
+                HunkLine::Context(text) | HunkLine::Remove(text) => Some(text.as_str()),
+                HunkLine::Add(_) => None,
+            })
+            .collect()
+    }
+
+    fn new_lines(&self) -> Vec<&str> {
+        self.lines
+            .iter()
+            .filter_map(|line| match line {
+                HunkLine::Context(text) | HunkLine::Add(text) => Some(text.as_str()),
+                HunkLine::Remove(_) => None,
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilePatch {
+    /// `None` for `/dev/null`.
+    pub old_path: Option<String>,
+    pub new_path: Option<String>,
+    pub operation: PatchOperation,
+    pub hunks: Vec<Hunk>,
+}
+
+impl FilePatch {
+    /// The workspace path this patch writes (or deletes).
+    pub fn path(&self) -> &str {
+        self.new_path
+            .as_deref()
+            .or(self.old_path.as_deref())
+            .unwrap_or_default()
+    }
+}
+
+/// A diff that could not be parsed; `line` is 1-based in the patch text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatchParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for PatchParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+fn parse_error(line: usize, message: impl Into<String>) -> PatchParseError {
+    PatchParseError {
+        line,
+        message: message.into(),
+    }
+}
+
+/// `a/src/lib.rs\t2024-01-01 ...` → `Some("src/lib.rs")`; `/dev/null` → `None`.
+fn parse_header_path(raw: &str) -> Option<String> {
+    let raw = raw.split('\t').next().unwrap_or_default().trim();
+    let raw = raw
+        .strip_prefix('"')
+        .and_then(|quoted| quoted.strip_suffix('"'))
+        .unwrap_or(raw);
+    if raw == DEV_NULL {
+        return None;
+    }
+    let path = raw
+        .strip_prefix("a/")
+        .or_else(|| raw.strip_prefix("b/"))
+        .unwrap_or(raw);
+    Some(path.to_string())
+}
+
+/// `-12,3` → `(12, 3)`; a missing count means 1.
+fn parse_range(raw: &str, sign: char) -> Option<(usize, usize)> {
+    let raw = raw.strip_prefix(sign)?;
+    match raw.split_once(',') {
+        Some((start, len)) => Some((start.parse().ok()?, len.parse().ok()?)),
+        None => Some((raw.parse().ok()?, 1)),
+    }
+}
+
+/// `@@ -1,3 +1,4 @@ fn main()` → old `(1, 3)`, new `(1, 4)`.
+fn parse_hunk_header(line: &str) -> Option<((usize, usize), (usize, usize))> {
+    let rest = line.strip_prefix("@@ ")?;
+    let (ranges, _) = rest.split_once(" @@")?;
+    let (old, new) = ranges.split_once(' ')?;
+    Some((parse_range(old, '-')?, parse_range(new, '+')?))
+}
+
+/// Parse unified diff text (plain `diff -u` or `git diff` output) into one
+/// [`FilePatch`] per file. Git extended headers are skipped.
+pub fn parse_unified_diff(text: &str) -> Result<Vec<FilePatch>, PatchParseError> {
+    let body = text.strip_suffix('\n').unwrap_or(text);
+    let lines = body
+        .split('\n')
+        .map(|line| line.strip_suffix('\r').unwrap_or(line))
+        .collect::<Vec<&str>>();
+    let mut patches = Vec::new();
+    let mut index = 0;
+    while index < lines.len() {
+        let line = lines[index];
+        if line.starts_with("@@") {
+            return Err(parse_error(
+                index + 1,
+                "hunk without a preceding '--- ' / '+++ ' file header",
+            ));
+        }
+        let Some(old_raw) = line.strip_prefix("--- ") else {
+            index += 1;
+            continue;
+        };
+        let Some(new_raw) = lines
+            .get(index + 1)
+            .and_then(|next| next.strip_prefix("+++ "))
+        else {
+            return Err(parse_error(
+                index + 2,
+                "expected '+++ ' after '--- ' header",
+            ));
+        };
+        let old_path = parse_header_path(old_raw);
+        let new_path = parse_header_path(new_raw);
+        let header_line = index + 1;
+        index += 2;
+
+        let operation = match (&old_path, &new_path) {
+            (None, None) => {
+                return Err(parse_error(
+                    header_line,
+                    "both sides of the header are /dev/null",
+                ));
+            }
+            (None, Some(_)) => PatchOperation::Create,
+            (Some(_), None) => PatchOperation::Delete,
+            (Some(old), Some(new)) if old != new => {
+                return Err(parse_error(
+                    header_line,
+                    format!("renames are not supported ('{old}' -> '{new}')"),
+                ));
+            }
+            (Some(_), Some(_)) => PatchOperation::Modify,
+        };
+
+        let mut hunks = Vec::new();
+        while let Some(line) = lines.get(index).copied()
+            && line.starts_with("@@")
+        {
+            let Some(((old_start, mut old_left), (_, mut new_left))) = parse_hunk_header(line)
+            else {
+                return Err(parse_error(
+                    index + 1,
+                    format!("malformed hunk header '{line}'"),
+                ));
+            };
+            let hunk_line = index + 1;
+            index += 1;
+            let mut hunk = Hunk {
+                old_start,
+                lines: Vec::new(),
+                old_missing_newline: false,
+                new_missing_newline: false,
+            };
+            while old_left > 0 || new_left > 0 {
+                let Some(line) = lines.get(index).copied() else {
+                    return Err(parse_error(
+                        hunk_line,
+                        "hunk ends before its line counts are satisfied",
+                    ));
+                };
+                let (marker, body) = match line.chars().next() {
+                    Some(marker) => (marker, &line[marker.len_utf8()..]),
+                    // Editors often strip the single space of an empty context line.
+                    None => (' ', ""),
+                };
+                let parsed = match marker {
+                    ' ' if old_left > 0 && new_left > 0 => {
+                        old_left -= 1;
+                        new_left -= 1;
+                        HunkLine::Context(body.to_string())
+                    }
+                    '-' if old_left > 0 => {
+                        old_left -= 1;
+                        HunkLine::Remove(body.to_string())
+                    }
+                    '+' if new_left > 0 => {
+                        new_left -= 1;
+                        HunkLine::Add(body.to_string())
+                    }
+                    '\\' => {
+                        mark_missing_newline(&mut hunk);
+                        index += 1;
+                        continue;
+                    }
+                    _ => {
+                        return Err(parse_error(
+                            index + 1,
+                            format!(
+                                "unexpected line in hunk at line {hunk_line}; line counts in the \
+                                 '@@' header do not match the hunk body"
+                            ),
+                        ));
+                    }
+                };
+                hunk.lines.push(parsed);
+                index += 1;
+            }
+            while let Some(line) = lines.get(index)
+                && line.starts_with('\\')
+            {
+                mark_missing_newline(&mut hunk);
+                index += 1;
+            }
+            match operation {
+                PatchOperation::Create if !hunk.old_lines().is_empty() => {
+                    return Err(parse_error(hunk_line, "a new-file hunk can only add lines"));
+                }
+                PatchOperation::Delete if !hunk.new_lines().is_empty() => {
+                    return Err(parse_error(
+                        hunk_line,
+                        "a delete-file hunk can only remove lines",
+                    ));
+                }
+                _ => {}
+            }
+            hunks.push(hunk);
+        }
+        if let Some(line) = lines.get(index)
+            && (line.starts_with('+') || line.starts_with(' ') || line.starts_with('-'))
+            && !line.starts_with("--- ")
+        {
+            return Err(parse_error(
+                index + 1,
+                "line after the last hunk looks like hunk content; line counts in the '@@' \
+                 header do not match the hunk body",
+            ));
+        }
+        if hunks.is_empty() && operation == PatchOperation::Modify {
+            return Err(parse_error(header_line, "file header has no hunks"));
+        }
+        patches.push(FilePatch {
+            old_path,
+            new_path,
+            operation,
+            hunks,
+        });
+    }
+    if patches.is_empty() {
+        return Err(parse_error(1, "no '--- ' / '+++ ' file headers found"));
+    }
+    Ok(patches)
+}
+
+/// Apply a `\ No newline at end of file` marker to the side of the last line.
+fn mark_missing_newline(hunk: &mut Hunk) {
+    match hunk.lines.last() {
+        Some(HunkLine::Remove(_)) => hunk.old_missing_newline = true,
+        Some(HunkLine::Add(_)) => hunk.new_missing_newline = true,
+        Some(HunkLine::Context(_)) => {
+            hunk.old_missing_newline = true;
+            hunk.new_missing_newline = true;
+        }
+        None => {}
+    }
+}
+
+/// Why a file's hunks could not be applied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HunkFailure {
+    /// 1-based index of the failing hunk; 0 when the file as a whole failed.
+    pub hunk: usize,
+    pub message: String,
+}
+
+/// Text split into lines, remembering its line ending and final newline.
+struct LineBuffer {
+    lines: Vec<String>,
+    crlf: bool,
+    trailing_newline: bool,
+}
+
+impl LineBuffer {
+    fn parse(text: &str) -> Self {
+        let crlf = text.contains("\r\n");
+        let trailing_newline = text.ends_with('\n');
+        let body = text.strip_suffix('\n').unwrap_or(text);
+        let lines = if text.is_empty() {
+            Vec::new()
+        } else {
+            body.split('\n')
+                .map(|line| line.strip_suffix('\r').unwrap_or(line).to_string())
+                .collect()
+        };
+        Self {
+            lines,
+            crlf,
+            trailing_newline,
+        }
+    }
+
+    fn render(&self) -> String {
+        if self.lines.is_empty() {
+            return String::new();
+        }
+        let ending = if self.crlf { "\r\n" } else { "\n" };
+        let mut out = self.lines.join(ending);
+        if self.trailing_newline {
+            out.push_str(ending);
+        }
+        out
+    }
+}
+
+fn matches_at(lines: &[String], at: usize, expected: &[&str]) -> bool {
+    at + expected.len() <= lines.len()
+        && lines[at..at + expected.len()]
+            .iter()
+            .zip(expected)
+            .all(|(actual, expected)| actual == expected)
+}
+
+/// The match for `expected` nearest to `preferred`, at or after `floor`.
+fn locate(lines: &[String], expected: &[&str], preferred: usize, floor: usize) -> Option<usize> {
+    let last = lines.len().checked_sub(expected.len())?;
+    let preferred = preferred.clamp(floor, last.max(floor));
+    (0..=last.max(preferred))
+        .flat_map(|distance| {
+            let after = preferred.checked_add(distance);
+            let before = preferred.checked_sub(distance).filter(|_| distance > 0);
+            [after, before]
+        })
+        .flatten()
+        .filter(|at| *at >= floor && *at <= last)
+        .find(|at| matches_at(lines, *at, expected))
+}
+
+/// Apply `patch` to `original` (the current file content, `None` when the
+/// file does not exist). Returns the new content, or `None` for a deletion.
+pub fn apply_file_patch(
+    original: Option<&str>,
+    patch: &FilePatch,
+) -> Result<Option<String>, HunkFailure> {
+    let whole_file = |message: String| HunkFailure { hunk: 0, message };
+    let mut buffer = match (patch.operation, original) {
+        (PatchOperation::Create, Some(_)) => {
+            return Err(whole_file("file already exists".to_string()));
+        }
+        (PatchOperation::Create, None) => LineBuffer {
+            lines: Vec::new(),
+            crlf: false,
+            trailing_newline: true,
+        },
+        (_, None) => return Err(whole_file("file does not exist".to_string())),
+        (_, Some(text)) => LineBuffer::parse(text),
+    };
+
+    let mut offset: isize = 0;
+    let mut floor = 0;
+    for (number, hunk) in patch.hunks.iter().enumerate() {
+        let old = hunk.old_lines();
+        let new = hunk.new_lines();
+        let preferred = (hunk.old_start.saturating_sub(1) as isize + offset).max(0) as usize;
+        let at = if old.is_empty() {
+            // Pure insertion: `-N,0` means "after line N".
+            let at = (hunk.old_start as isize + offset).max(0) as usize;
+            Some(at).filter(|at| *at >= floor && *at <= buffer.lines.len())
+        } else {
+            locate(&buffer.lines, &old, preferred, floor)
+        };
+        let Some(at) = at else {
+            let expected = old.first().copied().unwrap_or_default();
+            return Err(HunkFailure {
+                hunk: number + 1,
+                message: format!(
+                    "context mismatch: hunk {} (@@ -{} @@) does not match the file; expected \
+                     '{expected}' near line {}",
+                    number + 1,
+                    hunk.old_start,
+                    preferred + 1
+                ),
+            });
+        };
+        let reaches_end = at + old.len() == buffer.lines.len();
+        buffer
+            .lines
+            .splice(at..at + old.len(), new.iter().map(|line| line.to_string()));
+        if reaches_end {
+            buffer.trailing_newline = !hunk.new_missing_newline;
+        }
+        offset += new.len() as isize - old.len() as isize;
+        floor = at + new.len();
+    }
+
+    if patch.operation == PatchOperation::Delete {
+        if !buffer.lines.is_empty() {
+            return Err(whole_file(format!(
+                "file still has {} line(s) the delete patch does not remove",
+                buffer.lines.len()
+            )));
+        }
+        return Ok(None);
+    }
+    Ok(Some(buffer.render()))
+}
+
+/// Per-file outcome reported to the model.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilePatchResult {
+    pub path: String,
+    pub operation: PatchOperation,
+    pub applied: bool,
+    pub hunks_applied: usize,
+    pub hunks_total: usize,
+    /// SHA-256 of the resulting content; `None` after a delete or a failure.
+    pub sha256: Option<String>,
+    pub code: Option<&'static str>,
+    pub error: Option<String>,
+    pub failed_hunk: Option<usize>,
+}
+
+impl FilePatchResult {
+    fn failed(patch: &FilePatch, code: &'static str, failure: HunkFailure) -> Self {
+        Self {
+            path: patch.path().to_string(),
+            operation: patch.operation,
+            applied: false,
+            hunks_applied: 0,
+            hunks_total: patch.hunks.len(),
+            sha256: None,
+            code: Some(code),
+            error: Some(failure.message),
+            failed_hunk: (failure.hunk > 0).then_some(failure.hunk),
+        }
+    }
+
+    fn to_json(&self, dry_run: bool) -> Value {
+        let status = match (self.applied, dry_run) {
+            (false, _) => "failed",
+            (true, false) => "applied",
+            (true, true) => "would_apply",
+        };
+        let mut out = json!({
+            "path": self.path,
+            "operation": self.operation.label(),
+            "status": status,
+            "hunks_applied": self.hunks_applied,
+            "hunks_total": self.hunks_total,
+            "sha256": self.sha256,
+        });
+        if let Some(error) = &self.error {
+            out["code"] = json!(self.code);
+            out["error"] = json!(error);
+            out["failed_hunk"] = json!(self.failed_hunk);
+        }
+        out
+    }
+}
+
+fn read_target(resolved: &Path) -> Result<Option<String>, HunkFailure> {
+    let failure = |message: String| HunkFailure { hunk: 0, message };
+    let Ok(meta) = std::fs::metadata(resolved) else {
+        return Ok(None);
+    };
+    if meta.is_dir() {
+        return Err(failure("path is a directory".to_string()));
+    }
+    if meta.len() > MAX_FILE_SIZE {
+        return Err(failure(format!(
+            "file exceeds 10MB limit ({}MB)",
+            meta.len() / (1024 * 1024)
+        )));
+    }
+    std::fs::read_to_string(resolved)
+        .map(Some)
+        .map_err(|_| failure("file is not valid UTF-8 text".to_string()))
+}
+
+fn write_target(resolved: &Path, content: Option<&str>) -> std::io::Result<()> {
+    let _ = crate::file_history::snapshot_file(resolved);
+    match content {
+        Some(content) => {
+            if let Some(parent) = resolved.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(resolved, content.as_bytes())
+        }
+        None => std::fs::remove_file(resolved),
+    }
+}
+
+/// Patch one file: resolve and check its path, apply every hunk in memory,
+/// then write (unless `dry_run`).
+fn apply_to_workspace(patch: &FilePatch, workspace_root: &Path, dry_run: bool) -> FilePatchResult {
+    let path = patch.path();
+    let resolved: PathBuf = match resolve_fs_write_path(workspace_root, path) {
+        Ok(resolved) => resolved,
+        Err(_) => {
+            let failure = HunkFailure {
+                hunk: 0,
+                message: format!("path '{path}' could not be resolved"),
+            };
+            return FilePatchResult::failed(patch, "invalid_path", failure);
+        }
+    };
+    if let Err(err) = enforce_workspace_path_policy(path, &resolved, workspace_root) {
+        let failure = HunkFailure {
+            hunk: 0,
+            message: err.message,
+        };
+        return FilePatchResult::failed(patch, err.code, failure);
+    }
+
+    let original = match read_target(&resolved) {
+        Ok(original) => original,
+        Err(failure) => return FilePatchResult::failed(patch, "io_error", failure),
+    };
+    let updated = match apply_file_patch(original.as_deref(), patch) {
+        Ok(updated) => updated,
+        Err(failure) => return FilePatchResult::failed(patch, "patch_rejected", failure),
+    };
+
+    if !dry_run {
+        if let Err(err) = write_target(&resolved, updated.as_deref()) {
+            let failure = HunkFailure {
+                hunk: 0,
+                message: format!("failed to write '{path}': {err}"),
+            };
+            return FilePatchResult::failed(patch, "io_error", failure);
+        }
+        crate::change_log::record_tool_change(crate::change_log::ToolChange::FileWrite {
+            display_path: fs_read_display_path(&resolved, workspace_root),
+            path: resolved,
+            mode: patch.operation.label().to_string(),
+            before: original.map(String::into_bytes),
+            after: updated.clone().map(String::into_bytes),
+        });
+    }
+
+    FilePatchResult {
+        path: path.to_string(),
+        operation: patch.operation,
+        applied: true,
+        hunks_applied: patch.hunks.len(),
+        hunks_total: patch.hunks.len(),
+        sha256: updated
+            .as_deref()
+            .map(|content| sha256_hex(content.as_bytes())),
+        code: None,
+        error: None,
+        failed_hunk: None,
+    }
+}
+
+fn error_payload(code: &str, message: impl Into<String>) -> Value {
+    json!({
+        "status": "error",
+        "kind": "apply_patch",
+        "code": code,
+        "error": message.into(),
+    })
+}
+
+/// Line indexes of the lines each target file gains, in header order.
+fn added_lines_by_file(lines: &[&str]) -> Vec<(String, Vec<usize>)> {
+    let mut files = Vec::<(String, Vec<usize>)>::new();
+    let mut current = None::<usize>;
+    for (index, line) in lines.iter().enumerate() {
+        if let Some(raw) = line.strip_prefix("+++ ")
+            && index > 0
+            && lines[index - 1].starts_with("--- ")
+        {
+            current = parse_header_path(raw).map(|path| {
+                files.push((path, Vec::new()));
+                files.len() - 1
+            });
+            continue;
+        }
+        if line.starts_with('+')
+            && let Some(file) = current
+        {
+            files[file].1.push(index);
+        }
+    }
+    files
+}
+
+/// Scan the added lines of `patch_text` for secrets. Returns the patch to
+/// apply (redacted under the redact policy) and the `secrets_redacted`
+/// report, or the error payload of a refusal.
+fn screen_patch_secrets(
+    patch_text: &str,
+    args: &Value,
+    options: &SecretScanOptions,
+) -> Result<(String, Vec<Value>), Value> {
+    let lines = patch_text.split('\n').collect::<Vec<&str>>();
+    let mut out = lines.iter().map(|line| line.to_string()).collect::<Vec<String>>();
+    let mut redactions = Vec::new();
+    for (path, added) in added_lines_by_file(&lines) {
+        if added.is_empty() {
+            continue;
+        }
+        // Other lines are blanked so finding lines are patch lines.
+        let mut masked = vec![""; lines.len()];
+        for &index in &added {
+            masked[index] = &lines[index][1..];
+        }
+        let content = masked.join("\n");
+        match decide_secret_write(options, "apply_patch", &path, &content, args) {
+            SecretWriteDecision::Refuse { message, findings } => {
+                let mut payload = error_payload("secret_detected", message);
+                payload["path"] = json!(path);
+                payload["findings"] = secret_findings_json(&findings);
+                return Err(payload);
+            }
+            SecretWriteDecision::Write { content, redacted } => {
+                if redacted.is_empty() {
+                    continue;
+                }
+                let redacted_lines = content.split('\n').collect::<Vec<&str>>();
+                for &index in &added {
+                    out[index] = format!("+{}", redacted_lines[index]);
+                }
+                redactions.push(json!({
+                    "path": path,
+                    "findings": secret_findings_json(&redacted),
+                }));
+            }
+        }
+    }
+    Ok((out.join("\n"), redactions))
+}
+
+pub fn apply_patch_tool_response_with_root(args: &Value, workspace_root: &Path) -> Value {
+    apply_patch_tool_response_with_options(args, workspace_root, &SecretScanOptions::default())
+}
+
+pub fn apply_patch_tool_response_with_options(
+    args: &Value,
+    workspace_root: &Path,
+    options: &SecretScanOptions,
+) -> Value {
+    let Some(patch_text) = args
+        .get("patch")
+        .and_then(Value::as_str)
+        .filter(|text| !text.trim().is_empty())
+    else {
+        return error_payload("invalid_args", "'patch' is required for apply_patch");
+    };
+    let dry_run = args
+        .get("dry_run")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    let (patch_text, redactions) = match screen_patch_secrets(patch_text, args, options) {
+        Ok(screened) => screened,
+        Err(payload) => return payload,
+    };
+    let patches = match parse_unified_diff(&patch_text) {
+        Ok(patches) => patches,
+        Err(err) => return error_payload("malformed_patch", format!("malformed patch: {err}")),
+    };
+
+    let mut seen = Vec::<&str>::new();
+    let results = patches
+        .iter()
+        .map(|patch| {
+            if seen.contains(&patch.path()) {
+                let failure = HunkFailure {
+                    hunk: 0,
+                    message: "file appears more than once in the patch; merge its hunks"
+                        .to_string(),
+                };
+                return FilePatchResult::failed(patch, "malformed_patch", failure);
+            }
+            seen.push(patch.path());
+            apply_to_workspace(patch, workspace_root, dry_run)
+        })
+        .collect::<Vec<FilePatchResult>>();
+
+    let applied = results.iter().filter(|result| result.applied).count();
+    let status = match applied {
+        n if n == results.len() => "ok",
+        0 => "error",
+        _ => "partial",
+    };
+    let mut payload = json!({
+        "status": status,
+        "kind": "apply_patch",
+        "dry_run": dry_run,
+        "files_applied": applied,
+        "files_failed": results.len() - applied,
+        "files": results
+            .iter()
+            .map(|result| result.to_json(dry_run))
+            .collect::<Vec<Value>>(),
+    });
+    if !redactions.is_empty() {
+        payload["secrets_redacted"] = json!(redactions);
+    }
+    payload
+}
+
+pub fn apply_patch_tool_response(args: &Value) -> Value {
+    apply_patch_tool_response_with(args, &SecretScanOptions::default())
+}
+
+pub fn apply_patch_tool_response_with(args: &Value, options: &SecretScanOptions) -> Value {
+    match fs_read_workspace_root() {
+        Ok(root) => apply_patch_tool_response_with_options(args, &root, options),
+        Err(err) => error_payload(err.code, err.message),
+    }
+}
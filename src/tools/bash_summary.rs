@@ -0,0 +1,455 @@
+//! Structured summaries of `execute_bash` output (`execute_bash_summarize`).
+//!
+//! Test runners and git print far more than a model needs, and the counts it
+//! does need sit at the end of output that `max_output_chars` may cut. For a
+//! recognized command family, [`summarize_command_output`] reads the full
+//! captured output and returns a small JSON summary that rides alongside the
+//! truncated raw text. Each family has a pure parser over the combined
+//! stdout and stderr; unrecognized commands, and output a parser finds no
+//! signal in, get no summary.
+use serde_json::{Value, json};
+
+/// Upper bound on a serialized summary.
+pub const BASH_SUMMARY_MAX_CHARS: usize = 1024;
+
+const MAX_LISTED_NAMES: usize = 10;
+const MAX_NAME_CHARS: usize = 160;
+const MAX_ERROR_EXCERPT_LINES: usize = 8;
+const MAX_ERROR_EXCERPT_CHARS: usize = 400;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandFamily {
+    CargoTest,
+    CargoBuild,
+    GitStatus,
+    GitDiffStat,
+    Pytest,
+    NodeTest,
+}
+
+impl CommandFamily {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::CargoTest => "cargo_test",
+            Self::CargoBuild => "cargo_build",
+            Self::GitStatus => "git_status",
+            Self::GitDiffStat => "git_diff_stat",
+            Self::Pytest => "pytest",
+            Self::NodeTest => "node_test",
+        }
+    }
+}
+
+/// Family of the last recognized command in `command`, looking through
+/// `&&`, `||`, `;` and `|` chains and leading `VAR=value` assignments.
+pub fn command_family(command: &str) -> Option<CommandFamily> {
+    command
+        .rsplit(['&', '|', ';', '\n'])
+        .find_map(segment_family)
+}
+
+fn segment_family(segment: &str) -> Option<CommandFamily> {
+    let words = segment
+        .split_whitespace()
+        .skip_while(|word| is_env_assignment(word))
+        .collect::<Vec<&str>>();
+    let (program, args) = words.split_first()?;
+    let program = program.rsplit('/').next().unwrap_or(program);
+    let subcommand = || {
+        args.iter()
+            .copied()
+            .find(|arg| !arg.starts_with('-') && !arg.starts_with('+'))
+    };
+    match program {
+        "cargo" => match subcommand()? {
+            "test" | "t" | "nextest" => Some(CommandFamily::CargoTest),
+            "build" | "b" | "check" | "c" | "clippy" => Some(CommandFamily::CargoBuild),
+            _ => None,
+        },
+        "git" => match git_subcommand(args)? {
+            "status" => Some(CommandFamily::GitStatus),
+            "diff"
+                if args
+                    .iter()
+                    .any(|arg| *arg == "--stat" || *arg == "--shortstat") =>
+            {
+                Some(CommandFamily::GitDiffStat)
+            }
+            _ => None,
+        },
+        "pytest" | "py.test" => Some(CommandFamily::Pytest),
+        "python" | "python3" if args.windows(2).any(|pair| pair == ["-m", "pytest"]) => {
+            Some(CommandFamily::Pytest)
+        }
+        "npm" | "yarn" | "pnpm" => match subcommand()? {
+            "test" | "t" => Some(CommandFamily::NodeTest),
+            "run" if args.contains(&"test") => Some(CommandFamily::NodeTest),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn is_env_assignment(word: &str) -> bool {
+    word.split_once('=').is_some_and(|(name, _)| {
+        !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    })
+}
+
+/// First git subcommand, skipping global options and the values of `-C`/`-c`.
+fn git_subcommand<'a>(args: &[&'a str]) -> Option<&'a str> {
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match *arg {
+            "-C" | "-c" | "--git-dir" | "--work-tree" => {
+                args.next();
+            }
+            arg if arg.starts_with('-') => {}
+            arg => return Some(arg),
+        }
+    }
+    None
+}
+
+/// Summary for `command`'s output, or `None` when the command is not a
+/// recognized family or its output carries nothing to summarize.
+pub fn summarize_command_output(command: &str, stdout: &str, stderr: &str) -> Option<Value> {
+    let family = command_family(command)?;
+    let output = format!("{stdout}\n{stderr}");
+    let summary = match family {
+        CommandFamily::CargoTest => summarize_cargo_test(&output),
+        CommandFamily::CargoBuild => summarize_cargo_build(&output),
+        CommandFamily::GitStatus => summarize_git_status(&output),
+        CommandFamily::GitDiffStat => summarize_git_diff_stat(&output),
+        CommandFamily::Pytest => summarize_pytest(&output),
+        CommandFamily::NodeTest => summarize_node_test(&output),
+    }?;
+    Some(cap_summary(summary))
+}
+
+/// `cargo test`: totals across every `test result:` line (unit, integration
+/// and doc tests), failing test names, and compile errors when the build
+/// failed before any test ran.
+pub fn summarize_cargo_test(output: &str) -> Option<Value> {
+    let mut totals = (0u64, 0u64, 0u64);
+    let mut saw_result = false;
+    for line in output.lines() {
+        let Some(rest) = line.trim().strip_prefix("test result: ") else {
+            continue;
+        };
+        saw_result = true;
+        let counts = rest.split_once(". ").map_or(rest, |(_, counts)| counts);
+        totals.0 += count_before(counts, "passed");
+        totals.1 += count_before(counts, "failed");
+        totals.2 += count_before(counts, "ignored");
+    }
+    let failing = output
+        .lines()
+        .filter_map(|line| line.strip_prefix("test "))
+        .filter_map(|line| line.strip_suffix(" ... FAILED"))
+        .map(str::to_string)
+        .collect::<Vec<String>>();
+    let (errors, first_error) = rust_compile_errors(output);
+    if !saw_result && errors == 0 {
+        return None;
+    }
+    let mut summary = json!({
+        "family": CommandFamily::CargoTest.as_str(),
+        "passed": totals.0,
+        "failed": totals.1,
+        "ignored": totals.2,
+        "failing_tests": failing,
+    });
+    if errors > 0 {
+        summary["compile_errors"] = json!(errors);
+        summary["first_error"] = json!(first_error);
+    }
+    Some(summary)
+}
+
+/// `cargo build`/`check`/`clippy`: error and warning counts with the first
+/// error's excerpt.
+pub fn summarize_cargo_build(output: &str) -> Option<Value> {
+    let (errors, first_error) = rust_compile_errors(output);
+    let warnings = output
+        .lines()
+        .filter(|line| line.starts_with("warning:") || line.starts_with("warning["))
+        .filter(|line| !line.contains(" generated ") && !line.contains("build failed"))
+        .count();
+    let finished = output
+        .lines()
+        .any(|line| line.trim().starts_with("Finished "));
+    if errors == 0 && warnings == 0 && !finished {
+        return None;
+    }
+    Some(json!({
+        "family": CommandFamily::CargoBuild.as_str(),
+        "compile_errors": errors,
+        "warnings": warnings,
+        "first_error": first_error,
+    }))
+}
+
+/// Error count (from cargo's `due to N previous errors` when present) and an
+/// excerpt of the first diagnostic.
+fn rust_compile_errors(output: &str) -> (u64, Option<String>) {
+    let is_diagnostic = |line: &str| {
+        (line.starts_with("error:") || line.starts_with("error["))
+            && !line.starts_with("error: could not compile")
+            && !line.starts_with("error: test failed")
+            && !line.starts_with("error: aborting")
+    };
+    let counted = output.lines().filter(|line| is_diagnostic(line)).count() as u64;
+    let reported = output
+        .lines()
+        .filter_map(|line| line.split_once(" due to ").map(|(_, rest)| rest))
+        .map(|rest| count_before(rest, "previous"))
+        .sum::<u64>();
+    let first_error = output.lines().position(is_diagnostic).map(|start| {
+        let excerpt = output
+            .lines()
+            .skip(start)
+            .take_while(|line| !line.trim().is_empty())
+            .take(MAX_ERROR_EXCERPT_LINES)
+            .collect::<Vec<&str>>()
+            .join("\n");
+        clip(&excerpt, MAX_ERROR_EXCERPT_CHARS)
+    });
+    (reported.max(counted), first_error)
+}
+
+/// `git status`, long or `--short`/`--porcelain` form.
+pub fn summarize_git_status(output: &str) -> Option<Value> {
+    let mut counts = [0u64; 4];
+    let branch = output
+        .lines()
+        .find_map(|line| line.strip_prefix("On branch "))
+        .map(str::to_string);
+    if branch.is_some() || output.contains("HEAD detached") {
+        let mut section = None;
+        for line in output.lines() {
+            section = match line {
+                "Changes to be committed:" => Some(0),
+                "Changes not staged for commit:" => Some(1),
+                "Untracked files:" => Some(2),
+                "Unmerged paths:" => Some(3),
+                "" => None,
+                _ => section,
+            };
+            if let Some(index) = section
+                && line.starts_with('\t')
+            {
+                counts[index] += 1;
+            }
+        }
+    } else {
+        let mut saw_entry = false;
+        for line in output.lines() {
+            let mut codes = line.chars();
+            let (Some(x), Some(y), Some(' ')) = (codes.next(), codes.next(), codes.next()) else {
+                continue;
+            };
+            if !"MADRCU?! ".contains(x) || !"MADRCU?! ".contains(y) {
+                continue;
+            }
+            saw_entry = true;
+            match (x, y) {
+                ('?', '?') => counts[2] += 1,
+                ('!', '!') => {}
+                ('U', _) | (_, 'U') | ('A', 'A') | ('D', 'D') => counts[3] += 1,
+                _ => {
+                    counts[0] += u64::from(x != ' ');
+                    counts[1] += u64::from(y != ' ');
+                }
+            }
+        }
+        if !saw_entry && !output.trim().is_empty() {
+            return None;
+        }
+    }
+    Some(json!({
+        "family": CommandFamily::GitStatus.as_str(),
+        "branch": branch,
+        "staged": counts[0],
+        "unstaged": counts[1],
+        "untracked": counts[2],
+        "conflicted": counts[3],
+        "clean": counts.iter().all(|count| *count == 0),
+    }))
+}
+
+/// `git diff --stat`/`--shortstat`: the closing `N files changed` line.
+/// Empty output means an empty diff.
+pub fn summarize_git_diff_stat(output: &str) -> Option<Value> {
+    let totals = output
+        .lines()
+        .rev()
+        .find(|line| line.contains(" changed"))
+        .map(|line| {
+            (
+                count_before(line, "file"),
+                count_before(line, "insertion"),
+                count_before(line, "deletion"),
+            )
+        });
+    if totals.is_none() && !output.trim().is_empty() {
+        return None;
+    }
+    let (files, insertions, deletions) = totals.unwrap_or_default();
+    Some(json!({
+        "family": CommandFamily::GitDiffStat.as_str(),
+        "files_changed": files,
+        "insertions": insertions,
+        "deletions": deletions,
+    }))
+}
+
+/// `pytest`: the closing `=== 1 failed, 3 passed in 0.03s ===` line and the
+/// `FAILED`/`ERROR` entries of the short test summary.
+pub fn summarize_pytest(output: &str) -> Option<Value> {
+    let totals = output.lines().rev().find(|line| {
+        let line = line.trim().trim_matches('=').trim();
+        (line.contains(" in ") && (line.contains("passed") || line.contains("failed")))
+            || line.starts_with("no tests ran")
+    })?;
+    let failing = output
+        .lines()
+        .filter_map(|line| {
+            line.strip_prefix("FAILED ")
+                .or_else(|| line.strip_prefix("ERROR "))
+        })
+        .map(|entry| {
+            entry
+                .split(" - ")
+                .next()
+                .unwrap_or(entry)
+                .trim()
+                .to_string()
+        })
+        .collect::<Vec<String>>();
+    Some(json!({
+        "family": CommandFamily::Pytest.as_str(),
+        "passed": count_before(totals, "passed"),
+        "failed": count_before(totals, "failed"),
+        "skipped": count_before(totals, "skipped"),
+        "errors": count_before(totals, "error"),
+        "failing_tests": failing,
+    }))
+}
+
+/// `npm test`/`yarn test`: the `node --test` trailer (`# pass 2` from the TAP
+/// reporter, `ℹ pass 2` from the spec one) or a Jest/Vitest `Tests:` line.
+pub fn summarize_node_test(output: &str) -> Option<Value> {
+    let tap_count = |key: &str| {
+        output
+            .lines()
+            .filter_map(|line| {
+                let line = line.trim();
+                line.strip_prefix("# ").or_else(|| line.strip_prefix("ℹ "))
+            })
+            .find_map(|line| line.strip_prefix(key)?.strip_prefix(' '))
+            .and_then(|count| count.trim().parse::<u64>().ok())
+    };
+    let (passed, failed, skipped, failing) = if let Some(passed) = tap_count("pass") {
+        let failing = output
+            .lines()
+            .filter_map(|line| line.strip_prefix("not ok "))
+            .map(|rest| rest.split_once(" - ").map_or(rest, |(_, name)| name))
+            .map(|name| name.split(" # ").next().unwrap_or(name).to_string())
+            .collect::<Vec<String>>();
+        (
+            passed,
+            tap_count("fail").unwrap_or_default(),
+            tap_count("skipped").unwrap_or_default(),
+            failing,
+        )
+    } else {
+        let totals = output
+            .lines()
+            .rev()
+            .find_map(|line| line.trim().strip_prefix("Tests:"))?;
+        let failing = output
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix("● "))
+            .filter(|name| name.contains(" › "))
+            .map(str::to_string)
+            .collect::<Vec<String>>();
+        (
+            count_before(totals, "passed"),
+            count_before(totals, "failed"),
+            count_before(totals, "skipped"),
+            failing,
+        )
+    };
+    Some(json!({
+        "family": CommandFamily::NodeTest.as_str(),
+        "passed": passed,
+        "failed": failed,
+        "skipped": skipped,
+        "failing_tests": failing,
+    }))
+}
+
+/// The number directly before the first word starting with `label` in a
+/// `1 passed; 2 failed` or `3 files changed, 4 insertions(+)` list.
+fn count_before(text: &str, label: &str) -> u64 {
+    let words = text
+        .split(|c: char| c.is_whitespace() || c == ',' || c == ';')
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<&str>>();
+    words
+        .windows(2)
+        .find(|pair| pair[1].starts_with(label))
+        .and_then(|pair| pair[0].parse::<u64>().ok())
+        .unwrap_or_default()
+}
+
+fn clip(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let mut clipped = text.chars().take(max_chars).collect::<String>();
+    clipped.push('…');
+    clipped
+}
+
+/// Bound a summary to [`BASH_SUMMARY_MAX_CHARS`]: long names are clipped,
+/// the failing list keeps its first entries with an `omitted` count, and the
+/// error excerpt goes last if that is still not enough.
+fn cap_summary(mut summary: Value) -> Value {
+    if let Some(names) = summary
+        .get_mut("failing_tests")
+        .and_then(Value::as_array_mut)
+    {
+        for name in names.iter_mut() {
+            if let Some(text) = name.as_str() {
+                *name = json!(clip(text, MAX_NAME_CHARS));
+            }
+        }
+        let omitted = names.len().saturating_sub(MAX_LISTED_NAMES);
+        names.truncate(MAX_LISTED_NAMES);
+        if omitted > 0 {
+            summary["failing_tests_omitted"] = json!(omitted);
+        }
+    }
+    while summary.to_string().len() > BASH_SUMMARY_MAX_CHARS {
+        let Some(names) = summary
+            .get_mut("failing_tests")
+            .and_then(Value::as_array_mut)
+            .filter(|names| !names.is_empty())
+        else {
+            break;
+        };
+        names.pop();
+        let omitted = summary["failing_tests_omitted"]
+            .as_u64()
+            .unwrap_or_default();
+        summary["failing_tests_omitted"] = json!(omitted + 1);
+    }
+    if summary.to_string().len() > BASH_SUMMARY_MAX_CHARS
+        && let Some(object) = summary.as_object_mut()
+    {
+        object.remove("first_error");
+    }
+    summary
+}
@@ -0,0 +1,218 @@
+//! Per-prompt tool call budgets (`max_tool_calls_per_prompt`,
+//! `[tool_call_budgets]`).
+//!
+//! Every runtime tool is wrapped with [`BudgetedTool`]. Calls are counted in a
+//! [`ToolCallCounter`] stored on `RuntimeConfig`, which the prompt runners
+//! re-arm before each runner invocation, so a chat session gets a fresh budget
+//! every turn. Once a budget is spent the tool is not executed; the model gets
+//! a `budget_exhausted` payload instead and can answer with what it has.
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+
+use adk_rust::Result as AdkResult;
+use adk_rust::prelude::*;
+use async_trait::async_trait;
+use serde_json::{Value, json};
+
+use crate::telemetry::TelemetrySink;
+use crate::tool_policy::matches_wildcard;
+
+/// Budget limits; 0 or a missing entry means unlimited.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ToolBudgets {
+    pub max_calls_per_prompt: u32,
+    /// Tool name (or `*` pattern) → calls allowed per prompt.
+    pub per_tool: BTreeMap<String, u32>,
+}
+
+impl ToolBudgets {
+    pub fn is_empty(&self) -> bool {
+        self.max_calls_per_prompt == 0 && self.per_tool.values().all(|limit| *limit == 0)
+    }
+
+    /// The budget for `tool`: an exact entry wins, then the longest wildcard.
+    pub fn tool_limit(&self, tool: &str) -> Option<(&str, u32)> {
+        self.per_tool
+            .get_key_value(tool)
+            .or_else(|| {
+                self.per_tool
+                    .iter()
+                    .filter(|(pattern, _)| pattern.contains('*'))
+                    .filter(|(pattern, _)| matches_wildcard(pattern, tool))
+                    .max_by_key(|(pattern, _)| pattern.len())
+            })
+            .filter(|(_, limit)| **limit > 0)
+            .map(|(pattern, limit)| (pattern.as_str(), *limit))
+    }
+}
+
+#[derive(Debug, Default)]
+struct CounterState {
+    total: u32,
+    /// Keyed by budget pattern, so a wildcard budget is shared by its tools.
+    per_budget: HashMap<String, u32>,
+}
+
+/// Calls made in the current runner invocation. Clones share the count.
+#[derive(Debug, Clone, Default)]
+pub struct ToolCallCounter(Arc<Mutex<CounterState>>);
+
+/// Which budget stopped a call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BudgetExhausted {
+    /// `max_tool_calls_per_prompt`, or the per-tool pattern.
+    pub budget: String,
+    pub limit: u32,
+}
+
+impl ToolCallCounter {
+    /// Start a new prompt: every budget is available again.
+    pub fn rearm(&self) {
+        let mut state = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        *state = CounterState::default();
+    }
+
+    /// Count a call to `tool`, or report the budget it would exceed. Refused
+    /// calls are not counted.
+    pub fn try_acquire(&self, tool: &str, budgets: &ToolBudgets) -> Result<(), BudgetExhausted> {
+        let mut state = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let tool_limit = budgets.tool_limit(tool);
+        if let Some((pattern, limit)) = tool_limit
+            && state.per_budget.get(pattern).copied().unwrap_or(0) >= limit
+        {
+            return Err(BudgetExhausted {
+                budget: pattern.to_string(),
+                limit,
+            });
+        }
+        if budgets.max_calls_per_prompt > 0 && state.total >= budgets.max_calls_per_prompt {
+            return Err(BudgetExhausted {
+                budget: "max_tool_calls_per_prompt".to_string(),
+                limit: budgets.max_calls_per_prompt,
+            });
+        }
+        state.total += 1;
+        if let Some((pattern, _)) = tool_limit {
+            *state.per_budget.entry(pattern.to_string()).or_default() += 1;
+        }
+        Ok(())
+    }
+}
+
+/// Returned to the model in place of the tool's result.
+pub fn budget_exhausted_payload(tool: &str, exhausted: &BudgetExhausted) -> Value {
+    json!({
+        "status": "error",
+        "kind": tool,
+        "code": "budget_exhausted",
+        "error": format!(
+            "tool call budget '{}' ({} per prompt) is exhausted; {tool} was not run",
+            exhausted.budget, exhausted.limit
+        ),
+        "budget": exhausted.budget,
+        "limit": exhausted.limit,
+        "retry_hint": "Do not call this tool again for this request. Answer with the \
+                       information already gathered, or say what is missing.",
+    })
+}
+
+/// Wraps any tool and enforces the per-prompt budgets.
+pub struct BudgetedTool {
+    inner: Arc<dyn Tool>,
+    budgets: Arc<ToolBudgets>,
+    counter: ToolCallCounter,
+    telemetry: TelemetrySink,
+}
+
+#[async_trait]
+impl Tool for BudgetedTool {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn description(&self) -> &str {
+        self.inner.description()
+    }
+
+    fn enhanced_description(&self) -> String {
+        self.inner.enhanced_description()
+    }
+
+    fn is_long_running(&self) -> bool {
+        self.inner.is_long_running()
+    }
+
+    fn parameters_schema(&self) -> Option<Value> {
+        self.inner.parameters_schema()
+    }
+
+    fn response_schema(&self) -> Option<Value> {
+        self.inner.response_schema()
+    }
+
+    async fn execute(&self, ctx: Arc<dyn ToolContext>, args: Value) -> AdkResult<Value> {
+        let name = self.inner.name();
+        if let Err(exhausted) = self.counter.try_acquire(name, &self.budgets) {
+            tracing::warn!(tool = name, budget = %exhausted.budget, "Tool call budget exhausted");
+            self.telemetry.emit(
+                "tool.budget_exhausted",
+                json!({
+                    "tool": name,
+                    "budget": exhausted.budget,
+                    "limit": exhausted.limit,
+                }),
+            );
+            return Ok(budget_exhausted_payload(name, &exhausted));
+        }
+        self.inner.execute(ctx, args).await
+    }
+}
+
+/// Wrap every tool with [`BudgetedTool`]. No-op when no budget is set.
+pub fn wrap_with_tool_budgets(
+    tools: Vec<Arc<dyn Tool>>,
+    budgets: &ToolBudgets,
+    counter: &ToolCallCounter,
+    telemetry: &TelemetrySink,
+) -> Vec<Arc<dyn Tool>> {
+    if budgets.is_empty() {
+        return tools;
+    }
+    let budgets = Arc::new(budgets.clone());
+    tools
+        .into_iter()
+        .map(|tool| {
+            Arc::new(BudgetedTool {
+                inner: tool,
+                budgets: budgets.clone(),
+                counter: counter.clone(),
+                telemetry: telemetry.clone(),
+            }) as Arc<dyn Tool>
+        })
+        .collect()
+}
+
+/// `/tools` and `profiles show` line for the budgets.
+pub fn format_tool_budgets(budgets: &ToolBudgets) -> String {
+    if budgets.is_empty() {
+        return "Tool call budgets: none".to_string();
+    }
+    let total = match budgets.max_calls_per_prompt {
+        0 => "unlimited".to_string(),
+        limit => limit.to_string(),
+    };
+    let per_tool = budgets
+        .per_tool
+        .iter()
+        .filter(|(_, limit)| **limit > 0)
+        .map(|(pattern, limit)| format!("{pattern}={limit}"))
+        .collect::<Vec<String>>();
+    if per_tool.is_empty() {
+        format!("Tool call budgets: {total} calls per prompt")
+    } else {
+        format!(
+            "Tool call budgets: {total} calls per prompt; {}",
+            per_tool.join(", ")
+        )
+    }
+}
@@ -51,6 +51,19 @@ fn display_result(tool_name: &str, result: &Value) {
                 }
             }
         }
+        "apply_patch" => {
+            let files = result.get("files").and_then(|v| v.as_array());
+            for file in files.into_iter().flatten() {
+                let path = file.get("path").and_then(|v| v.as_str()).unwrap_or("?");
+                match file.get("error").and_then(|v| v.as_str()) {
+                    Some(err) => eprintln!("{RED}  ✗ {path}: {err}{RESET}"),
+                    None => eprintln!("{DIM}  ✓ patched {path}{RESET}"),
+                }
+            }
+            if let Some(err) = result.get("error").and_then(|v| v.as_str()) {
+                eprintln!("{RED}{err}{RESET}");
+            }
+        }
         "fs_write" => {
             let path = result.get("path").and_then(|v| v.as_str()).unwrap_or("");
             if result.get("error").is_some() {
@@ -115,6 +128,27 @@ impl ConfirmingTool {
     }
 }
 
+/// Color an `apply_patch` diff for the confirmation dialog.
+fn format_apply_patch_preview(args: &Value) -> String {
+    let patch = args.get("patch").and_then(|v| v.as_str()).unwrap_or("");
+    let mut out = String::new();
+    for line in patch.lines() {
+        let color = if line.starts_with("+++") || line.starts_with("---") {
+            BOLD
+        } else if line.starts_with('+') {
+            GREEN
+        } else if line.starts_with('-') {
+            RED
+        } else if line.starts_with("@@") {
+            CYAN
+        } else {
+            DIM
+        };
+        out.push_str(&format!("{color}{line}{RESET}\n"));
+    }
+    out
+}
+
 /// Format a file diff for the confirmation dialog with syntax highlighting.
 fn format_fs_write_diff(args: &Value) -> String {
     let path = args.get("path").and_then(|v| v.as_str()).unwrap_or("?");
@@ -206,14 +240,11 @@ fn render_diff(old: &str, new: &str, hl: &mut Option<()>) -> String {
     out
 }
 
-/// Format generic tool args for display.
+/// Format generic tool args for display, with the same redacted summary as
+/// the chat tool annotations.
 fn format_tool_args(args: &Value) -> String {
-    let pretty = serde_json::to_string_pretty(args).unwrap_or_else(|_| args.to_string());
-    if pretty.len() > 400 {
-        format!("{DIM}{}...{RESET}", &pretty[..400])
-    } else {
-        format!("{DIM}{pretty}{RESET}")
-    }
+    let summary = crate::tool_output::summarize_tool_args(args, 400);
+    format!("{DIM}{summary}{RESET}")
 }
 
 #[async_trait::async_trait]
@@ -242,6 +273,8 @@ impl Tool for ConfirmingTool {
         // Always show what the tool is doing (Q CLI pattern: transparency even when trusted)
         let display = if self.inner.name() == "fs_write" {
             format_fs_write_diff(&args)
+        } else if self.inner.name() == "apply_patch" {
+            format_apply_patch_preview(&args)
         } else if self.inner.name() == "execute_bash" {
             let cmd = args.get("command").and_then(|v| v.as_str()).unwrap_or("?");
             format!("{BOLD}{CYAN}${RESET} {cmd}\n")
@@ -266,8 +299,18 @@ impl Tool for ConfirmingTool {
 
         eprint!("{display}");
 
+        // PR merges and approvals, and writes that keep secrets, prompt on
+        // every call, even when trusted.
+        let always_confirm = match self.inner.name() {
+            "github_ops" => crate::tools::github_ops::github_ops_requires_confirmation(&args),
+            "fs_write" | "apply_patch" => {
+                crate::tools::secret_scan::secret_write_requires_confirmation(&args)
+            }
+            _ => false,
+        };
+
         // If trusted or display-only, show action and execute immediately
-        if trusted || self.display_only {
+        if (trusted || self.display_only) && !always_confirm {
             theme::resume_spinner();
             let mut approved_args = args;
             if let Some(obj) = approved_args.as_object_mut() {
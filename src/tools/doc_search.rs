@@ -0,0 +1,130 @@
+//! `doc_search`: the workspace retrieval index as a callable tool.
+//!
+//! Retrieval normally only augments the first prompt of a turn; this lets
+//! the model look things up in the configured retrieval doc when it needs
+//! to. The tool wraps the same [`RetrievalService`] the command built (a
+//! reload by `retrieval_watch` is seen by both), and applies the same
+//! `retrieval_min_score` and `retrieval_max_chars` policy as prompt
+//! injection. It is only registered when the retrieval backend is not
+//! `disabled`, and it is read-only.
+use std::sync::Arc;
+
+use adk_rust::prelude::*;
+use serde_json::{Value, json};
+
+use crate::cli::RetrievalBackend;
+use crate::config::RuntimeConfig;
+use crate::retrieval::{
+    RetrievalPolicy, RetrievalService, select_retrieval_chunks_with_truncation,
+};
+
+pub const DOC_SEARCH_TOOL_NAME: &str = "doc_search";
+/// Upper bound on the `max_chunks` argument.
+pub const DOC_SEARCH_MAX_CHUNKS_LIMIT: usize = 20;
+
+fn doc_search_error(code: &str, error: impl std::fmt::Display) -> Value {
+    json!({
+        "status": "error",
+        "kind": DOC_SEARCH_TOOL_NAME,
+        "code": code,
+        "error": error.to_string(),
+    })
+}
+
+/// Run one `doc_search` call. `max_chunks` defaults to the policy's and is
+/// clamped to [`DOC_SEARCH_MAX_CHUNKS_LIMIT`]; chunks below `min_score` are
+/// dropped and the rest share the `max_chars` budget.
+pub fn doc_search_tool_response(
+    retrieval: &dyn RetrievalService,
+    policy: RetrievalPolicy,
+    args: &Value,
+) -> Value {
+    let query = args
+        .get("query")
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .unwrap_or_default();
+    if query.is_empty() {
+        return doc_search_error("invalid_args", "'query' is required");
+    }
+    let max_chunks = match args.get("max_chunks") {
+        None => policy.max_chunks,
+        Some(value) => match value.as_u64() {
+            Some(max_chunks) if max_chunks > 0 => max_chunks as usize,
+            _ => {
+                return doc_search_error("invalid_args", "'max_chunks' must be a positive integer");
+            }
+        },
+    }
+    .clamp(1, DOC_SEARCH_MAX_CHUNKS_LIMIT);
+    let policy = RetrievalPolicy {
+        max_chunks,
+        ..policy
+    };
+    let (chunks, truncated_chars) =
+        match select_retrieval_chunks_with_truncation(retrieval, query, policy) {
+            Ok(selected) => selected,
+            Err(err) => return doc_search_error("retrieval_failed", format!("{err:#}")),
+        };
+    let chunks = chunks
+        .into_iter()
+        .map(|chunk| {
+            json!({
+                "source": chunk.source,
+                "score": chunk.score,
+                "text": chunk.text,
+            })
+        })
+        .collect::<Vec<Value>>();
+    json!({
+        "status": "ok",
+        "kind": DOC_SEARCH_TOOL_NAME,
+        "backend": retrieval.backend_name(),
+        "query": query,
+        "min_score": policy.min_score,
+        "max_chars": policy.max_chars,
+        "truncated_chars": truncated_chars,
+        "chunks": chunks,
+    })
+}
+
+/// `doc_search` over `retrieval`, or `None` when `cfg` has retrieval
+/// disabled.
+pub fn build_doc_search_tool(
+    cfg: &RuntimeConfig,
+    retrieval: Arc<dyn RetrievalService>,
+    policy: RetrievalPolicy,
+) -> Option<Arc<dyn Tool>> {
+    if cfg.retrieval_backend == RetrievalBackend::Disabled {
+        return None;
+    }
+    let tool = FunctionTool::new(
+        DOC_SEARCH_TOOL_NAME,
+        "Searches the workspace documentation configured for retrieval. Use it to look up \
+         project docs when the context you were given is not enough. \
+         Args: query (required), max_chunks (optional, default retrieval_max_chunks). \
+         Returns matching chunks with source, score (0-100) and text, best first.",
+        move |_ctx, args| {
+            let retrieval = retrieval.clone();
+            async move { Ok(doc_search_tool_response(retrieval.as_ref(), policy, &args)) }
+        },
+    )
+    .with_read_only(true)
+    .with_concurrency_safe(true);
+    Some(Arc::new(tool))
+}
+
+/// `/tools` line: whether `doc_search` is registered and over which backend.
+pub fn format_doc_search_status(cfg: &RuntimeConfig, registered: bool) -> String {
+    let backend = format!("{:?}", cfg.retrieval_backend).to_ascii_lowercase();
+    if registered {
+        format!(
+            "Doc search: {DOC_SEARCH_TOOL_NAME} available (backend={backend}, min_score={})",
+            cfg.retrieval_min_score
+        )
+    } else if cfg.retrieval_backend == RetrievalBackend::Disabled {
+        "Doc search: unavailable (retrieval backend disabled)".to_string()
+    } else {
+        format!("Doc search: unavailable (backend={backend}, removed by tool policy)")
+    }
+}
@@ -1,8 +1,14 @@
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
 use std::time::Duration;
 
 use serde_json::{Value, json};
 
+use super::bash_summary::summarize_command_output;
 use super::fs_read::parse_fs_read_usize_arg;
+use super::suggestions::{invalid_args_suggestions, with_suggestions};
+use crate::process_group::{PROCESS_GROUP_KILL_GRACE, register_child};
 
 pub const EXECUTE_BASH_DEFAULT_TIMEOUT_SECS: u64 = 20;
 pub const EXECUTE_BASH_DEFAULT_RETRY_ATTEMPTS: u32 = 1;
@@ -114,16 +120,24 @@ pub struct ExecuteBashRequest {
     pub command: String,
     pub approved: bool,
     pub allow_dangerous: bool,
+    /// Run unwrapped even though `execute_bash_sandbox` is configured;
+    /// requires `approved` and `allow_dangerous`.
+    pub no_sandbox: bool,
     pub timeout_secs: u64,
     pub retry_attempts: u32,
     pub retry_delay_ms: u64,
     pub max_output_chars: usize,
+    /// Attach a structured `summary` for recognized commands; set from
+    /// `execute_bash_summarize`, never from tool args.
+    pub summarize: bool,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ExecuteBashToolError {
     pub code: &'static str,
     pub message: String,
+    /// Last signal sent to the command's process group when it was killed.
+    pub killed_with: Option<&'static str>,
 }
 
 impl ExecuteBashToolError {
@@ -131,6 +145,7 @@ impl ExecuteBashToolError {
         Self {
             code,
             message: message.into(),
+            killed_with: None,
         }
     }
 }
@@ -145,14 +160,23 @@ pub fn execute_bash_error_payload(
     err: ExecuteBashToolError,
     attempts: u32,
 ) -> Value {
-    json!({
+    let suggestions = match err.code {
+        "invalid_args" => invalid_args_suggestions("execute_bash", &err.message),
+        _ => Vec::new(),
+    };
+    let mut payload = json!({
         "status": "error",
         "kind": "execute_bash",
         "code": err.code,
         "error": err.message,
         "command": command,
         "attempts": attempts
-    })
+    });
+    if let Some(signal) = err.killed_with {
+        payload["killed_process_group"] = json!(true);
+        payload["signal"] = json!(signal);
+    }
+    with_suggestions(payload, suggestions)
 }
 
 pub fn parse_execute_bash_u64_arg(
@@ -215,6 +239,10 @@ pub fn parse_execute_bash_request(
             .get("allow_dangerous")
             .and_then(Value::as_bool)
             .unwrap_or(false),
+        no_sandbox: args
+            .get("no_sandbox")
+            .and_then(Value::as_bool)
+            .unwrap_or(false),
         timeout_secs: parse_execute_bash_u64_arg(
             args,
             "timeout_secs",
@@ -237,6 +265,7 @@ pub fn parse_execute_bash_request(
             5000,
         )?,
         max_output_chars,
+        summarize: false,
     })
 }
 
@@ -442,21 +471,179 @@ pub fn truncate_text(text: &str, max_chars: usize) -> (String, bool) {
     }
 }
 
+/// Runtime settings for the `execute_bash` tool, from [`crate::config::execute_bash_options`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExecuteBashOptions {
+    pub sandbox: Option<ExecuteBashSandbox>,
+    /// `execute_bash_summarize`.
+    pub summarize: bool,
+}
+
+/// `execute_bash_sandbox`: a wrapper template such as
+/// `bwrap --ro-bind / / --bind {workspace} {workspace} -- sh -lc {command}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecuteBashSandbox {
+    pub template: String,
+    pub workspace: PathBuf,
+}
+
+/// Replace `{name}` placeholders in one pass, so substituted text (a
+/// command containing `{workspace}`, say) is never expanded again.
+fn substitute_placeholders(token: &str, values: &[(&str, &str)]) -> String {
+    let mut out = String::with_capacity(token.len());
+    let mut rest = token;
+    'scan: while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let candidate = &rest[start..];
+        for (name, value) in values {
+            let placeholder = format!("{{{name}}}");
+            if candidate.starts_with(&placeholder) {
+                out.push_str(value);
+                rest = &candidate[placeholder.len()..];
+                continue 'scan;
+            }
+        }
+        out.push('{');
+        rest = &candidate[1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// The argv for running `command` inside `template`. The template is split
+/// with shell quoting rules first and placeholders are filled per argument,
+/// so the command always lands in a single argv slot and is parsed by at
+/// most the one shell the template names.
+pub fn sandbox_argv(
+    template: &str,
+    command: &str,
+    workspace: &Path,
+) -> Result<Vec<String>, ExecuteBashToolError> {
+    let tokens = shlex::split(template).ok_or_else(|| {
+        ExecuteBashToolError::new(
+            "sandbox_config",
+            "execute_bash_sandbox has unbalanced quotes",
+        )
+    })?;
+    if tokens.is_empty() {
+        return Err(ExecuteBashToolError::new(
+            "sandbox_config",
+            "execute_bash_sandbox is empty",
+        ));
+    }
+    if !tokens.iter().any(|token| token.contains("{command}")) {
+        return Err(ExecuteBashToolError::new(
+            "sandbox_config",
+            "execute_bash_sandbox must contain a {command} placeholder",
+        ));
+    }
+    let workspace = workspace.display().to_string();
+    Ok(tokens
+        .iter()
+        .map(|token| {
+            substitute_placeholders(token, &[("command", command), ("workspace", &workspace)])
+        })
+        .collect())
+}
+
+/// First match for `program` on `path_env` (or the path itself when it
+/// contains a separator).
+pub fn find_executable(program: &str, path_env: Option<&OsStr>) -> Option<PathBuf> {
+    if program.contains(std::path::MAIN_SEPARATOR) {
+        let path = PathBuf::from(program);
+        return path.is_file().then_some(path);
+    }
+    std::env::split_paths(path_env?)
+        .map(|dir| dir.join(program))
+        .find(|candidate| candidate.is_file())
+}
+
+/// `doctor`: the sandbox wrapper's binary, resolved on `path_env`.
+pub fn check_sandbox_binary(template: &str, path_env: Option<&OsStr>) -> Result<PathBuf, String> {
+    let argv = sandbox_argv(template, "true", Path::new(".")).map_err(|err| err.message)?;
+    find_executable(&argv[0], path_env)
+        .ok_or_else(|| format!("sandbox binary '{}' not found on PATH", argv[0]))
+}
+
+/// Whether this request runs wrapped, or why it may not run unwrapped.
+pub fn resolve_sandbox_use<'a>(
+    request: &ExecuteBashRequest,
+    sandbox: Option<&'a ExecuteBashSandbox>,
+) -> Result<Option<&'a ExecuteBashSandbox>, ExecuteBashToolError> {
+    match sandbox {
+        Some(_) if request.no_sandbox => {
+            if request.approved && request.allow_dangerous {
+                Ok(None)
+            } else {
+                Err(ExecuteBashToolError::new(
+                    "sandbox_override_denied",
+                    "no_sandbox requires approved=true and allow_dangerous=true",
+                ))
+            }
+        }
+        other => Ok(other),
+    }
+}
+
 pub async fn run_execute_bash_once(
     command: &str,
     timeout_secs: u64,
 ) -> Result<std::process::Output, ExecuteBashToolError> {
-    let child = tokio::process::Command::new("sh")
-        .arg("-lc")
-        .arg(command)
-        .output();
-    match tokio::time::timeout(Duration::from_secs(timeout_secs), child).await {
-        Ok(result) => result
-            .map_err(|_| ExecuteBashToolError::new("io_error", "failed to launch shell command")),
-        Err(_) => Err(ExecuteBashToolError::new(
-            "timeout",
-            format!("command timed out after {timeout_secs}s"),
-        )),
+    run_execute_bash_once_in(command, timeout_secs, None).await
+}
+
+/// Like [`run_execute_bash_once`], wrapped in `sandbox` when given.
+pub async fn run_execute_bash_once_in(
+    command: &str,
+    timeout_secs: u64,
+    sandbox: Option<&ExecuteBashSandbox>,
+) -> Result<std::process::Output, ExecuteBashToolError> {
+    let mut process = match sandbox {
+        Some(sandbox) => {
+            let argv = sandbox_argv(&sandbox.template, command, &sandbox.workspace)?;
+            let mut process = tokio::process::Command::new(&argv[0]);
+            process.args(&argv[1..]);
+            process
+        }
+        None => {
+            let mut process = tokio::process::Command::new("sh");
+            process.arg("-lc").arg(command);
+            process
+        }
+    };
+    // The shell leads its own process group so a timeout reaches its
+    // children too; dropping the future (tool timeout, --max-runtime-secs)
+    // kills the shell and, through the guard, the group.
+    #[cfg(unix)]
+    process.process_group(0);
+    process
+        .kill_on_drop(true)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    let child = process
+        .spawn()
+        .map_err(|_| ExecuteBashToolError::new("io_error", "failed to launch shell command"))?;
+    let group = register_child(&child);
+    let output = child.wait_with_output();
+    tokio::pin!(output);
+    match tokio::time::timeout(Duration::from_secs(timeout_secs), &mut output).await {
+        Ok(result) => {
+            if let Some(group) = group {
+                group.release();
+            }
+            result.map_err(|_| ExecuteBashToolError::new("io_error", "failed to run shell command"))
+        }
+        Err(_) => {
+            let mut err = ExecuteBashToolError::new(
+                "timeout",
+                format!("command timed out after {timeout_secs}s"),
+            );
+            if let Some(group) = group {
+                err.killed_with = Some(group.kill(PROCESS_GROUP_KILL_GRACE).await);
+            }
+            Err(err)
+        }
     }
 }
 
@@ -465,67 +652,127 @@ pub fn execute_bash_output_payload(
     policy: &ExecuteBashPolicyDecision,
     attempts: u32,
     output: std::process::Output,
+) -> Value {
+    execute_bash_output_payload_in(request, policy, attempts, output, false)
+}
+
+pub fn execute_bash_output_payload_in(
+    request: &ExecuteBashRequest,
+    policy: &ExecuteBashPolicyDecision,
+    attempts: u32,
+    output: std::process::Output,
+    sandboxed: bool,
 ) -> Value {
     let stdout_text = String::from_utf8_lossy(&output.stdout).to_string();
     let stderr_text = String::from_utf8_lossy(&output.stderr).to_string();
     let (stdout, stdout_truncated) = truncate_text(&stdout_text, request.max_output_chars);
     let (stderr, stderr_truncated) = truncate_text(&stderr_text, request.max_output_chars);
 
-    if output.status.success() {
-        return json!({
+    let mut payload = if output.status.success() {
+        json!({
             "status": "ok",
             "kind": "execute_bash",
             "command": request.command,
             "attempts": attempts,
             "exit_code": output.status.code().unwrap_or(0),
             "read_only_auto_allow": policy.read_only_auto_allow,
+            "sandboxed": sandboxed,
             "stdout": stdout,
             "stderr": stderr,
             "stdout_truncated": stdout_truncated,
             "stderr_truncated": stderr_truncated
-        });
+        })
+    } else {
+        json!({
+            "status": "error",
+            "kind": "execute_bash",
+            "code": "command_failed",
+            "error": format!("command exited with non-zero status: {}", output.status),
+            "command": request.command,
+            "attempts": attempts,
+            "exit_code": output.status.code().unwrap_or(-1),
+            "read_only_auto_allow": policy.read_only_auto_allow,
+            "sandboxed": sandboxed,
+            "stdout": stdout,
+            "stderr": stderr,
+            "stdout_truncated": stdout_truncated,
+            "stderr_truncated": stderr_truncated
+        })
+    };
+    // Summaries read the full output, not the truncated copy.
+    if request.summarize
+        && let Some(summary) =
+            summarize_command_output(&request.command, &stdout_text, &stderr_text)
+    {
+        payload["summary"] = summary;
     }
-
-    json!({
-        "status": "error",
-        "kind": "execute_bash",
-        "code": "command_failed",
-        "error": format!("command exited with non-zero status: {}", output.status),
-        "command": request.command,
-        "attempts": attempts,
-        "exit_code": output.status.code().unwrap_or(-1),
-        "read_only_auto_allow": policy.read_only_auto_allow,
-        "stdout": stdout,
-        "stderr": stderr,
-        "stdout_truncated": stdout_truncated,
-        "stderr_truncated": stderr_truncated
-    })
+    payload
 }
 
 pub async fn execute_bash_tool_response(args: &Value) -> Value {
-    let request = match parse_execute_bash_request(args) {
+    execute_bash_tool_response_in(args, None).await
+}
+
+/// The policy always judges the inner command text; `sandbox` only changes
+/// how an allowed command is launched.
+pub async fn execute_bash_tool_response_in(
+    args: &Value,
+    sandbox: Option<&ExecuteBashSandbox>,
+) -> Value {
+    execute_bash_tool_response_with(
+        args,
+        &ExecuteBashOptions {
+            sandbox: sandbox.cloned(),
+            summarize: false,
+        },
+    )
+    .await
+}
+
+pub async fn execute_bash_tool_response_with(args: &Value, options: &ExecuteBashOptions) -> Value {
+    let sandbox = options.sandbox.as_ref();
+    let mut request = match parse_execute_bash_request(args) {
         Ok(request) => request,
         Err(err) => return execute_bash_error_payload("<missing>", err, 0),
     };
+    request.summarize = options.summarize;
     let policy = match evaluate_execute_bash_policy(&request) {
         Ok(decision) => decision,
         Err(err) => return execute_bash_error_payload(&request.command, err, 0),
     };
+    let sandbox = match resolve_sandbox_use(&request, sandbox) {
+        Ok(sandbox) => sandbox,
+        Err(err) => return execute_bash_error_payload(&request.command, err, 0),
+    };
 
     let mut attempts = 0u32;
     let mut last_error: Option<ExecuteBashToolError> = None;
 
     while attempts < request.retry_attempts {
         attempts += 1;
-        match run_execute_bash_once(&request.command, request.timeout_secs).await {
+        match run_execute_bash_once_in(&request.command, request.timeout_secs, sandbox).await {
             Ok(output) => {
-                let payload = execute_bash_output_payload(&request, &policy, attempts, output);
+                let payload = execute_bash_output_payload_in(
+                    &request,
+                    &policy,
+                    attempts,
+                    output,
+                    sandbox.is_some(),
+                );
                 let failed = payload
                     .get("status")
                     .and_then(Value::as_str)
                     .map(|status| status.eq_ignore_ascii_case("error"))
                     .unwrap_or(false);
                 if !failed || attempts >= request.retry_attempts {
+                    if !is_read_only_command(&request.command) {
+                        crate::change_log::record_tool_change(
+                            crate::change_log::ToolChange::Command {
+                                command: request.command.clone(),
+                                exit_code: payload.get("exit_code").and_then(Value::as_i64),
+                            },
+                        );
+                    }
                     return payload;
                 }
                 last_error = Some(ExecuteBashToolError::new(
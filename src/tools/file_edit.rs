@@ -107,6 +107,13 @@ pub fn file_edit_tool_response(args: &Value) -> Value {
 
     // Diff
     let display = fs_read_display_path(&resolved, &workspace_root);
+    crate::change_log::record_tool_change(crate::change_log::ToolChange::FileWrite {
+        path: resolved.clone(),
+        display_path: display.clone(),
+        mode: "edit".to_string(),
+        before: Some(original.clone().into_bytes()),
+        after: Some(updated.clone().into_bytes()),
+    });
     let diff = TextDiff::from_lines(&original, &updated);
     let patch = diff
         .unified_diff()
@@ -1,12 +1,24 @@
 use std::path::{Path, PathBuf};
 
 use serde_json::{Value, json};
+
+use super::suggestions::{
+    invalid_args_suggestions, path_suggestions, policy_rule_suggestion, with_suggestions,
+};
+use crate::session_bundle::sha256_hex;
+
 pub const FS_READ_DEFAULT_MAX_BYTES: usize = 8192;
 pub const FS_READ_MAX_BYTES_LIMIT: usize = 65536;
 pub const FS_READ_DEFAULT_MAX_LINES: usize = 200;
 pub const FS_READ_MAX_LINES_LIMIT: usize = 2000;
 pub const FS_READ_DEFAULT_MAX_ENTRIES: usize = 100;
 pub const FS_READ_MAX_ENTRIES_LIMIT: usize = 500;
+/// Bytes inspected when deciding whether a file is binary.
+pub const FS_READ_BINARY_SNIFF_BYTES: usize = 8192;
+/// Share of invalid UTF-8 bytes in the sniffed prefix above which a file
+/// counts as binary.
+pub const FS_READ_BINARY_INVALID_RATIO: f64 = 0.10;
+pub const FS_READ_HEX_PREVIEW_MAX_BYTES: usize = 256;
 pub const FS_READ_DENIED_SEGMENTS: &[&str] = &[".git", ".zavora"];
 pub const FS_READ_DENIED_FILE_NAMES: &[&str] =
     &[".env", ".env.local", ".env.development", ".env.production"];
@@ -18,6 +30,7 @@ pub struct FsReadRequest {
     max_lines: usize,
     max_bytes: usize,
     max_entries: usize,
+    hex_preview: bool,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -36,12 +49,19 @@ impl FsReadToolError {
 }
 
 pub fn fs_read_error_payload(path: &str, err: FsReadToolError) -> Value {
-    json!({
-        "status": "error",
-        "code": err.code,
-        "error": err.message,
-        "path": path
-    })
+    let suggestions = match err.code {
+        "invalid_args" => invalid_args_suggestions("fs_read", &err.message),
+        _ => Vec::new(),
+    };
+    with_suggestions(
+        json!({
+            "status": "error",
+            "code": err.code,
+            "error": err.message,
+            "path": path
+        }),
+        suggestions,
+    )
 }
 
 pub fn parse_fs_read_usize_arg(
@@ -116,9 +136,21 @@ pub fn parse_fs_read_request(args: &Value) -> Result<FsReadRequest, FsReadToolEr
             1,
             FS_READ_MAX_ENTRIES_LIMIT,
         )?,
+        hex_preview: parse_fs_read_bool_arg(args, "hex_preview")?,
     })
 }
 
+fn parse_fs_read_bool_arg(args: &Value, key: &str) -> Result<bool, FsReadToolError> {
+    match args.get(key) {
+        None | Some(Value::Null) => Ok(false),
+        Some(Value::Bool(value)) => Ok(*value),
+        Some(_) => Err(FsReadToolError::new(
+            "invalid_args",
+            format!("'{key}' must be a boolean"),
+        )),
+    }
+}
+
 pub fn fs_read_workspace_root() -> Result<PathBuf, FsReadToolError> {
     let cwd = std::env::current_dir().map_err(|_| {
         FsReadToolError::new(
@@ -161,20 +193,27 @@ pub fn resolve_fs_read_path(
     })
 }
 
-pub fn enforce_workspace_path_policy(
-    requested_path: &str,
-    resolved: &Path,
-    workspace_root: &Path,
-) -> Result<(), FsReadToolError> {
+/// Which workspace path rule refuses a resolved path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathDenial {
+    OutsideWorkspace,
+    Segment(String),
+    FileName(String),
+}
+
+impl PathDenial {
+    pub fn rule(&self) -> &'static str {
+        match self {
+            PathDenial::OutsideWorkspace => "outside_workspace",
+            PathDenial::Segment(_) => "denied_segment",
+            PathDenial::FileName(_) => "denied_file_name",
+        }
+    }
+}
+
+pub fn workspace_path_denial(resolved: &Path, workspace_root: &Path) -> Option<PathDenial> {
     if !resolved.starts_with(workspace_root) {
-        return Err(FsReadToolError::new(
-            "denied_path",
-            format!(
-                "fs_read denied path '{}': outside workspace root '{}'",
-                requested_path,
-                workspace_root.display()
-            ),
-        ));
+        return Some(PathDenial::OutsideWorkspace);
     }
 
     for component in resolved.components() {
@@ -183,13 +222,7 @@ pub fn enforce_workspace_path_policy(
             .iter()
             .any(|denied| segment.eq_ignore_ascii_case(denied))
         {
-            return Err(FsReadToolError::new(
-                "denied_path",
-                format!(
-                    "fs_read denied path '{}': segment '{}' is blocked by policy",
-                    requested_path, segment
-                ),
-            ));
+            return Some(PathDenial::Segment(segment.to_string()));
         }
     }
 
@@ -198,16 +231,43 @@ pub fn enforce_workspace_path_policy(
             .iter()
             .any(|denied| name.eq_ignore_ascii_case(denied))
     {
-        return Err(FsReadToolError::new(
-            "denied_path",
-            format!(
-                "fs_read denied path '{}': filename '{}' is blocked by policy",
-                requested_path, name
-            ),
-        ));
+        return Some(PathDenial::FileName(name.to_string()));
     }
 
-    Ok(())
+    None
+}
+
+pub fn enforce_workspace_path_policy(
+    requested_path: &str,
+    resolved: &Path,
+    workspace_root: &Path,
+) -> Result<(), FsReadToolError> {
+    let Some(denial) = workspace_path_denial(resolved, workspace_root) else {
+        return Ok(());
+    };
+    let message = match denial {
+        PathDenial::OutsideWorkspace => format!(
+            "fs_read denied path '{}': outside workspace root '{}'",
+            requested_path,
+            workspace_root.display()
+        ),
+        PathDenial::Segment(segment) => format!(
+            "fs_read denied path '{}': segment '{}' is blocked by policy",
+            requested_path, segment
+        ),
+        PathDenial::FileName(name) => format!(
+            "fs_read denied path '{}': filename '{}' is blocked by policy",
+            requested_path, name
+        ),
+    };
+    Err(FsReadToolError::new("denied_path", message))
+}
+
+/// `denied_path` hint for a path [`enforce_workspace_path_policy`] refused.
+pub fn denied_path_suggestions(resolved: &Path, workspace_root: &Path) -> Vec<Value> {
+    workspace_path_denial(resolved, workspace_root)
+        .map(|denial| vec![policy_rule_suggestion(&denial, workspace_root)])
+        .unwrap_or_default()
 }
 
 pub fn fs_read_display_path(path: &Path, workspace_root: &Path) -> String {
@@ -222,6 +282,97 @@ pub fn fs_read_display_path(path: &Path, workspace_root: &Path) -> String {
         .unwrap_or_else(|_| path.display().to_string())
 }
 
+/// Bytes that belong to invalid UTF-8 sequences. A sequence cut off at the
+/// end of `bytes` is not counted, since `bytes` may be a prefix.
+pub fn invalid_utf8_byte_count(bytes: &[u8]) -> usize {
+    let mut invalid = 0usize;
+    let mut rest = bytes;
+    while let Err(err) = std::str::from_utf8(rest) {
+        let Some(len) = err.error_len() else {
+            break;
+        };
+        invalid += len;
+        rest = &rest[err.valid_up_to() + len..];
+    }
+    invalid
+}
+
+/// Null bytes or a high share of invalid UTF-8 in the first
+/// [`FS_READ_BINARY_SNIFF_BYTES`] mark a file as binary.
+pub fn looks_binary(data: &[u8]) -> bool {
+    let sample = &data[..data.len().min(FS_READ_BINARY_SNIFF_BYTES)];
+    if sample.is_empty() {
+        return false;
+    }
+    if sample.contains(&0) {
+        return true;
+    }
+    invalid_utf8_byte_count(sample) as f64 / sample.len() as f64 > FS_READ_BINARY_INVALID_RATIO
+}
+
+/// MIME type from well-known magic bytes.
+pub fn guess_mime_from_magic(data: &[u8]) -> &'static str {
+    const SIGNATURES: &[(&[u8], &str)] = &[
+        (b"\x89PNG\r\n\x1a\n", "image/png"),
+        (b"\xff\xd8\xff", "image/jpeg"),
+        (b"GIF87a", "image/gif"),
+        (b"GIF89a", "image/gif"),
+        (b"%PDF-", "application/pdf"),
+        (b"SQLite format 3\0", "application/vnd.sqlite3"),
+        (b"PK\x03\x04", "application/zip"),
+        (b"\x1f\x8b", "application/gzip"),
+        (b"\x7fELF", "application/x-elf"),
+        (b"\0asm", "application/wasm"),
+    ];
+    if data.len() >= 12 && &data[..4] == b"RIFF" && &data[8..12] == b"WEBP" {
+        return "image/webp";
+    }
+    SIGNATURES
+        .iter()
+        .find(|(magic, _)| data.starts_with(magic))
+        .map(|(_, mime)| *mime)
+        .unwrap_or("application/octet-stream")
+}
+
+/// `offset  hex bytes` rows of 16 bytes.
+pub fn render_hex_preview(bytes: &[u8]) -> String {
+    bytes
+        .chunks(16)
+        .enumerate()
+        .map(|(row, chunk)| {
+            let hex = chunk
+                .iter()
+                .map(|byte| format!("{byte:02x}"))
+                .collect::<Vec<String>>()
+                .join(" ");
+            format!("{:08x}  {hex}", row * 16)
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn fs_read_binary_payload(data: &[u8], display_path: &str, request: &FsReadRequest) -> Value {
+    let mut payload = json!({
+        "status": "ok",
+        "kind": "binary",
+        "encoding": "binary",
+        "path": display_path,
+        "size_bytes": data.len(),
+        "mime": guess_mime_from_magic(data),
+        "sha256": sha256_hex(data)
+    });
+    if request.hex_preview {
+        let preview_len = data
+            .len()
+            .min(request.max_bytes)
+            .min(FS_READ_HEX_PREVIEW_MAX_BYTES);
+        payload["hex_preview"] = json!(render_hex_preview(&data[..preview_len]));
+        payload["hex_preview_bytes"] = json!(preview_len);
+        payload["truncated"] = json!(data.len() > preview_len);
+    }
+    payload
+}
+
 pub fn fs_read_file_payload(
     resolved: &Path,
     display_path: &str,
@@ -233,9 +384,24 @@ pub fn fs_read_file_payload(
             format!("failed to read file '{}'", display_path),
         )
     })?;
+    if looks_binary(&data) {
+        return Ok(fs_read_binary_payload(&data, display_path, request));
+    }
 
-    let bytes_to_use = data.len().min(request.max_bytes);
+    let mut bytes_to_use = data.len().min(request.max_bytes);
     let truncated_by_bytes = data.len() > bytes_to_use;
+    // Don't split a multi-byte char at the byte cap.
+    if truncated_by_bytes
+        && let Err(err) = std::str::from_utf8(&data[..bytes_to_use])
+        && err.error_len().is_none()
+    {
+        bytes_to_use = err.valid_up_to();
+    }
+    let encoding = if std::str::from_utf8(&data[..bytes_to_use]).is_ok() {
+        "utf-8"
+    } else {
+        "utf-8-lossy"
+    };
     let content = String::from_utf8_lossy(&data[..bytes_to_use]).to_string();
     let lines = content.lines().collect::<Vec<&str>>();
 
@@ -249,6 +415,7 @@ pub fn fs_read_file_payload(
     Ok(json!({
         "status": "ok",
         "kind": "file",
+        "encoding": encoding,
         "path": display_path,
         "start_line": request.start_line,
         "line_count": end_index.saturating_sub(start_index),
@@ -317,10 +484,18 @@ pub fn fs_read_tool_response_with_root(args: &Value, workspace_root: &Path) -> V
 
     let resolved = match resolve_fs_read_path(workspace_root, &request.path) {
         Ok(path) => path,
-        Err(err) => return fs_read_error_payload(&request.path, err),
+        Err(err) => {
+            return with_suggestions(
+                fs_read_error_payload(&request.path, err),
+                path_suggestions(workspace_root, &request.path),
+            );
+        }
     };
     if let Err(err) = enforce_workspace_path_policy(&request.path, &resolved, workspace_root) {
-        return fs_read_error_payload(&request.path, err);
+        return with_suggestions(
+            fs_read_error_payload(&request.path, err),
+            denied_path_suggestions(&resolved, workspace_root),
+        );
     }
 
     let display_path = fs_read_display_path(&resolved, workspace_root);
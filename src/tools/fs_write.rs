@@ -3,7 +3,15 @@ use std::path::{Path, PathBuf};
 
 use serde_json::{Value, json};
 
-use super::fs_read::{enforce_workspace_path_policy, fs_read_display_path, fs_read_workspace_root};
+use super::fs_read::{
+    denied_path_suggestions, enforce_workspace_path_policy, fs_read_display_path,
+    fs_read_workspace_root,
+};
+use super::secret_scan::{
+    SecretScanOptions, SecretWriteDecision, decide_secret_write, secret_findings_json,
+};
+use super::suggestions::{invalid_args_suggestions, path_suggestions, with_suggestions};
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FsWriteMode {
     Create,
@@ -40,8 +48,8 @@ pub struct FsWriteRequest {
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct FsWriteToolError {
-    code: &'static str,
-    message: String,
+    pub code: &'static str,
+    pub message: String,
 }
 
 impl FsWriteToolError {
@@ -54,12 +62,19 @@ impl FsWriteToolError {
 }
 
 pub fn fs_write_error_payload(path: &str, err: FsWriteToolError) -> Value {
-    json!({
-        "status": "error",
-        "code": err.code,
-        "error": err.message,
-        "path": path
-    })
+    let suggestions = match err.code {
+        "invalid_args" => invalid_args_suggestions("fs_write", &err.message),
+        _ => Vec::new(),
+    };
+    with_suggestions(
+        json!({
+            "status": "error",
+            "code": err.code,
+            "error": err.message,
+            "path": path
+        }),
+        suggestions,
+    )
 }
 
 pub fn parse_fs_write_mode(args: &Value) -> Result<FsWriteMode, FsWriteToolError> {
@@ -78,7 +93,7 @@ pub fn parse_fs_write_mode(args: &Value) -> Result<FsWriteMode, FsWriteToolError
         "patch" => Ok(FsWriteMode::Patch),
         _ => Err(FsWriteToolError::new(
             "invalid_args",
-            "mode must be one of: create, overwrite, append, patch",
+            "'mode' must be one of: create, overwrite, append, patch",
         )),
     }
 }
@@ -231,20 +246,65 @@ pub fn fs_write_ok_payload(
 }
 
 pub fn fs_write_tool_response_with_root(args: &Value, workspace_root: &Path) -> Value {
-    let request = match parse_fs_write_request(args) {
+    fs_write_tool_response_with_options(args, workspace_root, &SecretScanOptions::default())
+}
+
+/// The new text a request writes: `content`, or `patch.replace`.
+fn fs_write_new_text(request: &FsWriteRequest) -> &str {
+    match (&request.mode, &request.patch) {
+        (FsWriteMode::Patch, Some(patch)) => &patch.replace,
+        _ => request.content.as_deref().unwrap_or_default(),
+    }
+}
+
+pub fn fs_write_tool_response_with_options(
+    args: &Value,
+    workspace_root: &Path,
+    options: &SecretScanOptions,
+) -> Value {
+    let mut request = match parse_fs_write_request(args) {
         Ok(request) => request,
         Err(err) => return fs_write_error_payload("<missing>", err),
     };
 
     let resolved = match resolve_fs_write_path(workspace_root, &request.path) {
         Ok(path) => path,
-        Err(err) => return fs_write_error_payload(&request.path, err),
+        Err(err) => {
+            return with_suggestions(
+                fs_write_error_payload(&request.path, err),
+                path_suggestions(workspace_root, &request.path),
+            );
+        }
     };
     if let Err(err) = enforce_workspace_path_policy(&request.path, &resolved, workspace_root) {
-        return fs_write_error_payload(&request.path, FsWriteToolError::new(err.code, err.message));
+        return with_suggestions(
+            fs_write_error_payload(&request.path, FsWriteToolError::new(err.code, err.message)),
+            denied_path_suggestions(&resolved, workspace_root),
+        );
     }
 
     let display_path = fs_read_display_path(&resolved, workspace_root);
+    let new_text = fs_write_new_text(&request);
+    let redacted = match decide_secret_write(options, "fs_write", &display_path, new_text, args) {
+        SecretWriteDecision::Write { content, redacted } => {
+            if !redacted.is_empty() {
+                match request.patch.as_mut() {
+                    Some(patch) if request.mode == FsWriteMode::Patch => patch.replace = content,
+                    _ => request.content = Some(content),
+                }
+            }
+            redacted
+        }
+        SecretWriteDecision::Refuse { message, findings } => {
+            let mut payload = fs_write_error_payload(
+                &request.path,
+                FsWriteToolError::new("secret_detected", message),
+            );
+            payload["findings"] = secret_findings_json(&findings);
+            return payload;
+        }
+    };
+    let before = std::fs::read(&resolved).ok();
     let result = match request.mode {
         FsWriteMode::Create => {
             if resolved.exists() {
@@ -389,12 +449,34 @@ pub fn fs_write_tool_response_with_root(args: &Value, workspace_root: &Path) ->
     };
 
     match result {
-        Ok(payload) => payload,
+        Ok(mut payload) => {
+            if !redacted.is_empty() {
+                payload["secrets_redacted"] = secret_findings_json(&redacted);
+            }
+            crate::change_log::record_tool_change(crate::change_log::ToolChange::FileWrite {
+                after: std::fs::read(&resolved).ok(),
+                path: resolved,
+                display_path,
+                mode: request.mode.label().to_string(),
+                before,
+            });
+            payload
+        }
+        Err(err) if err.code == "invalid_path" && request.mode == FsWriteMode::Patch => {
+            with_suggestions(
+                fs_write_error_payload(&request.path, err),
+                path_suggestions(workspace_root, &request.path),
+            )
+        }
         Err(err) => fs_write_error_payload(&request.path, err),
     }
 }
 
 pub fn fs_write_tool_response(args: &Value) -> Value {
+    fs_write_tool_response_with(args, &SecretScanOptions::default())
+}
+
+pub fn fs_write_tool_response_with(args: &Value, options: &SecretScanOptions) -> Value {
     let workspace_root = match fs_read_workspace_root() {
         Ok(root) => root,
         Err(err) => {
@@ -404,5 +486,5 @@ pub fn fs_write_tool_response(args: &Value) -> Value {
             );
         }
     };
-    fs_write_tool_response_with_root(args, &workspace_root)
+    fs_write_tool_response_with_options(args, &workspace_root, options)
 }
@@ -1,6 +1,9 @@
 use std::io;
 
 use serde_json::{Value, json};
+
+use super::suggestions::{invalid_args_suggestions, with_suggestions};
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct GitHubOpsError {
     pub code: &'static str,
@@ -25,13 +28,20 @@ pub struct GitHubCliOutput {
 }
 
 pub fn github_ops_error_payload(action: &str, err: GitHubOpsError) -> Value {
-    json!({
-        "status": "error",
-        "kind": "github_ops",
-        "action": action,
-        "code": err.code,
-        "error": err.message
-    })
+    let suggestions = match err.code {
+        "invalid_args" => invalid_args_suggestions("github_ops", &err.message),
+        _ => Vec::new(),
+    };
+    with_suggestions(
+        json!({
+            "status": "error",
+            "kind": "github_ops",
+            "action": action,
+            "code": err.code,
+            "error": err.message
+        }),
+        suggestions,
+    )
 }
 
 pub fn github_token_present() -> bool {
@@ -80,6 +90,18 @@ pub fn parse_optional_string_list(args: &Value, key: &str) -> Result<Vec<String>
         .collect::<Vec<String>>())
 }
 
+/// Whether a call merges or approves a PR. These are confirmed on every call,
+/// even for a trusted tool or under `--tool-confirmation-mode never`.
+pub fn github_ops_requires_confirmation(args: &Value) -> bool {
+    let action = parse_optional_string_arg(args, "action")
+        .map(|value| value.to_ascii_lowercase())
+        .unwrap_or_default();
+    let event = parse_optional_string_arg(args, "event")
+        .map(|value| value.to_ascii_lowercase())
+        .unwrap_or_default();
+    action == "pr_merge" || (action == "pr_review" && event == "approve")
+}
+
 pub fn build_github_ops_command(args: &Value) -> Result<(String, Vec<String>), GitHubOpsError> {
     let action = parse_required_string_arg(args, "action")?.to_ascii_lowercase();
     match action.as_str() {
@@ -207,6 +229,97 @@ pub fn build_github_ops_command(args: &Value) -> Result<(String, Vec<String>), G
             }
             Ok((action, command))
         }
+        "issue_comment" => {
+            let repo = parse_required_string_arg(args, "repo")?;
+            let issue_number = parse_required_string_arg(args, "issue_number")?;
+            let body = parse_required_string_arg(args, "body")?;
+            Ok((
+                action,
+                vec![
+                    "issue".to_string(),
+                    "comment".to_string(),
+                    issue_number,
+                    "--repo".to_string(),
+                    repo,
+                    "--body".to_string(),
+                    body,
+                ],
+            ))
+        }
+        "pr_review" => {
+            let repo = parse_required_string_arg(args, "repo")?;
+            let pr_number = parse_required_string_arg(args, "pr_number")?;
+            let event = parse_required_string_arg(args, "event")?.to_ascii_lowercase();
+            let body = parse_optional_string_arg(args, "body");
+            let flag = match event.as_str() {
+                "approve" => "--approve",
+                "request_changes" => "--request-changes",
+                "comment" => "--comment",
+                _ => {
+                    return Err(GitHubOpsError::new(
+                        "invalid_args",
+                        "event must be one of: approve, request_changes, comment",
+                    ));
+                }
+            };
+            if body.is_none() && event != "approve" {
+                return Err(GitHubOpsError::new(
+                    "invalid_args",
+                    format!("'body' is required for pr_review event '{event}'"),
+                ));
+            }
+
+            let mut command = vec![
+                "pr".to_string(),
+                "review".to_string(),
+                pr_number,
+                "--repo".to_string(),
+                repo,
+                flag.to_string(),
+            ];
+            if let Some(body) = body {
+                command.push("--body".to_string());
+                command.push(body);
+            }
+            Ok((action, command))
+        }
+        "pr_merge" => {
+            let repo = parse_required_string_arg(args, "repo")?;
+            let pr_number = parse_required_string_arg(args, "pr_number")?;
+            let method = parse_optional_string_arg(args, "method")
+                .map(|value| value.to_ascii_lowercase())
+                .unwrap_or_else(|| "squash".to_string());
+            let flag = match method.as_str() {
+                "squash" => "--squash",
+                "merge" => "--merge",
+                "rebase" => "--rebase",
+                _ => {
+                    return Err(GitHubOpsError::new(
+                        "invalid_args",
+                        "method must be one of: squash, merge, rebase",
+                    ));
+                }
+            };
+            if args.get("confirm_merge").and_then(Value::as_bool) != Some(true) {
+                return Err(GitHubOpsError::new(
+                    "approval_required",
+                    format!(
+                        "pr_merge requires 'confirm_merge': true; confirm with the user before merging PR {pr_number}"
+                    ),
+                ));
+            }
+            Ok((
+                action,
+                vec![
+                    "pr".to_string(),
+                    "merge".to_string(),
+                    pr_number,
+                    "--repo".to_string(),
+                    repo,
+                    flag.to_string(),
+                ],
+            ))
+        }
         "project_item_update" => {
             let project_id = parse_required_string_arg(args, "project_id")?;
             let item_id = parse_required_string_arg(args, "item_id")?;
@@ -230,7 +343,7 @@ pub fn build_github_ops_command(args: &Value) -> Result<(String, Vec<String>), G
         }
         _ => Err(GitHubOpsError::new(
             "invalid_args",
-            "action must be one of: issue_create, issue_update, pr_create, project_item_update",
+            "action must be one of: issue_create, issue_update, issue_comment, pr_create, pr_review, pr_merge, project_item_update",
         )),
     }
 }
@@ -268,7 +381,12 @@ where
 {
     let (action, command) = match build_github_ops_command(args) {
         Ok(parsed) => parsed,
-        Err(err) => return github_ops_error_payload("unknown", err),
+        Err(err) => {
+            let action = parse_optional_string_arg(args, "action")
+                .map(|value| value.to_ascii_lowercase())
+                .unwrap_or_else(|| "unknown".to_string());
+            return github_ops_error_payload(&action, err);
+        }
     };
 
     if !token_present {
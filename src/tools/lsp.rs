@@ -7,6 +7,7 @@ use serde_json::{Value, json};
 use tokio::sync::OnceCell;
 
 use crate::lsp::manager::{LspManager, load_lsp_config};
+use crate::paths::ZavoraPaths;
 
 const MAX_FILE_SIZE: u64 = 10 * 1024 * 1024;
 
@@ -18,8 +19,8 @@ pub fn get_manager() -> Option<&'static Arc<LspManager>> {
 }
 
 /// Initialize the LSP manager (called once at startup if config exists).
-pub fn init_manager() -> bool {
-    let config = match load_lsp_config() {
+pub fn init_manager(paths: &ZavoraPaths) -> bool {
+    let config = match load_lsp_config(paths) {
         Some(c) if !c.servers.is_empty() => c,
         _ => return false,
     };
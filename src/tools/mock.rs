@@ -0,0 +1,277 @@
+//! Canned tool responses for offline development (`--mock-tools <file>`).
+//!
+//! The file maps tool names (with `*` wildcards) to a response payload:
+//!
+//! ```toml
+//! [tools."web_fetch"]
+//! response = '{"status": 200, "content": "cached page for {{url}}"}'
+//!
+//! [tools."github_ops.*"]
+//! response = { status = "ok", items = [] }
+//! ```
+//!
+//! `.json` files use the same shape. A string `response` is parsed as JSON;
+//! `{{arg}}` placeholders in string values are filled from the call's
+//! arguments (`{{args}}` is the whole argument object, `{{tool}}` the tool
+//! name). Matching tools never run their real implementation; exact names
+//! that no resolved tool provides (e.g. an unreachable MCP server's tools)
+//! are added as standalone mocks.
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use adk_rust::Result as AdkResult;
+use adk_rust::prelude::*;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use crate::telemetry::TelemetrySink;
+use crate::tool_policy::matches_wildcard;
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MockToolsFile {
+    #[serde(default)]
+    tools: BTreeMap<String, MockToolEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MockToolEntry {
+    response: Value,
+    description: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MockToolSpec {
+    pub pattern: String,
+    pub response: Value,
+    pub description: Option<String>,
+}
+
+/// Parsed `--mock-tools` file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MockToolSet {
+    pub specs: Vec<MockToolSpec>,
+}
+
+impl MockToolSet {
+    /// Parse mock definitions; `json` selects JSON over TOML.
+    pub fn parse(text: &str, json: bool) -> Result<Self> {
+        let file: MockToolsFile = if json {
+            serde_json::from_str(text).context("invalid mock tools JSON")?
+        } else {
+            toml::from_str(text).context("invalid mock tools TOML")?
+        };
+        let specs = file
+            .tools
+            .into_iter()
+            .map(|(pattern, entry)| {
+                let response = match entry.response {
+                    Value::String(text) => serde_json::from_str(&text).with_context(|| {
+                        format!("mock for tool '{pattern}' has an invalid JSON response")
+                    })?,
+                    other => other,
+                };
+                Ok(MockToolSpec {
+                    pattern,
+                    response,
+                    description: entry.description,
+                })
+            })
+            .collect::<Result<Vec<MockToolSpec>>>()?;
+        Ok(Self { specs })
+    }
+
+    /// The spec for `name`: an exact pattern wins, then the longest wildcard.
+    pub fn find(&self, name: &str) -> Option<&MockToolSpec> {
+        self.specs
+            .iter()
+            .find(|spec| spec.pattern == name)
+            .or_else(|| {
+                self.specs
+                    .iter()
+                    .filter(|spec| spec.pattern.contains('*'))
+                    .filter(|spec| matches_wildcard(&spec.pattern, name))
+                    .max_by_key(|spec| spec.pattern.len())
+            })
+    }
+}
+
+pub fn load_mock_tools(path: &str) -> Result<MockToolSet> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read mock tools file '{path}'"))?;
+    let json = Path::new(path)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+    MockToolSet::parse(&text, json).with_context(|| format!("failed to load mock tools '{path}'"))
+}
+
+fn arg_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn placeholder_value(key: &str, tool: &str, args: &Value) -> Option<Value> {
+    match key {
+        "args" => Some(args.clone()),
+        "tool" => Some(Value::String(tool.to_string())),
+        _ => args.get(key).cloned(),
+    }
+}
+
+fn fill_string(text: &str, tool: &str, args: &Value) -> Value {
+    // A string that is exactly one placeholder takes the argument's JSON type.
+    if let Some(key) = text
+        .strip_prefix("{{")
+        .and_then(|rest| rest.strip_suffix("}}"))
+        .filter(|key| !key.contains("{{"))
+        && let Some(value) = placeholder_value(key.trim(), tool, args)
+    {
+        return value;
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start..].find("}}") else {
+            break;
+        };
+        let key = rest[start + 2..start + len].trim();
+        out.push_str(&rest[..start]);
+        match placeholder_value(key, tool, args) {
+            Some(value) => out.push_str(&arg_text(&value)),
+            // Unknown placeholders stay visible.
+            None => out.push_str(&rest[start..start + len + 2]),
+        }
+        rest = &rest[start + len + 2..];
+    }
+    out.push_str(rest);
+    Value::String(out)
+}
+
+/// Fill `{{placeholders}}` in every string of `template` from `args`.
+pub fn render_mock_response(template: &Value, tool: &str, args: &Value) -> Value {
+    match template {
+        Value::String(text) => fill_string(text, tool, args),
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .map(|item| render_mock_response(item, tool, args))
+                .collect(),
+        ),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, value)| (key.clone(), render_mock_response(value, tool, args)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+/// Whether a tool response came from a mock.
+pub fn is_mocked_response(response: &Value) -> bool {
+    response.get("mocked").and_then(Value::as_bool) == Some(true)
+}
+
+/// Returns the canned payload instead of running the real tool.
+pub struct MockedTool {
+    name: String,
+    description: String,
+    inner: Option<Arc<dyn Tool>>,
+    spec: MockToolSpec,
+    telemetry: TelemetrySink,
+}
+
+#[async_trait]
+impl Tool for MockedTool {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn enhanced_description(&self) -> String {
+        match &self.inner {
+            Some(inner) => inner.enhanced_description(),
+            None => self.description.clone(),
+        }
+    }
+
+    fn parameters_schema(&self) -> Option<Value> {
+        self.inner
+            .as_ref()
+            .and_then(|inner| inner.parameters_schema())
+    }
+
+    fn response_schema(&self) -> Option<Value> {
+        self.inner
+            .as_ref()
+            .and_then(|inner| inner.response_schema())
+    }
+
+    async fn execute(&self, _ctx: Arc<dyn ToolContext>, args: Value) -> AdkResult<Value> {
+        self.telemetry.emit(
+            "tool.mocked",
+            json!({
+                "tool": self.name,
+                "pattern": self.spec.pattern,
+                "mocked": true,
+            }),
+        );
+        let mut response = render_mock_response(&self.spec.response, &self.name, &args);
+        match response.as_object_mut() {
+            Some(object) => {
+                object.insert("mocked".to_string(), Value::Bool(true));
+            }
+            None => response = json!({ "result": response, "mocked": true }),
+        }
+        Ok(response)
+    }
+}
+
+/// Replace every tool that has a mock, and add standalone mocks for exact
+/// names no tool provides. Other tools are returned untouched.
+pub fn apply_mock_tools(
+    tools: Vec<Arc<dyn Tool>>,
+    mocks: &MockToolSet,
+    telemetry: &TelemetrySink,
+) -> Vec<Arc<dyn Tool>> {
+    let mut tools = tools
+        .into_iter()
+        .map(|tool| match mocks.find(tool.name()) {
+            Some(spec) => {
+                tracing::debug!(tool = tool.name(), pattern = %spec.pattern, "Mocking tool");
+                Arc::new(MockedTool {
+                    name: tool.name().to_string(),
+                    description: tool.description().to_string(),
+                    inner: Some(tool),
+                    spec: spec.clone(),
+                    telemetry: telemetry.clone(),
+                }) as Arc<dyn Tool>
+            }
+            None => tool,
+        })
+        .collect::<Vec<Arc<dyn Tool>>>();
+    for spec in &mocks.specs {
+        if spec.pattern.contains('*') || tools.iter().any(|tool| tool.name() == spec.pattern) {
+            continue;
+        }
+        tools.push(Arc::new(MockedTool {
+            name: spec.pattern.clone(),
+            description: spec
+                .description
+                .clone()
+                .unwrap_or_else(|| format!("Mocked tool '{}'", spec.pattern)),
+            inner: None,
+            spec: spec.clone(),
+            telemetry: telemetry.clone(),
+        }));
+    }
+    tools
+}
@@ -1,4 +1,8 @@
+pub mod apply_patch;
+pub mod bash_summary;
+pub mod budget;
 pub mod confirming;
+pub mod doc_search;
 pub mod execute_bash;
 pub mod file_edit;
 pub mod fs_read;
@@ -6,6 +10,11 @@ pub mod fs_write;
 pub mod github_ops;
 pub mod glob;
 pub mod grep;
+pub mod mock;
+pub mod output_cap;
+pub mod policy_check;
+pub mod schema_pruning;
+pub mod secret_scan;
 pub mod bash_security;
 #[cfg(feature = "browser")]
 pub mod browser;
@@ -15,6 +24,8 @@ pub mod lsp;
 pub mod rag;
 #[cfg(feature = "sandbox")]
 pub mod sandbox;
+pub mod schemas;
+pub mod suggestions;
 pub mod tool_search;
 #[cfg(feature = "web-fetch")]
 pub mod web_fetch;
@@ -29,12 +40,94 @@ use crate::todos;
 pub const FS_READ_TOOL_NAME: &str = "fs_read";
 pub const FS_WRITE_TOOL_NAME: &str = "fs_write";
 pub const FILE_EDIT_TOOL_NAME: &str = "file_edit";
+pub const APPLY_PATCH_TOOL_NAME: &str = "apply_patch";
 pub const EXECUTE_BASH_TOOL_NAME: &str = "execute_bash";
 pub const GITHUB_OPS_TOOL_NAME: &str = "github_ops";
 pub const GLOB_TOOL_NAME: &str = "glob";
 pub const GREP_TOOL_NAME: &str = "grep";
 pub const TODO_TOOL_NAME: &str = "todo_list";
 
+/// `execute_bash`, launched through the configured sandbox and summarizing
+/// output when `options` ask for it.
+pub fn build_execute_bash_tool(options: execute_bash::ExecuteBashOptions) -> FunctionTool {
+    let options = Arc::new(options);
+    FunctionTool::new(
+        EXECUTE_BASH_TOOL_NAME,
+        "Executes shell commands with policy checks and approval gates. \
+         Args: command (required), approved, allow_dangerous, no_sandbox, timeout_secs, retry_attempts, retry_delay_ms, max_output_chars.",
+        move |_ctx, args| {
+            let options = options.clone();
+            async move { Ok(execute_bash::execute_bash_tool_response_with(&args, &options).await) }
+        },
+    )
+}
+
+/// Swap `execute_bash` in `tools` for one built with `options`.
+pub fn apply_execute_bash_options(
+    tools: &mut [Arc<dyn Tool>],
+    options: execute_bash::ExecuteBashOptions,
+) {
+    if options == execute_bash::ExecuteBashOptions::default() {
+        return;
+    }
+    for tool in tools
+        .iter_mut()
+        .filter(|tool| tool.name() == EXECUTE_BASH_TOOL_NAME)
+    {
+        *tool = Arc::new(build_execute_bash_tool(options.clone()));
+    }
+}
+
+/// `fs_write`, scanning content for secrets per `options`.
+pub fn build_fs_write_tool(options: secret_scan::SecretScanOptions) -> FunctionTool {
+    let options = Arc::new(options);
+    FunctionTool::new(
+        FS_WRITE_TOOL_NAME,
+        "Writes files within the workspace with safe modes. \
+         Args: path (required), mode=create|overwrite|append|patch, content, patch={find,replace,replace_all}, \
+         allow_secrets. Content that looks like a secret is refused (secret_detected); \
+         allow_secrets=true overrides a false positive after the user confirms the call.",
+        move |_ctx, args| {
+            let options = options.clone();
+            async move { Ok(fs_write::fs_write_tool_response_with(&args, &options)) }
+        },
+    )
+}
+
+/// `apply_patch`, scanning added lines for secrets per `options`.
+pub fn build_apply_patch_tool(options: secret_scan::SecretScanOptions) -> FunctionTool {
+    let options = Arc::new(options);
+    FunctionTool::new(
+        APPLY_PATCH_TOOL_NAME,
+        "Applies a unified diff (diff -u / git diff format) that may touch several files. \
+         Args: patch (required, diff text), dry_run (optional bool: check without writing), \
+         allow_secrets (keep secret-looking lines; the user confirms the call). \
+         Supports new files (--- /dev/null) and deletions (+++ /dev/null). Each file is applied \
+         atomically: all of its hunks apply or it is left untouched. Returns per-file status \
+         (applied/failed), hunks_applied, sha256 of the result and, on a context mismatch, \
+         the failing hunk. Added lines that look like secrets are refused (secret_detected).",
+        move |_ctx, args| {
+            let options = options.clone();
+            async move { Ok(apply_patch::apply_patch_tool_response_with(&args, &options)) }
+        },
+    )
+}
+
+/// Swap `fs_write` and `apply_patch` in `tools` for ones built with
+/// `options`.
+pub fn apply_secret_scan_options(
+    tools: &mut [Arc<dyn Tool>],
+    options: secret_scan::SecretScanOptions,
+) {
+    for tool in tools.iter_mut() {
+        match tool.name() {
+            FS_WRITE_TOOL_NAME => *tool = Arc::new(build_fs_write_tool(options.clone())),
+            APPLY_PATCH_TOOL_NAME => *tool = Arc::new(build_apply_patch_tool(options.clone())),
+            _ => {}
+        }
+    }
+}
+
 pub fn build_builtin_tools() -> Vec<Arc<dyn Tool>> {
     let current_time = FunctionTool::new(
         "current_unix_time",
@@ -73,18 +166,16 @@ pub fn build_builtin_tools() -> Vec<Arc<dyn Tool>> {
     let fs_read = FunctionTool::new(
         "fs_read",
         "Reads file content or directory entries within the workspace using path policy checks. \
-         Args: path (required), start_line, max_lines, max_bytes, max_entries.",
+         Args: path (required), start_line, max_lines, max_bytes, max_entries, hex_preview. \
+         Every file read reports encoding (utf-8, utf-8-lossy or binary). Binary files return \
+         kind=binary with size_bytes, mime (guessed from magic bytes) and sha256 instead of \
+         content; pass hex_preview=true to also get a hex dump of the first bytes.",
         |_ctx, args| async move { Ok(fs_read::fs_read_tool_response(&args)) },
     )
     .with_read_only(true)
     .with_concurrency_safe(true);
 
-    let fs_write = FunctionTool::new(
-        "fs_write",
-        "Writes files within the workspace with safe modes. \
-         Args: path (required), mode=create|overwrite|append|patch, content, patch={find,replace,replace_all}.",
-        |_ctx, args| async move { Ok(fs_write::fs_write_tool_response(&args)) },
-    );
+    let fs_write = build_fs_write_tool(secret_scan::SecretScanOptions::default());
 
     let file_edit = FunctionTool::new(
         "file_edit",
@@ -95,6 +186,8 @@ pub fn build_builtin_tools() -> Vec<Arc<dyn Tool>> {
         |_ctx, args| async move { Ok(file_edit::file_edit_tool_response(&args)) },
     );
 
+    let apply_patch = build_apply_patch_tool(secret_scan::SecretScanOptions::default());
+
     let glob_tool = FunctionTool::new(
         "glob",
         "Finds files matching a glob pattern. Respects .gitignore. \
@@ -141,17 +234,13 @@ pub fn build_builtin_tools() -> Vec<Arc<dyn Tool>> {
     .with_read_only(true)
     .with_concurrency_safe(true);
 
-    let execute_bash = FunctionTool::new(
-        "execute_bash",
-        "Executes shell commands with policy checks and approval gates. \
-         Args: command (required), approved, allow_dangerous, timeout_secs, retry_attempts, retry_delay_ms, max_output_chars.",
-        |_ctx, args| async move { Ok(execute_bash::execute_bash_tool_response(&args).await) },
-    );
+    let execute_bash = build_execute_bash_tool(execute_bash::ExecuteBashOptions::default());
 
     let github_ops = FunctionTool::new(
         "github_ops",
         "Runs GitHub workflow operations through gh CLI. \
-         Args: action=issue_create|issue_update|pr_create|project_item_update plus action-specific fields.",
+         Args: action=issue_create|issue_update|issue_comment|pr_create|pr_review|pr_merge|project_item_update \
+         plus action-specific fields. pr_merge requires confirm_merge=true.",
         |_ctx, args| async move { Ok(github_ops::github_ops_tool_response(&args)) },
     );
 
@@ -187,6 +276,7 @@ pub fn build_builtin_tools() -> Vec<Arc<dyn Tool>> {
         Arc::new(fs_read),
         Arc::new(fs_write),
         Arc::new(file_edit),
+        Arc::new(apply_patch),
         Arc::new(glob_tool),
         Arc::new(grep_tool),
         #[cfg(feature = "web-fetch")]
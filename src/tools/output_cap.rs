@@ -0,0 +1,226 @@
+//! Global cap on tool output injected into the model context
+//! (`tool_output_max_chars`).
+//!
+//! Every runtime tool is wrapped with [`CappedOutputTool`]. A response whose
+//! JSON is longer than the cap is written in full to a spillover file, and the
+//! response the model sees keeps its small fields while the largest ones are
+//! cut to a preview. Per-tool limits (`fs_read` `max_bytes`, `execute_bash`
+//! `max_output_chars`) still apply first; the cap only ever tightens them.
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use adk_rust::Result as AdkResult;
+use adk_rust::prelude::*;
+use async_trait::async_trait;
+use serde_json::{Map, Value, json};
+
+use crate::telemetry::TelemetrySink;
+use crate::tool_output::truncate_at_char_boundary;
+
+pub const DEFAULT_TOOL_OUTPUT_MAX_CHARS: usize = 32_000;
+
+/// Fields added to a capped response; never truncated themselves.
+const CAP_FIELDS: &[&str] = &["truncated", "original_chars", "spillover_ref"];
+
+static SPILLOVER_SEQ: AtomicU64 = AtomicU64::new(0);
+
+fn json_chars(value: &Value) -> usize {
+    value.to_string().chars().count()
+}
+
+fn field_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn preview(text: &str, keep_chars: usize) -> String {
+    let (kept, dropped) = truncate_at_char_boundary(text, keep_chars);
+    format!("{kept}… [truncated {dropped} bytes; full output in spillover_ref]")
+}
+
+/// Replace `object[key]` with a preview of `text`, shortening it until the
+/// object fits in `max_chars` or nothing of `text` is left.
+fn shrink_field(object: &mut Map<String, Value>, key: &str, text: &str, max_chars: usize) {
+    let mut keep = text.chars().count();
+    while keep > 0 {
+        let size = json_chars(&Value::Object(object.clone()));
+        if size <= max_chars {
+            return;
+        }
+        keep = keep.saturating_sub(size - max_chars);
+        object.insert(key.to_string(), Value::String(preview(text, keep)));
+    }
+}
+
+/// Shrink `response` to at most `max_chars` of JSON. Returns `None` when it
+/// already fits. Object responses keep their shape: the largest fields are
+/// replaced by previews until the rest fits; anything else becomes
+/// `{"preview": ...}`.
+pub fn cap_tool_response(
+    response: &Value,
+    max_chars: usize,
+    spillover_ref: Option<&str>,
+) -> Option<Value> {
+    let original_chars = json_chars(response);
+    if original_chars <= max_chars {
+        return None;
+    }
+    let mut header = Map::new();
+    header.insert("truncated".to_string(), Value::Bool(true));
+    header.insert("original_chars".to_string(), json!(original_chars));
+    header.insert("spillover_ref".to_string(), json!(spillover_ref));
+
+    if let Some(object) = response.as_object() {
+        let mut capped = object.clone();
+        capped.extend(header.clone());
+        let mut cut = Vec::<String>::new();
+        loop {
+            if json_chars(&Value::Object(capped.clone())) <= max_chars {
+                return Some(Value::Object(capped));
+            }
+            let largest = capped
+                .iter()
+                .filter(|(key, _)| !CAP_FIELDS.contains(&key.as_str()) && !cut.contains(key))
+                .max_by_key(|(_, value)| json_chars(value))
+                .map(|(key, value)| (key.clone(), field_text(value)));
+            let Some((key, text)) = largest else {
+                break;
+            };
+            shrink_field(&mut capped, &key, &text, max_chars);
+            cut.push(key);
+        }
+    }
+
+    let text = response.to_string();
+    header.insert("preview".to_string(), Value::String(text.clone()));
+    shrink_field(&mut header, "preview", &text, max_chars);
+    Some(Value::Object(header))
+}
+
+/// Write the full response under `dir`; the returned path is the
+/// `spillover_ref` shown to the model.
+pub fn write_spillover(dir: &Path, tool_name: &str, response: &Value) -> std::io::Result<PathBuf> {
+    std::fs::create_dir_all(dir)?;
+    let stamp = chrono::Utc::now().format("%Y%m%dT%H%M%S%3f");
+    let seq = SPILLOVER_SEQ.fetch_add(1, Ordering::Relaxed);
+    let safe_name = tool_name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+    let path = dir.join(format!("{safe_name}-{stamp}-{seq}.json"));
+    let body = serde_json::to_string_pretty(response).unwrap_or_else(|_| response.to_string());
+    std::fs::write(&path, body)?;
+    Ok(path)
+}
+
+/// Cap settings shared by every wrapped tool.
+#[derive(Debug, Clone)]
+pub struct ToolOutputCap {
+    pub max_chars: usize,
+    pub spillover_dir: PathBuf,
+    pub telemetry: TelemetrySink,
+}
+
+/// Wraps any tool and caps the JSON it hands back to the model.
+pub struct CappedOutputTool {
+    inner: Arc<dyn Tool>,
+    cap: ToolOutputCap,
+}
+
+impl CappedOutputTool {
+    pub fn new(inner: Arc<dyn Tool>, cap: ToolOutputCap) -> Self {
+        Self { inner, cap }
+    }
+
+    fn apply_cap(&self, response: Value) -> Value {
+        if json_chars(&response) <= self.cap.max_chars {
+            return response;
+        }
+        let name = self.inner.name();
+        let spillover = match write_spillover(&self.cap.spillover_dir, name, &response) {
+            Ok(path) => Some(path.display().to_string()),
+            Err(err) => {
+                tracing::warn!(tool = name, error = %err, "failed to write tool output spillover");
+                None
+            }
+        };
+        let Some(capped) = cap_tool_response(&response, self.cap.max_chars, spillover.as_deref())
+        else {
+            return response;
+        };
+        self.cap.telemetry.emit(
+            "tool.output_truncated",
+            json!({
+                "tool": name,
+                "original_chars": json_chars(&response),
+                "truncated_chars": json_chars(&capped),
+                "max_chars": self.cap.max_chars,
+                "spillover_ref": spillover,
+            }),
+        );
+        capped
+    }
+}
+
+#[async_trait]
+impl Tool for CappedOutputTool {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn description(&self) -> &str {
+        self.inner.description()
+    }
+
+    fn enhanced_description(&self) -> String {
+        self.inner.enhanced_description()
+    }
+
+    fn is_long_running(&self) -> bool {
+        self.inner.is_long_running()
+    }
+
+    fn parameters_schema(&self) -> Option<Value> {
+        self.inner.parameters_schema()
+    }
+
+    fn response_schema(&self) -> Option<Value> {
+        self.inner.response_schema()
+    }
+
+    async fn execute(&self, ctx: Arc<dyn ToolContext>, args: Value) -> AdkResult<Value> {
+        let response = self.inner.execute(ctx, args).await?;
+        Ok(self.apply_cap(response))
+    }
+}
+
+/// `/tools` and `profiles show` line for the cap.
+pub fn format_tool_output_cap(max_chars: usize, spillover_dir: &Path) -> String {
+    if max_chars == 0 {
+        return "Tool output cap: off".to_string();
+    }
+    format!(
+        "Tool output cap: {max_chars} chars (full results spill to {})",
+        spillover_dir.display()
+    )
+}
+
+/// Wrap every tool with [`CappedOutputTool`]. A cap of 0 disables it.
+pub fn wrap_with_output_cap(tools: Vec<Arc<dyn Tool>>, cap: &ToolOutputCap) -> Vec<Arc<dyn Tool>> {
+    if cap.max_chars == 0 {
+        return tools;
+    }
+    tools
+        .into_iter()
+        .map(|tool| Arc::new(CappedOutputTool::new(tool, cap.clone())) as Arc<dyn Tool>)
+        .collect()
+}
@@ -0,0 +1,301 @@
+//! `policy_check`: a read-only preflight that answers "would this
+//! `fs_read`/`fs_write`/`execute_bash` call be allowed?" without running it.
+//!
+//! Verdicts come from the same [`ToolPolicy`] and path/command functions the
+//! real tools call, so the model can pick a permitted path or command up
+//! front instead of learning the rules one failed call at a time. Each
+//! verdict names the rule that decided it and what would change it.
+use std::path::Path;
+use std::sync::Arc;
+
+use adk_rust::prelude::*;
+use serde::Serialize;
+use serde_json::{Value, json};
+
+use crate::config::RuntimeConfig;
+use crate::tool_policy::{ToolConfirmation, ToolOrigin, ToolPolicy, ToolUnavailable};
+use crate::tools::execute_bash::{
+    is_read_only_command, matched_denied_pattern, parse_execute_bash_request,
+};
+use crate::tools::fs_read::{
+    PathDenial, fs_read_workspace_root, resolve_fs_read_path, workspace_path_denial,
+};
+use crate::tools::fs_write::resolve_fs_write_path;
+
+pub const POLICY_CHECK_TOOL_NAME: &str = "policy_check";
+
+/// Operations `policy_check` can judge.
+pub const POLICY_CHECK_KINDS: &[&str] = &["fs_read", "fs_write", "execute_bash"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyVerdict {
+    /// The call runs as is.
+    Allowed,
+    /// The call runs once the user approves it.
+    ApprovalRequired,
+    /// The call fails whatever the user answers.
+    Denied,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PolicyCheck {
+    pub kind: String,
+    pub target: String,
+    pub verdict: PolicyVerdict,
+    /// Identifier of the deciding rule, e.g. `denied_segment` or `approval_required`.
+    pub rule: String,
+    pub reason: String,
+    /// Flags, approvals or config changes that would change the verdict.
+    pub remedies: Vec<String>,
+}
+
+impl PolicyCheck {
+    fn new(kind: &str, target: &str, verdict: PolicyVerdict, rule: &str, reason: String) -> Self {
+        Self {
+            kind: kind.to_string(),
+            target: target.to_string(),
+            verdict,
+            rule: rule.to_string(),
+            reason,
+            remedies: Vec::new(),
+        }
+    }
+
+    fn remedy(mut self, remedy: impl Into<String>) -> Self {
+        self.remedies.push(remedy.into());
+        self
+    }
+}
+
+fn unavailable_check(kind: &str, target: &str, reason: ToolUnavailable) -> PolicyCheck {
+    match reason {
+        ToolUnavailable::ReadOnly => PolicyCheck::new(
+            kind,
+            target,
+            PolicyVerdict::Denied,
+            "read_only_mode",
+            format!("{kind} is disabled in read-only mode"),
+        )
+        .remedy("the user restarts without --read-only (or read_only = false in the profile)"),
+        ToolUnavailable::Denied => PolicyCheck::new(
+            kind,
+            target,
+            PolicyVerdict::Denied,
+            "agent_deny_tools",
+            format!("{kind} matches the active agent's deny_tools"),
+        )
+        .remedy("switch to an agent that does not deny this tool"),
+        ToolUnavailable::NotAllowed => PolicyCheck::new(
+            kind,
+            target,
+            PolicyVerdict::Denied,
+            "agent_allow_tools",
+            format!("{kind} is not in the active agent's allow_tools"),
+        )
+        .remedy("switch to an agent whose allow_tools include this tool"),
+    }
+}
+
+fn check_path(policy: &ToolPolicy, kind: &str, path: &str, workspace_root: &Path) -> PolicyCheck {
+    let resolved = if kind == "fs_write" {
+        resolve_fs_write_path(workspace_root, path).map_err(|err| (err.code, err.message))
+    } else {
+        resolve_fs_read_path(workspace_root, path).map_err(|err| (err.code, err.message))
+    };
+    let resolved = match resolved {
+        Ok(resolved) => resolved,
+        Err((code, message)) => {
+            return PolicyCheck::new(kind, path, PolicyVerdict::Denied, code, message)
+                .remedy("check the path exists relative to the workspace root");
+        }
+    };
+    if let Some(denial) = workspace_path_denial(&resolved, workspace_root) {
+        let reason = policy
+            .evaluate_path(path, &resolved, workspace_root)
+            .err()
+            .map(|err| err.message.replacen("fs_read", kind, 1))
+            .unwrap_or_default();
+        let check = PolicyCheck::new(kind, path, PolicyVerdict::Denied, denial.rule(), reason);
+        return match denial {
+            PathDenial::OutsideWorkspace => check.remedy(format!(
+                "use a path inside the workspace root '{}'",
+                workspace_root.display()
+            )),
+            PathDenial::Segment(_) | PathDenial::FileName(_) => {
+                check.remedy("none: protected paths cannot be approved or overridden")
+            }
+        };
+    }
+    if policy.confirmation(kind, ToolOrigin::BuiltIn) == ToolConfirmation::Confirm {
+        return PolicyCheck::new(
+            kind,
+            path,
+            PolicyVerdict::ApprovalRequired,
+            "confirmation",
+            format!("{kind} asks the user before every call"),
+        )
+        .remedy("the user approves the call at the confirmation prompt")
+        .remedy(format!(
+            "an always_allow permission rule for {kind} skips the prompt"
+        ));
+    }
+    PolicyCheck::new(
+        kind,
+        path,
+        PolicyVerdict::Allowed,
+        "allowed",
+        format!("path is inside the workspace and {kind} runs without a prompt"),
+    )
+}
+
+fn check_command(policy: &ToolPolicy, command: &str) -> PolicyCheck {
+    let kind = "execute_bash";
+    let request = match parse_execute_bash_request(&json!({ "command": command })) {
+        Ok(request) => request,
+        Err(err) => {
+            return PolicyCheck::new(kind, command, PolicyVerdict::Denied, err.code, err.message);
+        }
+    };
+    match policy.evaluate_command(&request) {
+        Err(err) if err.code == crate::read_only::READ_ONLY_MODE_CODE => PolicyCheck::new(
+            kind,
+            command,
+            PolicyVerdict::Denied,
+            "read_only_mode",
+            err.message,
+        )
+        .remedy("use a read-only command (ls, cat, grep, git status, ...)")
+        .remedy("the user restarts without --read-only"),
+        Err(err) if err.code == "denied_command" => {
+            let rule = if matched_denied_pattern(command).is_some() {
+                "blocked_pattern"
+            } else {
+                "security_validator"
+            };
+            PolicyCheck::new(kind, command, PolicyVerdict::Denied, rule, err.message).remedy(
+                "allow_dangerous=true plus the user's approval (approved=true) overrides it",
+            )
+        }
+        Err(err) if err.code == "approval_required" => PolicyCheck::new(
+            kind,
+            command,
+            PolicyVerdict::ApprovalRequired,
+            "approval_required",
+            err.message,
+        )
+        .remedy("the user approves the call at the confirmation prompt, which sets approved=true")
+        .remedy("read-only commands (ls, cat, grep, git status, ...) run without approval"),
+        Err(err) => PolicyCheck::new(kind, command, PolicyVerdict::Denied, err.code, err.message),
+        Ok(decision) => {
+            if policy.confirmation(kind, ToolOrigin::BuiltIn) == ToolConfirmation::Confirm
+                && !is_read_only_command(command)
+            {
+                return PolicyCheck::new(
+                    kind,
+                    command,
+                    PolicyVerdict::ApprovalRequired,
+                    "confirmation",
+                    "execute_bash asks the user before every non-read-only command".to_string(),
+                )
+                .remedy("the user approves the call at the confirmation prompt");
+            }
+            let rule = if decision.read_only_auto_allow {
+                "read_only_command"
+            } else {
+                "allowed"
+            };
+            PolicyCheck::new(
+                kind,
+                command,
+                PolicyVerdict::Allowed,
+                rule,
+                "command passes the execute_bash policy".to_string(),
+            )
+        }
+    }
+}
+
+/// Verdict for one `policy_check` call, judged against `workspace_root`.
+pub fn policy_check(
+    policy: &ToolPolicy,
+    args: &Value,
+    workspace_root: &Path,
+) -> Result<PolicyCheck, String> {
+    let kind = args
+        .get("kind")
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .unwrap_or_default();
+    if !POLICY_CHECK_KINDS.contains(&kind) {
+        return Err(format!(
+            "kind must be one of: {}",
+            POLICY_CHECK_KINDS.join(", ")
+        ));
+    }
+    let (field, target) = if kind == "execute_bash" {
+        ("command", args.get("command"))
+    } else {
+        ("path", args.get("path"))
+    };
+    let target = target
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|target| !target.is_empty())
+        .ok_or_else(|| format!("'{field}' is required when kind={kind}"))?;
+    if let Some(reason) = policy.unavailable_reason(kind, ToolOrigin::BuiltIn) {
+        return Ok(unavailable_check(kind, target, reason));
+    }
+    Ok(if kind == "execute_bash" {
+        check_command(policy, target)
+    } else {
+        check_path(policy, kind, target, workspace_root)
+    })
+}
+
+pub fn policy_check_tool_response_with_root(
+    policy: &ToolPolicy,
+    args: &Value,
+    workspace_root: &Path,
+) -> Value {
+    match policy_check(policy, args, workspace_root) {
+        Ok(check) => {
+            let mut payload = serde_json::to_value(&check).unwrap_or_default();
+            payload["status"] = json!("ok");
+            payload
+        }
+        Err(message) => json!({
+            "status": "error",
+            "code": "invalid_args",
+            "error": message,
+        }),
+    }
+}
+
+pub fn policy_check_tool_response(policy: &ToolPolicy, args: &Value) -> Value {
+    match fs_read_workspace_root() {
+        Ok(root) => policy_check_tool_response_with_root(policy, args, &root),
+        Err(err) => json!({
+            "status": "error",
+            "code": err.code,
+            "error": err.message,
+        }),
+    }
+}
+
+pub fn build_policy_check_tool(cfg: &RuntimeConfig) -> FunctionTool {
+    let policy = Arc::new(ToolPolicy::from_config(cfg));
+    FunctionTool::new(
+        POLICY_CHECK_TOOL_NAME,
+        "Checks whether an fs_read, fs_write or execute_bash call would be allowed, without \
+         running it. Args: kind=fs_read|fs_write|execute_bash (required), path (file kinds), \
+         command (execute_bash). Returns verdict=allowed|approval_required|denied, the deciding \
+         rule, the reason and remedies that would change the outcome.",
+        move |_ctx, args| {
+            let policy = policy.clone();
+            async move { Ok(policy_check_tool_response(&policy, &args)) }
+        },
+    )
+    .with_read_only(true)
+    .with_concurrency_safe(true)
+}
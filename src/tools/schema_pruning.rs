@@ -0,0 +1,222 @@
+//! Per-request tool declaration pruning (`tool_schema_strategy = "relevant"`).
+//!
+//! Large toolsets (dozens of MCP tools plus the built-ins) make every model
+//! request carry every tool schema. In `relevant` mode the agent's
+//! before-model callback trims `LlmRequest::tools` to the declarations the
+//! current prompt is likely to need, so the runner and its tools stay as
+//! built and only what the provider sees changes. `full` leaves the request
+//! untouched.
+//!
+//! Kept declarations:
+//! - the top `tool_schema_top_k` tools by term overlap between the prompt and
+//!   the tool's name and description (tools with no overlap are never picked);
+//! - tools named in the prompt;
+//! - tools already called earlier in the session;
+//! - the core tools that `tool_search` also keeps, `tool_search` itself, and
+//!   the agent transfer tool.
+use std::collections::{BTreeSet, HashSet};
+
+use adk_rust::prelude::*;
+use serde_json::{Value, json};
+
+use crate::cli::ToolSchemaStrategy;
+use crate::retrieval::query_terms;
+use crate::telemetry::TelemetrySink;
+use crate::tools::tool_search::is_core_tool;
+
+pub const DEFAULT_TOOL_SCHEMA_TOP_K: usize = 12;
+
+/// Declarations kept whatever the prompt says.
+const ALWAYS_KEPT_TOOLS: &[&str] = &["tool_search", "transfer_to_agent"];
+
+/// Relevance of one tool to the prompt terms: a term matching part of the
+/// tool name counts twice, a term found in its description once.
+pub fn score_tool(prompt_terms: &[String], name: &str, description: &str) -> usize {
+    let name_parts = name
+        .split(['_', '-', '.'])
+        .map(str::to_ascii_lowercase)
+        .collect::<HashSet<String>>();
+    let description_terms = query_terms(description)
+        .into_iter()
+        .collect::<HashSet<String>>();
+    prompt_terms
+        .iter()
+        .collect::<BTreeSet<&String>>()
+        .into_iter()
+        .map(|term| {
+            if name_parts.contains(term) {
+                2
+            } else {
+                usize::from(description_terms.contains(term))
+            }
+        })
+        .sum()
+}
+
+/// Tool names written in `prompt` as whole words.
+pub fn mentioned_tools<'a>(prompt: &str, names: &[&'a str]) -> BTreeSet<&'a str> {
+    let words = prompt
+        .split(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.')))
+        .map(|word| word.trim_matches('.').to_ascii_lowercase())
+        .collect::<HashSet<String>>();
+    names
+        .iter()
+        .copied()
+        .filter(|name| words.contains(&name.to_ascii_lowercase()))
+        .collect()
+}
+
+/// Tools the model called anywhere in `contents`.
+pub fn previously_used_tools(contents: &[Content]) -> BTreeSet<String> {
+    contents
+        .iter()
+        .flat_map(|content| &content.parts)
+        .filter_map(|part| match part {
+            Part::FunctionCall { name, .. } => Some(name.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Text of the latest user turn, skipping tool results, so follow-up calls
+/// in a tool loop are judged against the prompt that started it.
+pub fn latest_prompt_text(contents: &[Content]) -> String {
+    contents
+        .iter()
+        .rev()
+        .filter(|content| content.role == "user")
+        .filter(|content| {
+            !content
+                .parts
+                .iter()
+                .any(|part| matches!(part, Part::FunctionResponse { .. }))
+        })
+        .find_map(|content| {
+            let text = content
+                .parts
+                .iter()
+                .filter_map(|part| match part {
+                    Part::Text { text } => Some(text.as_str()),
+                    _ => None,
+                })
+                .collect::<Vec<&str>>()
+                .join("\n");
+            (!text.trim().is_empty()).then_some(text)
+        })
+        .unwrap_or_default()
+}
+
+/// Names of the declarations to keep out of `tools` (name, description).
+pub fn select_tool_declarations(
+    prompt: &str,
+    tools: &[(&str, &str)],
+    used: &BTreeSet<String>,
+    top_k: usize,
+) -> BTreeSet<String> {
+    let names = tools.iter().map(|(name, _)| *name).collect::<Vec<&str>>();
+    let mut kept = mentioned_tools(prompt, &names)
+        .into_iter()
+        .map(str::to_string)
+        .collect::<BTreeSet<String>>();
+    kept.extend(
+        names
+            .iter()
+            .copied()
+            .filter(|name| {
+                used.contains(*name) || is_core_tool(name) || ALWAYS_KEPT_TOOLS.contains(name)
+            })
+            .map(str::to_string),
+    );
+
+    let terms = query_terms(prompt);
+    let mut scored = tools
+        .iter()
+        .filter(|(name, _)| !kept.contains(*name))
+        .map(|(name, description)| (score_tool(&terms, name, description), *name))
+        .filter(|(score, _)| *score > 0)
+        .collect::<Vec<(usize, &str)>>();
+    scored.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(b.1)));
+    kept.extend(
+        scored
+            .into_iter()
+            .take(top_k)
+            .map(|(_, name)| name.to_string()),
+    );
+    kept
+}
+
+/// Kept and dropped declaration counts after pruning one request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrunedToolSchemas {
+    pub kept: Vec<String>,
+    pub dropped: Vec<String>,
+}
+
+/// Trim `request.tools` per `strategy`. `full` returns `None` without
+/// touching the request.
+pub fn prune_request_tools(
+    request: &mut LlmRequest,
+    strategy: ToolSchemaStrategy,
+    top_k: usize,
+) -> Option<PrunedToolSchemas> {
+    if strategy == ToolSchemaStrategy::Full || request.tools.is_empty() {
+        return None;
+    }
+    let prompt = latest_prompt_text(&request.contents);
+    let used = previously_used_tools(&request.contents);
+    let declared = request
+        .tools
+        .iter()
+        .map(|(name, declaration)| {
+            let description = declaration
+                .get("description")
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+            (name.as_str(), description)
+        })
+        .collect::<Vec<(&str, &str)>>();
+    let keep = select_tool_declarations(&prompt, &declared, &used, top_k);
+    let mut dropped = request
+        .tools
+        .keys()
+        .filter(|name| !keep.contains(*name))
+        .cloned()
+        .collect::<Vec<String>>();
+    dropped.sort();
+    request.tools.retain(|name, _| keep.contains(name));
+    let mut kept = request.tools.keys().cloned().collect::<Vec<String>>();
+    kept.sort();
+    Some(PrunedToolSchemas { kept, dropped })
+}
+
+/// What the agent's before-model callback needs to prune each request.
+#[derive(Debug, Clone)]
+pub struct ToolSchemaPruning {
+    pub strategy: ToolSchemaStrategy,
+    pub top_k: usize,
+    pub telemetry: TelemetrySink,
+}
+
+impl ToolSchemaPruning {
+    /// Prune `request` and record a `tool.schema_pruned` event.
+    pub fn apply(&self, request: &mut LlmRequest) {
+        let Some(pruned) = prune_request_tools(request, self.strategy, self.top_k) else {
+            return;
+        };
+        tracing::debug!(
+            kept = pruned.kept.len(),
+            dropped = pruned.dropped.len(),
+            "Pruned tool declarations"
+        );
+        self.telemetry.emit(
+            "tool.schema_pruned",
+            json!({
+                "strategy": "relevant",
+                "top_k": self.top_k,
+                "kept": pruned.kept.len(),
+                "dropped": pruned.dropped.len(),
+                "kept_tools": pruned.kept,
+            }),
+        );
+    }
+}
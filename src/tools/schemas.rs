@@ -0,0 +1,610 @@
+//! Argument schemas for built-in tools and a pure pre-validation layer.
+//!
+//! Validation supports the subset of JSON Schema the built-in schemas use:
+//! `type`, `required`, `properties`, `items`, `enum`, `const`, `minimum`,
+//! and `allOf` with `if`/`then` for action-specific required fields.
+//! Schemas are only used for validation; the schema advertised to providers
+//! is left untouched because several providers reject `if`/`then`.
+use std::sync::Arc;
+
+use adk_rust::Result as AdkResult;
+use adk_rust::prelude::*;
+use async_trait::async_trait;
+use serde_json::{Value, json};
+
+use super::suggestions::{argument_suggestions, with_suggestions};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaViolation {
+    /// JSON pointer into the arguments (e.g. `/start_line`).
+    pub instance_path: String,
+    /// JSON pointer into the schema keyword that failed (e.g. `#/properties/start_line/type`).
+    pub schema_path: String,
+    pub message: String,
+}
+
+fn string_prop(description: &str) -> Value {
+    json!({ "type": "string", "description": description })
+}
+
+fn integer_prop(description: &str, minimum: u64) -> Value {
+    json!({ "type": "integer", "minimum": minimum, "description": description })
+}
+
+fn bool_prop(description: &str) -> Value {
+    json!({ "type": "boolean", "description": description })
+}
+
+fn action_requires(action: &str, required: &[&str]) -> Value {
+    json!({
+        "if": { "properties": { "action": { "const": action } } },
+        "then": { "required": required }
+    })
+}
+
+fn kind_requires(kind: &str, field: &str) -> Value {
+    json!({
+        "if": { "properties": { "kind": { "const": kind } } },
+        "then": { "required": [field] }
+    })
+}
+
+/// Validation schema for a built-in tool's arguments, if one is declared.
+pub fn builtin_tool_schema(name: &str) -> Option<Value> {
+    let schema = match name {
+        "fs_read" => json!({
+            "type": "object",
+            "required": ["path"],
+            "properties": {
+                "path": string_prop("file or directory path inside the workspace"),
+                "start_line": integer_prop("1-based first line to return", 1),
+                "max_lines": integer_prop("maximum lines to return", 1),
+                "max_bytes": integer_prop("maximum bytes to read", 1),
+                "max_entries": integer_prop("maximum directory entries", 1),
+                "hex_preview": bool_prop("include a bounded hex dump for binary files")
+            }
+        }),
+        "fs_write" => json!({
+            "type": "object",
+            "required": ["path"],
+            "properties": {
+                "path": string_prop("file path inside the workspace"),
+                "mode": {
+                    "type": "string",
+                    "enum": ["create", "overwrite", "update", "append", "patch"]
+                },
+                "content": string_prop("content for create/overwrite/append"),
+                "patch": {
+                    "type": "object",
+                    "required": ["find", "replace"],
+                    "properties": {
+                        "find": string_prop("exact text to find"),
+                        "replace": string_prop("replacement text"),
+                        "replace_all": bool_prop("replace every occurrence")
+                    }
+                },
+                "allow_secrets": bool_prop("write content that looks like a secret; the user confirms"),
+                "approved": bool_prop("set by the approval gate")
+            },
+            "allOf": [
+                {
+                    "if": { "properties": { "mode": { "const": "patch" } }, "required": ["mode"] },
+                    "then": { "required": ["patch"] }
+                }
+            ]
+        }),
+        "file_edit" => json!({
+            "type": "object",
+            "required": ["file_path", "old_string", "new_string"],
+            "properties": {
+                "file_path": string_prop("file to edit"),
+                "old_string": string_prop("exact text to replace"),
+                "new_string": string_prop("replacement text"),
+                "replace_all": bool_prop("replace every occurrence")
+            }
+        }),
+        "apply_patch" => json!({
+            "type": "object",
+            "required": ["patch"],
+            "properties": {
+                "patch": string_prop("unified diff covering one or more files"),
+                "dry_run": bool_prop("check that every hunk applies without writing"),
+                "allow_secrets": bool_prop("add lines that look like secrets; the user confirms"),
+                "approved": bool_prop("set by the approval gate")
+            }
+        }),
+        "glob" => json!({
+            "type": "object",
+            "required": ["pattern"],
+            "properties": {
+                "pattern": string_prop("glob pattern such as '**/*.rs'"),
+                "path": string_prop("search root")
+            }
+        }),
+        "grep" => json!({
+            "type": "object",
+            "required": ["pattern"],
+            "properties": {
+                "pattern": string_prop("regex pattern"),
+                "path": string_prop("search root"),
+                "glob": string_prop("file filter such as '*.rs'"),
+                "output_mode": {
+                    "type": "string",
+                    "enum": ["content", "files_with_matches", "count"]
+                },
+                "-i": bool_prop("case insensitive"),
+                "-A": integer_prop("lines after each match", 0),
+                "-B": integer_prop("lines before each match", 0),
+                "-C": integer_prop("lines around each match", 0),
+                "file_type": string_prop("ripgrep file type"),
+                "multiline": bool_prop("allow patterns to span lines"),
+                "head_limit": integer_prop("maximum results", 0),
+                "offset": integer_prop("results to skip", 0)
+            }
+        }),
+        "execute_bash" => json!({
+            "type": "object",
+            "required": ["command"],
+            "properties": {
+                "command": string_prop("shell command to run"),
+                "approved": bool_prop("set by the approval gate"),
+                "allow_dangerous": bool_prop("bypass the dangerous-command policy"),
+                "no_sandbox": bool_prop("run outside execute_bash_sandbox; needs approved and allow_dangerous"),
+                "timeout_secs": integer_prop("per-attempt timeout", 1),
+                "retry_attempts": integer_prop("attempts before giving up", 1),
+                "retry_delay_ms": integer_prop("delay between attempts", 0),
+                "max_output_chars": integer_prop("output truncation limit", 1)
+            }
+        }),
+        "github_ops" => json!({
+            "type": "object",
+            "required": ["action"],
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": [
+                        "issue_create",
+                        "issue_update",
+                        "issue_comment",
+                        "pr_create",
+                        "pr_review",
+                        "pr_merge",
+                        "project_item_update"
+                    ]
+                },
+                "repo": string_prop("owner/name"),
+                "title": string_prop("issue or PR title"),
+                "body": string_prop("issue or PR body"),
+                "labels": { "type": "array", "items": { "type": "string" } },
+                "issue_number": string_prop("issue number as a string"),
+                "state": { "type": "string", "enum": ["open", "closed"] },
+                "add_labels": { "type": "array", "items": { "type": "string" } },
+                "remove_labels": { "type": "array", "items": { "type": "string" } },
+                "head": string_prop("head branch"),
+                "base": string_prop("base branch"),
+                "draft": bool_prop("open the PR as a draft"),
+                "pr_number": string_prop("PR number as a string"),
+                "event": { "type": "string", "enum": ["approve", "request_changes", "comment"] },
+                "method": { "type": "string", "enum": ["squash", "merge", "rebase"] },
+                "confirm_merge": bool_prop("must be true; set only after the user confirms the merge"),
+                "project_id": string_prop("project node id"),
+                "item_id": string_prop("project item id"),
+                "field_id": string_prop("status field id"),
+                "status_option_id": string_prop("status option id")
+            },
+            "allOf": [
+                action_requires("issue_create", &["repo", "title", "body"]),
+                action_requires("issue_update", &["repo", "issue_number"]),
+                action_requires("issue_comment", &["repo", "issue_number", "body"]),
+                action_requires("pr_create", &["repo", "title", "body"]),
+                action_requires("pr_review", &["repo", "pr_number", "event"]),
+                action_requires("pr_merge", &["repo", "pr_number"]),
+                action_requires(
+                    "project_item_update",
+                    &["project_id", "item_id", "field_id", "status_option_id"]
+                )
+            ]
+        }),
+        "remember" => json!({
+            "type": "object",
+            "required": ["fact"],
+            "properties": {
+                "fact": string_prop("one-line durable fact to store")
+            }
+        }),
+        "recall" => json!({
+            "type": "object",
+            "required": ["query"],
+            "properties": {
+                "query": string_prop("terms to search remembered facts for"),
+                "limit": integer_prop("maximum facts to return", 1)
+            }
+        }),
+        "doc_search" => json!({
+            "type": "object",
+            "required": ["query"],
+            "properties": {
+                "query": string_prop("terms to search the retrieval docs for"),
+                "max_chunks": integer_prop("maximum chunks to return", 1)
+            }
+        }),
+        "policy_check" => json!({
+            "type": "object",
+            "required": ["kind"],
+            "properties": {
+                "kind": {
+                    "type": "string",
+                    "enum": ["fs_read", "fs_write", "execute_bash"]
+                },
+                "path": string_prop("file path to check (fs_read, fs_write)"),
+                "command": string_prop("shell command to check (execute_bash)")
+            },
+            "allOf": [
+                kind_requires("fs_read", "path"),
+                kind_requires("fs_write", "path"),
+                kind_requires("execute_bash", "command")
+            ]
+        }),
+        "todo_list" => json!({
+            "type": "object",
+            "required": ["action"],
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["create", "complete", "view", "list", "delete"]
+                },
+                "id": string_prop("todo list id"),
+                "description": string_prop("todo list description"),
+                "tasks": { "type": "array", "items": { "type": "string" } },
+                "task_index": integer_prop("0-based task index", 0)
+            },
+            "allOf": [
+                action_requires("create", &["id", "tasks"]),
+                action_requires("complete", &["id", "task_index"]),
+                action_requires("view", &["id"]),
+                action_requires("delete", &["id"])
+            ]
+        }),
+        _ => return None,
+    };
+    Some(schema)
+}
+
+/// A minimal valid call for a built-in tool, returned alongside `invalid_args` errors.
+pub fn builtin_tool_example(name: &str) -> Option<Value> {
+    let example = match name {
+        "fs_read" => json!({ "path": "src/main.rs", "start_line": 1, "max_lines": 100 }),
+        "fs_write" => json!({ "path": "notes.md", "mode": "create", "content": "# Notes\n" }),
+        "file_edit" => json!({
+            "file_path": "src/lib.rs",
+            "old_string": "fn old()",
+            "new_string": "fn new()"
+        }),
+        "apply_patch" => json!({
+            "patch": "--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1 +1 @@\n-fn old() {}\n+fn new() {}\n"
+        }),
+        "glob" => json!({ "pattern": "**/*.rs" }),
+        "grep" => json!({ "pattern": "TODO", "output_mode": "content" }),
+        "execute_bash" => json!({ "command": "cargo test", "timeout_secs": 120 }),
+        "github_ops" => json!({
+            "action": "issue_create",
+            "repo": "owner/name",
+            "title": "Short title",
+            "body": "Details"
+        }),
+        "todo_list" => json!({ "action": "create", "id": "plan", "tasks": ["first step"] }),
+        "remember" => json!({ "fact": "Releases are cut on Thursdays" }),
+        "recall" => json!({ "query": "release day" }),
+        "doc_search" => json!({ "query": "release process", "max_chunks": 3 }),
+        "policy_check" => json!({ "kind": "fs_write", "path": "notes.md" }),
+        _ => return None,
+    };
+    Some(example)
+}
+
+fn json_type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_i64() || n.is_u64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn type_matches(expected: &str, value: &Value) -> bool {
+    let actual = json_type_name(value);
+    actual == expected || (expected == "number" && actual == "integer")
+}
+
+fn escape_pointer(segment: &str) -> String {
+    segment.replace('~', "~0").replace('/', "~1")
+}
+
+fn validate_at(
+    schema: &Value,
+    value: &Value,
+    instance_path: &str,
+    schema_path: &str,
+) -> Result<(), SchemaViolation> {
+    let violation = |keyword: &str, message: String| SchemaViolation {
+        instance_path: if instance_path.is_empty() {
+            "/".to_string()
+        } else {
+            instance_path.to_string()
+        },
+        schema_path: format!("{schema_path}/{keyword}"),
+        message,
+    };
+    let display_path = if instance_path.is_empty() {
+        "arguments".to_string()
+    } else {
+        format!("'{}'", instance_path.trim_start_matches('/'))
+    };
+
+    if let Some(expected) = schema.get("type").and_then(Value::as_str)
+        && !type_matches(expected, value)
+    {
+        return Err(violation(
+            "type",
+            format!(
+                "{display_path} must be of type {expected}, got {}",
+                json_type_name(value)
+            ),
+        ));
+    }
+
+    if let Some(expected) = schema.get("const")
+        && value != expected
+    {
+        return Err(violation(
+            "const",
+            format!("{display_path} must equal {expected}"),
+        ));
+    }
+
+    if let Some(options) = schema.get("enum").and_then(Value::as_array)
+        && !options.contains(value)
+    {
+        let rendered = options
+            .iter()
+            .map(|option| {
+                option
+                    .as_str()
+                    .map(str::to_string)
+                    .unwrap_or_else(|| option.to_string())
+            })
+            .collect::<Vec<String>>()
+            .join(", ");
+        return Err(violation(
+            "enum",
+            format!("{display_path} must be one of: {rendered}"),
+        ));
+    }
+
+    if let Some(minimum) = schema.get("minimum").and_then(Value::as_f64)
+        && let Some(number) = value.as_f64()
+        && number < minimum
+    {
+        return Err(violation(
+            "minimum",
+            format!("{display_path} must be >= {minimum}"),
+        ));
+    }
+
+    if let Some(object) = value.as_object() {
+        if let Some(required) = schema.get("required").and_then(Value::as_array) {
+            for key in required.iter().filter_map(Value::as_str) {
+                if !object.contains_key(key) {
+                    return Err(SchemaViolation {
+                        instance_path: format!("{instance_path}/{}", escape_pointer(key)),
+                        schema_path: format!("{schema_path}/required"),
+                        message: format!("'{key}' is required"),
+                    });
+                }
+            }
+        }
+        if let Some(properties) = schema.get("properties").and_then(Value::as_object) {
+            for (key, property_schema) in properties {
+                if let Some(property_value) = object.get(key) {
+                    let escaped = escape_pointer(key);
+                    validate_at(
+                        property_schema,
+                        property_value,
+                        &format!("{instance_path}/{escaped}"),
+                        &format!("{schema_path}/properties/{escaped}"),
+                    )?;
+                }
+            }
+        }
+    }
+
+    if let Some(items) = value.as_array()
+        && let Some(item_schema) = schema.get("items")
+    {
+        for (index, item) in items.iter().enumerate() {
+            validate_at(
+                item_schema,
+                item,
+                &format!("{instance_path}/{index}"),
+                &format!("{schema_path}/items"),
+            )?;
+        }
+    }
+
+    if let Some(all_of) = schema.get("allOf").and_then(Value::as_array) {
+        for (index, sub_schema) in all_of.iter().enumerate() {
+            let sub_path = format!("{schema_path}/allOf/{index}");
+            if let Some(condition) = sub_schema.get("if") {
+                if validate_at(condition, value, instance_path, &sub_path).is_ok()
+                    && let Some(then) = sub_schema.get("then")
+                {
+                    validate_at(then, value, instance_path, &format!("{sub_path}/then"))?;
+                }
+            } else {
+                validate_at(sub_schema, value, instance_path, &sub_path)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Validate `args` against `schema`, returning the first violation found.
+pub fn validate_against_schema(schema: &Value, args: &Value) -> Result<(), SchemaViolation> {
+    validate_at(schema, args, "", "#")
+}
+
+/// Validate arguments for a built-in tool. Tools without a declared schema always pass.
+pub fn validate_tool_args(tool_name: &str, args: &Value) -> Result<(), SchemaViolation> {
+    match builtin_tool_schema(tool_name) {
+        Some(schema) => validate_against_schema(&schema, args),
+        None => Ok(()),
+    }
+}
+
+/// Structured `invalid_args` error for a call that failed schema validation.
+pub fn invalid_args_payload(
+    tool_name: &str,
+    violation: &SchemaViolation,
+    retry_hint: bool,
+) -> Value {
+    let example = builtin_tool_example(tool_name).unwrap_or(Value::Null);
+    let mut payload = json!({
+        "status": "error",
+        "kind": tool_name,
+        "code": "invalid_args",
+        "error": violation.message,
+        "instance_path": violation.instance_path,
+        "schema_path": violation.schema_path,
+        "example": example
+    });
+    payload = with_suggestions(payload, argument_suggestions(tool_name, &violation.message));
+    if retry_hint && let Some(obj) = payload.as_object_mut() {
+        obj.insert(
+            "retry_hint".to_string(),
+            Value::String(format!(
+                "The previous {tool_name} call was rejected before execution: {}. \
+                 Call {tool_name} again with corrected arguments matching the example; \
+                 do not give up on the task.",
+                violation.message
+            )),
+        );
+    }
+    payload
+}
+
+/// One-line summary of a schema's top-level properties for `/tools --verbose`.
+pub fn schema_summary(schema: &Value) -> String {
+    let required = schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(Value::as_str)
+                .collect::<Vec<&str>>()
+        })
+        .unwrap_or_default();
+    let Some(properties) = schema.get("properties").and_then(Value::as_object) else {
+        return "<no properties>".to_string();
+    };
+
+    let mut names = properties.keys().map(String::as_str).collect::<Vec<&str>>();
+    names.sort_by_key(|name| (!required.contains(name), *name));
+    names
+        .into_iter()
+        .map(|name| {
+            let ty = properties[name]
+                .get("type")
+                .and_then(Value::as_str)
+                .unwrap_or("any");
+            if required.contains(&name) {
+                format!("{name}: {ty} (required)")
+            } else {
+                format!("{name}: {ty}")
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+// ---------------------------------------------------------------------------
+// Validating tool wrapper
+// ---------------------------------------------------------------------------
+
+/// Wraps a built-in tool and rejects calls whose arguments fail its schema
+/// before the inner tool (or its confirmation prompt) runs.
+pub struct ValidatingTool {
+    inner: Arc<dyn Tool>,
+    schema: Value,
+    retry_hint: bool,
+}
+
+impl ValidatingTool {
+    pub fn new(inner: Arc<dyn Tool>, schema: Value, retry_hint: bool) -> Self {
+        Self {
+            inner,
+            schema,
+            retry_hint,
+        }
+    }
+}
+
+#[async_trait]
+impl Tool for ValidatingTool {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn description(&self) -> &str {
+        self.inner.description()
+    }
+
+    fn enhanced_description(&self) -> String {
+        self.inner.enhanced_description()
+    }
+
+    fn is_long_running(&self) -> bool {
+        self.inner.is_long_running()
+    }
+
+    fn parameters_schema(&self) -> Option<Value> {
+        self.inner.parameters_schema()
+    }
+
+    fn response_schema(&self) -> Option<Value> {
+        self.inner.response_schema()
+    }
+
+    async fn execute(&self, ctx: Arc<dyn ToolContext>, args: Value) -> AdkResult<Value> {
+        if let Err(violation) = validate_against_schema(&self.schema, &args) {
+            tracing::debug!(
+                tool = self.inner.name(),
+                schema_path = violation.schema_path.as_str(),
+                "Rejected tool call with invalid arguments"
+            );
+            return Ok(invalid_args_payload(
+                self.inner.name(),
+                &violation,
+                self.retry_hint,
+            ));
+        }
+        self.inner.execute(ctx, args).await
+    }
+}
+
+/// Wrap every tool that has a built-in schema with [`ValidatingTool`].
+pub fn wrap_with_arg_validation(tools: Vec<Arc<dyn Tool>>, retry_hint: bool) -> Vec<Arc<dyn Tool>> {
+    tools
+        .into_iter()
+        .map(|tool| match builtin_tool_schema(tool.name()) {
+            Some(schema) => {
+                Arc::new(ValidatingTool::new(tool, schema, retry_hint)) as Arc<dyn Tool>
+            }
+            None => tool,
+        })
+        .collect()
+}
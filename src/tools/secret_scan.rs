@@ -0,0 +1,448 @@
+//! Secret detection for content `fs_write` and `apply_patch` put on disk.
+//!
+//! The agent sees keys in tool output and chat history and will happily copy
+//! one into a config file. Before a write, the new text is checked for known
+//! key formats (AWS access key ids, GitHub, Slack and `sk-` API keys, PEM
+//! private keys), long high-entropy base64 tokens, and literal values assigned
+//! to a guardrail term (`api_key = "..."`; `${VAR}`-style placeholders are
+//! fine). `fs_write_secret_policy` decides what happens: `block` refuses the
+//! write with `secret_detected`, `redact` writes the content with each value
+//! replaced and `allow` writes it unchanged. A single call can override a
+//! false positive with `allow_secrets=true`; such calls always prompt, even
+//! for a trusted or allow-listed tool, and only run once the user approves
+//! them. Every finding emits `tool.secret_detected` with the kind and line, never the
+//! value.
+//!
+//! `fs_write_secret_allowlist` takes wildcard patterns. A pattern matching the
+//! file (`*.lock`, `package-lock.json`) turns off the entropy and guardrail
+//! heuristics for it; one matching a token (`sha512-*`) lets that token
+//! through anywhere. Known key formats are always checked.
+use serde_json::{Value, json};
+
+use crate::cli::FsWriteSecretPolicy;
+use crate::guardrail::{GuardrailMatcher, default_guardrail_terms};
+use crate::telemetry::TelemetrySink;
+use crate::tool_policy::matches_wildcard;
+
+/// Shortest token the entropy heuristic looks at.
+const HIGH_ENTROPY_MIN_CHARS: usize = 32;
+/// Shannon entropy, in bits per character, above which a mixed-case
+/// alphanumeric token reads as random. Hex digests top out at 4.0.
+const HIGH_ENTROPY_MIN_BITS: f64 = 4.2;
+/// Shortest literal value flagged after a guardrail term.
+const ASSIGNED_SECRET_MIN_CHARS: usize = 6;
+
+const PLACEHOLDER_VALUES: &[&str] = &[
+    "changeme",
+    "change_me",
+    "change-me",
+    "password",
+    "secret",
+    "redacted",
+    "[redacted]",
+    "placeholder",
+    "undefined",
+    "example",
+];
+
+#[derive(Debug, Clone)]
+pub struct SecretScanOptions {
+    pub policy: FsWriteSecretPolicy,
+    pub allowlist: Vec<String>,
+    pub guardrail_terms: Vec<String>,
+    pub redact_replacement: String,
+    pub telemetry: Option<TelemetrySink>,
+}
+
+impl Default for SecretScanOptions {
+    fn default() -> Self {
+        Self {
+            policy: FsWriteSecretPolicy::Block,
+            allowlist: Vec::new(),
+            guardrail_terms: default_guardrail_terms(),
+            redact_replacement: "[REDACTED]".to_string(),
+            telemetry: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SecretFinding {
+    /// `aws_access_key_id`, `github_token`, `slack_token`, `api_key`,
+    /// `private_key`, `high_entropy` or `guardrail_term`.
+    pub kind: &'static str,
+    /// 1-based line in the scanned text.
+    pub line: usize,
+    start: usize,
+    end: usize,
+}
+
+/// `[{kind, line}]`, safe to show the model and to log.
+pub fn secret_findings_json(findings: &[SecretFinding]) -> Value {
+    Value::Array(
+        findings
+            .iter()
+            .map(|finding| json!({ "kind": finding.kind, "line": finding.line }))
+            .collect(),
+    )
+}
+
+fn is_token_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '+' | '/' | '=' | '_' | '-')
+}
+
+/// Byte ranges of maximal token-character runs in `line`.
+fn token_spans(line: &str) -> Vec<(usize, usize)> {
+    let mut spans = Vec::new();
+    let mut start = None;
+    for (index, c) in line.char_indices() {
+        match (is_token_char(c), start) {
+            (true, None) => start = Some(index),
+            (false, Some(begin)) => {
+                spans.push((begin, index));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(begin) = start {
+        spans.push((begin, line.len()));
+    }
+    spans
+}
+
+fn known_secret_kind(token: &str) -> Option<&'static str> {
+    let alnum_after = |prefix: &str| {
+        token[prefix.len()..]
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric())
+    };
+    if (token.starts_with("AKIA") || token.starts_with("ASIA"))
+        && token.len() == 20
+        && token
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+    {
+        return Some("aws_access_key_id");
+    }
+    if ["ghp_", "gho_", "ghu_", "ghs_", "ghr_"]
+        .iter()
+        .any(|prefix| token.starts_with(prefix) && token.len() >= 40 && alnum_after(prefix))
+        || (token.starts_with("github_pat_") && token.len() >= 40)
+    {
+        return Some("github_token");
+    }
+    if ["xoxb-", "xoxa-", "xoxp-", "xoxr-", "xoxs-"]
+        .iter()
+        .any(|prefix| token.starts_with(prefix))
+        && token.len() >= 20
+    {
+        return Some("slack_token");
+    }
+    if token.starts_with("sk-") && token.len() >= 24 && token.chars().any(|c| c.is_ascii_digit()) {
+        return Some("api_key");
+    }
+    None
+}
+
+fn shannon_entropy(token: &str) -> f64 {
+    let mut counts = [0usize; 128];
+    for byte in token.bytes() {
+        counts[(byte & 0x7f) as usize] += 1;
+    }
+    let len = token.len() as f64;
+    counts
+        .iter()
+        .filter(|count| **count > 0)
+        .map(|count| {
+            let p = *count as f64 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+fn is_high_entropy(token: &str) -> bool {
+    token.len() >= HIGH_ENTROPY_MIN_CHARS
+        && token.chars().any(|c| c.is_ascii_uppercase())
+        && token.chars().any(|c| c.is_ascii_lowercase())
+        && token.chars().any(|c| c.is_ascii_digit())
+        && shannon_entropy(token) >= HIGH_ENTROPY_MIN_BITS
+}
+
+fn is_placeholder_value(value: &str, replacement: &str) -> bool {
+    let lower = value.to_ascii_lowercase();
+    value.chars().count() < ASSIGNED_SECRET_MIN_CHARS
+        || value.starts_with(['$', '<', '{', '%', '*'])
+        || value.contains("${")
+        || value.contains(char::is_whitespace)
+        || value == replacement
+        || value
+            .chars()
+            .all(|c| c == value.chars().next().unwrap_or_default())
+        || PLACEHOLDER_VALUES.contains(&lower.as_str())
+        || lower.contains("example")
+        || lower.starts_with("your_")
+        || lower.starts_with("your-")
+}
+
+/// Wildcard match against the workspace-relative path or the file name.
+fn path_matches(pattern: &str, path: &str) -> bool {
+    let path = path.trim_start_matches("./");
+    let file_name = path.rsplit('/').next().unwrap_or(path);
+    matches_wildcard(pattern, path) || matches_wildcard(pattern, file_name)
+}
+
+pub struct SecretScanner<'a> {
+    options: &'a SecretScanOptions,
+    guardrail: GuardrailMatcher,
+}
+
+impl<'a> SecretScanner<'a> {
+    pub fn new(options: &'a SecretScanOptions) -> Self {
+        Self {
+            options,
+            guardrail: GuardrailMatcher::new(&options.guardrail_terms),
+        }
+    }
+
+    fn token_allowed(&self, token: &str) -> bool {
+        self.options
+            .allowlist
+            .iter()
+            .any(|pattern| matches_wildcard(pattern, token))
+    }
+
+    /// Literal values assigned to a guardrail term on `line`: `key = "v"`,
+    /// `"key": "v"`, or a bare `KEY=v1` that ends the line.
+    fn assigned_values(&self, line: &str) -> Vec<(usize, usize)> {
+        let mut values = Vec::new();
+        for (_, term_end, _) in self.guardrail.match_spans(line) {
+            let rest = line[term_end..]
+                .trim_start_matches(|c: char| c.is_ascii_alphanumeric() || matches!(c, '_' | '-'))
+                .trim_start_matches(['"', '\''])
+                .trim_start();
+            let Some(after_op) = [":=", "=>", "=", ":"]
+                .iter()
+                .find_map(|op| rest.strip_prefix(op))
+            else {
+                continue;
+            };
+            let value_part = after_op.trim_start();
+            let value_start = line.len() - value_part.len();
+            let (start, end, quoted) = match value_part.chars().next() {
+                Some(quote @ ('"' | '\'')) => {
+                    let Some(close) = value_part[1..].find(quote) else {
+                        continue;
+                    };
+                    (value_start + 1, value_start + 1 + close, true)
+                }
+                _ => {
+                    let len = value_part
+                        .find(|c: char| c.is_whitespace() || matches!(c, ',' | ';' | '#'))
+                        .unwrap_or(value_part.len());
+                    (value_start, value_start + len, false)
+                }
+            };
+            let value = &line[start..end];
+            if is_placeholder_value(value, &self.options.redact_replacement) {
+                continue;
+            }
+            // A bare value is a variable or an expression unless it ends a
+            // `KEY=value` line and has a digit in it.
+            if !quoted
+                && (!line[end..].trim().is_empty()
+                    || !value.chars().any(|c| c.is_ascii_digit())
+                    || value.contains(['.', '(', '[', ')']))
+            {
+                continue;
+            }
+            values.push((start, end));
+        }
+        values
+    }
+
+    /// Findings in `text`, written to `path` (workspace-relative), in order.
+    pub fn scan(&self, path: &str, text: &str) -> Vec<SecretFinding> {
+        let heuristics = !self
+            .options
+            .allowlist
+            .iter()
+            .any(|pattern| path_matches(pattern, path));
+        let mut findings = Vec::<SecretFinding>::new();
+        let mut offset = 0usize;
+        for (index, line) in text.split_inclusive('\n').enumerate() {
+            let content = line.trim_end_matches(['\n', '\r']);
+            let mut push = |kind: &'static str, start: usize, end: usize| {
+                let (start, end) = (offset + start, offset + end);
+                if !findings
+                    .iter()
+                    .any(|found| start < found.end && found.start < end)
+                {
+                    findings.push(SecretFinding {
+                        kind,
+                        line: index + 1,
+                        start,
+                        end,
+                    });
+                }
+            };
+            if content.contains("-----BEGIN") && content.contains("PRIVATE KEY-----") {
+                push("private_key", 0, content.len());
+            }
+            for (start, end) in token_spans(content) {
+                let token = &content[start..end];
+                if self.token_allowed(token) {
+                    continue;
+                }
+                if let Some(kind) = known_secret_kind(token) {
+                    push(kind, start, end);
+                } else if heuristics && is_high_entropy(token) {
+                    push("high_entropy", start, end);
+                }
+            }
+            if heuristics {
+                for (start, end) in self.assigned_values(content) {
+                    if !self.token_allowed(&content[start..end]) {
+                        push("guardrail_term", start, end);
+                    }
+                }
+            }
+            offset += line.len();
+        }
+        findings.sort_by_key(|finding| finding.start);
+        findings
+    }
+}
+
+/// `text` with every finding replaced by `replacement`.
+pub fn redact_secrets(text: &str, findings: &[SecretFinding], replacement: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut cursor = 0usize;
+    for finding in findings {
+        out.push_str(&text[cursor..finding.start]);
+        out.push_str(replacement);
+        cursor = finding.end;
+    }
+    out.push_str(&text[cursor..]);
+    out
+}
+
+/// What a write should do after the scan.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SecretWriteDecision {
+    /// Write `content`; `redacted` lists what was replaced in it.
+    Write {
+        content: String,
+        redacted: Vec<SecretFinding>,
+    },
+    Refuse {
+        message: String,
+        findings: Vec<SecretFinding>,
+    },
+}
+
+fn summarize_findings(findings: &[SecretFinding]) -> String {
+    findings
+        .iter()
+        .map(|finding| format!("{} on line {}", finding.kind, finding.line))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+/// Whether a write asks to keep secrets. These are confirmed on every call,
+/// even for a trusted tool or under `--tool-confirmation-mode never`.
+pub fn secret_write_requires_confirmation(args: &Value) -> bool {
+    args.get("allow_secrets")
+        .and_then(Value::as_bool)
+        .unwrap_or(false)
+}
+
+/// Scan `content` for `tool` writing `path` and apply the policy, honouring
+/// the per-call `allow_secrets` override in `args` once the approval gate
+/// has confirmed it.
+pub fn decide_secret_write(
+    options: &SecretScanOptions,
+    tool: &str,
+    path: &str,
+    content: &str,
+    args: &Value,
+) -> SecretWriteDecision {
+    let findings = SecretScanner::new(options).scan(path, content);
+    if findings.is_empty() {
+        return SecretWriteDecision::Write {
+            content: content.to_string(),
+            redacted: Vec::new(),
+        };
+    }
+    let flag = |name: &str| args.get(name).and_then(Value::as_bool).unwrap_or(false);
+    let (allow_secrets, approved) = (flag("allow_secrets"), flag("approved"));
+    let (action, decision) = if allow_secrets && approved {
+        (
+            "overridden",
+            SecretWriteDecision::Write {
+                content: content.to_string(),
+                redacted: Vec::new(),
+            },
+        )
+    } else if allow_secrets {
+        (
+            "blocked",
+            SecretWriteDecision::Refuse {
+                message: format!(
+                    "{tool} writes content with secrets ({}) only after the user confirms the call",
+                    summarize_findings(&findings)
+                ),
+                findings: findings.clone(),
+            },
+        )
+    } else {
+        match options.policy {
+            FsWriteSecretPolicy::Allow => (
+                "allowed",
+                SecretWriteDecision::Write {
+                    content: content.to_string(),
+                    redacted: Vec::new(),
+                },
+            ),
+            FsWriteSecretPolicy::Redact => (
+                "redacted",
+                SecretWriteDecision::Write {
+                    content: redact_secrets(content, &findings, &options.redact_replacement),
+                    redacted: findings.clone(),
+                },
+            ),
+            FsWriteSecretPolicy::Block => (
+                "blocked",
+                SecretWriteDecision::Refuse {
+                    message: format!(
+                        "{tool} refused to write '{path}': content looks like it contains a \
+                         secret ({}). Reference an environment variable instead; if this is a \
+                         false positive, retry with allow_secrets=true and the user will be asked \
+                         to confirm the write.",
+                        summarize_findings(&findings)
+                    ),
+                    findings: findings.clone(),
+                },
+            ),
+        }
+    };
+    if let Some(telemetry) = &options.telemetry {
+        let mut kinds = findings
+            .iter()
+            .map(|finding| finding.kind)
+            .collect::<Vec<&str>>();
+        kinds.sort_unstable();
+        kinds.dedup();
+        telemetry.emit(
+            "tool.secret_detected",
+            json!({
+                "tool": tool,
+                "path": path,
+                "action": action,
+                "kinds": kinds,
+                "findings": secret_findings_json(&findings),
+            }),
+        );
+    }
+    decision
+}
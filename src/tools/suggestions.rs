@@ -0,0 +1,247 @@
+//! Correction hints for built-in tool errors.
+//!
+//! A bare "path does not exist" tends to get the same call retried verbatim,
+//! so error payloads carry a `suggestions` array the model can act on:
+//!
+//! - `invalid_path`: the nearest existing ancestor directory and, when a
+//!   close name exists, a `did_you_mean` path;
+//! - `invalid_args`: each field the error names, with its expected type,
+//!   allowed values and an example value from the tool's schema;
+//! - `denied_path`: the workspace policy rule that refused the path.
+//!
+//! The did-you-mean scan is bounded: it only lists the directories along the
+//! requested path, at most [`SUGGESTION_SCAN_MAX_ENTRIES`] entries each, and
+//! never walks the workspace.
+use std::path::{Component, Path, PathBuf};
+
+use serde_json::{Map, Value, json};
+
+use super::fs_read::{
+    FS_READ_DENIED_FILE_NAMES, FS_READ_DENIED_SEGMENTS, PathDenial, workspace_path_denial,
+};
+use super::schemas::{builtin_tool_example, builtin_tool_schema};
+
+/// Directory entries compared per path component.
+pub const SUGGESTION_SCAN_MAX_ENTRIES: usize = 512;
+/// Path components a did-you-mean candidate may correct.
+pub const SUGGESTION_MAX_CORRECTED_COMPONENTS: usize = 2;
+
+/// `payload` with `suggestions` added when there are any.
+pub fn with_suggestions(mut payload: Value, suggestions: Vec<Value>) -> Value {
+    if !suggestions.is_empty()
+        && let Some(object) = payload.as_object_mut()
+    {
+        object.insert("suggestions".to_string(), Value::Array(suggestions));
+    }
+    payload
+}
+
+/// Character edit distance, case-insensitive.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a = a.to_lowercase().chars().collect::<Vec<char>>();
+    let b = b.to_lowercase().chars().collect::<Vec<char>>();
+    let mut previous = (0..=b.len()).collect::<Vec<usize>>();
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// The closest of `names` to `wanted`, if it is close enough to be a typo:
+/// at most a third of the name's length (and at least one edit) away.
+pub fn closest_name<'a>(wanted: &str, names: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    let limit = (wanted.chars().count() / 3).max(1);
+    names
+        .into_iter()
+        .map(|name| (edit_distance(wanted, name), name))
+        .filter(|(distance, _)| *distance <= limit)
+        .min_by_key(|(distance, name)| (*distance, *name))
+        .map(|(_, name)| name)
+}
+
+fn absolute_request(workspace_root: &Path, requested: &str) -> PathBuf {
+    let requested = Path::new(requested);
+    if requested.is_absolute() {
+        requested.to_path_buf()
+    } else {
+        workspace_root.join(requested)
+    }
+}
+
+/// The deepest existing directory on the way to `requested`.
+pub fn nearest_existing_ancestor(workspace_root: &Path, requested: &str) -> Option<PathBuf> {
+    let absolute = absolute_request(workspace_root, requested);
+    absolute
+        .ancestors()
+        .skip(1)
+        .find(|dir| dir.is_dir())
+        .map(Path::to_path_buf)
+}
+
+fn dir_entry_names(dir: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .take(SUGGESTION_SCAN_MAX_ENTRIES)
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .collect()
+}
+
+/// An existing path that differs from `requested` by a typo in at most
+/// [`SUGGESTION_MAX_CORRECTED_COMPONENTS`] components.
+pub fn did_you_mean(workspace_root: &Path, requested: &str) -> Option<PathBuf> {
+    let absolute = absolute_request(workspace_root, requested);
+    let mut current = PathBuf::new();
+    let mut corrected = 0;
+    for component in absolute.components() {
+        let Component::Normal(name) = component else {
+            current.push(component);
+            continue;
+        };
+        let next = current.join(name);
+        if next.exists() {
+            current = next;
+            continue;
+        }
+        if corrected == SUGGESTION_MAX_CORRECTED_COMPONENTS {
+            return None;
+        }
+        let names = dir_entry_names(&current);
+        let wanted = name.to_string_lossy();
+        let candidate = closest_name(&wanted, names.iter().map(String::as_str))?;
+        current.push(candidate);
+        corrected += 1;
+    }
+    (corrected > 0 && current.exists()).then_some(current)
+}
+
+fn display_path(path: &Path, workspace_root: &Path) -> String {
+    match path.strip_prefix(workspace_root) {
+        Ok(relative) if relative.as_os_str().is_empty() => ".".to_string(),
+        Ok(relative) => relative.display().to_string(),
+        Err(_) => path.display().to_string(),
+    }
+}
+
+/// `invalid_path` hints: the nearest existing directory, then a
+/// did-you-mean candidate when there is one. Paths the workspace policy
+/// would refuse are never suggested.
+pub fn path_suggestions(workspace_root: &Path, requested: &str) -> Vec<Value> {
+    let allowed = |path: &PathBuf| workspace_path_denial(path, workspace_root).is_none();
+    let mut suggestions = Vec::new();
+    if let Some(dir) = nearest_existing_ancestor(workspace_root, requested).filter(allowed) {
+        suggestions.push(json!({
+            "kind": "nearest_existing_dir",
+            "path": display_path(&dir, workspace_root),
+        }));
+    }
+    if let Some(candidate) = did_you_mean(workspace_root, requested).filter(allowed) {
+        suggestions.push(json!({
+            "kind": "did_you_mean",
+            "path": display_path(&candidate, workspace_root),
+        }));
+    }
+    suggestions
+}
+
+/// `denied_path` hint naming the rule that refused the path.
+pub fn policy_rule_suggestion(denial: &PathDenial, workspace_root: &Path) -> Value {
+    let detail = match denial {
+        PathDenial::OutsideWorkspace => format!(
+            "paths must resolve inside the workspace root '{}'",
+            workspace_root.display()
+        ),
+        PathDenial::Segment(segment) => format!(
+            "paths with a '{segment}' segment are blocked (blocked segments: {})",
+            FS_READ_DENIED_SEGMENTS.join(", ")
+        ),
+        PathDenial::FileName(name) => format!(
+            "files named '{name}' are blocked (blocked names: {})",
+            FS_READ_DENIED_FILE_NAMES.join(", ")
+        ),
+    };
+    json!({ "kind": "policy_rule", "rule": denial.rule(), "detail": detail })
+}
+
+/// Argument names quoted in `message` (`'path' is required`), or failing
+/// that, any bare word that is one of `fields` (`event must be one of ...`).
+fn fields_named_in(message: &str, fields: &Map<String, Value>) -> Vec<String> {
+    let quoted = message
+        .split('\'')
+        .skip(1)
+        .step_by(2)
+        .filter(|name| fields.contains_key(*name))
+        .collect::<Vec<&str>>();
+    let named = if quoted.is_empty() {
+        message
+            .split(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .filter(|word| fields.contains_key(*word))
+            .collect()
+    } else {
+        quoted
+    };
+    let mut unique = Vec::<String>::new();
+    for name in named {
+        if !unique.iter().any(|seen| seen == name) {
+            unique.push(name.to_string());
+        }
+    }
+    unique
+}
+
+/// Hint for one argument of a built-in tool: its type, allowed values and
+/// an example, from the tool's schema.
+pub fn argument_suggestion(tool: &str, field: &str) -> Option<Value> {
+    let schema = builtin_tool_schema(tool)?;
+    let property = schema.get("properties")?.get(field)?;
+    let mut suggestion = json!({
+        "kind": "argument",
+        "field": field,
+        "expected": property.get("type").cloned().unwrap_or(Value::Null),
+    });
+    if let Some(allowed) = property.get("enum") {
+        suggestion["allowed"] = allowed.clone();
+    }
+    let example = builtin_tool_example(tool)
+        .and_then(|example| example.get(field).cloned())
+        .or_else(|| {
+            property
+                .get("enum")
+                .and_then(|values| values.get(0))
+                .cloned()
+        });
+    if let Some(example) = example {
+        suggestion["example"] = example;
+    }
+    Some(suggestion)
+}
+
+/// Hints for the fields of `tool` that `message` names.
+pub fn argument_suggestions(tool: &str, message: &str) -> Vec<Value> {
+    let Some(fields) = builtin_tool_schema(tool)
+        .and_then(|schema| schema.get("properties").and_then(Value::as_object).cloned())
+    else {
+        return Vec::new();
+    };
+    fields_named_in(message, &fields)
+        .iter()
+        .filter_map(|field| argument_suggestion(tool, field))
+        .collect()
+}
+
+/// `invalid_args` hints: [`argument_suggestions`] followed by a minimal
+/// valid call.
+pub fn invalid_args_suggestions(tool: &str, message: &str) -> Vec<Value> {
+    let mut suggestions = argument_suggestions(tool, message);
+    if let Some(example) = builtin_tool_example(tool) {
+        suggestions.push(json!({ "kind": "example_call", "arguments": example }));
+    }
+    suggestions
+}
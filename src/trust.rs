@@ -0,0 +1,308 @@
+//! Workspace trust (`zavora-cli trust`).
+//!
+//! A repository can ship `.zavora/` config that widens tool access, installs
+//! hooks or rewrites the agent instruction. Local config is only loaded once
+//! the workspace is trusted: the trust store in the state dir records each
+//! trusted root (canonical path) with a sha256 of every `*.toml` directly in
+//! its `.zavora/`. The profile and agent selection files are not hashed since
+//! `profiles select` / `agents select` rewrite them, but they are still
+//! ignored until the workspace is trusted.
+//!
+//! An untrusted workspace, or a trusted one whose files changed since `trust`,
+//! runs on global and built-in config only, with a warning on stderr.
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::paths::ZavoraPaths;
+use crate::session_bundle::sha256_hex;
+use crate::ui::Ui;
+
+/// Local files that are not hashed (see the module docs).
+const UNHASHED_FILES: &[&str] = &["agent-selection.toml", "profile-selection.toml"];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrustedWorkspace {
+    pub trusted_at: String,
+    /// File name in `.zavora/` → sha256 when trusted.
+    #[serde(default)]
+    pub files: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrustStore {
+    /// Keyed by the canonical workspace root.
+    #[serde(default)]
+    pub workspaces: BTreeMap<String, TrustedWorkspace>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WorkspaceTrust {
+    /// No `.zavora/` config to gate.
+    NoLocalConfig,
+    Untrusted,
+    Trusted,
+    /// Trusted, but these files were added, removed or edited since.
+    Changed(Vec<String>),
+}
+
+impl WorkspaceTrust {
+    /// Whether local config may be loaded.
+    pub fn allows_local_config(&self) -> bool {
+        matches!(self, Self::NoLocalConfig | Self::Trusted)
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::NoLocalConfig => "no local config",
+            Self::Untrusted => "untrusted",
+            Self::Trusted => "trusted",
+            Self::Changed(_) => "changed since trusted",
+        }
+    }
+}
+
+/// Canonical root of the workspace owning `workspace_dir`, when it exists.
+pub fn workspace_root(workspace_dir: &Path) -> Option<PathBuf> {
+    let dir = std::fs::canonicalize(workspace_dir).ok()?;
+    dir.parent().map(Path::to_path_buf)
+}
+
+fn local_toml_files(workspace_dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(workspace_dir) else {
+        return Vec::new();
+    };
+    let mut files = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "toml"))
+        .collect::<Vec<PathBuf>>();
+    files.sort();
+    files
+}
+
+/// Whether `workspace_dir` holds any config the trust check gates.
+pub fn has_local_config(workspace_dir: &Path) -> bool {
+    !local_toml_files(workspace_dir).is_empty()
+}
+
+/// sha256 of every hashed config file in `workspace_dir`, by file name.
+pub fn fingerprint_workspace(workspace_dir: &Path) -> Result<BTreeMap<String, String>> {
+    let mut files = BTreeMap::new();
+    for path in local_toml_files(workspace_dir) {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        if UNHASHED_FILES.contains(&name.as_str()) {
+            continue;
+        }
+        let bytes =
+            std::fs::read(&path).with_context(|| format!("failed to read {}", path.display()))?;
+        files.insert(name, sha256_hex(&bytes));
+    }
+    Ok(files)
+}
+
+fn changed_files(
+    trusted: &BTreeMap<String, String>,
+    current: &BTreeMap<String, String>,
+) -> Vec<String> {
+    let mut names = trusted
+        .keys()
+        .chain(current.keys())
+        .filter(|name| trusted.get(*name) != current.get(*name))
+        .cloned()
+        .collect::<Vec<String>>();
+    names.sort();
+    names.dedup();
+    names
+}
+
+impl TrustStore {
+    /// A missing store is empty; an unreadable one is an error.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let raw = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read trust store {}", path.display()))?;
+        serde_json::from_str(&raw).with_context(|| {
+            format!(
+                "invalid trust store {}; fix or delete it and run `zavora-cli trust` again",
+                path.display()
+            )
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+        let body = serde_json::to_string_pretty(self).context("failed to encode trust store")?;
+        std::fs::write(path, format!("{body}\n"))
+            .with_context(|| format!("failed to write {}", path.display()))
+    }
+
+    pub fn status(&self, workspace_dir: &Path) -> Result<WorkspaceTrust> {
+        if !has_local_config(workspace_dir) {
+            return Ok(WorkspaceTrust::NoLocalConfig);
+        }
+        let Some(root) = workspace_root(workspace_dir) else {
+            return Ok(WorkspaceTrust::Untrusted);
+        };
+        let Some(entry) = self.workspaces.get(&root.display().to_string()) else {
+            return Ok(WorkspaceTrust::Untrusted);
+        };
+        let changed = changed_files(&entry.files, &fingerprint_workspace(workspace_dir)?);
+        if changed.is_empty() {
+            Ok(WorkspaceTrust::Trusted)
+        } else {
+            Ok(WorkspaceTrust::Changed(changed))
+        }
+    }
+
+    /// Record the workspace with its current file hashes; returns the root.
+    pub fn trust(&mut self, workspace_dir: &Path, trusted_at: &str) -> Result<PathBuf> {
+        if !has_local_config(workspace_dir) {
+            anyhow::bail!(
+                "no local config to trust: {} has no .toml files",
+                workspace_dir.display()
+            );
+        }
+        let root = workspace_root(workspace_dir)
+            .with_context(|| format!("failed to resolve workspace {}", workspace_dir.display()))?;
+        self.workspaces.insert(
+            root.display().to_string(),
+            TrustedWorkspace {
+                trusted_at: trusted_at.to_string(),
+                files: fingerprint_workspace(workspace_dir)?,
+            },
+        );
+        Ok(root)
+    }
+
+    /// Forget the workspace; `None` when it was not trusted.
+    pub fn revoke(&mut self, workspace_dir: &Path) -> Option<PathBuf> {
+        let root = workspace_root(workspace_dir)?;
+        self.workspaces
+            .remove(&root.display().to_string())
+            .map(|_| root)
+    }
+}
+
+/// The trust store, or `None` when it would sit inside the workspace it
+/// vouches for (no home dir, or `--config-dir` under `.zavora/`): a repository
+/// could then ship its own trust.
+fn trust_store_path(paths: &ZavoraPaths) -> Option<PathBuf> {
+    let path = paths.trust_store_file();
+    (!is_workspace_path(&path, &paths.workspace_dir)).then_some(path)
+}
+
+pub fn workspace_trust(paths: &ZavoraPaths) -> Result<WorkspaceTrust> {
+    let store = match trust_store_path(paths) {
+        Some(path) => TrustStore::load(&path)?,
+        None => TrustStore::default(),
+    };
+    store.status(&paths.workspace_dir)
+}
+
+/// Re-trust after zavora itself wrote local config (onboarding, `setup`),
+/// unless the workspace held untrusted config before the write.
+pub fn retrust_after_write(paths: &ZavoraPaths, before: &WorkspaceTrust) -> Result<()> {
+    if !before.allows_local_config() || !has_local_config(&paths.workspace_dir) {
+        return Ok(());
+    }
+    let Some(store_path) = trust_store_path(paths) else {
+        return Ok(());
+    };
+    let mut store = TrustStore::load(&store_path)?;
+    store.trust(&paths.workspace_dir, &chrono::Utc::now().to_rfc3339())?;
+    store.save(&store_path)
+}
+
+/// Whether `config_path` lives inside `workspace_dir` (and so is local).
+pub fn is_workspace_path(config_path: &Path, workspace_dir: &Path) -> bool {
+    if config_path.starts_with(workspace_dir) {
+        return true;
+    }
+    match (
+        std::fs::canonicalize(config_path),
+        std::fs::canonicalize(workspace_dir),
+    ) {
+        (Ok(path), Ok(dir)) => path.starts_with(dir),
+        _ => false,
+    }
+}
+
+/// Stderr warning when local config is being skipped, `None` otherwise.
+pub fn format_trust_warning(trust: &WorkspaceTrust, workspace_dir: &Path) -> Option<String> {
+    let root = workspace_root(workspace_dir)
+        .map(|root| root.display().to_string())
+        .unwrap_or_else(|| workspace_dir.display().to_string());
+    match trust {
+        WorkspaceTrust::NoLocalConfig | WorkspaceTrust::Trusted => None,
+        WorkspaceTrust::Untrusted => Some(format!(
+            "Warning: workspace {root} is not trusted; its .zavora/ agents, profiles, \
+             selections and hooks were NOT loaded. Review them, then run `zavora-cli trust`."
+        )),
+        WorkspaceTrust::Changed(files) => Some(format!(
+            "Warning: .zavora/ config in {root} changed since it was trusted ({}); it was NOT \
+             loaded. Review the changes, then run `zavora-cli trust` again.",
+            files.join(", ")
+        )),
+    }
+}
+
+/// Print the trust warning, if any. It bypasses `--quiet`: skipped local
+/// config must never go unnoticed.
+pub fn report_workspace_trust(ui: &Ui, trust: &WorkspaceTrust, workspace_dir: &Path) {
+    if let Some(warning) = format_trust_warning(trust, workspace_dir) {
+        ui.security_warning(&warning);
+    }
+}
+
+/// `doctor` line for the current workspace.
+pub fn format_trust_status(trust: &WorkspaceTrust) -> String {
+    match trust {
+        WorkspaceTrust::Changed(files) => {
+            format!("Workspace trust: {} ({})", trust.label(), files.join(", "))
+        }
+        _ => format!("Workspace trust: {}", trust.label()),
+    }
+}
+
+pub fn run_trust(paths: &ZavoraPaths, revoke: bool) -> Result<()> {
+    let store_path = trust_store_path(paths).with_context(|| {
+        format!(
+            "the trust store {} is inside the workspace; set ZAVORA_HOME or --config-dir \
+             to a directory outside it",
+            paths.trust_store_file().display()
+        )
+    })?;
+    let mut store = TrustStore::load(&store_path)?;
+    if revoke {
+        match store.revoke(&paths.workspace_dir) {
+            Some(root) => {
+                store.save(&store_path)?;
+                println!("Revoked trust for {}.", root.display());
+            }
+            None => println!("Workspace is not trusted; nothing to revoke."),
+        }
+        return Ok(());
+    }
+    let root = store.trust(&paths.workspace_dir, &chrono::Utc::now().to_rfc3339())?;
+    store.save(&store_path)?;
+    let files = store
+        .workspaces
+        .get(&root.display().to_string())
+        .map(|entry| entry.files.len())
+        .unwrap_or_default();
+    println!(
+        "Trusted workspace {} ({files} config files hashed).",
+        root.display()
+    );
+    Ok(())
+}
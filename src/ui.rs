@@ -0,0 +1,115 @@
+//! Shared facade for user-facing output, so `--quiet` and `-v` are honored in
+//! one place rather than at every `println!`.
+//!
+//! The answer and explicitly requested reports always reach stdout, and
+//! errors and security warnings always reach stderr. Notices and warnings
+//! are informational: they go to stderr normally and become debug-level
+//! tracing under `--quiet`, which keeps scripted output to the answer alone.
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+
+use crate::config::RuntimeConfig;
+use crate::pager::page_or_print;
+use crate::verbosity::Verbosity;
+
+/// What a [`Ui::captured`] facade has written.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CapturedOutput {
+    pub stdout: String,
+    pub stderr: String,
+}
+
+#[derive(Debug, Clone)]
+enum Sink {
+    Terminal,
+    Captured(Arc<Mutex<CapturedOutput>>),
+}
+
+#[derive(Debug, Clone)]
+pub struct Ui {
+    verbosity: Verbosity,
+    sink: Sink,
+}
+
+impl Ui {
+    pub fn new(verbosity: Verbosity) -> Self {
+        Self {
+            verbosity,
+            sink: Sink::Terminal,
+        }
+    }
+
+    pub fn for_config(cfg: &RuntimeConfig) -> Self {
+        Self::new(cfg.verbosity)
+    }
+
+    /// A facade that records into memory instead of the terminal.
+    pub fn captured(verbosity: Verbosity) -> (Self, Arc<Mutex<CapturedOutput>>) {
+        let output = Arc::new(Mutex::new(CapturedOutput::default()));
+        (
+            Self {
+                verbosity,
+                sink: Sink::Captured(output.clone()),
+            },
+            output,
+        )
+    }
+
+    pub fn is_quiet(&self) -> bool {
+        self.verbosity == Verbosity::Quiet
+    }
+
+    /// The command's answer (ending with a newline); paged like before when
+    /// stdout is a TTY. Never suppressed.
+    pub fn answer(&self, cfg: &RuntimeConfig, text: &str) -> Result<()> {
+        match &self.sink {
+            Sink::Terminal => page_or_print(cfg, text),
+            Sink::Captured(output) => {
+                lock(output).stdout.push_str(text);
+                Ok(())
+            }
+        }
+    }
+
+    /// An informational stderr line such as a banner or status note.
+    pub fn notice(&self, message: &str) {
+        if self.is_quiet() {
+            tracing::debug!(message, "Suppressed notice (--quiet)");
+            return;
+        }
+        self.write_stderr(message);
+    }
+
+    /// A warning the user must see even under `--quiet`, such as workspace
+    /// config being skipped because it is not trusted.
+    pub fn security_warning(&self, message: &str) {
+        self.write_stderr(message);
+    }
+
+    fn write_stderr(&self, message: &str) {
+        match &self.sink {
+            Sink::Terminal => {
+                let mut stderr = std::io::stderr().lock();
+                let _ = writeln!(stderr, "{message}");
+            }
+            Sink::Captured(output) => {
+                let mut output = lock(output);
+                output.stderr.push_str(message);
+                output.stderr.push('\n');
+            }
+        }
+    }
+
+    /// A non-fatal problem, printed as `Warning: {message}`.
+    pub fn warn(&self, message: &str) {
+        self.notice(&format!("Warning: {message}"));
+    }
+}
+
+fn lock(output: &Mutex<CapturedOutput>) -> std::sync::MutexGuard<'_, CapturedOutput> {
+    output
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
@@ -0,0 +1,265 @@
+/// Opt-in check for newer releases (`update_check = true`).
+///
+/// The latest release is fetched at most once a day and cached in
+/// `<state>/update-check.json`. Command start only reads that cache: a stale
+/// cache triggers a background refresh whose result shows on the next run,
+/// so a slow or unreachable endpoint never delays or fails a command.
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::build_info::CLI_VERSION;
+use crate::config::RuntimeConfig;
+use crate::ui::Ui;
+
+pub const DEFAULT_UPDATE_CHECK_URL: &str =
+    "https://api.github.com/repos/zavora-ai/zavora-cli/releases/latest";
+pub const UPDATE_CHECK_INTERVAL_SECS: u64 = 24 * 60 * 60;
+pub const UPDATE_CHECK_TIMEOUT: Duration = Duration::from_secs(3);
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ReleaseInfo {
+    /// Tag with any leading `v` stripped.
+    pub version: String,
+    pub prerelease: bool,
+    pub url: Option<String>,
+}
+
+#[async_trait]
+pub trait ReleaseFetcher: Send + Sync {
+    async fn latest_release(&self) -> Result<ReleaseInfo>;
+}
+
+/// GitHub-style releases endpoint (`tag_name`, `prerelease`, `html_url`).
+pub struct HttpReleaseFetcher {
+    pub url: String,
+}
+
+#[cfg(feature = "update-check")]
+#[derive(Deserialize)]
+struct ReleasePayload {
+    tag_name: String,
+    #[serde(default)]
+    prerelease: bool,
+    html_url: Option<String>,
+}
+
+#[async_trait]
+impl ReleaseFetcher for HttpReleaseFetcher {
+    #[cfg(feature = "update-check")]
+    async fn latest_release(&self) -> Result<ReleaseInfo> {
+        let client = reqwest::Client::builder()
+            .timeout(UPDATE_CHECK_TIMEOUT)
+            .user_agent(concat!("zavora-cli/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .context("failed to build update check client")?;
+        let payload = client
+            .get(&self.url)
+            .header("Accept", "application/vnd.github+json")
+            .send()
+            .await
+            .and_then(|resp| resp.error_for_status())
+            .with_context(|| format!("failed to query releases from {}", self.url))?
+            .json::<ReleasePayload>()
+            .await
+            .context("failed to parse release response")?;
+        Ok(release_info(payload))
+    }
+
+    #[cfg(not(feature = "update-check"))]
+    async fn latest_release(&self) -> Result<ReleaseInfo> {
+        Err(anyhow::anyhow!(
+            "update check requires feature 'update-check'. Rebuild with: cargo build --features update-check"
+        ))
+    }
+}
+
+#[cfg(feature = "update-check")]
+fn release_info(payload: ReleasePayload) -> ReleaseInfo {
+    ReleaseInfo {
+        version: payload.tag_name.trim_start_matches('v').to_string(),
+        prerelease: payload.prerelease,
+        url: payload.html_url,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum UpdateCheckOutcome {
+    UpToDate { latest: String },
+    UpdateAvailable { latest: String, url: Option<String> },
+    Failed { error: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct UpdateCheckCache {
+    pub checked_unix_secs: u64,
+    /// Build that ran the check; a different build re-checks right away.
+    pub current_version: String,
+    #[serde(flatten)]
+    pub outcome: UpdateCheckOutcome,
+}
+
+/// `major.minor.patch` plus whether a `-pre` suffix is present. `None` for
+/// anything that is not a plain semver.
+fn parse_version(version: &str) -> Option<((u64, u64, u64), bool)> {
+    let version = version.trim().trim_start_matches('v');
+    let version = version.split_once('+').map_or(version, |(core, _)| core);
+    let (core, pre) = match version.split_once('-') {
+        Some((core, _)) => (core, true),
+        None => (version, false),
+    };
+    let mut parts = core.split('.').map(|part| part.parse::<u64>().ok());
+    let triple = (parts.next()??, parts.next()??, parts.next()??);
+    parts.next().is_none().then_some((triple, pre))
+}
+
+/// Prereleases and unparseable tags never count as updates.
+pub fn compare_release(current: &str, release: &ReleaseInfo) -> UpdateCheckOutcome {
+    let newer = match (parse_version(current), parse_version(&release.version)) {
+        (Some((current, _)), Some((latest, false))) => !release.prerelease && latest > current,
+        _ => false,
+    };
+    if newer {
+        UpdateCheckOutcome::UpdateAvailable {
+            latest: release.version.clone(),
+            url: release.url.clone(),
+        }
+    } else {
+        UpdateCheckOutcome::UpToDate {
+            latest: release.version.clone(),
+        }
+    }
+}
+
+pub async fn check_for_update(
+    fetcher: &dyn ReleaseFetcher,
+    current: &str,
+    now_unix_secs: u64,
+) -> UpdateCheckCache {
+    let outcome = match tokio::time::timeout(UPDATE_CHECK_TIMEOUT, fetcher.latest_release()).await {
+        Ok(Ok(release)) => compare_release(current, &release),
+        Ok(Err(err)) => UpdateCheckOutcome::Failed {
+            error: format!("{err:#}"),
+        },
+        Err(_) => UpdateCheckOutcome::Failed {
+            error: format!("timed out after {}s", UPDATE_CHECK_TIMEOUT.as_secs()),
+        },
+    };
+    UpdateCheckCache {
+        checked_unix_secs: now_unix_secs,
+        current_version: current.to_string(),
+        outcome,
+    }
+}
+
+/// Failed checks are cached too, so an offline machine tries once a day.
+pub fn update_check_is_fresh(cache: &UpdateCheckCache, current: &str, now_unix_secs: u64) -> bool {
+    cache.current_version == current
+        && now_unix_secs.saturating_sub(cache.checked_unix_secs) < UPDATE_CHECK_INTERVAL_SECS
+}
+
+pub fn load_update_check_cache(path: &Path) -> Option<UpdateCheckCache> {
+    let text = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&text).ok()
+}
+
+pub fn save_update_check_cache(path: &Path, cache: &UpdateCheckCache) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    let text = serde_json::to_string_pretty(cache).context("failed to encode update check")?;
+    std::fs::write(path, text).with_context(|| format!("failed to write {}", path.display()))
+}
+
+/// One-line stderr advisory, when the cached check found a newer release
+/// for this build.
+pub fn update_advisory(cache: &UpdateCheckCache, current: &str) -> Option<String> {
+    if cache.current_version != current {
+        return None;
+    }
+    let UpdateCheckOutcome::UpdateAvailable { latest, url } = &cache.outcome else {
+        return None;
+    };
+    let mut line = format!("zavora-cli {latest} is available (running {current})");
+    if let Some(url) = url {
+        line.push_str(&format!(": {url}"));
+    }
+    Some(line)
+}
+
+/// `doctor` line: last check time and result.
+pub fn format_update_check_status(enabled: bool, cache: Option<&UpdateCheckCache>) -> String {
+    let state = if enabled { "on" } else { "off" };
+    let Some(cache) = cache else {
+        return format!("Update check: {state} (never checked)");
+    };
+    let checked = chrono::DateTime::from_timestamp(cache.checked_unix_secs as i64, 0)
+        .map(|at| at.to_rfc3339())
+        .unwrap_or_else(|| cache.checked_unix_secs.to_string());
+    let result = match &cache.outcome {
+        UpdateCheckOutcome::UpToDate { latest } => format!("up to date (latest {latest})"),
+        UpdateCheckOutcome::UpdateAvailable { latest, .. } => {
+            format!("{latest} available (running {})", cache.current_version)
+        }
+        UpdateCheckOutcome::Failed { error } => format!("failed: {error}"),
+    };
+    format!("Update check: {state} (last checked {checked}: {result})")
+}
+
+fn now_unix_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+/// Command-start hook: print the cached advisory and, when the cache is
+/// stale, refresh it in the background. Silent when disabled or offline,
+/// and errors only land in the cache.
+pub fn start_update_check(cfg: &RuntimeConfig) {
+    if !cfg.update_check || cfg.offline {
+        return;
+    }
+    let path = cfg.paths.update_check_file();
+    let now = now_unix_secs();
+    let cache = load_update_check_cache(&path);
+    if let Some(advisory) = cache
+        .as_ref()
+        .and_then(|cache| update_advisory(cache, CLI_VERSION))
+    {
+        Ui::for_config(cfg).notice(&advisory);
+    }
+    if cache.is_some_and(|cache| update_check_is_fresh(&cache, CLI_VERSION, now)) {
+        return;
+    }
+    let fetcher = HttpReleaseFetcher {
+        url: cfg.update_check_url.clone(),
+    };
+    tokio::spawn(async move {
+        let cache = check_for_update(&fetcher, CLI_VERSION, now).await;
+        if let Err(err) = save_update_check_cache(&path, &cache) {
+            tracing::debug!(error = %err, "failed to save update check");
+        }
+    });
+}
+
+/// `doctor --check-update`: check now, whatever the cache or profile says.
+/// `None` under `--offline`, which never reaches the network.
+pub async fn run_update_check_now(cfg: &RuntimeConfig) -> Option<UpdateCheckCache> {
+    if cfg.offline {
+        return None;
+    }
+    let fetcher = HttpReleaseFetcher {
+        url: cfg.update_check_url.clone(),
+    };
+    let cache = check_for_update(&fetcher, CLI_VERSION, now_unix_secs()).await;
+    if let Err(err) = save_update_check_cache(&cfg.paths.update_check_file(), &cache) {
+        tracing::warn!(error = %err, "failed to save update check");
+    }
+    Some(cache)
+}
@@ -0,0 +1,263 @@
+/// Daily usage counters and the token/cost budget guard.
+///
+/// Counters live in `<state dir>/usage/daily-<YYYY-MM-DD>.json` (UTC dates)
+/// and are only maintained when a daily budget is configured.
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use adk_rust::Event;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Days, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::cli::UsageBudgetMode;
+use crate::config::RuntimeConfig;
+use crate::context::estimate_tokens;
+use crate::file_lock::{DEFAULT_FILE_LOCK_TIMEOUT, acquire_file_lock};
+use crate::ui::Ui;
+
+/// Machine-readable code carried by budget refusals.
+pub const BUDGET_EXCEEDED_CODE: &str = "provider.budget_exceeded";
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct DailyUsage {
+    pub date: String,
+    pub tokens: u64,
+    pub cost: f64,
+    pub calls: u64,
+}
+
+/// Returned (inside `anyhow::Error`) when `enforce` mode refuses a call.
+#[derive(Debug, Clone)]
+pub struct BudgetExceeded {
+    pub message: String,
+    pub reset_at: DateTime<Utc>,
+}
+
+impl fmt::Display for BudgetExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{BUDGET_EXCEEDED_CODE}: {}; resets at {}",
+            self.message,
+            self.reset_at.to_rfc3339()
+        )
+    }
+}
+
+impl std::error::Error for BudgetExceeded {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BudgetCheck {
+    WithinBudget,
+    /// Projected usage crosses a budget; the message names what remains.
+    OverBudget(String),
+}
+
+pub fn usage_budget_configured(cfg: &RuntimeConfig) -> bool {
+    cfg.daily_token_budget.is_some() || cfg.daily_cost_budget.is_some()
+}
+
+pub fn usage_date(now: DateTime<Utc>) -> String {
+    now.format("%Y-%m-%d").to_string()
+}
+
+/// Next UTC midnight, when today's counter stops applying.
+pub fn usage_reset_at(now: DateTime<Utc>) -> DateTime<Utc> {
+    now.date_naive()
+        .checked_add_days(Days::new(1))
+        .unwrap_or(now.date_naive())
+        .and_time(NaiveTime::MIN)
+        .and_utc()
+}
+
+pub fn usage_file_path(usage_dir: &str, date: &str) -> PathBuf {
+    Path::new(usage_dir).join(format!("daily-{date}.json"))
+}
+
+pub fn usage_cost(cfg: &RuntimeConfig, tokens: u64) -> f64 {
+    tokens as f64 / 1000.0 * cfg.usage_cost_per_1k_tokens
+}
+
+pub fn load_daily_usage(usage_dir: &str, date: &str) -> Result<DailyUsage> {
+    let path = usage_file_path(usage_dir, date);
+    if !path.exists() {
+        return Ok(DailyUsage {
+            date: date.to_string(),
+            ..DailyUsage::default()
+        });
+    }
+    let content = std::fs::read_to_string(&path)
+        .with_context(|| format!("failed to read usage counter '{}'", path.display()))?;
+    serde_json::from_str(&content)
+        .with_context(|| format!("invalid usage counter '{}'", path.display()))
+}
+
+/// Add one model call to the counter for `date`. The read-modify-write runs
+/// under the file lock so concurrent invocations do not lose updates.
+pub fn record_daily_usage(
+    usage_dir: &str,
+    date: &str,
+    tokens: u64,
+    cost: f64,
+) -> Result<DailyUsage> {
+    let path = usage_file_path(usage_dir, date);
+    std::fs::create_dir_all(usage_dir)
+        .with_context(|| format!("failed to create usage directory '{usage_dir}'"))?;
+    let _lock = acquire_file_lock(&path, DEFAULT_FILE_LOCK_TIMEOUT)?;
+
+    let mut usage = load_daily_usage(usage_dir, date)?;
+    usage.tokens += tokens;
+    usage.cost += cost;
+    usage.calls += 1;
+
+    let payload =
+        serde_json::to_string_pretty(&usage).context("failed to serialize usage counter")?;
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, payload)
+        .with_context(|| format!("failed to write usage counter '{}'", tmp.display()))?;
+    std::fs::rename(&tmp, &path)
+        .with_context(|| format!("failed to write usage counter '{}'", path.display()))?;
+    Ok(usage)
+}
+
+/// Compare today's usage plus `projected_tokens` against the budgets.
+pub fn evaluate_usage_budget(
+    cfg: &RuntimeConfig,
+    usage: &DailyUsage,
+    projected_tokens: u64,
+) -> BudgetCheck {
+    if let Some(budget) = cfg.daily_token_budget
+        && usage.tokens + projected_tokens > budget
+    {
+        return BudgetCheck::OverBudget(format!(
+            "daily token budget exceeded (used {} + projected {projected_tokens} > budget {budget}; remaining {})",
+            usage.tokens,
+            budget.saturating_sub(usage.tokens)
+        ));
+    }
+    if let Some(budget) = cfg.daily_cost_budget {
+        let projected_cost = usage_cost(cfg, projected_tokens);
+        if usage.cost + projected_cost > budget {
+            return BudgetCheck::OverBudget(format!(
+                "daily cost budget exceeded (used {:.4} + projected {projected_cost:.4} > budget {budget:.4}; remaining {:.4})",
+                usage.cost,
+                (budget - usage.cost).max(0.0)
+            ));
+        }
+    }
+    BudgetCheck::WithinBudget
+}
+
+/// Gate a model call at `now`. `warn` mode prints the remaining budget and
+/// continues; `enforce` mode fails with [`BudgetExceeded`].
+pub fn check_usage_budget_at(
+    cfg: &RuntimeConfig,
+    projected_tokens: u64,
+    now: DateTime<Utc>,
+) -> Result<()> {
+    if !usage_budget_configured(cfg) {
+        return Ok(());
+    }
+    let usage = load_daily_usage(&cfg.usage_dir, &usage_date(now))?;
+    let BudgetCheck::OverBudget(message) = evaluate_usage_budget(cfg, &usage, projected_tokens)
+    else {
+        return Ok(());
+    };
+    match cfg.usage_budget_mode {
+        UsageBudgetMode::Warn => {
+            tracing::warn!(message = %message, "Usage budget warning");
+            Ui::for_config(cfg).warn(&message);
+            Ok(())
+        }
+        UsageBudgetMode::Enforce => Err(anyhow::Error::new(BudgetExceeded {
+            message,
+            reset_at: usage_reset_at(now),
+        })),
+    }
+}
+
+pub fn check_usage_budget(cfg: &RuntimeConfig, projected_tokens: u64) -> Result<()> {
+    check_usage_budget_at(cfg, projected_tokens, Utc::now())
+}
+
+/// Record a finished model call. Failures are logged, never fatal.
+pub fn record_model_usage(cfg: &RuntimeConfig, tokens: u64) {
+    if !usage_budget_configured(cfg) {
+        return;
+    }
+    let date = usage_date(Utc::now());
+    if let Err(err) = record_daily_usage(&cfg.usage_dir, &date, tokens, usage_cost(cfg, tokens)) {
+        tracing::warn!(error = %err, "usage counter update failed");
+    }
+}
+
+/// Token count for one prompt run: provider-reported usage when available,
+/// otherwise a character-based estimate of prompt + response.
+#[derive(Debug, Default)]
+pub struct UsageMeter {
+    reported_tokens: u64,
+    estimated_chars: usize,
+}
+
+impl UsageMeter {
+    pub fn new(prompt: &str) -> Self {
+        Self {
+            reported_tokens: 0,
+            estimated_chars: prompt.chars().count(),
+        }
+    }
+
+    pub fn observe(&mut self, event: &Event, text: &str) {
+        if event.llm_response.partial {
+            return;
+        }
+        if let Some(meta) = &event.llm_response.usage_metadata
+            && meta.total_token_count > 0
+        {
+            self.reported_tokens += meta.total_token_count as u64;
+        }
+        self.estimated_chars += text.chars().count();
+    }
+
+    pub fn tokens(&self) -> u64 {
+        if self.reported_tokens > 0 {
+            self.reported_tokens
+        } else {
+            estimate_tokens(self.estimated_chars) as u64
+        }
+    }
+}
+
+pub fn run_usage_status(cfg: &RuntimeConfig) -> Result<()> {
+    let now = Utc::now();
+    let date = usage_date(now);
+    let usage = load_daily_usage(&cfg.usage_dir, &date)?;
+    println!(
+        "Usage for {date} (UTC, resets {}):",
+        usage_reset_at(now).to_rfc3339()
+    );
+    println!("Calls: {}", usage.calls);
+    match cfg.daily_token_budget {
+        Some(budget) => println!(
+            "Tokens: {} / {budget} ({} remaining)",
+            usage.tokens,
+            budget.saturating_sub(usage.tokens)
+        ),
+        None => println!("Tokens: {} (no daily_token_budget)", usage.tokens),
+    }
+    match cfg.daily_cost_budget {
+        Some(budget) => println!(
+            "Cost: {:.4} / {budget:.4} ({:.4} remaining, {} per 1k tokens)",
+            usage.cost,
+            (budget - usage.cost).max(0.0),
+            cfg.usage_cost_per_1k_tokens
+        ),
+        None => println!("Cost: {:.4} (no daily_cost_budget)", usage.cost),
+    }
+    println!("Mode: {:?}", cfg.usage_budget_mode);
+    if !usage_budget_configured(cfg) {
+        println!("No daily budget configured; usage is not being recorded.");
+    }
+    Ok(())
+}
@@ -0,0 +1,90 @@
+/// `-q` and `-v/-vv/-vvv`: one mapping to tracing filters plus the gate for
+/// extra CLI detail, carried on `RuntimeConfig::verbosity`.
+use crate::theme::{DIM, RESET};
+
+/// adk crates whose internals are traced at `-vvv`.
+const ADK_TRACE_TARGETS: &[&str] = &[
+    "adk_rust",
+    "adk_core",
+    "adk_agent",
+    "adk_model",
+    "adk_runner",
+    "adk_session",
+    "adk_tool",
+];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    /// `--quiet`: informational output is dropped; see [`crate::ui::Ui`].
+    Quiet,
+    /// No `-v`: `--log-filter`/`RUST_LOG` apply unchanged.
+    #[default]
+    Normal,
+    /// `-v`: zavora info logs; MCP discovery timing per server.
+    Info,
+    /// `-vv`: zavora debug logs; selected retrieval chunks with scores.
+    Debug,
+    /// `-vvv`: trace logs including adk internals; tool confirmation decisions.
+    Trace,
+}
+
+impl Verbosity {
+    /// `--quiet` wins; clap already rejects it together with `-v`.
+    pub fn from_flags(quiet: bool, count: u8) -> Self {
+        if quiet {
+            Self::Quiet
+        } else {
+            Self::from_count(count)
+        }
+    }
+
+    pub fn from_count(count: u8) -> Self {
+        match count {
+            0 => Self::Normal,
+            1 => Self::Info,
+            2 => Self::Debug,
+            _ => Self::Trace,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Quiet => "quiet",
+            Self::Normal => "normal",
+            Self::Info => "info",
+            Self::Debug => "debug",
+            Self::Trace => "trace",
+        }
+    }
+
+    pub fn enabled(self, level: Verbosity) -> bool {
+        self >= level
+    }
+
+    /// Print `message()` to stderr when running at `level` or above. The
+    /// message is only built when it will be shown.
+    pub fn detail(self, level: Verbosity, message: impl FnOnce() -> String) {
+        if self.enabled(level) {
+            for line in message().lines() {
+                eprintln!("{DIM}[{}] {line}{RESET}", level.label());
+            }
+        }
+    }
+}
+
+/// The tracing env-filter for `verbosity`; `base` (from `--log-filter` /
+/// `RUST_LOG`) is used as-is without `-v`.
+pub fn verbosity_log_filter(verbosity: Verbosity, base: &str) -> String {
+    match verbosity {
+        Verbosity::Quiet | Verbosity::Normal => base.to_string(),
+        Verbosity::Info => "warn,zavora_cli=info".to_string(),
+        Verbosity::Debug => "warn,zavora_cli=debug".to_string(),
+        Verbosity::Trace => {
+            let mut filter = "warn,zavora_cli=trace".to_string();
+            for target in ADK_TRACE_TARGETS {
+                filter.push_str(&format!(",{target}=trace"));
+            }
+            filter
+        }
+    }
+}
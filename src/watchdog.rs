@@ -0,0 +1,105 @@
+//! `--max-runtime-secs`: a hard ceiling on one whole command, so a hung MCP
+//! connection or a provider that never finishes its stream cannot wedge a CI
+//! job.
+//!
+//! The command's dispatch future runs under a timeout. When it fires the
+//! future is dropped, which drops the runner stream mid-event, and every
+//! `execute_bash` process group still running is killed (see
+//! [`crate::process_group`]) before returning. The caller
+//! then gets a [`CommandTimeout`] error naming the last progress phase
+//! reached; it maps to the `TIMEOUT` category and exit code 5.
+//!
+//! Chat and the servers are long-running by design, so a profile's
+//! `max_runtime_secs` leaves them alone; only the flag or its environment
+//! variable applies to them.
+use std::fmt;
+use std::future::Future;
+use std::io::Write;
+use std::time::Duration;
+
+use anyhow::Result;
+use serde_json::json;
+
+use crate::config::RuntimeConfig;
+use crate::process_group::{PROCESS_GROUP_KILL_GRACE, sweep_process_groups};
+use crate::progress::ProgressEmitter;
+use crate::telemetry::TelemetrySink;
+
+pub const COMMAND_TIMEOUT_EVENT: &str = "command.timeout";
+/// Printed to stdout after whatever partial output the command had written.
+pub const TIMED_OUT_MARKER: &str = "[timed out]";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandTimeout {
+    pub limit_secs: u64,
+    /// Last progress phase emitted before the limit, or `startup`.
+    pub phase: String,
+}
+
+impl fmt::Display for CommandTimeout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "command timed out after {}s (--max-runtime-secs) during phase '{}'",
+            self.limit_secs, self.phase
+        )
+    }
+}
+
+impl std::error::Error for CommandTimeout {}
+
+/// The limit for this command, if any. `long_running` marks chat and the
+/// servers, which only honor a limit passed with `--max-runtime-secs`.
+pub fn command_runtime_limit(cfg: &RuntimeConfig, long_running: bool) -> Option<Duration> {
+    if cfg.max_runtime_secs == 0 || (long_running && !cfg.max_runtime_from_cli) {
+        return None;
+    }
+    Some(Duration::from_secs(cfg.max_runtime_secs))
+}
+
+/// Await `run`, dropping it and returning [`CommandTimeout`] once `limit`
+/// passes.
+pub async fn run_with_watchdog<F>(
+    limit: Option<Duration>,
+    progress: &ProgressEmitter,
+    run: F,
+) -> Result<()>
+where
+    F: Future<Output = Result<()>>,
+{
+    let Some(limit) = limit else {
+        return run.await;
+    };
+    match tokio::time::timeout(limit, run).await {
+        Ok(result) => result,
+        Err(_) => {
+            sweep_process_groups(PROCESS_GROUP_KILL_GRACE);
+            Err(CommandTimeout {
+                limit_secs: limit.as_secs(),
+                phase: progress.current_phase(),
+            }
+            .into())
+        }
+    }
+}
+
+/// Close off a timed-out command: the marker after any partial output and
+/// a `command.timeout` event. Telemetry appends synchronously, so the event
+/// is on disk when this returns.
+pub fn report_command_timeout(
+    timeout: &CommandTimeout,
+    telemetry: &TelemetrySink,
+    duration_ms: u128,
+    out: &mut dyn Write,
+) {
+    let _ = writeln!(out, "\n{TIMED_OUT_MARKER}");
+    let _ = out.flush();
+    telemetry.emit(
+        COMMAND_TIMEOUT_EVENT,
+        json!({
+            "duration_ms": duration_ms,
+            "limit_secs": timeout.limit_secs,
+            "phase": timeout.phase,
+        }),
+    );
+}
@@ -9,7 +9,11 @@ use serde_json::{Value, json};
 
 use crate::cli::WorkflowMode;
 use crate::config::RuntimeConfig;
+use crate::progress::ProgressEmitter;
+use crate::retrieval::RetrievalService;
 use crate::runner::build_single_agent_with_tools;
+use crate::streaming::{event_text, run_prompt_with_retrieval_observed};
+use crate::telemetry::TelemetrySink;
 
 pub fn build_workflow_agent(
     mode: WorkflowMode,
@@ -395,7 +399,178 @@ fn build_loop_agent(model: Arc<dyn Llm>, max_iterations: u32) -> Result<Arc<dyn
     Ok(Arc::new(root))
 }
 
-pub fn build_release_planning_agent(model: Arc<dyn Llm>, releases: u32) -> Result<Arc<dyn Agent>> {
+/// `release-plan --refine` settings for the review loop.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReleasePlanReview {
+    pub iterations: u32,
+    pub max_scope_items: usize,
+}
+
+pub const RELEASE_PLAN_REVIEWER: &str = "release_reviewer";
+
+fn release_plan_review_instruction(max_scope_items: usize) -> String {
+    format!(
+        "Review the release plan in {{release_document}} against these criteria:\n\
+         1. Every release has objectives, acceptance criteria, and a demo output.\n\
+         2. No release has more than {max_scope_items} scope items.\n\
+         3. The risks section is present and non-empty.\n\
+         Start your reply with exactly one line: `VERDICT: APPROVED` when every criterion \
+         holds, otherwise `VERDICT: REVISE`. On REVISE, list each failed criterion as a `- ` \
+         bullet naming the release. On APPROVED, call exit_loop."
+    )
+}
+
+fn build_release_plan_refinement(
+    model: Arc<dyn Llm>,
+    review: ReleasePlanReview,
+) -> Result<Vec<Arc<dyn Agent>>> {
+    let reviewer = Arc::new(
+        LlmAgentBuilder::new(RELEASE_PLAN_REVIEWER)
+            .description("Checks the release plan against review criteria.")
+            .instruction(release_plan_review_instruction(review.max_scope_items))
+            .model(model.clone())
+            .tool(Arc::new(ExitLoopTool::new()))
+            .output_key("release_review")
+            .build()?,
+    );
+
+    let reviser = Arc::new(
+        LlmAgentBuilder::new("release_reviser")
+            .description("Revises the release plan to address review findings.")
+            .instruction(
+                "Revise {release_document} to fix every issue in {release_review?}. Keep the \
+                 same markdown sections and return the complete revised plan.",
+            )
+            .model(model.clone())
+            .output_key("release_document")
+            .build()?,
+    );
+
+    let loop_agent = Arc::new(
+        LoopAgent::new(
+            "release_plan_refinement",
+            vec![reviewer as Arc<dyn Agent>, reviser as Arc<dyn Agent>],
+        )
+        .with_max_iterations(review.iterations.max(1)),
+    );
+
+    let finalizer = Arc::new(
+        LlmAgentBuilder::new("release_finalizer")
+            .description("Returns the refined release plan.")
+            .instruction("Return the release plan from {release_document} verbatim.")
+            .model(model)
+            .build()?,
+    );
+
+    Ok(vec![
+        loop_agent as Arc<dyn Agent>,
+        finalizer as Arc<dyn Agent>,
+    ])
+}
+
+/// One reviewer turn of the refinement loop.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReleaseReviewVerdict {
+    pub approved: bool,
+    pub issues: Vec<String>,
+}
+
+/// Parse a reviewer reply; anything without an explicit approval counts as
+/// a revise verdict.
+pub fn parse_release_review_verdict(text: &str) -> ReleaseReviewVerdict {
+    let approved = text
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(|line| {
+            line.trim_matches('`')
+                .to_ascii_uppercase()
+                .starts_with("VERDICT: APPROVED")
+        })
+        .unwrap_or(false);
+    let issues = text
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("- "))
+        .map(|issue| issue.trim().to_string())
+        .filter(|issue| !issue.is_empty())
+        .collect();
+    ReleaseReviewVerdict { approved, issues }
+}
+
+/// Closing line noting how the refinement went.
+pub fn format_release_plan_refinement_summary(verdicts: &[ReleaseReviewVerdict]) -> String {
+    let signed_off = verdicts.last().is_some_and(|verdict| verdict.approved);
+    format!(
+        "Refinement: {} iteration(s); reviewer {}.",
+        verdicts.len(),
+        if signed_off {
+            "signed off"
+        } else {
+            "did not sign off"
+        }
+    )
+}
+
+/// Text of a complete reviewer reply that carries a verdict.
+fn release_review_text(event: &Event) -> Option<String> {
+    if event.author != RELEASE_PLAN_REVIEWER || event.llm_response.partial {
+        return None;
+    }
+    let text = event_text(event);
+    text.to_ascii_uppercase()
+        .contains("VERDICT:")
+        .then_some(text)
+}
+
+pub struct ReleasePlanOutcome {
+    pub answer: String,
+    /// Empty unless `--refine` was set.
+    pub verdicts: Vec<ReleaseReviewVerdict>,
+}
+
+/// Run the release-plan pipeline, emitting a `release_plan.review` event for
+/// each reviewer verdict.
+pub async fn run_release_plan(
+    runner: &Runner,
+    cfg: &RuntimeConfig,
+    prompt: &str,
+    retrieval: &dyn RetrievalService,
+    telemetry: &TelemetrySink,
+    progress: &ProgressEmitter,
+) -> Result<ReleasePlanOutcome> {
+    let mut verdicts = Vec::<ReleaseReviewVerdict>::new();
+    let mut observer = |event: &Event| {
+        if let Some(text) = release_review_text(event) {
+            let verdict = parse_release_review_verdict(&text);
+            telemetry.emit(
+                "release_plan.review",
+                json!({
+                    "iteration": verdicts.len() + 1,
+                    "verdict": if verdict.approved { "approved" } else { "revise" },
+                    "issues": &verdict.issues
+                }),
+            );
+            verdicts.push(verdict);
+        }
+    };
+    let answer = run_prompt_with_retrieval_observed(
+        runner,
+        cfg,
+        prompt,
+        retrieval,
+        telemetry,
+        progress,
+        &mut observer,
+    )
+    .await?;
+    Ok(ReleasePlanOutcome { answer, verdicts })
+}
+
+pub fn build_release_planning_agent(
+    model: Arc<dyn Llm>,
+    releases: u32,
+    review: Option<ReleasePlanReview>,
+) -> Result<Arc<dyn Agent>> {
     let scoper = Arc::new(
         LlmAgentBuilder::new("product_scoper")
             .instruction(
@@ -430,17 +605,20 @@ pub fn build_release_planning_agent(model: Arc<dyn Llm>, releases: u32) -> Resul
                  - Next sprint start tasks\n\
                  Use {release_plan}.",
             )
-            .model(model)
+            .model(model.clone())
+            .output_key("release_document")
             .build()?,
     );
 
-    let root = SequentialAgent::new(
-        "release_planning_pipeline",
-        vec![
-            scoper as Arc<dyn Agent>,
-            release_architect as Arc<dyn Agent>,
-            final_writer as Arc<dyn Agent>,
-        ],
-    );
+    let mut stages = vec![
+        scoper as Arc<dyn Agent>,
+        release_architect as Arc<dyn Agent>,
+        final_writer as Arc<dyn Agent>,
+    ];
+    if let Some(review) = review {
+        stages.extend(build_release_plan_refinement(model, review)?);
+    }
+
+    let root = SequentialAgent::new("release_planning_pipeline", stages);
     Ok(Arc::new(root))
 }
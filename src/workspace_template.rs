@@ -0,0 +1,477 @@
+//! `init --template`: bootstrap a workspace's `.zavora/` config (and an
+//! `evals/` skeleton) from a template.
+//!
+//! A template is a directory of files plus a `template.toml` manifest that
+//! declares its variables and the messages printed after init:
+//!
+//! ```toml
+//! description = "Rust CLI crate"
+//! messages = ["Run `zavora-cli trust` to load the new config."]
+//!
+//! [[variables]]
+//! name = "docs_path"
+//! description = "Document the retrieval backend reads"
+//! default = "docs/README.md"
+//! ```
+//!
+//! `{{name}}` in file contents and paths is replaced by the variable's value;
+//! `project_name` always exists and defaults to the workspace directory name.
+//! Files may only land under `.zavora/` or `evals/`. Built-in templates are
+//! embedded in the binary; a local directory or a git URL (cloned with
+//! `git`) supplies a custom one.
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use crate::config::{load_agent_catalog_file, load_profiles};
+use crate::doctor::format_broken_profiles;
+use crate::eval::load_eval_dataset;
+
+pub const TEMPLATE_MANIFEST_FILE: &str = "template.toml";
+pub const PROJECT_NAME_VARIABLE: &str = "project_name";
+
+/// Top-level directories a template may write into.
+const TEMPLATE_TARGET_DIRS: &[&str] = &[".zavora", "evals"];
+
+/// Built-in templates: name and (path, contents) of every file.
+const BUILTIN_TEMPLATES: &[(&str, &[(&str, &str)])] = &[
+    (
+        "rust-cli",
+        &[
+            (
+                TEMPLATE_MANIFEST_FILE,
+                include_str!("../templates/rust-cli/template.toml"),
+            ),
+            (
+                ".zavora/config.toml",
+                include_str!("../templates/rust-cli/.zavora/config.toml"),
+            ),
+            (
+                ".zavora/agents.toml",
+                include_str!("../templates/rust-cli/.zavora/agents.toml"),
+            ),
+            (
+                "evals/datasets/{{project_name}}.v1.json",
+                include_str!("../templates/rust-cli/evals/datasets/{{project_name}}.v1.json"),
+            ),
+        ],
+    ),
+    (
+        "docs-site",
+        &[
+            (
+                TEMPLATE_MANIFEST_FILE,
+                include_str!("../templates/docs-site/template.toml"),
+            ),
+            (
+                ".zavora/config.toml",
+                include_str!("../templates/docs-site/.zavora/config.toml"),
+            ),
+            (
+                ".zavora/agents.toml",
+                include_str!("../templates/docs-site/.zavora/agents.toml"),
+            ),
+            (
+                "evals/datasets/{{project_name}}.v1.json",
+                include_str!("../templates/docs-site/evals/datasets/{{project_name}}.v1.json"),
+            ),
+        ],
+    ),
+];
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TemplateManifest {
+    #[serde(default)]
+    pub description: String,
+    /// Printed after the files are written; variables are substituted.
+    #[serde(default)]
+    pub messages: Vec<String>,
+    #[serde(default)]
+    pub variables: Vec<TemplateVariable>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TemplateVariable {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Value when `--var` does not set one; required otherwise.
+    pub default: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkspaceTemplate {
+    /// Built-in name, directory or URL the template came from.
+    pub source: String,
+    pub manifest: TemplateManifest,
+    /// Relative `/`-separated path and contents, manifest excluded.
+    pub files: Vec<(String, String)>,
+}
+
+pub fn builtin_template_names() -> Vec<&'static str> {
+    BUILTIN_TEMPLATES.iter().map(|(name, _)| *name).collect()
+}
+
+fn parse_manifest(text: &str, source: &str) -> Result<TemplateManifest> {
+    toml::from_str(text)
+        .with_context(|| format!("invalid {TEMPLATE_MANIFEST_FILE} in template '{source}'"))
+}
+
+pub fn builtin_template(name: &str) -> Option<Result<WorkspaceTemplate>> {
+    let (_, files) = BUILTIN_TEMPLATES
+        .iter()
+        .find(|(builtin, _)| *builtin == name)?;
+    let manifest = files
+        .iter()
+        .find(|(path, _)| *path == TEMPLATE_MANIFEST_FILE)
+        .map(|(_, text)| parse_manifest(text, name))
+        .unwrap_or_else(|| Ok(TemplateManifest::default()));
+    Some(manifest.map(|manifest| {
+        WorkspaceTemplate {
+            source: name.to_string(),
+            manifest,
+            files: files
+                .iter()
+                .filter(|(path, _)| *path != TEMPLATE_MANIFEST_FILE)
+                .map(|(path, text)| (path.to_string(), text.to_string()))
+                .collect(),
+        }
+    }))
+}
+
+fn collect_template_files(
+    root: &Path,
+    dir: &Path,
+    files: &mut Vec<(String, String)>,
+) -> Result<()> {
+    let mut entries = std::fs::read_dir(dir)
+        .with_context(|| format!("failed to read template directory {}", dir.display()))?
+        .collect::<std::io::Result<Vec<_>>>()
+        .with_context(|| format!("failed to read template directory {}", dir.display()))?;
+    entries.sort_by_key(|entry| entry.file_name());
+    for entry in entries {
+        let path = entry.path();
+        if entry.file_name() == ".git" {
+            continue;
+        }
+        if path.is_dir() {
+            collect_template_files(root, &path, files)?;
+            continue;
+        }
+        let relative = path
+            .strip_prefix(root)
+            .unwrap_or(&path)
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        let text = std::fs::read_to_string(&path)
+            .with_context(|| format!("template file {} is not UTF-8 text", path.display()))?;
+        files.push((relative, text));
+    }
+    Ok(())
+}
+
+/// A template laid out in a local directory.
+pub fn load_template_dir(dir: &Path, source: &str) -> Result<WorkspaceTemplate> {
+    let manifest_path = dir.join(TEMPLATE_MANIFEST_FILE);
+    let text = std::fs::read_to_string(&manifest_path).with_context(|| {
+        format!(
+            "template '{source}' has no {TEMPLATE_MANIFEST_FILE} ({})",
+            manifest_path.display()
+        )
+    })?;
+    let manifest = parse_manifest(&text, source)?;
+    let mut files = Vec::new();
+    collect_template_files(dir, dir, &mut files)?;
+    files.retain(|(path, _)| path != TEMPLATE_MANIFEST_FILE);
+    Ok(WorkspaceTemplate {
+        source: source.to_string(),
+        manifest,
+        files,
+    })
+}
+
+pub fn is_git_template_url(spec: &str) -> bool {
+    ["https://", "http://", "ssh://", "git://", "file://", "git@"]
+        .iter()
+        .any(|prefix| spec.starts_with(prefix))
+        || spec.ends_with(".git")
+}
+
+fn clone_template(url: &str) -> Result<WorkspaceTemplate> {
+    let checkout = std::env::temp_dir().join(format!(
+        "zavora-template-{}-{}",
+        std::process::id(),
+        crate::telemetry::unix_ms_now()
+    ));
+    let output = Command::new("git")
+        .args(["clone", "--depth", "1", "--quiet", url])
+        .arg(&checkout)
+        .output()
+        .context("failed to run git to fetch the template")?;
+    let template = if output.status.success() {
+        load_template_dir(&checkout, url)
+    } else {
+        Err(anyhow::anyhow!(
+            "git clone of template '{url}' failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    };
+    let _ = std::fs::remove_dir_all(&checkout);
+    template
+}
+
+/// The template `spec` names: a built-in, then a local directory, then a
+/// git URL. `offline` allows built-ins only.
+pub fn resolve_template(spec: &str, offline: bool) -> Result<WorkspaceTemplate> {
+    if let Some(template) = builtin_template(spec) {
+        return template;
+    }
+    let builtins = builtin_template_names().join(", ");
+    if offline {
+        anyhow::bail!(
+            "template '{spec}' is not built in, and offline mode only allows the built-in \
+             templates ({builtins})"
+        );
+    }
+    let dir = Path::new(spec);
+    if dir.is_dir() {
+        return load_template_dir(dir, spec);
+    }
+    if is_git_template_url(spec) {
+        return clone_template(spec);
+    }
+    anyhow::bail!(
+        "unknown template '{spec}': use a built-in template ({builtins}), a template directory \
+         or a git URL"
+    )
+}
+
+/// Parse `--var name=value` arguments.
+pub fn parse_template_vars(raw: &[String]) -> Result<BTreeMap<String, String>> {
+    raw.iter()
+        .map(|entry| {
+            let (name, value) = entry
+                .split_once('=')
+                .filter(|(name, _)| !name.trim().is_empty())
+                .with_context(|| format!("--var '{entry}' must look like name=value"))?;
+            Ok((name.trim().to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+/// Variable values: `--var` overrides, then manifest defaults, then the
+/// workspace directory name for `project_name`.
+pub fn resolve_template_vars(
+    manifest: &TemplateManifest,
+    overrides: &BTreeMap<String, String>,
+    workspace_root: &Path,
+) -> Result<BTreeMap<String, String>> {
+    for name in overrides.keys() {
+        if name != PROJECT_NAME_VARIABLE
+            && !manifest
+                .variables
+                .iter()
+                .any(|variable| &variable.name == name)
+        {
+            anyhow::bail!("template does not declare a variable named '{name}'");
+        }
+    }
+    let mut values = BTreeMap::new();
+    let project_name = workspace_root
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "project".to_string());
+    values.insert(PROJECT_NAME_VARIABLE.to_string(), project_name);
+    for variable in &manifest.variables {
+        if let Some(default) = &variable.default {
+            values.insert(variable.name.clone(), default.clone());
+        }
+    }
+    values.extend(overrides.clone());
+    for variable in &manifest.variables {
+        if !values.contains_key(&variable.name) {
+            anyhow::bail!(
+                "template variable '{}' has no default; pass --var {}=<value> ({})",
+                variable.name,
+                variable.name,
+                variable.description
+            );
+        }
+    }
+    for (name, value) in &values {
+        if value.is_empty()
+            || value
+                .chars()
+                .any(|c| c.is_control() || c == '"' || c == '\\')
+        {
+            anyhow::bail!(
+                "template variable '{name}' must be non-empty and free of quotes, backslashes \
+                 and control characters"
+            );
+        }
+    }
+    Ok(values)
+}
+
+/// Replace every `{{name}}`; a placeholder left over is an error.
+pub fn substitute_template_vars(text: &str, values: &BTreeMap<String, String>) -> Result<String> {
+    let mut rendered = text.to_string();
+    for (name, value) in values {
+        rendered = rendered.replace(&format!("{{{{{name}}}}}"), value);
+    }
+    if let Some(start) = rendered.find("{{")
+        && let Some(end) = rendered[start..].find("}}")
+    {
+        anyhow::bail!(
+            "template uses undeclared variable '{}'",
+            &rendered[start + 2..start + end]
+        );
+    }
+    Ok(rendered)
+}
+
+fn checked_target_path(path: &str) -> Result<PathBuf> {
+    let relative = PathBuf::from(path);
+    let allowed = match relative.components().next() {
+        Some(Component::Normal(top)) => TEMPLATE_TARGET_DIRS.iter().any(|dir| top == *dir),
+        _ => false,
+    };
+    if !allowed
+        || relative
+            .components()
+            .any(|component| !matches!(component, Component::Normal(_)))
+    {
+        anyhow::bail!(
+            "template file '{path}' must be a relative path under {}",
+            TEMPLATE_TARGET_DIRS.join("/ or ")
+        );
+    }
+    Ok(relative)
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct InitOptions {
+    /// Overwrite files that already exist.
+    pub force: bool,
+    /// Leave out the template's `evals/` files.
+    pub skip_evals: bool,
+}
+
+/// Render `template` into `workspace_root`, returning the written paths.
+/// Nothing is written when any target exists and `force` is off.
+pub fn materialize_template(
+    template: &WorkspaceTemplate,
+    workspace_root: &Path,
+    values: &BTreeMap<String, String>,
+    options: InitOptions,
+) -> Result<Vec<PathBuf>> {
+    let mut rendered = Vec::new();
+    for (path, text) in &template.files {
+        let target = checked_target_path(&substitute_template_vars(path, values)?)?;
+        if options.skip_evals && target.starts_with("evals") {
+            continue;
+        }
+        let contents = substitute_template_vars(text, values)
+            .with_context(|| format!("failed to render template file '{path}'"))?;
+        rendered.push((workspace_root.join(target), contents));
+    }
+    let existing = rendered
+        .iter()
+        .filter(|(path, _)| path.exists())
+        .map(|(path, _)| path.display().to_string())
+        .collect::<Vec<String>>();
+    if !existing.is_empty() && !options.force {
+        anyhow::bail!(
+            "refusing to overwrite existing files (pass --force to replace them):\n- {}",
+            existing.join("\n- ")
+        );
+    }
+    let mut written = Vec::new();
+    for (path, contents) in rendered {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+        std::fs::write(&path, contents)
+            .with_context(|| format!("failed to write {}", path.display()))?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+/// Load every written config file the way the CLI does: profiles (with
+/// includes), agent catalogs and eval datasets. Returns one line per file.
+pub fn validate_materialized_files(written: &[PathBuf]) -> Result<Vec<String>> {
+    let mut report = Vec::new();
+    for path in written {
+        let display = path.display().to_string();
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string());
+        match name.as_deref() {
+            Some("config.toml") => {
+                let profiles = load_profiles(&display)?;
+                if !profiles.broken_profiles.is_empty() {
+                    anyhow::bail!(
+                        "{display} failed validation:\n{}",
+                        format_broken_profiles(&profiles)
+                    );
+                }
+                report.push(format!(
+                    "{display}: ok ({} profile(s))",
+                    profiles.profiles.len()
+                ));
+            }
+            Some("agents.toml") => {
+                let catalog = load_agent_catalog_file(path)?;
+                report.push(format!("{display}: ok ({} agent(s))", catalog.agents.len()));
+            }
+            Some(file)
+                if file.ends_with(".json")
+                    && path.components().any(|c| c.as_os_str() == "evals") =>
+            {
+                let dataset = load_eval_dataset(&display)?;
+                report.push(format!("{display}: ok ({} case(s))", dataset.cases.len()));
+            }
+            _ => {}
+        }
+    }
+    Ok(report)
+}
+
+pub fn run_init_template(
+    spec: &str,
+    raw_vars: &[String],
+    options: InitOptions,
+    offline: bool,
+) -> Result<()> {
+    let workspace_root = std::env::current_dir().context("failed to resolve current directory")?;
+    let template = resolve_template(spec, offline)?;
+    let values = resolve_template_vars(
+        &template.manifest,
+        &parse_template_vars(raw_vars)?,
+        &workspace_root,
+    )?;
+    let written = materialize_template(&template, &workspace_root, &values, options)?;
+    println!("Initialized from template '{}':", template.source);
+    for path in &written {
+        let shown = path.strip_prefix(&workspace_root).unwrap_or(path);
+        println!("  {}", shown.display());
+    }
+    println!("Config check:");
+    for line in validate_materialized_files(&written)? {
+        let line = line.replace(&format!("{}/", workspace_root.display()), "");
+        println!("  {line}");
+    }
+    for message in &template.manifest.messages {
+        println!("{}", substitute_template_vars(message, &values)?);
+    }
+    Ok(())
+}
@@ -0,0 +1,144 @@
+//! Black-box runs of the built binary against the scripted `--provider fake`:
+//! CLI parsing, streaming, the tool loop, telemetry and sqlite persistence,
+//! with no credentials or network.
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+
+const CREDENTIAL_ENVS: &[&str] = &[
+    "GOOGLE_API_KEY",
+    "OPENAI_API_KEY",
+    "ANTHROPIC_API_KEY",
+    "DEEPSEEK_API_KEY",
+    "GROQ_API_KEY",
+    "OLLAMA_HOST",
+];
+
+fn fixture(name: &str) -> String {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/fake_provider")
+        .join(name)
+        .display()
+        .to_string()
+}
+
+/// Runs `zavora-cli` in `workdir` with an isolated home, the fake provider,
+/// and sqlite sessions plus telemetry inside `workdir`.
+fn zavora(workdir: &Path, script: &str, args: &[&str]) -> Output {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_zavora-cli"));
+    for key in CREDENTIAL_ENVS {
+        command.env_remove(key);
+    }
+    command
+        .current_dir(workdir)
+        .env("ZAVORA_HOME", workdir.join("home"))
+        .env("ZAVORA_NO_ONBOARDING", "true")
+        .env("ZAVORA_NO_PAGER", "true")
+        .env("NO_COLOR", "1")
+        .args(["--provider", "fake", "--fake-script", &fixture(script)])
+        .args(["--session-backend", "sqlite"])
+        .args(["--session-db-url", "sqlite://sessions.db"])
+        .args(["--telemetry-path", "telemetry.jsonl"])
+        .args(args)
+        .stdin(Stdio::null())
+        .output()
+        .expect("zavora-cli runs")
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+fn telemetry_events(workdir: &Path) -> Vec<serde_json::Value> {
+    std::fs::read_to_string(workdir.join("telemetry.jsonl"))
+        .expect("telemetry file written")
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("telemetry line is JSON"))
+        .collect()
+}
+
+fn has_event(events: &[serde_json::Value], name: &str, needle: &str) -> bool {
+    events
+        .iter()
+        .any(|event| event["event"] == name && event.to_string().contains(needle))
+}
+
+fn workdir() -> (tempfile::TempDir, PathBuf) {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().to_path_buf();
+    (dir, path)
+}
+
+#[test]
+fn ask_answers_from_the_script_and_persists_the_session() {
+    let (_dir, root) = workdir();
+
+    let output = zavora(
+        &root,
+        "ask.json",
+        &["--session-id", "fake-ask", "ask", "what is the weather?"],
+    );
+    assert!(output.status.success(), "{output:?}");
+    assert!(stdout(&output).contains("Sunny all week in the scripted forecast."));
+
+    let events = telemetry_events(&root);
+    assert!(has_event(&events, "command.started", "ask"));
+    assert!(has_event(&events, "command.completed", "ask"));
+
+    let shown = zavora(
+        &root,
+        "ask.json",
+        &["--session-id", "fake-ask", "sessions", "show"],
+    );
+    assert!(shown.status.success(), "{shown:?}");
+    assert!(stdout(&shown).contains("what is the weather?"));
+    assert!(stdout(&shown).contains("Sunny all week in the scripted forecast."));
+}
+
+#[test]
+fn ask_surfaces_an_injected_provider_error() {
+    let (_dir, root) = workdir();
+
+    let output = zavora(&root, "ask.json", &["ask", "simulate an outage"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("simulated provider outage"));
+    assert!(has_event(
+        &telemetry_events(&root),
+        "command.failed",
+        "simulated provider outage"
+    ));
+}
+
+#[test]
+fn chat_script_runs_the_tool_loop_against_the_fake_provider() {
+    let (_dir, root) = workdir();
+    std::fs::write(root.join("notes.txt"), "Launch is on Friday.\n").expect("write notes");
+
+    let output = zavora(
+        &root,
+        "chat_tools.json",
+        &[
+            "--session-id",
+            "fake-chat",
+            "chat",
+            "--script",
+            &fixture("chat_tools.chat"),
+        ],
+    );
+    assert!(output.status.success(), "{output:?}");
+    let printed = stdout(&output);
+    assert!(printed.contains("Hello from the fake provider."));
+    assert!(printed.contains("The notes file says the launch is on Friday."));
+
+    let events = telemetry_events(&root);
+    assert!(has_event(&events, "tool.requested", "fs_read"));
+    assert!(has_event(&events, "tool.succeeded", "fs_read"));
+
+    let shown = zavora(
+        &root,
+        "chat_tools.json",
+        &["--session-id", "fake-chat", "sessions", "show"],
+    );
+    assert!(shown.status.success(), "{shown:?}");
+    assert!(stdout(&shown).contains("what do my notes say?"));
+    assert!(stdout(&shown).contains("The notes file says the launch is on Friday."));
+}